@@ -0,0 +1,112 @@
+//! Criterion benchmarks for the subnet processing pipeline.
+//!
+//! Each stage runs on synthetic estates of 1k, 10k and 100k subnets: /22
+//! VNets with sixteen /26 subnets each, every 50th subnet duplicated and
+//! every 100th VNet reusing an earlier VNet's CIDR in another subscription.
+//!
+//! Run with `cargo bench`; compare against a saved baseline with
+//! `cargo bench -- --save-baseline main` / `--baseline main`.
+
+use azure_subnet_summary::processing::{
+    de_duplicate_subnets, find_address_space_overlaps, find_overlapping_vnets, gaps,
+    group_vnet_cidrs,
+};
+use azure_subnet_summary::{Data, Ipv4, Subnet};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+use std::net::Ipv4Addr;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+const SUBNETS_PER_VNET: usize = 16;
+
+/// Synthetic subnet data with `count` subnets (plus duplicates).
+fn synthetic(count: usize) -> Data {
+    let mut subnets = Vec::with_capacity(count + count / 50);
+    for i in 0..count {
+        let vnet = i / SUBNETS_PER_VNET;
+        // Every 100th VNet overlaps the previous one's address space.
+        let block = if vnet % 100 == 99 { vnet - 1 } else { vnet };
+        let vnet_base = 0x0A00_0000u32 + (block as u32) * 1024;
+        let subnet_base = vnet_base + (i % SUBNETS_PER_VNET) as u32 * 64;
+
+        let mut s = Subnet::default();
+        s.subscription_id = format!("sub-{:04}", vnet / 500);
+        s.subscription_name = format!("Subscription {}", vnet / 500);
+        s.vnet_name = format!("vnet-{vnet:05}");
+        s.vnet_cidr = Ipv4 {
+            addr: Ipv4Addr::from(vnet_base),
+            mask: 22,
+        };
+        s.vnet_address_space = vec![s.vnet_cidr];
+        s.subnet_name = format!("snet-{i:06}");
+        s.subnet_cidr = Some(Ipv4 {
+            addr: Ipv4Addr::from(subnet_base),
+            mask: 26,
+        });
+        s.ip_configurations_count = Some((i % 60) as u32);
+        if i % 50 == 0 {
+            subnets.push(s.clone());
+        }
+        subnets.push(s);
+    }
+    Data {
+        count: subnets.len() as i32,
+        skip_token: None,
+        total_records: None,
+        data: subnets,
+    }
+}
+
+fn sorted(mut data: Data) -> Data {
+    data.data.sort_by_key(|s| s.subnet_cidr);
+    data
+}
+
+fn bench_dedup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dedup");
+    group.sample_size(10);
+    for size in SIZES {
+        let data = synthetic(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| de_duplicate_subnets(black_box(data.clone()), None).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_overlap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("overlap");
+    group.sample_size(10);
+    for size in SIZES {
+        let data = sorted(synthetic(size));
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("vnets", size), &data, |b, data| {
+            b.iter(|| find_overlapping_vnets(black_box(data)))
+        });
+        group.bench_with_input(BenchmarkId::new("address_space", size), &data, |b, data| {
+            b.iter(|| find_address_space_overlaps(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_gaps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gaps");
+    group.sample_size(10);
+    for size in SIZES {
+        let data = de_duplicate_subnets(synthetic(size), None).unwrap();
+        let data = sorted(data);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| {
+                let vnet_cidrs = group_vnet_cidrs(black_box(&data.data));
+                gaps(&vnet_cidrs, 4).len()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_dedup, bench_overlap, bench_gaps);
+criterion_main!(benches);
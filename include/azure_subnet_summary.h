@@ -0,0 +1,51 @@
+/*
+ * C API of azure-subnet-summary (`ffi` feature), see src/ffi.rs.
+ *
+ * Build: cargo rustc --lib --release --features ffi --crate-type cdylib
+ *
+ * Strings are NUL-terminated UTF-8. Returned `char *` strings belong to the
+ * caller and are released with azsub_string_free(). NULL means error (see
+ * azsub_last_error()) or, where documented, "no result" with no error set.
+ */
+#ifndef AZURE_SUBNET_SUMMARY_H
+#define AZURE_SUBNET_SUMMARY_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct AzsubSnapshot AzsubSnapshot;
+
+/* Message of the last failed call on this thread, or NULL. Owned by the
+ * library; valid until the next call on the thread. */
+const char *azsub_last_error(void);
+void azsub_string_free(char *s);
+
+/* Usable Azure hosts in a /prefix_len subnet, or -1 if too small. */
+int64_t azsub_azure_hosts(uint8_t prefix_len);
+/* JSON object: cidr, network, broadcast, addresses, azure_hosts,
+ * first_host, last_host. */
+char *azsub_cidr_info(const char *cidr);
+/* JSON array of the /new_prefix_len subnets of cidr; NULL with an error
+ * when that is more than 4096 subnets. */
+char *azsub_split(const char *cidr, uint8_t new_prefix_len);
+/* Subnet after cidr sized /prefix_len (0 keeps the size). */
+char *azsub_next_subnet(const char *cidr, uint8_t prefix_len);
+/* First free /prefix_len block in spaces_json (JSON array of CIDRs) not
+ * overlapping used_json; NULL without error when full. */
+char *azsub_find_free_subnet(const char *spaces_json, const char *used_json,
+                             uint8_t prefix_len);
+
+/* Load a subnet cache file (.json or .json.zst) for lookups. */
+AzsubSnapshot *azsub_snapshot_load(const char *path);
+void azsub_snapshot_free(AzsubSnapshot *snapshot);
+/* JSON of the subnet containing ip; NULL without error when none does. */
+char *azsub_lookup_ip(const AzsubSnapshot *snapshot, const char *ip);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AZURE_SUBNET_SUMMARY_H */
@@ -0,0 +1,182 @@
+//! Azure CLI account context of a fetch.
+//!
+//! `az graph query` runs under whatever account, tenant and default
+//! subscription `az account show` reports, and `az account set` in another
+//! terminal changes that for every running `az` process. The first Resource
+//! Graph page of a fetch pins the context; every later page checks it is
+//! unchanged and fails fast otherwise, so one snapshot never mixes pages
+//! from different contexts. The pinned context is recorded in the cache
+//! metadata ([`super::CacheMeta::account`]). [`reset_account_pin`] starts a
+//! new fetch, so a long-running process follows a later `az account set`.
+
+use super::cli;
+use super::graph_quota::{is_throttled, pace_graph_query, record_throttled};
+use super::paginate::PageEnvelope;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::sync::Mutex;
+
+/// Account context the Azure CLI queries with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountContext {
+    pub tenant_id: String,
+    /// Default subscription (`az account set`).
+    pub subscription_id: String,
+    /// Signed-in user or service principal.
+    pub user: String,
+}
+
+/// `az account show` output — only the fields compared.
+#[derive(Deserialize)]
+struct AccountShow {
+    #[serde(rename = "tenantId")]
+    tenant_id: String,
+    id: String,
+    user: AccountUser,
+}
+
+#[derive(Deserialize)]
+struct AccountUser {
+    name: String,
+}
+
+/// Context pinned by the first page of the current fetch.
+static PINNED: Mutex<Option<AccountContext>> = Mutex::new(None);
+
+impl AccountContext {
+    /// The context `az account show` reports now.
+    pub fn current() -> Result<AccountContext, Box<dyn Error>> {
+        let show: AccountShow = cli::run_az_json(&[
+            "account".to_string(),
+            "show".to_string(),
+            "--output".to_string(),
+            "json".to_string(),
+        ])
+        .map_err(|e| format!("Could not read the Azure CLI account (az account show): {e}"))?;
+        Ok(AccountContext {
+            tenant_id: show.tenant_id,
+            subscription_id: show.id,
+            user: show.user.name,
+        })
+    }
+
+    /// Error unless `now` is the same context as `self`.
+    pub fn ensure_unchanged(&self, now: &AccountContext) -> Result<(), String> {
+        if self == now {
+            return Ok(());
+        }
+        Err(format!(
+            "Azure CLI account changed during the fetch (tenant {} / subscription {} / {} -> \
+             tenant {} / subscription {} / {}); was `az account set` run in another terminal? \
+             Re-run once the account is stable.",
+            self.tenant_id,
+            self.subscription_id,
+            self.user,
+            now.tenant_id,
+            now.subscription_id,
+            now.user
+        ))
+    }
+}
+
+/// Pin the current context on the first call of the fetch; on later calls,
+/// fail if it has changed since.
+pub fn check_account() -> Result<(), Box<dyn Error>> {
+    let now = AccountContext::current()?;
+    let mut pinned = PINNED.lock().map_err(|_| "account context lock poisoned")?;
+    match pinned.as_ref() {
+        Some(expected) => expected.ensure_unchanged(&now)?,
+        None => {
+            log::info!(
+                "Fetching as '{}' in tenant {} (subscription {})",
+                now.user,
+                now.tenant_id,
+                now.subscription_id
+            );
+            *pinned = Some(now);
+        }
+    }
+    Ok(())
+}
+
+/// Context pinned by the current fetch, if anything was fetched from Azure.
+pub fn pinned_account() -> Option<AccountContext> {
+    PINNED.lock().ok().and_then(|p| p.clone())
+}
+
+/// Forget the pinned context; the next Resource Graph page pins it afresh.
+/// Called at the start of each fetch.
+pub fn reset_account_pin() {
+    if let Ok(mut pinned) = PINNED.lock() {
+        *pinned = None;
+    }
+}
+
+/// Bearer token for `resource` (e.g. `https://monitor.azure.com`) as the
+/// signed-in account.
+pub fn access_token(resource: &str) -> Result<String, Box<dyn Error>> {
+    let args: Vec<String> = [
+        "account",
+        "get-access-token",
+        "--resource",
+        resource,
+        "--query",
+        "accessToken",
+        "-o",
+        "tsv",
+    ]
+    .map(String::from)
+    .to_vec();
+    let token = cli::run_az(&args)
+        .map_err(|e| format!("Error getting an access token for {resource}: {e}"))?;
+    Ok(token.trim().to_string())
+}
+
+/// [`cli::run_az_json`] for Resource Graph pages, checking the account
+/// context first and keeping within the query quota. A throttled page is
+/// retried once, after the quota window.
+pub(crate) fn run_graph_page(args: &[String]) -> Result<PageEnvelope, Box<dyn Error>> {
+    check_account()?;
+    pace_graph_query();
+    match cli::run_az_json(args) {
+        Err(e) if is_throttled(&e.to_string()) => {
+            log::warn!("Resource Graph throttled the query; retrying after the quota window");
+            record_throttled();
+            pace_graph_query();
+            cli::run_az_json(args)
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_show_output_is_read_and_changes_are_reported() {
+        let show: AccountShow = serde_json::from_str(
+            r#"{"id": "sub-1", "name": "Prod", "tenantId": "tenant-a",
+                "user": {"name": "ops@example.com", "type": "user"}}"#,
+        )
+        .unwrap();
+        let pinned = AccountContext {
+            tenant_id: show.tenant_id,
+            subscription_id: show.id,
+            user: show.user.name,
+        };
+        assert!(pinned.ensure_unchanged(&pinned.clone()).is_ok());
+        let switched = AccountContext {
+            subscription_id: "sub-2".to_string(),
+            ..pinned.clone()
+        };
+        let err = pinned.ensure_unchanged(&switched).unwrap_err();
+        assert!(err.contains("subscription sub-1"), "{err}");
+        assert!(err.contains("subscription sub-2"), "{err}");
+
+        *PINNED.lock().unwrap() = Some(pinned);
+        assert!(pinned_account().is_some());
+        reset_account_pin();
+        assert_eq!(pinned_account(), None);
+    }
+}
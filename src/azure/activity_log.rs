@@ -0,0 +1,134 @@
+//! Azure Activity Log write operations on a resource.
+//!
+//! `az monitor activity-log list --resource-id` returns every event of an
+//! operation (Started, Accepted, Succeeded, ...), reads included. Only
+//! successful writes and deletes are kept — the "who changed it and when"
+//! that `diff --activity-log` attaches to subnet changes. The Activity Log
+//! keeps 90 days.
+
+use super::cli::run_az_json;
+use crate::models::ActivityEvent;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+use std::error::Error;
+
+/// How far back the Activity Log goes.
+pub const ACTIVITY_LOG_RETENTION_DAYS: i64 = 90;
+
+/// `{ "value": ..., "localizedValue": ... }` as used for names in events.
+#[derive(Deserialize, Debug, Default)]
+struct LocalizedName {
+    #[serde(default)]
+    value: String,
+}
+
+/// One event as returned by `az monitor activity-log list`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RawEvent {
+    #[serde(default)]
+    caller: Option<String>,
+    event_timestamp: DateTime<Utc>,
+    #[serde(default)]
+    operation_name: LocalizedName,
+    #[serde(default)]
+    status: LocalizedName,
+    #[serde(default)]
+    resource_id: String,
+}
+
+/// Successful writes and deletes among `events`, newest first.
+fn writes(events: Vec<RawEvent>) -> Vec<ActivityEvent> {
+    let mut writes: Vec<ActivityEvent> = events
+        .into_iter()
+        .filter(|e| e.status.value.eq_ignore_ascii_case("Succeeded"))
+        .filter(|e| {
+            let op = e.operation_name.value.to_ascii_lowercase();
+            op.ends_with("/write") || op.ends_with("/delete")
+        })
+        .map(|e| ActivityEvent {
+            caller: e.caller.unwrap_or_default(),
+            timestamp: e.event_timestamp,
+            operation: e.operation_name.value,
+            resource_id: e.resource_id,
+        })
+        .collect();
+    writes.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    writes
+}
+
+/// Subscription segment of an ARM resource ID.
+fn subscription_of(resource_id: &str) -> Option<&str> {
+    let mut parts = resource_id.split('/');
+    parts
+        .by_ref()
+        .find(|p| p.eq_ignore_ascii_case("subscriptions"))?;
+    parts.next().filter(|s| !s.is_empty())
+}
+
+/// Successful writes and deletes on `resource_id` since `since`, newest first.
+pub fn activity_log_writes(
+    resource_id: &str,
+    since: DateTime<Utc>,
+) -> Result<Vec<ActivityEvent>, Box<dyn Error>> {
+    let subscription = subscription_of(resource_id)
+        .ok_or_else(|| format!("No subscription in resource ID '{resource_id}'"))?;
+    let args: Vec<String> = [
+        "monitor",
+        "activity-log",
+        "list",
+        "--subscription",
+        subscription,
+        "--resource-id",
+        resource_id,
+        "--start-time",
+        &since.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "--max-events",
+        "500",
+        "--output",
+        "json",
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect();
+    let events: Vec<RawEvent> = run_az_json(&args)
+        .map_err(|e| format!("Error reading the Activity Log of '{resource_id}': {e}"))?;
+    Ok(writes(events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_successful_writes_newest_first() {
+        let json = r#"[
+            {"caller": "ops@contoso.com", "eventTimestamp": "2026-10-14T14:02:11.5Z",
+             "operationName": {"value": "Microsoft.Network/virtualNetworks/subnets/write"},
+             "status": {"value": "Succeeded"}, "resourceId": "/subscriptions/s/x/subnets/app"},
+            {"caller": "ops@contoso.com", "eventTimestamp": "2026-10-14T14:01:58Z",
+             "operationName": {"value": "Microsoft.Network/virtualNetworks/subnets/write"},
+             "status": {"value": "Started"}, "resourceId": "/subscriptions/s/x/subnets/app"},
+            {"caller": "reader@contoso.com", "eventTimestamp": "2026-10-14T15:00:00Z",
+             "operationName": {"value": "Microsoft.Network/virtualNetworks/subnets/read"},
+             "status": {"value": "Succeeded"}, "resourceId": "/subscriptions/s/x/subnets/app"},
+            {"caller": "0000-app-id", "eventTimestamp": "2026-10-15T01:00:00Z",
+             "operationName": {"value": "Microsoft.Network/virtualNetworks/subnets/delete"},
+             "status": {"value": "Succeeded"}, "resourceId": "/subscriptions/s/x/subnets/app"}
+        ]"#;
+        let events = writes(serde_json::from_str(json).unwrap());
+        let summary: Vec<_> = events
+            .iter()
+            .map(|e| (e.caller.as_str(), e.operation.rsplit('/').next().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            [("0000-app-id", "delete"), ("ops@contoso.com", "write")]
+        );
+        assert_eq!(
+            subscription_of("/subscriptions/abc/resourceGroups/rg"),
+            Some("abc")
+        );
+        assert_eq!(subscription_of(""), None);
+    }
+}
@@ -0,0 +1,18 @@
+//! Cache management for AKS agent pool data.
+
+use super::aks_graph::{run_aks_graph, AksData, AKS_QUERY};
+use super::azure_cache::AzureSource;
+use super::paginate::QueryOptions;
+use std::error::Error;
+
+impl AzureSource for AksData {
+    fn file_stem() -> &'static str {
+        "aks"
+    }
+    fn query() -> &'static str {
+        AKS_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_aks_graph(options)
+    }
+}
@@ -0,0 +1,97 @@
+//! Azure Resource Graph query for AKS agent pools.
+//!
+//! Queries `microsoft.containerservice/managedclusters` and expands
+//! `agentPoolProfiles` — one row per agent pool — with the subnet IDs, node
+//! counts and pod density needed to estimate subnet IP pressure.
+
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Duration;
+
+/// KQL: one row per AKS agent pool.
+pub(super) const AKS_QUERY: &str = r#"resources
+    | where type == "microsoft.containerservice/managedclusters"
+    | mv-expand pool = properties.agentPoolProfiles
+    | project subscription_id = subscriptionId
+             ,resource_group = resourceGroup
+             ,cluster_name = name
+             ,network_plugin = tostring(properties.networkProfile.networkPlugin)
+             ,network_plugin_mode = tostring(properties.networkProfile.networkPluginMode)
+             ,pool_name = tostring(pool.name)
+             ,vnet_subnet_id = tostring(pool.vnetSubnetID)
+             ,pod_subnet_id = tostring(pool.podSubnetID)
+             ,max_pods = toint(pool.maxPods)
+             ,node_count = toint(pool['count'])
+             ,max_count = toint(pool.maxCount)
+             ,enable_auto_scaling = tobool(pool.enableAutoScaling)
+             ,max_surge = tostring(pool.upgradeSettings.maxSurge)
+    | sort by cluster_name asc, pool_name asc"#;
+
+/// One row from the AKS query: a single agent pool of a cluster.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct AksPoolRow {
+    pub subscription_id: String,
+    #[serde(default)]
+    pub resource_group: String,
+    pub cluster_name: String,
+    /// `azure`, `kubenet` or `none`.
+    #[serde(default)]
+    pub network_plugin: String,
+    /// `overlay` when Azure CNI Overlay is used, otherwise empty.
+    #[serde(default)]
+    pub network_plugin_mode: String,
+    pub pool_name: String,
+    /// Node subnet ID; empty when AKS manages its own VNet.
+    #[serde(default)]
+    pub vnet_subnet_id: String,
+    /// Pod subnet ID for Azure CNI with dynamic pod IP allocation.
+    #[serde(default)]
+    pub pod_subnet_id: String,
+    #[serde(default)]
+    pub max_pods: Option<u32>,
+    #[serde(default)]
+    pub node_count: Option<u32>,
+    /// Autoscaler upper bound (only meaningful with `enable_auto_scaling`).
+    #[serde(default)]
+    pub max_count: Option<u32>,
+    #[serde(default)]
+    pub enable_auto_scaling: Option<bool>,
+    /// Upgrade surge, either a node count (`"1"`) or a percentage (`"33%"`).
+    #[serde(default)]
+    pub max_surge: String,
+}
+
+/// Response wrapper for the AKS query.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AksData {
+    pub data: Vec<AksPoolRow>,
+    pub skip_token: Option<String>,
+    pub total_records: Option<u32>,
+    pub count: i32,
+}
+
+/// Execute the Azure Resource Graph AKS query with automatic pagination.
+pub fn run_aks_graph(options: &QueryOptions) -> Result<AksData, Box<dyn Error>> {
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(AKS_QUERY, options, sleep, run_graph_page)?;
+
+    let data: Vec<AksPoolRow> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing AKS agent pool rows: {e}"))?;
+
+    let count = data.len() as i32;
+    let total_records = Some(data.len() as u32);
+
+    log::info!("Got {count} AKS agent pool rows from az graph query");
+
+    Ok(AksData {
+        data,
+        skip_token: None,
+        total_records,
+        count,
+    })
+}
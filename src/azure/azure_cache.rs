@@ -1,12 +1,40 @@
 //! Generic cache layer for all Azure data sources.
 //!
 //! Defines [`AzureSource`] and the single [`load`] function that implement
-//! the cache-or-fetch pattern shared across all four data sources.
+//! the cache-or-fetch pattern shared across all data sources.
+//!
+//! Cache files are plain JSON (`.json`) or zstd-compressed JSON (`.json.zst`).
+//! Either form is read transparently; new files are compressed when requested.
+//!
+//! Default cache filenames carry a short hash of the query text and its scope
+//! (tenant, management group, subscriptions), so a changed query or scope never
+//! reads another query's data. Each written file records that query under a
+//! `cache_meta` key for traceability, along with the time it was generated.
+//!
+//! The date in default filenames comes from the run's [`Clock`] (UTC unless
+//! configured otherwise).
+//!
+//! Fetching and writing today's cache happens under a [`CacheLock`], so a
+//! second run started meanwhile waits and reads the first run's file.
 
-use chrono;
-use serde::{de::DeserializeOwned, Serialize};
+use super::account::{pinned_account, AccountContext};
+use super::cache_lock::CacheLock;
+use super::paginate::QueryOptions;
+use crate::clock::Clock;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::error::Error;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// zstd level used for compressed cache files — a good ratio for large JSON
+/// without making daily writes noticeably slower.
+#[cfg(feature = "cli")]
+const ZSTD_LEVEL: i32 = 9;
+
+/// Cache file name that reads the snapshot from stdin (`--cache-file -`).
+pub const STDIN_CACHE: &str = "-";
+
+/// Frame magic of zstd data, used to detect a compressed snapshot on stdin.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
 
 /// Result of loading an Azure data source (from cache or a fresh fetch).
 #[derive(Debug)]
@@ -14,31 +42,133 @@ pub struct CacheResult<T> {
     pub data: T,
     pub from_cache: bool,
     pub cache_file: String,
+    /// When the data was fetched ([`CacheMeta::generated_at`]); None for
+    /// cache files written before it was recorded.
+    pub generated_at: Option<String>,
+}
+
+/// Key under which [`CacheMeta`] is stored in a cache file.
+const META_KEY: &str = "cache_meta";
+
+/// Provenance recorded inside every cache file written by [`load`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheMeta {
+    /// Hash of the query and scope, as used in the filename.
+    pub key: String,
+    /// KQL query text the data was fetched with.
+    pub query: String,
+    pub tenant: Option<String>,
+    pub management_group: Option<String>,
+    #[serde(default)]
+    pub subscriptions: Vec<String>,
+    /// When the data was fetched, as an RFC 3339 timestamp in the run's
+    /// timezone. Absent in files written before it was recorded.
+    #[serde(default)]
+    pub generated_at: Option<String>,
+    /// Azure CLI account context the data was fetched under; None for files
+    /// read from elsewhere or written before it was recorded.
+    #[serde(default)]
+    pub account: Option<AccountContext>,
+}
+
+impl CacheMeta {
+    fn new<S: AzureSource>(options: &QueryOptions, clock: &Clock) -> CacheMeta {
+        let mut subscriptions = options.subscriptions.clone();
+        subscriptions.sort();
+        CacheMeta {
+            key: cache_key::<S>(options),
+            query: S::query().to_string(),
+            tenant: options.tenant.clone(),
+            management_group: options.management_group.clone(),
+            subscriptions,
+            generated_at: Some(clock.timestamp()),
+            account: None,
+        }
+    }
 }
 
 /// Trait for Azure data types that can be persisted as a local JSON cache.
 pub trait AzureSource: DeserializeOwned + Serialize {
     /// Stem used in the date-stamped filename, e.g. `"subnet"` →
-    /// `net_YYYY-MM-DD_cache_subnet.json`.
+    /// `net_YYYY-MM-DD_cache_subnet_<key>.json`.
     fn file_stem() -> &'static str;
 
-    /// Fetch fresh data from Azure CLI.
-    fn fetch() -> Result<Self, Box<dyn Error>>;
+    /// KQL query text the data is fetched with; part of the cache key.
+    fn query() -> &'static str;
+
+    /// Fetch fresh data from Azure CLI, scoped by `options`.
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>>;
 }
 
 /// Load data from a cache file, or fetch from Azure when the file is absent.
 ///
 /// If `cache_file` is `Some`, that exact path is used and an error is returned
 /// if the file does not exist. If `None`, a date-stamped filename derived from
-/// [`AzureSource::file_stem`] is used, written into `cache_dir` (defaults to
-/// the current directory when `None`).
+/// [`AzureSource::file_stem`], today's date per `clock` and [`cache_key`] is used,
+/// written into `cache_dir` (defaults to the current directory when `None`). An existing `.json` or `.json.zst`
+/// file for today is read; on a cache miss, `options` scopes the fetch and the
+/// new file is written as `.json.zst` when `compress` is set.
 pub fn load<S: AzureSource>(
     cache_file: Option<&str>,
     cache_dir: Option<&Path>,
+    compress: bool,
+    options: &QueryOptions,
+    clock: &Clock,
 ) -> Result<CacheResult<S>, Box<dyn Error>> {
-    let now = chrono::Utc::now().with_timezone(&chrono_tz::Pacific::Auckland);
+    load_with(cache_file, cache_dir, compress, options, clock, || {
+        S::fetch(options)
+    })
+}
+
+/// Short, stable hash of the source's query text and the query scope in
+/// `options`. Subscription order does not matter; page size is ignored.
+/// A `--sample` run gets its own key so it never stands in for a full fetch.
+pub fn cache_key<S: AzureSource>(options: &QueryOptions) -> String {
+    let mut subscriptions = options.subscriptions.clone();
+    subscriptions.sort();
+    let mut input = [
+        S::query(),
+        options.tenant.as_deref().unwrap_or(""),
+        options.management_group.as_deref().unwrap_or(""),
+        &subscriptions.join(","),
+    ]
+    .join("\0");
+    if let Some(n) = options.sample {
+        input.push_str(&format!("\0sample={n}"));
+    }
+    format!("{:08x}", fnv1a(input.as_bytes()) >> 32)
+}
+
+/// 64-bit FNV-1a — stable across Rust versions, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Like [`load`], but calls `fetch` instead of [`AzureSource::fetch`] on a
+/// cache miss. Used when a source needs a non-default fetch strategy (e.g. an
+/// incremental refresh on top of a previous snapshot).
+pub fn load_with<S, F>(
+    cache_file: Option<&str>,
+    cache_dir: Option<&Path>,
+    compress: bool,
+    options: &QueryOptions,
+    clock: &Clock,
+    fetch: F,
+) -> Result<CacheResult<S>, Box<dyn Error>>
+where
+    S: AzureSource,
+    F: FnOnce() -> Result<S, Box<dyn Error>>,
+{
+    let meta = CacheMeta::new::<S>(options, clock);
+    let plain = plain_cache_path::<S>(cache_dir, &meta.key, clock);
 
     let cache_file_path = match cache_file {
+        Some(STDIN_CACHE) => {
+            log::info!("Reading cache from stdin");
+            STDIN_CACHE.to_string()
+        }
         Some(file) => {
             if !Path::new(file).exists() {
                 return Err(format!("Cache file does not exist: {file}").into());
@@ -46,34 +176,71 @@ pub fn load<S: AzureSource>(
             log::info!("Using provided cache file: {file}");
             file.to_string()
         }
-        None => {
-            let dir = cache_dir.unwrap_or(Path::new("."));
-            dir.join(format!(
-                "net_{}_cache_{}.json",
-                now.format("%Y-%m-%d"),
-                S::file_stem()
-            ))
+        None => default_cache_path(&plain, compress)
             .to_string_lossy()
-            .into_owned()
-        }
+            .into_owned(),
     };
 
-    let (data, from_cache) = match std::fs::read_to_string(&cache_file_path) {
+    let (data, from_cache, generated_at) = match read_cache_string(Path::new(&cache_file_path)) {
         Ok(json) => {
             log::info!("Reading from cache file: {cache_file_path}");
-            let data: S = serde_json::from_str(&json)
-                .map_err(|e| format!("Error parsing cache JSON: {e}"))?;
-            (data, true)
+            let (data, generated_at) = parse_cache_hit(&json, &cache_file_path, &meta)?;
+            (data, true, generated_at)
+        }
+        Err(e) if cache_file_path == STDIN_CACHE => {
+            return Err(format!("Error reading cache from stdin: {e}").into());
         }
         Err(_) => {
             log::warn!("Cache file not found: {cache_file_path}");
-            let data = S::fetch()?;
-            let json = serde_json::to_string_pretty(&data)
-                .map_err(|e| format!("Error serializing JSON: {e}"))?;
+            // Only today's default cache is shared between concurrent runs.
+            let _lock = cache_file.is_none().then(|| CacheLock::acquire(&plain));
+            if cache_file.is_none() {
+                // A run we waited for may have written the cache meanwhile.
+                let written = default_cache_path(&plain, compress)
+                    .to_string_lossy()
+                    .into_owned();
+                if let Ok(json) = read_cache_string(Path::new(&written)) {
+                    log::info!("Reusing cache file written by the other run: {written}");
+                    let (data, generated_at) = parse_cache_hit(&json, &written, &meta)?;
+                    return Ok(CacheResult {
+                        data,
+                        from_cache: true,
+                        cache_file: written,
+                        generated_at,
+                    });
+                }
+                let others = other_todays_caches::<S>(&plain, clock);
+                if let Some((path, json)) = same_scope_any_tenant::<S>(&others, options) {
+                    let path = path.to_string_lossy().into_owned();
+                    log::info!("No tenant to key the cache by — using {path}, same query scope");
+                    let (data, generated_at) = parse_cache_hit(&json, &path, &meta)?;
+                    return Ok(CacheResult {
+                        data,
+                        from_cache: true,
+                        cache_file: path,
+                        generated_at,
+                    });
+                }
+                if !others.is_empty() {
+                    let names: Vec<String> =
+                        others.iter().map(|p| p.display().to_string()).collect();
+                    log::warn!(
+                        "Not using today's cache file(s) {}: written for another query, scope or \
+                         tenant, or before cache files were keyed by them",
+                        names.join(", ")
+                    );
+                }
+            }
+            let data = fetch()?;
+            let meta = CacheMeta {
+                account: pinned_account(),
+                ..meta
+            };
+            let json = to_cache_json(&data, &meta)?;
             log::warn!("Writing data to cache file: {cache_file_path}");
-            std::fs::write(&cache_file_path, &json)
+            write_cache_string(Path::new(&cache_file_path), &json)
                 .map_err(|e| format!("Error writing cache file {cache_file_path}: {e}"))?;
-            (data, false)
+            (data, false, meta.generated_at)
         }
     };
 
@@ -81,9 +248,262 @@ pub fn load<S: AzureSource>(
         data,
         from_cache,
         cache_file: cache_file_path,
+        generated_at,
     })
 }
 
+/// Today's default cache file of `S` in its uncompressed form.
+fn plain_cache_path<S: AzureSource>(cache_dir: Option<&Path>, key: &str, clock: &Clock) -> PathBuf {
+    cache_dir.unwrap_or(Path::new(".")).join(format!(
+        "net_{}_cache_{}_{key}.json",
+        clock.date_str(),
+        S::file_stem()
+    ))
+}
+
+/// Today's other cache files of `S` next to `plain`: those of other query
+/// scopes or tenants, and those named before cache keys existed
+/// (`net_<date>_cache_<stem>.json`).
+fn other_todays_caches<S: AzureSource>(plain: &Path, clock: &Clock) -> Vec<PathBuf> {
+    let dir = plain
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let prefix = format!("net_{}_cache_{}", clock.date_str(), S::file_stem());
+    let is_cache = |name: &str| {
+        let name = name.strip_suffix(".zst").unwrap_or(name);
+        name.strip_suffix(".json")
+            .and_then(|n| n.strip_prefix(&prefix))
+            .is_some_and(|key| {
+                key.is_empty()
+                    || key
+                        .strip_prefix('_')
+                        .is_some_and(|k| k.len() == 8 && k.chars().all(|c| c.is_ascii_hexdigit()))
+            })
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut others: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(is_cache))
+        .filter(|p| *p != plain && *p != compressed_path(plain))
+        .collect();
+    others.sort();
+    others
+}
+
+/// The one file among `others` written for the same query scope as
+/// `options` in some tenant, when `options` names no tenant (`--offline`
+/// cannot ask `az` for the signed-in one). None when tenants differ.
+fn same_scope_any_tenant<S: AzureSource>(
+    others: &[PathBuf],
+    options: &QueryOptions,
+) -> Option<(PathBuf, String)> {
+    if options.tenant.is_some() {
+        return None;
+    }
+    let mut matches = others.iter().filter_map(|path| {
+        let json = read_cache_string(path).ok()?;
+        let meta = parse_cache_meta(&json)?;
+        let scoped = QueryOptions {
+            tenant: meta.tenant.clone(),
+            ..options.clone()
+        };
+        (meta.tenant.is_some() && cache_key::<S>(&scoped) == meta.key).then(|| (path.clone(), json))
+    });
+    let found = matches.next()?;
+    matches.next().is_none().then_some(found)
+}
+
+/// Whether [`load`] would find today's default cache of `S` (either form)
+/// in `cache_dir` instead of fetching, including the one same-scope file
+/// it falls back to when `options` names no tenant.
+pub fn todays_cache_exists<S: AzureSource>(
+    cache_dir: Option<&Path>,
+    options: &QueryOptions,
+    clock: &Clock,
+) -> bool {
+    let plain = plain_cache_path::<S>(cache_dir, &cache_key::<S>(options), clock);
+    plain.exists()
+        || compressed_path(&plain).exists()
+        || same_scope_any_tenant::<S>(&other_todays_caches::<S>(&plain, clock), options).is_some()
+}
+
+/// Delete the files of today's default cache of `S` that [`load`] would
+/// read, so its next call queries Azure again. Each file is removed while
+/// holding its [`CacheLock`]; caches of other scopes and tenants are kept.
+pub fn remove_todays_cache<S: AzureSource>(
+    cache_dir: Option<&Path>,
+    options: &QueryOptions,
+    clock: &Clock,
+) -> Result<(), Box<dyn Error>> {
+    let plain = plain_cache_path::<S>(cache_dir, &cache_key::<S>(options), clock);
+    let fallback = same_scope_any_tenant::<S>(&other_todays_caches::<S>(&plain, clock), options);
+    for file in std::iter::once(plain).chain(fallback.map(|(path, _)| path)) {
+        let _lock = CacheLock::acquire(&file);
+        for path in [compressed_path(&file), file] {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(format!("Error removing '{}': {e}", path.display()).into());
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Today's cache file for `plain`: whichever of the plain and compressed
+/// forms already exists, otherwise the one `compress` asks for.
+fn default_cache_path(plain: &Path, compress: bool) -> PathBuf {
+    let compressed = compressed_path(plain);
+    if plain.exists() {
+        plain.to_path_buf()
+    } else if compressed.exists() || compress {
+        compressed
+    } else {
+        plain.to_path_buf()
+    }
+}
+
+/// Parse a cache file read for `meta`, warning when it was written for
+/// another query or scope. Returns the data and when it was generated.
+fn parse_cache_hit<S: AzureSource>(
+    json: &str,
+    cache_file_path: &str,
+    meta: &CacheMeta,
+) -> Result<(S, Option<String>), Box<dyn Error>> {
+    let found = parse_cache_meta(json);
+    if let Some(found) = found.as_ref().filter(|m| m.key != meta.key) {
+        log::warn!(
+            "Cache file {cache_file_path} was written for a different query or scope (key {} != {})",
+            found.key,
+            meta.key
+        );
+    }
+    Ok((parse_cache_json(json)?, found.and_then(|m| m.generated_at)))
+}
+
+/// Read and parse an existing cache file (e.g. a previous day's snapshot).
+///
+/// Files ending in `.zst` are decompressed first.
+pub fn read_cache_file<S: AzureSource>(path: &Path) -> Result<S, Box<dyn Error>> {
+    let json = read_cache_string(path)
+        .map_err(|e| format!("Error reading cache file {}: {e}", path.display()))?;
+    parse_cache_json(&json)
+}
+
+/// Read the [`CacheMeta`] recorded in a cache file, if it has one.
+///
+/// Files written before cache keys were introduced have none.
+pub fn read_cache_meta(path: &Path) -> Result<Option<CacheMeta>, Box<dyn Error>> {
+    let json = read_cache_string(path)
+        .map_err(|e| format!("Error reading cache file {}: {e}", path.display()))?;
+    Ok(parse_cache_meta(&json))
+}
+
+/// Returns `true` when `path` names a zstd-compressed cache file.
+pub fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "zst")
+}
+
+/// `net_..._cache_subnet_<key>.json` → `net_..._cache_subnet_<key>.json.zst`.
+fn compressed_path(plain: &Path) -> PathBuf {
+    let mut name = plain.as_os_str().to_owned();
+    name.push(".zst");
+    PathBuf::from(name)
+}
+
+/// Decompress zstd data.
+#[cfg(feature = "cli")]
+fn zstd_decode(bytes: impl std::io::Read) -> std::io::Result<Vec<u8>> {
+    zstd::decode_all(bytes)
+}
+
+/// Compress with zstd at [`ZSTD_LEVEL`].
+#[cfg(feature = "cli")]
+fn zstd_encode(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    zstd::encode_all(bytes, ZSTD_LEVEL)
+}
+
+/// zstd is a C library; builds without `cli` (e.g. wasm) read plain JSON only.
+#[cfg(not(feature = "cli"))]
+fn zstd_decode(_bytes: impl std::io::Read) -> std::io::Result<Vec<u8>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "zstd-compressed caches need the `cli` feature",
+    ))
+}
+
+#[cfg(not(feature = "cli"))]
+fn zstd_encode(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    zstd_decode(bytes)
+}
+
+/// A snapshot piped in on stdin, zstd-compressed or plain JSON.
+fn decode_snapshot(mut bytes: Vec<u8>) -> std::io::Result<String> {
+    if bytes.starts_with(&ZSTD_MAGIC) {
+        bytes = zstd_decode(bytes.as_slice())?;
+    }
+    String::from_utf8(bytes).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+fn read_cache_string(path: &Path) -> std::io::Result<String> {
+    if path == Path::new(STDIN_CACHE) {
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::stdin().lock(), &mut bytes)?;
+        return decode_snapshot(bytes);
+    }
+    if !is_compressed(path) {
+        return std::fs::read_to_string(path);
+    }
+    let bytes = zstd_decode(std::fs::File::open(path)?)?;
+    String::from_utf8(bytes).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+fn write_cache_string(path: &Path, json: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let bytes = if is_compressed(path) {
+        zstd_encode(json.as_bytes())?
+    } else {
+        json.as_bytes().to_vec()
+    };
+    // Write aside and rename, so a concurrent reader never sees half a file.
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(format!(".{}.tmp", std::process::id()));
+    std::fs::write(&partial, bytes)?;
+    std::fs::rename(&partial, path).inspect_err(|_| {
+        std::fs::remove_file(&partial).ok();
+    })
+}
+
+/// Serialize `data` with `meta` added under [`META_KEY`].
+fn to_cache_json<S: AzureSource>(data: &S, meta: &CacheMeta) -> Result<String, Box<dyn Error>> {
+    let mut value =
+        serde_json::to_value(data).map_err(|e| format!("Error serializing JSON: {e}"))?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert(
+            META_KEY.to_string(),
+            serde_json::to_value(meta).map_err(|e| format!("Error serializing JSON: {e}"))?,
+        );
+    }
+    Ok(serde_json::to_string_pretty(&value).map_err(|e| format!("Error serializing JSON: {e}"))?)
+}
+
+fn parse_cache_meta(json: &str) -> Option<CacheMeta> {
+    let mut value: serde_json::Value = serde_json::from_str(json).ok()?;
+    serde_json::from_value(value.get_mut(META_KEY)?.take()).ok()
+}
+
+/// Parse the JSON text of a cache file, e.g. a snapshot a browser tool was
+/// handed, without touching the file system.
+pub fn parse_cache_json<S: AzureSource>(json: &str) -> Result<S, Box<dyn Error>> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("Error parsing cache JSON: {e}"))?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,7 +518,10 @@ mod tests {
         fn file_stem() -> &'static str {
             "stub"
         }
-        fn fetch() -> Result<Self, Box<dyn Error>> {
+        fn query() -> &'static str {
+            "resources | take 1"
+        }
+        fn fetch(_options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
             unreachable!("fetch should not be called in these tests")
         }
     }
@@ -108,7 +531,14 @@ mod tests {
         let path = "/tmp/azure_cache_test_load.json";
         std::fs::write(path, r#"{"v":42}"#).unwrap();
 
-        let result = load::<Stub>(Some(path), None).expect("load should succeed");
+        let result = load::<Stub>(
+            Some(path),
+            None,
+            false,
+            &QueryOptions::default(),
+            &Clock::default(),
+        )
+        .expect("load should succeed");
 
         assert!(result.from_cache, "should report from_cache = true");
         assert_eq!(result.data, Stub { v: 42 });
@@ -118,7 +548,13 @@ mod tests {
 
     #[test]
     fn load_fails_when_explicit_file_missing() {
-        let result = load::<Stub>(Some("/tmp/azure_cache_no_such_file_xyz.json"), None);
+        let result = load::<Stub>(
+            Some("/tmp/azure_cache_no_such_file_xyz.json"),
+            None,
+            false,
+            &QueryOptions::default(),
+            &Clock::default(),
+        );
 
         assert!(result.is_err());
         let msg = result.unwrap_err().to_string();
@@ -127,4 +563,277 @@ mod tests {
             "expected 'does not exist' in error, got: {msg}"
         );
     }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn compressed_cache_is_written_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = load_with::<Stub, _>(
+            None,
+            Some(dir.path()),
+            true,
+            &QueryOptions::default(),
+            &Clock::default(),
+            || Ok(Stub { v: 7 }),
+        )
+        .expect("fetch + write should succeed");
+        assert!(!written.from_cache);
+        let key = cache_key::<Stub>(&QueryOptions::default());
+        assert!(written
+            .cache_file
+            .ends_with(&format!("_cache_stub_{key}.json.zst")));
+        let raw = std::fs::read(&written.cache_file).unwrap();
+        assert!(
+            serde_json::from_slice::<Stub>(&raw).is_err(),
+            "file should be compressed"
+        );
+
+        // Second load reads the compressed file even without the compress toggle.
+        let again = load_with::<Stub, _>(
+            None,
+            Some(dir.path()),
+            false,
+            &QueryOptions::default(),
+            &Clock::default(),
+            || unreachable!("should read cache"),
+        )
+        .expect("cache read should succeed");
+        assert!(again.from_cache);
+        assert_eq!(again.data, Stub { v: 7 });
+        assert_eq!(
+            read_cache_file::<Stub>(Path::new(&again.cache_file)).unwrap(),
+            Stub { v: 7 }
+        );
+    }
+
+    /// Write today's cache of `Stub { v }` for `options` into `dir`.
+    fn write_stub(dir: &Path, options: &QueryOptions, v: i32) -> PathBuf {
+        let clock = Clock::default();
+        let meta = CacheMeta::new::<Stub>(options, &clock);
+        let path = plain_cache_path::<Stub>(Some(dir), &meta.key, &clock);
+        write_cache_string(&path, &to_cache_json(&Stub { v }, &meta).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn removing_todays_cache_keeps_other_tenants() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Clock::default();
+        let tenant = |t: &str| QueryOptions {
+            tenant: Some(t.to_string()),
+            ..QueryOptions::default()
+        };
+        let own = write_stub(dir.path(), &tenant("tenant-a"), 1);
+        let other = write_stub(dir.path(), &tenant("tenant-b"), 2);
+
+        remove_todays_cache::<Stub>(Some(dir.path()), &tenant("tenant-a"), &clock).unwrap();
+        assert!(!own.exists());
+        assert!(other.exists());
+        // Nothing left to remove is not an error.
+        remove_todays_cache::<Stub>(Some(dir.path()), &tenant("tenant-a"), &clock).unwrap();
+    }
+
+    #[test]
+    fn todays_caches_of_other_scopes_and_old_names_are_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Clock::default();
+        let tenant = |t: &str| QueryOptions {
+            tenant: Some(t.to_string()),
+            ..QueryOptions::default()
+        };
+        let own = write_stub(dir.path(), &tenant("tenant-a"), 1);
+        let other = write_stub(dir.path(), &tenant("tenant-b"), 2);
+        let legacy = dir
+            .path()
+            .join(format!("net_{}_cache_stub.json", clock.date_str()));
+        std::fs::write(&legacy, r#"{"v":3}"#).unwrap();
+        std::fs::write(dir.path().join("net_x_cache_stubborn.json"), "{}").unwrap();
+
+        let mut expected = vec![legacy, other];
+        expected.sort();
+        assert_eq!(other_todays_caches::<Stub>(&own, &clock), expected);
+    }
+
+    #[test]
+    fn unknown_tenant_reuses_the_only_same_scope_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let in_tenant = |t: &str| QueryOptions {
+            tenant: Some(t.to_string()),
+            ..QueryOptions::default()
+        };
+        let load_untenanted = || {
+            load_with::<Stub, _>(
+                None,
+                Some(dir.path()),
+                false,
+                &QueryOptions::default(),
+                &Clock::default(),
+                || Ok(Stub { v: 0 }),
+            )
+            .unwrap()
+        };
+        let written = write_stub(dir.path(), &in_tenant("tenant-a"), 1);
+        let hit = load_untenanted();
+        assert!(hit.from_cache);
+        assert_eq!(hit.data, Stub { v: 1 });
+        assert_eq!(Path::new(&hit.cache_file), written);
+
+        // Two tenants: ambiguous, so it fetches.
+        write_stub(dir.path(), &in_tenant("tenant-b"), 2);
+        let fetched = load_untenanted();
+        assert!(!fetched.from_cache);
+        assert_eq!(fetched.data, Stub { v: 0 });
+    }
+
+    #[test]
+    fn existing_plain_cache_wins_over_compress_toggle() {
+        let dir = tempfile::tempdir().unwrap();
+        load_with::<Stub, _>(
+            None,
+            Some(dir.path()),
+            false,
+            &QueryOptions::default(),
+            &Clock::default(),
+            || Ok(Stub { v: 1 }),
+        )
+        .unwrap();
+        let result = load_with::<Stub, _>(
+            None,
+            Some(dir.path()),
+            true,
+            &QueryOptions::default(),
+            &Clock::default(),
+            || unreachable!("should read cache"),
+        )
+        .unwrap();
+        assert!(result.from_cache);
+        assert!(result.cache_file.ends_with(".json"));
+    }
+
+    #[test]
+    fn cache_key_changes_with_query_scope_but_not_page_size_or_order() {
+        let base = QueryOptions::default();
+        let key = cache_key::<Stub>(&base);
+        assert_eq!(key.len(), 8);
+        assert_eq!(key, cache_key::<Stub>(&base), "stable");
+        let paged = QueryOptions {
+            page_size: 100,
+            ..base.clone()
+        };
+        assert_eq!(key, cache_key::<Stub>(&paged));
+        for scoped in [
+            QueryOptions {
+                tenant: Some("tenant-a".to_string()),
+                ..base.clone()
+            },
+            QueryOptions {
+                management_group: Some("mg-a".to_string()),
+                ..base.clone()
+            },
+            QueryOptions {
+                sample: Some(10),
+                ..base.clone()
+            },
+        ] {
+            assert_ne!(key, cache_key::<Stub>(&scoped));
+        }
+        let ab = QueryOptions {
+            subscriptions: vec!["a".to_string(), "b".to_string()],
+            ..base.clone()
+        };
+        let ba = QueryOptions {
+            subscriptions: vec!["b".to_string(), "a".to_string()],
+            ..base
+        };
+        assert_eq!(cache_key::<Stub>(&ab), cache_key::<Stub>(&ba));
+    }
+
+    #[test]
+    fn written_cache_records_query_and_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = QueryOptions {
+            management_group: Some("mg-a".to_string()),
+            ..QueryOptions::default()
+        };
+        let written = load_with::<Stub, _>(
+            None,
+            Some(dir.path()),
+            false,
+            &options,
+            &Clock::default(),
+            || Ok(Stub { v: 3 }),
+        )
+        .unwrap();
+        let meta = read_cache_meta(Path::new(&written.cache_file))
+            .unwrap()
+            .expect("meta");
+        assert_eq!(meta.query, "resources | take 1");
+        assert_eq!(meta.management_group.as_deref(), Some("mg-a"));
+        assert!(meta.generated_at.is_some());
+        assert!(written.cache_file.ends_with(&format!("_{}.json", meta.key)));
+        // The meta key does not get in the way of reading the data back.
+        assert_eq!(
+            read_cache_file::<Stub>(Path::new(&written.cache_file)).unwrap(),
+            Stub { v: 3 }
+        );
+
+        // A different scope uses a different file and fetches again.
+        let other = load_with::<Stub, _>(
+            None,
+            Some(dir.path()),
+            false,
+            &QueryOptions::default(),
+            &Clock::default(),
+            || Ok(Stub { v: 4 }),
+        )
+        .unwrap();
+        assert!(!other.from_cache);
+        assert_ne!(other.cache_file, written.cache_file);
+    }
+
+    #[test]
+    fn run_waiting_on_the_lock_reuses_the_other_runs_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = cache_key::<Stub>(&QueryOptions::default());
+        let clock = Clock::default();
+        let plain = dir
+            .path()
+            .join(format!("net_{}_cache_stub_{key}.json", clock.date_str()));
+        // The "other run": holds the lock, writes the cache, then releases.
+        let lock = CacheLock::acquire(&plain).unwrap();
+        let other = {
+            let plain = plain.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(300));
+                write_cache_string(&plain, r#"{"v":9}"#).unwrap();
+                drop(lock);
+            })
+        };
+        let result = load_with::<Stub, _>(
+            None,
+            Some(dir.path()),
+            false,
+            &QueryOptions::default(),
+            &clock,
+            || unreachable!("should reuse the other run's cache"),
+        )
+        .unwrap();
+        other.join().unwrap();
+        assert!(result.from_cache);
+        assert_eq!(result.data, Stub { v: 9 });
+        assert_eq!(
+            std::fs::read_dir(dir.path()).unwrap().count(),
+            1,
+            "no lock or temp file left"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn piped_snapshot_is_read_plain_or_compressed() {
+        let json = r#"{"v":7}"#;
+        assert_eq!(decode_snapshot(json.as_bytes().to_vec()).unwrap(), json);
+        let compressed = zstd::encode_all(json.as_bytes(), ZSTD_LEVEL).unwrap();
+        assert_eq!(decode_snapshot(compressed).unwrap(), json);
+    }
 }
@@ -3,15 +3,20 @@
 //! Provides caching functionality to avoid repeated Azure Graph API calls.
 
 use super::azure_cache::{self, AzureSource, CacheResult};
-use super::graph::{run_az_cli_graph, Data};
+use super::graph::{run_az_cli_graph, Data, SUBNET_QUERY};
+use super::paginate::QueryOptions;
+use crate::clock::Clock;
 use std::error::Error;
 
 impl AzureSource for Data {
     fn file_stem() -> &'static str {
         "subnet"
     }
-    fn fetch() -> Result<Self, Box<dyn Error>> {
-        run_az_cli_graph()
+    fn query() -> &'static str {
+        SUBNET_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_az_cli_graph(options)
     }
 }
 
@@ -22,7 +27,13 @@ impl AzureSource for Data {
 pub fn read_subnet_cache_with_status(
     cache_file: Option<&str>,
 ) -> Result<CacheResult<Data>, Box<dyn Error>> {
-    azure_cache::load(cache_file, None)
+    azure_cache::load(
+        cache_file,
+        None,
+        false,
+        &QueryOptions::default(),
+        &Clock::default(),
+    )
 }
 
 /// Read subnet data from cache file, or fetch from Azure if cache doesn't exist.
@@ -0,0 +1,254 @@
+//! Soft lock around writing a cache file.
+//!
+//! Two runs started at the same time (cron plus a colleague, or two CI jobs
+//! on one agent) would both miss today's cache, both query Azure and race to
+//! write the same file. The first run to miss creates `<cache file>.lock`;
+//! a second run waits for the lock to go away and then reads the cache the
+//! first run wrote instead of fetching again.
+//!
+//! The holder touches the lock file every [`heartbeat`] while it fetches, so
+//! a slow (paced or throttled) fetch keeps its lock. A lock file not touched
+//! for [`STALE_AFTER`] (a crashed or killed run) is taken over: it is
+//! renamed away first, so of several waiters only one gets it. Each lock
+//! file names its holder, and a run only touches or removes a lock file that
+//! still names it. A lock that cannot be created for any other reason
+//! (read-only directory) is skipped with a warning.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+/// Time without a heartbeat after which a lock file is assumed to be left
+/// behind by a dead run.
+pub const STALE_AFTER: Duration = Duration::from_secs(2 * 60);
+
+/// How often a waiting run checks the lock file.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Locks taken by this process, to tell its threads' locks apart.
+static LOCKS_TAKEN: AtomicU32 = AtomicU32::new(0);
+
+/// A held lock; the lock file is removed on drop.
+#[derive(Debug)]
+pub struct CacheLock {
+    path: PathBuf,
+    /// Holder line written to the lock file.
+    holder: String,
+    /// Stops the heartbeat thread when dropped.
+    heartbeat: Option<(mpsc::Sender<()>, JoinHandle<()>)>,
+}
+
+/// `net_..._cache_subnet_<key>.json` → `net_..._cache_subnet_<key>.json.lock`.
+fn lock_path(cache_file: &Path) -> PathBuf {
+    let mut name = cache_file.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// Age of the lock file, None when it is gone or its time is unreadable.
+fn lock_age(path: &Path) -> Option<Duration> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(
+        SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default(),
+    )
+}
+
+/// Whether the lock file at `path` names `holder`.
+fn held_by(path: &Path, holder: &str) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|h| h.trim() == holder)
+}
+
+/// How often a holder touches its lock file: well inside `stale_after`.
+fn heartbeat(stale_after: Duration) -> Duration {
+    (stale_after / 4).max(Duration::from_millis(100))
+}
+
+/// Touch the lock file every `every` until told to stop.
+fn start_heartbeat(
+    path: PathBuf,
+    holder: String,
+    every: Duration,
+) -> (mpsc::Sender<()>, JoinHandle<()>) {
+    let (stop, stopped) = mpsc::channel::<()>();
+    let handle = std::thread::spawn(move || {
+        while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(every) {
+            if !held_by(&path, &holder) {
+                return;
+            }
+            let touched = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .and_then(|f| f.set_modified(SystemTime::now()));
+            if let Err(e) = touched {
+                log::warn!("Could not refresh cache lock {}: {e}", path.display());
+            }
+        }
+    });
+    (stop, handle)
+}
+
+/// Move the stale lock file at `path` aside and delete it. Renaming is
+/// atomic, so when several runs find the same stale lock only one moves it;
+/// a lock created in the meantime by a run that got there first is put back.
+fn take_over_stale(path: &Path, stale: &str, claimant: u32) {
+    let mut aside = path.as_os_str().to_owned();
+    aside.push(format!(".stale-{}-{claimant}", std::process::id()));
+    let aside = PathBuf::from(aside);
+    if std::fs::rename(path, &aside).is_err() {
+        return;
+    }
+    if !held_by(&aside, stale) {
+        // Not the file judged stale: restore it unless a new lock exists.
+        std::fs::hard_link(&aside, path).ok();
+    }
+    std::fs::remove_file(&aside).ok();
+}
+
+impl CacheLock {
+    /// Lock `cache_file` with the default staleness and poll interval.
+    pub fn acquire(cache_file: &Path) -> Option<CacheLock> {
+        CacheLock::acquire_with(cache_file, STALE_AFTER, POLL_INTERVAL)
+    }
+
+    /// Create the lock file for `cache_file`, waiting while another live run
+    /// holds it. Returns None when locking is not possible; the caller then
+    /// goes ahead unlocked.
+    pub fn acquire_with(
+        cache_file: &Path,
+        stale_after: Duration,
+        poll: Duration,
+    ) -> Option<CacheLock> {
+        let path = lock_path(cache_file);
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).ok();
+        }
+        let claimant = LOCKS_TAKEN.fetch_add(1, Ordering::Relaxed);
+        let holder = format!("pid {} lock {claimant}", std::process::id());
+        let mut waiting = false;
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    // Holder details for whoever finds the lock.
+                    writeln!(file, "{holder}").ok();
+                    let heartbeat =
+                        start_heartbeat(path.clone(), holder.clone(), heartbeat(stale_after));
+                    return Some(CacheLock {
+                        path,
+                        holder,
+                        heartbeat: Some(heartbeat),
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let current = std::fs::read_to_string(&path).unwrap_or_default();
+                    match lock_age(&path) {
+                        Some(age) if age >= stale_after => {
+                            log::warn!(
+                                "Taking over stale cache lock {} ({}, {}s without a heartbeat)",
+                                path.display(),
+                                current.trim(),
+                                age.as_secs()
+                            );
+                            take_over_stale(&path, current.trim(), claimant);
+                        }
+                        _ => {
+                            if !waiting {
+                                log::warn!(
+                                    "Another run ({}) is writing {}; waiting for it",
+                                    current.trim(),
+                                    cache_file.display()
+                                );
+                                waiting = true;
+                            }
+                            std::thread::sleep(poll);
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Could not create cache lock {}: {e}; continuing without it",
+                        path.display()
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for CacheLock {
+    fn drop(&mut self) {
+        if let Some((stop, handle)) = self.heartbeat.take() {
+            drop(stop);
+            handle.join().ok();
+        }
+        // A run that took the lock over as stale now owns the file.
+        if held_by(&self.path, &self.holder) {
+            std::fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_run_waits_for_the_lock_and_stale_locks_are_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("net_cache_subnet.json");
+        let poll = Duration::from_millis(10);
+
+        let first = CacheLock::acquire_with(&cache, STALE_AFTER, poll).unwrap();
+        assert!(lock_path(&cache).exists());
+        let waiter = {
+            let cache = cache.clone();
+            std::thread::spawn(move || {
+                let started = std::time::Instant::now();
+                let lock = CacheLock::acquire_with(&cache, STALE_AFTER, poll);
+                (lock.is_some(), started.elapsed())
+            })
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        drop(first);
+        let (locked, waited) = waiter.join().unwrap();
+        assert!(locked);
+        assert!(waited >= Duration::from_millis(50), "{waited:?}");
+        assert!(!lock_path(&cache).exists(), "released on drop");
+
+        // A leftover lock from a killed run does not block forever.
+        std::fs::write(lock_path(&cache), "pid 1 lock 0\n").unwrap();
+        let lock = CacheLock::acquire_with(&cache, Duration::ZERO, poll).unwrap();
+        assert!(held_by(&lock_path(&cache), &lock.holder));
+        assert_eq!(
+            std::fs::read_dir(dir.path()).unwrap().count(),
+            1,
+            "nothing left aside"
+        );
+    }
+
+    #[test]
+    fn heartbeat_keeps_a_slow_fetch_locked_and_drop_spares_a_successor() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("net_cache_subnet.json");
+        let stale_after = Duration::from_millis(400);
+        let lock = CacheLock::acquire_with(&cache, stale_after, Duration::from_millis(10)).unwrap();
+        // Longer than `stale_after`: the heartbeat keeps the lock fresh.
+        std::thread::sleep(Duration::from_millis(700));
+        assert!(lock_age(&lock_path(&cache)).unwrap() < stale_after);
+
+        // Taken over anyway (say the holder was suspended): dropping the
+        // old lock leaves the new holder's file alone.
+        std::fs::write(lock_path(&cache), "pid 1 lock 9\n").unwrap();
+        drop(lock);
+        assert!(held_by(&lock_path(&cache), "pid 1 lock 9"));
+    }
+}
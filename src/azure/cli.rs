@@ -1,13 +1,43 @@
 //! Azure CLI command execution.
 //!
 //! Provides utilities for running Azure CLI commands and parsing their output.
+//! Commands are run without a shell: arguments are passed as an argv list and
+//! the executable is resolved on `PATH` (honouring `PATHEXT` on Windows, where
+//! the Azure CLI is `az.cmd`). JSON output can be streamed straight from the
+//! child's stdout into serde ([`run_az_json`]), so response size is unbounded.
 
-use colored::Colorize;
+use crate::output::{paint, Style};
 use regex::Regex;
+use serde::de::DeserializeOwned;
 use std::error::Error;
-use std::process::Command;
+use std::ffi::OsStr;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::OnceLock;
 
+/// Set by `--offline`: every Azure CLI invocation fails instead of running.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Forbid (or allow again) Azure CLI invocations for the rest of the run.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether `--offline` forbids Azure CLI invocations.
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fail when `program` is the Azure CLI and the run is offline.
+fn ensure_online(program: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    if program == "az" && is_offline() {
+        return Err(format!("--offline: refusing to run 'az {}'", args.join(" ")).into());
+    }
+    Ok(())
+}
+
 /// Regex for splitting command strings while preserving quoted substrings.
 static COMMAND_REGEX: OnceLock<Regex> = OnceLock::new();
 
@@ -17,61 +47,144 @@ fn get_command_regex() -> &'static Regex {
     })
 }
 
-/// Run a shell command and return its stdout.
+/// Run a command line and return its stdout.
 ///
 /// The command string is split on spaces, with quoted substrings preserved.
+/// Prefer [`run_az`] for Azure CLI calls: it takes an argv list and needs no quoting.
 ///
 /// # Arguments
 /// * `cmd` - The command string to execute
 ///
 /// # Returns
 /// * `Ok(String)` - The stdout output on success
-/// * `Err` - If the command fails or produces too much output
-///
-/// # Panics
-/// * If stdout exceeds 500KB (safety limit)
+/// * `Err` - If the command fails
 pub fn run(cmd: &str) -> Result<String, Box<dyn Error>> {
-    log::debug!("run({cmd})", cmd = cmd.on_blue());
-
     let cmds: Vec<&str> = split_and_strip(cmd);
     log::trace!("split cmds={:?}", cmds);
+    let (program, args) = cmds.split_first().ok_or("Empty command")?;
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    run_program(program, &args)
+}
 
-    // Build command and add args
-    let mut command = Command::new(cmds[0]);
-    for arg in cmds.iter().skip(1) {
-        command.arg(arg);
+/// Run the Azure CLI with `args` (without the leading `az`) and return its stdout.
+pub fn run_az(args: &[String]) -> Result<String, Box<dyn Error>> {
+    run_program("az", args)
+}
+
+/// Run the Azure CLI with `args` and deserialize its JSON stdout as `T`.
+///
+/// Stdout is parsed as it streams from the child process, so large responses
+/// are never buffered as one string. Stderr is collected on a separate thread
+/// and returned in the error if the command fails.
+pub fn run_az_json<T: DeserializeOwned>(args: &[String]) -> Result<T, Box<dyn Error>> {
+    ensure_online("az", args)?;
+    let cmd = format!("az {}", args.join(" "));
+    log::debug!("run_json({cmd})", cmd = paint(&cmd, Style::Command));
+
+    let mut child = Command::new(resolve_program("az"))
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| spawn_error("az", e))?;
+    let stdout = child.stdout.take().ok_or("Child stdout not captured")?;
+    let mut stderr = child.stderr.take().ok_or("Child stderr not captured")?;
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = String::new();
+        stderr.read_to_string(&mut buf).map(|_| buf)
+    });
+
+    let mut de = serde_json::Deserializer::from_reader(BufReader::new(stdout));
+    let parsed: Result<T, _> = serde_path_to_error::deserialize(&mut de);
+    if parsed.is_err() {
+        // Unblock a child still writing to the pipe.
+        let _ = child.kill();
+    }
+    let status = child.wait()?;
+    let stderr = stderr_reader
+        .join()
+        .map_err(|_| "stderr reader panicked")?
+        .unwrap_or_default();
+
+    if !status.success() {
+        log::warn!(
+            "{failed} to run {cmd}",
+            failed = paint("failed", Style::Failed),
+            cmd = paint(&cmd, Style::Command)
+        );
+        return Err(format!("ERROR running: {stderr}").into());
     }
+    parsed.map_err(|e| format!("Error parsing JSON: path={} error={}", e.path(), e).into())
+}
 
-    let output = command.output().map_err(|e| {
-        log::error!("Command execution failed: {e}");
+fn spawn_error(program: &str, e: std::io::Error) -> std::io::Error {
+    log::error!("Command execution failed: {e}");
+    let message = if e.kind() == std::io::ErrorKind::NotFound {
+        format!(
+            "'{program}' not found on PATH — install the Azure CLI (https://aka.ms/installazurecli); \
+             run the `doctor` subcommand to check the setup"
+        )
+    } else {
         format!("Failed to execute command: {e}")
-    })?;
+    };
+    // The kind is kept so callers can tell a missing program from a failure.
+    std::io::Error::new(e.kind(), message)
+}
+
+/// Resolve `name` to an executable on `PATH`, falling back to `name` itself.
+///
+/// On Windows the extensions in `PATHEXT` are tried, so `az` finds `az.cmd`.
+pub fn resolve_program(name: &str) -> PathBuf {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let pathext = if cfg!(windows) {
+        std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+    } else {
+        String::new()
+    };
+    find_program(name, &path, &pathext).unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// Search `path` for `name`, trying each `pathext` extension (`;`-separated).
+///
+/// With extensions given, a bare `name` is only accepted when it already has
+/// an extension — on Windows the Azure CLI ships both a bash `az` script and
+/// `az.cmd`, and only the latter can be executed.
+fn find_program(name: &str, path: &OsStr, pathext: &str) -> Option<PathBuf> {
+    let mut exts: Vec<&str> = pathext.split(';').filter(|e| !e.is_empty()).collect();
+    if exts.is_empty() || std::path::Path::new(name).extension().is_some() {
+        exts.insert(0, "");
+    }
+    std::env::split_paths(path)
+        .flat_map(|dir| exts.iter().map(move |ext| dir.join(format!("{name}{ext}"))))
+        .find(|candidate| candidate.is_file())
+}
+
+fn run_program(program: &str, args: &[String]) -> Result<String, Box<dyn Error>> {
+    ensure_online(program, args)?;
+    let cmd = format!("{program} {}", args.join(" "));
+    log::debug!("run({cmd})", cmd = paint(&cmd, Style::Command));
+
+    let mut command = Command::new(resolve_program(program));
+    command.args(args);
+
+    let output = command.output().map_err(|e| spawn_error(program, e))?;
 
     if output.status.success() {
         log::debug!("Success cmd: {cmd}");
         log::debug!("Success output.stdout.len(): {}", output.stdout.len());
         log::debug!("Success output.status.code(): {:?}", output.status.code());
-
-        if output.stdout.len() > 500_000 {
-            return Err(format!(
-                "Response too large: {} bytes for command: {:?}",
-                output.stdout.len(),
-                cmds
-            )
-            .into());
-        }
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         log::trace!(
             "code={code:?}, status={status}\n┎######\nstderr=\n{stderr}\n┖######",
             code = output.status.code(),
             status = output.status,
-            stderr = stderr.red()
+            stderr = paint(&stderr, Style::Stderr)
         );
         log::warn!(
             "{failed} to run {cmd}",
-            failed = "failed".on_red(),
-            cmd = cmd.on_blue()
+            failed = paint("failed", Style::Failed),
+            cmd = paint(&cmd, Style::Command)
         );
         return Err(format!("ERROR running: {stderr}").into());
     }
@@ -114,6 +227,22 @@ mod tests {
         assert_eq!(split_and_strip(input), expected);
     }
 
+    #[test]
+    fn find_program_prefers_pathext_over_bare_script() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("az"), "#!/bin/bash").unwrap();
+        std::fs::write(dir.path().join("az.cmd"), "@echo off").unwrap();
+        let path = std::env::join_paths([dir.path()]).unwrap();
+
+        let windows = find_program("az", &path, ".exe;.cmd").unwrap();
+        assert_eq!(windows.file_name().unwrap(), "az.cmd");
+
+        let unix = find_program("az", &path, "").unwrap();
+        assert_eq!(unix.file_name().unwrap(), "az");
+
+        assert!(find_program("no-such-program", &path, "").is_none());
+    }
+
     #[test]
     fn test_quoted_url() {
         let input = "curl \"https://mysite.com?\\$filter=name eq 'john' and surname eq 'smith'\"";
@@ -1,14 +1,30 @@
 //! Composite Azure data fetch.
 //!
-//! Provides [`fetch_azure_data`] — a single call that reads all four Azure data
-//! sources (subnets, peering, local-gateways, vWAN) from cache or Azure, logs
-//! their cache status, and returns an [`AzureData`] bundle.
+//! Provides [`fetch_azure_data`] — a single call that reads all Azure data
+//! sources (subnets, peering, local-gateways, vWAN, subscriptions, and optionally AKS) from
+//! cache or Azure, logs their cache status, and returns an [`AzureData`] bundle.
 
 use super::{
-    azure_cache, local_gateway::LocalGatewayData, peering_graph::PeeringData, vwan_graph::VWanData,
-    CacheResult,
+    account::reset_account_pin,
+    aks_graph::AksData,
+    azure_cache::{self, AzureSource},
+    flow_log::FlowLogData,
+    gateway::GatewayData,
+    incremental,
+    ipam_pool::IpamPoolData,
+    local_gateway::LocalGatewayData,
+    nic::NicData,
+    paginate::QueryOptions,
+    peering_graph::PeeringData,
+    public_ip::PublicIpData,
+    subscription_graph::SubscriptionData,
+    vwan_graph::VWanData,
+    CacheResult, STDIN_CACHE,
 };
 use crate::azure::graph::Data;
+use crate::clock::Clock;
+use crate::models::SubnetSource;
+use crate::processing::{log_non_canonical, normalize_cidrs};
 use std::error::Error;
 use std::path::Path;
 
@@ -16,7 +32,7 @@ use std::path::Path;
 ///
 /// All fields default to `None`, which means the standard date-stamped filename
 /// (`net_YYYY-MM-DD_cache_<source>.json`) will be used.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct FetchConfig {
     /// Override path for the subnet cache file.
     pub subnet_cache: Option<String>,
@@ -26,9 +42,43 @@ pub struct FetchConfig {
     pub local_gateway_cache: Option<String>,
     /// Override path for the vWAN cache file.
     pub vwan_cache: Option<String>,
+    /// Override path for the subscription metadata cache file.
+    pub subscription_cache: Option<String>,
+    /// Also load AKS agent pools (optional source).
+    pub aks: bool,
+    /// Override path for the AKS agent pool cache file.
+    pub aks_cache: Option<String>,
+    /// Also load Network Watcher flow logs (optional source).
+    pub flow_logs: bool,
+    /// Override path for the flow log cache file.
+    pub flow_log_cache: Option<String>,
+    /// Also load VPN / ExpressRoute gateways (optional source).
+    pub gateways: bool,
+    /// Override path for the gateway cache file.
+    pub gateway_cache: Option<String>,
+    /// Override path for the public IP cache file.
+    pub public_ip_cache: Option<String>,
+    /// Override path for the IPAM pool cache file.
+    pub ipam_pool_cache: Option<String>,
+    /// Override path for the network interface cache file.
+    pub nic_cache: Option<String>,
+    /// Previous subnet snapshot to refresh incrementally on a cache miss.
+    /// When `None`, a cache miss re-queries every subscription.
+    pub incremental_base: Option<String>,
+    /// Scope applied to Azure queries on a cache miss (e.g. management group).
+    pub query: QueryOptions,
+    /// Write new cache files zstd-compressed (`.json.zst`). Existing files are
+    /// read in either form regardless of this setting.
+    pub compress_cache: bool,
     /// Directory to write / read default cache files.
     /// When `None`, cache files are written to the current directory.
     pub cache_dir: Option<String>,
+    /// Timezone and date format for default cache filenames and timestamps.
+    pub clock: Clock,
+    /// Never query Azure (`--offline`): a missing cache is an error instead
+    /// of a fetch. Sources other than subnets that an exported snapshot
+    /// (`subnet_cache`) comes without are left empty.
+    pub offline: bool,
 }
 
 /// All Azure data fetched in a single call.
@@ -41,48 +91,37 @@ pub struct AzureData {
     pub local_gateways: LocalGatewayData,
     /// Virtual WAN topology data.
     pub vwan: VWanData,
+    /// Subscription metadata (state, tags, management group chain).
+    pub subscriptions: SubscriptionData,
+    /// AKS agent pools; `None` unless [`FetchConfig::aks`] is set.
+    pub aks: Option<AksData>,
+    /// Flow logs; `None` unless [`FetchConfig::flow_logs`] is set.
+    pub flow_logs: Option<FlowLogData>,
+    /// VPN / ExpressRoute gateways; `None` unless [`FetchConfig::gateways`] is set.
+    pub gateways: Option<GatewayData>,
 }
 
 /// Fetch all Azure data sources in one call.
 ///
 /// Reads from cache when available; falls back to Azure CLI queries and writes
 /// new cache files. Cache status is logged at `info` level inside this function
-/// so callers don't need to repeat the log pattern.
+/// so callers don't need to repeat the log pattern. Every call pins the Azure
+/// CLI account context afresh ([`reset_account_pin`]).
 ///
 /// # Errors
 /// Returns the first error encountered if any source fails.
 pub fn fetch_azure_data(config: &FetchConfig) -> Result<AzureData, Box<dyn Error>> {
-    let cache_dir: Option<&Path> = config.cache_dir.as_deref().map(Path::new);
+    reset_account_pin();
 
     // ── Subnets ──────────────────────────────────────────────────────────────
-    let subnet_result = azure_cache::load::<Data>(config.subnet_cache.as_deref(), cache_dir)?;
-    if subnet_result.from_cache {
-        log::info!("Subnet data read from cache '{}'", subnet_result.cache_file);
-    } else {
-        log::info!(
-            "Subnet data fetched from Azure (cache '{}')",
-            subnet_result.cache_file
-        );
-    }
+    let subnet_result = fetch_subnets(config)?;
 
     // ── Peering ───────────────────────────────────────────────────────────────
-    let peering_result =
-        azure_cache::load::<PeeringData>(config.peering_cache.as_deref(), cache_dir)?;
-    if peering_result.from_cache {
-        log::info!(
-            "Peering data read from cache '{}'",
-            peering_result.cache_file
-        );
-    } else {
-        log::info!(
-            "Peering data fetched from Azure (cache '{}')",
-            peering_result.cache_file
-        );
-    }
+    let peering_result = fetch_peering(config)?;
 
     // ── Local Gateways ────────────────────────────────────────────────────────
     let lgw_result =
-        azure_cache::load::<LocalGatewayData>(config.local_gateway_cache.as_deref(), cache_dir)?;
+        load_source::<LocalGatewayData>(config, config.local_gateway_cache.as_deref())?;
     if lgw_result.from_cache {
         log::info!(
             "Local gateway data read from cache '{}'",
@@ -96,7 +135,7 @@ pub fn fetch_azure_data(config: &FetchConfig) -> Result<AzureData, Box<dyn Error
     }
 
     // ── vWAN ──────────────────────────────────────────────────────────────────
-    let vwan_result = azure_cache::load::<VWanData>(config.vwan_cache.as_deref(), cache_dir)?;
+    let vwan_result = load_source::<VWanData>(config, config.vwan_cache.as_deref())?;
     if vwan_result.from_cache {
         log::info!("vWAN data read from cache '{}'", vwan_result.cache_file);
     } else {
@@ -106,14 +145,318 @@ pub fn fetch_azure_data(config: &FetchConfig) -> Result<AzureData, Box<dyn Error
         );
     }
 
+    // ── Subscriptions ─────────────────────────────────────────────────────────
+    let sub_result = fetch_subscriptions(config)?;
+
+    // ── AKS (optional) ────────────────────────────────────────────────────────
+    let aks = if config.aks {
+        let aks_result = load_source::<AksData>(config, config.aks_cache.as_deref())?;
+        if aks_result.from_cache {
+            log::info!("AKS data read from cache '{}'", aks_result.cache_file);
+        } else {
+            log::info!(
+                "AKS data fetched from Azure (cache '{}')",
+                aks_result.cache_file
+            );
+        }
+        Some(aks_result.data)
+    } else {
+        None
+    };
+
+    // ── Flow logs (optional) ──────────────────────────────────────────────────
+    let flow_logs = if config.flow_logs {
+        Some(fetch_flow_logs(config)?.data)
+    } else {
+        None
+    };
+
+    // ── Gateways (optional) ───────────────────────────────────────────────────
+    let gateways = if config.gateways {
+        Some(fetch_gateways(config)?.data)
+    } else {
+        None
+    };
+
     Ok(AzureData {
         subnets: subnet_result,
         peering_edges: peering_result.data,
         local_gateways: lgw_result.data,
         vwan: vwan_result.data,
+        subscriptions: sub_result.data,
+        aks,
+        flow_logs,
+        gateways,
     })
 }
 
+/// Load the non-subnet source `S` for `config`. A snapshot piped in with
+/// `--cache-file -` holds only subnets, so without an explicit cache file
+/// the source is left empty instead of being queried from Azure.
+fn load_source<S: AzureSource + Default>(
+    config: &FetchConfig,
+    cache_file: Option<&str>,
+) -> Result<CacheResult<S>, Box<dyn Error>> {
+    if cache_file.is_none() && config.subnet_cache.as_deref() == Some(STDIN_CACHE) {
+        log::warn!(
+            "No {} data in a snapshot read from stdin; continuing without it",
+            S::file_stem()
+        );
+        return Ok(CacheResult {
+            data: S::default(),
+            from_cache: true,
+            cache_file: STDIN_CACHE.to_string(),
+            generated_at: None,
+        });
+    }
+    if offline_cache_missing::<S>(config, cache_file) {
+        if config.subnet_cache.is_none() {
+            return Err(offline_error::<S>(config));
+        }
+        log::warn!(
+            "--offline: no {} cache for today; continuing without it",
+            S::file_stem()
+        );
+        return Ok(CacheResult {
+            data: S::default(),
+            from_cache: true,
+            cache_file: String::new(),
+            generated_at: None,
+        });
+    }
+    azure_cache::load::<S>(
+        cache_file,
+        config.cache_dir.as_deref().map(Path::new),
+        config.compress_cache,
+        &config.query,
+        &config.clock,
+    )
+}
+
+/// Delete today's default cache files [`fetch_azure_data`] would read with
+/// `config`, so its next call queries Azure again. Sources read from an
+/// override file and caches of other scopes or tenants are left alone.
+pub fn remove_todays_caches(config: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    fn remove<S: AzureSource>(
+        config: &FetchConfig,
+        cache_file: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        if cache_file.is_some() {
+            return Ok(());
+        }
+        azure_cache::remove_todays_cache::<S>(
+            config.cache_dir.as_deref().map(Path::new),
+            &config.query,
+            &config.clock,
+        )
+    }
+    remove::<Data>(config, config.subnet_cache.as_deref())?;
+    remove::<PeeringData>(config, config.peering_cache.as_deref())?;
+    remove::<LocalGatewayData>(config, config.local_gateway_cache.as_deref())?;
+    remove::<VWanData>(config, config.vwan_cache.as_deref())?;
+    remove::<SubscriptionData>(config, config.subscription_cache.as_deref())?;
+    if config.aks {
+        remove::<AksData>(config, config.aks_cache.as_deref())?;
+    }
+    if config.flow_logs {
+        remove::<FlowLogData>(config, config.flow_log_cache.as_deref())?;
+    }
+    if config.gateways {
+        remove::<GatewayData>(config, config.gateway_cache.as_deref())?;
+    }
+    Ok(())
+}
+
+/// Whether `--offline` forbids the fetch a load of `S` would need: no cache
+/// file given and none for today in the cache directory.
+fn offline_cache_missing<S: AzureSource>(config: &FetchConfig, cache_file: Option<&str>) -> bool {
+    config.offline
+        && cache_file.is_none()
+        && !azure_cache::todays_cache_exists::<S>(
+            config.cache_dir.as_deref().map(Path::new),
+            &config.query,
+            &config.clock,
+        )
+}
+
+fn offline_error<S: AzureSource>(config: &FetchConfig) -> Box<dyn Error> {
+    format!(
+        "--offline: no {} cache for {} in '{}' and Azure may not be queried; run once online to create it, or pass --cache-file with an exported snapshot",
+        S::file_stem(),
+        config.clock.date_str(),
+        config.cache_dir.as_deref().unwrap_or(".")
+    )
+    .into()
+}
+
+/// Fetch only the peering source (from cache or Azure), logging its cache status.
+pub fn fetch_peering(config: &FetchConfig) -> Result<CacheResult<PeeringData>, Box<dyn Error>> {
+    let peering_result = load_source::<PeeringData>(config, config.peering_cache.as_deref())?;
+    if peering_result.from_cache {
+        log::info!(
+            "Peering data read from cache '{}'",
+            peering_result.cache_file
+        );
+    } else {
+        log::info!(
+            "Peering data fetched from Azure (cache '{}')",
+            peering_result.cache_file
+        );
+    }
+    Ok(peering_result)
+}
+
+/// Fetch only the subscription source (from cache or Azure), logging its cache status.
+pub fn fetch_subscriptions(
+    config: &FetchConfig,
+) -> Result<CacheResult<SubscriptionData>, Box<dyn Error>> {
+    let result = load_source::<SubscriptionData>(config, config.subscription_cache.as_deref())?;
+    if result.from_cache {
+        log::info!("Subscription data read from cache '{}'", result.cache_file);
+    } else {
+        log::info!(
+            "Subscription data fetched from Azure (cache '{}')",
+            result.cache_file
+        );
+    }
+    Ok(result)
+}
+
+/// Fetch only the gateway source (from cache or Azure), logging its cache status.
+pub fn fetch_gateways(config: &FetchConfig) -> Result<CacheResult<GatewayData>, Box<dyn Error>> {
+    let result = load_source::<GatewayData>(config, config.gateway_cache.as_deref())?;
+    if result.from_cache {
+        log::info!("Gateway data read from cache '{}'", result.cache_file);
+    } else {
+        log::info!(
+            "Gateway data fetched from Azure (cache '{}')",
+            result.cache_file
+        );
+    }
+    Ok(result)
+}
+
+/// Fetch only the flow log source (from cache or Azure), logging its cache status.
+pub fn fetch_flow_logs(config: &FetchConfig) -> Result<CacheResult<FlowLogData>, Box<dyn Error>> {
+    let result = load_source::<FlowLogData>(config, config.flow_log_cache.as_deref())?;
+    if result.from_cache {
+        log::info!("Flow log data read from cache '{}'", result.cache_file);
+    } else {
+        log::info!(
+            "Flow log data fetched from Azure (cache '{}')",
+            result.cache_file
+        );
+    }
+    Ok(result)
+}
+
+/// Fetch only the subnet source (from cache or Azure), logging its cache status.
+///
+/// Used by subcommands that need subnets but not the other sources.
+pub fn fetch_subnets(config: &FetchConfig) -> Result<CacheResult<Data>, Box<dyn Error>> {
+    if offline_cache_missing::<Data>(config, config.subnet_cache.as_deref()) {
+        return Err(offline_error::<Data>(config));
+    }
+    let cache_dir: Option<&Path> = config.cache_dir.as_deref().map(Path::new);
+    let mut subnet_result = match config.incremental_base.as_deref() {
+        Some(base) => azure_cache::load_with::<Data, _>(
+            config.subnet_cache.as_deref(),
+            cache_dir,
+            config.compress_cache,
+            &config.query,
+            &config.clock,
+            || incremental::fetch_incremental(Path::new(base), &config.query, &config.clock),
+        )?,
+        None => azure_cache::load::<Data>(
+            config.subnet_cache.as_deref(),
+            cache_dir,
+            config.compress_cache,
+            &config.query,
+            &config.clock,
+        )?,
+    };
+    // Override cache files (e.g. --demo) are not keyed by the sample size.
+    if let Some(n) = config.query.sample {
+        let data = &mut subnet_result.data;
+        if data.data.len() > n as usize {
+            data.data.truncate(n as usize);
+            data.count = n as i32;
+            data.total_records = Some(n);
+        }
+        log::warn!("--sample {n}: the report covers only the first {n} subnet record(s)");
+    }
+    // Gap and overlap math needs network addresses; every command reads
+    // subnets through here, so prefixes with host bits set are fixed once.
+    let (data, non_canonical) = normalize_cidrs(std::mem::take(&mut subnet_result.data));
+    log_non_canonical(&non_canonical);
+    subnet_result.data = data;
+    // Records carry their own fetch time; older caches only have the file's.
+    if let Some(ref at) = subnet_result.generated_at {
+        for s in subnet_result.data.data.iter_mut() {
+            if s.fetched_at.is_none() && s.source == SubnetSource::Azure {
+                s.fetched_at = Some(at.clone());
+            }
+        }
+    }
+    if subnet_result.from_cache {
+        log::info!("Subnet data read from cache '{}'", subnet_result.cache_file);
+    } else {
+        log::info!(
+            "Subnet data fetched from Azure (cache '{}')",
+            subnet_result.cache_file
+        );
+    }
+    Ok(subnet_result)
+}
+
+/// Fetch only the public IP source (from cache or Azure), logging its cache status.
+///
+/// Used by the `public-ips` subcommand; the subnet report does not need it.
+pub fn fetch_public_ips(config: &FetchConfig) -> Result<CacheResult<PublicIpData>, Box<dyn Error>> {
+    let result = load_source::<PublicIpData>(config, config.public_ip_cache.as_deref())?;
+    if result.from_cache {
+        log::info!("Public IP data read from cache '{}'", result.cache_file);
+    } else {
+        log::info!(
+            "Public IP data fetched from Azure (cache '{}')",
+            result.cache_file
+        );
+    }
+    Ok(result)
+}
+
+/// Fetch only the IPAM pool source (from cache or Azure), logging its cache status.
+///
+/// Used by the `ipam-pools` subcommand; the subnet report does not need it.
+pub fn fetch_ipam_pools(config: &FetchConfig) -> Result<CacheResult<IpamPoolData>, Box<dyn Error>> {
+    let result = load_source::<IpamPoolData>(config, config.ipam_pool_cache.as_deref())?;
+    if result.from_cache {
+        log::info!("IPAM pool data read from cache '{}'", result.cache_file);
+    } else {
+        log::info!(
+            "IPAM pool data fetched from Azure (cache '{}')",
+            result.cache_file
+        );
+    }
+    Ok(result)
+}
+
+/// Fetch only the network interface source (from cache or Azure), logging its cache status.
+///
+/// Used by `enrich` to resolve NIC resource IDs; the subnet report does not need it.
+pub fn fetch_nics(config: &FetchConfig) -> Result<CacheResult<NicData>, Box<dyn Error>> {
+    let result = load_source::<NicData>(config, config.nic_cache.as_deref())?;
+    if result.from_cache {
+        log::info!("NIC data read from cache '{}'", result.cache_file);
+    } else {
+        log::info!(
+            "NIC data fetched from Azure (cache '{}')",
+            result.cache_file
+        );
+    }
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,12 +469,15 @@ mod tests {
                 "src/tests/test_data/local_gateway_test_cache_01.json".to_string(),
             ),
             vwan_cache: Some("src/tests/test_data/vwan_test_cache_01.json".to_string()),
+            subscription_cache: Some(
+                "src/tests/test_data/subscription_test_cache_01.json".to_string(),
+            ),
             ..FetchConfig::default()
         }
     }
 
     #[test]
-    fn fetch_azure_data_returns_all_four_sources() {
+    fn fetch_azure_data_returns_all_sources() {
         let data = fetch_azure_data(&test_config()).expect("fetch failed");
         // Subnets from subnet_test_cache_01.json
         assert!(
@@ -149,6 +495,42 @@ mod tests {
         assert_eq!(data.local_gateways.data[0].vnet_name, "test-hub-vnet");
         // vWAN: empty fixture
         assert_eq!(data.vwan.data.len(), 0, "expected 0 vWAN rows");
+        // Subscriptions from subscription_test_cache_01.json (2 entries)
+        assert_eq!(data.subscriptions.data.len(), 2, "expected 2 subscriptions");
+        // AKS is opt-in
+        assert!(data.aks.is_none(), "AKS must not load unless requested");
+    }
+
+    #[test]
+    fn fetch_azure_data_loads_aks_when_requested() {
+        let config = FetchConfig {
+            aks: true,
+            aks_cache: Some("src/tests/test_data/aks_test_cache_01.json".to_string()),
+            ..test_config()
+        };
+        let data = fetch_azure_data(&config).expect("fetch failed");
+        let aks = data.aks.expect("AKS data requested");
+        assert_eq!(aks.data.len(), 2, "expected 2 agent pools");
+        assert_eq!(aks.data[0].cluster_name, "aks-logs");
+    }
+
+    #[test]
+    fn fetch_azure_data_loads_flow_logs_when_requested() {
+        assert!(fetch_azure_data(&test_config())
+            .unwrap()
+            .flow_logs
+            .is_none());
+        let config = FetchConfig {
+            flow_logs: true,
+            flow_log_cache: Some("src/tests/test_data/flow_log_test_cache_01.json".to_string()),
+            ..test_config()
+        };
+        let flow_logs = fetch_azure_data(&config)
+            .unwrap()
+            .flow_logs
+            .expect("requested");
+        assert_eq!(flow_logs.data.len(), 1);
+        assert!(flow_logs.data[0].is_enabled() && !flow_logs.data[0].has_traffic_analytics());
     }
 
     #[test]
@@ -160,6 +542,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fetch_subnets_clears_host_bits() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("subnets.json");
+        let data = Data {
+            data: vec![crate::models::Subnet {
+                vnet_cidr: crate::models::Ipv4::new("10.1.0.7/16").unwrap(),
+                ..crate::models::Subnet::test("hub", "app", "10.1.2.5/24")
+            }],
+            ..Default::default()
+        };
+        std::fs::write(&file, serde_json::to_string(&data).unwrap()).unwrap();
+        let config = FetchConfig {
+            subnet_cache: Some(file.to_string_lossy().into_owned()),
+            ..FetchConfig::default()
+        };
+        let subnets = fetch_subnets(&config).unwrap().data;
+        assert_eq!(
+            subnets.data[0].subnet_cidr.unwrap().to_string(),
+            "10.1.2.0/24"
+        );
+        assert_eq!(subnets.data[0].vnet_cidr.to_string(), "10.1.0.0/16");
+    }
+
     #[test]
     fn fetch_azure_data_fails_when_subnet_cache_missing() {
         let config = FetchConfig {
@@ -169,4 +575,64 @@ mod tests {
         let result = fetch_azure_data(&config);
         assert!(result.is_err(), "should fail when subnet cache is missing");
     }
+
+    #[test]
+    fn offline_fails_on_missing_cache_but_reads_an_exported_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let offline = FetchConfig {
+            cache_dir: Some(dir.path().to_string_lossy().into_owned()),
+            offline: true,
+            ..FetchConfig::default()
+        };
+        let err = fetch_azure_data(&offline)
+            .err()
+            .expect("no cache, no fetch");
+        assert!(
+            err.to_string()
+                .starts_with("--offline: no subnet cache for "),
+            "{err}"
+        );
+
+        // A subnet snapshot alone: the other sources come up empty.
+        let snapshot = FetchConfig {
+            subnet_cache: Some("src/tests/test_data/subnet_test_cache_01.json".to_string()),
+            ..offline
+        };
+        let data = fetch_azure_data(&snapshot).expect("snapshot");
+        assert!(!data.subnets.data.data.is_empty());
+        assert!(data.peering_edges.data.is_empty());
+        assert!(data.subscriptions.data.is_empty());
+    }
+
+    #[test]
+    fn offline_reads_the_cache_an_online_run_keyed_by_tenant() {
+        let dir = tempfile::tempdir().unwrap();
+        let snapshot: Data = azure_cache::read_cache_file(Path::new(
+            "src/tests/test_data/subnet_test_cache_01.json",
+        ))
+        .unwrap();
+        let online = QueryOptions {
+            tenant: Some("tenant-a".to_string()),
+            ..QueryOptions::default()
+        };
+        let count = snapshot.data.len();
+        azure_cache::load_with::<Data, _>(
+            None,
+            Some(dir.path()),
+            false,
+            &online,
+            &Clock::default(),
+            || Ok(snapshot),
+        )
+        .unwrap();
+
+        let offline = FetchConfig {
+            cache_dir: Some(dir.path().to_string_lossy().into_owned()),
+            offline: true,
+            ..FetchConfig::default()
+        };
+        let hit = fetch_subnets(&offline).expect("today's tenant-keyed cache");
+        assert!(hit.from_cache);
+        assert_eq!(hit.data.data.len(), count);
+    }
 }
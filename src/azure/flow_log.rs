@@ -0,0 +1,81 @@
+//! Azure Resource Graph query for Network Watcher flow logs.
+//!
+//! Queries `microsoft.network/networkwatchers/flowlogs` — one row per flow
+//! log — with its target (NSG, VNet or subnet) and whether it and Traffic
+//! Analytics are enabled, for the flow log coverage audit.
+
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Duration;
+
+/// KQL: one row per flow log.
+pub(super) const FLOW_LOG_QUERY: &str = r#"resources
+    | where type == "microsoft.network/networkwatchers/flowlogs"
+    | project subscription_id = subscriptionId
+             ,name
+             ,target_resource_id = tostring(properties.targetResourceId)
+             ,enabled = tobool(properties.enabled)
+             ,traffic_analytics = tobool(properties.flowAnalyticsConfiguration.networkWatcherFlowAnalyticsConfiguration.enabled)
+             ,storage_id = tostring(properties.storageId)
+    | sort by name asc"#;
+
+/// One row from the flow log query.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct FlowLogRow {
+    pub subscription_id: String,
+    pub name: String,
+    /// NSG, VNet or subnet the flow log records.
+    pub target_resource_id: String,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Traffic Analytics processing of the flow log.
+    #[serde(default)]
+    pub traffic_analytics: Option<bool>,
+    #[serde(default)]
+    pub storage_id: String,
+}
+
+impl FlowLogRow {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    pub fn has_traffic_analytics(&self) -> bool {
+        self.is_enabled() && self.traffic_analytics.unwrap_or(false)
+    }
+}
+
+/// Response wrapper for the flow log query.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct FlowLogData {
+    pub data: Vec<FlowLogRow>,
+    pub skip_token: Option<String>,
+    pub total_records: Option<u32>,
+    pub count: i32,
+}
+
+/// Execute the Azure Resource Graph flow log query with automatic pagination.
+pub fn run_flow_log_graph(options: &QueryOptions) -> Result<FlowLogData, Box<dyn Error>> {
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(FLOW_LOG_QUERY, options, sleep, run_graph_page)?;
+
+    let data: Vec<FlowLogRow> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing flow log rows: {e}"))?;
+
+    let count = data.len() as i32;
+    let total_records = Some(data.len() as u32);
+
+    log::info!("Got {count} flow log rows from az graph query");
+
+    Ok(FlowLogData {
+        data,
+        skip_token: None,
+        total_records,
+        count,
+    })
+}
@@ -0,0 +1,18 @@
+//! Cache management for flow log data.
+
+use super::azure_cache::AzureSource;
+use super::flow_log::{run_flow_log_graph, FlowLogData, FLOW_LOG_QUERY};
+use super::paginate::QueryOptions;
+use std::error::Error;
+
+impl AzureSource for FlowLogData {
+    fn file_stem() -> &'static str {
+        "flow-log"
+    }
+    fn query() -> &'static str {
+        FLOW_LOG_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_flow_log_graph(options)
+    }
+}
@@ -0,0 +1,100 @@
+//! Azure Resource Graph query for VPN and ExpressRoute gateways.
+//!
+//! Queries `microsoft.network/virtualnetworkgateways` — one row per gateway —
+//! with its type, SKU, the VNet whose `GatewaySubnet` it sits in, and the
+//! point-to-site client address pool, so client VPN ranges can be checked
+//! against VNet address space.
+
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Duration;
+
+/// KQL: one row per virtual network gateway.
+pub(super) const GATEWAY_QUERY: &str = r#"resources
+    | where type == "microsoft.network/virtualnetworkgateways"
+    | extend subnet_id = tostring(properties.ipConfigurations[0].properties.subnet.id)
+    | project subscription_id = subscriptionId
+             ,resource_group = resourceGroup
+             ,name
+             ,location
+             ,gateway_type = tostring(properties.gatewayType)
+             ,vpn_type = tostring(properties.vpnType)
+             ,sku = tostring(properties.sku.name)
+             ,vnet_id = substring(subnet_id, 0, indexof(subnet_id, "/subnets/"))
+             ,p2s_address_pool = properties.vpnClientConfiguration.vpnClientAddressPool.addressPrefixes
+    | sort by name asc"#;
+
+/// One row from the gateway query.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct GatewayRow {
+    pub subscription_id: String,
+    #[serde(default)]
+    pub resource_group: String,
+    pub name: String,
+    #[serde(default)]
+    pub location: String,
+    /// `Vpn` or `ExpressRoute`.
+    #[serde(default)]
+    pub gateway_type: String,
+    /// `RouteBased` or `PolicyBased` (VPN gateways only).
+    #[serde(default)]
+    pub vpn_type: String,
+    /// Gateway SKU, e.g. `VpnGw2AZ` or `ErGw1AZ`.
+    #[serde(default)]
+    pub sku: String,
+    /// ID of the VNet whose `GatewaySubnet` hosts the gateway.
+    #[serde(default)]
+    pub vnet_id: String,
+    /// Point-to-site client address pool (empty without P2S).
+    #[serde(default, deserialize_with = "null_as_empty_vec")]
+    pub p2s_address_pool: Vec<String>,
+}
+
+fn null_as_empty_vec<'de, D>(de: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<Vec<String>>::deserialize(de).map(|v| v.unwrap_or_default())
+}
+
+impl GatewayRow {
+    /// Name of the gateway VNet (last segment of [`GatewayRow::vnet_id`]).
+    pub fn vnet_name(&self) -> &str {
+        self.vnet_id.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+/// Response wrapper for the gateway query.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GatewayData {
+    pub data: Vec<GatewayRow>,
+    pub skip_token: Option<String>,
+    pub total_records: Option<u32>,
+    pub count: i32,
+}
+
+/// Execute the Azure Resource Graph gateway query with automatic pagination.
+pub fn run_gateway_graph(options: &QueryOptions) -> Result<GatewayData, Box<dyn Error>> {
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(GATEWAY_QUERY, options, sleep, run_graph_page)?;
+
+    let data: Vec<GatewayRow> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing gateway rows: {e}"))?;
+
+    let count = data.len() as i32;
+    let total_records = Some(data.len() as u32);
+
+    log::info!("Got {count} gateway rows from az graph query");
+
+    Ok(GatewayData {
+        data,
+        skip_token: None,
+        total_records,
+        count,
+    })
+}
@@ -0,0 +1,18 @@
+//! Cache management for VPN / ExpressRoute gateway data.
+
+use super::azure_cache::AzureSource;
+use super::gateway::{run_gateway_graph, GatewayData, GATEWAY_QUERY};
+use super::paginate::QueryOptions;
+use std::error::Error;
+
+impl AzureSource for GatewayData {
+    fn file_stem() -> &'static str {
+        "gateway"
+    }
+    fn query() -> &'static str {
+        GATEWAY_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_gateway_graph(options)
+    }
+}
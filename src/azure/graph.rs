@@ -2,7 +2,11 @@
 //!
 //! Handles querying Azure Resource Graph for subnet information.
 
-use super::{cli, paginate::paginate};
+use super::{
+    account::run_graph_page,
+    paginate::{paginate_pages, QueryOptions},
+    schema::parse_subnet_pages,
+};
 use crate::config;
 use crate::models::Subnet;
 use serde::{Deserialize, Serialize};
@@ -10,24 +14,33 @@ use std::error::Error;
 use std::time::Duration;
 
 /// Azure Graph query for fetching subnet data.
-const SUBNET_QUERY: &str = r#"resources 
+pub(super) const SUBNET_QUERY: &str = r#"resources 
         | where type == "microsoft.network/virtualnetworks"
         | mv-expand properties.subnets 
         | project subscription_id=subscriptionId
+                ,resource_group=resourceGroup
+                ,vnet_id=id
                 ,vnet_name=name
                 ,vnet_cidr=properties.addressSpace.addressPrefixes
+                ,subnet_id=properties_subnets.id
                 ,subnet_name=properties_subnets.name
                 ,subnet_cidr=properties_subnets.properties.addressPrefix
                 ,nsg=properties_subnets.properties.networkSecurityGroup.id
+                ,delegations=properties_subnets.properties.delegations
+                ,nat_gateway=properties_subnets.properties.natGateway.id
+                ,load_balancers=extract_all(@"/loadBalancers/([^/]+)/", tostring(properties_subnets.properties.ipConfigurations))
                 ,location=location
                 ,dns_servers=properties.dhcpOptions.dnsServers
+                ,ddos_protection_plan=iff(tobool(properties.enableDdosProtection), tostring(properties.ddosProtectionPlan.id), "")
+                ,encryption=iff(tobool(properties.encryption.enabled), tostring(properties.encryption.enforcement), "")
                 ,ip_configurations_count=array_length(properties_subnets.properties.ipConfigurations)
+                ,service_association_links=array_length(properties_subnets.properties.serviceAssociationLinks)
         | join kind=leftouter (
             resourcecontainers
                 | where type == "microsoft.resources/subscriptions"
                 | project subscription_id=subscriptionId, subscription_name=name
             ) on subscription_id
-        | project subscription_id, subscription_name, vnet_name, vnet_cidr, subnet_name, subnet_cidr, nsg, location, dns_servers, ip_configurations_count
+        | project subscription_id, subscription_name, resource_group, vnet_id, vnet_name, vnet_cidr, subnet_id, subnet_name, subnet_cidr, nsg, delegations, nat_gateway, load_balancers, location, dns_servers, ddos_protection_plan, encryption, ip_configurations_count, service_association_links
         | sort by vnet_name asc"#;
 
 /// Response data from Azure Graph query.
@@ -45,17 +58,17 @@ pub struct Data {
 
 /// Execute Azure Resource Graph query to fetch all subnets.
 ///
-/// Handles pagination automatically using skip tokens.
+/// Handles pagination automatically using skip tokens. Each subnet records
+/// the page (`query_block`) it was returned in.
 ///
 /// # Returns
 /// * `Ok(Data)` - All subnet data from Azure
 /// * `Err` - If the query fails
-pub fn run_az_cli_graph() -> Result<Data, Box<dyn Error>> {
+pub fn run_az_cli_graph(options: &QueryOptions) -> Result<Data, Box<dyn Error>> {
     let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
-    let rows = paginate(SUBNET_QUERY, sleep, cli::run)?;
+    let pages = paginate_pages(SUBNET_QUERY, options, sleep, run_graph_page)?;
 
-    let data: Vec<Subnet> = serde_json::from_value(serde_json::Value::Array(rows))
-        .map_err(|e| format!("Error parsing subnet rows: {e}"))?;
+    let data = parse_subnet_pages(pages, options.strict_schema)?;
 
     let count = data.len() as i32;
     let total_records = Some(data.len() as u32);
@@ -0,0 +1,234 @@
+//! Resource Graph quota tracking and pacing.
+//!
+//! Resource Graph throttles each user to [`QUOTA_LIMIT`] queries per
+//! [`QUOTA_WINDOW`] across the tenant; every page is a query. `az graph
+//! query` does not pass on the `x-ms-user-quota-remaining` response header,
+//! so the quota is estimated from the pages this run sent: before each page
+//! [`pace_graph_query`] waits when the window's budget (the limit less
+//! [`QUOTA_RESERVE`] for the portal and other tools of the same user) is
+//! spent, and a throttled page marks the window as used up. The counts end
+//! up in the run summary via [`graph_quota_status`]; a long-running process
+//! starts each run's counts with [`reset_graph_quota`].
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Queries per user and [`QUOTA_WINDOW`] Resource Graph allows.
+pub const QUOTA_LIMIT: usize = 15;
+
+/// Length of the Resource Graph quota window.
+pub const QUOTA_WINDOW: Duration = Duration::from_secs(5);
+
+/// Queries of each window left to whatever else runs as the same user.
+const QUOTA_RESERVE: usize = 2;
+
+/// Query times of the current window plus the run's totals.
+#[derive(Debug)]
+struct QuotaTracker {
+    /// Start times of recent queries, oldest first; may lie in the future
+    /// for pages that are waiting their turn.
+    recent: VecDeque<Instant>,
+    queries: usize,
+    throttled: usize,
+    paced: Duration,
+    min_remaining: Option<usize>,
+}
+
+static QUOTA: Mutex<QuotaTracker> = Mutex::new(QuotaTracker::new());
+
+impl QuotaTracker {
+    const fn new() -> QuotaTracker {
+        QuotaTracker {
+            recent: VecDeque::new(),
+            queries: 0,
+            throttled: 0,
+            paced: Duration::ZERO,
+            min_remaining: None,
+        }
+    }
+
+    /// Book a query wanted at `now`; returns how long to wait before sending it.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        while self
+            .recent
+            .front()
+            .is_some_and(|t| *t + QUOTA_WINDOW <= now)
+        {
+            self.recent.pop_front();
+        }
+        let budget = QUOTA_LIMIT - QUOTA_RESERVE;
+        let mut slot = now;
+        if self.recent.len() >= budget {
+            slot = slot.max(self.recent[self.recent.len() - budget] + QUOTA_WINDOW);
+        }
+        if let Some(last) = self.recent.back() {
+            slot = slot.max(*last);
+        }
+        let in_window = self
+            .recent
+            .iter()
+            .filter(|t| **t + QUOTA_WINDOW > slot)
+            .count();
+        let remaining = QUOTA_LIMIT.saturating_sub(in_window + 1);
+        self.min_remaining = Some(self.min_remaining.map_or(remaining, |m| m.min(remaining)));
+        self.recent.push_back(slot);
+        self.queries += 1;
+        let wait = slot - now;
+        self.paced += wait;
+        wait
+    }
+
+    /// Resource Graph throttled a query sent around `now`: treat the whole
+    /// window as used.
+    fn throttled(&mut self, now: Instant) {
+        self.throttled += 1;
+        self.min_remaining = Some(0);
+        while self.recent.len() < QUOTA_LIMIT {
+            self.recent.push_back(now);
+        }
+    }
+
+    /// Zero the run's totals, keeping the window.
+    fn new_run(&mut self) {
+        let recent = std::mem::take(&mut self.recent);
+        *self = QuotaTracker {
+            recent,
+            ..QuotaTracker::new()
+        };
+    }
+
+    fn status(&self) -> GraphQuotaStatus {
+        GraphQuotaStatus {
+            queries: self.queries,
+            min_remaining: self.min_remaining.unwrap_or(QUOTA_LIMIT),
+            throttled: self.throttled,
+            paced: self.paced,
+        }
+    }
+}
+
+/// Resource Graph usage of this run, for the run summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphQuotaStatus {
+    /// Query pages sent.
+    pub queries: usize,
+    /// Lowest estimated quota left in a window during the run.
+    pub min_remaining: usize,
+    /// Pages Resource Graph refused with a throttling error.
+    pub throttled: usize,
+    /// Total time spent waiting for quota.
+    pub paced: Duration,
+}
+
+impl fmt::Display for GraphQuotaStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Resource Graph: {} query page(s), lowest remaining quota {} of {} per {}s (estimated)",
+            self.queries,
+            self.min_remaining,
+            QUOTA_LIMIT,
+            QUOTA_WINDOW.as_secs()
+        )?;
+        if !self.paced.is_zero() {
+            write!(f, ", paced {:.1}s", self.paced.as_secs_f64())?;
+        }
+        if self.throttled > 0 {
+            write!(f, ", {} throttled", self.throttled)?;
+        }
+        Ok(())
+    }
+}
+
+/// Wait, if needed, until the quota allows another Resource Graph query,
+/// and count it.
+pub fn pace_graph_query() {
+    let wait = QUOTA
+        .lock()
+        .map(|mut q| q.reserve(Instant::now()))
+        .unwrap_or_default();
+    if !wait.is_zero() {
+        log::info!(
+            "Resource Graph quota nearly used; pacing the next query by {:.1}s",
+            wait.as_secs_f64()
+        );
+        std::thread::sleep(wait);
+    }
+}
+
+/// Returns `true` when an `az graph query` error is Resource Graph throttling.
+pub fn is_throttled(err: &str) -> bool {
+    let err = err.to_lowercase();
+    err.contains("ratelimiting") || err.contains("too many requests") || err.contains("throttl")
+}
+
+/// Record a throttled query, so the next one waits for a fresh window.
+pub fn record_throttled() {
+    if let Ok(mut q) = QUOTA.lock() {
+        q.throttled(Instant::now());
+    }
+}
+
+/// Start the counts of a new run. Query times of the current window are
+/// kept, since Resource Graph still counts them.
+pub fn reset_graph_quota() {
+    if let Ok(mut q) = QUOTA.lock() {
+        q.new_run();
+    }
+}
+
+/// Resource Graph usage of this run so far.
+pub fn graph_quota_status() -> GraphQuotaStatus {
+    QUOTA
+        .lock()
+        .map(|q| q.status())
+        .unwrap_or(QuotaTracker::new().status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_are_paced_once_the_window_budget_is_spent() {
+        let mut q = QuotaTracker::new();
+        let start = Instant::now();
+        let budget = QUOTA_LIMIT - QUOTA_RESERVE;
+        for _ in 0..budget {
+            assert_eq!(q.reserve(start), Duration::ZERO);
+        }
+        assert_eq!(q.status().min_remaining, QUOTA_RESERVE);
+        // The next page waits for the first one to leave the window.
+        assert_eq!(q.reserve(start), QUOTA_WINDOW);
+        // Later in the window the wait is shorter.
+        let later = start + Duration::from_secs(2);
+        assert_eq!(q.reserve(later), Duration::from_secs(3));
+        // After a quiet window there is no wait.
+        assert_eq!(q.reserve(start + 3 * QUOTA_WINDOW), Duration::ZERO);
+
+        let now = start + 4 * QUOTA_WINDOW;
+        q.throttled(now);
+        assert_eq!(q.reserve(now), QUOTA_WINDOW);
+        let status = q.status();
+        assert_eq!(
+            (status.queries, status.throttled, status.min_remaining),
+            (17, 1, 0)
+        );
+        assert_eq!(status.paced, Duration::from_secs(13));
+        assert_eq!(
+            status.to_string(),
+            "Resource Graph: 17 query page(s), lowest remaining quota 0 of 15 per 5s (estimated), paced 13.0s, 1 throttled"
+        );
+        assert!(is_throttled(
+            "ERROR: (RateLimiting) Please provide below info"
+        ));
+        assert!(!is_throttled("ERROR: (BadRequest) Query is invalid"));
+
+        // The next run starts its counts afresh but still waits for the window.
+        q.new_run();
+        assert_eq!(q.status().queries, 0);
+        assert!(q.reserve(now) > Duration::ZERO);
+    }
+}
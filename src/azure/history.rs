@@ -0,0 +1,113 @@
+//! Previous subnet snapshots kept in the dated report directories.
+//!
+//! Every run writes its subnet cache to `report-<date>/cache/`, so the report
+//! directories double as a daily history. Only snapshots written for the same
+//! query and scope (matching [`cache_key`](super::azure_cache::cache_key)) are
+//! listed.
+
+use super::{azure_cache, graph::Data, paginate::QueryOptions};
+use crate::clock::Clock;
+use crate::processing::normalize_cidrs;
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+/// One dated subnet snapshot on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub date: NaiveDate,
+    pub path: PathBuf,
+}
+
+/// Date of a `net_<date>_cache_subnet_<key>.json[.zst]` file name, if it
+/// names a subnet snapshot with cache key `key` dated in `clock`'s format.
+fn snapshot_date(file_name: &str, key: &str, clock: &Clock) -> Option<NaiveDate> {
+    let suffix = format!("_cache_subnet_{key}.json");
+    let rest = file_name.strip_prefix("net_")?;
+    let date = rest
+        .strip_suffix(&suffix)
+        .or_else(|| rest.strip_suffix(&format!("{suffix}.zst")))?;
+    clock.parse_date(date)
+}
+
+/// List subnet snapshots under `root` (`report-*/cache/`) for the query scope
+/// in `options`, oldest first, one per date.
+pub fn list_snapshots(root: &Path, options: &QueryOptions, clock: &Clock) -> Vec<Snapshot> {
+    let key = azure_cache::cache_key::<Data>(options);
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<Snapshot> = entries
+        .filter_map(Result::ok)
+        .filter(|e| e.file_name().to_string_lossy().starts_with("report-"))
+        .filter_map(|e| std::fs::read_dir(e.path().join("cache")).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|e| {
+            let date = snapshot_date(&e.file_name().to_string_lossy(), &key, clock)?;
+            Some(Snapshot {
+                date,
+                path: e.path(),
+            })
+        })
+        .collect();
+    snapshots.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.path.cmp(&b.path)));
+    snapshots.dedup_by_key(|s| s.date);
+    snapshots
+}
+
+/// Read `snapshots` newest first, skipping (and logging) unreadable files.
+///
+/// Files are only read as the iterator is advanced.
+pub fn read_snapshots_newest_first(
+    snapshots: &[Snapshot],
+) -> impl Iterator<Item = (NaiveDate, Data)> + '_ {
+    snapshots
+        .iter()
+        .rev()
+        .filter_map(|s| match azure_cache::read_cache_file::<Data>(&s.path) {
+            Ok(data) => Some((s.date, normalize_cidrs(data).0)),
+            Err(e) => {
+                log::warn!("Skipping snapshot '{}': {e}", s.path.display());
+                None
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_are_listed_by_date_for_the_same_key_only() {
+        let root = tempfile::tempdir().unwrap();
+        let options = QueryOptions::default();
+        let key = azure_cache::cache_key::<Data>(&options);
+        for (date, ext) in [("2026-10-14", "json"), ("2026-10-12", "json.zst")] {
+            let cache = root.path().join(format!("report-{date}/cache"));
+            std::fs::create_dir_all(&cache).unwrap();
+            std::fs::write(
+                cache.join(format!("net_{date}_cache_subnet_{key}.{ext}")),
+                "{}",
+            )
+            .unwrap();
+            std::fs::write(
+                cache.join(format!("net_{date}_cache_vwan_{key}.json")),
+                "{}",
+            )
+            .unwrap();
+        }
+        let other = root.path().join("report-2026-10-13/cache");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(
+            other.join("net_2026-10-13_cache_subnet_ffffffff.json"),
+            "{}",
+        )
+        .unwrap();
+
+        let dates: Vec<String> = list_snapshots(root.path(), &options, &Clock::default())
+            .iter()
+            .map(|s| s.date.to_string())
+            .collect();
+        assert_eq!(dates, ["2026-10-12", "2026-10-14"]);
+    }
+}
@@ -0,0 +1,286 @@
+//! Incremental subnet refresh.
+//!
+//! Instead of re-querying every subscription, asks Resource Graph's
+//! `resourcechanges` table which subscriptions had VNet, subnet or NIC changes
+//! since the previous snapshot was written, re-queries only those
+//! subscriptions, and merges the fresh rows into the previous snapshot.
+//!
+//! NIC changes keep `ip_configurations_count` current, but only for NICs in
+//! the subscription of their subnet's VNet; a NIC attached across
+//! subscriptions is picked up by the next full fetch.
+
+use super::{
+    account::run_graph_page,
+    azure_cache,
+    graph::{run_az_cli_graph, Data},
+    history,
+    paginate::{paginate, QueryOptions},
+};
+use crate::clock::Clock;
+use crate::config;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Resource Graph keeps change history for 14 days; older snapshots need a full fetch.
+const CHANGE_HISTORY_DAYS: i64 = 14;
+
+/// Build the KQL listing subscriptions with VNet, subnet or NIC changes after `since`.
+fn changed_subscriptions_query(since: DateTime<Utc>) -> String {
+    format!(
+        r#"resourcechanges
+    | extend change_time = todatetime(properties.changeAttributes.timestamp)
+            ,target_type = tolower(tostring(properties.targetResourceType))
+    | where change_time > datetime({since})
+    | where target_type in ("microsoft.network/virtualnetworks", "microsoft.network/virtualnetworks/subnets", "microsoft.network/networkinterfaces")
+    | distinct subscription_id = subscriptionId"#,
+        since = since.format("%Y-%m-%dT%H:%M:%SZ")
+    )
+}
+
+#[derive(Deserialize)]
+struct ChangedSubscription {
+    subscription_id: String,
+}
+
+/// Return the IDs of subscriptions with network changes since `since`.
+pub fn changed_subscriptions(
+    since: DateTime<Utc>,
+    options: &QueryOptions,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(
+        &changed_subscriptions_query(since),
+        options,
+        sleep,
+        run_graph_page,
+    )?;
+    let changed: Vec<ChangedSubscription> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing resource change rows: {e}"))?;
+    let mut ids: Vec<String> = changed.into_iter().map(|c| c.subscription_id).collect();
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// Replace every row of the `changed` subscriptions in `base` with the rows in `fresh`.
+///
+/// Rows of unchanged subscriptions are kept as-is. The result is sorted by
+/// VNet name, matching the order of a full subnet query.
+pub fn merge_subscriptions(base: Data, fresh: Data, changed: &[String]) -> Data {
+    let changed: HashSet<&str> = changed.iter().map(String::as_str).collect();
+    let mut data: Vec<_> = base
+        .data
+        .into_iter()
+        .filter(|s| !changed.contains(s.subscription_id.as_str()))
+        .chain(fresh.data)
+        .collect();
+    data.sort_by(|a, b| a.vnet_name.cmp(&b.vnet_name));
+
+    let count = data.len() as i32;
+    Data {
+        data,
+        skip_token: None,
+        total_records: Some(count as u32),
+        count,
+    }
+}
+
+/// When the snapshot in `base_file` was fetched: `generated_at` from its cache
+/// metadata, or the file's modification time for files written before that
+/// was recorded. A copied or touched file keeps its fetch time.
+fn snapshot_time(base_file: &Path) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let generated_at = azure_cache::read_cache_meta(base_file)?
+        .and_then(|meta| meta.generated_at)
+        .and_then(|t| DateTime::parse_from_rfc3339(&t).ok());
+    if let Some(t) = generated_at {
+        return Ok(t.to_utc());
+    }
+    log::info!(
+        "Snapshot '{}' records no fetch time — using its modification time",
+        base_file.display()
+    );
+    let modified = std::fs::metadata(base_file)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Cannot read timestamp of {}: {e}", base_file.display()))?;
+    Ok(modified.into())
+}
+
+/// Refresh the subnet snapshot in `base_file` with only the subscriptions that changed.
+///
+/// The snapshot's fetch time is used as the change-detection start. When the
+/// snapshot is older than the Resource Graph change history at `clock`'s
+/// current time, a full fetch is done instead.
+pub fn fetch_incremental(
+    base_file: &Path,
+    options: &QueryOptions,
+    clock: &Clock,
+) -> Result<Data, Box<dyn Error>> {
+    let since = snapshot_time(base_file)?;
+
+    if clock.now().to_utc() - since > chrono::Duration::days(CHANGE_HISTORY_DAYS) {
+        log::warn!(
+            "Snapshot '{}' is older than {CHANGE_HISTORY_DAYS} days of change history — doing a full fetch",
+            base_file.display()
+        );
+        return run_az_cli_graph(options);
+    }
+
+    let base: Data = azure_cache::read_cache_file(base_file)?;
+    let changed = changed_subscriptions(since, options)?;
+    if changed.is_empty() {
+        log::info!(
+            "No network changes since {since} — reusing {} subnets from '{}'",
+            base.data.len(),
+            base_file.display()
+        );
+        return Ok(base);
+    }
+
+    log::info!(
+        "{} subscription(s) changed since {since} — re-querying only those",
+        changed.len()
+    );
+    let scoped = QueryOptions {
+        subscriptions: changed.clone(),
+        ..options.clone()
+    };
+    let fresh = run_az_cli_graph(&scoped)?;
+    Ok(merge_subscriptions(base, fresh, &changed))
+}
+
+/// Find the most recent subnet snapshot under `root`, ignoring files in `exclude_dir`.
+///
+/// Looks for `report-*/cache/net_<date>_cache_subnet_<key>.json` (or `.json.zst`)
+/// written for the same query and scope as `options`, with dates in `clock`'s
+/// format; the newest date wins.
+pub fn latest_snapshot(
+    root: &Path,
+    exclude_dir: &Path,
+    options: &QueryOptions,
+    clock: &Clock,
+) -> Option<PathBuf> {
+    let exclude_dir = exclude_dir.canonicalize().ok();
+    history::list_snapshots(root, options, clock)
+        .into_iter()
+        .rev()
+        .map(|s| s.path)
+        .find(|p| p.parent().and_then(|d| d.canonicalize().ok()) != exclude_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    fn make_subnet(vnet_name: &str, subscription_id: &str) -> Subnet {
+        Subnet {
+            vnet_name: vnet_name.to_string(),
+            subscription_id: subscription_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn make_data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            count: subnets.len() as i32,
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_replaces_only_changed_subscriptions() {
+        let base = make_data(vec![
+            make_subnet("a-vnet", "sub-1"),
+            make_subnet("b-vnet", "sub-2"),
+            make_subnet("old-vnet", "sub-2"),
+        ]);
+        let fresh = make_data(vec![make_subnet("c-vnet", "sub-2")]);
+
+        let merged = merge_subscriptions(base, fresh, &["sub-2".to_string()]);
+
+        let names: Vec<&str> = merged.data.iter().map(|s| s.vnet_name.as_str()).collect();
+        assert_eq!(names, vec!["a-vnet", "c-vnet"]);
+        assert_eq!(merged.count, 2);
+    }
+
+    #[test]
+    fn merge_drops_rows_of_changed_subscription_with_no_fresh_rows() {
+        // A subscription whose last VNet was deleted has changes but no rows.
+        let base = make_data(vec![make_subnet("gone-vnet", "sub-1")]);
+        let merged = merge_subscriptions(base, Data::default(), &["sub-1".to_string()]);
+        assert!(merged.data.is_empty());
+    }
+
+    #[test]
+    fn changed_subscriptions_query_filters_on_timestamp_and_network_types() {
+        let since = DateTime::parse_from_rfc3339("2026-10-14T06:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let q = changed_subscriptions_query(since);
+        assert!(q.contains("datetime(2026-10-14T06:00:00Z)"));
+        assert!(q.contains("microsoft.network/virtualnetworks"));
+        assert!(q.contains("microsoft.network/networkinterfaces"));
+        assert!(
+            !q.contains('\''),
+            "query is wrapped in single quotes by paginate"
+        );
+    }
+
+    #[test]
+    fn snapshot_time_prefers_generated_at_over_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let with_meta = dir.path().join("with_meta.json");
+        std::fs::write(
+            &with_meta,
+            r#"{"data":[],"count":0,"cache_meta":{"key":"k","query":"q","tenant":null,
+                "management_group":null,"generated_at":"2026-10-14T06:00:00+13:00"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            snapshot_time(&with_meta).unwrap().to_rfc3339(),
+            "2026-10-13T17:00:00+00:00"
+        );
+
+        let old = dir.path().join("old.json");
+        std::fs::write(&old, r#"{"data":[],"count":0}"#).unwrap();
+        let age = Clock::default().now().to_utc() - snapshot_time(&old).unwrap();
+        assert!(age < chrono::Duration::minutes(1));
+    }
+
+    #[test]
+    fn latest_snapshot_picks_newest_previous_day() {
+        let root = tempfile::tempdir().unwrap();
+        let options = QueryOptions::default();
+        let key = azure_cache::cache_key::<Data>(&options);
+        for (date, ext) in [
+            ("2026-10-12", "json"),
+            ("2026-10-13", "json.zst"),
+            ("2026-10-14", "json"),
+        ] {
+            let cache = root.path().join(format!("report-{date}/cache"));
+            std::fs::create_dir_all(&cache).unwrap();
+            std::fs::write(
+                cache.join(format!("net_{date}_cache_subnet_{key}.{ext}")),
+                "{}",
+            )
+            .unwrap();
+        }
+        // Newer, but written for another query scope.
+        let other = root.path().join("report-2026-10-13/cache");
+        std::fs::write(
+            other.join("net_2026-10-13_cache_subnet_ffffffff.json"),
+            "{}",
+        )
+        .unwrap();
+        let today = root.path().join("report-2026-10-14/cache");
+
+        let found =
+            latest_snapshot(root.path(), &today, &options, &Clock::default()).expect("snapshot");
+        assert!(found.ends_with(format!("net_2026-10-13_cache_subnet_{key}.json.zst")));
+    }
+}
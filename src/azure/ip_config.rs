@@ -0,0 +1,163 @@
+//! Targeted Resource Graph query for the IP configurations in one subnet.
+//!
+//! Starts from the subnet's own `ipConfigurations` list — the same list
+//! `ip_configurations_count` counts — and joins the NICs, so every consumer
+//! appears: VM and private endpoint NICs with their private IP, and load
+//! balancer frontends, application gateways, VM scale set instances or
+//! gateways identified from the IP configuration ID. Not cached; `subnet
+//! show` runs it on demand.
+
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Duration;
+
+/// KQL for the IP configurations of the subnet with lower-case ID `{id}`.
+const IP_CONFIG_QUERY: &str = r#"resources
+    | where type == "microsoft.network/virtualnetworks"
+    | mv-expand subnet = properties.subnets
+    | where tolower(tostring(subnet.id)) == "{id}"
+    | mv-expand ipconfig = subnet.properties.ipConfigurations
+    | project ipconfig_id = tolower(tostring(ipconfig.id)), ip_configuration_id = tostring(ipconfig.id)
+    | where isnotempty(ipconfig_id)
+    | join kind=leftouter (
+        resources
+        | where type == "microsoft.network/networkinterfaces"
+        | mv-expand ipconfig = properties.ipConfigurations
+        | where tolower(tostring(ipconfig.properties.subnet.id)) == "{id}"
+        | project ipconfig_id = tolower(tostring(ipconfig.id))
+                 ,nic_id = id
+                 ,private_ip = tostring(ipconfig.properties.privateIPAddress)
+                 ,vm_id = tostring(properties.virtualMachine.id)
+                 ,private_endpoint_id = tostring(properties.privateEndpoint.id)
+    ) on ipconfig_id
+    | project ip_configuration_id, nic_id, private_ip, vm_id, private_endpoint_id
+    | sort by private_ip asc, ip_configuration_id asc"#;
+
+/// One IP configuration in the subnet.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct IpConfigRow {
+    pub ip_configuration_id: String,
+    /// NIC the configuration belongs to; empty for other resource types.
+    #[serde(default)]
+    pub nic_id: String,
+    /// Private IP of a NIC configuration; empty for other resource types.
+    #[serde(default)]
+    pub private_ip: String,
+    /// VM the NIC is attached to.
+    #[serde(default)]
+    pub vm_id: String,
+    /// Private endpoint the NIC belongs to.
+    #[serde(default)]
+    pub private_endpoint_id: String,
+}
+
+/// Resource type and name of segment pair `providers/<ns>/<type>/<name>`.
+fn owner_from_id(id: &str) -> Option<(String, String)> {
+    let parts: Vec<&str> = id.split('/').collect();
+    let providers = parts
+        .iter()
+        .position(|p| p.eq_ignore_ascii_case("providers"))?;
+    let kind = parts.get(providers + 2)?;
+    let name = parts.get(providers + 3)?;
+    Some((kind.to_string(), name.to_string()))
+}
+
+impl IpConfigRow {
+    /// What uses the address: `(resource type, name)`, e.g.
+    /// `("virtualMachines", "vm-app-01")` or `("loadBalancers", "lb-int")`.
+    pub fn owner(&self) -> (String, String) {
+        [
+            &self.vm_id,
+            &self.private_endpoint_id,
+            &self.ip_configuration_id,
+        ]
+        .into_iter()
+        .filter(|id| !id.is_empty())
+        .find_map(|id| owner_from_id(id))
+        .unwrap_or_default()
+    }
+
+    /// Name of the NIC, if the configuration belongs to one.
+    pub fn nic_name(&self) -> &str {
+        self.nic_id.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+/// Query the IP configurations of the subnet `subnet_id`, scoped to its
+/// subscription.
+pub fn run_ip_config_graph(
+    subnet_id: &str,
+    subscription_id: &str,
+    options: &QueryOptions,
+) -> Result<Vec<IpConfigRow>, Box<dyn Error>> {
+    if subnet_id.is_empty() || subnet_id.contains(['"', '\\']) {
+        return Err(format!("Invalid subnet resource ID '{subnet_id}'").into());
+    }
+    let query = IP_CONFIG_QUERY.replace("{id}", &subnet_id.to_lowercase());
+    let options = QueryOptions {
+        management_group: None,
+        subscriptions: vec![subscription_id.to_string()],
+        sample: None,
+        ..options.clone()
+    };
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(&query, &options, sleep, run_graph_page)?;
+    let rows: Vec<IpConfigRow> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing IP configuration rows: {e}"))?;
+    log::info!("Got {} IP configurations in '{subnet_id}'", rows.len());
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owner_prefers_the_vm_then_the_endpoint_then_the_config_id() {
+        let sub = "/subscriptions/s/resourceGroups/rg/providers";
+        let vm_nic = IpConfigRow {
+            ip_configuration_id: format!(
+                "{sub}/Microsoft.Network/networkInterfaces/nic-1/ipConfigurations/ipconfig1"
+            ),
+            nic_id: format!("{sub}/Microsoft.Network/networkInterfaces/nic-1"),
+            private_ip: "10.0.0.4".to_string(),
+            vm_id: format!("{sub}/Microsoft.Compute/virtualMachines/vm-app-01"),
+            ..Default::default()
+        };
+        assert_eq!(
+            vm_nic.owner(),
+            ("virtualMachines".to_string(), "vm-app-01".to_string())
+        );
+        assert_eq!(vm_nic.nic_name(), "nic-1");
+
+        let lb = IpConfigRow {
+            ip_configuration_id: format!(
+                "{sub}/Microsoft.Network/loadBalancers/lb-int/frontendIPConfigurations/fe"
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            lb.owner(),
+            ("loadBalancers".to_string(), "lb-int".to_string())
+        );
+        assert_eq!(lb.nic_name(), "");
+
+        let detached = IpConfigRow {
+            ip_configuration_id: format!(
+                "{sub}/Microsoft.Network/networkInterfaces/nic-2/ipConfigurations/ipconfig1"
+            ),
+            nic_id: format!("{sub}/Microsoft.Network/networkInterfaces/nic-2"),
+            ..Default::default()
+        };
+        assert_eq!(
+            detached.owner(),
+            ("networkInterfaces".to_string(), "nic-2".to_string())
+        );
+        assert!(run_ip_config_graph("x\" or 1==1", "s", &QueryOptions::default()).is_err());
+    }
+}
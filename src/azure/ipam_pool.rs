@@ -0,0 +1,133 @@
+//! Azure Resource Graph query for Azure Virtual Network Manager IPAM pools.
+//!
+//! Queries `microsoft.network/networkmanagers/ipampools` — one row per pool —
+//! with the allocations VNets record against it
+//! (`addressSpace.ipamPoolPrefixAllocations`), for reconciling the pool
+//! records with the VNets that actually exist.
+
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Duration;
+
+/// KQL: one row per IPAM pool with the VNet allocations pointing at it.
+pub(super) const IPAM_POOL_QUERY: &str = r#"resources
+    | where type == "microsoft.network/networkmanagers/ipampools"
+    | project pool_id = tolower(id)
+             ,id
+             ,subscription_id = subscriptionId
+             ,resource_group = resourceGroup
+             ,network_manager = tostring(split(id, "/")[8])
+             ,name
+             ,location
+             ,address_prefixes = properties.addressPrefixes
+             ,parent_pool_name = tostring(properties.parentPoolName)
+    | join kind=leftouter (
+        resources
+        | where type == "microsoft.network/virtualnetworks"
+        | mv-expand alloc = properties.addressSpace.ipamPoolPrefixAllocations
+        | where isnotempty(alloc.pool.id)
+        | project pool_id = tolower(tostring(alloc.pool.id))
+                 ,allocation = pack("vnet_id", id
+                                   ,"vnet_name", name
+                                   ,"allocated_prefixes", alloc.allocatedAddressPrefixes
+                                   ,"number_of_ip_addresses", tostring(alloc.numberOfIpAddresses))
+        | summarize allocations = make_list(allocation) by pool_id
+    ) on pool_id
+    | join kind=leftouter (
+        resourcecontainers
+        | where type == "microsoft.resources/subscriptions"
+        | project subscription_id = subscriptionId, subscription_name = name
+    ) on subscription_id
+    | project subscription_id
+             ,subscription_name
+             ,resource_group
+             ,network_manager
+             ,name
+             ,id
+             ,location
+             ,address_prefixes
+             ,parent_pool_name
+             ,allocations = coalesce(allocations, dynamic([]))
+    | sort by network_manager asc, name asc"#;
+
+/// Address space a VNet has recorded as allocated from a pool.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct IpamAllocation {
+    pub vnet_id: String,
+    #[serde(default)]
+    pub vnet_name: String,
+    /// Prefixes the pool handed out (IPv4 or IPv6).
+    #[serde(default)]
+    pub allocated_prefixes: Vec<String>,
+    /// Requested size when allocated by address count; empty otherwise.
+    #[serde(default)]
+    pub number_of_ip_addresses: String,
+}
+
+/// One row from the IPAM pool query.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct IpamPoolRow {
+    pub subscription_id: String,
+    #[serde(default)]
+    pub subscription_name: String,
+    #[serde(default)]
+    pub resource_group: String,
+    /// Azure Virtual Network Manager the pool belongs to.
+    #[serde(default)]
+    pub network_manager: String,
+    pub name: String,
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub location: String,
+    /// Address prefixes of the pool (IPv4 or IPv6).
+    #[serde(default)]
+    pub address_prefixes: Vec<String>,
+    /// Name of the parent pool; empty for a root pool.
+    #[serde(default)]
+    pub parent_pool_name: String,
+    #[serde(default)]
+    pub allocations: Vec<IpamAllocation>,
+}
+
+impl IpamPoolRow {
+    /// `<network manager>/<pool>`, unique within the query scope.
+    pub fn display_name(&self) -> String {
+        format!("{}/{}", self.network_manager, self.name)
+    }
+}
+
+/// Response wrapper for the IPAM pool query.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct IpamPoolData {
+    pub data: Vec<IpamPoolRow>,
+    pub skip_token: Option<String>,
+    pub total_records: Option<u32>,
+    pub count: i32,
+}
+
+/// Execute the Azure Resource Graph IPAM pool query with automatic pagination.
+pub fn run_ipam_pool_graph(options: &QueryOptions) -> Result<IpamPoolData, Box<dyn Error>> {
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(IPAM_POOL_QUERY, options, sleep, run_graph_page)?;
+
+    let data: Vec<IpamPoolRow> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing IPAM pool rows: {e}"))?;
+
+    let count = data.len() as i32;
+    let total_records = Some(data.len() as u32);
+
+    log::info!("Got {count} IPAM pool rows from az graph query");
+
+    Ok(IpamPoolData {
+        data,
+        skip_token: None,
+        total_records,
+        count,
+    })
+}
@@ -0,0 +1,18 @@
+//! Cache management for IPAM pool data.
+
+use super::azure_cache::AzureSource;
+use super::ipam_pool::{run_ipam_pool_graph, IpamPoolData, IPAM_POOL_QUERY};
+use super::paginate::QueryOptions;
+use std::error::Error;
+
+impl AzureSource for IpamPoolData {
+    fn file_stem() -> &'static str {
+        "ipam-pool"
+    }
+    fn query() -> &'static str {
+        IPAM_POOL_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_ipam_pool_graph(options)
+    }
+}
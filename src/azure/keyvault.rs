@@ -0,0 +1,145 @@
+//! Azure Key Vault secret references (`keyvault://<vault>/<secret>`).
+//!
+//! Secrets needed by integrations (the `publish` tokens) are named in the
+//! config file by reference, never by value. They are read at run time with
+//! `az keyvault secret show` as the signed-in Azure identity (user, service
+//! principal or managed identity), so tokens never live in config files.
+
+use super::cli::run_az;
+use serde::{Deserialize, Deserializer};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// URI scheme of a secret reference.
+pub const KEYVAULT_SCHEME: &str = "keyvault://";
+
+/// A Key Vault secret, `keyvault://<vault>/<secret>[/<version>]`; without a
+/// version the current one is read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRef {
+    pub vault: String,
+    pub secret: String,
+    pub version: Option<String>,
+}
+
+/// Vault names are 3-24 characters and secret names 1-127, both letters,
+/// digits and hyphens.
+fn valid_name(name: &str, max: usize) -> bool {
+    !name.is_empty()
+        && name.len() <= max
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+impl FromStr for SecretRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            format!("'{s}' is not a Key Vault reference ({KEYVAULT_SCHEME}<vault>/<secret>); secrets are not accepted as plain values")
+        };
+        let path = s.strip_prefix(KEYVAULT_SCHEME).ok_or_else(err)?;
+        let mut parts = path.split('/');
+        let (Some(vault), Some(secret)) = (parts.next(), parts.next()) else {
+            return Err(err());
+        };
+        let version = parts.next().map(str::to_string);
+        if parts.next().is_some()
+            || vault.len() < 3
+            || !valid_name(vault, 24)
+            || !valid_name(secret, 127)
+            || version.as_deref().is_some_and(|v| !valid_name(v, 64))
+        {
+            return Err(err());
+        }
+        Ok(SecretRef {
+            vault: vault.to_string(),
+            secret: secret.to_string(),
+            version,
+        })
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{KEYVAULT_SCHEME}{}/{}", self.vault, self.secret)?;
+        if let Some(ref version) = self.version {
+            write!(f, "/{version}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl SecretRef {
+    /// `az` arguments printing the secret value.
+    fn show_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "keyvault",
+            "secret",
+            "show",
+            "--vault-name",
+            &self.vault,
+            "--name",
+            &self.secret,
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        if let Some(ref version) = self.version {
+            args.extend(["--version".to_string(), version.clone()]);
+        }
+        args.extend(["--query", "value", "-o", "tsv"].map(String::from));
+        args
+    }
+
+    /// Read the secret value as the signed-in Azure identity.
+    pub fn resolve(&self) -> Result<String, Box<dyn Error>> {
+        log::info!("Reading secret {self}");
+        let value = run_az(&self.show_args())
+            .map_err(|e| format!("Error reading Key Vault secret {self}: {e}"))?;
+        let value = value.trim_end_matches(['\r', '\n']);
+        if value.is_empty() {
+            return Err(format!("Key Vault secret {self} is empty").into());
+        }
+        Ok(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_are_parsed_and_literal_secrets_rejected() {
+        let r: SecretRef = "keyvault://kv-network/github-token".parse().unwrap();
+        assert_eq!(r.vault, "kv-network");
+        assert_eq!(r.secret, "github-token");
+        assert_eq!(r.to_string(), "keyvault://kv-network/github-token");
+        assert_eq!(
+            r.show_args().join(" "),
+            "keyvault secret show --vault-name kv-network --name github-token --query value -o tsv"
+        );
+        let pinned: SecretRef = "keyvault://kv-network/pat/0123abcd".parse().unwrap();
+        assert_eq!(pinned.version.as_deref(), Some("0123abcd"));
+        assert!(pinned.show_args().contains(&"--version".to_string()));
+
+        for bad in [
+            "ghp_0123456789abcdef",
+            "keyvault://kv",
+            "keyvault://kv/secret",
+            "keyvault://kv-network/",
+            "keyvault://kv-network/a/b/c",
+            "keyvault://kv_network/secret",
+        ] {
+            assert!(bad.parse::<SecretRef>().is_err(), "{bad}");
+        }
+    }
+}
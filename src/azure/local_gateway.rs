@@ -3,7 +3,10 @@
 //! Fetches site-to-site VPN connections and resolves which VNet each
 //! Local Network Gateway (on-premises CIDR block) is associated with.
 
-use super::{cli, paginate::paginate};
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
 use crate::config;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -16,7 +19,7 @@ use std::time::Duration;
 ///
 /// Only IPsec (site-to-site VPN) connections are included — those are the only
 /// connection type that has a `localNetworkGateway2` reference.
-const LOCAL_GATEWAY_QUERY: &str = r#"resources
+pub(super) const LOCAL_GATEWAY_QUERY: &str = r#"resources
     | where type == "microsoft.network/connections"
     | where isnotnull(properties.localNetworkGateway2)
     | project
@@ -99,9 +102,9 @@ pub struct LocalGatewayData {
 }
 
 /// Execute the Azure Resource Graph local gateway query with automatic pagination.
-pub fn run_local_gateway_graph() -> Result<LocalGatewayData, Box<dyn Error>> {
+pub fn run_local_gateway_graph(options: &QueryOptions) -> Result<LocalGatewayData, Box<dyn Error>> {
     let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
-    let rows = paginate(LOCAL_GATEWAY_QUERY, sleep, cli::run)?;
+    let rows = paginate(LOCAL_GATEWAY_QUERY, options, sleep, run_graph_page)?;
 
     let data: Vec<LocalGatewayRow> = serde_json::from_value(serde_json::Value::Array(rows))
         .map_err(|e| format!("Error parsing local gateway rows: {e}"))?;
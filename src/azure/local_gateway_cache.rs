@@ -1,15 +1,20 @@
 //! Cache management for Local Network Gateway data.
 
 use super::azure_cache::{self, AzureSource, CacheResult};
-use super::local_gateway::{run_local_gateway_graph, LocalGatewayData};
+use super::local_gateway::{run_local_gateway_graph, LocalGatewayData, LOCAL_GATEWAY_QUERY};
+use super::paginate::QueryOptions;
+use crate::clock::Clock;
 use std::error::Error;
 
 impl AzureSource for LocalGatewayData {
     fn file_stem() -> &'static str {
         "local-gateway"
     }
-    fn fetch() -> Result<Self, Box<dyn Error>> {
-        run_local_gateway_graph()
+    fn query() -> &'static str {
+        LOCAL_GATEWAY_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_local_gateway_graph(options)
     }
 }
 
@@ -17,7 +22,13 @@ impl AzureSource for LocalGatewayData {
 pub fn read_local_gateway_cache_with_status(
     cache_file: Option<&str>,
 ) -> Result<CacheResult<LocalGatewayData>, Box<dyn Error>> {
-    azure_cache::load(cache_file, None)
+    azure_cache::load(
+        cache_file,
+        None,
+        false,
+        &QueryOptions::default(),
+        &Clock::default(),
+    )
 }
 
 /// Read local gateway data from cache, or fetch from Azure if not cached.
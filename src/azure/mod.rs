@@ -5,28 +5,77 @@
 //! - [`cache`] - Caching of subnet data
 //! - [`graph`] - Azure Resource Graph queries
 
+mod account;
+mod activity_log;
+mod aks_cache;
+mod aks_graph;
 mod azure_cache;
 mod cache;
+mod cache_lock;
 mod cli;
 mod fetch;
+mod flow_log;
+mod flow_log_cache;
+mod gateway;
+mod gateway_cache;
 mod graph;
+mod graph_quota;
+mod history;
+mod incremental;
+mod ip_config;
+mod ipam_pool;
+mod ipam_pool_cache;
+mod keyvault;
 mod local_gateway;
 mod local_gateway_cache;
+mod nic;
+mod nic_cache;
 mod paginate;
 mod peering_cache;
 mod peering_graph;
+mod public_ip;
+mod public_ip_cache;
+mod schema;
+mod subscription_cache;
+mod subscription_graph;
 mod vwan_cache;
 mod vwan_graph;
 
-pub use fetch::{fetch_azure_data, AzureData, FetchConfig};
+pub use fetch::{
+    fetch_azure_data, fetch_flow_logs, fetch_gateways, fetch_ipam_pools, fetch_nics, fetch_peering,
+    fetch_public_ips, fetch_subnets, fetch_subscriptions, remove_todays_caches, AzureData,
+    FetchConfig,
+};
 
-pub use azure_cache::CacheResult;
+pub use account::{access_token, check_account, pinned_account, reset_account_pin, AccountContext};
+pub use activity_log::{activity_log_writes, ACTIVITY_LOG_RETENTION_DAYS};
+pub use aks_graph::{AksData, AksPoolRow};
+pub use azure_cache::{
+    cache_key, parse_cache_json, read_cache_file, read_cache_meta, CacheMeta, CacheResult,
+    STDIN_CACHE,
+};
 pub use cache::{read_subnet_cache, read_subnet_cache_with_status};
-pub use cli::run;
+pub use cli::{is_offline, resolve_program, run, run_az, run_az_json, set_offline};
+pub use flow_log::{FlowLogData, FlowLogRow};
+pub use gateway::{GatewayData, GatewayRow};
 pub use graph::{run_az_cli_graph, Data};
+pub use graph_quota::{graph_quota_status, reset_graph_quota, GraphQuotaStatus};
+pub use history::{list_snapshots, read_snapshots_newest_first, Snapshot};
+pub use incremental::{
+    changed_subscriptions, fetch_incremental, latest_snapshot, merge_subscriptions,
+};
+pub use ip_config::{run_ip_config_graph, IpConfigRow};
+pub use ipam_pool::{IpamAllocation, IpamPoolData, IpamPoolRow};
+pub use keyvault::{SecretRef, KEYVAULT_SCHEME};
 pub use local_gateway::{LocalGatewayData, LocalGatewayRow};
 pub use local_gateway_cache::{read_local_gateway_cache, read_local_gateway_cache_with_status};
+pub use nic::{NicData, NicRow};
+pub use paginate::{QueryOptions, DEFAULT_PAGE_SIZE};
 pub use peering_cache::{read_peering_cache, read_peering_cache_with_status};
 pub use peering_graph::{PeeringData, PeeringEdge};
+pub use public_ip::{PublicIpAssociation, PublicIpData, PublicIpRow};
+pub use schema::{parse_subnet_pages, SchemaProblem};
+pub use subscription_cache::{read_subscription_cache, read_subscription_cache_with_status};
+pub use subscription_graph::SubscriptionData;
 pub use vwan_cache::{read_vwan_cache, read_vwan_cache_with_status};
 pub use vwan_graph::{VWanData, VWanRow};
@@ -0,0 +1,67 @@
+//! Azure Resource Graph query for network interfaces.
+//!
+//! Queries `microsoft.network/networkinterfaces` — one row per IP
+//! configuration — with its private IP and subnet, so NIC resource IDs from an
+//! inventory can be resolved to subnets (`enrich`).
+
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Duration;
+
+/// KQL: one row per NIC IP configuration.
+pub(super) const NIC_QUERY: &str = r#"resources
+    | where type == "microsoft.network/networkinterfaces"
+    | mv-expand ipconfig = properties.ipConfigurations
+    | project nic_id = id
+             ,vm_id = tostring(properties.virtualMachine.id)
+             ,private_ip = tostring(ipconfig.properties.privateIPAddress)
+             ,subnet_id = tostring(ipconfig.properties.subnet.id)
+    | sort by nic_id asc"#;
+
+/// One row from the NIC query.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct NicRow {
+    pub nic_id: String,
+    /// Attached VM; empty for a detached NIC or a private endpoint.
+    #[serde(default)]
+    pub vm_id: String,
+    #[serde(default)]
+    pub private_ip: String,
+    #[serde(default)]
+    pub subnet_id: String,
+}
+
+/// Response wrapper for the NIC query.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct NicData {
+    pub data: Vec<NicRow>,
+    pub skip_token: Option<String>,
+    pub total_records: Option<u32>,
+    pub count: i32,
+}
+
+/// Execute the Azure Resource Graph NIC query with automatic pagination.
+pub fn run_nic_graph(options: &QueryOptions) -> Result<NicData, Box<dyn Error>> {
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(NIC_QUERY, options, sleep, run_graph_page)?;
+
+    let data: Vec<NicRow> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing NIC rows: {e}"))?;
+
+    let count = data.len() as i32;
+    let total_records = Some(data.len() as u32);
+
+    log::info!("Got {count} NIC rows from az graph query");
+
+    Ok(NicData {
+        data,
+        skip_token: None,
+        total_records,
+        count,
+    })
+}
@@ -0,0 +1,18 @@
+//! Cache management for network interface data.
+
+use super::azure_cache::AzureSource;
+use super::nic::{run_nic_graph, NicData, NIC_QUERY};
+use super::paginate::QueryOptions;
+use std::error::Error;
+
+impl AzureSource for NicData {
+    fn file_stem() -> &'static str {
+        "nic"
+    }
+    fn query() -> &'static str {
+        NIC_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_nic_graph(options)
+    }
+}
@@ -3,47 +3,167 @@
 //! Drives the skip-token pagination loop common to all Azure Graph query modules.
 
 use std::error::Error;
+use std::io::Write;
 use std::time::Duration;
 
 use serde::Deserialize;
 use serde_json::Value;
 
+/// Records per page requested by default — the Resource Graph maximum.
+pub const DEFAULT_PAGE_SIZE: u32 = 1000;
+
+/// Smallest page size the automatic fallback shrinks to.
+const MIN_PAGE_SIZE: u32 = 10;
+
+/// Scope and options applied to every `az graph query` invocation.
+#[derive(Debug, Clone)]
+pub struct QueryOptions {
+    /// Tenant ID the signed-in account queries. Not passed to `az` (which uses
+    /// the signed-in tenant); it keys cache files so tenants never share one.
+    pub tenant: Option<String>,
+    /// Restrict the query to a management group (`--management-groups <id>`).
+    /// When `None` the query covers every subscription the signed-in account can see.
+    pub management_group: Option<String>,
+    /// Restrict the query to these subscription IDs (`--subscriptions <id>...`).
+    /// Empty means no subscription restriction.
+    pub subscriptions: Vec<String>,
+    /// Records requested per page (`--first`). Halved automatically when a
+    /// page fails because the response is too large.
+    pub page_size: u32,
+    /// Fail the subnet fetch on rows missing required fields instead of
+    /// warning (`--strict-schema`). Unparseable rows always fail it.
+    pub strict_schema: bool,
+    /// Stop after this many records (`--sample`) for a quick smoke run.
+    /// `None` fetches every page.
+    pub sample: Option<u32>,
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        QueryOptions {
+            tenant: None,
+            management_group: None,
+            subscriptions: Vec::new(),
+            page_size: DEFAULT_PAGE_SIZE,
+            strict_schema: false,
+            sample: None,
+        }
+    }
+}
+
+/// Returns `true` when an `az graph query` error means the page was too big.
+fn is_response_too_large(err: &str) -> bool {
+    let err = err.to_lowercase();
+    err.contains("responsepayloadtoolarge")
+        || err.contains("response payload size")
+        || err.contains("response too large")
+}
+
+impl QueryOptions {
+    /// Extra `az graph query` arguments that scope the query (may be empty).
+    fn scope_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(mg) = &self.management_group {
+            args.push("--management-groups".to_string());
+            args.push(mg.clone());
+        }
+        if !self.subscriptions.is_empty() {
+            args.push("--subscriptions".to_string());
+            args.extend(self.subscriptions.iter().cloned());
+        }
+        args
+    }
+}
+
 /// Minimal page envelope — only the fields pagination needs.
 #[derive(Deserialize)]
-struct PageEnvelope {
-    data: Vec<Value>,
-    skip_token: Option<String>,
+pub(crate) struct PageEnvelope {
+    pub data: Vec<Value>,
+    pub skip_token: Option<String>,
 }
 
 /// Execute a paginated Azure Resource Graph query and return all rows.
 ///
-/// `runner` is called once per page with the full `az` CLI command string and
-/// must return the raw JSON output.  In production pass [`crate::azure::cli::run`];
-/// in tests pass a closure that returns canned JSON.
+/// `options` scopes every page request (e.g. to a management group).
+/// The KQL is written to a temporary file and passed as `-q @<file>`, so it
+/// needs no shell or argv quoting.
+/// `runner` is called once per page with the `az` arguments (without the
+/// leading `az`) and must return the parsed page.  In production pass
+/// [`crate::azure::cli::run_az_json`], which streams the response; in tests
+/// pass a closure that parses canned JSON.
 ///
 /// # Errors
 /// Returns an error if any page fails to parse or if the skip-token repeats
 /// (indicating an infinite loop).
 pub(crate) fn paginate<F>(
     query: &str,
+    options: &QueryOptions,
     sleep: Duration,
-    mut runner: F,
+    runner: F,
 ) -> Result<Vec<Value>, Box<dyn Error>>
 where
-    F: FnMut(&str) -> Result<String, Box<dyn Error>>,
+    F: FnMut(&[String]) -> Result<PageEnvelope, Box<dyn Error>>,
+{
+    Ok(paginate_pages(query, options, sleep, runner)?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+/// Like [`paginate`], but keeps the rows of each page (query block) apart,
+/// in the order the pages were returned.
+pub(crate) fn paginate_pages<F>(
+    query: &str,
+    options: &QueryOptions,
+    sleep: Duration,
+    mut runner: F,
+) -> Result<Vec<Vec<Value>>, Box<dyn Error>>
+where
+    F: FnMut(&[String]) -> Result<PageEnvelope, Box<dyn Error>>,
 {
-    let mut all_rows: Vec<Value> = Vec::new();
+    let mut query_file = tempfile::Builder::new()
+        .prefix("az-graph-query-")
+        .suffix(".kql")
+        .tempfile()
+        .map_err(|e| format!("Error creating query file: {e}"))?;
+    query_file
+        .write_all(query.as_bytes())
+        .map_err(|e| format!("Error writing query file: {e}"))?;
+    let query_arg = format!("@{}", query_file.path().display());
+
+    let mut pages: Vec<Vec<Value>> = Vec::new();
     let mut skip_token_param = String::new();
+    let scope = options.scope_args();
+    let mut page_size = options.page_size.max(1);
+    let mut remaining = options.sample.map(|n| n.max(1) as usize);
 
     while skip_token_param != "--skip-token null" {
-        let cmd =
-            format!("az graph query --first 50 {skip_token_param} -q '{query}' --output json");
-
-        let output = runner(&cmd)?;
+        let first = match remaining {
+            Some(n) => page_size.min(n as u32),
+            None => page_size,
+        };
+        let mut args: Vec<String> = vec![
+            "graph".to_string(),
+            "query".to_string(),
+            "--first".to_string(),
+            first.to_string(),
+        ];
+        if let Some(token) = skip_token_param.strip_prefix("--skip-token ") {
+            args.extend(["--skip-token".to_string(), token.to_string()]);
+        }
+        args.extend(scope.iter().cloned());
+        args.extend(["-q".to_string(), query_arg.clone()]);
+        args.extend(["--output".to_string(), "json".to_string()]);
 
-        let mut de = serde_json::Deserializer::from_str(&output);
-        let page: PageEnvelope = serde_path_to_error::deserialize(&mut de)
-            .map_err(|e| format!("Error parsing page JSON: path={} error={}", e.path(), e))?;
+        let page = match runner(&args) {
+            Ok(page) => page,
+            Err(e) if page_size > MIN_PAGE_SIZE && is_response_too_large(&e.to_string()) => {
+                page_size = (page_size / 2).max(MIN_PAGE_SIZE);
+                log::warn!("Resource Graph response too large — retrying with --first {page_size}");
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
 
         let next_token = page.skip_token.unwrap_or_else(|| "null".to_string());
         let next_token_param = format!("--skip-token {next_token}");
@@ -52,7 +172,20 @@ where
             return Err("skip token not unique — possible infinite loop".into());
         }
 
-        all_rows.extend(page.data);
+        let mut rows = page.data;
+        if let Some(n) = remaining.as_mut() {
+            rows.truncate(*n);
+            *n -= rows.len();
+            if *n == 0 {
+                log::warn!(
+                    "--sample: stopping after {} record(s)",
+                    options.sample.unwrap_or(0)
+                );
+                pages.push(rows);
+                break;
+            }
+        }
+        pages.push(rows);
         skip_token_param = next_token_param;
 
         if skip_token_param != "--skip-token null" {
@@ -60,22 +193,26 @@ where
         }
     }
 
-    Ok(all_rows)
+    Ok(pages)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn page(json: &str) -> Result<PageEnvelope, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
     // ------------------------------------------------------------------
     // Behavior 1 — single page (skip_token: null) returns all rows
     // ------------------------------------------------------------------
     #[test]
     fn single_page_returns_all_rows() {
         let response = r#"{"data":[{"name":"row1"},{"name":"row2"}],"skip_token":null,"count":2}"#;
-        let runner = |_: &str| -> Result<String, Box<dyn Error>> { Ok(response.to_string()) };
+        let runner = |_: &[String]| -> Result<PageEnvelope, Box<dyn Error>> { page(response) };
 
-        let rows = paginate("SELECT 1", Duration::ZERO, runner).unwrap();
+        let rows = paginate("SELECT 1", &QueryOptions::default(), Duration::ZERO, runner).unwrap();
 
         assert_eq!(rows.len(), 2);
         assert_eq!(rows[0]["name"], "row1");
@@ -91,11 +228,11 @@ mod tests {
         let page2 = r#"{"data":[{"name":"b"},{"name":"c"}],"skip_token":null,"count":2}"#;
 
         let responses = std::cell::RefCell::new(vec![page1, page2].into_iter());
-        let runner = |_: &str| -> Result<String, Box<dyn Error>> {
-            Ok(responses.borrow_mut().next().unwrap().to_string())
+        let runner = |_: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            page(responses.borrow_mut().next().unwrap())
         };
 
-        let rows = paginate("SELECT 1", Duration::ZERO, runner).unwrap();
+        let rows = paginate("SELECT 1", &QueryOptions::default(), Duration::ZERO, runner).unwrap();
 
         assert_eq!(rows.len(), 3);
         assert_eq!(rows[0]["name"], "a");
@@ -110,15 +247,178 @@ mod tests {
         // Both pages return the same non-null token.
         let stuck = r#"{"data":[{"name":"x"}],"skip_token":"stuck","count":1}"#;
         let responses = std::cell::RefCell::new(vec![stuck, stuck].into_iter());
-        let runner = |_: &str| -> Result<String, Box<dyn Error>> {
-            Ok(responses.borrow_mut().next().unwrap().to_string())
+        let runner = |_: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            page(responses.borrow_mut().next().unwrap())
         };
 
-        let err = paginate("SELECT 1", Duration::ZERO, runner).unwrap_err();
+        let err =
+            paginate("SELECT 1", &QueryOptions::default(), Duration::ZERO, runner).unwrap_err();
 
         assert!(
             err.to_string().contains("infinite loop"),
             "unexpected error: {err}"
         );
     }
+
+    // ------------------------------------------------------------------
+    // Behavior 4 — management group scope is passed on every page
+    // ------------------------------------------------------------------
+    #[test]
+    fn management_group_scope_is_added_to_every_page() {
+        let page1 = r#"{"data":[{"name":"a"}],"skip_token":"tok1","count":1}"#;
+        let page2 = r#"{"data":[{"name":"b"}],"skip_token":null,"count":1}"#;
+        let responses = std::cell::RefCell::new(vec![page1, page2].into_iter());
+        let commands = std::cell::RefCell::new(Vec::new());
+        let runner = |args: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            commands.borrow_mut().push(args.join(" "));
+            page(responses.borrow_mut().next().unwrap())
+        };
+        let options = QueryOptions {
+            management_group: Some("mg-platform".to_string()),
+            ..QueryOptions::default()
+        };
+
+        paginate("SELECT 1", &options, Duration::ZERO, runner).unwrap();
+
+        let commands = commands.into_inner();
+        assert_eq!(commands.len(), 2);
+        assert!(commands
+            .iter()
+            .all(|c| c.contains("--management-groups mg-platform")));
+    }
+
+    #[test]
+    fn unscoped_query_has_no_management_group_argument() {
+        let response = r#"{"data":[],"skip_token":null,"count":0}"#;
+        let command = std::cell::RefCell::new(String::new());
+        let runner = |args: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            *command.borrow_mut() = args.join(" ");
+            page(response)
+        };
+
+        paginate("SELECT 1", &QueryOptions::default(), Duration::ZERO, runner).unwrap();
+
+        assert!(!command.borrow().contains("--management-groups"));
+    }
+
+    #[test]
+    fn subscription_scope_lists_every_subscription() {
+        let response = r#"{"data":[],"skip_token":null,"count":0}"#;
+        let command = std::cell::RefCell::new(String::new());
+        let runner = |args: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            *command.borrow_mut() = args.join(" ");
+            page(response)
+        };
+        let options = QueryOptions {
+            subscriptions: vec!["sub-a".to_string(), "sub-b".to_string()],
+            ..QueryOptions::default()
+        };
+
+        paginate("SELECT 1", &options, Duration::ZERO, runner).unwrap();
+
+        assert!(command.borrow().contains("--subscriptions sub-a sub-b"));
+    }
+
+    #[test]
+    fn query_is_passed_as_file_and_skip_token_as_separate_argument() {
+        let page1 = r#"{"data":[],"skip_token":"tok 1","count":0}"#;
+        let page2 = r#"{"data":[],"skip_token":null,"count":0}"#;
+        let responses = std::cell::RefCell::new(vec![page1, page2].into_iter());
+        let calls = std::cell::RefCell::new(Vec::new());
+        let runner = |args: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            let q = &args[args.iter().position(|a| a == "-q").unwrap() + 1];
+            let file = q.strip_prefix('@').expect("query passed as @file");
+            assert_eq!(
+                std::fs::read_to_string(file).unwrap(),
+                "resources | where name == 'x'"
+            );
+            calls.borrow_mut().push(args.to_vec());
+            page(responses.borrow_mut().next().unwrap())
+        };
+
+        paginate(
+            "resources | where name == 'x'",
+            &QueryOptions::default(),
+            Duration::ZERO,
+            runner,
+        )
+        .unwrap();
+
+        let calls = calls.into_inner();
+        assert!(!calls[0].contains(&"--skip-token".to_string()));
+        let i = calls[1].iter().position(|a| a == "--skip-token").unwrap();
+        assert_eq!(calls[1][i + 1], "tok 1");
+    }
+
+    #[test]
+    fn page_size_defaults_to_resource_graph_maximum() {
+        let response = r#"{"data":[],"skip_token":null,"count":0}"#;
+        let command = std::cell::RefCell::new(String::new());
+        let runner = |args: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            *command.borrow_mut() = args.join(" ");
+            page(response)
+        };
+
+        paginate("SELECT 1", &QueryOptions::default(), Duration::ZERO, runner).unwrap();
+
+        assert!(command.borrow().contains("--first 1000"));
+    }
+
+    #[test]
+    fn too_large_response_halves_page_size_and_retries() {
+        let sizes = std::cell::RefCell::new(Vec::new());
+        let runner = |args: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            let i = args.iter().position(|a| a == "--first").unwrap();
+            let size: u32 = args[i + 1].parse().unwrap();
+            sizes.borrow_mut().push(size);
+            if size > 250 {
+                Err(
+                    "ERROR running: (ResponsePayloadTooLarge) Response payload size exceeded"
+                        .into(),
+                )
+            } else {
+                page(r#"{"data":[{"name":"a"}],"skip_token":null,"count":1}"#)
+            }
+        };
+
+        let rows = paginate("SELECT 1", &QueryOptions::default(), Duration::ZERO, runner).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(sizes.into_inner(), vec![1000, 500, 250]);
+    }
+
+    #[test]
+    fn other_errors_are_not_retried() {
+        let calls = std::cell::Cell::new(0);
+        let runner = |_: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            calls.set(calls.get() + 1);
+            Err("ERROR running: AuthorizationFailed".into())
+        };
+
+        assert!(paginate("SELECT 1", &QueryOptions::default(), Duration::ZERO, runner).is_err());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn sample_stops_after_n_records() {
+        let firsts = std::cell::RefCell::new(Vec::new());
+        let runner = |args: &[String]| -> Result<PageEnvelope, Box<dyn Error>> {
+            let i = args.iter().position(|a| a == "--first").unwrap();
+            firsts.borrow_mut().push(args[i + 1].clone());
+            let token = format!("t{}", firsts.borrow().len());
+            page(&format!(
+                r#"{{"data":[{{"name":"a"}},{{"name":"b"}}],"skip_token":"{token}","count":2}}"#
+            ))
+        };
+        let options = QueryOptions {
+            page_size: 2,
+            sample: Some(3),
+            ..QueryOptions::default()
+        };
+
+        let rows = paginate("SELECT 1", &options, Duration::ZERO, runner).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(firsts.into_inner(), ["2", "1"]);
+    }
 }
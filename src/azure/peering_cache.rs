@@ -1,15 +1,20 @@
 //! Cache management for VNet peering data.
 
 use super::azure_cache::{self, AzureSource, CacheResult};
-use super::peering_graph::{run_peering_graph, PeeringData};
+use super::paginate::QueryOptions;
+use super::peering_graph::{run_peering_graph, PeeringData, PEERING_QUERY};
+use crate::clock::Clock;
 use std::error::Error;
 
 impl AzureSource for PeeringData {
     fn file_stem() -> &'static str {
         "peering"
     }
-    fn fetch() -> Result<Self, Box<dyn Error>> {
-        run_peering_graph()
+    fn query() -> &'static str {
+        PEERING_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_peering_graph(options)
     }
 }
 
@@ -17,7 +22,13 @@ impl AzureSource for PeeringData {
 pub fn read_peering_cache_with_status(
     cache_file: Option<&str>,
 ) -> Result<CacheResult<PeeringData>, Box<dyn Error>> {
-    azure_cache::load(cache_file, None)
+    azure_cache::load(
+        cache_file,
+        None,
+        false,
+        &QueryOptions::default(),
+        &Clock::default(),
+    )
 }
 
 /// Read peering data from cache, or fetch from Azure if not cached.
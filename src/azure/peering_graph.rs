@@ -1,6 +1,9 @@
 //! Azure Resource Graph query for VNet peering data.
 
-use super::{cli, paginate::paginate};
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
 use crate::config;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -10,7 +13,7 @@ use std::time::Duration;
 ///
 /// One row per directed peering. VNets with no peerings are excluded (mv-expand drops them).
 /// Standalone VNets are inferred from subnet data instead.
-const PEERING_QUERY: &str = r#"resources
+pub(super) const PEERING_QUERY: &str = r#"resources
         | where type == "microsoft.network/virtualnetworks"
         | mv-expand peering=properties.virtualNetworkPeerings
         | project subscription_id=subscriptionId
@@ -81,9 +84,9 @@ pub struct PeeringData {
 }
 
 /// Execute Azure Resource Graph peering query with automatic pagination.
-pub fn run_peering_graph() -> Result<PeeringData, Box<dyn Error>> {
+pub fn run_peering_graph(options: &QueryOptions) -> Result<PeeringData, Box<dyn Error>> {
     let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
-    let rows = paginate(PEERING_QUERY, sleep, cli::run)?;
+    let rows = paginate(PEERING_QUERY, options, sleep, run_graph_page)?;
 
     let data: Vec<PeeringEdge> = serde_json::from_value(serde_json::Value::Array(rows))
         .map_err(|e| format!("Error parsing peering rows: {e}"))?;
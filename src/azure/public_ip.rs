@@ -0,0 +1,197 @@
+//! Azure Resource Graph query for public IP addresses.
+//!
+//! Queries `microsoft.network/publicipaddresses` — one row per public IP —
+//! with the resource it is associated with (NIC, load balancer, NAT gateway,
+//! ...), for the `public-ips` inventory.
+
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+/// KQL: one row per public IP address.
+pub(super) const PUBLIC_IP_QUERY: &str = r#"resources
+    | where type == "microsoft.network/publicipaddresses"
+    | join kind=leftouter (
+        resourcecontainers
+        | where type == "microsoft.resources/subscriptions"
+        | project subscription_id = subscriptionId, subscription_name = name
+    ) on $left.subscriptionId == $right.subscription_id
+    | project subscription_id = subscriptionId
+             ,subscription_name
+             ,resource_group = resourceGroup
+             ,name
+             ,ip_address = tostring(properties.ipAddress)
+             ,allocation = tostring(properties.publicIPAllocationMethod)
+             ,sku = tostring(sku.name)
+             ,location
+             ,ip_configuration_id = tostring(properties.ipConfiguration.id)
+             ,nat_gateway_id = tostring(properties.natGateway.id)
+    | sort by subscription_name asc, name asc"#;
+
+/// What a public IP is attached to, derived from its resource IDs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublicIpAssociation {
+    /// Network interface (VM); carries the NIC name.
+    Nic(String),
+    /// Load balancer frontend; carries the load balancer name.
+    LoadBalancer(String),
+    /// NAT gateway; carries the NAT gateway name.
+    NatGateway(String),
+    /// Any other resource type (application gateway, VPN gateway, firewall,
+    /// Bastion, ...); carries `<type>/<name>`.
+    Other(String),
+    /// Not attached to anything — still billed.
+    Unassociated,
+}
+
+impl fmt::Display for PublicIpAssociation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublicIpAssociation::Nic(name) => write!(f, "NIC {name}"),
+            PublicIpAssociation::LoadBalancer(name) => write!(f, "LB {name}"),
+            PublicIpAssociation::NatGateway(name) => write!(f, "NAT GW {name}"),
+            PublicIpAssociation::Other(name) => write!(f, "{name}"),
+            PublicIpAssociation::Unassociated => f.write_str("UNASSOCIATED"),
+        }
+    }
+}
+
+/// One row from the public IP query.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PublicIpRow {
+    pub subscription_id: String,
+    #[serde(default)]
+    pub subscription_name: String,
+    #[serde(default)]
+    pub resource_group: String,
+    pub name: String,
+    /// Assigned address; empty for an unallocated dynamic IP.
+    #[serde(default)]
+    pub ip_address: String,
+    /// `Static` or `Dynamic`.
+    #[serde(default)]
+    pub allocation: String,
+    /// `Basic` or `Standard`.
+    #[serde(default)]
+    pub sku: String,
+    #[serde(default)]
+    pub location: String,
+    /// ID of the IP configuration using this address (NIC, LB, gateway, ...).
+    #[serde(default)]
+    pub ip_configuration_id: String,
+    /// ID of the NAT gateway using this address.
+    #[serde(default)]
+    pub nat_gateway_id: String,
+}
+
+impl PublicIpRow {
+    /// Classify the resource this public IP is attached to.
+    pub fn association(&self) -> PublicIpAssociation {
+        if !self.nat_gateway_id.is_empty() {
+            let name = self.nat_gateway_id.rsplit('/').next().unwrap_or_default();
+            return PublicIpAssociation::NatGateway(name.to_string());
+        }
+        if self.ip_configuration_id.is_empty() {
+            return PublicIpAssociation::Unassociated;
+        }
+        // .../providers/Microsoft.Network/<type>/<name>/<child type>/<child name>
+        let parts: Vec<&str> = self.ip_configuration_id.split('/').collect();
+        let Some(pos) = parts
+            .iter()
+            .position(|p| p.eq_ignore_ascii_case("Microsoft.Network"))
+        else {
+            return PublicIpAssociation::Other(self.ip_configuration_id.clone());
+        };
+        let kind = parts.get(pos + 1).copied().unwrap_or_default();
+        let name = parts.get(pos + 2).copied().unwrap_or_default().to_string();
+        match kind.to_ascii_lowercase().as_str() {
+            "networkinterfaces" => PublicIpAssociation::Nic(name),
+            "loadbalancers" => PublicIpAssociation::LoadBalancer(name),
+            _ => PublicIpAssociation::Other(format!("{kind}/{name}")),
+        }
+    }
+}
+
+/// Response wrapper for the public IP query.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PublicIpData {
+    pub data: Vec<PublicIpRow>,
+    pub skip_token: Option<String>,
+    pub total_records: Option<u32>,
+    pub count: i32,
+}
+
+/// Execute the Azure Resource Graph public IP query with automatic pagination.
+pub fn run_public_ip_graph(options: &QueryOptions) -> Result<PublicIpData, Box<dyn Error>> {
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(PUBLIC_IP_QUERY, options, sleep, run_graph_page)?;
+
+    let data: Vec<PublicIpRow> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing public IP rows: {e}"))?;
+
+    let count = data.len() as i32;
+    let total_records = Some(data.len() as u32);
+
+    log::info!("Got {count} public IP rows from az graph query");
+
+    Ok(PublicIpData {
+        data,
+        skip_token: None,
+        total_records,
+        count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(ip_configuration_id: &str, nat_gateway_id: &str) -> PublicIpRow {
+        PublicIpRow {
+            ip_configuration_id: ip_configuration_id.to_string(),
+            nat_gateway_id: nat_gateway_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn association_is_derived_from_resource_ids() {
+        let base = "/subscriptions/s/resourceGroups/rg/providers/Microsoft.Network";
+        assert_eq!(
+            row(
+                &format!("{base}/networkInterfaces/vm1-nic/ipConfigurations/ipconfig1"),
+                ""
+            )
+            .association(),
+            PublicIpAssociation::Nic("vm1-nic".to_string())
+        );
+        assert_eq!(
+            row(
+                &format!("{base}/loadBalancers/lb-web/frontendIPConfigurations/fe"),
+                ""
+            )
+            .association(),
+            PublicIpAssociation::LoadBalancer("lb-web".to_string())
+        );
+        assert_eq!(
+            row("", &format!("{base}/natGateways/ngw-egress")).association(),
+            PublicIpAssociation::NatGateway("ngw-egress".to_string())
+        );
+        assert_eq!(
+            row(
+                &format!("{base}/azureFirewalls/fw-hub/azureFirewallIpConfigurations/c"),
+                ""
+            )
+            .association()
+            .to_string(),
+            "azureFirewalls/fw-hub"
+        );
+        assert_eq!(row("", "").association(), PublicIpAssociation::Unassociated);
+    }
+}
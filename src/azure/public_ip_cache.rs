@@ -0,0 +1,18 @@
+//! Cache management for public IP address data.
+
+use super::azure_cache::AzureSource;
+use super::paginate::QueryOptions;
+use super::public_ip::{run_public_ip_graph, PublicIpData, PUBLIC_IP_QUERY};
+use std::error::Error;
+
+impl AzureSource for PublicIpData {
+    fn file_stem() -> &'static str {
+        "public-ip"
+    }
+    fn query() -> &'static str {
+        PUBLIC_IP_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_public_ip_graph(options)
+    }
+}
@@ -0,0 +1,153 @@
+//! Schema validation of Resource Graph subnet rows.
+//!
+//! Rows are parsed one at a time so a bad row does not hide the ones after
+//! it: every problem is collected with its page, row and JSON path, and the
+//! fetch fails once with the full list. Rows that parse but lack a field the
+//! reports rely on (names, IDs, location) are warnings, or errors in strict
+//! mode (`--strict-schema`).
+
+use crate::models::Subnet;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+
+/// Problems listed in the error before the rest are summarised as a count.
+const MAX_LISTED: usize = 20;
+
+/// One problem in a Resource Graph row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaProblem {
+    /// Location of the value, e.g. `page[1][37].vnet_cidr[0]`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Required fields of `s` that are empty.
+fn missing_fields(s: &Subnet) -> Vec<&'static str> {
+    [
+        ("subscription_id", s.subscription_id.is_empty()),
+        ("vnet_id", s.vnet_id.is_empty()),
+        ("vnet_name", s.vnet_name.is_empty()),
+        ("subnet_name", s.subnet_name.is_empty()),
+        ("location", s.location.is_empty()),
+    ]
+    .into_iter()
+    .filter_map(|(field, missing)| missing.then_some(field))
+    .collect()
+}
+
+/// Parse the subnet rows of every page, tagging each with its page as
+/// [`Subnet::query_block`].
+///
+/// Fails with every parse problem at once; rows missing required fields fail
+/// the same way when `strict`, otherwise they are kept with a warning.
+pub fn parse_subnet_pages(
+    pages: Vec<Vec<Value>>,
+    strict: bool,
+) -> Result<Vec<Subnet>, Box<dyn Error>> {
+    let mut subnets = Vec::new();
+    let mut errors: Vec<SchemaProblem> = Vec::new();
+    for (block, rows) in pages.into_iter().enumerate() {
+        for (row, value) in rows.into_iter().enumerate() {
+            let at = format!("page[{block}][{row}]");
+            let subnet: Subnet = match serde_path_to_error::deserialize(value) {
+                Ok(s) => s,
+                Err(e) => {
+                    let path = e.path().to_string();
+                    errors.push(SchemaProblem {
+                        path: if path == "." {
+                            at
+                        } else {
+                            format!("{at}.{path}")
+                        },
+                        message: e.into_inner().to_string(),
+                    });
+                    continue;
+                }
+            };
+            for field in missing_fields(&subnet) {
+                let problem = SchemaProblem {
+                    path: format!("{at}.{field}"),
+                    message: format!(
+                        "required field is empty (vnet '{}', subnet '{}')",
+                        subnet.vnet_name, subnet.subnet_name
+                    ),
+                };
+                if strict {
+                    errors.push(problem);
+                } else {
+                    log::warn!("Subnet row {problem}");
+                }
+            }
+            subnets.push(Subnet {
+                query_block: Some(block as u32),
+                ..subnet
+            });
+        }
+    }
+    if errors.is_empty() {
+        return Ok(subnets);
+    }
+    let mut msg = format!(
+        "{} problem(s) in the subnet rows from Resource Graph:",
+        errors.len()
+    );
+    for e in errors.iter().take(MAX_LISTED) {
+        msg.push_str(&format!("\n  {e}"));
+    }
+    if errors.len() > MAX_LISTED {
+        msg.push_str(&format!("\n  ... and {} more", errors.len() - MAX_LISTED));
+    }
+    Err(msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(vnet: &str, subnet: &str) -> Value {
+        json!({
+            "subscription_id": "sub-1", "vnet_id": format!("/vnets/{vnet}"),
+            "vnet_name": vnet, "vnet_cidr": ["10.0.0.0/16"],
+            "subnet_name": subnet, "subnet_cidr": "10.0.1.0/24",
+            "location": "eastus", "subscription_name": "Sub 1"
+        })
+    }
+
+    #[test]
+    fn all_problems_are_reported_with_their_paths() {
+        let mut bad_cidr = row("hub", "a");
+        bad_cidr["subnet_cidr"] = json!("10.0.1.0/99");
+        let mut null_name = row("hub", "b");
+        null_name["vnet_name"] = Value::Null;
+        let pages = vec![
+            vec![row("hub", "ok"), bad_cidr],
+            vec![null_name, row("spoke", "")],
+        ];
+
+        let relaxed = parse_subnet_pages(pages.clone(), false)
+            .unwrap_err()
+            .to_string();
+        assert!(relaxed.starts_with("2 problem(s)"), "{relaxed}");
+        assert!(
+            relaxed.contains("\n  page[0][1].subnet_cidr: "),
+            "{relaxed}"
+        );
+        assert!(relaxed.contains("\n  page[1][0].vnet_name: "), "{relaxed}");
+
+        let strict = parse_subnet_pages(pages, true).unwrap_err().to_string();
+        assert!(strict.starts_with("3 problem(s)"), "{strict}");
+        assert!(strict.contains("page[1][1].subnet_name: required field is empty"));
+
+        let ok =
+            parse_subnet_pages(vec![vec![row("hub", "a")], vec![row("hub", "b")]], true).unwrap();
+        assert_eq!(ok[1].query_block, Some(1));
+    }
+}
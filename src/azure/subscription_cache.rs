@@ -0,0 +1,58 @@
+//! Cache management for subscription metadata.
+
+use super::azure_cache::{self, AzureSource, CacheResult};
+use super::paginate::QueryOptions;
+use super::subscription_graph::{run_subscription_graph, SubscriptionData, SUBSCRIPTION_QUERY};
+use crate::clock::Clock;
+use std::error::Error;
+
+impl AzureSource for SubscriptionData {
+    fn file_stem() -> &'static str {
+        "subscription"
+    }
+    fn query() -> &'static str {
+        SUBSCRIPTION_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_subscription_graph(options)
+    }
+}
+
+/// Read subscription data from cache, or fetch from Azure if cache doesn't exist.
+pub fn read_subscription_cache_with_status(
+    cache_file: Option<&str>,
+) -> Result<CacheResult<SubscriptionData>, Box<dyn Error>> {
+    azure_cache::load(
+        cache_file,
+        None,
+        false,
+        &QueryOptions::default(),
+        &Clock::default(),
+    )
+}
+
+/// Read subscription data from cache, or fetch from Azure if not cached.
+pub fn read_subscription_cache(
+    cache_file: Option<&str>,
+) -> Result<SubscriptionData, Box<dyn Error>> {
+    Ok(read_subscription_cache_with_status(cache_file)?.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_subscription_cache_returns_metadata() {
+        let data =
+            read_subscription_cache(Some("src/tests/test_data/subscription_test_cache_01.json"))
+                .expect("Error reading subscription cache");
+        assert_eq!(data.count, 2, "Expected 2 subscriptions");
+        let prod = data
+            .get("7789013b-1e8e-4444-eeee-abab809aeead")
+            .expect("prod sub");
+        assert_eq!(prod.state, "Enabled");
+        assert_eq!(prod.management_group(), Some("mg-corp"));
+        assert_eq!(prod.tags.get("env").map(String::as_str), Some("prod"));
+    }
+}
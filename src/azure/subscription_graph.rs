@@ -0,0 +1,67 @@
+//! Azure Resource Graph query for subscription metadata.
+//!
+//! Queries `resourcecontainers` for every subscription in scope — state, tags,
+//! and the management group ancestor chain — so reports can be grouped or
+//! filtered by management group.
+
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
+use crate::config;
+use crate::models::Subscription;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Duration;
+
+/// KQL: one row per subscription.
+///
+/// `managementGroupAncestorsChain` is ordered from the direct parent up to the
+/// tenant root group; each element carries `name` and `displayName`.
+pub(super) const SUBSCRIPTION_QUERY: &str = r#"resourcecontainers
+    | where type == "microsoft.resources/subscriptions"
+    | project subscription_id = subscriptionId
+             ,subscription_name = name
+             ,state = tostring(properties.state)
+             ,tags
+             ,management_group_chain = properties.managementGroupAncestorsChain
+    | sort by subscription_name asc"#;
+
+/// Response wrapper for the subscription query.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SubscriptionData {
+    pub data: Vec<Subscription>,
+    pub skip_token: Option<String>,
+    pub total_records: Option<u32>,
+    pub count: i32,
+}
+
+impl SubscriptionData {
+    /// Look up a subscription by ID.
+    pub fn get(&self, subscription_id: &str) -> Option<&Subscription> {
+        self.data
+            .iter()
+            .find(|s| s.subscription_id == subscription_id)
+    }
+}
+
+/// Execute the Azure Resource Graph subscription query with automatic pagination.
+pub fn run_subscription_graph(options: &QueryOptions) -> Result<SubscriptionData, Box<dyn Error>> {
+    let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
+    let rows = paginate(SUBSCRIPTION_QUERY, options, sleep, run_graph_page)?;
+
+    let data: Vec<Subscription> = serde_json::from_value(serde_json::Value::Array(rows))
+        .map_err(|e| format!("Error parsing subscription rows: {e}"))?;
+
+    let count = data.len() as i32;
+    let total_records = Some(data.len() as u32);
+
+    log::info!("Got {count} subscription rows from az graph query");
+
+    Ok(SubscriptionData {
+        data,
+        skip_token: None,
+        total_records,
+        count,
+    })
+}
@@ -1,15 +1,20 @@
 //! Cache management for Virtual WAN topology data.
 
 use super::azure_cache::{self, AzureSource, CacheResult};
-use super::vwan_graph::{run_vwan_graph, VWanData};
+use super::paginate::QueryOptions;
+use super::vwan_graph::{run_vwan_graph, VWanData, VWAN_QUERY};
+use crate::clock::Clock;
 use std::error::Error;
 
 impl AzureSource for VWanData {
     fn file_stem() -> &'static str {
         "vwan"
     }
-    fn fetch() -> Result<Self, Box<dyn Error>> {
-        run_vwan_graph()
+    fn query() -> &'static str {
+        VWAN_QUERY
+    }
+    fn fetch(options: &QueryOptions) -> Result<Self, Box<dyn Error>> {
+        run_vwan_graph(options)
     }
 }
 
@@ -17,7 +22,13 @@ impl AzureSource for VWanData {
 pub fn read_vwan_cache_with_status(
     cache_file: Option<&str>,
 ) -> Result<CacheResult<VWanData>, Box<dyn Error>> {
-    azure_cache::load(cache_file, None)
+    azure_cache::load(
+        cache_file,
+        None,
+        false,
+        &QueryOptions::default(),
+        &Clock::default(),
+    )
 }
 
 /// Read vWAN data from cache, or fetch from Azure if not cached.
@@ -4,7 +4,10 @@
 //! address prefixes, and vWAN associations. Spoke connections are derived
 //! separately from `HV_*` peering edges in the peering cache.
 
-use super::{cli, paginate::paginate};
+use super::{
+    account::run_graph_page,
+    paginate::{paginate, QueryOptions},
+};
 use crate::config;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -14,7 +17,7 @@ use std::time::Duration;
 ///
 /// `hubvirtualnetworkconnections` is a child resource not indexed in ARG — query
 /// the parent `virtualhubs` instead to get hub name, CIDR, and vWAN name.
-const VWAN_QUERY: &str = r#"resources
+pub(super) const VWAN_QUERY: &str = r#"resources
     | where type == "microsoft.network/virtualhubs"
     | extend virtual_wan_name = tostring(split(tolower(tostring(properties.virtualWan.id)), "/")[8])
     | join kind=leftouter (
@@ -59,9 +62,9 @@ pub struct VWanData {
 }
 
 /// Execute the Azure Resource Graph vWAN query with automatic pagination.
-pub fn run_vwan_graph() -> Result<VWanData, Box<dyn Error>> {
+pub fn run_vwan_graph(options: &QueryOptions) -> Result<VWanData, Box<dyn Error>> {
     let sleep = Duration::from_millis(config::SLEEP_MSEC * 5);
-    let rows = paginate(VWAN_QUERY, sleep, cli::run)?;
+    let rows = paginate(VWAN_QUERY, options, sleep, run_graph_page)?;
 
     let data: Vec<VWanRow> = serde_json::from_value(serde_json::Value::Array(rows))
         .map_err(|e| format!("Error parsing vWAN rows: {e}"))?;
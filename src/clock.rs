@@ -0,0 +1,120 @@
+//! Timezone and date format used for dated file names and timestamps.
+//!
+//! Cache files, report directories and output files carry the date of the run
+//! (`report-<date>/`, `net_<date>_...`). That date is taken in a configurable
+//! timezone (default UTC) and rendered with a configurable `strftime` format
+//! (default `%Y-%m-%d`). Structured outputs also record an explicit
+//! generation timestamp in the same timezone.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use chrono_tz::Tz;
+use std::error::Error;
+
+/// Date format used when none is configured.
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Timezone and date format for the current run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clock {
+    timezone: Tz,
+    date_format: String,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock {
+            timezone: Tz::UTC,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+        }
+    }
+}
+
+impl Clock {
+    /// Build a clock from an IANA timezone name (e.g. `Pacific/Auckland`) and
+    /// a `strftime` date format; `None` keeps the default.
+    ///
+    /// The date format must round-trip to a calendar date (so earlier dated
+    /// files can be found again) and may not contain path separators.
+    pub fn new(timezone: Option<&str>, date_format: Option<&str>) -> Result<Clock, Box<dyn Error>> {
+        let mut clock = Clock::default();
+        if let Some(tz) = timezone {
+            clock.timezone = tz
+                .parse()
+                .map_err(|e| format!("Invalid timezone '{tz}': {e}"))?;
+        }
+        if let Some(format) = date_format {
+            if format.contains(['/', '\\']) {
+                return Err(
+                    format!("Date format '{format}' must not contain path separators").into(),
+                );
+            }
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(format!("Invalid date format '{format}'").into());
+            }
+            let probe = NaiveDate::from_ymd_opt(2026, 12, 31).expect("valid date");
+            let rendered = probe.format(format).to_string();
+            if NaiveDate::parse_from_str(&rendered, format).ok() != Some(probe) {
+                return Err(
+                    format!("Date format '{format}' must include the year, month and day").into(),
+                );
+            }
+            clock.date_format = format.to_string();
+        }
+        Ok(clock)
+    }
+
+    /// Current time in the configured timezone.
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.timezone)
+    }
+
+    /// Today's date in the configured timezone.
+    pub fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+
+    /// Today's date rendered with the configured format, for file names.
+    pub fn date_str(&self) -> String {
+        self.format_date(self.today())
+    }
+
+    /// Render `date` with the configured format.
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(&self.date_format).to_string()
+    }
+
+    /// Parse a date rendered by [`format_date`](Self::format_date).
+    pub fn parse_date(&self, text: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(text, &self.date_format).ok()
+    }
+
+    /// Current time as an RFC 3339 timestamp with the timezone offset, e.g.
+    /// `2026-10-15T09:30:00+13:00` (`Z` for UTC).
+    pub fn timestamp(&self) -> String {
+        self.now().to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_format_round_trips_dates() {
+        let clock = Clock::new(Some("Pacific/Auckland"), Some("%d.%m.%Y")).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 5).unwrap();
+        assert_eq!(clock.format_date(date), "05.10.2026");
+        assert_eq!(clock.parse_date("05.10.2026"), Some(date));
+        assert_eq!(Clock::default().format_date(date), "2026-10-05");
+        assert!(Clock::default().timestamp().ends_with('Z'));
+    }
+
+    #[test]
+    fn unusable_settings_are_rejected() {
+        assert!(Clock::new(Some("Mars/Olympus"), None).is_err());
+        assert!(Clock::new(None, Some("%Y/%m/%d")).is_err());
+        assert!(Clock::new(None, Some("%Y-%m")).is_err());
+        assert!(Clock::new(None, Some("%Y-%m-%Q")).is_err());
+    }
+}
@@ -0,0 +1,34 @@
+//! ARM template subnet resource object.
+//!
+//! Each subnet renders as one object for an ARM template's `resources` array.
+
+/// Built-in ARM subnet resource template.
+pub const TEMPLATE: &str = r#"{
+  "type": "Microsoft.Network/virtualNetworks/subnets",
+  "apiVersion": "2023-09-01",
+  "name": "{{vnet_name}}/{{name}}",
+  "properties": {
+    "addressPrefix": "{{address_prefix}}"
+  }
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::{emit, tests::spec, Emit};
+
+    #[test]
+    fn multiple_subnets_form_a_valid_json_array() {
+        let out = emit(
+            Emit::Arm,
+            &[
+                spec("a-snet", "10.0.0.0/26"),
+                spec("b-snet", "10.0.0.64/26"),
+            ],
+            None,
+        );
+        let resources: serde_json::Value =
+            serde_json::from_str(&format!("[{out}]")).expect("valid JSON array");
+        assert_eq!(resources[1]["name"], "hub-vnet/b-snet");
+        assert_eq!(resources[1]["properties"]["addressPrefix"], "10.0.0.64/26");
+    }
+}
@@ -0,0 +1,28 @@
+//! Bicep subnet resource template.
+//!
+//! References the existing VNet by name so the snippet can be added to any
+//! Bicep file deployed to the VNet's resource group.
+
+/// Built-in Bicep subnet template.
+pub const TEMPLATE: &str = r#"resource {{resource_name}} 'Microsoft.Network/virtualNetworks/subnets@2023-09-01' = {
+  name: '{{vnet_name}}/{{name}}'
+  properties: {
+    addressPrefix: '{{address_prefix}}'
+  }
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::{emit, tests::spec, Emit};
+
+    #[test]
+    fn renders_bicep_subnet_resource() {
+        let out = emit(Emit::Bicep, &[spec("app-snet", "10.0.0.64/27")], None);
+        assert!(out.starts_with(
+            "resource app_snet 'Microsoft.Network/virtualNetworks/subnets@2023-09-01' = {"
+        ));
+        assert!(out.contains("name: 'hub-vnet/app-snet'"));
+        assert!(out.contains("addressPrefix: '10.0.0.64/27'"));
+    }
+}
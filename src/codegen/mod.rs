@@ -0,0 +1,143 @@
+//! Infrastructure-as-code snippet generation.
+//!
+//! Renders subnets proposed by the allocator (`suggest`, `split`) as
+//! copy-pasteable Terraform, Bicep or ARM. Each emitter is a built-in template
+//! with `{{placeholder}}` variables; users can supply their own template file.
+//!
+//! - [`terraform`] - `azurerm_subnet` resource blocks
+//! - [`bicep`] - `Microsoft.Network/virtualNetworks/subnets` Bicep resources
+//! - [`arm`] - ARM template resource objects
+
+pub mod arm;
+pub mod bicep;
+pub mod terraform;
+
+use crate::models::Ipv4;
+use clap::ValueEnum;
+
+/// Infrastructure-as-code format to emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// Terraform `azurerm_subnet` resource block.
+    Terraform,
+    /// Bicep subnet resource.
+    Bicep,
+    /// ARM template resource object.
+    Arm,
+}
+
+/// One subnet to render.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubnetSpec {
+    pub name: String,
+    pub vnet_name: String,
+    /// Full ARM resource ID of the VNet, empty when unknown.
+    pub vnet_id: String,
+    pub resource_group: String,
+    pub subscription_id: String,
+    pub address_prefix: Ipv4,
+}
+
+impl SubnetSpec {
+    /// Template variables available to every emitter.
+    ///
+    /// `resource_name` is `name` with characters invalid in Terraform/Bicep
+    /// identifiers replaced by `_`.
+    pub fn vars(&self) -> Vec<(&'static str, String)> {
+        let resource_name: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        vec![
+            ("resource_name", resource_name),
+            ("name", self.name.clone()),
+            ("resource_group", self.resource_group.clone()),
+            ("vnet_name", self.vnet_name.clone()),
+            ("vnet_id", self.vnet_id.clone()),
+            ("address_prefix", self.address_prefix.to_string()),
+            ("subscription_id", self.subscription_id.clone()),
+        ]
+    }
+}
+
+/// Replace every `{{key}}` in `template` with its value from `vars`.
+pub fn render_template(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter().fold(template.to_string(), |out, (key, value)| {
+        out.replace(&format!("{{{{{key}}}}}"), value)
+    })
+}
+
+/// Built-in template for `format`.
+pub fn default_template(format: Emit) -> &'static str {
+    match format {
+        Emit::Terraform => terraform::TEMPLATE,
+        Emit::Bicep => bicep::TEMPLATE,
+        Emit::Arm => arm::TEMPLATE,
+    }
+}
+
+/// Render `specs` in `format`, using `template` instead of the built-in one when given.
+///
+/// Terraform and Bicep blocks are separated by blank lines; ARM objects are
+/// comma-separated so they can be pasted into a `resources` array.
+pub fn emit(format: Emit, specs: &[SubnetSpec], template: Option<&str>) -> String {
+    let template = template.unwrap_or(default_template(format));
+    let separator = match format {
+        Emit::Arm => ",\n",
+        Emit::Terraform | Emit::Bicep => "\n",
+    };
+    specs
+        .iter()
+        .map(|spec| render_template(template, &spec.vars()))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn spec(name: &str, cidr: &str) -> SubnetSpec {
+        SubnetSpec {
+            name: name.to_string(),
+            vnet_name: "hub-vnet".to_string(),
+            vnet_id: String::new(),
+            resource_group: "rg-network".to_string(),
+            subscription_id: "sub-1".to_string(),
+            address_prefix: Ipv4::new(cidr).unwrap(),
+        }
+    }
+
+    #[test]
+    fn custom_template_placeholders_are_replaced() {
+        let out = emit(
+            Emit::Terraform,
+            &[spec("x", "10.0.0.64/27")],
+            Some("{{address_prefix}} in {{vnet_name}} ({{subscription_id}})"),
+        );
+        assert_eq!(out, "10.0.0.64/27 in hub-vnet (sub-1)");
+    }
+
+    #[test]
+    fn resource_name_is_a_valid_identifier() {
+        let vars = spec("app-snet.01", "10.0.0.0/26").vars();
+        assert_eq!(vars[0], ("resource_name", "app_snet_01".to_string()));
+    }
+
+    #[test]
+    fn every_builtin_template_renders_all_placeholders() {
+        for format in [Emit::Terraform, Emit::Bicep, Emit::Arm] {
+            let out = emit(
+                format,
+                &[
+                    spec("a-snet", "10.0.0.0/26"),
+                    spec("b-snet", "10.0.0.64/26"),
+                ],
+                None,
+            );
+            assert!(!out.contains("{{"), "{format:?} left a placeholder: {out}");
+            assert!(out.contains("10.0.0.64/26"));
+        }
+    }
+}
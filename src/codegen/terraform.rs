@@ -0,0 +1,25 @@
+//! Terraform `azurerm_subnet` template.
+
+/// Built-in `azurerm_subnet` template.
+pub const TEMPLATE: &str = r#"resource "azurerm_subnet" "{{resource_name}}" {
+  name                 = "{{name}}"
+  resource_group_name  = "{{resource_group}}"
+  virtual_network_name = "{{vnet_name}}"
+  address_prefixes     = ["{{address_prefix}}"]
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::{emit, tests::spec, Emit};
+
+    #[test]
+    fn renders_azurerm_subnet() {
+        let out = emit(Emit::Terraform, &[spec("app-snet", "10.0.0.64/27")], None);
+        assert!(out.starts_with(r#"resource "azurerm_subnet" "app_snet" {"#));
+        assert!(out.contains(r#"name                 = "app-snet""#));
+        assert!(out.contains(r#"resource_group_name  = "rg-network""#));
+        assert!(out.contains(r#"virtual_network_name = "hub-vnet""#));
+        assert!(out.contains(r#"address_prefixes     = ["10.0.0.64/27"]"#));
+    }
+}
@@ -0,0 +1,101 @@
+//! `check` subcommand — report findings and fail when any at or above a
+//! severity is not covered by the suppressions file (for CI gates).
+
+use crate::azure::{fetch_subnets, fetch_subscriptions, FetchConfig, SubscriptionData};
+use crate::config::Config;
+use crate::pipeline::resolve_subnets;
+use crate::processing::IgnoreList;
+use crate::report::{Finding, Report, Severity};
+use crate::suppressions::{Suppression, Suppressions};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// Fail when an unsuppressed finding has at least this severity.
+    #[arg(long, value_enum, default_value_t = Severity::Error, value_name = "SEVERITY")]
+    pub fail_on: Severity,
+    /// Suppressions file (TOML). Defaults to
+    /// `azure-subnet-summary.suppressions.toml` when present.
+    #[arg(long, value_name = "FILE")]
+    pub suppressions: Option<String>,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Render active findings, then suppressed ones with their reason.
+pub fn format_findings(active: &[Finding], suppressed: &[(Finding, &Suppression)]) -> String {
+    let line = |f: &Finding| {
+        let target = if f.subnet_name.is_empty() {
+            f.vnet_name.clone()
+        } else {
+            format!("{}/{}", f.vnet_name, f.subnet_name)
+        };
+        format!(
+            "{}: '{target}' {} - {}",
+            f.severity.to_string().to_uppercase(),
+            f.kind.id(),
+            f.message
+        )
+    };
+    let mut lines: Vec<String> = active.iter().map(line).collect();
+    lines.extend(
+        suppressed
+            .iter()
+            .map(|(f, s)| format!("SUPPRESSED {} ({})", line(f), s.reason)),
+    );
+    if lines.is_empty() {
+        lines.push("No findings.".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Run the `check` subcommand.
+pub fn run(
+    args: &CheckArgs,
+    fetch: &FetchConfig,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let fetch_config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let config = Config::load(config_path, profile)?;
+    let ignore = IgnoreList::from_rules(&config.ignore);
+    ignore.log_active();
+    let suppressions = Suppressions::load(args.suppressions.as_deref())?;
+
+    let mut subnets = fetch_subnets(&fetch_config)?.data;
+    subnets.data.sort_by_key(|s| s.subnet_cidr);
+    let subnets = resolve_subnets(subnets, &ignore)?.active;
+    let mut report = Report::from_subnets(&subnets);
+    // Subscriptions are only needed to resolve management group scopes.
+    let subscriptions = if config.policy.iter().any(|p| p.management_group.is_some()) {
+        fetch_subscriptions(&fetch_config)?.data
+    } else {
+        SubscriptionData::default()
+    };
+    report.add_policy_findings(&subnets, &subscriptions, &config.policy)?;
+
+    let today = fetch_config.clock.today();
+    let (mut active, suppressed) = suppressions.partition(report.findings, today);
+    active.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    println!("{}", format_findings(&active, &suppressed));
+
+    let failing = active.iter().filter(|f| f.severity >= args.fail_on).count();
+    if failing > 0 {
+        return Err(format!(
+            "{failing} unsuppressed finding(s) at severity {} or above",
+            args.fail_on
+        )
+        .into());
+    }
+    Ok(())
+}
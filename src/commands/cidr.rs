@@ -0,0 +1,171 @@
+//! `cidr` subcommands — an IP calculator built on the [`crate::models`] helpers.
+//!
+//! Host counts follow Azure rules: 5 addresses are reserved in every subnet
+//! (network, default gateway, 2× DNS, broadcast).
+
+use crate::models::{
+    broadcast_addr, cut_addr, get_cidr_mask, next_subnet_ipv4, num_az_hosts, split_subnet, Ipv4,
+};
+use crate::output::format::{binary, size_description, thousands};
+use clap::Subcommand;
+use std::error::Error;
+use std::net::Ipv4Addr;
+
+/// Refuse splits that would print more than 2^12 = 4096 subnets.
+const MAX_SPLIT_BITS: u8 = 12;
+
+#[derive(Debug, Subcommand)]
+pub enum CidrCommand {
+    /// Show network, broadcast, netmask and Azure usable hosts for a CIDR.
+    ///
+    /// Example: cidr info 10.1.2.0/26
+    Info {
+        /// CIDR to describe, e.g. 10.1.2.0/26.
+        cidr: String,
+    },
+    /// List every subnet of size `--into` inside a CIDR.
+    ///
+    /// Example: cidr split 10.0.0.0/22 --into 26
+    Split {
+        /// CIDR to split, e.g. 10.0.0.0/22.
+        cidr: String,
+        /// Prefix length of the resulting subnets.
+        #[arg(long, value_name = "MASK")]
+        into: u8,
+    },
+    /// Show the next subnet after a CIDR (optionally with a different size).
+    ///
+    /// Example: cidr next 10.0.1.0/24 --mask 26
+    Next {
+        /// CIDR to start from, e.g. 10.0.1.0/24.
+        cidr: String,
+        /// Prefix length of the next subnet (defaults to the input's).
+        #[arg(long, value_name = "MASK")]
+        mask: Option<u8>,
+    },
+}
+
+/// Run a `cidr` subcommand, printing the result.
+pub fn run(cmd: &CidrCommand) -> Result<(), Box<dyn Error>> {
+    let out = match cmd {
+        CidrCommand::Info { cidr } => info(Ipv4::new(cidr)?)?,
+        CidrCommand::Split { cidr, into } => split(Ipv4::new(cidr)?, *into)?,
+        CidrCommand::Next { cidr, mask } => next(Ipv4::new(cidr)?, *mask)?,
+    };
+    println!("{out}");
+    Ok(())
+}
+
+/// Describe `cidr`: network, broadcast, netmask, size and Azure usable hosts.
+pub fn info(cidr: Ipv4) -> Result<String, Box<dyn Error>> {
+    let network = cut_addr(cidr.addr, cidr.mask)?;
+    let broadcast = broadcast_addr(cidr.addr, cidr.mask)?;
+    let netmask = Ipv4Addr::from(get_cidr_mask(cidr.mask)?);
+    let addresses = 1u64 << (32 - u32::from(cidr.mask));
+
+    let mut lines = Vec::new();
+    if network != cidr.addr {
+        lines.push(format!(
+            "Warning: {cidr} is not aligned; network is {network}/{}",
+            cidr.mask
+        ));
+    }
+    lines.push(format!("CIDR:        {network}/{}", cidr.mask));
+    lines.push(format!("Network:     {network}"));
+    lines.push(format!("Broadcast:   {broadcast}"));
+    lines.push(format!("Netmask:     {netmask}"));
+    lines.push(if addresses >= 1024 {
+        format!(
+            "Addresses:   {} ({})",
+            thousands(addresses),
+            binary(addresses)
+        )
+    } else {
+        format!("Addresses:   {addresses}")
+    });
+    match num_az_hosts(cidr.mask) {
+        Ok(hosts) if hosts > 0 => {
+            // Azure reserves .0-.3 and the broadcast address.
+            let first = Ipv4Addr::from(u32::from(network) + 4);
+            let last = Ipv4Addr::from(u32::from(broadcast) - 1);
+            lines.push(format!("Azure hosts: {hosts} ({first} - {last})"));
+        }
+        _ => lines.push("Azure hosts: n/a (smallest Azure subnet is /29)".to_string()),
+    }
+    Ok(lines.join("\n"))
+}
+
+/// List every `/into` subnet inside `cidr`, one per line.
+pub fn split(cidr: Ipv4, into: u8) -> Result<String, Box<dyn Error>> {
+    if into.saturating_sub(cidr.mask) > MAX_SPLIT_BITS {
+        return Err(format!(
+            "Splitting {cidr} into /{into} exceeds {} subnets",
+            1u32 << MAX_SPLIT_BITS
+        )
+        .into());
+    }
+    let parts = split_subnet(cidr, into)?;
+    let mut lines = vec![format!(
+        "{cidr} → {} × {}",
+        parts.len(),
+        size_description(into)
+    )];
+    lines.extend(parts.iter().map(|p| p.to_string()));
+    Ok(lines.join("\n"))
+}
+
+/// Return the subnet following `cidr`, sized `mask` (defaults to the same size).
+pub fn next(cidr: Ipv4, mask: Option<u8>) -> Result<String, Box<dyn Error>> {
+    Ok(next_subnet_ipv4(cidr, mask)?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_reports_azure_host_range() {
+        let out = info(Ipv4::new("10.1.2.0/26").unwrap()).unwrap();
+        assert!(out.contains("Network:     10.1.2.0"));
+        assert!(out.contains("Broadcast:   10.1.2.63"));
+        assert!(out.contains("Netmask:     255.255.255.192"));
+        assert!(out.contains("Addresses:   64"));
+        assert!(out.contains("Azure hosts: 59 (10.1.2.4 - 10.1.2.62)"));
+        assert!(!out.contains("Warning"));
+    }
+
+    #[test]
+    fn info_warns_on_unaligned_and_tiny_subnets() {
+        let out = info(Ipv4::new("10.1.2.5/30").unwrap()).unwrap();
+        assert!(out.contains("Warning: 10.1.2.5/30 is not aligned; network is 10.1.2.4/30"));
+        assert!(out.contains("n/a (smallest Azure subnet is /29)"));
+    }
+
+    #[test]
+    fn split_lists_every_subnet() {
+        let out = split(Ipv4::new("10.0.0.0/22").unwrap(), 26).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "10.0.0.0/22 → 16 × /26 = 59 az hosts");
+        assert_eq!(lines[1], "10.0.0.0/26");
+        assert_eq!(lines[16], "10.0.3.192/26");
+        assert_eq!(lines.len(), 17);
+    }
+
+    #[test]
+    fn split_rejects_huge_or_larger_targets() {
+        assert!(split(Ipv4::new("10.0.0.0/8").unwrap(), 32).is_err());
+        assert!(split(Ipv4::new("10.0.0.0/24").unwrap(), 22).is_err());
+    }
+
+    #[test]
+    fn next_follows_input_or_requested_size() {
+        assert_eq!(
+            next(Ipv4::new("10.0.1.0/24").unwrap(), None).unwrap(),
+            "10.0.2.0/24"
+        );
+        assert_eq!(
+            next(Ipv4::new("10.0.1.0/24").unwrap(), Some(26)).unwrap(),
+            "10.0.2.0/26"
+        );
+    }
+}
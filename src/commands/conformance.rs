@@ -0,0 +1,42 @@
+//! `conformance` subcommand — score how well the VNets fit the address plan
+//! (`[[plan]]` blocks per region) in the config file.
+
+use crate::azure::{fetch_subnets, FetchConfig};
+use crate::config::Config;
+use crate::processing::{format_conformance, plan_conformance, validate_plan};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct ConformanceArgs {
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `conformance` subcommand.
+pub fn run(
+    args: &ConformanceArgs,
+    fetch: &FetchConfig,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let plan = Config::load(config_path, profile)?.plan;
+    if plan.is_empty() {
+        return Err("No address plan: add [[plan]] blocks to the config file".into());
+    }
+    validate_plan(&plan)?;
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let subnets = fetch_subnets(&config)?.data;
+    let vnets = plan_conformance(&subnets, &plan);
+    println!("{}", format_conformance(&vnets, &plan));
+    Ok(())
+}
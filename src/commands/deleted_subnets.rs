@@ -0,0 +1,51 @@
+//! `deleted-subnets` subcommand — list subnets that disappeared in the last
+//! N days of the snapshot history, with their previous prefix and VNet.
+
+use crate::azure::{fetch_subnets, list_snapshots, read_snapshots_newest_first, FetchConfig};
+use crate::processing::{find_deleted_subnets, format_deleted_subnets};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct DeletedSubnetsArgs {
+    /// Only list subnets deleted in the last N days.
+    #[arg(long, default_value_t = 30, value_name = "N")]
+    pub days: u64,
+    /// Directory holding the `report-<date>/` snapshot history.
+    #[arg(long, default_value = ".", value_name = "DIR")]
+    pub history_dir: String,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `deleted-subnets` subcommand.
+pub fn run(args: &DeletedSubnetsArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let current = fetch_subnets(&config)?.data;
+    // Cache file names are dated by the run's clock; use the same calendar for "today".
+    let today = config.clock.today();
+    let snapshots = list_snapshots(Path::new(&args.history_dir), &config.query, &config.clock);
+    log::info!(
+        "Checking {} earlier snapshot(s) in '{}'",
+        snapshots.iter().filter(|s| s.date < today).count(),
+        args.history_dir
+    );
+    let deleted = find_deleted_subnets(
+        &current,
+        today,
+        read_snapshots_newest_first(&snapshots),
+        args.days,
+    );
+    println!("{}", format_deleted_subnets(&deleted));
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! `dev` subcommands — helpers for working on the tool itself.
+
+use crate::azure::{read_cache_file, Data};
+use crate::processing::anonymize;
+use clap::Subcommand;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Subcommand)]
+pub enum DevCommand {
+    /// Write an anonymized copy of a subnet cache file (random names and
+    /// subscription IDs, CIDRs kept) for bug reports or test data.
+    ///
+    /// Example: dev gen-fixture report-2026-10-15/cache/net_..._cache_subnet_<key>.json -o repro.json
+    GenFixture {
+        /// Subnet cache file to anonymize (`.json` or `.json.zst`).
+        input: PathBuf,
+        /// Output file; prints to stdout when omitted.
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Seed for the pseudonyms; the same seed gives the same fixture.
+        /// Defaults to a random seed.
+        #[arg(long, value_name = "N")]
+        seed: Option<u64>,
+    },
+}
+
+/// Run a `dev` subcommand.
+pub fn run(cmd: &DevCommand) -> Result<(), Box<dyn Error>> {
+    match cmd {
+        DevCommand::GenFixture {
+            input,
+            output,
+            seed,
+        } => gen_fixture(input, output.as_deref(), *seed),
+    }
+}
+
+fn gen_fixture(
+    input: &Path,
+    output: Option<&Path>,
+    seed: Option<u64>,
+) -> Result<(), Box<dyn Error>> {
+    let data: Data = read_cache_file(input)?;
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+    let json = serde_json::to_string_pretty(&anonymize(&data, seed))
+        .map_err(|e| format!("Error serializing JSON: {e}"))?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, json)
+                .map_err(|e| format!("Error writing {}: {e}", path.display()))?;
+            log::info!(
+                "Wrote anonymized fixture ({} subnets, seed {seed}) to {}",
+                data.data.len(),
+                path.display()
+            );
+        }
+        None => println!("{json}"),
+    }
+    Ok(())
+}
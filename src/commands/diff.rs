@@ -0,0 +1,89 @@
+//! `diff` subcommand — subnets added, removed, renamed or changed since an
+//! earlier snapshot, optionally with the Activity Log write behind each.
+
+use crate::azure::{
+    activity_log_writes, fetch_subnets, list_snapshots, read_cache_file, read_cache_meta, Data,
+    FetchConfig, ACTIVITY_LOG_RETENTION_DAYS,
+};
+use crate::clock::Clock;
+use crate::processing::{annotate_changes, diff_subnets, format_subnet_changes, normalize_cidrs};
+use chrono::{DateTime, Duration, Utc};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Earlier subnet cache file to compare against; defaults to the newest
+    /// snapshot before today in `--history-dir`.
+    #[arg(long, value_name = "FILE")]
+    pub from: Option<String>,
+    /// Directory holding the `report-<date>/` snapshot history.
+    #[arg(long, default_value = ".", value_name = "DIR")]
+    pub history_dir: String,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+    /// Annotate each change with the latest write on the subnet (or its
+    /// VNet) in the Azure Activity Log since the earlier snapshot: who, when
+    /// and which operation. Needs Activity Log read access.
+    #[arg(long)]
+    pub activity_log: bool,
+}
+
+/// Start of the Activity Log search: when the earlier snapshot was fetched,
+/// within the Activity Log retention counted back from `clock`.
+fn activity_log_since(from: &Path, clock: &Clock) -> DateTime<Utc> {
+    let oldest = clock.now().to_utc() - Duration::days(ACTIVITY_LOG_RETENTION_DAYS);
+    read_cache_meta(from)
+        .ok()
+        .flatten()
+        .and_then(|meta| meta.generated_at)
+        .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+        .map_or(oldest, |t| t.to_utc().max(oldest))
+}
+
+/// Run the `diff` subcommand.
+pub fn run(args: &DiffArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let from = match &args.from {
+        Some(file) => file.into(),
+        None => {
+            let today = config.clock.today();
+            list_snapshots(Path::new(&args.history_dir), &config.query, &config.clock)
+                .into_iter()
+                .rev()
+                .find(|s| s.date < today)
+                .map(|s| s.path)
+                .ok_or_else(|| {
+                    format!(
+                        "No earlier snapshot in '{}'; pass --from FILE",
+                        args.history_dir
+                    )
+                })?
+        }
+    };
+    log::info!("Comparing against '{}'", from.display());
+    let (old, _) = normalize_cidrs(read_cache_file::<Data>(&from)?);
+    let new = fetch_subnets(&config)?.data;
+    let mut changes = diff_subnets(&old, &new);
+    if args.activity_log {
+        let since = activity_log_since(&from, &config.clock);
+        annotate_changes(&mut changes, &config.clock, |id| {
+            activity_log_writes(id, since).unwrap_or_else(|e| {
+                log::warn!("{e}");
+                Vec::new()
+            })
+        });
+    }
+    println!("{}", format_subnet_changes(&changes));
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! `dns-servers` subcommand — group VNets by their custom DNS servers and
+//! optionally flag servers that no longer live in the estate.
+
+use crate::azure::{fetch_subnets, FetchConfig};
+use crate::config::Config;
+use crate::processing::{dns_server_usage, format_dns_server_usage};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct DnsServersArgs {
+    /// Check that each server address is in a known subnet or a configured
+    /// on-prem `[[range]]`, and fail when one is not.
+    #[arg(long)]
+    pub check_resolvers: bool,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `dns-servers` subcommand.
+pub fn run(
+    args: &DnsServersArgs,
+    fetch: &FetchConfig,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let ranges = Config::load(config_path, profile)?.range;
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let subnets = fetch_subnets(&config)?.data;
+    let usage = dns_server_usage(&subnets, &ranges, args.check_resolvers);
+    println!("{}", format_dns_server_usage(&usage));
+    let dead = usage.iter().filter(|u| u.is_dead()).count();
+    if dead > 0 {
+        return Err(format!("{dead} DNS server(s) are not in any known subnet or range").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! `doctor` subcommand — preflight checks for the Azure CLI, login, Resource
+//! Graph access and the config file, each with an actionable fix on failure.
+
+use crate::azure::{is_offline, run_az};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::pipeline::Args as ReportArgs;
+use clap::Parser;
+use std::error::Error;
+
+/// Outcome of running one `az` invocation.
+#[derive(Debug)]
+pub enum AzFailure {
+    /// The `az` executable could not be started.
+    NotFound,
+    /// `az` ran but failed; holds its stderr.
+    Failed(String),
+    /// `--offline` forbids running `az`.
+    Offline,
+}
+
+/// Result of a single check: `Ok(detail)` or `Err(problem + fix)`.
+pub type CheckResult = Result<String, String>;
+
+/// Run `az` with `args`, returning stdout.
+fn az(args: &[&str]) -> Result<String, AzFailure> {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    run_az(&args).map_err(|e| {
+        let not_found = e
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound);
+        if is_offline() {
+            AzFailure::Offline
+        } else if not_found {
+            AzFailure::NotFound
+        } else {
+            let e = e.to_string();
+            AzFailure::Failed(
+                e.strip_prefix("ERROR running: ")
+                    .unwrap_or(&e)
+                    .trim()
+                    .to_string(),
+            )
+        }
+    })
+}
+
+const AZ_NOT_FOUND: &str =
+    "Azure CLI `az` not found on PATH — install it from https://aka.ms/installazurecli";
+
+const AZ_OFFLINE: &str = "not checked: --offline forbids running `az`";
+
+/// Check that `az` is installed, reporting its version.
+pub fn check_cli(az: &dyn Fn(&[&str]) -> Result<String, AzFailure>) -> CheckResult {
+    match az(&["version", "--output", "json"]) {
+        Ok(out) => {
+            let version = serde_json::from_str::<serde_json::Value>(&out)
+                .ok()
+                .and_then(|v| v["azure-cli"].as_str().map(str::to_string))
+                .unwrap_or_else(|| "unknown version".to_string());
+            Ok(format!("azure-cli {version}"))
+        }
+        Err(AzFailure::NotFound) => Err(AZ_NOT_FOUND.to_string()),
+        Err(AzFailure::Offline) => Err(AZ_OFFLINE.to_string()),
+        Err(AzFailure::Failed(e)) => Err(format!("`az version` failed: {e}")),
+    }
+}
+
+/// Check that `az` is logged in, reporting the account and subscription.
+pub fn check_login(az: &dyn Fn(&[&str]) -> Result<String, AzFailure>) -> CheckResult {
+    match az(&["account", "show", "--output", "json"]) {
+        Ok(out) => {
+            let account: serde_json::Value = serde_json::from_str(&out)
+                .map_err(|e| format!("Unexpected `az account show` output: {e}"))?;
+            Ok(format!(
+                "{} on subscription '{}'",
+                account["user"]["name"].as_str().unwrap_or("unknown user"),
+                account["name"].as_str().unwrap_or("unknown")
+            ))
+        }
+        Err(AzFailure::NotFound) => Err(AZ_NOT_FOUND.to_string()),
+        Err(AzFailure::Offline) => Err(AZ_OFFLINE.to_string()),
+        Err(AzFailure::Failed(_)) => Err("Not logged in — run `az login`".to_string()),
+    }
+}
+
+/// Check Resource Graph access by running a 1-record query.
+pub fn check_resource_graph(az: &dyn Fn(&[&str]) -> Result<String, AzFailure>) -> CheckResult {
+    let query =
+        "resources | where type =~ 'microsoft.network/virtualnetworks' | project id | limit 1";
+    match az(&[
+        "graph", "query", "--first", "1", "-q", query, "--output", "json",
+    ]) {
+        Ok(out) => {
+            let count = serde_json::from_str::<serde_json::Value>(&out)
+                .ok()
+                .and_then(|v| v["count"].as_u64())
+                .unwrap_or(0);
+            if count == 0 {
+                Ok("query works, but no VNets are visible — check Reader access on the subscriptions".to_string())
+            } else {
+                Ok("query works, VNets are visible".to_string())
+            }
+        }
+        Err(AzFailure::NotFound) => Err(AZ_NOT_FOUND.to_string()),
+        Err(AzFailure::Offline) => Err(AZ_OFFLINE.to_string()),
+        Err(AzFailure::Failed(e)) if e.contains("graph") && e.contains("extension") => Err(
+            "Resource Graph extension missing — run `az extension add --name resource-graph`"
+                .to_string(),
+        ),
+        Err(AzFailure::Failed(e)) if e.contains("Authorization") || e.contains("Forbidden") => Err(
+            format!("No Resource Graph permission — grant Reader on the subscriptions ({e})"),
+        ),
+        Err(AzFailure::Failed(e)) => Err(format!("Resource Graph query failed: {e}")),
+    }
+}
+
+/// Check the config file parses, its values are in range and its timezone /
+/// date format are usable.
+pub fn check_config(path: Option<&str>) -> CheckResult {
+    let loaded = Config::load(path, None)
+        .and_then(|config| {
+            ReportArgs::parse_from(["azure-subnet-summary"]).apply_config(&config)?;
+            Ok(config)
+        })
+        .and_then(|config| Clock::new(config.timezone.as_deref(), config.date_format.as_deref()));
+    match loaded {
+        Ok(_) => Ok(format!(
+            "'{}' is valid (or absent)",
+            path.unwrap_or(crate::config::DEFAULT_CONFIG_FILE)
+        )),
+        Err(e) => Err(format!("{e} — fix or remove the file")),
+    }
+}
+
+/// Run all checks with `az`, printing one line per check.
+pub fn run(config_path: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let checks: [(&str, CheckResult); 4] = [
+        ("Azure CLI", check_cli(&az)),
+        ("Login", check_login(&az)),
+        ("Resource Graph", check_resource_graph(&az)),
+        ("Config", check_config(config_path)),
+    ];
+    let mut problems = 0;
+    for (name, result) in &checks {
+        match result {
+            Ok(detail) => println!("✔ {name}: {detail}"),
+            Err(fix) => {
+                problems += 1;
+                println!("✘ {name}: {fix}");
+            }
+        }
+    }
+    if problems > 0 {
+        return Err(format!("doctor found {problems} problem(s)").into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missing(_: &[&str]) -> Result<String, AzFailure> {
+        Err(AzFailure::NotFound)
+    }
+
+    #[test]
+    fn missing_az_gives_install_hint_for_every_az_check() {
+        for result in [
+            check_cli(&missing),
+            check_login(&missing),
+            check_resource_graph(&missing),
+        ] {
+            assert!(result
+                .unwrap_err()
+                .contains("https://aka.ms/installazurecli"));
+        }
+    }
+
+    #[test]
+    fn offline_leaves_every_az_check_unchecked() {
+        let offline = |_: &[&str]| Err(AzFailure::Offline);
+        for result in [
+            check_cli(&offline),
+            check_login(&offline),
+            check_resource_graph(&offline),
+        ] {
+            assert_eq!(result.unwrap_err(), AZ_OFFLINE);
+        }
+    }
+
+    #[test]
+    fn cli_and_login_report_details() {
+        let fake = |args: &[&str]| -> Result<String, AzFailure> {
+            match args[0] {
+                "version" => Ok(r#"{"azure-cli": "2.61.0"}"#.to_string()),
+                "account" => {
+                    Ok(r#"{"name": "Prod", "user": {"name": "me@example.com"}}"#.to_string())
+                }
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(check_cli(&fake).unwrap(), "azure-cli 2.61.0");
+        assert_eq!(
+            check_login(&fake).unwrap(),
+            "me@example.com on subscription 'Prod'"
+        );
+    }
+
+    #[test]
+    fn login_failure_suggests_az_login() {
+        let fake = |_: &[&str]| Err(AzFailure::Failed("Please run 'az login'".to_string()));
+        assert_eq!(
+            check_login(&fake).unwrap_err(),
+            "Not logged in — run `az login`"
+        );
+    }
+
+    #[test]
+    fn resource_graph_errors_are_actionable() {
+        let no_ext = |_: &[&str]| {
+            Err(AzFailure::Failed(
+                "'graph' is misspelled or not recognized; the extension resource-graph is not installed".to_string(),
+            ))
+        };
+        assert!(check_resource_graph(&no_ext)
+            .unwrap_err()
+            .contains("az extension add --name resource-graph"));
+
+        let one = |_: &[&str]| Ok(r#"{"count": 1, "data": [{"id": "x"}]}"#.to_string());
+        assert_eq!(
+            check_resource_graph(&one).unwrap(),
+            "query works, VNets are visible"
+        );
+    }
+
+    #[test]
+    fn invalid_config_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bad.toml");
+        std::fs::write(&file, "nope = 1").unwrap();
+        let err = check_config(Some(file.to_str().unwrap())).unwrap_err();
+        assert!(err.contains("unknown field"), "got: {err}");
+
+        std::fs::write(&file, "timezone = \"Mars/Olympus\"").unwrap();
+        let err = check_config(Some(file.to_str().unwrap())).unwrap_err();
+        assert!(err.contains("Invalid timezone"), "got: {err}");
+    }
+}
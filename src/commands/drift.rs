@@ -0,0 +1,75 @@
+//! `drift` subcommand — compare the estate with an approved address plan
+//! file kept in git, or export the current estate as a new one.
+
+use crate::azure::{fetch_subnets, FetchConfig};
+use crate::processing::{baseline_drift, format_baseline_drift, Baseline};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+
+/// Baseline file used without `--baseline`.
+pub const DEFAULT_BASELINE_FILE: &str = "address-plan.toml";
+
+#[derive(Debug, Args)]
+pub struct DriftArgs {
+    /// Approved address plan file (TOML).
+    #[arg(long, default_value = DEFAULT_BASELINE_FILE, value_name = "FILE")]
+    pub baseline: String,
+    /// Write the current estate to the baseline file instead of comparing,
+    /// to review and commit as the approved plan.
+    #[arg(long)]
+    pub export: bool,
+    /// Overwrite an existing baseline file with `--export`.
+    #[arg(long, requires = "export")]
+    pub force: bool,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `drift` subcommand; fails when the estate drifted from the plan.
+pub fn run(args: &DriftArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let path = Path::new(&args.baseline);
+    if args.export && path.exists() && !args.force {
+        return Err(format!(
+            "'{}' exists; it is the approved plan, pass --force to replace it",
+            args.baseline
+        )
+        .into());
+    }
+    let subnets = fetch_subnets(&config)?.data;
+
+    if args.export {
+        let baseline = Baseline::from_data(&subnets);
+        std::fs::write(path, baseline.to_toml()?)
+            .map_err(|e| format!("Error writing {}: {e}", args.baseline))?;
+        println!(
+            "Wrote {} VNet(s) to '{}'; review and commit it as the approved plan",
+            baseline.vnets.len(),
+            args.baseline
+        );
+        return Ok(());
+    }
+
+    let baseline = Baseline::load(path)?;
+    let drift = baseline_drift(&baseline, &subnets);
+    println!("{}", format_baseline_drift(&drift));
+    if !drift.is_empty() {
+        return Err(format!(
+            "{} difference(s) from the approved plan '{}'",
+            drift.len(),
+            args.baseline
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! `empty-subnets` subcommand — list subnets that stayed empty across the
+//! snapshot history as reclamation candidates.
+
+use crate::azure::{fetch_subnets, list_snapshots, read_snapshots_newest_first, FetchConfig};
+use crate::processing::{find_empty_subnets, format_empty_subnets};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct EmptySubnetsArgs {
+    /// Only list subnets empty for at least this many days.
+    #[arg(long, default_value_t = 30, value_name = "N")]
+    pub days: i64,
+    /// Directory holding the `report-<date>/` snapshot history.
+    #[arg(long, default_value = ".", value_name = "DIR")]
+    pub history_dir: String,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `empty-subnets` subcommand.
+pub fn run(args: &EmptySubnetsArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let current = fetch_subnets(&config)?.data;
+    // Cache file names are dated by the run's clock; use the same calendar for "today".
+    let today = config.clock.today();
+    let snapshots = list_snapshots(Path::new(&args.history_dir), &config.query, &config.clock);
+    log::info!(
+        "Checking {} earlier snapshot(s) in '{}'",
+        snapshots.iter().filter(|s| s.date < today).count(),
+        args.history_dir
+    );
+    let empty = find_empty_subnets(
+        &current,
+        today,
+        read_snapshots_newest_first(&snapshots),
+        args.days,
+    );
+    println!("{}", format_empty_subnets(&empty));
+    Ok(())
+}
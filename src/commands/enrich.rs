@@ -0,0 +1,225 @@
+//! `enrich` subcommand — append subnet, VNet, NSG and subscription columns to
+//! an inventory CSV (e.g. a CMDB export) of IP addresses or NIC resource IDs.
+
+use crate::azure::{fetch_nics, fetch_subnets, FetchConfig};
+use crate::models::Subnet;
+use crate::processing::SubnetIndex;
+use clap::Args;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+
+/// Columns appended to every row.
+const ENRICH_COLUMNS: [&str; 7] = [
+    "subnet_name",
+    "subnet_cidr",
+    "vnet_name",
+    "nsg",
+    "subscription_name",
+    "subscription_id",
+    "subnet_id",
+];
+
+#[derive(Debug, Args)]
+pub struct EnrichArgs {
+    /// Inventory CSV with a header row.
+    pub input: PathBuf,
+    /// Column holding the IP address or NIC resource ID (default: first column).
+    #[arg(long, value_name = "NAME")]
+    pub column: Option<String>,
+    /// Write the enriched CSV here instead of stdout.
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+    /// NIC cache file to read instead of today's cache (only read when the
+    /// input has NIC resource IDs).
+    #[arg(long, value_name = "FILE")]
+    pub nic_cache_file: Option<String>,
+}
+
+/// Run the `enrich` subcommand.
+pub fn run(args: &EnrichArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        nic_cache: args.nic_cache_file.clone().or(fetch.nic_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let input = std::fs::read_to_string(&args.input)
+        .map_err(|e| format!("Error reading {}: {e}", args.input.display()))?;
+    let subnets = fetch_subnets(&config)?.data;
+    let index = SubnetIndex::new(&subnets);
+    let nic_subnets: HashMap<String, String> =
+        if input.to_ascii_lowercase().contains("/networkinterfaces/") {
+            fetch_nics(&config)?
+                .data
+                .data
+                .into_iter()
+                .map(|n| (n.nic_id.to_lowercase(), n.subnet_id))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+    let resolve = |key: &str| -> Option<&Subnet> {
+        if let Ok(ip) = key.parse::<Ipv4Addr>() {
+            return index.find_ip(ip);
+        }
+        // NIC IDs may point at an IP configuration: .../networkInterfaces/<nic>/ipConfigurations/<c>
+        let lower = key.to_lowercase();
+        let nic_id = match lower.find("/ipconfigurations/") {
+            Some(pos) => &lower[..pos],
+            None => &lower,
+        };
+        index.find_id(nic_subnets.get(nic_id)?)
+    };
+    let enriched = enrich_csv(&input, args.column.as_deref(), resolve)?;
+
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, enriched)
+                .map_err(|e| format!("Error writing {}: {e}", path.display()))?;
+            log::info!("Wrote enriched CSV '{}'", path.display());
+        }
+        None => std::io::stdout().write_all(enriched.as_bytes())?,
+    }
+    Ok(())
+}
+
+/// Append [`ENRICH_COLUMNS`] to every row of `input`, resolving the value in
+/// `column` (default: the first) with `resolve`. Unresolved rows get empty
+/// columns and are counted in a warning.
+///
+/// Quoted fields are supported, but not line breaks inside them.
+pub fn enrich_csv<'a>(
+    input: &str,
+    column: Option<&str>,
+    resolve: impl Fn(&str) -> Option<&'a Subnet>,
+) -> Result<String, Box<dyn Error>> {
+    let mut lines = input.lines();
+    let header_line = lines.next().ok_or("Inventory CSV is empty")?;
+    let header = split_csv_line(header_line);
+    let key_col = match column {
+        Some(name) => header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Column '{name}' not found in header: {header_line}"))?,
+        None => 0,
+    };
+
+    let mut out = String::new();
+    out.push_str(header_line);
+    for name in ENRICH_COLUMNS {
+        out.push(',');
+        out.push_str(name);
+    }
+    out.push('\n');
+
+    let mut unresolved = 0;
+    for line in lines.filter(|l| !l.trim().is_empty()) {
+        let fields = split_csv_line(line);
+        let key = fields.get(key_col).map(|f| f.trim()).unwrap_or_default();
+        let subnet = resolve(key);
+        if subnet.is_none() {
+            unresolved += 1;
+        }
+        let extra: [String; 7] = match subnet {
+            Some(s) => [
+                s.subnet_name.clone(),
+                s.subnet_cidr.map(|c| c.to_string()).unwrap_or_default(),
+                s.vnet_name.clone(),
+                s.nsg
+                    .as_deref()
+                    .and_then(|id| id.rsplit('/').next())
+                    .unwrap_or_default()
+                    .to_string(),
+                s.subscription_name.clone(),
+                s.subscription_id.clone(),
+                s.subnet_id.clone(),
+            ],
+            None => Default::default(),
+        };
+        out.push_str(line);
+        for value in extra {
+            out.push(',');
+            out.push_str(&quote_csv_field(&value));
+        }
+        out.push('\n');
+    }
+    if unresolved > 0 {
+        log::warn!("{unresolved} inventory row(s) did not resolve to a subnet");
+    }
+    Ok(out)
+}
+
+/// Split one CSV line into fields, honouring double-quoted fields.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn quote_csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::Data;
+
+    #[test]
+    fn rows_get_subnet_columns_by_ip_in_named_column() {
+        let s = Subnet {
+            nsg: Some("/subscriptions/s/networkSecurityGroups/nsg-app".to_string()),
+            subscription_name: "Prod, EU".to_string(),
+            subscription_id: "sub-1".to_string(),
+            ..Subnet::test("hub", "app", "10.0.1.0/24")
+        };
+        let data = Data {
+            data: vec![s],
+            ..Default::default()
+        };
+        let index = SubnetIndex::new(&data);
+        let input =
+            "host,\"owner, team\",ip\nvm1,\"Ops, \"\"core\"\"\",10.0.1.7\nvm2,Dev,192.168.0.1\n";
+        let out = enrich_csv(input, Some("IP"), |key| index.find_ip(key.parse().ok()?)).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "host,\"owner, team\",ip,subnet_name,subnet_cidr,vnet_name,nsg,subscription_name,subscription_id,subnet_id"
+        );
+        assert_eq!(
+            lines[1],
+            "vm1,\"Ops, \"\"core\"\"\",10.0.1.7,app,10.0.1.0/24,hub,nsg-app,\"Prod, EU\",sub-1,"
+        );
+        assert_eq!(lines[2], "vm2,Dev,192.168.0.1,,,,,,,");
+        assert!(enrich_csv(input, Some("nic"), |_| None).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! `ipam-pools` subcommand — compare Azure Virtual Network Manager IPAM pool
+//! allocations with the VNets that actually exist and report drift.
+
+use crate::azure::{fetch_ipam_pools, fetch_subnets, FetchConfig};
+use crate::processing::{format_ipam_reconciliation, reconcile_ipam_pools};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct IpamPoolsArgs {
+    /// Only print drift, not the per-pool usage.
+    #[arg(long)]
+    pub drift_only: bool,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+    /// IPAM pool cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub pool_cache_file: Option<String>,
+}
+
+/// Run the `ipam-pools` subcommand.
+pub fn run(args: &IpamPoolsArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        ipam_pool_cache: args
+            .pool_cache_file
+            .clone()
+            .or(fetch.ipam_pool_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let pools = fetch_ipam_pools(&config)?.data;
+    let subnets = fetch_subnets(&config)?.data;
+    let mut reconciliation = reconcile_ipam_pools(&pools.data, &subnets);
+    if !reconciliation.drift.is_empty() {
+        log::warn!(
+            "{} IPAM pool record(s) disagree with the VNets in Azure",
+            reconciliation.drift.len()
+        );
+    }
+    if args.drift_only {
+        if reconciliation.drift.is_empty() {
+            println!("No IPAM drift found.");
+            return Ok(());
+        }
+        reconciliation.usage.clear();
+    }
+    println!("{}", format_ipam_reconciliation(&reconciliation));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::IpamDriftKind;
+
+    #[test]
+    fn fixture_pool_reports_allocation_for_deleted_vnet() {
+        let config = FetchConfig {
+            subnet_cache: Some("src/tests/test_data/subnet_test_cache_01.json".to_string()),
+            ipam_pool_cache: Some("src/tests/test_data/ipam_pool_test_cache_01.json".to_string()),
+            ..FetchConfig::default()
+        };
+        let pools = fetch_ipam_pools(&config).expect("fixture").data;
+        let subnets = fetch_subnets(&config).expect("fixture").data;
+        let reconciliation = reconcile_ipam_pools(&pools.data, &subnets);
+
+        let kinds: Vec<IpamDriftKind> = reconciliation.drift.iter().map(|d| d.kind).collect();
+        assert_eq!(kinds, [IpamDriftKind::MissingVnet]);
+        let usage = &reconciliation.usage[0];
+        assert_eq!((usage.allocated, usage.in_use), (3072, 2048));
+    }
+}
@@ -0,0 +1,201 @@
+//! `log-analytics` subcommand — send the subnet and gap rows to a Log
+//! Analytics custom table through the Logs Ingestion API.
+//!
+//! Rows are posted to a data collection rule (DCR) stream on its ingestion
+//! endpoint (a data collection endpoint, or the DCR's own endpoint), as the
+//! signed-in `az` identity, which needs the *Monitoring Metrics Publisher*
+//! role on the DCR. Every record carries `TimeGenerated`, the snapshot time,
+//! so nightly runs can be told apart and joined with other inventory in KQL.
+
+use crate::azure::{access_token, fetch_subnets, FetchConfig};
+use crate::config::Config;
+use crate::output::build_rows;
+use crate::pipeline::resolve_subnets;
+use crate::processing::{IgnoreList, SubnetPrintRow};
+use clap::Args;
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde_json::Value;
+use std::error::Error;
+
+/// Logs Ingestion API version.
+const INGESTION_API_VERSION: &str = "2023-01-01";
+
+/// Token audience of the Logs Ingestion API.
+const MONITOR_RESOURCE: &str = "https://monitor.azure.com";
+
+/// Largest request body the API accepts is 1 MB; stay below it.
+const MAX_BATCH_BYTES: usize = 900 * 1024;
+
+#[derive(Debug, Args)]
+pub struct LogAnalyticsArgs {
+    /// Logs ingestion endpoint of the data collection endpoint or rule, e.g.
+    /// `https://dce-net-abcd.westeurope-1.ingest.monitor.azure.com`.
+    #[arg(long, value_name = "URL")]
+    pub endpoint: String,
+    /// Immutable ID of the data collection rule (`dcr-...`).
+    #[arg(long, value_name = "ID")]
+    pub dcr: String,
+    /// Stream declared in the DCR.
+    #[arg(long, default_value = "Custom-SubnetSummary_CL", value_name = "NAME")]
+    pub stream: String,
+    /// Minimum gap-block mask of the gap rows (see the main `--gap-mask`).
+    #[arg(long, default_value_t = 4, value_name = "N")]
+    pub gap_mask: u8,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Ingestion URL of `stream` in the DCR `dcr`.
+pub fn ingestion_url(endpoint: &str, dcr: &str, stream: &str) -> Result<Url, Box<dyn Error>> {
+    if !dcr.starts_with("dcr-") {
+        return Err(format!("'{dcr}' is not a DCR immutable ID (dcr-...)").into());
+    }
+    Ok(Url::parse_with_params(
+        &format!(
+            "{}/dataCollectionRules/{dcr}/streams/{stream}",
+            endpoint.trim_end_matches('/')
+        ),
+        &[("api-version", INGESTION_API_VERSION)],
+    )?)
+}
+
+/// `rows` as ingestion records, each with `TimeGenerated` set to `generated_at`.
+pub fn ingestion_records(
+    rows: &[SubnetPrintRow],
+    generated_at: &str,
+) -> Result<Vec<Value>, Box<dyn Error>> {
+    rows.iter()
+        .map(|row| {
+            let mut record = serde_json::to_value(row)?;
+            record["TimeGenerated"] = Value::from(generated_at);
+            Ok(record)
+        })
+        .collect()
+}
+
+/// Split `records` into JSON array bodies of at most `max_bytes` each (a
+/// single larger record gets a body of its own).
+pub fn ingestion_batches(records: &[Value], max_bytes: usize) -> Vec<String> {
+    let mut batches = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut size = 2;
+    for record in records {
+        let json = record.to_string();
+        if !current.is_empty() && size + json.len() + 1 > max_bytes {
+            batches.push(format!("[{}]", current.join(",")));
+            current.clear();
+            size = 2;
+        }
+        size += json.len() + 1;
+        current.push(json);
+    }
+    if !current.is_empty() {
+        batches.push(format!("[{}]", current.join(",")));
+    }
+    batches
+}
+
+/// Run the `log-analytics` subcommand.
+pub fn run(
+    args: &LogAnalyticsArgs,
+    fetch: &FetchConfig,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let url = ingestion_url(&args.endpoint, &args.dcr, &args.stream)?;
+    let ignore = IgnoreList::from_rules(&Config::load(config_path, profile)?.ignore);
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let mut subnets = fetch_subnets(&config)?.data;
+    subnets.data.sort_by_key(|s| s.subnet_cidr);
+    let resolved = resolve_subnets(subnets, &ignore)?;
+    let rows = build_rows(
+        &resolved.active.data,
+        &resolved.excluded,
+        args.gap_mask,
+        &[],
+    );
+    let records = ingestion_records(&rows, &config.clock.timestamp())?;
+    let batches = ingestion_batches(&records, MAX_BATCH_BYTES);
+
+    let token = access_token(MONITOR_RESOURCE)?;
+    let client = Client::new();
+    for (i, body) in batches.iter().enumerate() {
+        let response = client
+            .post(url.clone())
+            .bearer_auth(&token)
+            .header("Content-Type", "application/json")
+            .body(body.clone())
+            .send()
+            .map_err(|e| format!("Error sending batch {} to Log Analytics: {e}", i + 1))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(format!(
+                "Log Analytics ingestion of batch {} failed: {status} {}",
+                i + 1,
+                body.trim()
+            )
+            .into());
+        }
+    }
+    println!(
+        "Sent {} rows in {} request(s) to stream '{}' of {}",
+        records.len(),
+        batches.len(),
+        args.stream,
+        args.dcr
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Ipv4, Subnet};
+
+    #[test]
+    fn rows_become_timestamped_records_in_size_limited_batches() {
+        let url = ingestion_url(
+            "https://dce-net.westeurope-1.ingest.monitor.azure.com/",
+            "dcr-0123abcd",
+            "Custom-SubnetSummary_CL",
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://dce-net.westeurope-1.ingest.monitor.azure.com/dataCollectionRules/dcr-0123abcd/streams/Custom-SubnetSummary_CL?api-version=2023-01-01"
+        );
+        assert!(ingestion_url("https://dce", "my-rule", "Custom-X").is_err());
+
+        let s = Subnet {
+            vnet_cidr: Ipv4::new("10.0.0.0/24").unwrap(),
+            ..Subnet::test("hub", "app", "10.0.0.0/26")
+        };
+        let rows = build_rows(&[s], &[], 4, &[]);
+        let records = ingestion_records(&rows, "2026-10-15T02:00:00Z").unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["TimeGenerated"], "2026-10-15T02:00:00Z");
+        assert_eq!(records[0]["subnet_name"], "app");
+
+        let one = ingestion_batches(&records, MAX_BATCH_BYTES);
+        assert_eq!(one.len(), 1);
+        let parsed: Vec<Value> = serde_json::from_str(&one[0]).unwrap();
+        assert_eq!(parsed, records);
+        let small = records[0].to_string().len() + 3;
+        let split = ingestion_batches(&records, small);
+        assert_eq!(split.len(), 3);
+        assert!(split
+            .iter()
+            .all(|b| b.starts_with("[{") && b.ends_with("}]")));
+    }
+}
@@ -0,0 +1,213 @@
+//! Subcommands that run instead of the default subnet report.
+//!
+//! - [`check`] - Findings with severities; fails on unsuppressed ones (CI gate)
+//! - [`cidr`] - Standalone IP calculator using Azure host accounting
+//! - [`conformance`] - Address plan conformance score and VNets outside their region's block
+//! - [`deleted_subnets`] - Subnets deleted in the last N days of snapshot history
+//! - [`dev`] - Developer helpers (anonymized fixture generation)
+//! - [`diff`] - Subnet changes (added, removed, renamed, modified) since an earlier snapshot
+//! - [`drift`] - Drift from an approved address plan file kept in git, and its export
+//! - [`dns_servers`] - VNets grouped by custom DNS server, dead resolver detection
+//! - [`doctor`] - Preflight checks for az CLI, login, Resource Graph and config
+//! - [`enrich`] - Subnet / VNet / NSG / subscription columns for an IP or NIC inventory CSV
+//! - [`empty_subnets`] - Long-empty subnets from snapshot history (reclamation candidates)
+//! - [`ipam_pools`] - AVNM IPAM pool usage and drift against actual VNets
+//! - [`log_analytics`] - Subnet and gap rows sent to a Log Analytics custom table
+//! - [`public_ips`] - Public IP address inventory per subscription
+//! - [`publish`] - Report upload to an Azure DevOps wiki page or a GitHub repository file
+//! - [`schema`] - JSON Schemas of the subnet, finding, report and cache outputs
+//! - [`serve`] - HTTP server refreshing the snapshot and reports on a cron schedule
+//! - [`simulate`] - What-if reclaim / add analysis on the subnet data
+//! - [`split`] - Subnet splitting planner
+//! - [`subnet`] - Drill-down into one subnet: what uses its addresses
+//! - [`subnets_by_nsg`] - Subnets grouped by NSG, shared NSGs first
+//! - [`suggest`] - Free CIDR suggestion with optional IaC output
+//! - [`summarize_routes`] - Summarized VNet prefix list for on-prem advertisement
+//! - [`verify`] - minisign signature check of report files (`--sign`)
+//! - [`wasted_space`] - Address space wasted by oversized subnets (peak usage from history)
+
+pub mod check;
+pub mod cidr;
+pub mod conformance;
+pub mod deleted_subnets;
+pub mod dev;
+pub mod diff;
+pub mod dns_servers;
+pub mod doctor;
+pub mod drift;
+pub mod empty_subnets;
+pub mod enrich;
+pub mod ipam_pools;
+pub mod log_analytics;
+pub mod public_ips;
+pub mod publish;
+pub mod schema;
+pub mod serve;
+pub mod simulate;
+pub mod split;
+pub mod subnet;
+pub mod subnets_by_nsg;
+pub mod suggest;
+pub mod summarize_routes;
+pub mod verify;
+pub mod wasted_space;
+
+use crate::azure::FetchConfig;
+use crate::codegen::{emit, Emit, SubnetSpec};
+use crate::pipeline;
+use clap::Subcommand;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Optional subcommand; without one the full subnet report is generated.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Report findings (missing NSG, resize, ...) and fail on unsuppressed
+    /// ones at or above `--fail-on`; known issues go in a suppressions file.
+    Check(check::CheckArgs),
+    /// IP math helpers (info, split, next) with Azure-specific host counts.
+    #[command(subcommand)]
+    Cidr(cidr::CidrCommand),
+    /// Score how well the VNets fit the address plan (`[[plan]]` blocks per
+    /// region) and list VNets allocated outside their region's block.
+    Conformance(conformance::ConformanceArgs),
+    /// List subnets deleted in the last N days (from the snapshot history)
+    /// with their previous prefix and whether it has been reused.
+    DeletedSubnets(deleted_subnets::DeletedSubnetsArgs),
+    /// Developer helpers, e.g. anonymized test fixtures.
+    #[command(subcommand)]
+    Dev(dev::DevCommand),
+    /// Compare today's subnets with an earlier snapshot: added, removed,
+    /// renamed (recreated at the same prefix) and changed subnets.
+    Diff(diff::DiffArgs),
+    /// Group VNets by their custom DNS servers; `--check-resolvers` flags
+    /// servers outside every known subnet and on-prem range.
+    DnsServers(dns_servers::DnsServersArgs),
+    /// Compare the estate with an approved address plan file kept in git
+    /// (VNets, address space, subnet prefixes) and fail on drift;
+    /// `--export` writes the current estate as a new plan to review.
+    Drift(drift::DriftArgs),
+    /// Check az CLI install, login, Resource Graph access and the config file.
+    Doctor,
+    /// List subnets with no IP configurations for N days (from the snapshot
+    /// history), largest first, with reclaimable space per VNet.
+    EmptySubnets(empty_subnets::EmptySubnetsArgs),
+    /// Append subnet, VNet, NSG and subscription columns to an inventory CSV
+    /// of IP addresses or NIC resource IDs.
+    Enrich(enrich::EnrichArgs),
+    /// Compare Azure Virtual Network Manager IPAM pool allocations with the
+    /// VNets in Azure and report drift.
+    IpamPools(ipam_pools::IpamPoolsArgs),
+    /// Send the subnet and gap rows to a Log Analytics custom table through
+    /// the Logs Ingestion API (data collection rule stream).
+    LogAnalytics(log_analytics::LogAnalyticsArgs),
+    /// List public IP addresses per subscription with what they are attached to.
+    PublicIps(public_ips::PublicIpsArgs),
+    /// Push a generated report to an Azure DevOps wiki page or a file in a
+    /// GitHub repository (token from the environment).
+    Publish(publish::PublishArgs),
+    /// Print the JSON Schema of the subnet, finding, report or cache file
+    /// output, for generating types and validating payloads.
+    Schema(schema::SchemaArgs),
+    /// Run as a server: refresh the snapshot and regenerate the reports on a
+    /// cron schedule, with the last run's status at `GET /status`.
+    Serve(serve::ServeArgs),
+    /// What-if analysis on the subnet data (nothing is changed in Azure).
+    #[command(subcommand)]
+    Simulate(simulate::SimulateCommand),
+    /// Propose how to split a subnet or free gap into N equal subnets or a mix of sizes.
+    Split(split::SplitArgs),
+    /// Drill down into one subnet, e.g. `subnet show` to list the NICs and
+    /// other resources using its addresses.
+    #[command(subcommand)]
+    Subnet(subnet::SubnetCommand),
+    /// List every NSG with the subnets it protects, NSGs shared across
+    /// VNets first.
+    SubnetsByNsg(subnets_by_nsg::SubnetsByNsgArgs),
+    /// Suggest the first free CIDR of a given size in a VNet.
+    Suggest(suggest::SuggestArgs),
+    /// Summarize VNet address space into a short prefix list to advertise
+    /// on-premises over ExpressRoute / VPN.
+    SummarizeRoutes(summarize_routes::SummarizeRoutesArgs),
+    /// Check the minisign signatures (`<file>.minisig`) of report files
+    /// written with `--sign`, against the publisher's public key.
+    Verify(verify::VerifyArgs),
+    /// Estimate address space wasted by subnets far larger than their peak
+    /// IP usage (from the snapshot history), per VNet and subscription.
+    WastedSpace(wasted_space::WastedSpaceArgs),
+}
+
+/// Run `command`, printing its output to stdout.
+///
+/// `fetch` supplies the cache location and query scope for commands that read
+/// Azure data; `report` holds the top-level options: the `--config` file and
+/// `--profile`, and the report options `serve` regenerates its reports with.
+pub fn run(
+    command: &Command,
+    fetch: &FetchConfig,
+    report: pipeline::Args,
+) -> Result<(), Box<dyn Error>> {
+    let config_path = report.config.as_deref();
+    let profile = report.profile.as_deref();
+    match command {
+        Command::Check(args) => check::run(args, fetch, config_path, profile),
+        Command::Cidr(cmd) => cidr::run(cmd),
+        Command::Conformance(args) => conformance::run(args, fetch, config_path, profile),
+        Command::DeletedSubnets(args) => deleted_subnets::run(args, fetch),
+        Command::Dev(cmd) => dev::run(cmd),
+        Command::Diff(args) => diff::run(args, fetch),
+        Command::DnsServers(args) => dns_servers::run(args, fetch, config_path, profile),
+        Command::Drift(args) => drift::run(args, fetch),
+        Command::Doctor => doctor::run(config_path),
+        Command::EmptySubnets(args) => empty_subnets::run(args, fetch),
+        Command::Enrich(args) => enrich::run(args, fetch),
+        Command::IpamPools(args) => ipam_pools::run(args, fetch),
+        Command::LogAnalytics(args) => log_analytics::run(args, fetch, config_path, profile),
+        Command::PublicIps(args) => public_ips::run(args, fetch),
+        Command::Publish(args) => publish::run(args, config_path, profile),
+        Command::Schema(args) => schema::run(args),
+        Command::Serve(args) => serve::run(args, fetch, report),
+        Command::Simulate(cmd) => simulate::run(cmd, fetch, config_path, profile),
+        Command::Split(args) => split::run(args, config_path, profile),
+        Command::Subnet(cmd) => subnet::run(cmd, fetch),
+        Command::SubnetsByNsg(args) => subnets_by_nsg::run(args, fetch),
+        Command::Suggest(args) => suggest::run(args, fetch, config_path, profile),
+        Command::SummarizeRoutes(args) => summarize_routes::run(args, fetch),
+        Command::Verify(args) => verify::run(args),
+        Command::WastedSpace(args) => wasted_space::run(args, fetch),
+    }
+}
+
+/// Infrastructure-as-code output options shared by `suggest` and `split`.
+#[derive(Debug, clap::Args)]
+pub struct EmitArgs {
+    /// Also print the proposed subnets as infrastructure-as-code.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub emit: Option<Emit>,
+    /// Template file overriding the built-in one for `--emit`
+    /// (placeholders like `{{name}}`, `{{vnet_name}}`, `{{vnet_id}}`, `{{address_prefix}}`).
+    #[arg(long, value_name = "FILE", requires = "emit")]
+    pub template: Option<PathBuf>,
+    /// Resource group of the VNet in generated code. `suggest` defaults to
+    /// the VNet's own resource group; `split` requires it with `--emit`.
+    #[arg(long, value_name = "RG", requires = "emit")]
+    pub resource_group: Option<String>,
+}
+
+impl EmitArgs {
+    /// Render `specs` in the requested format, or `None` without `--emit`.
+    pub fn render(&self, specs: &[SubnetSpec]) -> Result<Option<String>, Box<dyn Error>> {
+        let Some(format) = self.emit else {
+            return Ok(None);
+        };
+        let template = self
+            .template
+            .as_ref()
+            .map(|p| {
+                std::fs::read_to_string(p)
+                    .map_err(|e| format!("Error reading template {}: {e}", p.display()))
+            })
+            .transpose()?;
+        Ok(Some(emit(format, specs, template.as_deref())))
+    }
+}
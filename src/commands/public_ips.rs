@@ -0,0 +1,121 @@
+//! `public-ips` subcommand — inventory public IP addresses per subscription.
+
+use crate::azure::{fetch_public_ips, FetchConfig, PublicIpAssociation, PublicIpRow};
+use clap::Args;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct PublicIpsArgs {
+    /// Only list public IPs in this subscription (ID or name).
+    #[arg(long)]
+    pub subscription: Option<String>,
+    /// Only list public IPs not attached to anything (billed but unused).
+    #[arg(long)]
+    pub unassociated: bool,
+    /// Public IP cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `public-ips` subcommand.
+pub fn run(args: &PublicIpsArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        public_ip_cache: args.cache_file.clone().or(fetch.public_ip_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let data = fetch_public_ips(&config)?.data;
+    let rows: Vec<&PublicIpRow> = data
+        .data
+        .iter()
+        .filter(|r| {
+            args.subscription.as_deref().is_none_or(|sub| {
+                r.subscription_id.eq_ignore_ascii_case(sub)
+                    || r.subscription_name.eq_ignore_ascii_case(sub)
+            })
+        })
+        .filter(|r| !args.unassociated || r.association() == PublicIpAssociation::Unassociated)
+        .collect();
+    println!("{}", format_public_ips(&rows));
+    Ok(())
+}
+
+/// Render public IPs grouped by subscription, with a count per association type.
+pub fn format_public_ips(rows: &[&PublicIpRow]) -> String {
+    let mut by_sub: BTreeMap<(&str, &str), Vec<&PublicIpRow>> = BTreeMap::new();
+    for row in rows {
+        by_sub
+            .entry((row.subscription_name.as_str(), row.subscription_id.as_str()))
+            .or_default()
+            .push(row);
+    }
+
+    let mut lines = Vec::new();
+    for ((sub_name, sub_id), rows) in by_sub {
+        let unassociated = rows
+            .iter()
+            .filter(|r| r.association() == PublicIpAssociation::Unassociated)
+            .count();
+        lines.push(format!(
+            "SUBSCRIPTION: '{sub_name}' ({sub_id}) - {} public IP(s), {unassociated} unassociated",
+            rows.len()
+        ));
+        for r in rows {
+            let ip = if r.ip_address.is_empty() {
+                "(not allocated)"
+            } else {
+                r.ip_address.as_str()
+            };
+            lines.push(format!(
+                "  {ip:<16} {:<30} {:<8} {:<8} {:<20} {}",
+                r.name,
+                r.sku,
+                r.allocation,
+                r.resource_group,
+                r.association()
+            ));
+        }
+    }
+    if lines.is_empty() {
+        lines.push("No public IP addresses found.".to_string());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Vec<PublicIpRow> {
+        let config = FetchConfig {
+            public_ip_cache: Some("src/tests/test_data/public_ip_test_cache_01.json".to_string()),
+            ..FetchConfig::default()
+        };
+        fetch_public_ips(&config).expect("fixture").data.data
+    }
+
+    #[test]
+    fn public_ips_are_grouped_per_subscription_with_association() {
+        let rows = fixture();
+        let refs: Vec<&PublicIpRow> = rows.iter().collect();
+        let out = format_public_ips(&refs);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 5, "{out}");
+        assert!(lines[0].starts_with("SUBSCRIPTION: 'Another Sub'"));
+        assert!(lines[1].ends_with("LB lb-web"));
+        assert!(lines[2].contains("2 public IP(s), 1 unassociated"));
+        assert!(out.contains("NAT GW ngw-egress"));
+        assert!(out.contains("(not allocated)"));
+        assert!(out.contains("UNASSOCIATED"));
+    }
+
+    #[test]
+    fn empty_inventory_says_so() {
+        assert_eq!(format_public_ips(&[]), "No public IP addresses found.");
+    }
+}
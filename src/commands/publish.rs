@@ -0,0 +1,354 @@
+//! `publish` subcommand — push a generated report to an Azure DevOps wiki
+//! page or a file in a GitHub repository through their REST APIs.
+//!
+//! Meant for the nightly pipeline: the page is created on the first run and
+//! replaced on later ones. Tokens come from the environment,
+//! `AZURE_DEVOPS_EXT_PAT` (the variable the `az devops` extension reads) for
+//! Azure DevOps and `GITHUB_TOKEN` or `GH_TOKEN` for GitHub, or else from the
+//! Key Vault secrets referenced by `ado_pat` / `github_token` in the config.
+
+use crate::azure::{SecretRef, KEYVAULT_SCHEME};
+use crate::config::Config;
+use base64::Engine;
+use clap::Args;
+use reqwest::blocking::Client;
+use reqwest::{StatusCode, Url};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::path::PathBuf;
+
+/// GitHub REST API root; `GITHUB_API_URL` overrides it (GitHub Enterprise).
+const GITHUB_API: &str = "https://api.github.com";
+
+/// Azure DevOps wiki REST API version.
+const ADO_API_VERSION: &str = "7.1";
+
+#[derive(Debug, Args)]
+pub struct PublishArgs {
+    /// Report file to publish, e.g. `report-<date>/net_<date>_peering.md`.
+    pub file: PathBuf,
+    /// Azure DevOps wiki, as `https://dev.azure.com/<org>/<project>/_wiki/wikis/<wiki>`.
+    #[arg(long, value_name = "URL", required_unless_present = "github")]
+    pub ado_wiki: Option<String>,
+    /// GitHub repository, as `<owner>/<repo>`.
+    #[arg(long, value_name = "OWNER/REPO", conflicts_with = "ado_wiki")]
+    pub github: Option<String>,
+    /// Wiki page path (`/Network/Subnets`) or file path in the repository
+    /// (`docs/subnets.md`).
+    #[arg(long)]
+    pub path: String,
+    /// Branch to commit to (GitHub; default: the repository's default branch).
+    #[arg(long)]
+    pub branch: Option<String>,
+    /// Commit message (GitHub).
+    #[arg(long, default_value = "Update subnet report")]
+    pub message: String,
+}
+
+/// An Azure DevOps wiki.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdoWiki {
+    /// `https://dev.azure.com/<org>` (or the `<org>.visualstudio.com` form).
+    pub org_url: String,
+    pub project: String,
+    /// Wiki name or ID.
+    pub wiki: String,
+}
+
+/// Parse a wiki URL as shown in the browser,
+/// `https://dev.azure.com/<org>/<project>/_wiki/wikis/<wiki>[/...]`.
+pub fn parse_ado_wiki_url(url: &str) -> Result<AdoWiki, String> {
+    let err = || {
+        format!("'{url}' is not an Azure DevOps wiki URL (https://dev.azure.com/<org>/<project>/_wiki/wikis/<wiki>)")
+    };
+    let (base, rest) = url.split_once("/_wiki/wikis/").ok_or_else(err)?;
+    let wiki = rest
+        .split('/')
+        .next()
+        .filter(|w| !w.is_empty())
+        .ok_or_else(err)?;
+    let (org_url, project) = base
+        .trim_end_matches('/')
+        .rsplit_once('/')
+        .ok_or_else(err)?;
+    if project.is_empty() || !org_url.contains("://") {
+        return Err(err());
+    }
+    Ok(AdoWiki {
+        org_url: org_url.to_string(),
+        project: project.to_string(),
+        wiki: wiki.to_string(),
+    })
+}
+
+/// REST URL of the wiki page at `path`.
+pub fn ado_page_url(wiki: &AdoWiki, path: &str) -> Result<Url, Box<dyn Error>> {
+    let base = format!(
+        "{}/{}/_apis/wiki/wikis/{}/pages",
+        wiki.org_url, wiki.project, wiki.wiki
+    );
+    Ok(Url::parse_with_params(
+        &base,
+        &[("path", path), ("api-version", ADO_API_VERSION)],
+    )?)
+}
+
+/// REST URL of `path` in the GitHub repository `repo` (`owner/repo`).
+pub fn github_contents_url(api: &str, repo: &str, path: &str) -> Result<Url, Box<dyn Error>> {
+    if repo.split('/').filter(|p| !p.is_empty()).count() != 2 {
+        return Err(format!("GitHub repository must be <owner>/<repo>, got '{repo}'").into());
+    }
+    Ok(Url::parse(&format!(
+        "{}/repos/{repo}/contents/{}",
+        api.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    ))?)
+}
+
+/// Body of the GitHub "create or update file contents" request; `sha` is
+/// the blob being replaced, `None` to create the file.
+pub fn github_put_body(
+    content: &[u8],
+    message: &str,
+    branch: Option<&str>,
+    sha: Option<&str>,
+) -> Value {
+    let mut body = json!({
+        "message": message,
+        "content": base64::engine::general_purpose::STANDARD.encode(content),
+    });
+    if let Some(branch) = branch {
+        body["branch"] = json!(branch);
+    }
+    if let Some(sha) = sha {
+        body["sha"] = json!(sha);
+    }
+    body
+}
+
+/// The first non-empty of `vars`, else the value of the `setting` secret.
+fn token(
+    vars: &[&str],
+    setting: &str,
+    secret: Option<&SecretRef>,
+) -> Result<String, Box<dyn Error>> {
+    if let Some(t) = vars
+        .iter()
+        .find_map(|v| std::env::var(v).ok().filter(|t| !t.is_empty()))
+    {
+        return Ok(t);
+    }
+    match secret {
+        Some(secret) => secret.resolve(),
+        None => Err(format!(
+            "Set {} or `{setting} = \"{KEYVAULT_SCHEME}<vault>/<secret>\"` in the config to publish",
+            vars.join(" or ")
+        )
+        .into()),
+    }
+}
+
+/// Error for a failed request, with the response body for context.
+fn request_failed(what: &str, response: reqwest::blocking::Response) -> Box<dyn Error> {
+    let status = response.status();
+    let body = response.text().unwrap_or_default();
+    format!("{what} failed: {status} {}", body.trim()).into()
+}
+
+/// Create or replace the wiki page; returns `true` when it already existed.
+fn publish_ado(
+    client: &Client,
+    wiki: &AdoWiki,
+    path: &str,
+    content: &str,
+    pat: &str,
+) -> Result<bool, Box<dyn Error>> {
+    let url = ado_page_url(wiki, path)?;
+    // Updating a page needs its current version (ETag) in `If-Match`.
+    let existing = client
+        .get(url.clone())
+        .basic_auth("", Some(&pat))
+        .send()
+        .map_err(|e| format!("Error reading wiki page '{path}': {e}"))?;
+    let etag = match existing.status() {
+        StatusCode::NOT_FOUND => None,
+        s if s.is_success() => existing
+            .headers()
+            .get("ETag")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        _ => {
+            return Err(request_failed(
+                &format!("Reading wiki page '{path}'"),
+                existing,
+            ))
+        }
+    };
+    let mut put = client
+        .put(url)
+        .basic_auth("", Some(&pat))
+        .json(&json!({ "content": content }));
+    if let Some(ref etag) = etag {
+        put = put.header("If-Match", etag);
+    }
+    let response = put
+        .send()
+        .map_err(|e| format!("Error writing wiki page '{path}': {e}"))?;
+    if !response.status().is_success() {
+        return Err(request_failed(
+            &format!("Writing wiki page '{path}'"),
+            response,
+        ));
+    }
+    Ok(etag.is_some())
+}
+
+/// Create or replace the repository file; returns `true` when it already existed.
+fn publish_github(
+    client: &Client,
+    repo: &str,
+    args: &PublishArgs,
+    content: &[u8],
+    token: &str,
+) -> Result<bool, Box<dyn Error>> {
+    let api = std::env::var("GITHUB_API_URL").unwrap_or_else(|_| GITHUB_API.to_string());
+    let url = github_contents_url(&api, repo, &args.path)?;
+    let request = |method: reqwest::Method, url: Url| {
+        client
+            .request(method, url)
+            .bearer_auth(token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "azure-subnet-summary")
+    };
+    // Replacing a file needs the SHA of the current blob.
+    let mut get_url = url.clone();
+    if let Some(ref branch) = args.branch {
+        get_url.query_pairs_mut().append_pair("ref", branch);
+    }
+    let existing = request(reqwest::Method::GET, get_url)
+        .send()
+        .map_err(|e| format!("Error reading '{}' in {repo}: {e}", args.path))?;
+    let sha = match existing.status() {
+        StatusCode::NOT_FOUND => None,
+        s if s.is_success() => {
+            let body: Value = existing
+                .json()
+                .map_err(|e| format!("Error parsing GitHub response: {e}"))?;
+            body["sha"].as_str().map(str::to_string)
+        }
+        _ => {
+            return Err(request_failed(
+                &format!("Reading '{}' in {repo}", args.path),
+                existing,
+            ))
+        }
+    };
+    let body = github_put_body(
+        content,
+        &args.message,
+        args.branch.as_deref(),
+        sha.as_deref(),
+    );
+    let response = request(reqwest::Method::PUT, url)
+        .json(&body)
+        .send()
+        .map_err(|e| format!("Error writing '{}' in {repo}: {e}", args.path))?;
+    if !response.status().is_success() {
+        return Err(request_failed(
+            &format!("Writing '{}' in {repo}", args.path),
+            response,
+        ));
+    }
+    Ok(sha.is_some())
+}
+
+/// Run the `publish` subcommand.
+pub fn run(
+    args: &PublishArgs,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let config = Config::load(config_path, profile)?;
+    let content = std::fs::read(&args.file)
+        .map_err(|e| format!("Error reading {}: {e}", args.file.display()))?;
+    let client = Client::new();
+    let (existed, target) = match (&args.ado_wiki, &args.github) {
+        (Some(url), _) => {
+            let wiki = parse_ado_wiki_url(url)?;
+            let text = String::from_utf8(content)
+                .map_err(|_| format!("{} is not UTF-8 text", args.file.display()))?;
+            let pat = token(
+                &["AZURE_DEVOPS_EXT_PAT"],
+                "ado_pat",
+                config.ado_pat.as_ref(),
+            )?;
+            let existed = publish_ado(&client, &wiki, &args.path, &text, &pat)?;
+            (
+                existed,
+                format!("wiki '{}' page '{}'", wiki.wiki, args.path),
+            )
+        }
+        (None, Some(repo)) => (
+            publish_github(
+                &client,
+                repo,
+                args,
+                &content,
+                &token(
+                    &["GITHUB_TOKEN", "GH_TOKEN"],
+                    "github_token",
+                    config.github_token.as_ref(),
+                )?,
+            )?,
+            format!("{repo}:{}", args.path),
+        ),
+        (None, None) => return Err("Give --ado-wiki or --github".into()),
+    };
+    println!(
+        "{} {target} from '{}'",
+        if existed { "Updated" } else { "Created" },
+        args.file.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_are_parsed_into_rest_requests() {
+        let wiki = parse_ado_wiki_url(
+            "https://dev.azure.com/contoso/Network/_wiki/wikis/Network.wiki/12/Home",
+        )
+        .unwrap();
+        assert_eq!(
+            wiki,
+            AdoWiki {
+                org_url: "https://dev.azure.com/contoso".to_string(),
+                project: "Network".to_string(),
+                wiki: "Network.wiki".to_string(),
+            }
+        );
+        assert_eq!(
+            ado_page_url(&wiki, "/Reports/Subnets").unwrap().as_str(),
+            "https://dev.azure.com/contoso/Network/_apis/wiki/wikis/Network.wiki/pages\
+             ?path=%2FReports%2FSubnets&api-version=7.1"
+        );
+        assert!(parse_ado_wiki_url("https://dev.azure.com/contoso").is_err());
+
+        assert_eq!(
+            github_contents_url(GITHUB_API, "contoso/netdocs", "/docs/subnets.md")
+                .unwrap()
+                .as_str(),
+            "https://api.github.com/repos/contoso/netdocs/contents/docs/subnets.md"
+        );
+        assert!(github_contents_url(GITHUB_API, "netdocs", "a.md").is_err());
+        let body = github_put_body(b"# Subnets\n", "Update", Some("main"), Some("abc123"));
+        assert_eq!(body["content"], "IyBTdWJuZXRzCg==");
+        assert_eq!(body["branch"], "main");
+        assert_eq!(body["sha"], "abc123");
+        assert!(github_put_body(b"", "Add", None, None).get("sha").is_none());
+    }
+}
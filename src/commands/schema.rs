@@ -0,0 +1,22 @@
+//! `schema` subcommand — print the JSON Schemas of the serialized outputs.
+
+use crate::schema::{schema, SCHEMAS};
+use clap::builder::PossibleValuesParser;
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct SchemaArgs {
+    /// Output type to print the schema of; without it every definition is
+    /// printed under `$defs`.
+    #[arg(value_parser = PossibleValuesParser::new(SCHEMAS.iter().map(|(name, _)| *name)))]
+    pub name: Option<String>,
+}
+
+/// Run the `schema` subcommand.
+pub fn run(args: &SchemaArgs) -> Result<(), Box<dyn Error>> {
+    let name = args.name.as_deref();
+    let schema = schema(name).ok_or_else(|| format!("Unknown schema '{}'", name.unwrap_or("")))?;
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
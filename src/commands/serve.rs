@@ -0,0 +1,99 @@
+//! `serve` subcommand — keep the snapshot and reports fresh on a schedule and
+//! answer HTTP requests about them.
+
+use crate::azure::{
+    fetch_azure_data, remove_todays_caches, reset_account_pin, reset_graph_quota, FetchConfig,
+};
+use crate::config::Config;
+use crate::pipeline::{self, Args as ReportArgs, GraphvizRenderer};
+use crate::server::{CronSchedule, History, RefreshOutcome, Server};
+use clap::Args;
+use std::collections::HashSet;
+use std::error::Error;
+use std::net::TcpListener;
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Address to listen on; use `0.0.0.0:8080` in a container.
+    #[arg(long, default_value = "127.0.0.1:8080", value_name = "ADDR")]
+    pub listen: String,
+    /// When to refresh the snapshot and regenerate the reports, as a cron
+    /// expression in `--timezone`, e.g. `"0 6 * * *"`; overrides
+    /// `refresh_cron` in the config file. Without either, the snapshot is
+    /// refreshed once at startup.
+    #[arg(long, value_name = "CRON")]
+    pub refresh_cron: Option<CronSchedule>,
+    /// `/readyz` reports not ready while the served snapshot is older than
+    /// this many hours (default: any age).
+    #[arg(long, value_name = "HOURS")]
+    pub max_snapshot_age: Option<u32>,
+    /// Directory holding the `report-<date>/` snapshot history read by
+    /// `/changes`.
+    #[arg(long, default_value = ".", value_name = "DIR")]
+    pub history_dir: String,
+}
+
+/// Run the `serve` subcommand; each refresh writes the report a plain run
+/// with the top-level options in `report_args` would, to the report
+/// directory and the `--notify` targets (there is no blob or webhook upload).
+pub fn run(
+    args: &ServeArgs,
+    fetch: &FetchConfig,
+    mut report_args: ReportArgs,
+) -> Result<(), Box<dyn Error>> {
+    let config = Config::load(
+        report_args.config.as_deref(),
+        report_args.profile.as_deref(),
+    )?;
+    let schedule = args.refresh_cron.clone().or(config.refresh_cron.clone());
+
+    report_args.apply_config(&config)?;
+    report_args.tenant = fetch.query.tenant.clone();
+    report_args.management_group = fetch.query.management_group.clone();
+    report_args.compress_cache = fetch.compress_cache;
+
+    let history = History {
+        root: args.history_dir.clone().into(),
+        options: fetch.query.clone(),
+    };
+    let clock = fetch.clock.clone();
+    let fetch = fetch.clone();
+    let refresh = move || {
+        // The date moves on while the server runs.
+        let date = fetch.clock.date_str();
+        let cache_dir = format!("report-{date}/cache");
+        std::fs::create_dir_all(&cache_dir)?;
+        let fetch = FetchConfig {
+            cache_dir: Some(cache_dir),
+            ..fetch.clone()
+        };
+        remove_todays_caches(&fetch)?;
+        // Each refresh is a run of its own: follow `az account set` since
+        // the last one and count its Resource Graph queries alone.
+        reset_account_pin();
+        reset_graph_quota();
+        let azure = fetch_azure_data(&fetch)?;
+        let subnets = &azure.subnets.data.data;
+        let outcome = RefreshOutcome {
+            subnets: subnets.len(),
+            vnets: subnets
+                .iter()
+                .map(|s| (&s.subscription_id, &s.vnet_name))
+                .collect::<HashSet<_>>()
+                .len(),
+            generated_at: azure.subnets.generated_at.clone(),
+            cache_file: azure.subnets.cache_file.clone(),
+        };
+        pipeline::run(azure, &report_args, &GraphvizRenderer)?;
+        Ok(outcome)
+    };
+
+    let listener = TcpListener::bind(&args.listen)
+        .map_err(|e| format!("Error listening on '{}': {e}", args.listen))?;
+    let mut server = Server::new(clock, schedule.as_ref()).with_history(history);
+    if let Some(hours) = args.max_snapshot_age {
+        server = server.with_max_snapshot_age(chrono::Duration::hours(hours.into()));
+    }
+    server.start_scheduler(schedule, Box::new(refresh));
+    server.serve(listener)
+}
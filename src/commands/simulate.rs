@@ -0,0 +1,162 @@
+//! `simulate` subcommands — what-if changes to the subnet data, analysed
+//! locally without touching Azure.
+
+use crate::azure::{fetch_subnets, Data, FetchConfig};
+use crate::config::Config;
+use crate::models::Ipv4;
+use crate::processing::{
+    add_subnets, compare_usage, format_usage_changes, parse_buffer, remove_subnets, space_usage,
+};
+use clap::Subcommand;
+use std::error::Error;
+
+#[derive(Debug, Subcommand)]
+pub enum SimulateCommand {
+    /// Remove subnets and show the address space that would be freed.
+    ///
+    /// Example: simulate reclaim --subnet hub-weu/old-app,10.20.8.0/24
+    Reclaim {
+        /// Comma-separated subnets to remove: `vnet/subnet`, subnet name or CIDR.
+        #[arg(long, value_delimiter = ',', required = true, value_name = "SUBNETS")]
+        subnet: Vec<String>,
+        /// CIDR kept free on purpose (growth buffer); not counted as freed.
+        /// Adds to `buffer` in the config file. Repeatable.
+        #[arg(long, value_name = "CIDR", value_parser = parse_buffer)]
+        buffer: Vec<Ipv4>,
+        /// Subnet cache file to read instead of today's cache.
+        #[arg(long, value_name = "FILE")]
+        cache_file: Option<String>,
+    },
+    /// Add planned subnets to a VNet, check them against live subnets and the
+    /// address space, and show the resulting usage.
+    ///
+    /// Example: simulate add --vnet hub-weu --cidr 10.20.4.0/24,10.20.5.0/26
+    Add {
+        /// VNet to add the subnets to (case-insensitive).
+        #[arg(long)]
+        vnet: String,
+        /// Comma-separated CIDRs of the planned subnets.
+        #[arg(long, value_delimiter = ',', required = true, value_name = "CIDRS")]
+        cidr: Vec<String>,
+        /// Comma-separated names for the planned subnets, in `--cidr` order
+        /// (default `planned-1`, `planned-2`, ...).
+        #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+        name: Vec<String>,
+        /// Subscription ID or name, required when the VNet name is not unique.
+        #[arg(long)]
+        subscription: Option<String>,
+        /// CIDR kept free on purpose (growth buffer); planned subnets may not
+        /// overlap it. Adds to `buffer` in the config file. Repeatable.
+        #[arg(long, value_name = "CIDR", value_parser = parse_buffer)]
+        buffer: Vec<Ipv4>,
+        /// Subnet cache file to read instead of today's cache.
+        #[arg(long, value_name = "FILE")]
+        cache_file: Option<String>,
+    },
+}
+
+/// Run a `simulate` subcommand, printing the result.
+pub fn run(
+    cmd: &SimulateCommand,
+    fetch: &FetchConfig,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let mut buffers = Config::load(config_path, profile)?.buffer;
+    match cmd {
+        SimulateCommand::Reclaim {
+            subnet,
+            buffer,
+            cache_file,
+        } => {
+            buffers.extend(buffer.iter().copied());
+            let data = load(cache_file, fetch)?;
+            let (after, removed) = remove_subnets(&data, subnet)?;
+            for s in &removed {
+                println!(
+                    "REMOVED: '{}/{}' {} [{}]",
+                    s.vnet_name,
+                    s.subnet_name,
+                    s.subnet_cidr
+                        .map(|c| c.to_string())
+                        .unwrap_or_else(|| "None".to_string()),
+                    s.subscription_name
+                );
+            }
+            let changes = compare_usage(
+                &space_usage(&data, &buffers),
+                &space_usage(&after, &buffers),
+            );
+            println!("{}", format_usage_changes(&changes));
+            Ok(())
+        }
+        SimulateCommand::Add {
+            vnet,
+            cidr,
+            name,
+            subscription,
+            buffer,
+            cache_file,
+        } => {
+            buffers.extend(buffer.iter().copied());
+            if !name.is_empty() && name.len() != cidr.len() {
+                return Err(format!(
+                    "--name has {} entries but --cidr has {}",
+                    name.len(),
+                    cidr.len()
+                )
+                .into());
+            }
+            let proposals = cidr
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    let label = name
+                        .get(i)
+                        .cloned()
+                        .unwrap_or_else(|| format!("planned-{}", i + 1));
+                    Ok((label, Ipv4::new(c)?))
+                })
+                .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+            let data = load(cache_file, fetch)?;
+            let (after, conflicts) =
+                add_subnets(&data, vnet, subscription.as_deref(), &proposals, &buffers)?;
+            for (label, c) in &proposals {
+                match conflicts.iter().find(|x| &x.name == label && x.cidr == *c) {
+                    Some(conflict) => {
+                        println!("CONFLICT: '{vnet}/{label}' {c} - {}", conflict.reason)
+                    }
+                    None => println!("ADDED: '{vnet}/{label}' {c}"),
+                }
+            }
+            let changes = compare_usage(
+                &space_usage(&data, &buffers),
+                &space_usage(&after, &buffers),
+            );
+            println!("{}", format_usage_changes(&changes));
+            if !conflicts.is_empty() {
+                return Err(format!(
+                    "{} planned subnet(s) conflict with the VNet",
+                    conflicts.len()
+                )
+                .into());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Load subnets from `cache_file` or today's cache.
+fn load(cache_file: &Option<String>, fetch: &FetchConfig) -> Result<Data, Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    Ok(fetch_subnets(&config)?.data)
+}
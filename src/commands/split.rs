@@ -0,0 +1,83 @@
+//! `split` subcommand — plan how to carve a subnet or free gap into smaller subnets.
+
+use super::EmitArgs;
+use crate::codegen::SubnetSpec;
+use crate::config::Config;
+use crate::models::Ipv4;
+use crate::processing::{
+    format_split_plan, parse_buffer, parse_size_mix, split_equal, split_sizes,
+};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct SplitArgs {
+    /// Existing subnet or free gap to split, e.g. 10.0.0.0/24.
+    pub cidr: String,
+    /// Split into this many equal subnets (rounded up to a power of two).
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "sizes",
+        required_unless_present = "sizes"
+    )]
+    pub count: Option<u32>,
+    /// Split into a mix of sizes, e.g. "2x26,4x28".
+    #[arg(long, value_name = "MIX")]
+    pub sizes: Option<String>,
+    /// VNet the subnets belong to in generated code; required with `--emit`.
+    #[arg(long)]
+    pub vnet: Option<String>,
+    /// Generated subnets are named `<prefix>-01`, `<prefix>-02`, ...
+    #[arg(long, value_name = "PREFIX", default_value = "subnet")]
+    pub name_prefix: String,
+    /// CIDR kept free on purpose (growth buffer); never allocated. Adds to
+    /// `buffer` in the config file. Repeatable.
+    #[arg(long, value_name = "CIDR", value_parser = parse_buffer)]
+    pub buffer: Vec<Ipv4>,
+    #[command(flatten)]
+    pub emit: EmitArgs,
+}
+
+/// Run the `split` subcommand, printing the plan.
+pub fn run(
+    args: &SplitArgs,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let space = Ipv4::new(&args.cidr)?;
+    let (vnet, resource_group) = match (&args.emit.emit, &args.vnet, &args.emit.resource_group) {
+        (Some(_), None, _) => return Err("--emit needs --vnet for split".into()),
+        (Some(_), _, None) => return Err("--emit needs --resource-group for split".into()),
+        (_, vnet, rg) => (
+            vnet.clone().unwrap_or_default(),
+            rg.clone().unwrap_or_default(),
+        ),
+    };
+    let mut buffers = Config::load(config_path, profile)?.buffer;
+    buffers.extend(args.buffer.iter().copied());
+    let plan = match (&args.sizes, args.count) {
+        (Some(sizes), _) => split_sizes(space, &parse_size_mix(sizes)?, &buffers)?,
+        (None, Some(count)) => split_equal(space, count, &buffers)?,
+        (None, None) => return Err("Either --count or --sizes is required".into()),
+    };
+    println!("{}", format_split_plan(&plan));
+
+    let specs: Vec<SubnetSpec> = plan
+        .allocations
+        .iter()
+        .enumerate()
+        .map(|(i, cidr)| SubnetSpec {
+            name: format!("{}-{:02}", args.name_prefix, i + 1),
+            vnet_name: vnet.clone(),
+            vnet_id: String::new(),
+            resource_group: resource_group.clone(),
+            subscription_id: String::new(),
+            address_prefix: *cidr,
+        })
+        .collect();
+    if let Some(code) = args.emit.render(&specs)? {
+        println!("\n{code}");
+    }
+    Ok(())
+}
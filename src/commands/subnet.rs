@@ -0,0 +1,154 @@
+//! `subnet` subcommands — drill down into one subnet.
+
+use crate::azure::{fetch_subnets, run_ip_config_graph, Data, FetchConfig};
+use crate::models::{num_az_hosts, Ipv4, Subnet};
+use crate::output::copy_to_clipboard;
+use clap::{Args, Subcommand};
+use std::error::Error;
+
+#[derive(Debug, Subcommand)]
+pub enum SubnetCommand {
+    /// List what uses the addresses of a subnet: NICs with their private IP
+    /// and VM or private endpoint, load balancer frontends, gateways, ...
+    ///
+    /// Example: subnet show hub-vnet/app-snet
+    Show(ShowArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ShowArgs {
+    /// Subnet CIDR (e.g. 10.0.1.0/24), name, or `vnet/subnet` when the name
+    /// is not unique.
+    pub subnet: String,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+    /// Also copy the listing to the clipboard.
+    #[arg(long)]
+    pub copy: bool,
+}
+
+/// Subnets matching `query`: a CIDR, a subnet name or `vnet/subnet`.
+fn find_subnets<'a>(data: &'a Data, query: &str) -> Vec<&'a Subnet> {
+    if let Ok(cidr) = Ipv4::new(query) {
+        return data
+            .data
+            .iter()
+            .filter(|s| s.subnet_cidr == Some(cidr))
+            .collect();
+    }
+    let (vnet, name) = match query.split_once('/') {
+        Some((vnet, name)) => (Some(vnet), name),
+        None => (None, query),
+    };
+    data.data
+        .iter()
+        .filter(|s| s.subnet_name.eq_ignore_ascii_case(name))
+        .filter(|s| vnet.is_none_or(|v| s.vnet_name.eq_ignore_ascii_case(v)))
+        .collect()
+}
+
+/// Run a `subnet` subcommand.
+pub fn run(command: &SubnetCommand, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let SubnetCommand::Show(args) = command;
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let data = fetch_subnets(&config)?.data;
+    let subnet = match find_subnets(&data, &args.subnet)[..] {
+        [subnet] => subnet,
+        [] => return Err(format!("No subnet matches '{}'", args.subnet).into()),
+        ref many => {
+            let names: Vec<String> = many
+                .iter()
+                .map(|s| format!("{}/{}", s.vnet_name, s.subnet_name))
+                .collect();
+            return Err(format!(
+                "'{}' matches {} subnets ({}); use vnet/subnet",
+                args.subnet,
+                many.len(),
+                names.join(", ")
+            )
+            .into());
+        }
+    };
+    if subnet.subnet_id.is_empty() {
+        return Err(format!(
+            "The cache has no resource ID for '{}/{}'; refresh it to query its IP configurations",
+            subnet.vnet_name, subnet.subnet_name
+        )
+        .into());
+    }
+
+    let rows = run_ip_config_graph(&subnet.subnet_id, &subnet.subscription_id, &config.query)?;
+    let cidr = subnet
+        .subnet_cidr
+        .map(|c| c.to_string())
+        .unwrap_or_default();
+    let hosts = subnet
+        .subnet_cidr
+        .and_then(|c| num_az_hosts(c.mask).ok())
+        .unwrap_or(0);
+    let mut report = format!(
+        "SUBNET: '{}/{}' {cidr} in '{}' - {} of {hosts} Azure host address(es) in use\n",
+        subnet.vnet_name,
+        subnet.subnet_name,
+        subnet.subscription_name,
+        rows.len()
+    );
+    for row in &rows {
+        let (kind, name) = row.owner();
+        let ip = if row.private_ip.is_empty() {
+            "-"
+        } else {
+            &row.private_ip
+        };
+        let nic = match row.nic_name() {
+            "" => String::new(),
+            nic if nic.eq_ignore_ascii_case(&name) => String::new(),
+            nic => format!(" (NIC '{nic}')"),
+        };
+        report.push_str(&format!("  {ip:<15} {kind}: '{name}'{nic}\n"));
+    }
+    print!("{report}");
+    if args.copy {
+        if let Err(e) = copy_to_clipboard(&report) {
+            log::warn!("--copy: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subnets_are_found_by_cidr_name_or_vnet_and_name() {
+        let data = Data {
+            data: vec![
+                Subnet::test("hub", "app", "10.0.0.0/24"),
+                Subnet::test("spoke", "app", "10.1.0.0/24"),
+                Subnet::test("spoke", "db", "10.1.1.0/24"),
+            ],
+            ..Default::default()
+        };
+        let found = |q: &str| -> Vec<String> {
+            find_subnets(&data, q)
+                .iter()
+                .map(|s| format!("{}/{}", s.vnet_name, s.subnet_name))
+                .collect()
+        };
+        assert_eq!(found("10.1.1.0/24"), ["spoke/db"]);
+        assert_eq!(found("DB"), ["spoke/db"]);
+        assert_eq!(found("app"), ["hub/app", "spoke/app"]);
+        assert_eq!(found("Spoke/app"), ["spoke/app"]);
+        assert!(found("10.9.0.0/24").is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+//! `subnets-by-nsg` subcommand — list every NSG with the subnets it protects,
+//! NSGs shared across VNets first.
+
+use crate::azure::{fetch_subnets, FetchConfig};
+use crate::processing::{format_nsg_usage, NsgIndex};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct SubnetsByNsgArgs {
+    /// Only list NSGs protecting subnets in at least this many VNets.
+    #[arg(long, default_value_t = 1, value_name = "N")]
+    pub min_vnets: usize,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `subnets-by-nsg` subcommand.
+pub fn run(args: &SubnetsByNsgArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let data = fetch_subnets(&config)?.data;
+    let mut usage = NsgIndex::new(&data).usage();
+    usage.retain(|u| u.vnets >= args.min_vnets);
+    println!("{}", format_nsg_usage(&usage));
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! `suggest` subcommand — propose a free CIDR for a new subnet in a VNet.
+
+use super::EmitArgs;
+use crate::azure::{fetch_subnets, FetchConfig};
+use crate::codegen::SubnetSpec;
+use crate::config::Config;
+use crate::models::Ipv4;
+use crate::output::copy_to_clipboard;
+use crate::processing::{parse_buffer, suggest_subnet};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct SuggestArgs {
+    /// VNet to allocate in (case-insensitive).
+    #[arg(long)]
+    pub vnet: String,
+    /// Prefix length of the new subnet, e.g. 26.
+    #[arg(long, value_name = "MASK")]
+    pub mask: u8,
+    /// Subscription ID or name, required when the VNet name is not unique.
+    #[arg(long)]
+    pub subscription: Option<String>,
+    /// Name for the new subnet in generated code.
+    #[arg(long, default_value = "new-subnet")]
+    pub name: String,
+    /// CIDR kept free on purpose (growth buffer); never suggested. Adds to
+    /// `buffer` in the config file. Repeatable.
+    #[arg(long, value_name = "CIDR", value_parser = parse_buffer)]
+    pub buffer: Vec<Ipv4>,
+    #[command(flatten)]
+    pub emit: EmitArgs,
+    /// Also copy the suggestion (and generated code) to the clipboard.
+    #[arg(long)]
+    pub copy: bool,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `suggest` subcommand.
+pub fn run(
+    args: &SuggestArgs,
+    fetch: &FetchConfig,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let subnets = fetch_subnets(&config)?.data;
+    let mut buffers = Config::load(config_path, profile)?.buffer;
+    buffers.extend(args.buffer.iter().copied());
+    let suggestion = suggest_subnet(
+        &subnets,
+        &args.vnet,
+        args.subscription.as_deref(),
+        args.mask,
+        &buffers,
+    )?;
+    let mut report = format!(
+        "Free /{} in VNet '{}' ({}) [{}]: {}\n",
+        args.mask,
+        suggestion.vnet_name,
+        suggestion.vnet_cidr,
+        suggestion.subscription_name,
+        suggestion.cidr
+    );
+
+    let resource_group = match &args.emit.resource_group {
+        Some(rg) => rg.clone(),
+        None if args.emit.emit.is_some() && suggestion.resource_group.is_empty() => {
+            return Err(format!(
+                "The cache has no resource group for VNet '{}'; pass --resource-group",
+                suggestion.vnet_name
+            )
+            .into())
+        }
+        None => suggestion.resource_group,
+    };
+    let spec = SubnetSpec {
+        name: args.name.clone(),
+        vnet_name: suggestion.vnet_name,
+        vnet_id: suggestion.vnet_id,
+        resource_group,
+        subscription_id: suggestion.subscription_id,
+        address_prefix: suggestion.cidr,
+    };
+    if let Some(code) = args.emit.render(&[spec])? {
+        report.push_str(&format!("\n{code}\n"));
+    }
+    print!("{report}");
+    if args.copy {
+        if let Err(e) = copy_to_clipboard(&report) {
+            log::warn!("--copy: {e}");
+        }
+    }
+    Ok(())
+}
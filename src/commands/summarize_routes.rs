@@ -0,0 +1,120 @@
+//! `summarize-routes` subcommand — summarize the Azure VNet address space
+//! into a short prefix list to advertise on-premises over ExpressRoute / VPN.
+
+use crate::azure::{fetch_peering, fetch_subnets, FetchConfig};
+use crate::models::Ipv4;
+use crate::output::build_topology;
+use crate::processing::summarize_prefixes;
+use clap::Args;
+use std::collections::{BTreeSet, HashSet};
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct SummarizeRoutesArgs {
+    /// Only VNets in these regions (comma-separated, e.g. `australiaeast`).
+    #[arg(long, value_delimiter = ',', value_name = "REGIONS")]
+    pub region: Vec<String>,
+    /// Only VNets behind this hub: the spokes of a vWAN hub of that name, or a
+    /// hub VNet of that name and the VNets peered with it.
+    #[arg(long, value_name = "NAME")]
+    pub hub: Option<String>,
+    /// Advertise at most this many prefixes, merging neighbouring blocks into
+    /// supernets (this also advertises some unused addresses).
+    #[arg(long, value_name = "N")]
+    pub max_prefixes: Option<usize>,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+    /// Peering cache file to read instead of today's cache (used with `--hub`).
+    #[arg(long, value_name = "FILE")]
+    pub peering_cache_file: Option<String>,
+}
+
+/// Run the `summarize-routes` subcommand.
+pub fn run(args: &SummarizeRoutesArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        peering_cache: args
+            .peering_cache_file
+            .clone()
+            .or(fetch.peering_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let subnets = fetch_subnets(&config)?.data;
+
+    let behind_hub: Option<HashSet<String>> = match &args.hub {
+        Some(hub) => {
+            let peering = fetch_peering(&config)?.data;
+            let topo = build_topology(&peering.data, &subnets, &[], &[]);
+            let members: HashSet<String> = match topo
+                .vwan_hubs
+                .iter()
+                .find(|h| h.hub_name.eq_ignore_ascii_case(hub))
+            {
+                Some(vwan_hub) => vwan_hub.spoke_vnets.iter().cloned().collect(),
+                None => topo
+                    .bidir_pairs
+                    .iter()
+                    .filter_map(|(a, b)| {
+                        if a.eq_ignore_ascii_case(hub) {
+                            Some(b.clone())
+                        } else if b.eq_ignore_ascii_case(hub) {
+                            Some(a.clone())
+                        } else {
+                            None
+                        }
+                    })
+                    .chain(
+                        topo.vnet_meta
+                            .keys()
+                            .filter(|v| v.eq_ignore_ascii_case(hub))
+                            .cloned(),
+                    )
+                    .collect(),
+            };
+            if members.is_empty() {
+                return Err(format!("No vWAN hub or VNet named '{hub}' found").into());
+            }
+            Some(members)
+        }
+        None => None,
+    };
+
+    let unset = Ipv4::new("0.0.0.0/0")?;
+    let prefixes: BTreeSet<Ipv4> = subnets
+        .data
+        .iter()
+        .filter(|s| {
+            args.region.is_empty()
+                || args
+                    .region
+                    .iter()
+                    .any(|r| r.eq_ignore_ascii_case(&s.location))
+        })
+        .filter(|s| behind_hub.as_ref().is_none_or(|m| m.contains(&s.vnet_name)))
+        .map(|s| s.vnet_cidr)
+        .filter(|c| *c != unset)
+        .collect();
+    if prefixes.is_empty() {
+        return Err("No VNet prefixes match the given filters".into());
+    }
+
+    let prefixes: Vec<Ipv4> = prefixes.into_iter().collect();
+    let summary = summarize_prefixes(&prefixes, args.max_prefixes);
+    for prefix in &summary.prefixes {
+        println!("{prefix}");
+    }
+    println!(
+        "# {} VNet prefix(es) summarized to {} route(s): {} addresses, {} extra advertised",
+        prefixes.len(),
+        summary.prefixes.len(),
+        summary.covered,
+        summary.extra
+    );
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! `verify` subcommand — check the minisign signatures of report files.
+
+use crate::signing::{PublicKey, SIGNATURE_EXTENSION};
+use clap::Args;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// minisign public key file, or the base64 key itself.
+    #[arg(long, short = 'P', value_name = "KEY")]
+    pub public_key: String,
+    /// Report files to check against their `<file>.minisig`; a directory
+    /// checks every signed file in it.
+    #[arg(required = true, value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+}
+
+/// Signed files in `dir`: those with a `.minisig` next to them.
+fn signed_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Error reading {}: {e}", dir.display()))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == SIGNATURE_EXTENSION))
+        .map(|p| p.with_extension(""))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Run the `verify` subcommand; fails unless every file checks out.
+pub fn run(args: &VerifyArgs) -> Result<(), Box<dyn Error>> {
+    let key = PublicKey::from_arg(&args.public_key)?;
+    let mut files = Vec::new();
+    for path in &args.files {
+        if path.is_dir() {
+            files.extend(signed_files(path)?);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    if files.is_empty() {
+        return Err("No signed files found".into());
+    }
+    let mut failed = 0;
+    for file in &files {
+        match key.verify_file(file) {
+            Ok(comment) => println!("OK: '{}' - {comment}", file.display()),
+            Err(e) => {
+                failed += 1;
+                println!("FAILED: '{}' - {e}", file.display());
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} of {} file(s) failed verification", files.len()).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! `wasted-space` subcommand — estimate address space wasted by subnets sized
+//! well beyond their peak usage in the snapshot history.
+
+use crate::azure::{fetch_subnets, list_snapshots, read_snapshots_newest_first, FetchConfig};
+use crate::processing::{find_oversized_subnets, format_oversized_subnets};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct WastedSpaceArgs {
+    /// Growth headroom in percent on top of the peak IP count when sizing.
+    #[arg(long, default_value_t = 20, value_name = "PCT")]
+    pub headroom: u64,
+    /// Directory holding the `report-<date>/` snapshot history.
+    #[arg(long, default_value = ".", value_name = "DIR")]
+    pub history_dir: String,
+    /// Subnet cache file to read instead of today's cache.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+}
+
+/// Run the `wasted-space` subcommand.
+pub fn run(args: &WastedSpaceArgs, fetch: &FetchConfig) -> Result<(), Box<dyn Error>> {
+    let config = FetchConfig {
+        subnet_cache: args.cache_file.clone().or(fetch.subnet_cache.clone()),
+        query: fetch.query.clone(),
+        cache_dir: fetch.cache_dir.clone(),
+        compress_cache: fetch.compress_cache,
+        offline: fetch.offline,
+        clock: fetch.clock.clone(),
+        ..FetchConfig::default()
+    };
+    let current = fetch_subnets(&config)?.data;
+    let today = config.clock.today();
+    let snapshots = list_snapshots(Path::new(&args.history_dir), &config.query, &config.clock);
+    log::info!(
+        "Taking peak usage over {} earlier snapshot(s) in '{}'",
+        snapshots.iter().filter(|s| s.date < today).count(),
+        args.history_dir
+    );
+    let oversized = find_oversized_subnets(
+        &current,
+        today,
+        read_snapshots_newest_first(&snapshots),
+        args.headroom,
+    );
+    println!("{}", format_oversized_subnets(&oversized));
+    Ok(())
+}
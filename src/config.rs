@@ -1,2 +1,323 @@
+//! Runtime constants and the optional configuration file.
+//!
+//! Settings are read from `azure-subnet-summary.toml` in the current directory
+//! (or the file given with `--config`). Every setting is optional; CLI flags
+//! take precedence over the file.
+//!
+//! Named profiles (`[profile.<name>]` tables holding any of the top-level
+//! settings) let one file serve several reporting jobs: `--profile <name>`
+//! applies the profile's settings over the top-level ones, a profile key
+//! replacing the top-level value (lists included).
+
+use crate::azure::SecretRef;
+use crate::models::{Ipv4, ReservationModel};
+#[cfg(feature = "cli")]
+use crate::output::NotifyTarget;
+use crate::output::OutputFormat;
+use crate::processing::{ExtraRange, IgnoreRule, PlanBlock, VnetPolicy};
+#[cfg(feature = "cli")]
+use crate::server::CronSchedule;
+use serde::Deserialize;
+use std::error::Error;
+use std::path::Path;
+
 // Set to 1000 to see output
 pub const SLEEP_MSEC: u64 = 1;
+
+/// Config file read when `--config` is not given; it is fine for it to be absent.
+pub const DEFAULT_CONFIG_FILE: &str = "azure-subnet-summary.toml";
+
+/// Table of named profiles in the config file.
+const PROFILE_KEY: &str = "profile";
+
+/// Settings from the configuration file.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Restrict Resource Graph queries to this management group (`--management-group`).
+    pub management_group: Option<String>,
+    /// Tenant ID used to key cache files (`--tenant`).
+    pub tenant: Option<String>,
+    /// Only report subnets under this management group (`--only-management-group`).
+    pub only_management_group: Option<String>,
+    /// Write new cache files zstd-compressed (`--compress-cache`).
+    pub compress_cache: bool,
+    /// Resource Graph records per page (`--page-size`).
+    pub page_size: Option<u32>,
+    /// Fail on Resource Graph subnet rows missing required fields (`--strict-schema`).
+    pub strict_schema: bool,
+    /// Fail on any intersecting subnet ranges across subscriptions (`--strict-duplicates`).
+    pub strict_duplicates: bool,
+    /// VNets allowed to overlap each other when not peered (`--isolated-vnets`).
+    pub isolated_vnets: Vec<String>,
+    /// Report networking limit usage at or above this percentage (`--quota-warn-pct`).
+    pub quota_warn_pct: Option<u8>,
+    /// Subnets to leave out, each scoped by subnet, subscription, VNet and/or
+    /// CIDR (`--ignore`); nothing is ignored by default.
+    pub ignore: Vec<IgnoreRule>,
+    /// IANA timezone for dated file names and timestamps (`--timezone`).
+    pub timezone: Option<String>,
+    /// `strftime` date format for dated file names (`--date-format`).
+    pub date_format: Option<String>,
+    /// VNet policies evaluated by `check` (`[[policy]]` tables).
+    pub policy: Vec<VnetPolicy>,
+    /// Intended address blocks per region, scored by `conformance`
+    /// (`[[plan]]` tables).
+    pub plan: Vec<PlanBlock>,
+    /// Reserved, on-prem and planned ranges merged into the report (`--range`).
+    pub range: Vec<ExtraRange>,
+    /// `terraform show -json` plan whose new subnets are merged into the
+    /// report (`--terraform-plan`).
+    pub terraform_plan: Option<String>,
+    /// Intentional gaps (growth buffers), shown as `-buffer-` and left out of
+    /// free space, `suggest`, `split` and `simulate` (`--buffer`).
+    pub buffer: Vec<Ipv4>,
+    /// Handlebars templates rendered into the report directory (`--template`).
+    pub template: Vec<String>,
+    /// Write the address space ownership heatmap (`--heatmap`).
+    pub heatmap: bool,
+    /// Supernet sliced by the heatmap (`--heatmap-supernet`).
+    pub heatmap_supernet: Option<Ipv4>,
+    /// Heatmap slice size (`--heatmap-slice`).
+    pub heatmap_slice: Option<u8>,
+    /// Directory of the per-VNet YAML files (`--gitops-dir`).
+    pub gitops_dir: Option<String>,
+    /// Sign the report files (`--sign`).
+    pub sign: bool,
+    /// minisign secret key used to sign (`--signing-key`).
+    pub signing_key: Option<String>,
+    /// Addresses reserved per subnet in host counts (`--host-reservation`).
+    pub host_reservation: Option<ReservationModel>,
+    /// Write `run_manifest.json` into the report directory (`--manifest`).
+    pub manifest: bool,
+    /// Event Grid topics / storage queues told when a run completes (`--notify`).
+    #[cfg(feature = "cli")]
+    pub notify: Vec<NotifyTarget>,
+    /// Published location of the report, sent in run events (`--report-url`).
+    pub report_url: Option<String>,
+    /// When `serve` refreshes the snapshot and reports (`--refresh-cron`).
+    #[cfg(feature = "cli")]
+    pub refresh_cron: Option<CronSchedule>,
+    /// Terminal presentation of the subnet rows (`--output`).
+    pub output: Option<OutputFormat>,
+    /// Azure DevOps PAT for `publish`, as a `keyvault://<vault>/<secret>`
+    /// reference; `AZURE_DEVOPS_EXT_PAT` wins when set.
+    pub ado_pat: Option<SecretRef>,
+    /// GitHub token for `publish`, as a `keyvault://<vault>/<secret>`
+    /// reference; `GITHUB_TOKEN` / `GH_TOKEN` win when set.
+    pub github_token: Option<SecretRef>,
+}
+
+impl Config {
+    /// Parse configuration from TOML text, ignoring profiles (which are
+    /// still validated).
+    pub fn parse(toml_text: &str) -> Result<Config, Box<dyn Error>> {
+        Config::parse_profile(toml_text, None)
+    }
+
+    /// Parse configuration from TOML text with the settings of `profile`
+    /// applied over the top-level ones. Every profile is validated, so a typo
+    /// in one is reported whichever is selected.
+    pub fn parse_profile(toml_text: &str, profile: Option<&str>) -> Result<Config, Box<dyn Error>> {
+        let mut base: toml::Table =
+            toml::from_str(toml_text).map_err(|e| format!("Invalid config: {e}"))?;
+        let profiles = match base.remove(PROFILE_KEY) {
+            None => toml::Table::new(),
+            Some(toml::Value::Table(profiles)) => profiles,
+            Some(_) => {
+                return Err(
+                    "Invalid config: 'profile' must be a table of [profile.<name>] tables".into(),
+                )
+            }
+        };
+        let mut selected = None;
+        for (name, settings) in &profiles {
+            let toml::Value::Table(settings) = settings else {
+                return Err(format!("Invalid config: profile '{name}' must be a table").into());
+            };
+            if settings.contains_key(PROFILE_KEY) {
+                return Err(
+                    format!("Invalid config: profile '{name}' cannot contain profiles").into(),
+                );
+            }
+            let mut merged = base.clone();
+            merged.extend(settings.clone());
+            let config: Config = merged
+                .try_into()
+                .map_err(|e| format!("Invalid config in profile '{name}': {e}"))?;
+            if profile == Some(name.as_str()) {
+                selected = Some(config);
+            }
+        }
+        match (profile, selected) {
+            (None, _) => Ok(base
+                .try_into()
+                .map_err(|e| format!("Invalid config: {e}"))?),
+            (Some(_), Some(config)) => Ok(config),
+            (Some(name), None) => Err(format!(
+                "Unknown profile '{name}' (defined: {})",
+                if profiles.is_empty() {
+                    "none".to_string()
+                } else {
+                    profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                }
+            )
+            .into()),
+        }
+    }
+
+    /// Load the config file, with `profile` applied when given.
+    ///
+    /// With `path == None` the [`DEFAULT_CONFIG_FILE`] is read if it exists,
+    /// otherwise defaults are returned. An explicit `path` must exist, as must
+    /// a config file when a profile is selected.
+    pub fn load(path: Option<&str>, profile: Option<&str>) -> Result<Config, Box<dyn Error>> {
+        let file = path.unwrap_or(DEFAULT_CONFIG_FILE);
+        if path.is_none() && !Path::new(file).exists() {
+            if let Some(name) = profile {
+                return Err(format!("Profile '{name}' selected but {file} does not exist").into());
+            }
+            return Ok(Config::default());
+        }
+        let text = std::fs::read_to_string(file)
+            .map_err(|e| format!("Error reading config file {file}: {e}"))?;
+        match profile {
+            Some(name) => log::info!("Using config file '{file}', profile '{name}'"),
+            None => log::info!("Using config file '{file}'"),
+        }
+        Config::parse_profile(&text, profile).map_err(|e| format!("{file}: {e}").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_example_config_parses() {
+        let config = Config::parse(include_str!("../azure-subnet-summary.example.toml")).unwrap();
+        assert!(config
+            .ignore
+            .iter()
+            .any(|r| r.subnet.as_deref() == Some("default")));
+    }
+
+    #[test]
+    fn parse_reads_known_settings() {
+        let config = Config::parse(
+            r#"
+            management_group = "mg-corp"
+            compress_cache = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.management_group.as_deref(), Some("mg-corp"));
+        assert!(config.compress_cache);
+        assert_eq!(config.only_management_group, None);
+
+        let config = Config::parse(r#"github_token = "keyvault://kv-net/gh-token""#).unwrap();
+        assert_eq!(
+            config.github_token.map(|s| s.to_string()).as_deref(),
+            Some("keyvault://kv-net/gh-token")
+        );
+        assert!(Config::parse(r#"ado_pat = "plain-token-value""#).is_err());
+    }
+
+    #[test]
+    fn parse_reads_scoped_ignore_rules() {
+        let config = Config::parse(
+            r#"
+            [[ignore]]
+            subnet = "default"
+            vnet = "lab-vnet"
+
+            [[ignore]]
+            cidr = "10.9.0.0/24"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.ignore.len(), 2);
+        assert_eq!(config.ignore[0].vnet.as_deref(), Some("lab-vnet"));
+        assert_eq!(
+            config.ignore[1].cidr.map(|c| c.to_string()).as_deref(),
+            Some("10.9.0.0/24")
+        );
+        assert!(Config::parse("[[ignore]]\nregion = \"westeurope\"").is_err());
+    }
+
+    #[test]
+    fn parse_reads_vnet_policies() {
+        let config = Config::parse(
+            r#"
+            [[policy]]
+            name = "prod-ddos"
+            subscription = "prod"
+            require_ddos = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.policy.len(), 1);
+        assert!(config.policy[0].require_ddos && !config.policy[0].require_encryption);
+        assert!(Config::parse("[[policy]]\nrequire_nsg = true").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_keys() {
+        let err = Config::parse("compress_cahce = true")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown field"), "got: {err}");
+    }
+
+    #[test]
+    fn load_fails_when_explicit_config_file_is_missing() {
+        assert!(Config::load(Some("/tmp/no-such-azure-subnet-summary.toml"), None).is_err());
+    }
+
+    #[test]
+    fn profiles_override_top_level_settings() {
+        let text = r#"
+            tenant = "corp"
+            compress_cache = true
+
+            [[ignore]]
+            subnet = "default"
+
+            [profile.prod]
+            management_group = "mg-prod"
+
+            [profile.lab]
+            tenant = "lab-tenant"
+            ignore = [{ cidr = "10.99.0.0/16" }]
+            output = "table"
+        "#;
+        let base = Config::parse(text).unwrap();
+        assert_eq!(base.tenant.as_deref(), Some("corp"));
+        assert_eq!(base.management_group, None);
+
+        let prod = Config::parse_profile(text, Some("prod")).unwrap();
+        assert_eq!(prod.tenant.as_deref(), Some("corp"));
+        assert_eq!(prod.management_group.as_deref(), Some("mg-prod"));
+        assert_eq!(prod.ignore, base.ignore);
+
+        let lab = Config::parse_profile(text, Some("lab")).unwrap();
+        assert_eq!(lab.tenant.as_deref(), Some("lab-tenant"));
+        assert!(lab.compress_cache);
+        assert_eq!(lab.ignore.len(), 1);
+        assert!(lab.ignore[0].subnet.is_none());
+        assert_eq!(lab.output, Some(OutputFormat::Table));
+
+        let err = Config::parse_profile(text, Some("dev"))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "Unknown profile 'dev' (defined: lab, prod)");
+        // A typo in any profile fails, whichever one is selected.
+        let err = Config::parse(&format!("{text}\n[profile.typo]\ntennant = \"x\"\n"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("profile 'typo'") && err.contains("unknown field"),
+            "got: {err}"
+        );
+    }
+}
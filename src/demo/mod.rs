@@ -0,0 +1,86 @@
+//! Built-in synthetic dataset for `--demo`.
+//!
+//! A made-up tenant (hub, prod and dev spokes, a VPN site, a VPN gateway whose
+//! client pool overlaps dev, an AKS cluster and a lab VNet that duplicates
+//! prod's address space) embedded in the binary,
+//! so the report can be tried without Azure access. The files are written as
+//! cache files and the fetch is pointed at them, which runs the full pipeline
+//! and every subcommand unchanged.
+
+use crate::azure::FetchConfig;
+use std::error::Error;
+use std::path::Path;
+
+/// Cache file contents for sources the demo tenant has none of.
+const EMPTY: &str = r#"{"data": [], "skip_token": null, "total_records": 0, "count": 0}"#;
+
+const SUBNETS: &str = include_str!("subnets.json");
+const PEERING: &str = include_str!("peering.json");
+const LOCAL_GATEWAYS: &str = include_str!("local_gateways.json");
+const SUBSCRIPTIONS: &str = include_str!("subscriptions.json");
+const AKS: &str = include_str!("aks.json");
+const FLOW_LOGS: &str = include_str!("flow_logs.json");
+const GATEWAYS: &str = include_str!("gateways.json");
+
+/// Write the demo dataset to `dir` and point every cache override of
+/// `config` at it, so nothing is fetched from Azure.
+pub fn demo_fetch_config(dir: &Path, config: FetchConfig) -> Result<FetchConfig, Box<dyn Error>> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Error creating demo directory {}: {e}", dir.display()))?;
+    let write = |name: &str, json: &str| -> Result<Option<String>, Box<dyn Error>> {
+        let path = dir.join(format!("demo_{name}.json"));
+        std::fs::write(&path, json)
+            .map_err(|e| format!("Error writing demo data {}: {e}", path.display()))?;
+        Ok(Some(path.to_string_lossy().into_owned()))
+    };
+    log::info!(
+        "Demo mode: using the built-in synthetic dataset in '{}'",
+        dir.display()
+    );
+    Ok(FetchConfig {
+        subnet_cache: write("subnet", SUBNETS)?,
+        peering_cache: write("peering", PEERING)?,
+        local_gateway_cache: write("local_gateway", LOCAL_GATEWAYS)?,
+        vwan_cache: write("vwan", EMPTY)?,
+        subscription_cache: write("subscription", SUBSCRIPTIONS)?,
+        aks_cache: write("aks", AKS)?,
+        flow_log_cache: write("flow_log", FLOW_LOGS)?,
+        gateway_cache: write("gateway", GATEWAYS)?,
+        public_ip_cache: write("public_ip", EMPTY)?,
+        ipam_pool_cache: write("ipam_pool", EMPTY)?,
+        nic_cache: write("nic", EMPTY)?,
+        incremental_base: None,
+        ..config
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::fetch_azure_data;
+
+    #[test]
+    fn demo_dataset_loads_every_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FetchConfig {
+            aks: true,
+            flow_logs: true,
+            gateways: true,
+            ..FetchConfig::default()
+        };
+        let data = fetch_azure_data(&demo_fetch_config(dir.path(), config).unwrap()).unwrap();
+        assert!(data.subnets.from_cache);
+        assert_eq!(data.subnets.data.data.len(), 13);
+        assert_eq!(data.peering_edges.data.len(), 6);
+        assert_eq!(data.subscriptions.data.len(), 3);
+        assert_eq!(data.aks.unwrap().data.len(), 2);
+        assert_eq!(data.flow_logs.unwrap().data.len(), 1);
+        assert_eq!(data.gateways.unwrap().data[0].vnet_name(), "vnet-hub-weu");
+        assert!(data
+            .subnets
+            .data
+            .data
+            .iter()
+            .all(|s| s.subscription_name.starts_with("Demo ")));
+    }
+}
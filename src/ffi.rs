@@ -0,0 +1,354 @@
+//! C API over the IP math and subnet lookup (`ffi` feature).
+//!
+//! For tooling in other languages (Python `ctypes` / `cffi`) that needs the
+//! same CIDR arithmetic and lookups as the report instead of a second
+//! implementation. Build the shared library with
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`; the
+//! declarations are in `include/azure_subnet_summary.h`.
+//!
+//! Strings cross the boundary as NUL-terminated UTF-8. Functions returning
+//! `char *` give a string owned by the caller, to be released with
+//! [`azsub_string_free`]; they return `NULL` on error, with the message
+//! available from [`azsub_last_error`] on the same thread. Structured results
+//! are JSON.
+
+use crate::azure::{read_cache_file, Data};
+use crate::models::{broadcast_addr, cut_addr, next_subnet_ipv4, num_az_hosts, split_subnet, Ipv4};
+use crate::processing::{find_free_block, normalize_cidrs, SubnetIndex};
+use std::cell::RefCell;
+use std::error::Error;
+use std::ffi::{c_char, CStr, CString};
+use std::net::Ipv4Addr;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).expect("NUL bytes removed");
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Run `f`, turning an error or panic into `NULL` plus [`azsub_last_error`].
+fn ffi_string<F>(f: F) -> *mut c_char
+where
+    F: FnOnce() -> Result<Option<String>, Box<dyn Error>>,
+{
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(Some(s))) => match CString::new(s) {
+            Ok(s) => s.into_raw(),
+            Err(e) => {
+                set_last_error(format!("Result contains a NUL byte: {e}"));
+                ptr::null_mut()
+            }
+        },
+        Ok(Ok(None)) => ptr::null_mut(),
+        Ok(Err(e)) => {
+            set_last_error(e.to_string());
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("Internal error (panic)".to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Borrow a C string argument.
+///
+/// # Safety
+/// `s` must be `NULL` or a valid NUL-terminated string.
+unsafe fn arg<'a>(s: *const c_char, name: &str) -> Result<&'a str, Box<dyn Error>> {
+    if s.is_null() {
+        return Err(format!("'{name}' is NULL").into());
+    }
+    Ok(CStr::from_ptr(s)
+        .to_str()
+        .map_err(|e| format!("'{name}' is not UTF-8: {e}"))?)
+}
+
+/// JSON array of CIDR strings.
+fn cidr_list(json: &str, name: &str) -> Result<Vec<Ipv4>, Box<dyn Error>> {
+    let items: Vec<String> = serde_json::from_str(json)
+        .map_err(|e| format!("'{name}' is not a JSON string array: {e}"))?;
+    items.iter().map(|c| Ipv4::new(c)).collect()
+}
+
+/// Message of the last failed call on this thread, or `NULL`. Owned by the
+/// library and valid until the next call on the thread.
+#[no_mangle]
+pub extern "C" fn azsub_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Release a string returned by this library.
+///
+/// # Safety
+/// `s` must be `NULL` or a string returned by an `azsub_` function, not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn azsub_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Usable Azure hosts in a `/prefix_len` subnet (5 reserved addresses), or
+/// -1 when Azure does not allow the size.
+#[no_mangle]
+pub extern "C" fn azsub_azure_hosts(prefix_len: u8) -> i64 {
+    match num_az_hosts(prefix_len) {
+        Ok(hosts) if hosts > 0 => hosts as i64,
+        _ => -1,
+    }
+}
+
+/// Describe `cidr` as JSON: `cidr`, `network`, `broadcast`, `addresses`,
+/// `azure_hosts` and the first / last Azure host (`null` below /29).
+///
+/// # Safety
+/// `cidr` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn azsub_cidr_info(cidr: *const c_char) -> *mut c_char {
+    ffi_string(|| {
+        let cidr = Ipv4::new(arg(cidr, "cidr")?)?;
+        let network = cut_addr(cidr.addr, cidr.mask)?;
+        let broadcast = broadcast_addr(cidr.addr, cidr.mask)?;
+        let hosts = num_az_hosts(cidr.mask).unwrap_or(0);
+        let (first, last) = if hosts > 0 {
+            (
+                Some(Ipv4Addr::from(u32::from(network) + 4).to_string()),
+                Some(Ipv4Addr::from(u32::from(broadcast) - 1).to_string()),
+            )
+        } else {
+            (None, None)
+        };
+        Ok(Some(
+            serde_json::json!({
+                "cidr": format!("{network}/{}", cidr.mask),
+                "network": network.to_string(),
+                "broadcast": broadcast.to_string(),
+                "addresses": 1u64 << (32 - u32::from(cidr.mask)),
+                "azure_hosts": hosts,
+                "first_host": first,
+                "last_host": last,
+            })
+            .to_string(),
+        ))
+    })
+}
+
+/// Every `/new_prefix_len` subnet inside `cidr`, as a JSON string array;
+/// NULL with an error when that is more than 4096 subnets.
+///
+/// # Safety
+/// `cidr` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn azsub_split(cidr: *const c_char, new_prefix_len: u8) -> *mut c_char {
+    ffi_string(|| {
+        let parts = split_subnet(Ipv4::new(arg(cidr, "cidr")?)?, new_prefix_len)?;
+        let parts: Vec<String> = parts.iter().map(Ipv4::to_string).collect();
+        Ok(Some(serde_json::to_string(&parts)?))
+    })
+}
+
+/// The subnet following `cidr`, sized `/prefix_len` (0 keeps the size).
+///
+/// # Safety
+/// `cidr` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn azsub_next_subnet(cidr: *const c_char, prefix_len: u8) -> *mut c_char {
+    ffi_string(|| {
+        let mask = (prefix_len > 0).then_some(prefix_len);
+        Ok(Some(
+            next_subnet_ipv4(Ipv4::new(arg(cidr, "cidr")?)?, mask)?.to_string(),
+        ))
+    })
+}
+
+/// First free `/prefix_len` block in the address spaces `spaces_json` not
+/// overlapping `used_json` (both JSON arrays of CIDRs), as `suggest` picks
+/// it. Returns `NULL` with no error set when there is no room.
+///
+/// # Safety
+/// `spaces_json` and `used_json` must be valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn azsub_find_free_subnet(
+    spaces_json: *const c_char,
+    used_json: *const c_char,
+    prefix_len: u8,
+) -> *mut c_char {
+    ffi_string(|| {
+        let spaces = cidr_list(arg(spaces_json, "spaces_json")?, "spaces_json")?;
+        let used = cidr_list(arg(used_json, "used_json")?, "used_json")?;
+        Ok(find_free_block(&spaces, &used, prefix_len).map(|b| b.to_string()))
+    })
+}
+
+/// A loaded subnet snapshot indexed for lookups.
+pub struct AzsubSnapshot {
+    /// Points into `data`, which is freed only after it.
+    index: SubnetIndex<'static>,
+    data: *mut Data,
+}
+
+/// Load a subnet cache file (`net_<date>_cache_subnet_<key>.json[.zst]`) for
+/// [`azsub_lookup_ip`]. Returns `NULL` on error; release with
+/// [`azsub_snapshot_free`].
+///
+/// # Safety
+/// `path` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn azsub_snapshot_load(path: *const c_char) -> *mut AzsubSnapshot {
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+    let loaded = catch_unwind(AssertUnwindSafe(|| -> Result<Data, Box<dyn Error>> {
+        Ok(normalize_cidrs(read_cache_file(Path::new(arg(path, "path")?))?).0)
+    }));
+    match loaded {
+        Ok(Ok(data)) => {
+            let data = Box::into_raw(Box::new(data));
+            let index = SubnetIndex::new(&*data);
+            Box::into_raw(Box::new(AzsubSnapshot { index, data }))
+        }
+        Ok(Err(e)) => {
+            set_last_error(e.to_string());
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("Internal error (panic)".to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Release a snapshot.
+///
+/// # Safety
+/// `snapshot` must be `NULL` or returned by [`azsub_snapshot_load`], not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn azsub_snapshot_free(snapshot: *mut AzsubSnapshot) {
+    if !snapshot.is_null() {
+        let AzsubSnapshot { index, data } = *Box::from_raw(snapshot);
+        drop(index);
+        drop(Box::from_raw(data));
+    }
+}
+
+/// The subnet containing `ip`, as JSON in the cache file's subnet format.
+/// Returns `NULL` with no error set when no subnet contains it.
+///
+/// # Safety
+/// `snapshot` must come from [`azsub_snapshot_load`] and `ip` must be a valid
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn azsub_lookup_ip(
+    snapshot: *const AzsubSnapshot,
+    ip: *const c_char,
+) -> *mut c_char {
+    ffi_string(|| {
+        let snapshot = snapshot.as_ref().ok_or("'snapshot' is NULL")?;
+        let ip = arg(ip, "ip")?;
+        let ip: Ipv4Addr = ip
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid IP address '{ip}': {e}"))?;
+        snapshot
+            .index
+            .find_ip(ip)
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(Into::into)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    /// Call through the C API and take ownership of the result.
+    fn owned(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let text = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { azsub_string_free(s) };
+        Some(text)
+    }
+
+    fn last_error() -> Option<String> {
+        let e = azsub_last_error();
+        (!e.is_null()).then(|| unsafe { CStr::from_ptr(e) }.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn c_api_matches_the_rust_functions() {
+        let c = |s: &str| CString::new(s).unwrap();
+        assert_eq!(azsub_azure_hosts(24), 251);
+        assert_eq!(azsub_azure_hosts(30), -1);
+
+        let info: serde_json::Value = serde_json::from_str(
+            &owned(unsafe { azsub_cidr_info(c("10.1.2.7/26").as_ptr()) }).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(info["cidr"], "10.1.2.0/26");
+        assert_eq!(info["azure_hosts"], 59);
+        assert_eq!(info["first_host"], "10.1.2.4");
+
+        assert_eq!(
+            owned(unsafe { azsub_split(c("10.0.0.0/24").as_ptr(), 26) }).unwrap(),
+            r#"["10.0.0.0/26","10.0.0.64/26","10.0.0.128/26","10.0.0.192/26"]"#
+        );
+        assert_eq!(
+            owned(unsafe { azsub_split(c("0.0.0.0/0").as_ptr(), 32) }),
+            None
+        );
+        assert!(last_error().unwrap().contains("at most 4096"));
+        assert_eq!(
+            owned(unsafe { azsub_next_subnet(c("10.0.0.0/24").as_ptr(), 0) }).as_deref(),
+            Some("10.0.1.0/24")
+        );
+        let free = |used: &str| {
+            owned(unsafe {
+                azsub_find_free_subnet(c(r#"["10.0.0.0/24"]"#).as_ptr(), c(used).as_ptr(), 26)
+            })
+        };
+        assert_eq!(free(r#"["10.0.0.0/26"]"#).as_deref(), Some("10.0.0.64/26"));
+        assert_eq!(free(r#"["10.0.0.0/24"]"#), None);
+        assert_eq!(last_error(), None);
+
+        assert_eq!(owned(unsafe { azsub_cidr_info(c("nope").as_ptr()) }), None);
+        assert!(last_error().is_some());
+        assert_eq!(owned(unsafe { azsub_cidr_info(ptr::null()) }), None);
+        assert_eq!(last_error().as_deref(), Some("'cidr' is NULL"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        let s = Subnet {
+            subnet_name: "app".to_string(),
+            subnet_cidr: Some(Ipv4::new("10.0.0.0/26").unwrap()),
+            ..Default::default()
+        };
+        let data = Data {
+            data: vec![s],
+            ..Default::default()
+        };
+        std::fs::write(&path, serde_json::to_string(&data).unwrap()).unwrap();
+        let snapshot = unsafe { azsub_snapshot_load(c(path.to_str().unwrap()).as_ptr()) };
+        assert!(!snapshot.is_null());
+        let hit = owned(unsafe { azsub_lookup_ip(snapshot, c("10.0.0.9").as_ptr()) }).unwrap();
+        assert!(hit.contains(r#""app""#), "{hit}");
+        assert_eq!(
+            owned(unsafe { azsub_lookup_ip(snapshot, c("10.0.1.9").as_ptr()) }),
+            None
+        );
+        unsafe { azsub_snapshot_free(snapshot) };
+        assert!(unsafe { azsub_snapshot_load(c("/missing.json").as_ptr()) }.is_null());
+        assert!(last_error().is_some());
+    }
+}
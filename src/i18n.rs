@@ -0,0 +1,128 @@
+//! Message catalog for report headers and summary lines (`--lang`).
+//!
+//! English text doubles as the message key, so untranslated messages (and
+//! the default `en` locale) print exactly as written in the code. Templates
+//! take positional `{}` placeholders filled by [`Lang::fill`]; a translation
+//! must keep the same number of placeholders as its key.
+
+use std::fmt::Display;
+
+/// Language of report headers and summary messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Lang {
+    /// English.
+    #[default]
+    En,
+    /// German.
+    De,
+}
+
+/// German translations, keyed by the English text.
+const DE: &[(&str, &str)] = &[
+    // Subnet CSV header (kept ASCII for spreadsheet imports)
+    ("cnt", "anz"),
+    ("gap", "luecke"),
+    ("subnet_cidr", "subnetz_cidr"),
+    ("subnet_name", "subnetz_name"),
+    ("subscription_name", "abonnement_name"),
+    ("location", "region"),
+    ("subscription_id", "abonnement_id"),
+    ("generated_at", "erstellt_am"),
+    ("resource_group", "ressourcengruppe"),
+    ("role", "rolle"),
+    // Subnet table
+    ("Kind", "Art"),
+    ("Used/Hosts", "Belegt/Hosts"),
+    ("Subnet", "Subnetz"),
+    (
+        "Unallocated space between VNets",
+        "Nicht zugewiesener Adressraum zwischen VNets",
+    ),
+    (
+        "Gap space: {} of {} addresses ({}) in {} block(s)",
+        "Freier Adressraum: {} von {} Adressen ({}) in {} Block/Blöcken",
+    ),
+    (
+        "Gap space: {} addresses in {} block(s)",
+        "Freier Adressraum: {} Adressen in {} Block/Blöcken",
+    ),
+    // VNet summary
+    (
+        "VNET: '{}' '{}' - {} [{} subnet(s)]",
+        "VNET: '{}' '{}' - {} [{} Subnetz(e)]",
+    ),
+    (
+        "  EXCL: '{}' '{}' - {} [{} subnet(s)] [DUP of '{}']",
+        "  EXCL: '{}' '{}' - {} [{} Subnetz(e)] [DUP von '{}']",
+    ),
+    (
+        "  PREFIX: {} - {} of {} addresses free [{} subnet(s)]",
+        "  PREFIX: {} - {} von {} Adressen frei [{} Subnetz(e)]",
+    ),
+    (" [DDoS plan '{}']", " [DDoS-Plan '{}']"),
+    (" [encryption: {}]", " [Verschlüsselung: {}]"),
+    (
+        "-- no VNets shown ({} match) --",
+        "-- keine VNets angezeigt ({} Treffer) --",
+    ),
+    ("-- VNets {}-{} of {} --", "-- VNets {}-{} von {} --"),
+    (" next page: --offset {}", " nächste Seite: --offset {}"),
+    ("0 VNets found in {}", "0 VNets gefunden in {}"),
+];
+
+impl Lang {
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::En => &[],
+            Lang::De => DE,
+        }
+    }
+
+    /// `msg` in this language; `msg` itself when there is no translation.
+    pub fn tr(self, msg: &'static str) -> &'static str {
+        self.catalog()
+            .iter()
+            .find(|(key, _)| *key == msg)
+            .map_or(msg, |(_, text)| text)
+    }
+
+    /// Translate the template `msg` and replace its `{}` placeholders with
+    /// `args`, in order.
+    pub fn fill(self, msg: &'static str, args: &[&dyn Display]) -> String {
+        let mut out = String::new();
+        for (i, part) in self.tr(msg).split("{}").enumerate() {
+            if i > 0 {
+                if let Some(arg) = args.get(i - 1) {
+                    out.push_str(&arg.to_string());
+                }
+            }
+            out.push_str(part);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_keep_their_placeholders() {
+        for (key, text) in DE {
+            assert_eq!(
+                key.matches("{}").count(),
+                text.matches("{}").count(),
+                "{key}"
+            );
+        }
+        assert_eq!(
+            Lang::De.fill("-- VNets {}-{} of {} --", &[&1, &10, &42]),
+            "-- VNets 1-10 von 42 --"
+        );
+        assert_eq!(
+            Lang::En.fill("-- VNets {}-{} of {} --", &[&1, &10, &42]),
+            "-- VNets 1-10 of 42 --"
+        );
+        assert_eq!(Lang::De.tr("nsg"), "nsg");
+    }
+}
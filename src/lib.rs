@@ -7,6 +7,30 @@
 //! - [`azure`] - Azure CLI and Graph API interaction
 //! - [`processing`] - Business logic for subnet processing
 //! - [`output`] - Output formatting (CSV, terminal)
+//! - [`commands`] - Subcommands (`cidr`, ...)
+//! - [`demo`] - Built-in synthetic dataset for `--demo`
+//! - [`report`] - Findings collected for a run (missing NSG, resize, ...)
+//! - [`suppressions`] - Accepted findings that stop failing `check`
+//! - [`snapshot`] - Serializable pipeline output for golden tests
+//! - [`i18n`] - Message catalog for report headers and summary lines (`--lang`)
+//! - [`clock`] - Timezone and date format for dated file names and timestamps
+//! - [`codegen`] - Terraform / Bicep / ARM snippets for proposed subnets
+//! - [`schema`] - JSON Schemas of the serialized outputs (`schema`)
+//! - [`server`] - Long-running server mode (`serve`): cron refreshes and HTTP status
+//! - [`signing`] - minisign signatures of report files (`--sign`, `verify`)
+//! - `ffi` - C API over the IP math and subnet lookup
+//!
+//! # Features
+//! - `cli` (default) - `az` CLI / HTTP integrations, [`commands`], [`pipeline`],
+//!   [`server`] and compressed caches; the binary needs it
+//! - `parallel` (with `cli`) - per-VNet computation on all cores
+//! - `wasm` - browser clock for `wasm32-unknown-unknown`
+//! - `ffi` - `#[no_mangle]` C API (`azsub_*`) for a `cdylib` build
+//!
+//! With `--no-default-features` only the pure computation modules
+//! ([`models`], [`processing`], [`report`], [`output`] formatting) are built,
+//! so a browser planning tool can load a snapshot JSON and run the gap,
+//! overlap and allocation analysis client-side.
 //!
 //! # Example
 //! ```no_run
@@ -18,13 +42,31 @@
 
 // New modular structure
 pub mod azure;
+pub mod clock;
+pub mod codegen;
+#[cfg(feature = "cli")]
+pub mod commands;
+#[cfg(feature = "cli")]
+pub mod demo;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod i18n;
 pub mod models;
 pub mod output;
+#[cfg(feature = "cli")]
 pub mod pipeline;
 pub mod processing;
+pub mod report;
+pub mod schema;
+#[cfg(feature = "cli")]
+pub mod server;
+#[cfg(feature = "cli")]
+pub mod signing;
+#[cfg(feature = "cli")]
+pub mod snapshot;
+pub mod suppressions;
 
-mod cmd;
-mod config;
+pub mod config;
 
 use std::collections::HashSet;
 
@@ -88,3 +130,26 @@ pub fn check_for_duplicate_subnets(data: &azure::Data) -> Result<(), Box<dyn std
     }
     Ok(())
 }
+
+/// Stricter alternative to [`check_for_duplicate_subnets`]: fail when any two
+/// subnets' address ranges intersect, whatever their subscriptions.
+///
+/// Pairs in VNets that are both listed in `isolated_vnets` and not peered with
+/// each other are allowed. Every overlap found is logged as a warning.
+pub fn check_for_overlapping_subnets(
+    data: &azure::Data,
+    peering: &[azure::PeeringEdge],
+    isolated_vnets: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let overlaps = processing::find_overlapping_subnets(data, peering, isolated_vnets);
+    processing::log_overlapping_subnets(&overlaps);
+    if overlaps.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Found {} overlapping subnet pair(s); allow isolated VNets with --isolated-vnets",
+            overlaps.len()
+        )
+        .into())
+    }
+}
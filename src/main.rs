@@ -4,41 +4,110 @@
 //! identifies gaps in IP address allocation, and outputs a CSV summary.
 
 use azure_subnet_summary::{
-    azure::{fetch_azure_data, FetchConfig},
+    azure::{fetch_azure_data, latest_snapshot, set_offline, FetchConfig},
+    commands::{self, Command},
+    config::Config,
+    demo::demo_fetch_config,
+    output::{disable_color, no_color_requested, CsvColumn, OutputFormat},
     pipeline::{run, Args, GraphvizRenderer},
 };
 use clap::Parser;
 use std::error::Error;
+use std::path::Path;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    if args.plain || no_color_requested(std::env::var("NO_COLOR").ok().as_deref()) {
+        disable_color();
+        // The logger's level highlighting follows NO_COLOR.
+        std::env::set_var("NO_COLOR", "1");
+    }
 
     // Initialize logging — fall back to default console logger if config file is missing
     if log4rs::init_file("log4rs.yml", Default::default()).is_err() {
-        let stdout = log4rs::append::console::ConsoleAppender::builder().build();
+        // stderr, so stdout stays clean for piped output (`--output json`)
+        let stderr = log4rs::append::console::ConsoleAppender::builder()
+            .target(log4rs::append::console::Target::Stderr)
+            .build();
         let config = log4rs::Config::builder()
-            .appender(log4rs::config::Appender::builder().build("stdout", Box::new(stdout)))
+            .appender(log4rs::config::Appender::builder().build("stderr", Box::new(stderr)))
             .build(
                 log4rs::config::Root::builder()
-                    .appender("stdout")
+                    .appender("stderr")
                     .build(log::LevelFilter::Info),
             )?;
         log4rs::init_config(config)?;
     }
     dotenv::dotenv().ok();
 
-    log::info!("#Start main()");
+    // `doctor` reports config errors itself instead of failing here.
+    if !matches!(args.command, Some(Command::Doctor)) {
+        args.apply_config(&Config::load(
+            args.config.as_deref(),
+            args.profile.as_deref(),
+        )?)?;
+    }
+
+    if matches!(args.output, Some(OutputFormat::Table | OutputFormat::Json)) && args.stream {
+        return Err(
+            "--stream prints CSV or JSON Lines rows; use it with --output csv or jsonl".into(),
+        );
+    }
+
+    args.check_offline()?;
+    if args.offline {
+        set_offline(true);
+    }
+    args.resolve_tenant();
 
-    let date_str = chrono::Utc::now()
-        .with_timezone(&chrono_tz::Pacific::Auckland)
-        .format("%Y-%m-%d")
-        .to_string();
+    let clock = args.clock()?;
+    let date_str = clock.date_str();
     let cache_dir = format!("report-{date_str}/cache");
+    let fetch_config = FetchConfig {
+        cache_dir: Some(cache_dir.clone()),
+        compress_cache: args.compress_cache,
+        subnet_cache: args.cache_file.clone(),
+        aks: args.aks,
+        flow_logs: args.flow_logs || args.columns.contains(&CsvColumn::FlowLogs),
+        gateways: args.gateways,
+        query: args.query_options(),
+        clock,
+        offline: args.offline,
+        ..FetchConfig::default()
+    };
+    let fetch_config = if args.demo {
+        demo_fetch_config(&Path::new(&cache_dir).join("demo"), fetch_config)?
+    } else {
+        fetch_config
+    };
+
+    if let Some(command) = args.command.take() {
+        return commands::run(&command, &fetch_config, args);
+    }
+
+    log::info!("#Start main()");
     std::fs::create_dir_all(&cache_dir)?;
 
+    let incremental_base = if args.incremental {
+        let base = latest_snapshot(
+            Path::new(&args.history_dir),
+            Path::new(&cache_dir),
+            &fetch_config.query,
+            &fetch_config.clock,
+        );
+        match &base {
+            Some(p) => log::info!("Incremental fetch based on '{}'", p.display()),
+            None => log::warn!("--incremental: no previous snapshot found — doing a full fetch"),
+        }
+        base.map(|p| p.to_string_lossy().into_owned())
+    } else {
+        None
+    };
+
     let azure = fetch_azure_data(&FetchConfig {
-        cache_dir: Some(cache_dir),
-        ..FetchConfig::default()
+        incremental_base,
+        ..fetch_config
     })?;
     run(azure, &args, &GraphvizRenderer)?;
 
@@ -0,0 +1,15 @@
+//! Azure Activity Log event model.
+
+use chrono::{DateTime, Utc};
+
+/// A successful write or delete on a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityEvent {
+    /// User principal name, or application ID of a service principal
+    /// (pipelines, Terraform).
+    pub caller: String,
+    pub timestamp: DateTime<Utc>,
+    /// Operation, e.g. `Microsoft.Network/virtualNetworks/subnets/write`.
+    pub operation: String,
+    pub resource_id: String,
+}
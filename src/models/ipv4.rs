@@ -12,6 +12,10 @@ use std::str::FromStr;
 /// Maximum length for an IPv4 subnet mask (32 bits).
 pub const MAX_LENGTH: u8 = 32;
 
+/// Most prefix bits a split may add, so one split yields at most 4096 subnets
+/// instead of an allocation of up to 2^32 entries.
+pub const MAX_SPLIT_BITS: u8 = 12;
+
 /// Get the CIDR mask as a u32 from an [`Ipv4`] struct.
 pub fn get_cidr_mask_ipv4(ipv4: Ipv4) -> Result<u32, Box<dyn Error>> {
     get_cidr_mask(ipv4.mask)
@@ -115,17 +119,84 @@ pub fn broadcast_addr(addr: Ipv4Addr, len: u8) -> Result<Ipv4Addr, Box<dyn Error
     }
 }
 
+/// Addresses a platform reserves in every subnet, for host counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReservationModel {
+    /// Azure: network, broadcast, gateway and 2 DNS addresses (5).
+    #[default]
+    Azure,
+    /// Classic IP networking: network and broadcast addresses (2).
+    Classic,
+    /// Every address is usable (0).
+    None,
+}
+
+impl ReservationModel {
+    /// Number of reserved addresses per subnet.
+    pub fn reserved(self) -> u64 {
+        match self {
+            ReservationModel::Azure => 5,
+            ReservationModel::Classic => 2,
+            ReservationModel::None => 0,
+        }
+    }
+}
+
+/// Calculate the number of usable host addresses in a `/len` subnet when
+/// `reserved` addresses of it are taken by the platform.
+///
+/// Subnets too small to have a usable address left count 0 hosts; only a
+/// length above /32 is an error.
+pub fn num_hosts(len: u8, reserved: ReservationModel) -> Result<u64, Box<dyn Error>> {
+    if len > MAX_LENGTH {
+        return Err("Network length is too long or invalid".into());
+    }
+    let size = 1u64 << (MAX_LENGTH - len);
+    Ok(size.saturating_sub(reserved.reserved()))
+}
+
 /// Calculate the number of usable host addresses in an Azure subnet.
 ///
-/// Azure reserves 5 IP addresses per subnet (network, broadcast, gateway, and 2 DNS).
+/// Azure reserves 5 IP addresses per subnet (network, broadcast, gateway, and 2 DNS),
+/// so /29 (the smallest Azure subnet) has 3 hosts and /30 - /32 have none.
 pub fn num_az_hosts(len: u8) -> Result<u64, Box<dyn Error>> {
-    if len >= MAX_LENGTH - 2 {
-        // /29 = 6 IPs, only 1 host usable
-        Err("Network length is too long or invalid".into())
-    } else {
-        let num_az_hosts = (1u64 << (MAX_LENGTH - len)) - 5;
-        Ok(num_az_hosts)
+    num_hosts(len, ReservationModel::Azure)
+}
+
+/// Split an [`Ipv4`] network into all subnets of prefix length `new_mask`.
+///
+/// The input is cut to its network address first, so `10.0.0.5/24` splits the
+/// same as `10.0.0.0/24`. At most [`MAX_SPLIT_BITS`] prefix bits may be added.
+pub fn split_subnet(ipv4: Ipv4, new_mask: u8) -> Result<Vec<Ipv4>, Box<dyn Error>> {
+    if new_mask > MAX_LENGTH {
+        return Err("Network length is too long".into());
     }
+    if new_mask < ipv4.mask {
+        return Err(format!(
+            "Cannot split /{} into larger /{new_mask} subnets",
+            ipv4.mask
+        )
+        .into());
+    }
+    if new_mask - ipv4.mask > MAX_SPLIT_BITS {
+        return Err(format!(
+            "Splitting /{} into /{new_mask} gives {} subnets (at most {})",
+            ipv4.mask,
+            1u64 << (new_mask - ipv4.mask),
+            1u64 << MAX_SPLIT_BITS
+        )
+        .into());
+    }
+    let start = u32::from(cut_addr(ipv4.addr, ipv4.mask)?);
+    let count = 1u64 << (new_mask - ipv4.mask);
+    let step = 1u64 << (MAX_LENGTH - new_mask);
+    Ok((0..count)
+        .map(|i| Ipv4 {
+            addr: Ipv4Addr::from((u64::from(start) + i * step) as u32),
+            mask: new_mask,
+        })
+        .collect())
 }
 
 /// Calculate the minimum mask for an IP address based on trailing zeros.
@@ -170,11 +241,19 @@ impl<'de> Deserialize<'de> for Ipv4 {
             .map_err(|_| de::Error::custom(format!("invalid IP address: {}", parts[0])))?;
         let mask = u8::from_str(parts[1])
             .map_err(|_| de::Error::custom(format!("invalid subnet mask: {}", parts[1])))?;
+        if mask > MAX_LENGTH {
+            return Err(de::Error::custom(format!("subnet mask too long: /{mask}")));
+        }
 
         Ok(Ipv4 { addr, mask })
     }
 }
 
+/// Parse a CIDR command-line value, e.g. `10.0.0.0/8` (clap value parser).
+pub fn parse_cidr(s: &str) -> Result<Ipv4, String> {
+    Ipv4::new(s).map_err(|e| format!("'{s}' is not a CIDR like 10.0.0.0/8: {e}"))
+}
+
 impl Ipv4 {
     /// Create a new [`Ipv4`] from a CIDR string (e.g., "10.0.0.0/24").
     pub fn new(addr_cidr: &str) -> Result<Ipv4, Box<dyn Error>> {
@@ -214,6 +293,14 @@ impl Ipv4 {
             .unwrap_or_else(|e| panic!("Error calculating minimum address for {self}: {e}"))
     }
 
+    /// Address range `[lo, hi)` of the subnet, as integers.
+    pub fn range(&self) -> (u64, u64) {
+        (
+            u64::from(u32::from(self.lo())),
+            u64::from(u32::from(self.hi())) + 1,
+        )
+    }
+
     /// Check if an IP address is contained within this subnet.
     pub fn contains(&self, ip: Ipv4Addr) -> bool {
         ip >= self.lo() && ip <= self.hi()
@@ -269,6 +356,29 @@ mod tests {
         assert!(ip_after_subnet(Ipv4Addr::new(255, 255, 255, 255), 24).is_err());
     }
 
+    #[test]
+    fn test_split_subnet() {
+        let parts = split_subnet(Ipv4::new("10.0.0.0/22").unwrap(), 24).unwrap();
+        let parts: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            parts,
+            vec!["10.0.0.0/24", "10.0.1.0/24", "10.0.2.0/24", "10.0.3.0/24"]
+        );
+        assert_eq!(
+            split_subnet(Ipv4::new("10.0.0.9/30").unwrap(), 30).unwrap(),
+            vec![Ipv4::new("10.0.0.8/30").unwrap()]
+        );
+        assert!(split_subnet(Ipv4::new("10.0.0.0/24").unwrap(), 23).is_err());
+        assert!(split_subnet(Ipv4::new("10.0.0.0/24").unwrap(), 33).is_err());
+        assert_eq!(
+            split_subnet(Ipv4::new("10.0.0.0/8").unwrap(), 20)
+                .unwrap()
+                .len(),
+            4096
+        );
+        assert!(split_subnet(Ipv4::new("0.0.0.0/0").unwrap(), 32).is_err());
+    }
+
     #[test]
     fn test_next_subnet_ipv4() {
         let ip1 = Ipv4::new("10.1.1.0/28").unwrap();
@@ -361,11 +471,23 @@ mod tests {
         assert_eq!(num_az_hosts(27).unwrap(), 27);
         assert_eq!(num_az_hosts(28).unwrap(), 11);
         assert_eq!(num_az_hosts(29).unwrap(), 3);
+        assert_eq!(num_az_hosts(30).unwrap(), 0);
+        assert_eq!(num_az_hosts(32).unwrap(), 0);
         assert_eq!(
-            num_az_hosts(30).unwrap_err().to_string(),
+            num_az_hosts(33).unwrap_err().to_string(),
             "Network length is too long or invalid"
         );
-        assert!(num_az_hosts(33).is_err());
+    }
+
+    #[test]
+    fn test_num_hosts_per_reservation_model() {
+        assert_eq!(num_hosts(24, ReservationModel::Azure).unwrap(), 251);
+        assert_eq!(num_hosts(24, ReservationModel::Classic).unwrap(), 254);
+        assert_eq!(num_hosts(24, ReservationModel::None).unwrap(), 256);
+        assert_eq!(num_hosts(30, ReservationModel::Classic).unwrap(), 2);
+        assert_eq!(num_hosts(31, ReservationModel::Classic).unwrap(), 0);
+        assert_eq!(num_hosts(32, ReservationModel::None).unwrap(), 1);
+        assert!(num_hosts(33, ReservationModel::None).is_err());
     }
 
     #[test]
@@ -427,7 +549,7 @@ mod tests {
         // Test that checking multiple vnets works correctly
         let vnet1 = Ipv4::new("10.0.0.0/16").unwrap();
         let vnet2 = Ipv4::new("10.6.8.0/24").unwrap();
-        let vnets = vec![vnet1, vnet2];
+        let vnets = [vnet1, vnet2];
 
         // IP in first vnet
         let ip1 = Ipv4Addr::new(10, 0, 5, 100);
@@ -445,4 +567,12 @@ mod tests {
         let ip4 = Ipv4Addr::new(192, 168, 1, 1);
         assert!(!vnets.iter().any(|vnet| vnet.contains(ip4)));
     }
+
+    #[test]
+    fn test_range_and_parse_cidr() {
+        let cidr = parse_cidr("10.0.1.7/24").unwrap();
+        assert_eq!(cidr.range(), (0x0A00_0100, 0x0A00_0200));
+        assert_eq!(Ipv4::new("0.0.0.0/0").unwrap().range(), (0, 1 << 32));
+        assert!(parse_cidr("10.0.0.0").unwrap_err().contains("not a CIDR"));
+    }
 }
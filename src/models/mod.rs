@@ -1,18 +1,25 @@
 //! Domain models for Azure subnet summary.
 //!
 //! This module contains the core data structures used throughout the application:
+//! - [`ActivityEvent`] - A write or delete from the Azure Activity Log
 //! - [`Ipv4`] - IPv4 address with CIDR notation support
 //! - [`Subnet`] - Azure subnet representation
+//! - [`Subscription`] - Azure subscription metadata (state, tags, management groups)
 //! - [`Vnet`] and [`VnetList`] - Virtual network structures
 
+mod activity;
 mod ipv4;
 mod subnet;
+mod subscription;
 mod vnet;
 
 // Re-export public types
+pub use activity::ActivityEvent;
 pub use ipv4::{
     broadcast_addr, cut_addr, cut_addr_ipv4, get_cidr_mask, get_cidr_mask_ipv4, ip_after_subnet,
-    lo_mask, next_subnet_ipv4, num_az_hosts, Ipv4, MAX_LENGTH,
+    lo_mask, next_subnet_ipv4, num_az_hosts, num_hosts, parse_cidr, split_subnet, Ipv4,
+    ReservationModel, MAX_LENGTH, MAX_SPLIT_BITS,
 };
-pub use subnet::Subnet;
+pub use subnet::{Subnet, SubnetSource};
+pub use subscription::{ManagementGroupRef, Subscription};
 pub use vnet::{Vnet, VnetList};
@@ -18,19 +18,122 @@ fn pick_vnet_cidr(cidrs: &[Ipv4], subnet_cidr: Option<Ipv4>) -> Ipv4 {
         .unwrap_or_else(|| Ipv4::new("0.0.0.0/0").expect("valid sentinel"))
 }
 
+/// Resource group segment of an ARM resource ID, matched case-insensitively.
+fn resource_group_from_id(id: &str) -> Option<&str> {
+    let mut parts = id.split('/');
+    parts
+        .by_ref()
+        .find(|p| p.eq_ignore_ascii_case("resourceGroups"))?;
+    parts.next().filter(|rg| !rg.is_empty())
+}
+
+/// A subnet delegation as returned by Resource Graph (full object) or as
+/// written back to the cache (service name only).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DelegationRaw {
+    Service(String),
+    Object { properties: DelegationProperties },
+}
+
+#[derive(Deserialize)]
+struct DelegationProperties {
+    #[serde(rename = "serviceName")]
+    service_name: String,
+}
+
+impl From<DelegationRaw> for String {
+    fn from(raw: DelegationRaw) -> Self {
+        match raw {
+            DelegationRaw::Service(name) => name,
+            DelegationRaw::Object { properties } => properties.service_name,
+        }
+    }
+}
+
+/// Where a subnet record comes from.
+///
+/// Live Azure data is merged with address ranges from other sources so every
+/// analysis covers the full address universe.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum SubnetSource {
+    /// Resource Graph (or a cache of it).
+    #[default]
+    Azure,
+    /// Range reserved for future use.
+    Reserved,
+    /// Range in use on-premises (or in another cloud).
+    OnPrem,
+    /// Subnet that is planned but not yet deployed (e.g. a Terraform plan).
+    Planned,
+    /// Point-to-site client address pool of a VPN gateway.
+    VpnClient,
+}
+
+impl SubnetSource {
+    /// `true` for subnets that are (or will be) Azure resources, as opposed
+    /// to ranges that only stand in for address space used elsewhere.
+    pub fn is_azure_resource(self) -> bool {
+        matches!(self, SubnetSource::Azure | SubnetSource::Planned)
+    }
+}
+
+impl fmt::Display for SubnetSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SubnetSource::Azure => "azure",
+            SubnetSource::Reserved => "reserved",
+            SubnetSource::OnPrem => "on-prem",
+            SubnetSource::Planned => "planned",
+            SubnetSource::VpnClient => "vpn-client",
+        })
+    }
+}
+
 /// Raw deserialization target — vnet_cidr kept as Vec to enable correct CIDR selection.
 #[derive(Deserialize)]
 struct SubnetRaw {
+    // Resource IDs are missing from caches written before they were queried.
+    #[serde(default)]
+    resource_group: String,
+    #[serde(default)]
+    vnet_id: String,
     vnet_name: String,
     vnet_cidr: Vec<Ipv4>,
+    // Full address space; written back to the cache because `vnet_cidr` is
+    // narrowed to one prefix. Absent in Resource Graph output and old caches.
+    #[serde(default)]
+    vnet_address_space: Option<Vec<Ipv4>>,
+    #[serde(default)]
+    subnet_id: String,
     subnet_name: String,
     subnet_cidr: Option<Ipv4>,
     nsg: Option<String>,
+    #[serde(default)]
+    delegations: Option<Vec<DelegationRaw>>,
+    #[serde(default)]
+    nat_gateway: Option<String>,
+    #[serde(default)]
+    load_balancers: Option<Vec<String>>,
     location: String,
     dns_servers: Option<Vec<String>>,
+    // Empty string when the VNet has no DDoS plan / encryption.
+    #[serde(default)]
+    ddos_protection_plan: Option<String>,
+    #[serde(default)]
+    encryption: Option<String>,
     subscription_id: String,
     subscription_name: String,
     ip_configurations_count: Option<u32>,
+    #[serde(default)]
+    service_association_links: Option<u32>,
+    #[serde(default)]
+    source: SubnetSource,
+    #[serde(default)]
+    fetched_at: Option<String>,
+    #[serde(default)]
+    query_block: Option<u32>,
 }
 
 /// Serializes `vnet_cidr` back to a single-element JSON array to match the cache format.
@@ -48,48 +151,165 @@ where
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(from = "SubnetRaw")]
 pub struct Subnet {
+    /// Resource group of the virtual network (empty when unknown).
+    pub resource_group: String,
+    /// Full ARM resource ID of the virtual network (empty for old caches).
+    pub vnet_id: String,
     /// Name of the virtual network containing this subnet.
     pub vnet_name: String,
     /// The specific VNet_CIDR (address space) this subnet belongs to.
     /// Serialized as a single-element JSON array to match the Azure cache format.
     #[serde(serialize_with = "serialize_vnet_cidr")]
     pub vnet_cidr: Ipv4,
+    /// Every address prefix of the VNet, in Azure's order.
+    pub vnet_address_space: Vec<Ipv4>,
+    /// Full ARM resource ID of the subnet (empty for old caches).
+    pub subnet_id: String,
     /// Name of the subnet.
     pub subnet_name: String,
     /// CIDR block of the subnet (None if not configured).
     pub subnet_cidr: Option<Ipv4>,
     /// Network Security Group ID (if attached).
     pub nsg: Option<String>,
+    /// Service names the subnet is delegated to, e.g. `Microsoft.Web/serverFarms`.
+    pub delegations: Vec<String>,
+    /// NAT gateway ID (if attached).
+    pub nat_gateway: Option<String>,
+    /// Names of load balancers with a frontend IP configuration in this subnet
+    /// (internal load balancers), sorted and de-duplicated.
+    pub load_balancers: Vec<String>,
     /// Azure region location.
     pub location: String,
     /// Custom DNS servers configured on the VNet.
     pub dns_servers: Option<Vec<String>>,
+    /// DDoS Network Protection plan ID of the VNet (None when protection is off).
+    pub ddos_protection_plan: Option<String>,
+    /// VNet encryption enforcement (`AllowUnencrypted` / `DropUnencrypted`);
+    /// None when encryption is disabled.
+    pub encryption: Option<String>,
     /// Azure subscription ID.
     pub subscription_id: String,
     /// Azure subscription display name.
     pub subscription_name: String,
     /// Number of IP configurations (NICs) using this subnet.
     pub ip_configurations_count: Option<u32>,
+    /// Number of service association links (App Service plans / Container Apps
+    /// environments integrated with this subnet).
+    pub service_association_links: Option<u32>,
+    /// Where this record comes from (live Azure data unless merged in).
+    pub source: SubnetSource,
+    /// When the record was fetched from Azure, as an RFC 3339 timestamp
+    /// (None for other sources and caches without a generation time).
+    pub fetched_at: Option<String>,
+    /// Resource Graph result page the record came from, starting at 0
+    /// (None for other sources and older caches).
+    pub query_block: Option<u32>,
 }
 
 impl From<SubnetRaw> for Subnet {
     fn from(raw: SubnetRaw) -> Self {
         let vnet_cidr = pick_vnet_cidr(&raw.vnet_cidr, raw.subnet_cidr);
+        // Caches written before resource_group was queried may still carry the vnet ID.
+        let resource_group = if raw.resource_group.is_empty() {
+            resource_group_from_id(&raw.vnet_id)
+                .unwrap_or_default()
+                .to_string()
+        } else {
+            raw.resource_group
+        };
         Subnet {
+            resource_group,
+            vnet_id: raw.vnet_id,
             vnet_name: raw.vnet_name,
             vnet_cidr,
+            vnet_address_space: raw.vnet_address_space.unwrap_or(raw.vnet_cidr),
+            subnet_id: raw.subnet_id,
             subnet_name: raw.subnet_name,
             subnet_cidr: raw.subnet_cidr,
             nsg: raw.nsg,
+            delegations: raw
+                .delegations
+                .unwrap_or_default()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            nat_gateway: raw.nat_gateway,
+            load_balancers: {
+                // One entry per frontend IP configuration; report each LB once.
+                let mut lbs = raw.load_balancers.unwrap_or_default();
+                lbs.sort_by_key(|lb| lb.to_lowercase());
+                lbs.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+                lbs
+            },
             location: raw.location,
             dns_servers: raw.dns_servers,
+            ddos_protection_plan: raw.ddos_protection_plan.filter(|p| !p.is_empty()),
+            encryption: raw.encryption.filter(|e| !e.is_empty()),
             subscription_id: raw.subscription_id,
             subscription_name: raw.subscription_name,
             ip_configurations_count: raw.ip_configurations_count,
+            service_association_links: raw.service_association_links,
+            source: raw.source,
+            fetched_at: raw.fetched_at,
+            query_block: raw.query_block,
+        }
+    }
+}
+
+impl Default for Subnet {
+    fn default() -> Self {
+        Subnet {
+            resource_group: "".to_string(),
+            vnet_id: "".to_string(),
+            vnet_name: "blank".to_string(),
+            vnet_cidr: Ipv4::new("0.0.0.0/0").expect("valid sentinel"),
+            vnet_address_space: Vec::new(),
+            subnet_id: "".to_string(),
+            subnet_name: "".to_string(),
+            subnet_cidr: None,
+            nsg: None,
+            delegations: Vec::new(),
+            nat_gateway: None,
+            load_balancers: Vec::new(),
+            location: "blank".to_string(),
+            dns_servers: None,
+            ddos_protection_plan: None,
+            encryption: None,
+            subscription_id: "blank".to_string(),
+            subscription_name: "blank".to_string(),
+            ip_configurations_count: None,
+            service_association_links: None,
+            source: SubnetSource::Azure,
+            fetched_at: None,
+            query_block: None,
         }
     }
 }
 
+#[cfg(test)]
+impl Subnet {
+    /// Test fixture: subnet `name` with `cidr` (empty for none) in VNet
+    /// `vnet`; set other fields with `..Subnet::test(...)`.
+    pub(crate) fn test(vnet: &str, name: &str, cidr: &str) -> Subnet {
+        Subnet {
+            vnet_name: vnet.to_string(),
+            subnet_name: name.to_string(),
+            subnet_cidr: (!cidr.is_empty()).then(|| Ipv4::new(cidr).expect("test CIDR")),
+            ..Subnet::default()
+        }
+    }
+}
+
+impl fmt::Display for Subnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cidr = self
+            .subnet_cidr
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "None".to_string());
+        write!(f, "{}/{} ({})", self.vnet_name, self.subnet_name, cidr)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,6 +352,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn full_address_space_survives_a_cache_round_trip() {
+        let json = r#"{
+            "vnet_name": "v",
+            "vnet_cidr": ["10.1.0.0/16", "10.2.0.0/16"],
+            "subnet_name": "s",
+            "subnet_cidr": "10.2.1.0/24",
+            "location": "westeurope",
+            "subscription_id": "sub-001",
+            "subscription_name": "Test Sub"
+        }"#;
+        let subnet: Subnet = serde_json::from_str(json).expect("deserialize failed");
+        let space = vec![
+            Ipv4::new("10.1.0.0/16").unwrap(),
+            Ipv4::new("10.2.0.0/16").unwrap(),
+        ];
+        assert_eq!(subnet.vnet_address_space, space);
+
+        let cached: Subnet =
+            serde_json::from_str(&serde_json::to_string(&subnet).unwrap()).unwrap();
+        assert_eq!(cached.vnet_cidr, Ipv4::new("10.2.0.0/16").unwrap());
+        assert_eq!(cached.vnet_address_space, space);
+    }
+
     #[test]
     fn excluded_by_in_cached_json_is_ignored_on_deserialize() {
         // Old cache files may contain excluded_by — must deserialize without error.
@@ -149,31 +393,110 @@ mod tests {
         let subnet: Subnet = serde_json::from_str(json).expect("deserialize failed");
         assert_eq!(subnet.vnet_name, "my-vnet");
     }
-}
 
-impl Default for Subnet {
-    fn default() -> Self {
-        Subnet {
-            vnet_name: "blank".to_string(),
-            vnet_cidr: Ipv4::new("0.0.0.0/0").expect("valid sentinel"),
-            subnet_name: "".to_string(),
-            subnet_cidr: None,
-            nsg: None,
-            location: "blank".to_string(),
-            dns_servers: None,
-            subscription_id: "blank".to_string(),
-            subscription_name: "blank".to_string(),
-            ip_configurations_count: None,
-        }
+    #[test]
+    fn resource_ids_round_trip_and_default_when_missing() {
+        let json = r#"{
+            "vnet_id": "/subscriptions/sub-001/resourceGroups/rg-net/providers/Microsoft.Network/virtualNetworks/my-vnet",
+            "vnet_name": "my-vnet",
+            "vnet_cidr": ["10.0.0.0/16"],
+            "subnet_id": "/subscriptions/sub-001/resourceGroups/rg-net/providers/Microsoft.Network/virtualNetworks/my-vnet/subnets/my-subnet",
+            "subnet_name": "my-subnet",
+            "subnet_cidr": "10.0.1.0/24",
+            "location": "eastus",
+            "subscription_id": "sub-001",
+            "subscription_name": "Test Sub"
+        }"#;
+        let subnet: Subnet = serde_json::from_str(json).expect("deserialize failed");
+        assert!(subnet
+            .subnet_id
+            .ends_with("/virtualNetworks/my-vnet/subnets/my-subnet"));
+        let again: Subnet = serde_json::from_str(&serde_json::to_string(&subnet).unwrap()).unwrap();
+        assert_eq!(again.vnet_id, subnet.vnet_id);
+        assert_eq!(again.subnet_id, subnet.subnet_id);
+
+        let old: Subnet = serde_json::from_str(
+            r#"{"vnet_name": "v", "vnet_cidr": ["10.0.0.0/16"], "subnet_name": "s",
+                "subnet_cidr": null, "location": "eastus",
+                "subscription_id": "x", "subscription_name": "X"}"#,
+        )
+        .expect("deserialize failed");
+        assert!(old.vnet_id.is_empty());
+        assert!(old.subnet_id.is_empty());
     }
-}
 
-impl fmt::Display for Subnet {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let cidr = self
-            .subnet_cidr
-            .map(|c| c.to_string())
-            .unwrap_or_else(|| "None".to_string());
-        write!(f, "{}/{} ({})", self.vnet_name, self.subnet_name, cidr)
+    #[test]
+    fn provenance_round_trips_and_defaults_for_old_caches() {
+        let subnet = Subnet {
+            fetched_at: Some("2024-05-01T12:00:00+00:00".to_string()),
+            query_block: Some(2),
+            ..Subnet::default()
+        };
+        let again: Subnet = serde_json::from_str(&serde_json::to_string(&subnet).unwrap()).unwrap();
+        assert_eq!(again.fetched_at, subnet.fetched_at);
+        assert_eq!(again.query_block, Some(2));
+        assert_eq!(again.source, SubnetSource::Azure);
+
+        let old: Subnet = serde_json::from_str(
+            r#"{"vnet_name": "v", "vnet_cidr": ["10.0.0.0/16"], "subnet_name": "s",
+                "subnet_cidr": null, "location": "eastus",
+                "subscription_id": "x", "subscription_name": "X"}"#,
+        )
+        .expect("deserialize failed");
+        assert_eq!((old.fetched_at, old.query_block), (None, None));
+    }
+
+    #[test]
+    fn resource_group_falls_back_to_vnet_id() {
+        let json = r#"{
+            "vnet_id": "/subscriptions/sub-001/resourceGroups/rg-net/providers/Microsoft.Network/virtualNetworks/v",
+            "vnet_name": "v", "vnet_cidr": ["10.0.0.0/16"], "subnet_name": "s",
+            "subnet_cidr": null, "location": "eastus",
+            "subscription_id": "sub-001", "subscription_name": "X"
+        }"#;
+        let subnet: Subnet = serde_json::from_str(json).expect("deserialize failed");
+        assert_eq!(subnet.resource_group, "rg-net");
+        assert_eq!(
+            resource_group_from_id("/subscriptions/x/resourcegroups/RG-A/providers"),
+            Some("RG-A")
+        );
+        assert_eq!(resource_group_from_id(""), None);
+    }
+
+    #[test]
+    fn delegations_deserialize_from_graph_objects_and_cached_names() {
+        let graph = r#"{
+            "vnet_name": "v", "vnet_cidr": ["10.0.0.0/16"], "subnet_name": "s",
+            "subnet_cidr": "10.0.1.0/24", "location": "eastus",
+            "subscription_id": "x", "subscription_name": "X",
+            "delegations": [{"name": "d", "properties": {"serviceName": "Microsoft.Web/serverFarms"}}]
+        }"#;
+        let subnet: Subnet = serde_json::from_str(graph).expect("deserialize failed");
+        assert_eq!(subnet.delegations, ["Microsoft.Web/serverFarms"]);
+
+        let cached: Subnet = serde_json::from_str(&serde_json::to_string(&subnet).unwrap())
+            .expect("deserialize failed");
+        assert_eq!(cached.delegations, subnet.delegations);
+
+        let none: Subnet = serde_json::from_str(&graph.replace(
+            r#"[{"name": "d", "properties": {"serviceName": "Microsoft.Web/serverFarms"}}]"#,
+            "null",
+        ))
+        .expect("deserialize failed");
+        assert!(none.delegations.is_empty());
+    }
+
+    #[test]
+    fn load_balancers_are_deduplicated_per_subnet() {
+        let json = r#"{
+            "vnet_name": "v", "vnet_cidr": ["10.0.0.0/16"], "subnet_name": "s",
+            "subnet_cidr": "10.0.1.0/24", "location": "eastus",
+            "subscription_id": "x", "subscription_name": "X",
+            "nat_gateway": "/subscriptions/x/resourceGroups/rg/providers/Microsoft.Network/natGateways/ngw-1",
+            "load_balancers": ["ilb-b", "ilb-a", "ILB-B"]
+        }"#;
+        let subnet: Subnet = serde_json::from_str(json).expect("deserialize failed");
+        assert_eq!(subnet.load_balancers, ["ilb-a", "ilb-b"]);
+        assert!(subnet.nat_gateway.unwrap().ends_with("/natGateways/ngw-1"));
     }
 }
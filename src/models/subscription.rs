@@ -0,0 +1,145 @@
+//! Azure subscription metadata model.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// One entry of a subscription's management group ancestor chain.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ManagementGroupRef {
+    /// Management group ID (the `name` of the management group resource).
+    pub name: String,
+    /// Human-readable display name.
+    #[serde(default, rename = "displayName")]
+    pub display_name: String,
+}
+
+/// Deserialize a JSON value that may be an object/array or `null`, treating
+/// `null` as `Default::default()`.
+fn null_as_default<'de, D, T>(de: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::<T>::deserialize(de).map(|v| v.unwrap_or_default())
+}
+
+/// An Azure subscription with its state, tags, and management group placement.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Subscription {
+    /// Azure subscription ID.
+    pub subscription_id: String,
+    /// Azure subscription display name.
+    pub subscription_name: String,
+    /// Subscription state (`Enabled`, `Disabled`, `Warned`, ...).
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub state: String,
+    /// Resource tags on the subscription.
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tags: BTreeMap<String, String>,
+    /// Management group ancestors, ordered from the direct parent up to the
+    /// tenant root group (the order Azure returns them in).
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub management_group_chain: Vec<ManagementGroupRef>,
+}
+
+impl Subscription {
+    /// Name of the management group the subscription is placed in directly.
+    pub fn management_group(&self) -> Option<&str> {
+        self.management_group_chain
+            .first()
+            .map(|mg| mg.name.as_str())
+    }
+
+    /// Management group path from the root down to the direct parent,
+    /// e.g. `"Tenant Root Group / Platform / Connectivity"`.
+    ///
+    /// Display names are preferred; the ID is used when no display name is known.
+    pub fn management_group_path(&self) -> String {
+        self.management_group_chain
+            .iter()
+            .rev()
+            .map(|mg| {
+                if mg.display_name.is_empty() {
+                    mg.name.as_str()
+                } else {
+                    mg.display_name.as_str()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// Returns `true` when `management_group` (ID or display name, case-insensitive)
+    /// appears anywhere in the ancestor chain.
+    pub fn in_management_group(&self, management_group: &str) -> bool {
+        self.management_group_chain.iter().any(|mg| {
+            mg.name.eq_ignore_ascii_case(management_group)
+                || mg.display_name.eq_ignore_ascii_case(management_group)
+        })
+    }
+}
+
+impl fmt::Display for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) [{}]",
+            self.subscription_name, self.subscription_id, self.state
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Subscription {
+        serde_json::from_str(
+            r#"{
+                "subscription_id": "aaaa-1111",
+                "subscription_name": "Prod Sub",
+                "state": "Enabled",
+                "tags": {"env": "prod"},
+                "management_group_chain": [
+                    {"name": "mg-connectivity", "displayName": "Connectivity"},
+                    {"name": "mg-platform", "displayName": "Platform"},
+                    {"name": "tenant-root", "displayName": "Tenant Root Group"}
+                ]
+            }"#,
+        )
+        .expect("deserialize failed")
+    }
+
+    #[test]
+    fn management_group_is_direct_parent() {
+        assert_eq!(sample().management_group(), Some("mg-connectivity"));
+    }
+
+    #[test]
+    fn management_group_path_runs_root_to_leaf() {
+        assert_eq!(
+            sample().management_group_path(),
+            "Tenant Root Group / Platform / Connectivity"
+        );
+    }
+
+    #[test]
+    fn in_management_group_matches_ancestors_by_id_or_display_name() {
+        let sub = sample();
+        assert!(sub.in_management_group("mg-platform"));
+        assert!(sub.in_management_group("platform"));
+        assert!(!sub.in_management_group("landing-zones"));
+    }
+
+    #[test]
+    fn null_tags_and_chain_deserialize_as_empty() {
+        let sub: Subscription = serde_json::from_str(
+            r#"{"subscription_id": "x", "subscription_name": "X", "state": null, "tags": null, "management_group_chain": null}"#,
+        )
+        .expect("deserialize failed");
+        assert!(sub.tags.is_empty());
+        assert!(sub.management_group_chain.is_empty());
+        assert_eq!(sub.management_group(), None);
+    }
+}
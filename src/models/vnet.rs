@@ -13,10 +13,16 @@ pub struct Vnet<'a> {
     pub vnet_cidr: Vec<Ipv4>,
     /// Azure region location.
     pub location: &'a str,
+    /// Resource group of the virtual network (empty when unknown).
+    pub resource_group: &'a str,
     /// Azure subscription ID.
     pub subscription_id: &'a str,
     /// Azure subscription display name.
     pub subscription_name: &'a str,
+    /// DDoS Network Protection plan ID (None when protection is off).
+    pub ddos_protection_plan: Option<&'a str>,
+    /// VNet encryption enforcement (None when encryption is disabled).
+    pub encryption: Option<&'a str>,
     /// Subnets within this VNet.
     pub subnets: Vec<&'a Subnet>,
 }
@@ -28,8 +34,11 @@ impl<'a> Vnet<'a> {
             vnet_name: &subnet.vnet_name,
             vnet_cidr: vec![subnet.vnet_cidr],
             location: &subnet.location,
+            resource_group: &subnet.resource_group,
             subscription_id: &subnet.subscription_id,
             subscription_name: &subnet.subscription_name,
+            ddos_protection_plan: subnet.ddos_protection_plan.as_deref(),
+            encryption: subnet.encryption.as_deref(),
             subnets: vec![subnet],
         }
     }
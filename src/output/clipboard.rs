@@ -0,0 +1,97 @@
+//! Report text on the system clipboard (`--copy`), for pasting small
+//! outputs into tickets.
+//!
+//! The text is piped to the platform's clipboard tool — `pbcopy` on macOS,
+//! `clip` on Windows, `wl-copy`, `xclip` or `xsel` on Linux (and `clip.exe`
+//! under WSL) — the same way Azure is reached through `az`, instead of
+//! linking a windowing system.
+
+use crate::azure::resolve_program;
+use std::error::Error;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Clipboard tools to try, in order, with their arguments.
+fn clipboard_tools() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(windows) {
+        vec![("clip", &[])]
+    } else {
+        let mut tools: Vec<(&str, &[&str])> = vec![
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+            ("clip.exe", &[]),
+        ];
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            tools.insert(0, ("wl-copy", &[]));
+        }
+        tools
+    }
+}
+
+/// Pipe `text` to the first of `tools` that is installed.
+fn copy_with(text: &str, tools: &[(&str, &[&str])]) -> Result<(), Box<dyn Error>> {
+    for (tool, args) in tools {
+        let child = Command::new(resolve_program(tool))
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            // xclip, xsel and wl-copy fork a process that keeps serving the
+            // clipboard; a piped stderr would stay open until it exits.
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to run '{tool}': {e}").into()),
+        };
+        // A tool that fails early closes its input; report its exit status
+        // rather than the broken pipe.
+        let written = match child.stdin.take() {
+            Some(mut stdin) => stdin.write_all(text.as_bytes()),
+            None => Ok(()),
+        };
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(format!("'{tool}' could not copy to the clipboard: {status}").into());
+        }
+        written.map_err(|e| format!("Failed to write to '{tool}': {e}"))?;
+        log::info!("Copied {} bytes to the clipboard with '{tool}'", text.len());
+        return Ok(());
+    }
+    let names: Vec<&str> = tools.iter().map(|(tool, _)| *tool).collect();
+    Err(format!(
+        "No clipboard tool found (tried {}); install one or drop --copy",
+        names.join(", ")
+    )
+    .into())
+}
+
+/// Put `text` on the system clipboard.
+pub fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn Error>> {
+    copy_with(text, &clipboard_tools())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_goes_to_the_first_installed_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("clipboard.txt");
+        let script = format!("cat > '{}'", file.display());
+        let tools: [(&str, &[&str]); 2] = [
+            ("azsub-no-such-clipboard-tool", &[]),
+            ("sh", &["-c", &script]),
+        ];
+        copy_with("10.0.4.0/26\n", &tools).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "10.0.4.0/26\n");
+
+        let err = copy_with("x", &tools[..1]).unwrap_err().to_string();
+        assert!(err.contains("azsub-no-such-clipboard-tool"), "{err}");
+        let err = copy_with("x", &[("sh", &["-c", "exit 3"])]).unwrap_err();
+        assert!(err.to_string().contains("'sh' could not copy"), "{err}");
+    }
+}
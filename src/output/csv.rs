@@ -1,19 +1,172 @@
 //! CSV output formatting for subnet data.
+//!
+//! The default layout quotes every field and pads it into columns, which
+//! reads well in a terminal and in Excel with English regional settings.
+//! [`CsvDialect`] changes the delimiter (`;` for Excel in most European
+//! locales), drops the padding, and adds a UTF-8 BOM so Excel detects the
+//! encoding of non-ASCII names.
 
 use crate::azure::{Data, VWanRow};
+use crate::clock::Clock;
+use crate::i18n::Lang;
 use crate::models::{num_az_hosts, Subnet};
-use crate::processing::gap_finder::{extract_nsg_name, format_dns_servers};
-use crate::processing::{gaps, ExcludedSubnet, GapKind, SubnetPrintRow, VnetCidr};
-use chrono::Local;
+use crate::processing::gap_finder::{
+    extract_resource_name, format_dns_servers, format_load_balancers,
+};
+use crate::processing::role::role_label;
+use crate::processing::{gaps, group_vnet_cidrs, ExcludedSubnet, GapKind, SubnetPrintRow};
 use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::net::Ipv4Addr;
 use std::path::Path;
 
+use super::stream::RowStream;
 use super::terminal::format_field;
 
+/// Optional columns appended to the subnet CSV after `generated_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CsvColumn {
+    /// Resource group of the VNet.
+    ResourceGroup,
+    /// Well-known subnet role (gateway, firewall, bastion, ...).
+    Role,
+    /// NAT gateway attached to the subnet.
+    NatGateway,
+    /// Load balancers with a frontend IP in the subnet.
+    LoadBalancers,
+    /// Flow log coverage of the subnet's NSG / VNet (loads flow logs).
+    FlowLogs,
+    /// Where the record comes from (`azure`, `reserved`, `on-prem`, `planned`).
+    Source,
+    /// When the record was fetched from Azure.
+    FetchedAt,
+    /// Resource Graph result page the record came from.
+    QueryBlock,
+    /// Start of the subnet's current run of snapshots (reads the snapshot
+    /// history).
+    FirstSeen,
+    /// Date Azure last returned the subnet (reads the snapshot history).
+    LastSeen,
+}
+
+impl CsvColumn {
+    pub(super) fn header(self) -> &'static str {
+        match self {
+            CsvColumn::ResourceGroup => "resource_group",
+            CsvColumn::Role => "role",
+            CsvColumn::NatGateway => "nat_gateway",
+            CsvColumn::LoadBalancers => "load_balancers",
+            CsvColumn::FlowLogs => "flow_logs",
+            CsvColumn::Source => "source",
+            CsvColumn::FetchedAt => "fetched_at",
+            CsvColumn::QueryBlock => "query_block",
+            CsvColumn::FirstSeen => "first_seen",
+            CsvColumn::LastSeen => "last_seen",
+        }
+    }
+
+    fn width(self) -> usize {
+        match self {
+            CsvColumn::Role => 15,
+            CsvColumn::FirstSeen | CsvColumn::LastSeen => 12,
+            _ => 24,
+        }
+    }
+
+    pub(super) fn text(self, row: &SubnetPrintRow) -> &str {
+        match self {
+            CsvColumn::ResourceGroup => &row.resource_group,
+            CsvColumn::Role => &row.role,
+            CsvColumn::NatGateway => &row.nat_gateway,
+            CsvColumn::LoadBalancers => &row.load_balancers,
+            CsvColumn::FlowLogs => &row.flow_logs,
+            CsvColumn::Source => &row.source,
+            CsvColumn::FetchedAt => &row.fetched_at,
+            CsvColumn::QueryBlock => &row.query_block,
+            CsvColumn::FirstSeen => &row.first_seen,
+            CsvColumn::LastSeen => &row.last_seen,
+        }
+    }
+}
+
+/// How fields are quoted in the subnet CSV.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum CsvQuoting {
+    /// Every field quoted and right-aligned into columns.
+    #[default]
+    Aligned,
+    /// Every field quoted, without padding.
+    All,
+    /// Only fields holding the delimiter, a quote or a line break are quoted
+    /// (RFC 4180).
+    Minimal,
+}
+
+/// Header language, delimiter, quoting and BOM of the subnet CSV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    /// Language of the header row.
+    pub lang: Lang,
+    pub delimiter: char,
+    pub quoting: CsvQuoting,
+    /// Start the output with a UTF-8 byte order mark.
+    pub bom: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            lang: Lang::En,
+            delimiter: ',',
+            quoting: CsvQuoting::Aligned,
+            bom: false,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Parse a `--csv-delimiter` value: one character, or `tab`.
+    pub fn parse_delimiter(value: &str) -> Result<char, String> {
+        if value.eq_ignore_ascii_case("tab") {
+            return Ok('\t');
+        }
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if !matches!(c, '"' | '\r' | '\n') => Ok(c),
+            _ => Err(format!(
+                "'{value}' is not a CSV delimiter; use one character other than a quote, or 'tab'"
+            )),
+        }
+    }
+
+    /// `value` quoted for this dialect; `width` pads [`CsvQuoting::Aligned`].
+    fn field(&self, value: &str, width: usize) -> String {
+        match self.quoting {
+            // Kept byte for byte as before dialects existed; quotes in
+            // values are doubled like in the other styles.
+            CsvQuoting::Aligned => format_field(value, width),
+            CsvQuoting::All => format!("\"{}\"", value.replace('"', "\"\"")),
+            CsvQuoting::Minimal => {
+                if value.contains([self.delimiter, '"', '\r', '\n']) {
+                    format!("\"{}\"", value.replace('"', "\"\""))
+                } else {
+                    value.to_string()
+                }
+            }
+        }
+    }
+
+    fn write_bom<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if self.bom {
+            writer.write_all("\u{feff}".as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
 /// Build the ordered flat list of [`SubnetPrintRow`]s for all subnets.
 ///
 /// Produces gap rows, inserts excluded-subnet (`DUP_EXCL_VNET`) rows after
@@ -26,23 +179,7 @@ pub fn build_rows(
     vwan: &[VWanRow],
 ) -> Vec<SubnetPrintRow> {
     // ── 1. Group subnets into VnetCidr objects ──────────────────────────────
-    let mut vnet_cidrs: Vec<VnetCidr> = Vec::new();
-    for subnet in subnets {
-        if let Some(last) = vnet_cidrs.last_mut() {
-            if last.cidr == subnet.vnet_cidr {
-                last.subnets.push(subnet.clone());
-                continue;
-            }
-        }
-        vnet_cidrs.push(VnetCidr {
-            cidr: subnet.vnet_cidr,
-            vnet_name: subnet.vnet_name.clone(),
-            subscription_id: subnet.subscription_id.clone(),
-            subscription_name: subnet.subscription_name.clone(),
-            location: subnet.location.clone(),
-            subnets: vec![subnet.clone()],
-        });
-    }
+    let vnet_cidrs = group_vnet_cidrs(subnets);
 
     // ── 2. Map GapEvent → SubnetPrintRow ────────────────────────────────────
     let gap_events = gaps(&vnet_cidrs, gap_cidr_mask);
@@ -66,10 +203,24 @@ pub fn build_rows(
                     vnet_cidr: subnet.vnet_cidr.to_string(),
                     vnet_name: subnet.vnet_name.clone(),
                     location: subnet.location.clone(),
-                    nsg: extract_nsg_name(subnet.nsg.as_deref()),
+                    nsg: extract_resource_name(subnet.nsg.as_deref()),
                     dns: format_dns_servers(subnet.dns_servers.as_deref()),
                     subscription_id: subnet.subscription_id.clone(),
+                    resource_group: subnet.resource_group.clone(),
+                    role: role_label(subnet),
+                    nat_gateway: extract_resource_name(subnet.nat_gateway.as_deref()),
+                    load_balancers: format_load_balancers(&subnet.load_balancers),
+                    flow_logs: String::new(),
+                    source: subnet.source.to_string(),
+                    fetched_at: subnet.fetched_at.clone().unwrap_or_default(),
+                    query_block: subnet
+                        .query_block
+                        .map(|b| b.to_string())
+                        .unwrap_or_default(),
                     ip_configurations_count: subnet.ip_configurations_count.unwrap_or(0),
+                    first_seen: String::new(),
+                    last_seen: String::new(),
+                    extra: BTreeMap::new(),
                 }
             }
             GapKind::Vnet(vc) => SubnetPrintRow {
@@ -86,7 +237,18 @@ pub fn build_rows(
                 nsg: "Unused_nsg".to_string(),
                 dns: "Unused_dns".to_string(),
                 subscription_id: vc.subscription_id.clone(),
+                resource_group: vc.resource_group.clone(),
+                role: String::new(),
+                nat_gateway: String::new(),
+                load_balancers: String::new(),
+                flow_logs: String::new(),
+                source: String::new(),
+                fetched_at: String::new(),
+                query_block: String::new(),
                 ip_configurations_count: 0,
+                first_seen: String::new(),
+                last_seen: String::new(),
+                extra: BTreeMap::new(),
             },
             GapKind::Gap => SubnetPrintRow {
                 j: 0,
@@ -102,7 +264,18 @@ pub fn build_rows(
                 nsg: "Unused_nsg".to_string(),
                 dns: "Unused_dns".to_string(),
                 subscription_id: "None".to_string(),
+                resource_group: "None".to_string(),
+                role: String::new(),
+                nat_gateway: String::new(),
+                load_balancers: String::new(),
+                flow_logs: String::new(),
+                source: String::new(),
+                fetched_at: String::new(),
+                query_block: String::new(),
                 ip_configurations_count: 0,
+                first_seen: String::new(),
+                last_seen: String::new(),
+                extra: BTreeMap::new(),
             },
         };
         output_rows.push(row);
@@ -139,10 +312,21 @@ pub fn build_rows(
             vnet_cidr: s.vnet_cidr.to_string(),
             vnet_name: s.vnet_name.clone(),
             location: s.location.clone(),
-            nsg: extract_nsg_name(s.nsg.as_deref()),
+            nsg: extract_resource_name(s.nsg.as_deref()),
             dns: format_dns_servers(s.dns_servers.as_deref()),
             subscription_id: s.subscription_id.clone(),
+            resource_group: s.resource_group.clone(),
+            role: role_label(s),
+            nat_gateway: extract_resource_name(s.nat_gateway.as_deref()),
+            load_balancers: format_load_balancers(&s.load_balancers),
+            flow_logs: String::new(),
+            source: s.source.to_string(),
+            fetched_at: s.fetched_at.clone().unwrap_or_default(),
+            query_block: s.query_block.map(|b| b.to_string()).unwrap_or_default(),
             ip_configurations_count: s.ip_configurations_count.unwrap_or(0),
+            first_seen: String::new(),
+            last_seen: String::new(),
+            extra: BTreeMap::new(),
         };
         if !dup_groups.contains_key(&winner) {
             winner_order.push(winner.clone());
@@ -205,7 +389,18 @@ pub fn build_rows(
             nsg: "None".to_string(),
             dns: "None".to_string(),
             subscription_id: hub.subscription_id.clone(),
+            resource_group: "None".to_string(),
+            role: String::new(),
+            nat_gateway: String::new(),
+            load_balancers: String::new(),
+            flow_logs: String::new(),
+            source: String::new(),
+            fetched_at: String::new(),
+            query_block: String::new(),
             ip_configurations_count: 0,
+            first_seen: String::new(),
+            last_seen: String::new(),
+            extra: BTreeMap::new(),
         };
         hub_rows.push((start_u32, row));
     }
@@ -228,30 +423,33 @@ pub fn build_rows(
 /// # Arguments
 /// * `data`          - The active (winner) subnet data
 /// * `excluded`      - Subnets excluded due to VNet overlap conflicts
-/// * `gap_cidr_mask` - The default CIDR mask for gap subnets
-/// * `vwan`          - vWAN hub rows; their address prefixes are injected as
-///   `VWAN_HUB` rows so reserved hub IP space is visible
+/// * `rows`          - Rows to write, from [`build_rows`] (optionally filtered
+///   with a [`RowFilter`](crate::processing::RowFilter))
+/// * `columns`       - Optional columns appended to every row
 /// * `output_dir`    - Directory in which to write `net_<date>_subnets.csv`
 ///   and `net_<date>_duplicates.md`. Pass `Path::new(".")` for the current
 ///   directory.
+/// * `clock`         - Dates the file names; its timestamp fills the
+///   `generated_at` column
+/// * `dialect`       - Header language, delimiter, quoting and BOM
 ///
 /// # Returns
 /// The path to the generated CSV file
 pub fn subnet_print(
     data: &Data,
     excluded: &[ExcludedSubnet],
-    gap_cidr_mask: u8,
-    vwan: &[VWanRow],
+    rows: &[SubnetPrintRow],
+    columns: &[CsvColumn],
     output_dir: &Path,
+    clock: &Clock,
+    dialect: CsvDialect,
 ) -> Result<String, Box<dyn Error>> {
-    log::info!(
-        "#Start subnet_print() add gap subnets with mask /{}",
-        gap_cidr_mask
-    );
+    log::info!("#Start subnet_print()");
     log::info!("# Got subnet count = {} == {}", data.count, data.data.len());
 
     // Generate filename with current date
-    let date_str = Local::now().format("%Y-%m-%d").to_string();
+    let date_str = clock.date_str();
+    let generated_at = clock.timestamp();
     let filename = output_dir
         .join(format!("net_{date_str}_subnets.csv"))
         .to_string_lossy()
@@ -261,22 +459,23 @@ pub fn subnet_print(
     let file = File::create(&filename)?;
     let mut writer = BufWriter::new(file);
 
-    // Write CSV header
-    writeln!(
-        writer,
-        r#" "cnt", "gap"  , "subnet_cidr"    ,"vms"        ,  "broadcast"      , "subnet_name"          ,  "subscription_name",     "vnet_cidr"        ,      "vnet_name","location","nsg","dns","subscription_id""#
-    )?;
+    // Columns added by row processors follow the `--columns` ones
+    let extra: BTreeSet<&str> = rows
+        .iter()
+        .flat_map(|r| r.extra.keys().map(String::as_str))
+        .collect();
 
-    let output_rows = build_rows(&data.data, excluded, gap_cidr_mask, vwan);
+    dialect.write_bom(&mut writer)?;
+    write_csv_header(&mut writer, columns, &extra, &dialect)?;
 
     // Write the subnets as CSV
-    for row in &output_rows {
-        write_csv_row(&mut writer, row)?;
+    for row in rows {
+        write_csv_row(&mut writer, row, columns, &extra, &generated_at, &dialect)?;
     }
 
     writer.flush()?;
 
-    log::info!("Wrote {} rows to '{}'", output_rows.len(), filename);
+    log::info!("Wrote {} rows to '{}'", rows.len(), filename);
 
     // Also write the duplicates markdown report alongside the CSV
     let md_filename = output_dir
@@ -288,31 +487,168 @@ pub fn subnet_print(
     Ok(filename)
 }
 
-fn write_csv_row<W: Write>(writer: &mut W, row: &SubnetPrintRow) -> Result<(), Box<dyn Error>> {
-    writeln!(
-        writer,
-        r#"{j},{gap},{subnet_cidr},{host_cnt},{broadcast},{subnet_name},{subscription_name},{vnet_cidr},{vnet_name},{location},{nsg},{dns},{subscription_id}"#,
-        j = format_field(row.j, 6),
-        gap = format_field(&row.gap, 8),
-        subnet_cidr = format_field(&row.subnet_cidr, 18),
-        host_cnt = format_field(
+/// Header row of the subnet CSV, padded to line up with [`write_csv_row`].
+const CSV_HEADER: &str = r#" "cnt", "gap"  , "subnet_cidr"    ,"vms"        ,  "broadcast"      , "subnet_name"          ,  "subscription_name",     "vnet_cidr"        ,      "vnet_name","location","nsg","dns","subscription_id","generated_at""#;
+
+/// Translate the quoted column names of `header`, keeping its padding.
+fn translate_header(header: &'static str, lang: Lang) -> String {
+    header
+        .split('"')
+        .enumerate()
+        .map(|(i, part)| if i % 2 == 1 { lang.tr(part) } else { part })
+        .collect::<Vec<_>>()
+        .join("\"")
+}
+
+fn write_csv_header<W: Write>(
+    writer: &mut W,
+    columns: &[CsvColumn],
+    extra: &BTreeSet<&str>,
+    dialect: &CsvDialect,
+) -> Result<(), Box<dyn Error>> {
+    let (lang, delimiter) = (dialect.lang, dialect.delimiter);
+    let mut header = match dialect.quoting {
+        // The column names hold no commas, only the separators do
+        CsvQuoting::Aligned => {
+            translate_header(CSV_HEADER, lang).replace(',', &delimiter.to_string())
+        }
+        _ => CSV_HEADER
+            .split('"')
+            .skip(1)
+            .step_by(2)
+            .map(|name| dialect.field(lang.tr(name), 0))
+            .collect::<Vec<_>>()
+            .join(&delimiter.to_string()),
+    };
+    for column in columns {
+        header.push(delimiter);
+        header.push_str(&dialect.field(lang.tr(column.header()), 0));
+    }
+    for name in extra {
+        header.push(delimiter);
+        header.push_str(&dialect.field(name, 0));
+    }
+    writeln!(writer, "{header}")?;
+    Ok(())
+}
+
+/// CSV rows written as they are produced (`--stream`), one batch (VNet) at a
+/// time and flushed after each, in the layout of [`subnet_print`].
+///
+/// The header is written before the first batch, so the processor columns
+/// ([`SubnetPrintRow::extra`]) are given up front, from all rows.
+pub struct CsvStream<W: Write> {
+    writer: W,
+    columns: Vec<CsvColumn>,
+    /// Processor columns.
+    extra: BTreeSet<String>,
+    header_written: bool,
+    generated_at: String,
+    dialect: CsvDialect,
+    rows: usize,
+}
+
+impl<W: Write> CsvStream<W> {
+    pub fn new(
+        writer: W,
+        columns: &[CsvColumn],
+        extra: BTreeSet<String>,
+        generated_at: String,
+        dialect: CsvDialect,
+    ) -> Self {
+        CsvStream {
+            writer,
+            columns: columns.to_vec(),
+            extra,
+            header_written: false,
+            generated_at,
+            dialect,
+            rows: 0,
+        }
+    }
+}
+
+/// Processor columns ([`SubnetPrintRow::extra`]) of `rows`, by header.
+pub fn extra_columns(rows: &[SubnetPrintRow]) -> BTreeSet<String> {
+    rows.iter().flat_map(|r| r.extra.keys().cloned()).collect()
+}
+
+impl<W: Write> RowStream for CsvStream<W> {
+    /// Write `rows`, preceded by the header on the first call.
+    fn write_rows(&mut self, rows: &[SubnetPrintRow]) -> Result<(), Box<dyn Error>> {
+        let extra: BTreeSet<&str> = self.extra.iter().map(String::as_str).collect();
+        if !self.header_written {
+            self.dialect.write_bom(&mut self.writer)?;
+            write_csv_header(&mut self.writer, &self.columns, &extra, &self.dialect)?;
+            self.header_written = true;
+        }
+        for row in rows {
+            write_csv_row(
+                &mut self.writer,
+                row,
+                &self.columns,
+                &extra,
+                &self.generated_at,
+                &self.dialect,
+            )?;
+        }
+        self.writer.flush()?;
+        self.rows += rows.len();
+        Ok(())
+    }
+
+    /// Write the header if no batch came, and return the number of rows written.
+    fn finish(&mut self) -> Result<usize, Box<dyn Error>> {
+        if !self.header_written {
+            self.write_rows(&[])?;
+        }
+        Ok(self.rows)
+    }
+}
+
+fn write_csv_row<W: Write>(
+    writer: &mut W,
+    row: &SubnetPrintRow,
+    columns: &[CsvColumn],
+    extra: &BTreeSet<&str>,
+    generated_at: &str,
+    dialect: &CsvDialect,
+) -> Result<(), Box<dyn Error>> {
+    let fields: [(String, usize); 14] = [
+        (row.j.to_string(), 6),
+        (row.gap.clone(), 8),
+        (row.subnet_cidr.clone(), 18),
+        (
             format!(
                 "{hosts_used}/{hosts_max}_vms",
                 hosts_used = row.ip_configurations_count,
                 hosts_max = row.az_hosts
             ),
-            13
+            13,
         ),
-        broadcast = format_field(format!("{}_br", row.broadcast), 19),
-        subnet_name = format_field(&row.subnet_name, 24),
-        subscription_name = format_field(&row.subscription_name, 21),
-        vnet_cidr = format_field(format!("{}_vnet", row.vnet_cidr), 24),
-        vnet_name = format_field(&row.vnet_name, 30),
-        location = format_field(&row.location, 16),
-        nsg = format_field(&row.nsg, 13),
-        dns = format_field(&row.dns, 13),
-        subscription_id = format_field(&row.subscription_id, 39),
-    )?;
+        (format!("{}_br", row.broadcast), 19),
+        (row.subnet_name.clone(), 24),
+        (row.subscription_name.clone(), 21),
+        (format!("{}_vnet", row.vnet_cidr), 24),
+        (row.vnet_name.clone(), 30),
+        (row.location.clone(), 16),
+        (row.nsg.clone(), 13),
+        (row.dns.clone(), 13),
+        (row.subscription_id.clone(), 39),
+        (generated_at.to_string(), 25),
+    ];
+    let mut line: Vec<String> = fields
+        .iter()
+        .map(|(value, width)| dialect.field(value, *width))
+        .collect();
+    for column in columns {
+        line.push(dialect.field(column.text(row), column.width()));
+    }
+    for &name in extra {
+        let value = row.extra.get(name).map(String::as_str).unwrap_or("");
+        line.push(dialect.field(value, 24));
+    }
+    writeln!(writer, "{}", line.join(&dialect.delimiter.to_string()))?;
     Ok(())
 }
 
@@ -360,6 +696,17 @@ mod tests {
     // Serialize tests that write to the date-based CSV filename to avoid race conditions.
     static CSV_FILE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
+    #[test]
+    fn header_translation_keeps_padding() {
+        assert_eq!(translate_header(CSV_HEADER, Lang::En), CSV_HEADER);
+        let de = translate_header(CSV_HEADER, Lang::De);
+        assert!(
+            de.starts_with(r#" "anz", "luecke"  , "subnetz_cidr"    ,"vms""#),
+            "{de}"
+        );
+        assert!(de.ends_with(r#""abonnement_id","erstellt_am""#), "{de}");
+    }
+
     #[test]
     fn test_subnet_print_04() {
         let cache_file = Some("src/tests/test_data/subnet_test_cache_04.json");
@@ -371,10 +718,10 @@ mod tests {
 
         let filter = vec![
             "default",
-            "pkrsn1ooslfxj77",
-            "pkrsnsnajtq3h3i",
-            "pkrsnxocivqofa6",
-            "orggmcmg",
+            "tmpsn1ooslfxj77",
+            "tmpsnsnajtq3h3i",
+            "tmpsnxocivqofa6",
+            "labspace",
         ];
 
         let result_unsorted = de_duplicate_subnets(data_unsorted, Some(&filter))
@@ -391,7 +738,7 @@ mod tests {
         let mut gf = GapFinder::new(28);
         let rows = gf.push(&result.data[0], 1);
 
-        assert_eq!(result.data[0].subnet_name, "jenkinsarm-snet");
+        assert_eq!(result.data[0].subnet_name, "buildagent-snet");
         assert_eq!(rows.len(), 1);
         assert_eq!(rows[0].subnet_cidr, "10.0.0.0/24");
     }
@@ -409,14 +756,12 @@ mod tests {
             vnet_cidr: &str,
             subnet_cidr: &str,
         ) -> Subnet {
-            let mut s: Subnet = Default::default();
-            s.vnet_name = vnet_name.to_string();
-            s.subscription_name = sub_name.to_string();
-            s.subscription_id = "sub-id".to_string();
-            s.vnet_cidr = Ipv4::new(vnet_cidr).unwrap();
-            s.subnet_cidr = Some(Ipv4::new(subnet_cidr).unwrap());
-            s.subnet_name = "my-subnet".to_string();
-            s
+            Subnet {
+                subscription_name: sub_name.to_string(),
+                subscription_id: "sub-id".to_string(),
+                vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+                ..Subnet::test(vnet_name, "my-subnet", subnet_cidr)
+            }
         }
 
         // active: only the winner subnet; excluded: loser passed separately
@@ -436,8 +781,16 @@ mod tests {
             winner_vnet_name: "winner-vnet".to_string(),
         }];
 
-        let path = subnet_print(&data, &excluded, 28, &[], std::path::Path::new("."))
-            .expect("subnet_print must not panic");
+        let path = subnet_print(
+            &data,
+            &excluded,
+            &build_rows(&data.data, &excluded, 28, &[]),
+            &[],
+            std::path::Path::new("."),
+            &Clock::default(),
+            CsvDialect::default(),
+        )
+        .expect("subnet_print must not panic");
         let contents = std::fs::read_to_string(&path).expect("can read CSV");
         let _ = std::fs::remove_file(&path);
 
@@ -464,14 +817,12 @@ mod tests {
             vnet_cidr: &str,
             subnet_cidr: &str,
         ) -> Subnet {
-            let mut s: Subnet = Default::default();
-            s.vnet_name = vnet_name.to_string();
-            s.subscription_name = sub_name.to_string();
-            s.subscription_id = "sub-id".to_string();
-            s.vnet_cidr = Ipv4::new(vnet_cidr).unwrap();
-            s.subnet_cidr = Some(Ipv4::new(subnet_cidr).unwrap());
-            s.subnet_name = "snet".to_string();
-            s
+            Subnet {
+                subscription_name: sub_name.to_string(),
+                subscription_id: "sub-id".to_string(),
+                vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+                ..Subnet::test(vnet_name, "snet", subnet_cidr)
+            }
         }
 
         // active: winner-vnet and later-vnet; excluded: loser-vnet
@@ -489,8 +840,16 @@ mod tests {
             winner_vnet_name: "winner-vnet".to_string(),
         }];
 
-        let path = subnet_print(&data, &excluded, 28, &[], std::path::Path::new("."))
-            .expect("must not panic");
+        let path = subnet_print(
+            &data,
+            &excluded,
+            &build_rows(&data.data, &excluded, 28, &[]),
+            &[],
+            std::path::Path::new("."),
+            &Clock::default(),
+            CsvDialect::default(),
+        )
+        .expect("must not panic");
         let contents = std::fs::read_to_string(&path).expect("can read");
         let _ = std::fs::remove_file(&path);
 
@@ -511,13 +870,12 @@ mod tests {
         use crate::azure::Data;
         use crate::models::{Ipv4, Subnet};
 
-        let mut s: Subnet = Default::default();
-        s.vnet_name = "my-vnet".to_string();
-        s.subscription_name = "my-sub".to_string();
-        s.subscription_id = "sub-id".to_string();
-        s.vnet_cidr = Ipv4::new("10.0.0.0/16").unwrap();
-        s.subnet_cidr = Some(Ipv4::new("10.0.0.0/24").unwrap());
-        s.subnet_name = "only-subnet".to_string();
+        let s = Subnet {
+            subscription_name: "my-sub".to_string(),
+            subscription_id: "sub-id".to_string(),
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            ..Subnet::test("my-vnet", "only-subnet", "10.0.0.0/24")
+        };
 
         let data = Data {
             count: 1,
@@ -526,8 +884,16 @@ mod tests {
             data: vec![s],
         };
 
-        let path =
-            subnet_print(&data, &[], 28, &[], std::path::Path::new(".")).expect("must not panic");
+        let path = subnet_print(
+            &data,
+            &[],
+            &build_rows(&data.data, &[], 28, &[]),
+            &[],
+            std::path::Path::new("."),
+            &Clock::default(),
+            CsvDialect::default(),
+        )
+        .expect("must not panic");
         let contents = std::fs::read_to_string(&path).expect("can read CSV");
         let _ = std::fs::remove_file(&path);
 
@@ -549,14 +915,12 @@ mod tests {
         use crate::processing::ExcludedSubnet;
 
         fn make_subnet(vnet_name: &str, vnet_cidr: &str, subnet_cidr: &str) -> Subnet {
-            let mut s: Subnet = Default::default();
-            s.vnet_name = vnet_name.to_string();
-            s.subscription_name = "Sub".to_string();
-            s.subscription_id = "sub-id".to_string();
-            s.vnet_cidr = Ipv4::new(vnet_cidr).unwrap();
-            s.subnet_cidr = Some(Ipv4::new(subnet_cidr).unwrap());
-            s.subnet_name = "snet".to_string();
-            s
+            Subnet {
+                subscription_name: "Sub".to_string(),
+                subscription_id: "sub-id".to_string(),
+                vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+                ..Subnet::test(vnet_name, "snet", subnet_cidr)
+            }
         }
 
         let data = Data {
@@ -570,8 +934,16 @@ mod tests {
             winner_vnet_name: "winner-vnet".to_string(),
         }];
 
-        let csv_path = subnet_print(&data, &excluded, 28, &[], std::path::Path::new("."))
-            .expect("must not panic");
+        let csv_path = subnet_print(
+            &data,
+            &excluded,
+            &build_rows(&data.data, &excluded, 28, &[]),
+            &[],
+            std::path::Path::new("."),
+            &Clock::default(),
+            CsvDialect::default(),
+        )
+        .expect("must not panic");
         let md_path = csv_path.replace("_subnets.csv", "_duplicates.md");
         let _ = std::fs::remove_file(&csv_path);
         assert!(
@@ -590,14 +962,225 @@ mod tests {
         subnet_cidr: &str,
         subnet_name: &str,
     ) -> Subnet {
-        let mut s: Subnet = Default::default();
-        s.vnet_name = vnet_name.to_string();
-        s.subscription_name = subscription_name.to_string();
-        s.subscription_id = "sub-id".to_string();
-        s.vnet_cidr = crate::models::Ipv4::new(vnet_cidr).unwrap();
-        s.subnet_cidr = Some(crate::models::Ipv4::new(subnet_cidr).unwrap());
-        s.subnet_name = subnet_name.to_string();
-        s
+        Subnet {
+            subscription_name: subscription_name.to_string(),
+            subscription_id: "sub-id".to_string(),
+            vnet_cidr: crate::models::Ipv4::new(vnet_cidr).unwrap(),
+            ..Subnet::test(vnet_name, subnet_name, subnet_cidr)
+        }
+    }
+
+    #[test]
+    fn resource_group_column_is_appended_only_when_requested() {
+        let mut subnet =
+            make_subnet_for_build("my-vnet", "Prod", "10.0.0.0/24", "10.0.0.0/24", "snet");
+        subnet.resource_group = "rg-network".to_string();
+        let rows = build_rows(&[subnet], &[], 28, &[]);
+
+        let mut plain = Vec::new();
+        write_csv_row(
+            &mut plain,
+            &rows[0],
+            &[],
+            &BTreeSet::new(),
+            "",
+            &CsvDialect::default(),
+        )
+        .unwrap();
+        let mut extra = Vec::new();
+        write_csv_row(
+            &mut extra,
+            &rows[0],
+            &[CsvColumn::ResourceGroup],
+            &BTreeSet::new(),
+            "",
+            &CsvDialect::default(),
+        )
+        .unwrap();
+
+        let plain = String::from_utf8(plain).unwrap();
+        let extra = String::from_utf8(extra).unwrap();
+        assert!(!plain.contains("rg-network"));
+        assert!(extra.starts_with(plain.trim_end()));
+        assert!(extra.trim_end().ends_with("rg-network\""), "{extra}");
+    }
+
+    #[test]
+    fn role_column_labels_well_known_subnets() {
+        let gateway =
+            make_subnet_for_build("hub", "Prod", "10.0.0.0/24", "10.0.0.0/27", "GatewaySubnet");
+        let rows = build_rows(&[gateway], &[], 28, &[]);
+        assert_eq!(rows[0].role, "gateway");
+        assert!(
+            rows[1..].iter().all(|r| r.role.is_empty()),
+            "gap rows have no role"
+        );
+
+        let mut out = Vec::new();
+        write_csv_row(
+            &mut out,
+            &rows[0],
+            &[CsvColumn::Role],
+            &BTreeSet::new(),
+            "",
+            &CsvDialect::default(),
+        )
+        .unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .trim_end()
+            .ends_with("\"gateway\""));
+    }
+
+    #[test]
+    fn egress_columns_show_nat_gateway_and_load_balancers() {
+        let mut subnet =
+            make_subnet_for_build("spoke", "Prod", "10.0.0.0/24", "10.0.0.0/24", "app");
+        subnet.nat_gateway = Some("/subscriptions/s/natGateways/ngw-egress".to_string());
+        subnet.load_balancers = vec!["ilb-app".to_string(), "ilb-api".to_string()];
+        let rows = build_rows(&[subnet], &[], 28, &[]);
+
+        let mut out = Vec::new();
+        let columns = [CsvColumn::NatGateway, CsvColumn::LoadBalancers];
+        write_csv_row(
+            &mut out,
+            &rows[0],
+            &columns,
+            &BTreeSet::new(),
+            "",
+            &CsvDialect::default(),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\"ngw-egress\""), "{out}");
+        assert!(out.trim_end().ends_with("\"ilb-app,ilb-api\""), "{out}");
+    }
+
+    #[test]
+    fn dialects_change_delimiter_quoting_and_bom() {
+        let mut subnet =
+            make_subnet_for_build("spoke", "Zürich; Prod", "10.0.0.0/24", "10.0.0.0/24", "app");
+        subnet.load_balancers = vec!["ilb-app".to_string(), "ilb-api".to_string()];
+        let rows = build_rows(&[subnet], &[], 28, &[]);
+        let columns = [CsvColumn::LoadBalancers];
+        let write = |dialect: CsvDialect| {
+            let mut out = Vec::new();
+            let mut stream = CsvStream::new(
+                &mut out,
+                &columns,
+                BTreeSet::new(),
+                "t".to_string(),
+                dialect,
+            );
+            stream.write_rows(&rows).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let aligned = write(CsvDialect {
+            delimiter: ';',
+            ..CsvDialect::default()
+        });
+        let default = write(CsvDialect::default());
+        // Same padding, only the separators change
+        for (a, d) in aligned.lines().zip(default.lines()) {
+            assert_eq!(a.len(), d.len());
+        }
+        assert!(
+            aligned.starts_with(r#" "cnt"; "gap"  ; "subnet_cidr"    ;"vms""#),
+            "{aligned}"
+        );
+        assert!(aligned.contains(r#""Zürich; Prod";"#), "{aligned}");
+        let quoted = CsvDialect::default();
+        assert_eq!(quoted.field(r#"app "blue""#, 0), r#""app ""blue""""#);
+
+        let minimal = write(CsvDialect {
+            lang: Lang::De,
+            delimiter: ';',
+            quoting: CsvQuoting::Minimal,
+            bom: true,
+        });
+        let lines: Vec<&str> = minimal.lines().collect();
+        assert!(
+            lines[0].starts_with("\u{feff}anz;luecke;subnetz_cidr;vms;"),
+            "{}",
+            lines[0]
+        );
+        assert!(
+            lines[0].ends_with(";erstellt_am;load_balancers"),
+            "{}",
+            lines[0]
+        );
+        assert!(
+            lines[1].starts_with("1;;10.0.0.0/24;0/251_vms;"),
+            "{}",
+            lines[1]
+        );
+        assert!(lines[1].contains(";\"Zürich; Prod\";"), "{}", lines[1]);
+        assert!(lines[1].ends_with(";t;ilb-app,ilb-api"), "{}", lines[1]);
+
+        let all = write(CsvDialect {
+            quoting: CsvQuoting::All,
+            ..CsvDialect::default()
+        });
+        assert!(all.starts_with("\"cnt\",\"gap\",\"subnet_cidr\""), "{all}");
+        assert!(all.contains("\n\"1\",\"\",\"10.0.0.0/24\""), "{all}");
+
+        assert_eq!(CsvDialect::parse_delimiter("tab"), Ok('\t'));
+        assert_eq!(CsvDialect::parse_delimiter(";"), Ok(';'));
+        assert!(CsvDialect::parse_delimiter("\"").is_err());
+        assert!(CsvDialect::parse_delimiter(";;").is_err());
+    }
+
+    #[test]
+    fn stream_writes_the_header_once_with_every_processor_column() {
+        let rows = build_rows(
+            &[
+                make_subnet_for_build("a", "Prod", "10.0.0.0/24", "10.0.0.0/24", "app"),
+                make_subnet_for_build("b", "Prod", "10.0.1.0/24", "10.0.1.0/24", "db"),
+            ],
+            &[],
+            28,
+            &[],
+        );
+        let (mut first, mut second) = (rows[0].clone(), rows[1].clone());
+        first
+            .extra
+            .insert("cost_centre".to_string(), "CC-1".to_string());
+        second
+            .extra
+            .insert("owner".to_string(), "team-db".to_string());
+
+        let extra = extra_columns(&[first.clone(), second.clone()]);
+        let mut out = Vec::new();
+        let mut stream = CsvStream::new(&mut out, &[], extra, String::new(), CsvDialect::default());
+        stream.write_rows(&[first]).unwrap();
+        stream.write_rows(&[second]).unwrap();
+        assert_eq!(stream.finish().unwrap(), 2);
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(
+            lines[0].ends_with(r#","cost_centre","owner""#),
+            "{}",
+            lines[0]
+        );
+        assert!(
+            lines[2].trim_end().ends_with(r#""team-db""#),
+            "{}",
+            lines[2]
+        );
+
+        let mut empty = Vec::new();
+        CsvStream::new(
+            &mut empty,
+            &[],
+            BTreeSet::new(),
+            String::new(),
+            CsvDialect::default(),
+        )
+        .finish()
+        .unwrap();
+        assert_eq!(String::from_utf8(empty).unwrap().lines().count(), 1);
     }
 
     #[test]
@@ -1,7 +1,8 @@
 //! Markdown report for duplicate (excluded) VNets.
 
+use super::format::size_description;
 use crate::azure::Data;
-use crate::processing::ExcludedSubnet;
+use crate::processing::{ExcludedSubnet, MetadataIndex};
 use std::collections::HashMap;
 use std::error::Error;
 use std::fs::File;
@@ -32,13 +33,12 @@ pub fn write_duplicates_md(
             .push(&e.subnet);
     }
 
-    // Find the first active subnet for a vnet to get its CIDR + subscription
+    // The first active VNet with the winner's name gives its CIDR + subscription
+    let index = MetadataIndex::new(active);
     let winner_info = |vnet_name: &str| -> (String, String) {
-        active
-            .data
-            .iter()
-            .find(|s| s.vnet_name == vnet_name)
-            .map(|s| (s.vnet_cidr.to_string(), s.subscription_name.clone()))
+        index
+            .vnet_named(vnet_name)
+            .map(|v| (v.vnet_cidr.to_string(), v.subscription_name.to_string()))
             .unwrap_or_default()
     };
 
@@ -82,11 +82,15 @@ pub fn write_duplicates_md(
                 w,
                 "\n### Duplicate VNET: `{excl_vnet}` ({excl_cidr}) — {excl_sub}"
             )?;
-            writeln!(w, "| Subnet | CIDR |")?;
-            writeln!(w, "|--------|------|")?;
+            writeln!(w, "| Subnet | CIDR | Size |")?;
+            writeln!(w, "|--------|------|------|")?;
             for s in subnets.iter() {
                 let cidr = s.subnet_cidr.map(|c| c.to_string()).unwrap_or_default();
-                writeln!(w, "| `{}` | {} |", s.subnet_name, cidr)?;
+                let size = s
+                    .subnet_cidr
+                    .map(|c| size_description(c.mask))
+                    .unwrap_or_default();
+                writeln!(w, "| `{}` | {} | {} |", s.subnet_name, cidr, size)?;
             }
         }
     }
@@ -108,14 +112,12 @@ mod tests {
         subnet_cidr: &str,
         subnet_name: &str,
     ) -> Subnet {
-        let mut s: Subnet = Default::default();
-        s.vnet_name = vnet_name.to_string();
-        s.subscription_name = sub_name.to_string();
-        s.subscription_id = "sub-id".to_string();
-        s.vnet_cidr = Ipv4::new(vnet_cidr).unwrap();
-        s.subnet_cidr = Some(Ipv4::new(subnet_cidr).unwrap());
-        s.subnet_name = subnet_name.to_string();
-        s
+        Subnet {
+            subscription_name: sub_name.to_string(),
+            subscription_id: "sub-id".to_string(),
+            vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+            ..Subnet::test(vnet_name, subnet_name, subnet_cidr)
+        }
     }
 
     #[test]
@@ -0,0 +1,90 @@
+//! Human-readable numbers shared by the terminal, table and Markdown output,
+//! and HTML escaping for the HTML and Graphviz output.
+//!
+//! Address counts get thousands separators (`65,531`) or binary units
+//! (`64Ki`), shares are whole percentages rounded down, and subnet sizes are
+//! described by their Azure host count (`/22 = 1019 az hosts`).
+
+use crate::models::num_az_hosts;
+
+/// `n` with `,` between groups of three digits, e.g. `65,531`.
+pub fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// `n` in binary units with at most one decimal, e.g. `64Ki`, `1.5Mi`;
+/// plain below 1024.
+pub fn binary(n: u64) -> String {
+    const UNITS: [&str; 3] = ["Ki", "Mi", "Gi"];
+    let mut value = n as f64;
+    let mut unit = None;
+    for u in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = Some(u);
+    }
+    match unit {
+        None => n.to_string(),
+        Some(u) => {
+            let text = format!("{value:.1}");
+            format!("{}{u}", text.strip_suffix(".0").unwrap_or(&text))
+        }
+    }
+}
+
+/// `part` as a whole percentage of `whole`, rounded down, e.g. `81%`;
+/// `n/a` when `whole` is 0.
+pub fn percent(part: u64, whole: u64) -> String {
+    if whole == 0 {
+        return "n/a".to_string();
+    }
+    format!("{}%", part * 100 / whole)
+}
+
+/// Size of a `/mask` subnet, e.g. `/22 = 1019 az hosts`.
+pub fn size_description(mask: u8) -> String {
+    match num_az_hosts(mask) {
+        Ok(hosts) if hosts > 0 => format!("/{mask} = {hosts} az hosts"),
+        _ => format!("/{mask} = too small for Azure"),
+    }
+}
+
+/// `s` safe inside HTML text and attribute values (and Graphviz HTML
+/// labels).
+pub fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_grouped_scaled_and_described() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(65_531), "65,531");
+        assert_eq!(thousands(16_777_216), "16,777,216");
+        assert_eq!(binary(1000), "1000");
+        assert_eq!(binary(65_536), "64Ki");
+        assert_eq!(binary(1536 * 1024), "1.5Mi");
+        assert_eq!(percent(1664, 2048), "81%");
+        assert_eq!(percent(1, 0), "n/a");
+        assert_eq!(size_description(22), "/22 = 1019 az hosts");
+        assert_eq!(size_description(29), "/29 = 3 az hosts");
+        assert_eq!(size_description(30), "/30 = too small for Azure");
+    }
+}
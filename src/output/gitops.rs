@@ -0,0 +1,329 @@
+//! GitOps export (`--gitops-dir`): one YAML file per VNet in a directory
+//! tree meant to be committed to git.
+//!
+//! Files are laid out like ARM, `<subscription>/<resource group>/<vnet>.yaml`,
+//! so an ordinary `git diff` of two commits shows how the estate changed and
+//! a pull request can gate address changes. Only properties that change when
+//! someone changes the network are written — no IP configuration counts or
+//! fetch times — so an unchanged estate gives an empty diff. Files of VNets
+//! that disappeared are removed; files the export did not write are left
+//! alone.
+
+use crate::azure::Data;
+use crate::models::{Ipv4, Subnet, SubnetSource};
+use crate::processing::gap_finder::extract_resource_name;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// First line of every exported file; marks the files the export owns.
+const GITOPS_HEADER: &str =
+    "# Generated by azure-subnet-summary --gitops-dir; changes are overwritten.";
+
+/// One VNet file.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GitopsVnet {
+    pub name: String,
+    pub id: String,
+    pub subscription: String,
+    pub subscription_id: String,
+    pub resource_group: String,
+    pub location: String,
+    pub address_space: Vec<Ipv4>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_servers: Option<Vec<String>>,
+    pub subnets: Vec<GitopsSubnet>,
+}
+
+/// One subnet of a VNet file.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GitopsSubnet {
+    pub name: String,
+    pub cidr: Option<Ipv4>,
+    /// NSG name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nsg: Option<String>,
+    /// NAT gateway name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nat_gateway: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub delegations: Vec<String>,
+}
+
+/// What [`write_gitops_tree`] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GitopsSummary {
+    /// Files created or changed.
+    pub written: usize,
+    pub unchanged: usize,
+    /// Files of VNets no longer in the data.
+    pub removed: usize,
+}
+
+/// Path segment for a name: lower case, anything but `[a-z0-9._-]` as `-`.
+fn path_segment(name: &str) -> String {
+    let segment: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    match segment.trim_matches('.') {
+        "" => "unknown".to_string(),
+        s => s.to_string(),
+    }
+}
+
+fn gitops_subnet(s: &Subnet) -> GitopsSubnet {
+    GitopsSubnet {
+        name: s.subnet_name.clone(),
+        cidr: s.subnet_cidr,
+        nsg: s.nsg.as_deref().map(|id| extract_resource_name(Some(id))),
+        nat_gateway: s
+            .nat_gateway
+            .as_deref()
+            .map(|id| extract_resource_name(Some(id))),
+        delegations: s.delegations.clone(),
+    }
+}
+
+/// Relative file path of a VNet: `<subscription>/<resource group>/<vnet>.yaml`.
+fn vnet_path(vnet: &GitopsVnet, suffix: &str) -> PathBuf {
+    Path::new(&path_segment(&vnet.subscription))
+        .join(path_segment(&vnet.resource_group))
+        .join(format!("{}{suffix}.yaml", path_segment(&vnet.name)))
+}
+
+/// The Azure VNets of `data` keyed by their relative file path, subnets
+/// sorted by prefix. VNets whose names map to the same path (same names in
+/// two subscriptions, or names differing only in case or punctuation) get
+/// the first 8 characters of their subscription ID, then a counter, added
+/// to the file name.
+pub fn gitops_vnets(data: &Data) -> BTreeMap<PathBuf, GitopsVnet> {
+    // Azure resource IDs are case-insensitive.
+    let mut by_id: BTreeMap<String, GitopsVnet> = BTreeMap::new();
+    for s in data.data.iter().filter(|s| s.source == SubnetSource::Azure) {
+        let vnet = by_id
+            .entry(s.vnet_id.to_lowercase())
+            .or_insert_with(|| GitopsVnet {
+                name: s.vnet_name.clone(),
+                id: s.vnet_id.clone(),
+                subscription: s.subscription_name.clone(),
+                subscription_id: s.subscription_id.clone(),
+                resource_group: s.resource_group.clone(),
+                location: s.location.clone(),
+                address_space: if s.vnet_address_space.is_empty() {
+                    vec![s.vnet_cidr]
+                } else {
+                    s.vnet_address_space.clone()
+                },
+                dns_servers: s.dns_servers.clone(),
+                subnets: Vec::new(),
+            });
+        let subnet = gitops_subnet(s);
+        if !vnet.subnets.contains(&subnet) {
+            vnet.subnets.push(subnet);
+        }
+    }
+
+    let mut per_path: BTreeMap<PathBuf, usize> = BTreeMap::new();
+    for vnet in by_id.values() {
+        *per_path.entry(vnet_path(vnet, "")).or_default() += 1;
+    }
+    let mut vnets: BTreeMap<PathBuf, GitopsVnet> = BTreeMap::new();
+    for mut vnet in by_id.into_values() {
+        vnet.subnets
+            .sort_by(|a, b| (a.cidr, &a.name).cmp(&(b.cidr, &b.name)));
+        let plain = vnet_path(&vnet, "");
+        let path = if per_path[&plain] == 1 {
+            plain
+        } else {
+            let sub: String = vnet.subscription_id.chars().take(8).collect();
+            let suffix = format!("-{}", path_segment(&sub));
+            let mut path = vnet_path(&vnet, &suffix);
+            let mut n = 2;
+            while vnets.contains_key(&path) {
+                path = vnet_path(&vnet, &format!("{suffix}-{n}"));
+                n += 1;
+            }
+            log::warn!(
+                "GitOps: VNet '{}' shares its path with another VNet; writing '{}'",
+                vnet.id,
+                path.display()
+            );
+            path
+        };
+        vnets.insert(path, vnet);
+    }
+    vnets
+}
+
+/// Exported files under `dir`: YAML files starting with [`GITOPS_HEADER`].
+fn exported_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.file_name().is_some_and(|n| n == ".git") {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|e| e == "yaml")
+                && std::fs::read_to_string(&path).is_ok_and(|t| t.starts_with(GITOPS_HEADER))
+            {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Write one YAML file per VNet of `data` under `dir`, and remove exported
+/// files of VNets that are gone. Unchanged files are not rewritten.
+pub fn write_gitops_tree(data: &Data, dir: &Path) -> Result<GitopsSummary, Box<dyn Error>> {
+    let vnets = gitops_vnets(data);
+    let mut summary = GitopsSummary::default();
+    let mut current = BTreeSet::new();
+    for (relative, vnet) in &vnets {
+        let path = dir.join(relative);
+        let yaml = serde_yaml::to_string(vnet)
+            .map_err(|e| format!("Error writing YAML for VNet '{}': {e}", vnet.name))?;
+        let text = format!("{GITOPS_HEADER}\n{yaml}");
+        if std::fs::read_to_string(&path).is_ok_and(|old| old == text) {
+            summary.unchanged += 1;
+        } else {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Error creating {}: {e}", parent.display()))?;
+            }
+            std::fs::write(&path, text)
+                .map_err(|e| format!("Error writing {}: {e}", path.display()))?;
+            summary.written += 1;
+        }
+        current.insert(path);
+    }
+    for stale in exported_files(dir) {
+        if current.contains(&stale) {
+            continue;
+        }
+        std::fs::remove_file(&stale)
+            .map_err(|e| format!("Error removing {}: {e}", stale.display()))?;
+        log::info!("GitOps: removed '{}' (VNet gone)", stale.display());
+        summary.removed += 1;
+        // Drop directories the removal emptied; fails harmlessly otherwise.
+        let mut parent = stale.parent();
+        while let Some(p) = parent.filter(|p| *p != dir) {
+            if std::fs::remove_dir(p).is_err() {
+                break;
+            }
+            parent = p.parent();
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subnet(sub: &str, vnet: &str, name: &str, cidr: &str) -> Subnet {
+        let mut s = Subnet {
+            subscription_id: format!("{sub}-id"),
+            subscription_name: sub.to_string(),
+            resource_group: "RG-Net".to_string(),
+            vnet_id: format!("/subscriptions/{sub}-id/resourceGroups/RG-Net/providers/Microsoft.Network/virtualNetworks/{vnet}"),
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            location: "westeurope".to_string(),
+            ip_configurations_count: Some(3),
+            ..Subnet::test(vnet, name, cidr)
+        };
+        s.vnet_address_space = vec![s.vnet_cidr];
+        s
+    }
+
+    fn data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn one_yaml_file_per_vnet_with_stale_files_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = subnet("Prod Subscription", "hub", "app", "10.0.1.0/24");
+        app.nsg = Some("/subscriptions/x/networkSecurityGroups/nsg-app".to_string());
+        let first = data(vec![
+            subnet("Prod Subscription", "hub", "db", "10.0.2.0/24"),
+            app.clone(),
+            subnet("Dev", "lab", "app", "10.9.0.0/24"),
+        ]);
+        let summary = write_gitops_tree(&first, dir.path()).unwrap();
+        assert_eq!(summary.written, 2);
+
+        let hub = dir.path().join("prod-subscription/rg-net/hub.yaml");
+        let text = std::fs::read_to_string(&hub).unwrap();
+        assert!(text.starts_with(GITOPS_HEADER), "{text}");
+        assert!(text.contains("address_space:\n- 10.0.0.0/16\n"), "{text}");
+        assert!(
+            text.contains(
+                "subnets:\n- name: app\n  cidr: 10.0.1.0/24\n  nsg: nsg-app\n- name: db\n"
+            ),
+            "{text}"
+        );
+        assert!(!text.contains("ip_configurations"), "{text}");
+
+        // Same estate with new usage counts: nothing rewritten.
+        let mut busier = first.clone();
+        busier.data[0].ip_configurations_count = Some(40);
+        let summary = write_gitops_tree(&busier, dir.path()).unwrap();
+        assert_eq!((summary.written, summary.unchanged), (0, 2));
+
+        // The lab VNet is gone; a hand-written file stays.
+        let notes = dir.path().join("dev/notes.yaml");
+        std::fs::write(&notes, "owner: team-dev\n").unwrap();
+        let summary = write_gitops_tree(&data(vec![app]), dir.path()).unwrap();
+        assert_eq!((summary.written, summary.removed), (1, 1));
+        assert!(!dir.path().join("dev/rg-net").exists());
+        assert!(notes.exists());
+        assert_eq!(path_segment("../Sub/One"), "-sub-one");
+    }
+
+    #[test]
+    fn vnets_with_the_same_path_get_separate_files() {
+        // Same subscription name, different subscriptions.
+        let mut other = subnet("Prod", "hub", "db", "10.0.2.0/24");
+        other.subscription_id = "87654321-aaaa".to_string();
+        other.vnet_id = other.vnet_id.replace("Prod-id", "87654321-aaaa");
+        // Resource group names differing only in punctuation.
+        let mut spaced = subnet("Prod", "hub", "web", "10.0.3.0/24");
+        spaced.resource_group = "RG Net".to_string();
+        spaced.vnet_id = spaced.vnet_id.replace("RG-Net", "RG Net");
+        let vnets = gitops_vnets(&data(vec![
+            subnet("Prod", "hub", "app", "10.0.1.0/24"),
+            other,
+            spaced,
+        ]));
+        let paths: Vec<String> = vnets.keys().map(|p| p.display().to_string()).collect();
+        assert_eq!(
+            paths,
+            [
+                "prod/rg-net/hub-87654321.yaml",
+                "prod/rg-net/hub-prod-id-2.yaml",
+                "prod/rg-net/hub-prod-id.yaml",
+            ]
+        );
+        assert!(vnets.values().all(|v| v.subnets.len() == 1));
+    }
+}
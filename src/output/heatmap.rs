@@ -0,0 +1,164 @@
+//! Address space ownership heatmap (`--heatmap`): CSV and HTML renderings
+//! of [`crate::processing::heatmap`].
+//!
+//! The HTML page is self-contained: a grid with one cell per slice, shaded
+//! by the share of the slice held by VNets, with the owners in the cell
+//! tooltip.
+
+use super::format::{html_escape, percent, thousands};
+use crate::models::Ipv4;
+use crate::processing::HeatmapSlice;
+use std::error::Error;
+use std::path::Path;
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// Owners of a slice, `subscription/vnet (addresses)`, most addresses first.
+fn owner_list(slice: &HeatmapSlice, sep: &str) -> String {
+    slice
+        .owners
+        .iter()
+        .map(|o| format!("{}/{} ({})", o.subscription_name, o.vnet_name, o.addresses))
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+/// One CSV row per slice: size, used and free addresses, and the owners.
+pub fn heatmap_csv(slices: &[HeatmapSlice]) -> String {
+    let mut out = String::from("\"slice\",\"size\",\"used\",\"free\",\"used_pct\",\"owners\"\n");
+    for s in slices {
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            quote(&s.cidr.to_string()),
+            s.size(),
+            s.used(),
+            s.free,
+            s.used() * 100 / s.size(),
+            quote(&owner_list(s, "; "))
+        ));
+    }
+    out
+}
+
+/// Cell colour for a slice `used_pct` percent held: white (free) to red.
+fn shade(used_pct: u64) -> String {
+    let level = 255 - (used_pct.min(100) * 200 / 100) as u8;
+    format!("rgb(255,{level},{level})")
+}
+
+/// Self-contained HTML page with a grid of the slices of `supernet`.
+pub fn heatmap_html(slices: &[HeatmapSlice], supernet: Ipv4, timestamp: &str) -> String {
+    // Square-ish grid: 16 columns for 256 slices, 64 for 4096.
+    let bits = slices.len().max(1).ilog2();
+    let columns = 1usize << bits.div_ceil(2);
+    let used: u64 = slices.iter().map(|s| s.used()).sum();
+    let size: u64 = slices.iter().map(|s| s.size()).sum();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>Address space heatmap {supernet}</title>\n"
+    ));
+    html.push_str(
+        "<style>\nbody { font-family: sans-serif; }\n\
+         table { border-collapse: collapse; }\n\
+         td { border: 1px solid #ccc; font-size: 10px; padding: 2px 4px; text-align: center; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!(
+        "<h1>Address space heatmap {supernet}</h1>\n<p>{} of {} addresses held by VNets ({}), \
+         generated {}</p>\n<table>\n",
+        thousands(used),
+        thousands(size),
+        percent(used, size),
+        html_escape(timestamp)
+    ));
+    for row in slices.chunks(columns) {
+        html.push_str("<tr>");
+        for s in row {
+            let used_pct = s.used() * 100 / s.size();
+            let owners = if s.owners.is_empty() {
+                "free".to_string()
+            } else {
+                owner_list(s, "\n")
+            };
+            html.push_str(&format!(
+                "<td style=\"background:{}\" title=\"{} - {}% used\n{}\">{}</td>",
+                shade(used_pct),
+                s.cidr,
+                used_pct,
+                html_escape(&owners),
+                s.cidr.addr
+            ));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// Write `net_<date>_heatmap.csv` and `net_<date>_heatmap.html` into `dir`,
+/// returning the written paths.
+pub fn write_heatmap(
+    slices: &[HeatmapSlice],
+    supernet: Ipv4,
+    dir: &Path,
+    date_str: &str,
+    timestamp: &str,
+) -> Result<(String, String), Box<dyn Error>> {
+    let csv_file = dir
+        .join(format!("net_{date_str}_heatmap.csv"))
+        .to_string_lossy()
+        .into_owned();
+    std::fs::write(&csv_file, heatmap_csv(slices))
+        .map_err(|e| format!("Error writing {csv_file}: {e}"))?;
+    let html_file = dir
+        .join(format!("net_{date_str}_heatmap.html"))
+        .to_string_lossy()
+        .into_owned();
+    std::fs::write(&html_file, heatmap_html(slices, supernet, timestamp))
+        .map_err(|e| format!("Error writing {html_file}: {e}"))?;
+    Ok((csv_file, html_file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::SliceOwner;
+
+    #[test]
+    fn slices_are_written_as_csv_rows_and_html_cells() {
+        let slice = |cidr: &str, owners: Vec<SliceOwner>, free| HeatmapSlice {
+            cidr: Ipv4::new(cidr).unwrap(),
+            owners,
+            free,
+        };
+        let slices = vec![
+            slice(
+                "10.0.0.0/16",
+                vec![SliceOwner {
+                    subscription_name: "Prod".to_string(),
+                    vnet_name: "hub<1>".to_string(),
+                    addresses: 16_384,
+                }],
+                49_152,
+            ),
+            slice("10.1.0.0/16", Vec::new(), 65_536),
+        ];
+        let csv = heatmap_csv(&slices);
+        assert_eq!(
+            csv.lines().collect::<Vec<_>>(),
+            [
+                "\"slice\",\"size\",\"used\",\"free\",\"used_pct\",\"owners\"",
+                "\"10.0.0.0/16\",65536,16384,49152,25,\"Prod/hub<1> (16384)\"",
+                "\"10.1.0.0/16\",65536,0,65536,0,\"\"",
+            ]
+        );
+        let html = heatmap_html(&slices, Ipv4::new("10.0.0.0/15").unwrap(), "2024-05-01");
+        assert!(html.contains("16,384 of 131,072 addresses held by VNets (12%)"));
+        assert!(html.contains("title=\"10.0.0.0/16 - 25% used\nProd/hub&lt;1&gt; (16384)\""));
+        assert!(html.contains("title=\"10.1.0.0/16 - 0% used\nfree\">10.1.0.0</td>"));
+    }
+}
@@ -0,0 +1,177 @@
+//! Run manifest (`--manifest`): `run_manifest.json` in the report directory.
+//!
+//! Records what a run was given (cache file, query scope, filters and a
+//! SHA-256 of the config file) and what each pipeline stage produced, with a
+//! summary of the findings, so a report can be audited and reproduced later.
+
+use crate::report::Report;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+/// The config file a run used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestConfig {
+    pub path: String,
+    /// Hex SHA-256 of the file contents.
+    pub sha256: String,
+}
+
+/// What the run was given.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ManifestInputs {
+    /// Subnet cache the data came from (read or written).
+    pub cache_file: String,
+    /// `true` when an existing cache was read instead of querying Azure.
+    pub from_cache: bool,
+    pub demo: bool,
+    pub tenant: Option<String>,
+    pub management_group: Option<String>,
+    pub only_management_group: Option<String>,
+    pub sample: Option<u32>,
+    pub config: Option<ManifestConfig>,
+    /// Config file profile (`--profile`).
+    pub profile: Option<String>,
+    /// Active ignore rules, in `--ignore` syntax.
+    pub ignore: Vec<String>,
+    /// Merged ranges, as `source cidr name`.
+    pub ranges: Vec<String>,
+    pub terraform_plan: Option<String>,
+    pub buffers: Vec<String>,
+    pub gap_mask: u8,
+    pub min_free: Option<u64>,
+    pub only_gaps_larger_than: Option<u8>,
+}
+
+/// Record counts after each pipeline stage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StageCounts {
+    /// Subnets in the fetched (or cached) data.
+    pub fetched: usize,
+    /// After merging ranges, Terraform plan subnets and P2S pools.
+    pub merged: usize,
+    /// Subnets of VNets excluded by overlap resolution.
+    pub excluded_overlapping: usize,
+    /// Subnets matched by the ignore rules.
+    pub ignored: usize,
+    /// Subnets reported, after ignore rules and de-duplication.
+    pub active: usize,
+    pub vnets: usize,
+    /// Subnet and gap rows before `--min-free` / `--only-gaps-larger-than`.
+    pub rows_built: usize,
+    /// Rows written to the CSV.
+    pub rows_written: usize,
+    /// Gap rows (`-gap-`, `-vgap-`) written.
+    pub gaps: usize,
+}
+
+/// Findings of the run, counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FindingsSummary {
+    pub total: usize,
+    pub by_severity: BTreeMap<String, usize>,
+    pub by_kind: BTreeMap<String, usize>,
+}
+
+impl FindingsSummary {
+    pub fn from_report(report: &Report) -> Self {
+        let mut summary = FindingsSummary {
+            total: report.findings.len(),
+            ..Default::default()
+        };
+        for f in &report.findings {
+            *summary
+                .by_severity
+                .entry(f.severity.to_string())
+                .or_default() += 1;
+            *summary.by_kind.entry(f.kind.id().to_string()).or_default() += 1;
+        }
+        summary
+    }
+}
+
+/// Contents of `run_manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunManifest {
+    pub tool_version: String,
+    /// RFC 3339 generation time.
+    pub generated_at: String,
+    pub inputs: ManifestInputs,
+    pub counts: StageCounts,
+    pub findings: FindingsSummary,
+}
+
+/// [`ManifestConfig`] for the config file at `path`, `None` if it is absent.
+pub fn manifest_config(path: &str) -> Result<Option<ManifestConfig>, Box<dyn Error>> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(path).map_err(|e| format!("Error reading {path}: {e}"))?;
+    let sha256 = Sha256::digest(&bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    Ok(Some(ManifestConfig {
+        path: path.to_string(),
+        sha256,
+    }))
+}
+
+/// Write `manifest` as `run_manifest.json` into `dir`, returning the path.
+pub fn write_manifest(manifest: &RunManifest, dir: &Path) -> Result<String, Box<dyn Error>> {
+    let file = dir.join("run_manifest.json").to_string_lossy().into_owned();
+    let json = serde_json::to_string_pretty(manifest)?;
+    std::fs::write(&file, json + "\n").map_err(|e| format!("Error writing {file}: {e}"))?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::Data;
+    use crate::models::Subnet;
+
+    #[test]
+    fn manifest_hashes_config_and_counts_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("azure-subnet-summary.toml");
+        std::fs::write(&config, "compress_cache = true\n").unwrap();
+        let config = manifest_config(&config.to_string_lossy()).unwrap().unwrap();
+        assert_eq!(
+            config.sha256,
+            "2a559b1e6729f4c259874d95ead2805fc6634b4f0917293290ee1902847dc319"
+        );
+        assert!(manifest_config("missing.toml").unwrap().is_none());
+
+        let s = Subnet::test("hub", "app", "10.0.0.0/24");
+        let report = Report::from_subnets(&Data {
+            data: vec![s],
+            ..Default::default()
+        });
+        let findings = FindingsSummary::from_report(&report);
+        assert_eq!(findings.total, 1);
+        assert_eq!(findings.by_kind["missing_nsg"], 1);
+        assert_eq!(findings.by_severity["error"], 1);
+
+        let manifest = RunManifest {
+            tool_version: "0.1.0".to_string(),
+            generated_at: "2024-05-01T00:00:00+00:00".to_string(),
+            inputs: ManifestInputs {
+                config: Some(config),
+                ..Default::default()
+            },
+            counts: StageCounts {
+                fetched: 1,
+                ..Default::default()
+            },
+            findings,
+        };
+        let file = write_manifest(&manifest, dir.path()).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+        assert_eq!(json["counts"]["fetched"], 1);
+        assert_eq!(json["findings"]["by_kind"]["missing_nsg"], 1);
+    }
+}
@@ -1,23 +1,67 @@
 //! Output formatting for subnet data.
 //!
 //! This module handles formatting and outputting subnet data:
+//! - [`clipboard`] - Report text on the system clipboard (`--copy`)
 //! - [`csv`] - CSV output formatting
 //! - [`dup_report`] - Markdown duplicate VNet report
-//! - [`terminal`] - Terminal output with colors
+//! - [`format`] - Human-readable numbers (`65,531`, `64Ki`, `81%`, `/22 = 1019 az hosts`)
+//! - [`gitops`] - One YAML file per VNet in a directory tree for git (`--gitops-dir`)
+//! - [`heatmap`] - Address space ownership heatmap (CSV + HTML, `--heatmap`)
+//! - [`manifest`] - Run manifest with inputs, stage counts and findings (`--manifest`)
+//! - [`notify`] - Run completion events to Event Grid topics / storage queues (`--notify`)
+//! - [`peering_overlap`] - Overlapping address space between VNets that can reach each other
+//! - [`stream`] - Rows written batch by batch as they are finished (`--stream`, `--output jsonl`)
+//! - [`template`] - User-provided Handlebars output templates (`--template`)
+//! - [`table`] - Aligned per-VNet terminal tables (`--output table`)
+//! - [`remediation`] - Review-before-run `az` remediation script from report findings
+//! - [`terminal`] - Terminal output; the one place colors are applied (`--plain`, `NO_COLOR`)
 //! - [`validate_dot`] - Pre-render validation of generated DOT files
 
+mod clipboard;
 mod csv;
 mod dup_report;
+pub mod format;
+mod gitops;
+mod heatmap;
+mod manifest;
+#[cfg(feature = "cli")]
+mod notify;
 mod peering_diagram;
 mod peering_dot;
+mod peering_overlap;
 mod peering_topology;
+mod remediation;
+mod stream;
+mod table;
+mod template;
 mod terminal;
 pub mod validate_dot;
 
-pub use csv::subnet_print;
+pub use clipboard::copy_to_clipboard;
+pub use csv::{
+    build_rows, extra_columns, subnet_print, CsvColumn, CsvDialect, CsvQuoting, CsvStream,
+};
 pub use dup_report::write_duplicates_md;
+pub use gitops::{gitops_vnets, write_gitops_tree, GitopsSubnet, GitopsSummary, GitopsVnet};
+pub use heatmap::{heatmap_csv, heatmap_html, write_heatmap};
+pub use manifest::{
+    manifest_config, write_manifest, FindingsSummary, ManifestConfig, ManifestInputs, RunManifest,
+    StageCounts,
+};
+#[cfg(feature = "cli")]
+pub use notify::{event_grid_body, queue_message_body, send_run_event, NotifyTarget, RunEvent};
 pub use peering_diagram::write_peering_diagram;
 pub use peering_dot::write_peering_dot;
+pub use peering_overlap::{
+    find_peering_overlaps, format_peering_overlaps, PeeringOverlap, PeeringPath,
+};
 pub use peering_topology::{build_topology, PeeringTopology};
-pub use terminal::format_field;
+pub use remediation::{remediation_script, write_remediation_script};
+pub use stream::{JsonlStream, RowStream};
+pub use table::{format_table, OutputFormat};
+pub use template::{
+    render_report_json, render_report_template, template_output_name, write_template,
+    TemplateContext,
+};
+pub use terminal::{disable_color, format_field, no_color_requested, paint, Style};
 pub use validate_dot::validate_dot_file;
@@ -0,0 +1,217 @@
+//! Run completion events (`--notify`).
+//!
+//! After a run has written its report, a small [`RunEvent`] (stage counts,
+//! findings, report location) is sent to each `--notify` target so
+//! downstream automation can react without polling: an Event Grid topic
+//! (`https://<topic>.<region>-1.eventgrid.azure.net/api/events`, Event Grid
+//! schema) or a storage queue (`https://<account>.queue.core.windows.net/<queue>`,
+//! base64 JSON message). Both are sent as the signed-in `az` identity, which
+//! needs *EventGrid Data Sender* or *Storage Queue Data Message Sender*.
+
+use super::manifest::{FindingsSummary, StageCounts};
+use crate::azure::access_token;
+use base64::Engine;
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// `eventType` of the Event Grid event.
+pub const RUN_COMPLETED_EVENT: &str = "AzureSubnetSummary.RunCompleted";
+
+/// Storage REST API version sent with queue requests (bearer tokens need
+/// 2017-11-09 or later).
+const STORAGE_API_VERSION: &str = "2021-08-06";
+
+/// What a completed run reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunEvent {
+    pub generated_at: String,
+    /// Query scope in words, e.g. `management group 'mg-corp'`.
+    pub scope: String,
+    pub report_dir: String,
+    /// Where the report was published (`--report-url`), if anywhere.
+    pub report_url: Option<String>,
+    pub counts: StageCounts,
+    pub findings: FindingsSummary,
+}
+
+impl RunEvent {
+    /// Stable event ID, so a re-sent event can be de-duplicated.
+    fn id(&self) -> String {
+        let digest = Sha256::digest(format!(
+            "{}|{}|{}",
+            self.generated_at, self.scope, self.report_dir
+        ));
+        digest[..16].iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
+
+/// Where a [`RunEvent`] is sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifyTarget {
+    /// Event Grid topic endpoint (`.../api/events`).
+    EventGrid(Url),
+    /// Storage queue (`https://<account>.queue.core.windows.net/<queue>`).
+    StorageQueue(Url),
+}
+
+impl FromStr for NotifyTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err =
+            || format!("'{s}' is neither an Event Grid topic endpoint nor a storage queue URL");
+        let mut url = Url::parse(s).map_err(|e| format!("'{s}': {e}"))?;
+        let host = url.host_str().ok_or_else(err)?.to_lowercase();
+        if url.scheme() != "https" {
+            return Err(format!("'{s}': notification targets must use https"));
+        }
+        if host.ends_with(".eventgrid.azure.net") {
+            if url.path() == "/" {
+                url.set_path("/api/events");
+            }
+            return Ok(NotifyTarget::EventGrid(url));
+        }
+        if host.contains(".queue.core.") {
+            let queue = url.path().trim_matches('/');
+            if queue.is_empty() || queue.contains('/') {
+                return Err(err());
+            }
+            return Ok(NotifyTarget::StorageQueue(url));
+        }
+        Err(err())
+    }
+}
+
+impl fmt::Display for NotifyTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyTarget::EventGrid(url) => write!(f, "Event Grid topic {url}"),
+            NotifyTarget::StorageQueue(url) => write!(f, "storage queue {url}"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NotifyTarget {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Event Grid schema body: an array holding the one event.
+pub fn event_grid_body(event: &RunEvent) -> Value {
+    json!([{
+        "id": event.id(),
+        "eventType": RUN_COMPLETED_EVENT,
+        "subject": event.report_dir,
+        "eventTime": event.generated_at,
+        "dataVersion": "1.0",
+        "data": event,
+    }])
+}
+
+/// Put Message body: the event JSON, base64-encoded as queue triggers expect.
+pub fn queue_message_body(event: &RunEvent) -> Result<String, Box<dyn Error>> {
+    let text = base64::engine::general_purpose::STANDARD.encode(serde_json::to_vec(event)?);
+    Ok(format!(
+        "<QueueMessage><MessageText>{text}</MessageText></QueueMessage>"
+    ))
+}
+
+/// Send `event` to `target`.
+pub fn send_run_event(target: &NotifyTarget, event: &RunEvent) -> Result<(), Box<dyn Error>> {
+    let client = Client::new();
+    let request = match target {
+        NotifyTarget::EventGrid(url) => client
+            .post(url.clone())
+            .bearer_auth(access_token("https://eventgrid.azure.net")?)
+            .json(&event_grid_body(event)),
+        NotifyTarget::StorageQueue(url) => {
+            let mut messages = url.clone();
+            messages.set_path(&format!("{}/messages", url.path().trim_end_matches('/')));
+            client
+                .post(messages)
+                .bearer_auth(access_token("https://storage.azure.com")?)
+                .header("x-ms-version", STORAGE_API_VERSION)
+                .header("Content-Type", "application/xml")
+                .body(queue_message_body(event)?)
+        }
+    };
+    let response = request
+        .send()
+        .map_err(|e| format!("Error notifying {target}: {e}"))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        return Err(format!("Notifying {target} failed: {status} {}", body.trim()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_are_recognised_and_events_encoded() {
+        let topic: NotifyTarget = "https://net-runs.westeurope-1.eventgrid.azure.net"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            topic,
+            NotifyTarget::EventGrid(
+                Url::parse("https://net-runs.westeurope-1.eventgrid.azure.net/api/events").unwrap()
+            )
+        );
+        let queue: NotifyTarget = "https://stnet.queue.core.windows.net/subnet-runs"
+            .parse()
+            .unwrap();
+        assert!(matches!(queue, NotifyTarget::StorageQueue(_)));
+        for bad in [
+            "https://example.com/hook",
+            "http://net-runs.westeurope-1.eventgrid.azure.net/api/events",
+            "https://stnet.queue.core.windows.net/",
+            "not a url",
+        ] {
+            assert!(bad.parse::<NotifyTarget>().is_err(), "{bad}");
+        }
+
+        let event = RunEvent {
+            generated_at: "2026-10-15T02:00:00Z".to_string(),
+            scope: "tenant".to_string(),
+            report_dir: "report-2026-10-15".to_string(),
+            report_url: Some("https://stnet.blob.core.windows.net/reports/2026-10-15/".to_string()),
+            counts: StageCounts {
+                active: 120,
+                vnets: 14,
+                ..Default::default()
+            },
+            findings: FindingsSummary::default(),
+        };
+        let body = event_grid_body(&event);
+        assert_eq!(body[0]["eventType"], RUN_COMPLETED_EVENT);
+        assert_eq!(body[0]["subject"], "report-2026-10-15");
+        assert_eq!(body[0]["data"]["counts"]["vnets"], 14);
+        assert_eq!(body[0]["id"].as_str().unwrap().len(), 32);
+
+        let xml = queue_message_body(&event).unwrap();
+        let text = xml
+            .strip_prefix("<QueueMessage><MessageText>")
+            .and_then(|x| x.strip_suffix("</MessageText></QueueMessage>"))
+            .unwrap();
+        let decoded: Value = serde_json::from_slice(
+            &base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(decoded["report_url"], event.report_url.unwrap());
+    }
+}
@@ -18,14 +18,18 @@ use std::io::{BufWriter, Write};
 ///
 /// * `topo`     – pre-built peering topology (see [`build_topology`])
 /// * `filename` – output path for the `.md` file
-pub fn write_peering_diagram(topo: &PeeringTopology, filename: &str) -> Result<(), Box<dyn Error>> {
+/// * `generated_at` – generation timestamp written under the title
+pub fn write_peering_diagram(
+    topo: &PeeringTopology,
+    filename: &str,
+    generated_at: &str,
+) -> Result<(), Box<dyn Error>> {
     let file = File::create(filename)?;
     let mut w = BufWriter::new(file);
-    let date = chrono::Local::now().format("%Y-%m-%d");
 
     writeln!(w, "# Azure VNet Peering Diagram")?;
     writeln!(w)?;
-    writeln!(w, "Generated: {date}")?;
+    writeln!(w, "Generated: {generated_at}")?;
     writeln!(w)?;
     writeln!(w, "```mermaid")?;
     writeln!(
@@ -143,6 +147,8 @@ mod tests {
     use super::*;
     use crate::azure::{Data, PeeringEdge};
 
+    const GENERATED_AT: &str = "2026-10-15T09:30:00+13:00";
+
     fn arm_id(sub: &str, vnet: &str) -> String {
         format!(
             "/subscriptions/{sub}/resourceGroups/rg/providers/Microsoft.Network/virtualNetworks/{vnet}"
@@ -168,7 +174,7 @@ mod tests {
         }];
         let topo = build_topology(&edges, &empty_data(), &[], &[]);
         let f = "/tmp/test-mermaid-elk.md";
-        write_peering_diagram(&topo, f).unwrap();
+        write_peering_diagram(&topo, f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -197,7 +203,7 @@ mod tests {
         ];
         let topo = build_topology(&edges, &empty_data(), &[], &[]);
         let f = "/tmp/test-peering-bidir.md";
-        write_peering_diagram(&topo, f).unwrap();
+        write_peering_diagram(&topo, f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -216,7 +222,7 @@ mod tests {
         }];
         let topo = build_topology(&edges, &empty_data(), &[], &[]);
         let f = "/tmp/test-peering-broken.md";
-        write_peering_diagram(&topo, f).unwrap();
+        write_peering_diagram(&topo, f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(c.contains("--x"), "Expected --x stop arrow:\n{c}");
@@ -234,7 +240,7 @@ mod tests {
         }];
         let topo = build_topology(&edges, &empty_data(), &[], &[]);
         let f = "/tmp/test-peering-label.md";
-        write_peering_diagram(&topo, f).unwrap();
+        write_peering_diagram(&topo, f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -246,10 +252,10 @@ mod tests {
     #[test]
     fn gateway_vnet_gets_external_node() {
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "hub-vnet".into();
-        s.subnet_name = "GatewaySubnet".into();
-        s.subscription_name = "Prod Sub".into();
+        let s = Subnet {
+            subscription_name: "Prod Sub".into(),
+            ..Subnet::test("hub-vnet", "GatewaySubnet", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -258,7 +264,7 @@ mod tests {
         };
         let topo = build_topology(&[], &data, &[], &[]);
         let f = "/tmp/test-peering-gateway.md";
-        write_peering_diagram(&topo, f).unwrap();
+        write_peering_diagram(&topo, f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -271,10 +277,10 @@ mod tests {
     #[test]
     fn standalone_vnet_gets_own_subgraph() {
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "standalone-vnet".into();
-        s.subnet_name = "default".into();
-        s.subscription_name = "Standalone Sub".into();
+        let s = Subnet {
+            subscription_name: "Standalone Sub".into(),
+            ..Subnet::test("standalone-vnet", "default", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -283,7 +289,7 @@ mod tests {
         };
         let topo = build_topology(&[], &data, &[], &[]);
         let f = "/tmp/test-peering-standalone.md";
-        write_peering_diagram(&topo, f).unwrap();
+        write_peering_diagram(&topo, f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(c.contains("subgraph"), "Must have subgraph:\n{c}");
@@ -325,7 +331,7 @@ mod tests {
             local_gateways: vec![],
         };
         let f = "/tmp/test-prebuilt-topo.md";
-        write_peering_diagram(&topo, f).unwrap();
+        write_peering_diagram(&topo, f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(c.contains("Sub-X/alpha"), "Label must appear:\n{c}");
@@ -9,18 +9,13 @@
 //! # or open in VSCode with the "Graphviz Preview" extension
 //! ```
 
+use super::format::html_escape;
 use super::peering_topology::{node_id, PeeringTopology, VWanHub};
 use crate::azure::LocalGatewayRow;
 use std::error::Error;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-}
-
 /// Escape a string for use inside a DOT plain string label (`label="..."`).
 ///
 /// DOT plain string labels only support two escape sequences:
@@ -41,12 +36,19 @@ fn dot_escape(s: &str) -> String {
 ///
 /// * `topo`     – pre-built peering topology (see [`build_topology`])
 /// * `filename` – output path for the `.dot` file
-pub fn write_peering_dot(topo: &PeeringTopology, filename: &str) -> Result<(), Box<dyn Error>> {
+/// * `generated_at` – generation timestamp written into the header comment
+pub fn write_peering_dot(
+    topo: &PeeringTopology,
+    filename: &str,
+    generated_at: &str,
+) -> Result<(), Box<dyn Error>> {
     let file = File::create(filename)?;
     let mut w = BufWriter::new(file);
-    let date = chrono::Local::now().format("%Y-%m-%d");
 
-    writeln!(w, "// Azure VNet Peering Diagram — generated {date}")?;
+    writeln!(
+        w,
+        "// Azure VNet Peering Diagram — generated {generated_at}"
+    )?;
     writeln!(w, "// Render: dot -Kfdp -Tsvg {filename} -o peering.svg")?;
     writeln!(w, "digraph azure_vnet_peering {{")?;
     // Layout tuning — kept engine-agnostic so fdp/sfdp/neato all accept these:
@@ -491,6 +493,8 @@ mod tests {
     use super::*;
     use crate::azure::{Data, PeeringEdge};
 
+    const GENERATED_AT: &str = "2026-10-15T09:30:00+13:00";
+
     fn arm_id(sub: &str, vnet: &str) -> String {
         format!(
             "/subscriptions/{sub}/resourceGroups/rg/providers/Microsoft.Network/virtualNetworks/{vnet}"
@@ -519,10 +523,10 @@ mod tests {
     fn dot_vwan_hub_node_rendered() {
         use crate::azure::VWanRow;
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "spoke-vnet".into();
-        s.subnet_name = "default".into();
-        s.subscription_name = "Prod Sub".into();
+        let s = Subnet {
+            subscription_name: "Prod Sub".into(),
+            ..Subnet::test("spoke-vnet", "default", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -536,7 +540,7 @@ mod tests {
             ..Default::default()
         };
         let f = "/tmp/test-dot-vwan-hub.dot";
-        write_peering_dot(&topo(&[], &data, &[], &[vwan_row]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[], &[vwan_row]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -557,10 +561,10 @@ mod tests {
     fn dot_vwan_spoke_to_hub_edge_rendered() {
         use crate::azure::VWanRow;
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "spoke-vnet".into();
-        s.subnet_name = "default".into();
-        s.subscription_name = "Prod Sub".into();
+        let s = Subnet {
+            subscription_name: "Prod Sub".into(),
+            ..Subnet::test("spoke-vnet", "default", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -574,7 +578,7 @@ mod tests {
             ..Default::default()
         };
         let f = "/tmp/test-dot-vwan-edge.dot";
-        write_peering_dot(&topo(&[], &data, &[], &[vwan_row]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[], &[vwan_row]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -600,7 +604,7 @@ mod tests {
             ..Default::default()
         }];
         let f = "/tmp/test-dot-hv-hidden.dot";
-        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -616,7 +620,7 @@ mod tests {
     #[test]
     fn dot_file_starts_with_digraph() {
         let f = "/tmp/test-dot-header.dot";
-        write_peering_dot(&topo(&[], &empty_data(), &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &empty_data(), &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -645,7 +649,7 @@ mod tests {
             },
         ];
         let f = "/tmp/test-dot-bidir.dot";
-        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -663,7 +667,7 @@ mod tests {
             ..Default::default()
         }];
         let f = "/tmp/test-dot-broken.dot";
-        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(c.contains("color=red"), "Broken edge must be red:\n{c}");
@@ -683,7 +687,7 @@ mod tests {
             ..Default::default()
         }];
         let f = "/tmp/test-dot-label.dot";
-        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -696,10 +700,10 @@ mod tests {
     fn dot_gateway_vnet_has_external_node() {
         use crate::azure::LocalGatewayRow;
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "hub-vnet".into();
-        s.subnet_name = "GatewaySubnet".into();
-        s.subscription_name = "Prod Sub".into();
+        let s = Subnet {
+            subscription_name: "Prod Sub".into(),
+            ..Subnet::test("hub-vnet", "GatewaySubnet", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -715,7 +719,7 @@ mod tests {
             ..Default::default()
         };
         let f = "/tmp/test-dot-gateway.dot";
-        write_peering_dot(&topo(&[], &data, &[lng], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[lng], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -732,10 +736,10 @@ mod tests {
     #[test]
     fn dot_gateway_vnet_no_lng_has_no_external_node() {
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "hub-vnet".into();
-        s.subnet_name = "GatewaySubnet".into();
-        s.subscription_name = "Prod Sub".into();
+        let s = Subnet {
+            subscription_name: "Prod Sub".into(),
+            ..Subnet::test("hub-vnet", "GatewaySubnet", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -743,7 +747,7 @@ mod tests {
             total_records: None,
         };
         let f = "/tmp/test-dot-gateway-no-lng.dot";
-        write_peering_dot(&topo(&[], &data, &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -760,17 +764,17 @@ mod tests {
     #[test]
     fn dot_vnet_with_two_address_spaces_shows_both_cidrs() {
         use crate::models::{Ipv4, Subnet};
-        let mut s1 = Subnet::default();
-        s1.vnet_name = "multi-cidr-vnet".into();
-        s1.subnet_name = "subnet-a".into();
-        s1.subscription_name = "Prod Sub".into();
-        s1.vnet_cidr = Ipv4::new("10.0.0.0/16").unwrap();
-
-        let mut s2 = Subnet::default();
-        s2.vnet_name = "multi-cidr-vnet".into();
-        s2.subnet_name = "subnet-b".into();
-        s2.subscription_name = "Prod Sub".into();
-        s2.vnet_cidr = Ipv4::new("172.17.8.0/21").unwrap();
+        let s1 = Subnet {
+            subscription_name: "Prod Sub".into(),
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            ..Subnet::test("multi-cidr-vnet", "subnet-a", "")
+        };
+
+        let s2 = Subnet {
+            subscription_name: "Prod Sub".into(),
+            vnet_cidr: Ipv4::new("172.17.8.0/21").unwrap(),
+            ..Subnet::test("multi-cidr-vnet", "subnet-b", "")
+        };
 
         let data = Data {
             data: vec![s1, s2],
@@ -779,7 +783,7 @@ mod tests {
             total_records: None,
         };
         let f = "/tmp/test-dot-multi-cidr.dot";
-        write_peering_dot(&topo(&[], &data, &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -795,10 +799,10 @@ mod tests {
     #[test]
     fn dot_standalone_vnet_in_cluster() {
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "standalone-vnet".into();
-        s.subnet_name = "default".into();
-        s.subscription_name = "Standalone Sub".into();
+        let s = Subnet {
+            subscription_name: "Standalone Sub".into(),
+            ..Subnet::test("standalone-vnet", "default", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -806,7 +810,7 @@ mod tests {
             total_records: None,
         };
         let f = "/tmp/test-dot-standalone.dot";
-        write_peering_dot(&topo(&[], &data, &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -822,17 +826,17 @@ mod tests {
     #[test]
     fn dot_vnet_header_is_compact_with_bold_name_and_vnet_cidrs_label() {
         use crate::models::{Ipv4, Subnet};
-        let mut s1 = Subnet::default();
-        s1.vnet_name = "pd-ibe-westus-arm".into();
-        s1.subnet_name = "subnet-a".into();
-        s1.subscription_name = "Prod Sub".into();
-        s1.vnet_cidr = Ipv4::new("10.0.0.0/16").unwrap();
-
-        let mut s2 = Subnet::default();
-        s2.vnet_name = "pd-ibe-westus-arm".into();
-        s2.subnet_name = "subnet-b".into();
-        s2.subscription_name = "Prod Sub".into();
-        s2.vnet_cidr = Ipv4::new("172.17.8.0/21").unwrap();
+        let s1 = Subnet {
+            subscription_name: "Prod Sub".into(),
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            ..Subnet::test("pd-ibe-westus-arm", "subnet-a", "")
+        };
+
+        let s2 = Subnet {
+            subscription_name: "Prod Sub".into(),
+            vnet_cidr: Ipv4::new("172.17.8.0/21").unwrap(),
+            ..Subnet::test("pd-ibe-westus-arm", "subnet-b", "")
+        };
 
         let data = Data {
             data: vec![s1, s2],
@@ -841,7 +845,7 @@ mod tests {
             total_records: None,
         };
         let f = "/tmp/test-dot-compact-header.dot";
-        write_peering_dot(&topo(&[], &data, &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
 
@@ -860,19 +864,17 @@ mod tests {
         use crate::models::{Ipv4, Subnet};
         // subnet-z in 172.17.8.0/21 (higher vnet_cidr) and subnet-a in 10.0.0.0/16 (lower).
         // subnet-a must appear before subnet-z in the label.
-        let mut s1 = Subnet::default();
-        s1.vnet_name = "multi-cidr-vnet".into();
-        s1.subnet_name = "subnet-z".into();
-        s1.subscription_name = "Prod Sub".into();
-        s1.vnet_cidr = Ipv4::new("172.17.8.0/21").unwrap();
-        s1.subnet_cidr = Some(Ipv4::new("172.17.8.0/24").unwrap());
-
-        let mut s2 = Subnet::default();
-        s2.vnet_name = "multi-cidr-vnet".into();
-        s2.subnet_name = "subnet-a".into();
-        s2.subscription_name = "Prod Sub".into();
-        s2.vnet_cidr = Ipv4::new("10.0.0.0/16").unwrap();
-        s2.subnet_cidr = Some(Ipv4::new("10.0.0.0/24").unwrap());
+        let s1 = Subnet {
+            subscription_name: "Prod Sub".into(),
+            vnet_cidr: Ipv4::new("172.17.8.0/21").unwrap(),
+            ..Subnet::test("multi-cidr-vnet", "subnet-z", "172.17.8.0/24")
+        };
+
+        let s2 = Subnet {
+            subscription_name: "Prod Sub".into(),
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            ..Subnet::test("multi-cidr-vnet", "subnet-a", "10.0.0.0/24")
+        };
 
         let data = Data {
             data: vec![s1, s2],
@@ -881,7 +883,7 @@ mod tests {
             total_records: None,
         };
         let f = "/tmp/test-dot-subnet-vnet-cidr-order.dot";
-        write_peering_dot(&topo(&[], &data, &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
 
@@ -896,11 +898,11 @@ mod tests {
     #[test]
     fn dot_subscription_name_with_quote_is_escaped_in_cluster_label() {
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "my-vnet".into();
-        s.subnet_name = "default".into();
-        // Subscription name contains a `"` — must be escaped to `\"` in DOT
-        s.subscription_name = r#"My "Special" Sub"#.into();
+        let s = Subnet {
+            // Subscription name contains a `"` — must be escaped to `\"` in DOT
+            subscription_name: r#"My "Special" Sub"#.into(),
+            ..Subnet::test("my-vnet", "default", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -908,7 +910,7 @@ mod tests {
             total_records: None,
         };
         let f = "/tmp/test-dot-sub-quote.dot";
-        write_peering_dot(&topo(&[], &data, &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -927,7 +929,7 @@ mod tests {
             ..Default::default()
         };
         let f = "/tmp/test-dot-hub-quote.dot";
-        write_peering_dot(&topo(&[], &empty_data(), &[], &[row]), f).unwrap();
+        write_peering_dot(&topo(&[], &empty_data(), &[], &[row]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
@@ -950,7 +952,7 @@ mod tests {
             ..Default::default()
         }];
         let f = "/tmp/test-dot-hub-no-non-ascii.dot";
-        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f).unwrap();
+        write_peering_dot(&topo(&edges, &empty_data(), &[], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         // Verify no non-ASCII in plain string labels (the crash-inducing context)
@@ -968,10 +970,10 @@ mod tests {
     fn dot_lng_label_contains_no_non_ascii() {
         use crate::azure::LocalGatewayRow;
         use crate::models::Subnet;
-        let mut s = Subnet::default();
-        s.vnet_name = "hub-vnet".into();
-        s.subnet_name = "GatewaySubnet".into();
-        s.subscription_name = "Prod Sub".into();
+        let s = Subnet {
+            subscription_name: "Prod Sub".into(),
+            ..Subnet::test("hub-vnet", "GatewaySubnet", "")
+        };
         let data = Data {
             data: vec![s],
             count: 1,
@@ -987,7 +989,7 @@ mod tests {
             ..Default::default()
         };
         let f = "/tmp/test-dot-lng-no-non-ascii.dot";
-        write_peering_dot(&topo(&[], &data, &[lng], &[]), f).unwrap();
+        write_peering_dot(&topo(&[], &data, &[lng], &[]), f, GENERATED_AT).unwrap();
         let c = std::fs::read_to_string(f).unwrap();
         std::fs::remove_file(f).ok();
         assert!(
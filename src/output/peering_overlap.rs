@@ -0,0 +1,214 @@
+//! Overlapping address space between VNets that can reach each other.
+//!
+//! Overlapping VNets that never talk are harmless; once they are peered —
+//! directly, as spokes of the same vWAN hub, or as spokes peered to the same
+//! gateway hub VNet — traffic for the shared range is routed to only one of
+//! them. These pairs are the dangerous overlaps, ranked by affected IPs.
+
+use super::format::thousands;
+use super::peering_topology::{canonical_pair, PeeringTopology};
+use crate::models::Ipv4;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// How two VNets reach each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeeringPath {
+    /// Connected peering in both directions.
+    Direct,
+    /// Spokes of the same Virtual WAN hub.
+    VwanHub(String),
+    /// Both peered to the same VNet hosting a gateway (hub-and-spoke transit).
+    GatewayHub(String),
+}
+
+impl fmt::Display for PeeringPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeeringPath::Direct => f.write_str("peered directly"),
+            PeeringPath::VwanHub(hub) => write!(f, "via vWAN hub '{hub}'"),
+            PeeringPath::GatewayHub(hub) => write!(f, "via hub VNet '{hub}'"),
+        }
+    }
+}
+
+/// Two connected VNets whose address spaces overlap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeeringOverlap {
+    pub first: String,
+    pub second: String,
+    pub path: PeeringPath,
+    /// Overlapping `(first, second)` address prefixes.
+    pub prefixes: Vec<(Ipv4, Ipv4)>,
+    /// Addresses in the overlapping ranges.
+    pub affected: u64,
+}
+
+fn addresses(c: &Ipv4) -> u64 {
+    1u64 << (32 - u32::from(c.mask))
+}
+
+/// Find connected VNet pairs with overlapping address space, most affected
+/// addresses first.
+pub fn find_peering_overlaps(topo: &PeeringTopology) -> Vec<PeeringOverlap> {
+    let mut neighbours: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for (a, b) in &topo.bidir_pairs {
+        neighbours.entry(a).or_default().insert(b);
+        neighbours.entry(b).or_default().insert(a);
+    }
+    let path =
+        |a: &str, b: &str| -> Option<PeeringPath> {
+            if topo.bidir_pairs.contains(&canonical_pair(a, b)) {
+                return Some(PeeringPath::Direct);
+            }
+            if let Some(hub) = topo.vwan_hubs.iter().find(|h| {
+                h.spoke_vnets.iter().any(|s| s == a) && h.spoke_vnets.iter().any(|s| s == b)
+            }) {
+                return Some(PeeringPath::VwanHub(hub.hub_name.clone()));
+            }
+            let (na, nb) = (neighbours.get(a)?, neighbours.get(b)?);
+            let mut hubs: Vec<&str> = na
+                .intersection(nb)
+                .copied()
+                .filter(|h| topo.vnet_meta.get(*h).is_some_and(|m| m.has_gateway))
+                .collect();
+            hubs.sort();
+            hubs.first().map(|h| PeeringPath::GatewayHub(h.to_string()))
+        };
+
+    let mut vnets: Vec<(&String, Vec<Ipv4>)> = topo
+        .vnet_meta
+        .iter()
+        .map(|(name, meta)| {
+            let cidrs = meta
+                .vnet_cidr
+                .iter()
+                .filter_map(|c| Ipv4::new(c).ok())
+                .collect();
+            (name, cidrs)
+        })
+        .collect();
+    vnets.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut overlaps = Vec::new();
+    for (i, (a, a_cidrs)) in vnets.iter().enumerate() {
+        for (b, b_cidrs) in &vnets[i + 1..] {
+            let prefixes: Vec<(Ipv4, Ipv4)> = a_cidrs
+                .iter()
+                .flat_map(|x| b_cidrs.iter().map(move |y| (*x, *y)))
+                .filter(|(x, y)| x.lo() <= y.hi() && y.lo() <= x.hi())
+                .collect();
+            if prefixes.is_empty() {
+                continue;
+            }
+            let Some(path) = path(a, b) else {
+                continue;
+            };
+            // Aligned prefixes either nest or are disjoint: the overlap is the smaller one.
+            let affected = prefixes
+                .iter()
+                .map(|(x, y)| addresses(x).min(addresses(y)))
+                .sum();
+            overlaps.push(PeeringOverlap {
+                first: a.to_string(),
+                second: b.to_string(),
+                path,
+                prefixes,
+                affected,
+            });
+        }
+    }
+    overlaps.sort_by(|a, b| {
+        b.affected
+            .cmp(&a.affected)
+            .then_with(|| (&a.first, &a.second).cmp(&(&b.first, &b.second)))
+    });
+    overlaps
+}
+
+/// Render connected overlaps as terminal lines.
+pub fn format_peering_overlaps(overlaps: &[PeeringOverlap]) -> String {
+    overlaps
+        .iter()
+        .map(|o| {
+            let prefixes: Vec<String> = o
+                .prefixes
+                .iter()
+                .map(|(x, y)| {
+                    if x == y {
+                        x.to_string()
+                    } else {
+                        format!("{x} ~ {y}")
+                    }
+                })
+                .collect();
+            format!(
+                "PEERED OVERLAP: '{}' and '{}' ({}) - {} addresses in {}",
+                o.first,
+                o.second,
+                o.path,
+                thousands(o.affected),
+                prefixes.join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::peering_topology::build_topology;
+    use super::*;
+    use crate::azure::{Data, PeeringEdge};
+    use crate::models::Subnet;
+
+    fn edge(from: &str, to: &str) -> PeeringEdge {
+        PeeringEdge {
+            vnet_name: from.into(),
+            peering_state: "Connected".into(),
+            remote_vnet_id: format!(
+                "/subscriptions/s/resourceGroups/rg/providers/Microsoft.Network/virtualNetworks/{to}"
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn subnet(vnet: &str, vnet_cidr: &str, name: &str) -> Subnet {
+        Subnet {
+            vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+            ..Subnet::test(vnet, name, "")
+        }
+    }
+
+    #[test]
+    fn only_connected_overlaps_are_reported_largest_first() {
+        let data = Data {
+            data: vec![
+                subnet("hub", "10.0.0.0/24", "GatewaySubnet"),
+                subnet("spoke-a", "10.1.0.0/16", "app"),
+                subnet("spoke-b", "10.1.0.0/24", "app"),
+                subnet("peer-c", "10.0.0.0/25", "app"),
+                subnet("lonely", "10.1.0.0/16", "app"),
+            ],
+            ..Default::default()
+        };
+        let edges = [("hub", "spoke-a"), ("hub", "spoke-b"), ("hub", "peer-c")]
+            .iter()
+            .flat_map(|(a, b)| [edge(a, b), edge(b, a)])
+            .collect::<Vec<_>>();
+        let topo = build_topology(&edges, &data, &[], &[]);
+        let overlaps = find_peering_overlaps(&topo);
+
+        let pairs: Vec<(&str, &str, u64)> = overlaps
+            .iter()
+            .map(|o| (o.first.as_str(), o.second.as_str(), o.affected))
+            .collect();
+        // `lonely` overlaps both spokes but is not peered.
+        assert_eq!(pairs, [("spoke-a", "spoke-b", 256), ("hub", "peer-c", 128)]);
+        assert_eq!(overlaps[0].path, PeeringPath::GatewayHub("hub".into()));
+        assert_eq!(
+            format_peering_overlaps(&overlaps[1..]),
+            "PEERED OVERLAP: 'hub' and 'peer-c' (peered directly) - 128 addresses in 10.0.0.0/24 ~ 10.0.0.0/25"
+        );
+    }
+}
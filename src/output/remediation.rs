@@ -0,0 +1,154 @@
+//! Azure CLI remediation script generation.
+//!
+//! Turns [`Report`] findings into `az network vnet subnet update` commands.
+//! The script is meant to be reviewed and edited before running; it never
+//! runs anything itself.
+
+use crate::report::{FindingKind, Report};
+use std::error::Error;
+
+/// Render the remediation script for `report`.
+///
+/// Subnets with a known resource ID are addressed with `--ids`; for older
+/// caches without IDs the command resolves the VNet's resource group at run
+/// time instead. NSG fixes need the
+/// `NSG_ID` environment variable (an NSG resource ID) to be set.
+pub fn remediation_script(report: &Report) -> String {
+    let mut out = vec![
+        "#!/usr/bin/env bash".to_string(),
+        "# ─────────────────────────────────────────────────────────────────────".to_string(),
+        "# REVIEW BEFORE RUNNING — generated remediation commands.".to_string(),
+        format!(
+            "# Generated {} by azure-subnet-summary.",
+            report.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ),
+        "# Every command changes live Azure networking; check each one first.".to_string(),
+        "# ─────────────────────────────────────────────────────────────────────".to_string(),
+        "set -euo pipefail".to_string(),
+        "exit 1  # remove this line once the commands below have been reviewed".to_string(),
+    ];
+
+    let mut commands = 0;
+    for f in &report.findings {
+        let change = match &f.kind {
+            FindingKind::MissingNsg => {
+                "--network-security-group \"${NSG_ID:?set NSG_ID to the NSG resource ID}\""
+                    .to_string()
+            }
+            FindingKind::ResizeRecommended { suggested } => {
+                format!("--address-prefixes {suggested}")
+            }
+            // VNet-level policy findings need a DDoS plan, a maintenance
+            // window or a redeployment in another region, and splitting a
+            // shared NSG needs new rules; they are not scripted.
+            FindingKind::DdosProtectionRequired { .. }
+            | FindingKind::EncryptionRequired { .. }
+            | FindingKind::LocationMismatch { .. }
+            | FindingKind::SharedNsg { .. } => continue,
+        };
+        out.push(String::new());
+        out.push(format!("# {} [{}]", f.message, f.subscription_name));
+        if let FindingKind::ResizeRecommended { .. } = f.kind {
+            out.push(
+                "# NOTE: Azure may refuse to resize a subnet with resources attached.".to_string(),
+            );
+        }
+        let target = if f.subnet_id.is_empty() {
+            let rg = format!(
+                "$(az network vnet list --subscription '{sub}' --query \"[?name=='{vnet}'].resourceGroup | [0]\" -o tsv)",
+                sub = f.subscription_id,
+                vnet = f.vnet_name
+            );
+            format!(
+                "--subscription '{}' --resource-group \"{rg}\" \\\n    --vnet-name '{}' --name '{}'",
+                f.subscription_id, f.vnet_name, f.subnet_name
+            )
+        } else {
+            format!("--ids '{}' \\\n   ", f.subnet_id)
+        };
+        out.push(format!("az network vnet subnet update {target} {change}"));
+        commands += 1;
+    }
+    if commands == 0 {
+        out.push(String::new());
+        out.push("# No remediations needed.".to_string());
+    }
+    out.push(String::new());
+    out.join("\n")
+}
+
+/// Write the remediation script for `report` to `filename`.
+pub fn write_remediation_script(report: &Report, filename: &str) -> Result<(), Box<dyn Error>> {
+    std::fs::write(filename, remediation_script(report))
+        .map_err(|e| format!("Error writing remediation script {filename}: {e}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ipv4;
+    use crate::report::Finding;
+
+    fn finding(kind: FindingKind) -> Finding {
+        Finding {
+            severity: kind.severity(),
+            kind,
+            subscription_id: "sub-1".to_string(),
+            subscription_name: "Prod".to_string(),
+            vnet_name: "hub".to_string(),
+            subnet_name: "app".to_string(),
+            subnet_id: String::new(),
+            subnet_cidr: Some(Ipv4::new("10.0.0.32/27").unwrap()),
+            message: "msg".to_string(),
+        }
+    }
+
+    #[test]
+    fn script_is_marked_review_before_run_and_blocked_by_default() {
+        let report = Report {
+            generated_at: chrono::Utc::now(),
+            findings: vec![],
+            ..Default::default()
+        };
+        let script = remediation_script(&report);
+        assert!(script.contains("REVIEW BEFORE RUNNING"));
+        assert!(script.contains("\nexit 1"));
+        assert!(script.contains("# No remediations needed."));
+    }
+
+    #[test]
+    fn findings_become_subnet_update_commands() {
+        let report = Report {
+            generated_at: chrono::Utc::now(),
+            findings: vec![
+                finding(FindingKind::MissingNsg),
+                finding(FindingKind::ResizeRecommended {
+                    suggested: Ipv4::new("10.0.0.0/26").unwrap(),
+                }),
+            ],
+            ..Default::default()
+        };
+        let script = remediation_script(&report);
+        assert_eq!(script.matches("az network vnet subnet update").count(), 2);
+        assert!(script.contains("--vnet-name 'hub' --name 'app' --network-security-group"));
+        assert!(script.contains("--address-prefixes 10.0.0.0/26"));
+        assert!(script.contains("[?name=='hub'].resourceGroup"));
+    }
+
+    #[test]
+    fn known_subnet_id_is_addressed_directly() {
+        let mut f = finding(FindingKind::MissingNsg);
+        f.subnet_id = "/subscriptions/sub-1/resourceGroups/rg-net/providers/Microsoft.Network/virtualNetworks/hub/subnets/app".to_string();
+        let report = Report {
+            generated_at: chrono::Utc::now(),
+            findings: vec![f],
+            ..Default::default()
+        };
+        let script = remediation_script(&report);
+        assert!(script.contains(
+            "az network vnet subnet update --ids '/subscriptions/sub-1/resourceGroups/rg-net/"
+        ));
+        assert!(!script.contains("resourceGroup | [0]"));
+    }
+}
@@ -0,0 +1,78 @@
+//! Row output written while the rows are produced.
+//!
+//! A [`RowStream`] receives the subnet rows batch by batch — one VNet at a
+//! time with `--stream`, all at once otherwise — so stdout shows results
+//! before the run ends. [`JsonlStream`] writes JSON Lines (`--output jsonl`),
+//! one object per subnet or gap row, for `jq`, Azure Data Explorer ingestion
+//! and log pipelines; the CSV stream lives with the other CSV code.
+
+use crate::processing::SubnetPrintRow;
+use std::error::Error;
+use std::io::Write;
+
+/// Destination for rows written as they are finished.
+pub trait RowStream {
+    /// Write one batch of rows and flush.
+    fn write_rows(&mut self, rows: &[SubnetPrintRow]) -> Result<(), Box<dyn Error>>;
+    /// Complete the output; returns the number of rows written.
+    fn finish(&mut self) -> Result<usize, Box<dyn Error>>;
+}
+
+/// JSON Lines: one compact JSON object per row, newline-terminated.
+pub struct JsonlStream<W: Write> {
+    writer: W,
+    rows: usize,
+}
+
+impl<W: Write> JsonlStream<W> {
+    pub fn new(writer: W) -> Self {
+        JsonlStream { writer, rows: 0 }
+    }
+}
+
+impl<W: Write> RowStream for JsonlStream<W> {
+    fn write_rows(&mut self, rows: &[SubnetPrintRow]) -> Result<(), Box<dyn Error>> {
+        for row in rows {
+            serde_json::to_writer(&mut self.writer, row)?;
+            writeln!(self.writer)?;
+        }
+        self.writer.flush()?;
+        self.rows += rows.len();
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<usize, Box<dyn Error>> {
+        Ok(self.rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Ipv4, Subnet};
+    use crate::output::build_rows;
+
+    #[test]
+    fn every_row_is_one_json_object_per_line() {
+        let s = Subnet {
+            vnet_cidr: Ipv4::new("10.0.0.0/24").unwrap(),
+            ..Subnet::test("hub", "app", "10.0.0.0/25")
+        };
+        let rows = build_rows(&[s], &[], 4, &[]);
+
+        let mut out = Vec::new();
+        let mut stream = JsonlStream::new(&mut out);
+        stream.write_rows(&rows[..1]).unwrap();
+        stream.write_rows(&rows[1..]).unwrap();
+        assert_eq!(stream.finish().unwrap(), 2);
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["subnet_name"], "app");
+        assert_eq!(lines[1]["gap"], "-vgap-");
+        assert_eq!(lines[1]["subnet_cidr"], "10.0.0.128/25");
+    }
+}
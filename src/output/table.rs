@@ -0,0 +1,224 @@
+//! Aligned terminal table of the subnet rows (`--output table`).
+//!
+//! Renders the same rows as the CSV ([`build_rows`](super::csv::build_rows)),
+//! one bordered table per VNet CIDR with a section header, sized to the
+//! terminal instead of padded to fixed widths. Gap rows are indented under the
+//! subnets of their VNet and each section ends with its total gap space.
+
+use super::csv::CsvColumn;
+use super::format::{percent, thousands};
+use crate::i18n::Lang;
+use crate::models::Ipv4;
+use crate::processing::{SubnetPrintRow, BUFFER, VNET_BUFFER};
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL_CONDENSED;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use serde::Deserialize;
+
+/// How the subnet rows are presented on the terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Only write the CSV file (the terminal shows the VNet summary).
+    #[default]
+    Csv,
+    /// Also print the subnet rows as aligned tables, one per VNet.
+    Table,
+    /// Print the rows and findings as JSON on stdout; summaries and logs go
+    /// to stderr so the output can be piped (e.g. into `jq`).
+    Json,
+    /// Print one JSON object per row (JSON Lines) on stdout; summaries and
+    /// logs go to stderr.
+    Jsonl,
+}
+
+impl OutputFormat {
+    /// `true` when stdout carries machine-readable output only.
+    pub fn is_json(self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Jsonl)
+    }
+}
+
+/// `true` for gap and buffer rows outside any VNet.
+fn between_vnets(row: &SubnetPrintRow) -> bool {
+    row.gap == "-gap-" || row.gap == BUFFER
+}
+
+/// Section header for the VNet CIDR `row` belongs to.
+fn section_header(row: &SubnetPrintRow, lang: Lang) -> String {
+    if between_vnets(row) {
+        lang.tr("Unallocated space between VNets").to_string()
+    } else {
+        format!(
+            "VNet '{}' {} — {} ({})",
+            row.vnet_name, row.vnet_cidr, row.subscription_name, row.location
+        )
+    }
+}
+
+fn is_gap(row: &SubnetPrintRow) -> bool {
+    row.gap == "-vgap-" || row.gap == "-gap-"
+}
+
+/// Addresses in a row's CIDR (0 if it does not parse).
+fn addresses(row: &SubnetPrintRow) -> u64 {
+    Ipv4::new(&row.subnet_cidr)
+        .map(|c| 1u64 << (32 - u32::from(c.mask)))
+        .unwrap_or(0)
+}
+
+/// Footer with the total gap space of a section, and its share of the VNet.
+/// Buffers are intentional and not counted.
+fn section_footer(rows: &[SubnetPrintRow], lang: Lang) -> String {
+    let gap_rows: Vec<&SubnetPrintRow> = rows.iter().filter(|r| is_gap(r)).collect();
+    let free: u64 = gap_rows.iter().map(|r| addresses(r)).sum();
+    let blocks = gap_rows.len();
+    match Ipv4::new(&rows[0].vnet_cidr) {
+        Ok(vnet) if !between_vnets(&rows[0]) => {
+            let size = 1u64 << (32 - u32::from(vnet.mask));
+            lang.fill(
+                "Gap space: {} of {} addresses ({}) in {} block(s)",
+                &[
+                    &thousands(free),
+                    &thousands(size),
+                    &percent(free, size),
+                    &blocks,
+                ],
+            )
+        }
+        _ => lang.fill(
+            "Gap space: {} addresses in {} block(s)",
+            &[&thousands(free), &blocks],
+        ),
+    }
+}
+
+/// Rows of one section share a VNet CIDR; gap rows between VNets share one.
+fn same_section(a: &SubnetPrintRow, b: &SubnetPrintRow) -> bool {
+    between_vnets(a) == between_vnets(b)
+        && a.vnet_name == b.vnet_name
+        && a.vnet_cidr == b.vnet_cidr
+        && a.subscription_name == b.subscription_name
+}
+
+fn section_table(rows: &[SubnetPrintRow], columns: &[CsvColumn], lang: Lang) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+
+    let header: Vec<&str> = [
+        "#",
+        "Kind",
+        "CIDR",
+        "Used/Hosts",
+        "Broadcast",
+        "Subnet",
+        "NSG",
+        "DNS",
+    ]
+    .into_iter()
+    .chain(columns.iter().map(|c| c.header()))
+    .map(|h| lang.tr(h))
+    .collect();
+    table.set_header(header);
+
+    for row in rows {
+        let mut cells = vec![
+            if row.j == 0 {
+                String::new()
+            } else {
+                row.j.to_string()
+            },
+            row.gap.clone(),
+            // Indent gaps inside a VNet beneath its subnets.
+            if row.gap == "-vgap-" || row.gap == VNET_BUFFER {
+                format!("└ {}", row.subnet_cidr)
+            } else {
+                row.subnet_cidr.clone()
+            },
+            format!("{}/{}", row.ip_configurations_count, row.az_hosts),
+            row.broadcast.clone(),
+            row.subnet_name.clone(),
+            row.nsg.clone(),
+            row.dns.clone(),
+        ];
+        cells.extend(columns.iter().map(|c| c.text(row).to_string()));
+        table.add_row(cells);
+    }
+    for i in [0, 3] {
+        if let Some(column) = table.column_mut(i) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    table
+}
+
+/// Render `rows` as one table per VNet CIDR, each under a section header and
+/// followed by its total gap space.
+///
+/// `columns` adds the same optional columns as the CSV; headers and footers
+/// are in language `lang`. Without rows this is a single header-only table.
+pub fn format_table(rows: &[SubnetPrintRow], columns: &[CsvColumn], lang: Lang) -> String {
+    if rows.is_empty() {
+        return section_table(rows, columns, lang).to_string();
+    }
+    rows.chunk_by(same_section)
+        .map(|section| {
+            format!(
+                "{}\n{}\n{}",
+                section_header(&section[0], lang),
+                section_table(section, columns, lang),
+                section_footer(section, lang)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Ipv4, Subnet};
+    use crate::output::build_rows;
+
+    fn make_subnet(vnet_name: &str, vnet_cidr: &str, subnet_cidr: &str, name: &str) -> Subnet {
+        Subnet {
+            subscription_name: "Prod".to_string(),
+            subscription_id: "sub-id".to_string(),
+            location: "westeurope".to_string(),
+            vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+            ip_configurations_count: Some(3),
+            ..Subnet::test(vnet_name, name, subnet_cidr)
+        }
+    }
+
+    #[test]
+    fn rows_are_split_into_one_table_per_vnet() {
+        let subnets = [
+            make_subnet("hub", "10.0.0.0/24", "10.0.0.0/25", "app"),
+            make_subnet("spoke", "10.0.4.0/24", "10.0.4.0/24", "web"),
+        ];
+        let rows = build_rows(&subnets, &[], 28, &[]);
+        let out = format_table(&rows, &[CsvColumn::Role], Lang::En);
+
+        let headers: Vec<&str> = out.lines().filter(|l| l.starts_with(['V', 'U'])).collect();
+        assert_eq!(
+            headers,
+            [
+                "VNet 'hub' 10.0.0.0/24 — Prod (westeurope)",
+                "Unallocated space between VNets",
+                "VNet 'spoke' 10.0.4.0/24 — Prod (westeurope)",
+            ]
+        );
+        assert!(out.contains("3/123 ┆"), "{out}");
+        assert!(out.contains("┆ └ 10.0.0.128/28"), "vgap indented: {out}");
+        assert!(
+            out.contains("Gap space: 128 of 256 addresses (50%) in 8 block(s)"),
+            "{out}"
+        );
+        assert!(out.contains("role"), "optional column header: {out}");
+        assert!(!out.contains('"'), "no CSV quoting: {out}");
+    }
+}
@@ -0,0 +1,139 @@
+//! User-provided output templates (`--template`).
+//!
+//! Renders the report through a Handlebars template, so bespoke formats (a
+//! CMDB import file, a wiki table) need a template file rather than a new
+//! writer. Templates see `generated_at`, the report `findings` and the
+//! subnet and gap `rows` of the CSV; see [`TemplateContext`]. Rendering is
+//! strict: a misspelt field is an error instead of an empty value, and
+//! nothing is HTML-escaped.
+
+use crate::processing::SubnetPrintRow;
+use crate::report::{Finding, Report};
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::error::Error;
+use std::path::Path;
+
+/// Data a template is rendered with.
+#[derive(Serialize)]
+pub struct TemplateContext<'a> {
+    /// Generation time, `YYYY-MM-DD HH:MM:SS UTC`.
+    pub generated_at: String,
+    /// Report findings (missing NSGs, resizes), as in `check --format json`.
+    pub findings: &'a [Finding],
+    /// Subnet and gap rows, with the fields of the subnet CSV.
+    pub rows: &'a [SubnetPrintRow],
+}
+
+impl<'a> TemplateContext<'a> {
+    pub fn new(report: &'a Report, rows: &'a [SubnetPrintRow]) -> Self {
+        TemplateContext {
+            generated_at: report
+                .generated_at
+                .format("%Y-%m-%d %H:%M:%S UTC")
+                .to_string(),
+            findings: &report.findings,
+            rows,
+        }
+    }
+}
+
+/// Render `template` (Handlebars source) with `report` and `rows`.
+pub fn render_report_template(
+    template: &str,
+    report: &Report,
+    rows: &[SubnetPrintRow],
+) -> Result<String, Box<dyn Error>> {
+    let mut hb = Handlebars::new();
+    hb.set_strict_mode(true);
+    hb.register_escape_fn(handlebars::no_escape);
+    Ok(hb.render_template(template, &TemplateContext::new(report, rows))?)
+}
+
+/// `report` and `rows` as the JSON document of `--output json`: the
+/// [`TemplateContext`] templates are rendered with.
+pub fn render_report_json(
+    report: &Report,
+    rows: &[SubnetPrintRow],
+) -> Result<String, Box<dyn Error>> {
+    Ok(serde_json::to_string_pretty(&TemplateContext::new(
+        report, rows,
+    ))?)
+}
+
+/// Output file name for `template_file`: its file name without a trailing
+/// `.hbs`, prefixed `net_<date>_` (`cmdb.csv.hbs` → `net_<date>_cmdb.csv`).
+pub fn template_output_name(template_file: &str, date_str: &str) -> String {
+    let name = Path::new(template_file)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| template_file.to_string());
+    let name = name.strip_suffix(".hbs").unwrap_or(&name);
+    format!("net_{date_str}_{name}")
+}
+
+/// Render `template_file` into `dir`, returning the written path.
+pub fn write_template(
+    template_file: &str,
+    report: &Report,
+    rows: &[SubnetPrintRow],
+    dir: &Path,
+    date_str: &str,
+) -> Result<String, Box<dyn Error>> {
+    let template = std::fs::read_to_string(template_file)
+        .map_err(|e| format!("Error reading template {template_file}: {e}"))?;
+    let text = render_report_template(&template, report, rows)
+        .map_err(|e| format!("Error rendering template {template_file}: {e}"))?;
+    let out = dir
+        .join(template_output_name(template_file, date_str))
+        .to_string_lossy()
+        .into_owned();
+    std::fs::write(&out, text).map_err(|e| format!("Error writing {out}: {e}"))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::Data;
+    use crate::models::{Ipv4, Subnet};
+    use crate::output::build_rows;
+
+    #[test]
+    fn templates_render_rows_and_findings_strictly() {
+        let mut s = Subnet {
+            subscription_name: "Prod".to_string(),
+            vnet_cidr: Ipv4::new("10.0.0.0/24").unwrap(),
+            ..Subnet::test("hub", "app&web", "10.0.0.0/24")
+        };
+        s.vnet_address_space = vec![s.vnet_cidr];
+        let data = Data {
+            data: vec![s],
+            ..Default::default()
+        };
+        let report = Report::from_subnets(&data);
+        let rows = build_rows(&data.data, &[], 32, &[]);
+
+        let text = render_report_template(
+            "{{#each rows}}{{vnet_name}};{{subnet_name}};{{subnet_cidr}}\n{{/each}}\
+             {{#each findings}}{{kind}}: {{message}}\n{{/each}}",
+            &report,
+            &rows,
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            "hub;app&web;10.0.0.0/24\nmissing_nsg: Subnet 'app&web' has no NSG attached\n"
+        );
+        assert!(render_report_template("{{#each rows}}{{vnet}}{{/each}}", &report, &rows).is_err());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_report_json(&report, &rows).unwrap()).unwrap();
+        assert_eq!(json["rows"][0]["subnet_name"], "app&web");
+        assert_eq!(json["findings"][0]["kind"], "missing_nsg");
+        assert_eq!(
+            template_output_name("templates/cmdb.csv.hbs", "2024-05-01"),
+            "net_2024-05-01_cmdb.csv"
+        );
+    }
+}
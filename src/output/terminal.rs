@@ -1,8 +1,53 @@
 //! Terminal output utilities.
 //!
-//! Provides formatting helpers for terminal output.
+//! Provides formatting helpers for terminal output. All colouring goes
+//! through [`paint`], so it is switched off in one place: by `--plain`
+//! ([`disable_color`]), by `NO_COLOR`, or when stdout is not a terminal
+//! (CI logs, pipes).
 
-/// Format a value as a quoted, right-aligned field.
+use colored::Colorize;
+use std::fmt::Display;
+
+/// What a piece of coloured text is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// VNet that won duplicate resolution.
+    Winner,
+    /// VNet excluded as a duplicate.
+    Duplicate,
+    /// Command line of an external program, in debug logs.
+    Command,
+    /// "failed" marker in error messages.
+    Failed,
+    /// Captured stderr of a failed program.
+    Stderr,
+}
+
+/// `true` when the `NO_COLOR` convention asks for no colour: the variable is
+/// set to a non-empty value.
+pub fn no_color_requested(value: Option<&str>) -> bool {
+    value.is_some_and(|v| !v.is_empty())
+}
+
+/// Turn colour off for the rest of the run (`--plain`).
+pub fn disable_color() {
+    colored::control::set_override(false);
+}
+
+/// `text` in `style`, or unchanged when colour is off.
+pub fn paint(text: impl Display, style: Style) -> String {
+    let text = text.to_string();
+    match style {
+        Style::Winner => text.green(),
+        Style::Duplicate | Style::Stderr => text.red(),
+        Style::Command => text.on_blue(),
+        Style::Failed => text.on_red(),
+    }
+    .to_string()
+}
+
+/// Format a value as a quoted, right-aligned field; quotes in the value are
+/// doubled as in RFC 4180.
 ///
 /// # Arguments
 /// * `value` - The value to format
@@ -12,7 +57,7 @@
 /// A quoted, right-aligned string
 pub fn format_field<T: ToString>(value: T, width: usize) -> String {
     let value_str = value.to_string();
-    let quoted = format!("\"{value_str}\"");
+    let quoted = format!("\"{}\"", value_str.replace('"', "\"\""));
     let quoted_len = quoted.len();
 
     if quoted_len >= width {
@@ -41,8 +86,23 @@ mod tests {
         assert_eq!(format_field("long_value", 5), "\"long_value\"");
     }
 
+    #[test]
+    fn test_format_field_escapes_quotes() {
+        assert_eq!(format_field(r#"say "hi""#, 14), r#"  "say ""hi""""#);
+    }
+
     #[test]
     fn test_format_field_number() {
         assert_eq!(format_field(42, 6), "  \"42\"");
     }
+
+    #[test]
+    fn plain_output_has_no_escape_codes() {
+        assert!(no_color_requested(Some("1")));
+        assert!(!no_color_requested(Some("")));
+        assert!(!no_color_requested(None));
+        disable_color();
+        assert_eq!(paint("hub", Style::Winner), "hub");
+        assert_eq!(paint("failed", Style::Failed), "failed");
+    }
 }
@@ -2,25 +2,52 @@
 //!
 //! Provides [`run`] which orchestrates the full pipeline from fetched
 //! [`AzureData`] to output files. [`SvgRenderer`] is an injectable seam
-//! so SVG rendering can be tested in isolation.
+//! so SVG rendering can be tested in isolation, and the
+//! [`RowProcessor`](crate::report::RowProcessor)s of a [`Report`] let
+//! embedding applications transform the subnet rows or add computed columns
+//! ([`run_with_report`]).
 
 use crate::{
-    azure::AzureData,
-    check_for_duplicate_subnets,
+    azure::{
+        graph_quota_status, list_snapshots, read_snapshots_newest_first, AccountContext, AzureData,
+        Data, QueryOptions, DEFAULT_PAGE_SIZE,
+    },
+    check_for_duplicate_subnets, check_for_overlapping_subnets,
+    clock::Clock,
+    commands::Command,
+    config::{Config, DEFAULT_CONFIG_FILE},
+    i18n::Lang,
+    models::{parse_cidr, Ipv4, ReservationModel},
     output::{
-        build_topology, subnet_print, validate_dot_file, write_peering_diagram, write_peering_dot,
+        build_rows, build_topology, copy_to_clipboard, extra_columns, find_peering_overlaps,
+        format_peering_overlaps, format_table, manifest_config, render_report_json, send_run_event,
+        subnet_print, validate_dot_file, write_gitops_tree, write_heatmap, write_manifest,
+        write_peering_diagram, write_peering_dot, write_remediation_script, write_template,
+        CsvColumn, CsvDialect, CsvQuoting, CsvStream, FindingsSummary, JsonlStream, ManifestInputs,
+        NotifyTarget, OutputFormat, RowStream, RunEvent, RunManifest, StageCounts,
     },
     processing::{
-        de_duplicate_subnets, find_overlapping_vnets, get_vnets, log_overlapping_vnets,
-        print_vnets, resolve_overlapping_vnets,
+        aks_subnet_pressure, count_hosts, de_duplicate_subnets_ignoring, delegated_subnet_usage,
+        filter_by_management_group, find_address_space_overlaps, find_duplicate_names,
+        find_overlapping_vnets, flow_log_gaps, format_aks_pressure, format_delegated_usage,
+        format_flow_log_gaps, format_gateways, format_group_summary, format_quota_usage,
+        gateway_summary, get_vnets, heatmap, log_address_space_overlaps, log_duplicate_names,
+        log_overlapping_vnets, mark_buffers, merge_sources, p2s_overlaps, p2s_subnets,
+        parse_buffer, parse_extra_range, parse_ignore_rule, parse_prefix_len, parse_terraform_plan,
+        quota_usage, quota_warnings, resolve_overlapping_vnets, summarize_groups, vnet_summary,
+        ConflictResolutionOutput, ExtraRange, FlowLogAnnotator, FlowLogCoverage, GroupBy,
+        IgnoreList, IgnoreRule, RowFilter, SubnetAgeAnnotator, SubnetPrintRow, TerraformPlan,
+        VnetPage, DEFAULT_HEATMAP_SLICE, DEFAULT_HEATMAP_SUPERNET,
     },
+    report::Report,
+    signing::{sign_files, SigningKey},
 };
 use clap::Parser;
 use std::collections::HashSet;
 use std::error::Error;
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt as _;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// CLI arguments for azure-subnet-summary.
 #[derive(Parser, Debug)]
@@ -39,6 +66,505 @@ pub struct Args {
     /// Example: --diagram md,svg   --diagram dot   --diagram svg
     #[arg(long, default_value = "md,svg", value_name = "TYPES")]
     pub diagram: String,
+
+    /// Restrict Azure Resource Graph queries to this management group ID
+    /// (`az graph query --management-groups <ID>`). Only applies when data is
+    /// fetched from Azure; existing cache files are used as-is.
+    #[arg(long, value_name = "ID")]
+    pub management_group: Option<String>,
+
+    /// Tenant ID the signed-in `az` account queries (defaults to
+    /// `$AZURE_TENANT_ID`, then the tenant `az account show` reports). Only
+    /// used to key cache files, so data from different tenants never shares
+    /// a cache.
+    #[arg(long, value_name = "ID")]
+    pub tenant: Option<String>,
+
+    /// On a subnet cache miss, start from the most recent previous snapshot
+    /// for the same query scope (`report-*/cache/net_*_cache_subnet_<key>.json`) and re-query only the
+    /// subscriptions with VNet/subnet changes since then.
+    #[arg(long)]
+    pub incremental: bool,
+
+    /// Run on a built-in synthetic dataset instead of Azure: no sign-in
+    /// needed, and the report shows a made-up hub/spoke tenant.
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Never run the Azure CLI: read only existing caches (today's, or
+    /// `--cache-file`) and fail when one is missing. For air-gapped analysis
+    /// of exported snapshots and deterministic CI runs.
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Write new cache files zstd-compressed (`net_*.json.zst`). Compressed
+    /// and plain cache files are always read transparently.
+    #[arg(long)]
+    pub compress_cache: bool,
+
+    /// Subnet cache file to read instead of today's cache; `-` reads a
+    /// snapshot (plain or zstd-compressed JSON) from stdin.
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<String>,
+
+    /// Directory holding the `report-<date>/` snapshot history read by
+    /// `--incremental` and the `first-seen` / `last-seen` columns.
+    #[arg(long, default_value = ".", value_name = "DIR")]
+    pub history_dir: String,
+
+    /// Resource Graph records per page (1-1000, default 1000). Halved
+    /// automatically when Azure rejects a response as too large.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=1000))]
+    pub page_size: Option<u32>,
+
+    /// Fail the subnet fetch when a Resource Graph row lacks a required field
+    /// (subscription, VNet or subnet name/ID, location) instead of warning.
+    /// All problems are listed together with their JSON paths.
+    #[arg(long)]
+    pub strict_schema: bool,
+
+    /// Fetch or load only the first N records of each Resource Graph query:
+    /// a quick smoke run to check sign-in, config and output formats before
+    /// a full fetch. Sampled fetches get their own cache files.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub sample: Option<u32>,
+
+    /// Only report subnets whose subscription sits under this management group.
+    /// Matches the management group ID or display name anywhere in the ancestor chain.
+    #[arg(long, value_name = "MG")]
+    pub only_management_group: Option<String>,
+
+    /// Print a VNet/subnet summary grouped by subscription, management group
+    /// or resource group.
+    #[arg(long, value_enum, value_name = "GROUP")]
+    pub group_by: Option<GroupBy>,
+
+    /// Terminal presentation of the subnet rows: `csv` (default) only writes
+    /// the CSV file; `table` also prints them as aligned tables, one per VNet;
+    /// `json` prints rows and findings on stdout; `jsonl` prints one JSON
+    /// object per row.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub output: Option<OutputFormat>,
+
+    /// Print the CSV (or `--output jsonl`) rows on stdout as each VNet is
+    /// finished, instead of only at the end; the summary follows the rows.
+    #[arg(long)]
+    pub stream: bool,
+
+    /// Also copy the report to the clipboard for pasting into tickets: the
+    /// tables with `--output table`, the JSON with `--output json`, otherwise
+    /// the subnet CSV file. Meant for small, filtered reports.
+    #[arg(long)]
+    pub copy: bool,
+
+    /// No colours in terminal output and logs, for CI logs and screen
+    /// readers; same as setting `NO_COLOR`.
+    #[arg(long)]
+    pub plain: bool,
+
+    /// Language of the CSV/table headers and the VNet summary.
+    #[arg(long, value_enum, default_value_t = Lang::En, value_name = "LANG")]
+    pub lang: Lang,
+
+    /// Also fail when any two subnets' address ranges intersect, whatever
+    /// their subscriptions (identical CIDRs are routing hazards once networks
+    /// are connected). The default check only rejects exact (CIDR,
+    /// subscription) duplicates.
+    #[arg(long)]
+    pub strict_duplicates: bool,
+
+    /// Comma-separated VNets that are deliberately isolated: `--strict-duplicates`
+    /// allows overlaps between two of them unless they are peered.
+    #[arg(long, value_delimiter = ',', value_name = "VNETS")]
+    pub isolated_vnets: Vec<String>,
+
+    /// Check Azure networking limits (VNets per subscription and region,
+    /// subnets per VNet, peerings per VNet) and report usage at or above
+    /// this percentage of the limit.
+    #[arg(long, value_name = "PCT", value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub quota_warn_pct: Option<u8>,
+
+    /// Leave subnets out of the report; repeat for several rules. Each rule
+    /// combines `subnet=`, `subscription=`, `vnet=` and `cidr=` and matches
+    /// when all of them match, e.g. `--ignore subnet=default,vnet=lab-vnet`.
+    /// Nothing is ignored by default; active rules are listed at startup.
+    #[arg(long, value_name = "RULE", value_parser = parse_ignore_rule)]
+    pub ignore: Vec<IgnoreRule>,
+
+    /// Merge a non-Azure range into the report; repeat for several, e.g.
+    /// `--range source=on-prem,cidr=192.168.0.0/16,name=dc-1`. Sources are
+    /// `reserved`, `on-prem` and `planned`.
+    #[arg(long, value_name = "RANGE", value_parser = parse_extra_range)]
+    pub range: Vec<ExtraRange>,
+
+    /// CIDR kept free on purpose (growth buffer): its gaps show as `-buffer-`
+    /// / `-vbuffer-` and are not counted as free space. Repeatable.
+    #[arg(long, value_name = "CIDR", value_parser = parse_buffer)]
+    pub buffer: Vec<Ipv4>,
+
+    /// Merge the subnets created by a Terraform plan (`terraform show -json
+    /// <planfile>` output) into the report.
+    #[arg(long, value_name = "FILE")]
+    pub terraform_plan: Option<String>,
+
+    /// Addresses reserved per subnet when counting usable hosts (default
+    /// azure = 5; classic = 2 for ranges mirrored from other platforms, none = 0).
+    #[arg(long, value_enum, value_name = "MODEL")]
+    pub host_reservation: Option<ReservationModel>,
+
+    /// Only write/print subnet and gap rows with at least this many free Azure
+    /// hosts (CSV and table).
+    #[arg(long, value_name = "N")]
+    pub min_free: Option<u64>,
+
+    /// Only write/print gap rows larger than this size, e.g. `/25` keeps /24
+    /// and larger gaps (CSV and table).
+    #[arg(long, value_name = "/N", value_parser = parse_prefix_len)]
+    pub only_gaps_larger_than: Option<u8>,
+
+    /// Only show this VNet (case-insensitive) in the terminal VNet summary
+    /// and the `--output table` rows.
+    #[arg(long, value_name = "NAME")]
+    pub only_vnet: Option<String>,
+
+    /// Skip this many VNets of the terminal VNet summary and the
+    /// `--output table` rows (for paging).
+    #[arg(long, default_value_t = 0, value_name = "N")]
+    pub offset: usize,
+
+    /// Show at most this many VNets in the terminal VNet summary and the
+    /// `--output table` rows.
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Comma-separated optional columns to append to the subnet CSV.
+    /// Example: --columns resource-group
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Vec<CsvColumn>,
+
+    /// Field separator of the subnet CSV: one character, or `tab`. Excel
+    /// with most European regional settings expects `;`.
+    #[arg(long, default_value = ",", value_parser = CsvDialect::parse_delimiter, value_name = "CHAR")]
+    pub csv_delimiter: char,
+
+    /// Quoting of the subnet CSV: `aligned` (default) quotes and pads every
+    /// field into columns; `all` quotes without padding; `minimal` quotes
+    /// only fields that need it.
+    #[arg(long, value_enum, default_value_t = CsvQuoting::Aligned, value_name = "STYLE")]
+    pub csv_quoting: CsvQuoting,
+
+    /// Start the subnet CSV with a UTF-8 byte order mark, so Excel shows
+    /// non-ASCII names correctly.
+    #[arg(long)]
+    pub csv_bom: bool,
+
+    /// Also render this Handlebars template with the subnet rows and findings
+    /// into `net_<date>_<name>` (a trailing `.hbs` is dropped). Repeatable.
+    #[arg(long, value_name = "FILE")]
+    pub template: Vec<String>,
+
+    /// Also write `net_<date>_heatmap.csv` and `net_<date>_heatmap.html`: per
+    /// slice of the supernet, the subscriptions / VNets holding it and its
+    /// free space.
+    #[arg(long)]
+    pub heatmap: bool,
+
+    /// Also write one YAML file per VNet (address space, subnets, NSG, ...)
+    /// under `DIR/<subscription>/<resource group>/<vnet>.yaml`, to commit to
+    /// git so diffs and pull requests show estate changes. Files of VNets
+    /// that are gone are removed.
+    #[arg(long, value_name = "DIR")]
+    pub gitops_dir: Option<String>,
+
+    /// Sign every report file this run writes, writing `<file>.minisig`
+    /// next to it, so consumers can check them with `verify`. Other files
+    /// in the report directory are not signed.
+    #[arg(long)]
+    pub sign: bool,
+
+    /// Unencrypted minisign secret key for `--sign` (`minisign -G -W`).
+    #[arg(long, value_name = "FILE")]
+    pub signing_key: Option<String>,
+
+    /// Supernet sliced by `--heatmap` (default 10.0.0.0/8).
+    #[arg(long, value_name = "CIDR", value_parser = parse_cidr)]
+    pub heatmap_supernet: Option<Ipv4>,
+
+    /// Slice size of `--heatmap`, e.g. `/20` (default /16).
+    #[arg(long, value_name = "/N", value_parser = parse_prefix_len)]
+    pub heatmap_slice: Option<u8>,
+
+    /// Also write `run_manifest.json` with the run's inputs (query scope,
+    /// filters, config file hash, cache file), record counts per pipeline
+    /// stage and a summary of the findings.
+    #[arg(long)]
+    pub manifest: bool,
+
+    /// When the run completes, send an event with the stage counts, findings
+    /// and report location to this Event Grid topic endpoint or storage queue
+    /// URL. Repeatable.
+    #[arg(long, value_name = "URL")]
+    pub notify: Vec<NotifyTarget>,
+
+    /// Where the report directory is published (e.g. a blob container URL),
+    /// included in `--notify` events.
+    #[arg(long, value_name = "URL")]
+    pub report_url: Option<String>,
+
+    /// Also write `net_<date>_remediation.sh` with `az network vnet subnet update`
+    /// commands for findings (missing NSG, resize). Review before running.
+    #[arg(long)]
+    pub remediation_script: bool,
+
+    /// Also query AKS agent pools and report IP pressure on their subnets
+    /// (Azure CNI node + pod IPs at autoscaler maximum plus upgrade surge).
+    #[arg(long)]
+    pub aks: bool,
+
+    /// Also query Network Watcher flow logs and audit subnets whose NSG / VNet
+    /// has no flow logs or no Traffic Analytics (implied by `--columns flow-logs`).
+    #[arg(long)]
+    pub flow_logs: bool,
+
+    /// Also query VPN / ExpressRoute gateways: report their SKU, GatewaySubnet
+    /// and point-to-site client pools, and include the P2S pools in the
+    /// overlap checks.
+    #[arg(long)]
+    pub gateways: bool,
+
+    /// IANA timezone for the date in cache/report file names and for
+    /// generation timestamps, e.g. `Pacific/Auckland` (default UTC).
+    #[arg(long, value_name = "TZ")]
+    pub timezone: Option<String>,
+
+    /// `strftime` format for the date in cache/report file names
+    /// (default `%Y-%m-%d`). Must include year, month and day.
+    #[arg(long, value_name = "FORMAT")]
+    pub date_format: Option<String>,
+
+    /// Config file (TOML). Defaults to `azure-subnet-summary.toml` when present.
+    /// CLI flags override its settings.
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<String>,
+
+    /// Config file profile (`[profile.<name>]`) whose settings apply over the
+    /// top-level ones, e.g. `--profile prod`.
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Run a subcommand instead of the subnet report.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Args {
+    /// Print human-readable output: to stdout, or to stderr when stdout
+    /// carries the `--output json` document.
+    fn say(&self, text: &str) {
+        if self.output.unwrap_or_default().is_json() {
+            eprintln!("{text}");
+        } else {
+            println!("{text}");
+        }
+    }
+
+    /// The query scope in words, e.g. `management group 'mg-corp'`.
+    pub fn scope(&self) -> String {
+        let mut scope = match (&self.management_group, &self.tenant) {
+            (Some(mg), _) => format!("management group '{mg}'"),
+            (None, Some(tenant)) => format!("tenant '{tenant}'"),
+            (None, None) => "all subscriptions of the signed-in account".to_string(),
+        };
+        if let Some(ref mg) = self.only_management_group {
+            scope.push_str(&format!(" under management group '{mg}'"));
+        }
+        scope
+    }
+
+    /// Fill settings not given on the command line from the config file.
+    ///
+    /// Fails on config values the matching flag would reject.
+    pub fn apply_config(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        if self.management_group.is_none() {
+            self.management_group = config.management_group.clone();
+        }
+        if self.tenant.is_none() {
+            self.tenant = config.tenant.clone();
+        }
+        if self.only_management_group.is_none() {
+            self.only_management_group = config.only_management_group.clone();
+        }
+        self.compress_cache |= config.compress_cache;
+        if self.page_size.is_none() {
+            if let Some(n) = config.page_size.filter(|n| !(1..=1000).contains(n)) {
+                return Err(
+                    format!("page_size = {n} in the config file is not in 1..=1000").into(),
+                );
+            }
+            self.page_size = config.page_size;
+        }
+        self.strict_schema |= config.strict_schema;
+        self.strict_duplicates |= config.strict_duplicates;
+        if self.isolated_vnets.is_empty() {
+            self.isolated_vnets = config.isolated_vnets.clone();
+        }
+        if self.ignore.is_empty() {
+            self.ignore = config.ignore.clone();
+        }
+        if self.range.is_empty() {
+            self.range = config.range.clone();
+        }
+        if self.terraform_plan.is_none() {
+            self.terraform_plan = config.terraform_plan.clone();
+        }
+        if self.buffer.is_empty() {
+            self.buffer = config.buffer.clone();
+        }
+        if self.template.is_empty() {
+            self.template = config.template.clone();
+        }
+        self.heatmap |= config.heatmap;
+        if self.gitops_dir.is_none() {
+            self.gitops_dir = config.gitops_dir.clone();
+        }
+        self.sign |= config.sign;
+        if self.signing_key.is_none() {
+            self.signing_key = config.signing_key.clone();
+        }
+        self.manifest |= config.manifest;
+        if self.notify.is_empty() {
+            self.notify = config.notify.clone();
+        }
+        if self.report_url.is_none() {
+            self.report_url = config.report_url.clone();
+        }
+        if self.heatmap_supernet.is_none() {
+            self.heatmap_supernet = config.heatmap_supernet;
+        }
+        if self.heatmap_slice.is_none() {
+            self.heatmap_slice = config.heatmap_slice;
+        }
+        if self.host_reservation.is_none() {
+            self.host_reservation = config.host_reservation;
+        }
+        if self.output.is_none() {
+            self.output = config.output;
+        }
+        if self.quota_warn_pct.is_none() {
+            self.quota_warn_pct = config.quota_warn_pct;
+        }
+        if self.timezone.is_none() {
+            self.timezone = config.timezone.clone();
+        }
+        if self.date_format.is_none() {
+            self.date_format = config.date_format.clone();
+        }
+        Ok(())
+    }
+
+    /// VNets of the terminal summary and table selected by `--only-vnet`,
+    /// `--offset` and `--limit`. The CSV and other files always hold every
+    /// VNet.
+    pub fn vnet_page(&self) -> VnetPage {
+        VnetPage {
+            only_vnet: self.only_vnet.clone(),
+            offset: self.offset,
+            limit: self.limit,
+        }
+    }
+
+    /// Header language, delimiter, quoting and BOM of the subnet CSV.
+    pub fn csv_dialect(&self) -> CsvDialect {
+        CsvDialect {
+            lang: self.lang,
+            delimiter: self.csv_delimiter,
+            quoting: self.csv_quoting,
+            bom: self.csv_bom,
+        }
+    }
+
+    /// Row thresholds from `--min-free` and `--only-gaps-larger-than`.
+    pub fn row_filter(&self) -> RowFilter {
+        RowFilter {
+            min_free: self.min_free,
+            gaps_larger_than: self.only_gaps_larger_than,
+        }
+    }
+
+    /// Clock for the configured `--timezone` and `--date-format`.
+    pub fn clock(&self) -> Result<Clock, Box<dyn Error>> {
+        Clock::new(self.timezone.as_deref(), self.date_format.as_deref())
+    }
+
+    /// Resource Graph query scope of the run, which also keys the cache
+    /// files and the snapshot history.
+    pub fn query_options(&self) -> QueryOptions {
+        QueryOptions {
+            tenant: self
+                .tenant
+                .clone()
+                .or_else(|| std::env::var("AZURE_TENANT_ID").ok()),
+            management_group: self.management_group.clone(),
+            page_size: self.page_size.unwrap_or(DEFAULT_PAGE_SIZE),
+            strict_schema: self.strict_schema,
+            sample: self.sample,
+            ..QueryOptions::default()
+        }
+    }
+
+    /// Take the tenant of the signed-in `az` account when neither `--tenant`
+    /// nor `$AZURE_TENANT_ID` names one, so cache files of different tenants
+    /// never mix after an `az login` to another tenant. Left unset for
+    /// `--demo`, `--offline` and commands that read no Azure data, or when
+    /// `az account show` fails.
+    pub fn resolve_tenant(&mut self) {
+        let local_only = matches!(
+            self.command,
+            Some(Command::Cidr(_) | Command::Doctor | Command::Schema(_) | Command::Split(_))
+        );
+        if self.tenant.is_some()
+            || std::env::var("AZURE_TENANT_ID").is_ok()
+            || self.demo
+            || self.offline
+            || local_only
+        {
+            return;
+        }
+        match AccountContext::current() {
+            Ok(account) => {
+                log::info!(
+                    "Keying cache files by signed-in tenant {}",
+                    account.tenant_id
+                );
+                self.tenant = Some(account.tenant_id);
+            }
+            Err(e) => log::info!("No signed-in tenant to key cache files by: {e}"),
+        }
+    }
+
+    /// Error when `--offline` is combined with something that always calls
+    /// Azure, so the run fails before doing any work.
+    pub fn check_offline(&self) -> Result<(), String> {
+        if !self.offline {
+            return Ok(());
+        }
+        let online_only = match self.command {
+            Some(Command::LogAnalytics(_)) => Some("log-analytics"),
+            Some(Command::Publish(_)) => Some("publish"),
+            Some(Command::Serve(_)) => Some("serve"),
+            _ => None,
+        };
+        if let Some(command) = online_only {
+            return Err(format!("--offline: `{command}` needs Azure; run it online"));
+        }
+        if !self.notify.is_empty() {
+            return Err(
+                "--offline: --notify sends events as the az identity; drop it or run online"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
 }
 
 /// Injectable SVG rendering seam.
@@ -49,6 +575,24 @@ pub trait SvgRenderer {
     fn render(&self, dot_file: &str, svg_file: &str);
 }
 
+/// Split `rows` into runs of consecutive rows of the same VNet (gap rows
+/// between VNets form their own runs), the unit `--stream` prints.
+fn vnet_batches(rows: Vec<SubnetPrintRow>) -> Vec<Vec<SubnetPrintRow>> {
+    let mut batches: Vec<Vec<SubnetPrintRow>> = Vec::new();
+    for row in rows {
+        match batches.last_mut() {
+            Some(batch)
+                if batch[0].subscription_id == row.subscription_id
+                    && batch[0].vnet_name == row.vnet_name =>
+            {
+                batch.push(row)
+            }
+            _ => batches.push(vec![row]),
+        }
+    }
+    batches
+}
+
 /// Production renderer: tries local `dot` engines, falls back to Docker.
 pub struct GraphvizRenderer;
 
@@ -66,59 +610,233 @@ fn parse_diagram_types(raw: &str) -> HashSet<String> {
         .collect()
 }
 
+/// Drop VNets that lose overlap resolution (production subscription wins),
+/// then drop subnets matched by `ignore`, de-duplicate and check the rest.
+///
+/// `active` holds the subnets every output is built from; shared by [`run`]
+/// and [`PipelineSnapshot`](crate::snapshot::PipelineSnapshot).
+pub fn resolve_subnets(
+    subnets: Data,
+    ignore: &IgnoreList,
+) -> Result<ConflictResolutionOutput, Box<dyn Error>> {
+    let cr_out = resolve_overlapping_vnets(subnets);
+    for e in &cr_out.excluded {
+        log::warn!(
+            "Excluding VNet '{}' — overlaps with kept VNet '{}'",
+            e.subnet.vnet_name,
+            e.winner_vnet_name,
+        );
+    }
+    let active = de_duplicate_subnets_ignoring(cr_out.active, ignore)?;
+    check_for_duplicate_subnets(&active)?;
+    Ok(ConflictResolutionOutput {
+        active,
+        excluded: cr_out.excluded,
+    })
+}
+
 /// Execute the full output pipeline for the fetched Azure data.
 ///
 /// Writes all output files into a `report-<date>` subdirectory (created if it
 /// does not exist) and calls `renderer` only when the `svg` diagram type is
 /// requested.
 pub fn run(data: AzureData, args: &Args, renderer: &dyn SvgRenderer) -> Result<(), Box<dyn Error>> {
+    run_with_report(data, args, renderer, &Report::default())
+}
+
+/// [`run`] with the row processors registered on `report`
+/// ([`Report::add_processor`]) applied to the subnet rows.
+pub fn run_with_report(
+    data: AzureData,
+    args: &Args,
+    renderer: &dyn SvgRenderer,
+    report: &Report,
+) -> Result<(), Box<dyn Error>> {
     let diagram_types = parse_diagram_types(&args.diagram);
 
+    let cache_file = data.subnets.cache_file.clone();
+    let from_cache = data.subnets.from_cache;
+    let generated_at = data.subnets.generated_at.clone();
     let cache_source = if data.subnets.from_cache {
         format!("existing cache '{}'", data.subnets.cache_file)
     } else {
         format!("Azure (new cache written to '{}')", data.subnets.cache_file)
     };
+    let mut counts = StageCounts {
+        fetched: data.subnets.data.data.len(),
+        ..Default::default()
+    };
+    // Prefixes were normalized by `fetch_subnets`.
     let mut subnets = data.subnets.data;
     subnets.data.sort_by_key(|s| s.subnet_cidr);
+    let subscriptions = data.subscriptions;
+    if let Some(ref mg) = args.only_management_group {
+        subnets = filter_by_management_group(subnets, &subscriptions, mg);
+    }
+    if !args.range.is_empty() || args.terraform_plan.is_some() {
+        let plan = match &args.terraform_plan {
+            Some(file) => parse_terraform_plan(
+                &std::fs::read_to_string(file)
+                    .map_err(|e| format!("Error reading Terraform plan {file}: {e}"))?,
+            )?,
+            None => TerraformPlan::default(),
+        };
+        subnets = merge_sources(subnets, &args.range, &plan)?;
+        subnets.data.sort_by_key(|s| s.subnet_cidr);
+    }
 
     let peering_data = data.peering_edges;
     let local_gw_data = data.local_gateways;
     let vwan_data = data.vwan;
+    let aks_data = data.aks;
+    let flow_log_data = data.flow_logs;
+
+    // Gateway report; P2S client pools join the overlap checks as stand-in VNets.
+    let gateway_report = data.gateways.map(|gateways| {
+        let summary = gateway_summary(&gateways, &subnets);
+        let overlaps = p2s_overlaps(&gateways, &subnets);
+        subnets.data.extend(p2s_subnets(&gateways));
+        subnets.data.sort_by_key(|s| s.subnet_cidr);
+        subnets.count = subnets.data.len() as i32;
+        subnets.total_records = Some(subnets.data.len() as u32);
+        (summary, overlaps)
+    });
+
+    // Warn about subnet names repeated within a VNet and VNet names reused across subscriptions
+    log_duplicate_names(&find_duplicate_names(&subnets));
 
     // Check for and log overlapping VNet CIDRs
     let conflicts = find_overlapping_vnets(&subnets);
     log_overlapping_vnets(&conflicts);
-
-    // Filter overlapping VNets (production subscription wins)
-    let cr_out = resolve_overlapping_vnets(subnets);
-    for e in &cr_out.excluded {
-        log::warn!(
-            "Excluding VNet '{}' — overlaps with kept VNet '{}'",
-            e.subnet.vnet_name,
-            e.winner_vnet_name,
-        );
+    log_address_space_overlaps(&find_address_space_overlaps(&subnets));
+    if args.strict_duplicates {
+        check_for_overlapping_subnets(&subnets, &peering_data.data, &args.isolated_vnets)?;
     }
-    let subnets = cr_out.active;
+    // Networking limits count every VNet in Azure, including ones excluded below.
+    let quota = args
+        .quota_warn_pct
+        .map(|pct| quota_warnings(quota_usage(&subnets, &peering_data.data), pct));
 
-    let subnets = de_duplicate_subnets(subnets, None)?;
-    check_for_duplicate_subnets(&subnets)?;
+    let ignore = IgnoreList::from_rules(&args.ignore);
+    ignore.log_active();
+    counts.merged = subnets.data.len();
+    counts.ignored = subnets.data.iter().filter(|s| ignore.ignores(s)).count();
+    let cr_out = resolve_subnets(subnets, &ignore)?;
+    let subnets = cr_out.active;
+    counts.excluded_overlapping = cr_out.excluded.len();
+    counts.active = subnets.data.len();
 
     // Create the dated report subdirectory
-    let date_str = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let clock = args.clock()?;
+    let date_str = clock.date_str();
     let report_dir = format!("report-{date_str}");
     std::fs::create_dir_all(&report_dir)?;
     let report_path = Path::new(&report_dir);
     log::info!("Writing output to '{report_dir}/'");
+    // Loaded up front so a bad key fails before the work is done.
+    let signing_key = match (args.sign, &args.signing_key) {
+        (false, _) => None,
+        (true, Some(file)) => Some(SigningKey::load(Path::new(file))?),
+        (true, None) => {
+            return Err("--sign needs --signing-key FILE or signing_key in the config file".into())
+        }
+    };
+
+    // Subnet + gap rows, shared by the CSV and the table output. Row
+    // processors see all rows at once; with `--stream` every VNet's rows are
+    // then filtered and printed before the next.
+    let built = report.apply_processors(count_hosts(
+        mark_buffers(
+            build_rows(
+                &subnets.data,
+                &cr_out.excluded,
+                args.gap_mask,
+                &vwan_data.data,
+            ),
+            &args.buffer,
+        ),
+        args.host_reservation.unwrap_or_default(),
+    ))?;
+    counts.rows_built = built.len();
+    let extra = extra_columns(&built);
+    let batches = if args.stream {
+        vnet_batches(built)
+    } else {
+        vec![built]
+    };
+    let row_filter = args.row_filter();
+    let flow_logs = flow_log_data
+        .as_ref()
+        .map(|flow_logs| FlowLogAnnotator::new(&subnets, flow_logs));
+    let ages = args
+        .columns
+        .iter()
+        .any(|c| matches!(c, CsvColumn::FirstSeen | CsvColumn::LastSeen))
+        .then(|| {
+            // The data is dated by its fetch time, so an older snapshot
+            // passed with --cache-file is not taken as seen today.
+            let dated = generated_at
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map_or_else(|| clock.today(), |t| t.date_naive());
+            let snapshots =
+                list_snapshots(Path::new(&args.history_dir), &args.query_options(), &clock);
+            log::info!(
+                "Dating subnets from {} snapshot(s) in '{}/report-*/cache/'",
+                snapshots.len(),
+                args.history_dir
+            );
+            SubnetAgeAnnotator::new(&subnets, dated, read_snapshots_newest_first(&snapshots))
+        });
+    let mut stream: Option<Box<dyn RowStream>> = match args.output.unwrap_or_default() {
+        OutputFormat::Jsonl => Some(Box::new(JsonlStream::new(std::io::stdout()))),
+        OutputFormat::Csv if args.stream => Some(Box::new(CsvStream::new(
+            std::io::stdout(),
+            &args.columns,
+            extra,
+            clock.timestamp(),
+            args.csv_dialect(),
+        ))),
+        _ => None,
+    };
+    let mut rows = Vec::new();
+    for batch in batches {
+        let mut batch = row_filter.apply(batch);
+        if let Some(ref flow_logs) = flow_logs {
+            flow_logs.annotate(&mut batch);
+        }
+        if let Some(ref ages) = ages {
+            ages.annotate(&mut batch);
+        }
+        if let Some(ref mut stream) = stream {
+            stream.write_rows(&batch)?;
+        }
+        rows.extend(batch);
+    }
+    if let Some(ref mut stream) = stream {
+        log::info!("Wrote {} rows to stdout", stream.finish()?);
+    }
+    counts.rows_written = rows.len();
+    counts.gaps = rows
+        .iter()
+        .filter(|r| r.gap == "-gap-" || r.gap == "-vgap-")
+        .count();
+
+    // Report files this run writes, for --sign.
+    let mut written: Vec<PathBuf> = Vec::new();
 
     // Output subnet CSV + duplicates.md (both written by subnet_print)
     let csv_file = subnet_print(
         &subnets,
         &cr_out.excluded,
-        args.gap_mask,
-        &vwan_data.data,
+        &rows,
+        &args.columns,
         report_path,
+        &clock,
+        args.csv_dialect(),
     )?;
+    written.push(csv_file.clone().into());
+    written.push(report_path.join(format!("net_{date_str}_duplicates.md")));
 
     // Build peering topology once; pass to both diagram writers.
     let topo = build_topology(
@@ -127,14 +845,17 @@ pub fn run(data: AzureData, args: &Args, renderer: &dyn SvgRenderer) -> Result<(
         &local_gw_data.data,
         &vwan_data.data,
     );
+    // Overlaps that matter: VNets that can route to each other.
+    let peered_overlaps = find_peering_overlaps(&topo);
 
     if diagram_types.contains("md") {
         let peering_file = report_path
             .join(format!("net_{date_str}_peering.md"))
             .to_string_lossy()
             .into_owned();
-        write_peering_diagram(&topo, &peering_file)?;
+        write_peering_diagram(&topo, &peering_file, &clock.timestamp())?;
         log::info!("Peering diagram written to '{peering_file}'");
+        written.push(peering_file.into());
     }
 
     // Generate DOT file (needed for both dot and svg outputs).
@@ -143,16 +864,236 @@ pub fn run(data: AzureData, args: &Args, renderer: &dyn SvgRenderer) -> Result<(
             .join(format!("net_{date_str}_peering.dot"))
             .to_string_lossy()
             .into_owned();
-        write_peering_dot(&topo, &f)?;
+        write_peering_dot(&topo, &f, &clock.timestamp())?;
         log::info!("Peering DOT diagram written to '{f}'");
+        written.push(f.clone().into());
         Some(f)
     } else {
         None
     };
 
+    for template in &args.template {
+        let file = write_template(
+            template,
+            &Report::from_subnets(&subnets),
+            &rows,
+            report_path,
+            &date_str,
+        )?;
+        log::info!("Template '{template}' rendered to '{file}'");
+        written.push(file.into());
+    }
+
+    if args.heatmap {
+        let supernet = match args.heatmap_supernet {
+            Some(cidr) => cidr,
+            None => Ipv4::new(DEFAULT_HEATMAP_SUPERNET)?,
+        };
+        let slices = heatmap(
+            &subnets,
+            supernet,
+            args.heatmap_slice.unwrap_or(DEFAULT_HEATMAP_SLICE),
+        )?;
+        let (csv, html) = write_heatmap(
+            &slices,
+            supernet,
+            report_path,
+            &date_str,
+            &clock.timestamp(),
+        )?;
+        log::info!("Address space heatmap written to '{csv}' and '{html}'");
+        written.extend([csv.into(), html.into()]);
+    }
+
+    if let Some(ref dir) = args.gitops_dir {
+        let summary = write_gitops_tree(&subnets, Path::new(dir))?;
+        log::info!(
+            "GitOps tree '{dir}': {} VNet file(s) written, {} unchanged, {} removed",
+            summary.written,
+            summary.unchanged,
+            summary.removed
+        );
+    }
+
+    if args.remediation_script {
+        let report = Report::from_subnets(&subnets);
+        let script_file = report_path
+            .join(format!("net_{date_str}_remediation.sh"))
+            .to_string_lossy()
+            .into_owned();
+        write_remediation_script(&report, &script_file)?;
+        log::info!(
+            "Remediation script ({} finding(s)) written to '{script_file}' — review before running",
+            report.findings.len()
+        );
+        written.push(script_file.into());
+    }
+
+    let printed = match args.output.unwrap_or_default() {
+        // Written above, as the rows were finished.
+        OutputFormat::Csv | OutputFormat::Jsonl => None,
+        OutputFormat::Table => Some(format_table(
+            &args.vnet_page().rows(&rows),
+            &args.columns,
+            args.lang,
+        )),
+        OutputFormat::Json => Some(render_report_json(&Report::from_subnets(&subnets), &rows)?),
+    };
+    if let Some(ref text) = printed {
+        println!("{text}");
+    }
+    if args.copy {
+        // The report is written either way; a missing clipboard is no
+        // reason to fail the run.
+        let copied = match printed {
+            Some(text) => copy_to_clipboard(&text),
+            None => std::fs::read_to_string(&csv_file)
+                .map_err(|e| format!("Error reading {csv_file}: {e}").into())
+                .and_then(|text| copy_to_clipboard(&text)),
+        };
+        if let Err(e) = copied {
+            log::warn!("--copy: {e}");
+        }
+    }
+
     // Output VNet summary
     let vnets = get_vnets(&subnets)?;
-    print_vnets(&vnets, &cr_out.excluded)?;
+    counts.vnets = vnets.vnets.len();
+    if vnets.vnets.is_empty() && cr_out.excluded.is_empty() {
+        log::warn!("No VNets found in {}", args.scope());
+        args.say(&args.lang.fill("0 VNets found in {}", &[&args.scope()]));
+    } else {
+        args.say(&vnet_summary(
+            &vnets,
+            &cr_out.excluded,
+            &args.vnet_page(),
+            args.lang,
+        ));
+    }
+    if let Some(group_by) = args.group_by {
+        let groups = summarize_groups(&subnets, &subscriptions, group_by);
+        args.say(&format_group_summary(&groups, group_by));
+    }
+    let delegated = delegated_subnet_usage(&subnets);
+    for u in delegated.iter().filter(|u| !u.burst_fits()) {
+        log::warn!(
+            "{} subnet '{}/{}' cannot absorb a burst: {} of {} IPs in use",
+            u.kind,
+            u.vnet_name,
+            u.subnet_name,
+            u.in_use,
+            u.capacity
+        );
+    }
+    if !delegated.is_empty() {
+        args.say(&format_delegated_usage(&delegated));
+    }
+    if let Some(ref aks) = aks_data {
+        let pressure = aks_subnet_pressure(&subnets, aks);
+        for p in pressure.iter().filter(|p| !p.fits()) {
+            log::warn!(
+                "AKS subnet '{}/{}' cannot fit a node surge: needs {} of {} IPs ({})",
+                p.vnet_name,
+                p.subnet_name,
+                p.required(),
+                p.capacity,
+                p.pools.join(", ")
+            );
+        }
+        args.say(&format_aks_pressure(&pressure));
+    }
+    if let Some((ref summary, ref overlaps)) = gateway_report {
+        if !overlaps.is_empty() {
+            log::warn!(
+                "{} point-to-site client pool(s) overlap VNet address space",
+                overlaps.len()
+            );
+        }
+        args.say(&format_gateways(summary, overlaps));
+    }
+    if let Some(ref flow_logs) = flow_log_data {
+        let gaps = flow_log_gaps(&subnets, flow_logs);
+        let unlogged = gaps
+            .iter()
+            .filter(|g| g.coverage == FlowLogCoverage::None)
+            .count();
+        if unlogged > 0 {
+            log::warn!("{unlogged} subnet(s) have no flow logs on their NSG, VNet or subnet");
+        }
+        args.say(&format_flow_log_gaps(&gaps));
+    }
+    if !peered_overlaps.is_empty() {
+        log::warn!(
+            "{} pair(s) of connected VNets have overlapping address space",
+            peered_overlaps.len()
+        );
+        args.say(&format_peering_overlaps(&peered_overlaps));
+    }
+    if let Some(ref quota) = quota {
+        for u in quota {
+            log::warn!(
+                "'{}' uses {}% of the Azure limit of {} {}",
+                u.scope,
+                u.pct(),
+                u.limit,
+                u.kind
+            );
+        }
+        args.say(&format_quota_usage(quota));
+    }
+    let graph_quota = graph_quota_status();
+    if graph_quota.queries > 0 {
+        args.say(&graph_quota.to_string());
+    }
+
+    // Only the run event and the manifest carry the findings.
+    let findings = if !args.notify.is_empty() || args.manifest {
+        FindingsSummary::from_report(&Report::from_subnets(&subnets))
+    } else {
+        FindingsSummary::default()
+    };
+    let event = (!args.notify.is_empty()).then(|| RunEvent {
+        generated_at: clock.timestamp(),
+        scope: args.scope(),
+        report_dir: report_path.to_string_lossy().into_owned(),
+        report_url: args.report_url.clone(),
+        counts: counts.clone(),
+        findings: findings.clone(),
+    });
+
+    if args.manifest {
+        let manifest = RunManifest {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: clock.timestamp(),
+            inputs: ManifestInputs {
+                cache_file: cache_file.clone(),
+                from_cache,
+                demo: args.demo,
+                tenant: args.tenant.clone(),
+                management_group: args.management_group.clone(),
+                only_management_group: args.only_management_group.clone(),
+                sample: args.sample,
+                config: manifest_config(args.config.as_deref().unwrap_or(DEFAULT_CONFIG_FILE))?,
+                profile: args.profile.clone(),
+                ignore: ignore.rules.iter().map(|r| r.to_string()).collect(),
+                ranges: args
+                    .range
+                    .iter()
+                    .map(|r| format!("{} {} {}", r.source, r.cidr, r.name))
+                    .collect(),
+                terraform_plan: args.terraform_plan.clone(),
+                buffers: args.buffer.iter().map(|b| b.to_string()).collect(),
+                gap_mask: args.gap_mask,
+                min_free: args.min_free,
+                only_gaps_larger_than: args.only_gaps_larger_than,
+            },
+            counts,
+            findings,
+        };
+        let file = write_manifest(&manifest, report_path)?;
+        log::info!("Run manifest written to '{file}'");
+        written.push(file.into());
+    }
 
     log::info!("Complete: Generated '{}' from {}", csv_file, cache_source);
 
@@ -163,7 +1104,41 @@ pub fn run(data: AzureData, args: &Args, renderer: &dyn SvgRenderer) -> Result<(
                 .join(format!("net_{date_str}_peering.svg"))
                 .to_string_lossy()
                 .into_owned();
+            // Rendering is best effort; sign the SVG only if this run made it.
+            std::fs::remove_file(&peering_svg_file).ok();
             renderer.render(dot_file, &peering_svg_file);
+            if Path::new(&peering_svg_file).is_file() {
+                written.push(peering_svg_file.into());
+            }
+        }
+    }
+
+    if let Some(ref key) = signing_key {
+        let signed = sign_files(key, &written, &clock)?;
+        log::info!(
+            "Signed {signed} report file(s) in '{report_dir}/' with key {}",
+            key.public_key().key_id()
+        );
+    }
+
+    if let Some(event) = event {
+        // Every target is tried; one being down does not silence the others.
+        let mut failed = 0;
+        for target in &args.notify {
+            match send_run_event(target, &event) {
+                Ok(()) => log::info!("Run completion sent to {target}"),
+                Err(e) => {
+                    log::error!("Run completion not sent to {target}: {e}");
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(format!(
+                "Run completion not sent to {failed} of {} notify target(s)",
+                args.notify.len()
+            )
+            .into());
         }
     }
 
@@ -307,7 +1282,9 @@ fn render_svg(dot_file: &str, svg_file: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::azure::{fetch_azure_data, FetchConfig};
+    use crate::azure::{fetch_azure_data, CacheResult, Data, FetchConfig};
+    use crate::models::Subnet;
+    use crate::report::RowProcessor;
     use std::cell::RefCell;
 
     struct SpyRenderer {
@@ -339,6 +1316,9 @@ mod tests {
                 "src/tests/test_data/local_gateway_test_cache_01.json".to_string(),
             ),
             vwan_cache: Some("src/tests/test_data/vwan_test_cache_01.json".to_string()),
+            subscription_cache: Some(
+                "src/tests/test_data/subscription_test_cache_01.json".to_string(),
+            ),
             ..FetchConfig::default()
         };
         fetch_azure_data(&config).expect("test fixture fetch failed")
@@ -346,10 +1326,7 @@ mod tests {
 
     #[test]
     fn renderer_called_when_svg_in_diagram_types() {
-        let args = Args {
-            gap_mask: 4,
-            diagram: "svg".to_string(),
-        };
+        let args = Args::parse_from(["azure-subnet-summary", "--diagram", "svg"]);
         let renderer = SpyRenderer::new();
         run(test_azure_data(), &args, &renderer).expect("pipeline run failed");
         assert!(
@@ -360,10 +1337,7 @@ mod tests {
 
     #[test]
     fn renderer_not_called_when_svg_not_in_diagram_types() {
-        let args = Args {
-            gap_mask: 4,
-            diagram: "md".to_string(),
-        };
+        let args = Args::parse_from(["azure-subnet-summary", "--diagram", "md"]);
         let renderer = SpyRenderer::new();
         run(test_azure_data(), &args, &renderer).expect("pipeline run failed");
         assert!(
@@ -371,4 +1345,276 @@ mod tests {
             "renderer should NOT have been called without svg"
         );
     }
+
+    #[test]
+    fn apply_config_fills_unset_flags_but_cli_wins() {
+        let config = Config {
+            tenant: Some("tenant-from-config".to_string()),
+            management_group: Some("mg-from-config".to_string()),
+            only_management_group: Some("mg-only".to_string()),
+            compress_cache: true,
+            page_size: Some(200),
+            strict_schema: true,
+            strict_duplicates: true,
+            isolated_vnets: vec!["lab-a".to_string()],
+            quota_warn_pct: Some(80),
+            ignore: vec![IgnoreRule::subnet_name("scratch")],
+            timezone: Some("Pacific/Auckland".to_string()),
+            date_format: None,
+            policy: Vec::new(),
+            plan: Vec::new(),
+            range: vec![
+                parse_extra_range("source=reserved,cidr=10.50.0.0/16,name=future").unwrap(),
+            ],
+            terraform_plan: Some("plan.json".to_string()),
+            buffer: vec![Ipv4::new("10.0.128.0/17").unwrap()],
+            template: vec!["cmdb.csv.hbs".to_string()],
+            heatmap: true,
+            heatmap_supernet: Some(Ipv4::new("172.16.0.0/12").unwrap()),
+            heatmap_slice: Some(20),
+            gitops_dir: Some("estate".to_string()),
+            sign: true,
+            signing_key: Some("report.key".to_string()),
+            host_reservation: Some(ReservationModel::Classic),
+            manifest: true,
+            notify: vec!["https://stnet.queue.core.windows.net/subnet-runs"
+                .parse()
+                .unwrap()],
+            report_url: Some("https://stnet.blob.core.windows.net/reports/".to_string()),
+            refresh_cron: Some("0 6 * * *".parse().unwrap()),
+            output: Some(OutputFormat::Json),
+            ado_pat: None,
+            github_token: None,
+        };
+        let mut args = Args::parse_from([
+            "azure-subnet-summary",
+            "--management-group",
+            "mg-cli",
+            "--output",
+            "table",
+        ]);
+        args.apply_config(&config).unwrap();
+        assert_eq!(args.management_group.as_deref(), Some("mg-cli"));
+        assert_eq!(args.only_management_group.as_deref(), Some("mg-only"));
+        assert!(args.compress_cache);
+        assert_eq!(args.page_size, Some(200));
+        assert_eq!(args.tenant.as_deref(), Some("tenant-from-config"));
+        assert_eq!(args.timezone.as_deref(), Some("Pacific/Auckland"));
+        assert!(args.strict_schema);
+        assert!(args.strict_duplicates);
+        assert_eq!(args.isolated_vnets, ["lab-a"]);
+        assert_eq!(args.quota_warn_pct, Some(80));
+        assert_eq!(args.ignore, [IgnoreRule::subnet_name("scratch")]);
+        assert_eq!(args.range.len(), 1);
+        assert_eq!(args.terraform_plan.as_deref(), Some("plan.json"));
+        assert_eq!(args.template, ["cmdb.csv.hbs"]);
+        assert_eq!(args.buffer, [Ipv4::new("10.0.128.0/17").unwrap()]);
+        assert!(args.heatmap);
+        assert_eq!(args.heatmap_slice, Some(20));
+        assert_eq!(args.gitops_dir.as_deref(), Some("estate"));
+        assert!(args.sign);
+        assert_eq!(args.signing_key.as_deref(), Some("report.key"));
+        assert_eq!(args.host_reservation, Some(ReservationModel::Classic));
+        assert!(args.manifest);
+        assert_eq!(args.output, Some(OutputFormat::Table));
+        assert!(args.clock().is_ok());
+    }
+
+    #[test]
+    fn apply_config_rejects_out_of_range_page_size() {
+        for page_size in [0, 1001] {
+            let config = Config {
+                page_size: Some(page_size),
+                ..Config::default()
+            };
+            let mut args = Args::parse_from(["azure-subnet-summary"]);
+            let err = args.apply_config(&config).unwrap_err().to_string();
+            assert!(err.contains("page_size"), "{err}");
+        }
+        // The command line wins, so a bad config value it overrides is moot.
+        let config = Config {
+            page_size: Some(0),
+            ..Config::default()
+        };
+        let mut args = Args::parse_from(["azure-subnet-summary", "--page-size", "500"]);
+        args.apply_config(&config).unwrap();
+        assert_eq!(args.page_size, Some(500));
+    }
+
+    #[test]
+    fn empty_scope_produces_empty_outputs() {
+        let data = AzureData {
+            subnets: CacheResult {
+                data: Data::default(),
+                from_cache: true,
+                cache_file: "empty.json".to_string(),
+                generated_at: None,
+            },
+            peering_edges: Default::default(),
+            local_gateways: Default::default(),
+            vwan: Default::default(),
+            subscriptions: Default::default(),
+            aks: None,
+            flow_logs: None,
+            gateways: None,
+        };
+        let args = Args::parse_from([
+            "azure-subnet-summary",
+            "--diagram",
+            "md,dot",
+            "--output",
+            "table",
+            "--management-group",
+            "mg-empty",
+            "--group-by",
+            "subscription",
+        ]);
+        let renderer = SpyRenderer::new();
+        run(data, &args, &renderer).expect("empty scope should not fail");
+        assert_eq!(args.scope(), "management group 'mg-empty'");
+        assert!(format_table(&[], &[], Lang::En).contains("CIDR"));
+    }
+
+    #[test]
+    fn offline_rejects_online_only_work_up_front() {
+        let check = |argv: &[&str]| {
+            Args::parse_from(["azure-subnet-summary"].iter().chain(argv)).check_offline()
+        };
+        assert!(check(&["--offline"]).is_ok());
+        assert!(check(&["--offline", "doctor"]).is_ok());
+        assert!(check(&[
+            "--notify",
+            "https://topic.westeurope-1.eventgrid.azure.net/api/events"
+        ])
+        .is_ok());
+        for argv in [
+            &["--offline", "serve"][..],
+            &[
+                "--offline",
+                "publish",
+                "r.md",
+                "--github",
+                "o/r",
+                "--path",
+                "r.md",
+            ],
+            &[
+                "--offline",
+                "--notify",
+                "https://topic.westeurope-1.eventgrid.azure.net/api/events",
+            ],
+        ] {
+            assert!(
+                check(argv).unwrap_err().starts_with("--offline"),
+                "{argv:?}"
+            );
+        }
+    }
+
+    struct CostCenter;
+
+    impl RowProcessor for CostCenter {
+        fn name(&self) -> &str {
+            "cost-center"
+        }
+        fn process(
+            &self,
+            mut rows: Vec<SubnetPrintRow>,
+        ) -> Result<Vec<SubnetPrintRow>, Box<dyn Error>> {
+            for row in &mut rows {
+                let cc = if row.vnet_name.starts_with("prod") {
+                    "CC-100"
+                } else {
+                    "CC-900"
+                };
+                row.extra.insert("cost_center".to_string(), cc.to_string());
+            }
+            Ok(rows)
+        }
+    }
+
+    struct DropGaps;
+
+    impl RowProcessor for DropGaps {
+        fn name(&self) -> &str {
+            "drop-gaps"
+        }
+        fn process(
+            &self,
+            rows: Vec<SubnetPrintRow>,
+        ) -> Result<Vec<SubnetPrintRow>, Box<dyn Error>> {
+            if rows.is_empty() {
+                return Err("no rows".into());
+            }
+            Ok(rows.into_iter().filter(|r| r.gap != "-vgap-").collect())
+        }
+    }
+
+    #[test]
+    fn row_processors_run_in_order_and_add_csv_columns() {
+        let mut s = Subnet {
+            vnet_cidr: Ipv4::new("10.0.0.0/24").unwrap(),
+            ..Subnet::test("prod-hub", "app", "10.0.0.0/25")
+        };
+        s.vnet_address_space = vec![s.vnet_cidr];
+        let data = Data {
+            data: vec![s],
+            ..Default::default()
+        };
+        let mut report = Report::default();
+        report
+            .add_processor(Box::new(DropGaps))
+            .add_processor(Box::new(CostCenter));
+        let rows = report
+            .apply_processors(build_rows(&data.data, &[], 4, &[]))
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].extra["cost_center"], "CC-100");
+
+        let dir = tempfile::tempdir().unwrap();
+        let csv = subnet_print(
+            &data,
+            &[],
+            &rows,
+            &[],
+            dir.path(),
+            &Clock::default(),
+            CsvDialect::default(),
+        )
+        .unwrap();
+        let csv = std::fs::read_to_string(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(
+            lines[0].ends_with(r#""generated_at","cost_center""#),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[1].trim_end().ends_with(r#""CC-100""#), "{}", lines[1]);
+
+        let err = report.apply_processors(Vec::new()).unwrap_err().to_string();
+        assert_eq!(err, "Row processor 'drop-gaps' failed: no rows");
+    }
+
+    #[test]
+    fn stream_batches_are_runs_of_one_vnet() {
+        let subnet = |vnet: &str, vnet_cidr: &str, cidr: &str| Subnet {
+            vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+            ..Subnet::test(vnet, "app", cidr)
+        };
+        let rows = build_rows(
+            &[
+                subnet("hub", "10.0.0.0/24", "10.0.0.0/25"),
+                subnet("spoke", "10.0.4.0/24", "10.0.4.0/24"),
+            ],
+            &[],
+            4,
+            &[],
+        );
+        let total = rows.len();
+        let batches = vnet_batches(rows);
+        let vnets: Vec<&str> = batches.iter().map(|b| b[0].vnet_name.as_str()).collect();
+        assert_eq!(vnets, ["hub", "None", "spoke"]);
+        assert_eq!(batches[0].len(), 2, "subnet and its vgap");
+        assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), total);
+    }
 }
@@ -0,0 +1,259 @@
+//! Address plan conformance.
+//!
+//! `[[plan]]` entries in the config file describe the intended hierarchy:
+//! each region gets one or more address blocks, optionally with a hub block
+//! inside it and a standard spoke size, e.g. `westeurope: 10.20.0.0/14, hub
+//! 10.20.0.0/22, spokes /22`. Every Azure VNet is checked against the blocks
+//! of its region; the score is the share of VNets in planned regions that
+//! fit without an issue.
+
+use crate::azure::Data;
+use crate::models::Ipv4;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::error::Error;
+
+/// One `[[plan]]` block of a region.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanBlock {
+    /// Name shown in the report, e.g. `weu`.
+    pub name: String,
+    /// Azure location the block is for, e.g. `westeurope`.
+    pub region: String,
+    /// Address block VNets of the region are allocated from.
+    pub cidr: Ipv4,
+    /// Block inside `cidr` reserved for hub VNets.
+    pub hub: Option<Ipv4>,
+    /// Prefix length of spoke VNets (VNets outside the hub block).
+    pub spoke_mask: Option<u8>,
+}
+
+/// `true` when `inner` lies entirely within `outer`.
+fn within(inner: Ipv4, outer: Ipv4) -> bool {
+    outer.mask <= inner.mask && outer.contains(inner.lo())
+}
+
+/// Check the blocks themselves: hub blocks inside their block, valid spoke
+/// sizes that fit the block.
+pub fn validate_plan(plan: &[PlanBlock]) -> Result<(), Box<dyn Error>> {
+    for b in plan {
+        if let Some(hub) = b.hub.filter(|&hub| !within(hub, b.cidr)) {
+            return Err(format!("Plan block '{}': hub {hub} is outside {}", b.name, b.cidr).into());
+        }
+        if let Some(mask) = b.spoke_mask.filter(|&m| m > 32 || m < b.cidr.mask) {
+            return Err(format!(
+                "Plan block '{}': spoke size /{mask} does not fit in {}",
+                b.name, b.cidr
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Why a VNet prefix does not fit the plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanIssue {
+    /// The prefix is outside every block of the VNet's region; `found_in` is
+    /// the block (of another region) holding it, if any.
+    OutsideBlock {
+        prefix: Ipv4,
+        found_in: Option<String>,
+    },
+    /// A spoke prefix is not the planned spoke size.
+    WrongSize {
+        prefix: Ipv4,
+        block: String,
+        spoke_mask: u8,
+    },
+}
+
+/// A VNet checked against the plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VnetConformance {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub location: String,
+    /// Names of the region's blocks; empty when the region has none.
+    pub blocks: Vec<String>,
+    pub issues: Vec<PlanIssue>,
+}
+
+impl VnetConformance {
+    pub fn planned(&self) -> bool {
+        !self.blocks.is_empty()
+    }
+}
+
+/// Check every Azure VNet in `data` against `plan`, in input order.
+pub fn plan_conformance(data: &Data, plan: &[PlanBlock]) -> Vec<VnetConformance> {
+    let mut seen = HashSet::new();
+    let mut vnets = Vec::new();
+    for s in &data.data {
+        if !s.source.is_azure_resource()
+            || !seen.insert((s.subscription_id.as_str(), s.vnet_name.as_str()))
+        {
+            continue;
+        }
+        let region: Vec<&PlanBlock> = plan
+            .iter()
+            .filter(|b| b.region.eq_ignore_ascii_case(&s.location))
+            .collect();
+        let mut issues = Vec::new();
+        if !region.is_empty() {
+            for &prefix in &s.vnet_address_space {
+                match region.iter().find(|b| within(prefix, b.cidr)) {
+                    None => issues.push(PlanIssue::OutsideBlock {
+                        prefix,
+                        found_in: plan
+                            .iter()
+                            .find(|b| within(prefix, b.cidr))
+                            .map(|b| b.name.clone()),
+                    }),
+                    Some(b) => {
+                        let in_hub = b.hub.is_some_and(|hub| within(prefix, hub));
+                        if let Some(mask) = b.spoke_mask.filter(|&m| !in_hub && m != prefix.mask) {
+                            issues.push(PlanIssue::WrongSize {
+                                prefix,
+                                block: b.name.clone(),
+                                spoke_mask: mask,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        vnets.push(VnetConformance {
+            subscription_name: s.subscription_name.clone(),
+            vnet_name: s.vnet_name.clone(),
+            location: s.location.clone(),
+            blocks: region.iter().map(|b| b.name.clone()).collect(),
+            issues,
+        });
+    }
+    vnets
+}
+
+/// Render the conformance score, then one line per issue and per VNet in a
+/// region without blocks.
+pub fn format_conformance(vnets: &[VnetConformance], plan: &[PlanBlock]) -> String {
+    let planned: Vec<&VnetConformance> = vnets.iter().filter(|v| v.planned()).collect();
+    let fitting = planned.iter().filter(|v| v.issues.is_empty()).count();
+    let mut lines = vec![if planned.is_empty() {
+        "CONFORMANCE: no VNets in planned regions".to_string()
+    } else {
+        format!(
+            "CONFORMANCE: {fitting} of {} VNets in planned regions fit their blocks ({}%)",
+            planned.len(),
+            fitting * 100 / planned.len()
+        )
+    }];
+    let block = |name: &str| {
+        plan.iter()
+            .find(|b| b.name == name)
+            .map(|b| format!("'{}' {}", b.name, b.cidr))
+            .unwrap_or_default()
+    };
+    for v in &planned {
+        let vnet = format!("'{}/{}' ({})", v.subscription_name, v.vnet_name, v.location);
+        for issue in &v.issues {
+            lines.push(match issue {
+                PlanIssue::OutsideBlock { prefix, found_in } => format!(
+                    "OUTSIDE: {vnet} {prefix} outside {}{}",
+                    v.blocks
+                        .iter()
+                        .map(|b| block(b))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    found_in
+                        .as_deref()
+                        .map(|b| format!(" [in block {}]", block(b)))
+                        .unwrap_or_default()
+                ),
+                PlanIssue::WrongSize {
+                    prefix,
+                    block: name,
+                    spoke_mask,
+                } => format!(
+                    "SIZE: {vnet} {prefix} in block {} - spokes are /{spoke_mask}",
+                    block(name)
+                ),
+            });
+        }
+    }
+    for v in vnets.iter().filter(|v| !v.planned()) {
+        lines.push(format!(
+            "UNPLANNED: '{}/{}' ({}) - no block for the region",
+            v.subscription_name, v.vnet_name, v.location
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    fn vnet(name: &str, location: &str, prefixes: &[&str]) -> Subnet {
+        Subnet {
+            subscription_name: "Prod".to_string(),
+            subscription_id: "sub-1".to_string(),
+            vnet_name: name.to_string(),
+            location: location.to_string(),
+            vnet_address_space: prefixes.iter().map(|p| Ipv4::new(p).unwrap()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn vnets_are_scored_against_the_blocks_of_their_region() {
+        let plan = vec![
+            PlanBlock {
+                name: "weu".to_string(),
+                region: "westeurope".to_string(),
+                cidr: Ipv4::new("10.20.0.0/14").unwrap(),
+                hub: Some(Ipv4::new("10.20.0.0/20").unwrap()),
+                spoke_mask: Some(22),
+            },
+            PlanBlock {
+                name: "neu".to_string(),
+                region: "northeurope".to_string(),
+                cidr: Ipv4::new("10.24.0.0/14").unwrap(),
+                hub: None,
+                spoke_mask: None,
+            },
+        ];
+        validate_plan(&plan).unwrap();
+        let data = Data {
+            data: vec![
+                vnet("hub", "westeurope", &["10.20.0.0/20"]),
+                vnet("app", "WestEurope", &["10.20.16.0/22"]),
+                vnet("big", "westeurope", &["10.20.32.0/21"]),
+                vnet("stray", "westeurope", &["10.24.4.0/22"]),
+                vnet("dr", "northeurope", &["10.25.0.0/16"]),
+                vnet("lab", "eastus", &["192.168.0.0/24"]),
+            ],
+            ..Default::default()
+        };
+        let vnets = plan_conformance(&data, &plan);
+        assert_eq!(vnets.len(), 6);
+        let out = format_conformance(&vnets, &plan);
+        assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            [
+                "CONFORMANCE: 3 of 5 VNets in planned regions fit their blocks (60%)",
+                "SIZE: 'Prod/big' (westeurope) 10.20.32.0/21 in block 'weu' 10.20.0.0/14 - spokes are /22",
+                "OUTSIDE: 'Prod/stray' (westeurope) 10.24.4.0/22 outside 'weu' 10.20.0.0/14 [in block 'neu' 10.24.0.0/14]",
+                "UNPLANNED: 'Prod/lab' (eastus) - no block for the region",
+            ]
+        );
+
+        let bad = [PlanBlock {
+            hub: Some(Ipv4::new("10.30.0.0/20").unwrap()),
+            ..plan[0].clone()
+        }];
+        assert!(validate_plan(&bad).is_err());
+    }
+}
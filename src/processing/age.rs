@@ -0,0 +1,236 @@
+//! Subnet age from snapshot history: the first and last date each subnet
+//! was seen, for the `first_seen` / `last_seen` report columns.
+//!
+//! Reviewers use them to tell long-standing allocations from last week's
+//! experiments. `first_seen` starts the current unbroken run of snapshots
+//! the subnet is in, so a subnet deleted and recreated under the same ID
+//! dates from its recreation; it can be no older than the oldest snapshot
+//! kept, so on a short history it means "at least since". `last_seen` is
+//! when Azure last returned the subnet: its record's fetch date (older for
+//! records an incremental fetch kept), or a later snapshot when the report
+//! runs on an older cache file.
+
+use super::empty::subnet_key;
+use super::gap_finder::SubnetPrintRow;
+use crate::azure::Data;
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+
+/// First and last date a subnet was seen in its current run of snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubnetAge {
+    pub first_seen: NaiveDate,
+    pub last_seen: NaiveDate,
+}
+
+/// Per-subnet progress through the newest-first snapshot history.
+struct Run {
+    age: SubnetAge,
+    /// Newest date of the run of later snapshots reaching back to `today`.
+    later: Option<NaiveDate>,
+    /// A snapshot on or before `today` lacked the subnet: older snapshots
+    /// belong to an earlier incarnation.
+    broken: bool,
+}
+
+/// Age of every subnet of `current` (dated `today`), keyed by subnet
+/// identity. `history` is read newest first, as `read_snapshots_newest_first`
+/// yields it, and only as far back as some subnet's run continues.
+pub fn subnet_ages(
+    current: &Data,
+    today: NaiveDate,
+    history: impl IntoIterator<Item = (NaiveDate, Data)>,
+) -> HashMap<String, SubnetAge> {
+    let mut runs: HashMap<String, Run> = current
+        .data
+        .iter()
+        .map(|s| {
+            let fetched = s
+                .fetched_at
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map_or(today, |t| t.date_naive().min(today));
+            let age = SubnetAge {
+                first_seen: fetched,
+                last_seen: fetched,
+            };
+            let run = Run {
+                age,
+                later: None,
+                broken: false,
+            };
+            (subnet_key(s), run)
+        })
+        .collect();
+    for (date, snapshot) in history {
+        if date <= today && runs.values().all(|r| r.broken) {
+            break;
+        }
+        let present: HashSet<String> = snapshot.data.iter().map(subnet_key).collect();
+        for (key, run) in runs.iter_mut() {
+            let seen = present.contains(key);
+            if date > today {
+                // Newest first: a gap resets the run, so what is left at
+                // `today` is the run adjoining it.
+                run.later = if seen { run.later.or(Some(date)) } else { None };
+            } else if !run.broken {
+                if seen {
+                    run.age.first_seen = run.age.first_seen.min(date);
+                } else {
+                    run.broken = true;
+                }
+            }
+        }
+    }
+    runs.into_iter()
+        .map(|(key, run)| {
+            let mut age = run.age;
+            if let Some(later) = run.later {
+                age.last_seen = age.last_seen.max(later);
+            }
+            (key, age)
+        })
+        .collect()
+}
+
+/// Ages per subnet row, for filling the `first_seen` / `last_seen` columns
+/// batch by batch.
+pub struct SubnetAgeAnnotator<'a> {
+    ages: HashMap<(&'a str, &'a str, &'a str), SubnetAge>,
+}
+
+impl<'a> SubnetAgeAnnotator<'a> {
+    pub fn new(
+        current: &'a Data,
+        today: NaiveDate,
+        history: impl IntoIterator<Item = (NaiveDate, Data)>,
+    ) -> SubnetAgeAnnotator<'a> {
+        let by_key = subnet_ages(current, today, history);
+        let ages = current
+            .data
+            .iter()
+            .filter_map(|s| {
+                let key = (
+                    s.subscription_id.as_str(),
+                    s.vnet_name.as_str(),
+                    s.subnet_name.as_str(),
+                );
+                Some((key, *by_key.get(&subnet_key(s))?))
+            })
+            .collect();
+        SubnetAgeAnnotator { ages }
+    }
+
+    /// Fill the `first_seen` / `last_seen` columns of the subnet rows in
+    /// `rows`; gap rows stay empty.
+    pub fn annotate(&self, rows: &mut [SubnetPrintRow]) {
+        for row in rows {
+            let key = (
+                row.subscription_id.as_str(),
+                row.vnet_name.as_str(),
+                row.subnet_name.as_str(),
+            );
+            if let Some(age) = self.ages.get(&key) {
+                row.first_seen = age.first_seen.to_string();
+                row.last_seen = age.last_seen.to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Ipv4, Subnet};
+    use crate::output::build_rows;
+
+    fn subnet(name: &str, cidr: &str) -> Subnet {
+        Subnet {
+            subscription_id: "sub-1".to_string(),
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            subnet_id: format!("/subscriptions/sub-1/virtualNetworks/hub/subnets/{name}"),
+            ..Subnet::test("hub", name, cidr)
+        }
+    }
+
+    fn data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ages_span_every_snapshot_the_subnet_is_in() {
+        let date = |d: &str| d.parse::<NaiveDate>().unwrap();
+        let app = subnet("app", "10.0.1.0/24");
+        let lab = subnet("lab", "10.0.2.0/24");
+        let current = data(vec![app.clone(), lab.clone()]);
+        // Newest first, as read_snapshots_newest_first yields them.
+        let history = vec![
+            (date("2026-10-14"), data(vec![app.clone(), lab])),
+            (
+                date("2026-10-08"),
+                data(vec![app.clone(), subnet("old", "10.0.9.0/24")]),
+            ),
+            (date("2026-09-01"), data(vec![app])),
+        ];
+        let ages = SubnetAgeAnnotator::new(&current, date("2026-10-15"), history);
+
+        let mut rows = build_rows(&current.data, &[], 4, &[]);
+        ages.annotate(&mut rows);
+        let seen = |name: &str| {
+            let row = rows.iter().find(|r| r.subnet_name == name).unwrap();
+            (row.first_seen.as_str(), row.last_seen.as_str())
+        };
+        assert_eq!(seen("app"), ("2026-09-01", "2026-10-15"));
+        assert_eq!(seen("lab"), ("2026-10-14", "2026-10-15"));
+        assert!(rows
+            .iter()
+            .filter(|r| r.gap.contains("gap"))
+            .all(|r| r.first_seen.is_empty()));
+    }
+
+    #[test]
+    fn recreated_subnets_date_from_their_current_run() {
+        let date = |d: &str| d.parse::<NaiveDate>().unwrap();
+        let app = subnet("app", "10.0.1.0/24");
+        let kept = Subnet {
+            fetched_at: Some("2026-10-12T06:00:00Z".to_string()),
+            ..subnet("kept", "10.0.2.0/24")
+        };
+        let current = data(vec![app.clone(), kept.clone()]);
+        let history = vec![
+            (date("2026-10-14"), data(vec![app.clone(), kept.clone()])),
+            // Deleted and recreated: older snapshots are another subnet.
+            (date("2026-10-10"), data(vec![kept.clone()])),
+            (date("2026-09-01"), data(vec![app, kept])),
+        ];
+        let ages = subnet_ages(&current, date("2026-10-15"), history);
+        let age = |name: &str| {
+            let key = format!("/subscriptions/sub-1/virtualnetworks/hub/subnets/{name}");
+            let age = ages[&key];
+            (age.first_seen.to_string(), age.last_seen.to_string())
+        };
+        assert_eq!(age("app"), ("2026-10-14".into(), "2026-10-15".into()));
+        // Kept from an earlier fetch by an incremental run.
+        assert_eq!(age("kept"), ("2026-09-01".into(), "2026-10-12".into()));
+    }
+
+    #[test]
+    fn later_snapshots_extend_last_seen_of_an_older_cache() {
+        let date = |d: &str| d.parse::<NaiveDate>().unwrap();
+        let app = subnet("app", "10.0.1.0/24");
+        let current = data(vec![app.clone()]);
+        let history = vec![
+            (date("2026-10-14"), data(vec![app.clone()])),
+            (date("2026-10-12"), data(vec![])),
+            (date("2026-10-11"), data(vec![app.clone()])),
+            (date("2026-10-10"), data(vec![app])),
+        ];
+        let ages = subnet_ages(&current, date("2026-10-10"), history);
+        let age = ages["/subscriptions/sub-1/virtualnetworks/hub/subnets/app"];
+        assert_eq!(age.first_seen, date("2026-10-10"));
+        assert_eq!(age.last_seen, date("2026-10-11"));
+    }
+}
@@ -0,0 +1,298 @@
+//! AKS subnet IP pressure estimation.
+//!
+//! With Azure CNI every node takes one IP for itself plus `max_pods` pod IPs up
+//! front, so a node pool's peak demand — autoscaler maximum plus upgrade
+//! surge — must fit in its subnet. With a dedicated pod subnet the pod IPs
+//! come from that subnet instead; Overlay and kubenet only use node IPs.
+
+use crate::azure::{AksData, AksPoolRow, Data};
+use crate::models::{num_az_hosts, Ipv4};
+use std::collections::BTreeMap;
+
+/// `maxPods` AKS uses for Azure CNI pools that do not report one.
+const DEFAULT_MAX_PODS: u32 = 30;
+
+/// Nodes AKS adds during an upgrade when `maxSurge` is unset.
+const DEFAULT_MAX_SURGE: u64 = 1;
+
+/// Estimated IP demand of the AKS pools using one subnet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AksSubnetPressure {
+    pub subnet_id: String,
+    pub vnet_name: String,
+    pub subnet_name: String,
+    pub subnet_cidr: Option<Ipv4>,
+    /// Usable Azure hosts in the subnet.
+    pub capacity: u64,
+    /// IP configurations currently in the subnet (AKS and anything else).
+    pub in_use: u64,
+    /// IPs the AKS pools take at their current node counts.
+    pub aks_current: u64,
+    /// IPs the AKS pools need at peak scale plus upgrade surge.
+    pub aks_peak: u64,
+    /// `cluster/pool` names using the subnet.
+    pub pools: Vec<String>,
+}
+
+impl AksSubnetPressure {
+    /// IPs needed when every pool is at peak, keeping non-AKS usage as is.
+    pub fn required(&self) -> u64 {
+        self.in_use.saturating_sub(self.aks_current) + self.aks_peak
+    }
+
+    /// `true` when the subnet can hold a full node surge.
+    pub fn fits(&self) -> bool {
+        self.required() <= self.capacity
+    }
+}
+
+/// `true` when pods get VNet IPs (Azure CNI without Overlay).
+fn pods_use_vnet_ips(pool: &AksPoolRow) -> bool {
+    pool.network_plugin.eq_ignore_ascii_case("azure")
+        && !pool.network_plugin_mode.eq_ignore_ascii_case("overlay")
+}
+
+/// IPs one node of `pool` takes from the subnet `subnet_id`.
+fn ips_per_node(pool: &AksPoolRow, subnet_id: &str) -> u64 {
+    let on_node_subnet = pool.vnet_subnet_id.eq_ignore_ascii_case(subnet_id);
+    let pods = if pods_use_vnet_ips(pool) {
+        u64::from(pool.max_pods.unwrap_or(DEFAULT_MAX_PODS))
+    } else {
+        0
+    };
+    let pods_here = if pool.pod_subnet_id.is_empty() {
+        on_node_subnet
+    } else {
+        pool.pod_subnet_id.eq_ignore_ascii_case(subnet_id)
+    };
+    u64::from(on_node_subnet) + if pods_here { pods } else { 0 }
+}
+
+/// Node count the pool can reach: the autoscaler maximum when autoscaling.
+fn peak_nodes(pool: &AksPoolRow) -> u64 {
+    let count = u64::from(pool.node_count.unwrap_or(0));
+    if pool.enable_auto_scaling == Some(true) {
+        count.max(u64::from(pool.max_count.unwrap_or(0)))
+    } else {
+        count
+    }
+}
+
+/// Extra nodes added during an upgrade; `maxSurge` is a count or a percentage.
+fn surge_nodes(pool: &AksPoolRow, nodes: u64) -> u64 {
+    let surge = pool.max_surge.trim();
+    if let Some(pct) = surge.strip_suffix('%') {
+        match pct.trim().parse::<u64>() {
+            Ok(pct) => (nodes * pct).div_ceil(100).max(1),
+            Err(_) => DEFAULT_MAX_SURGE,
+        }
+    } else {
+        surge.parse().unwrap_or(DEFAULT_MAX_SURGE)
+    }
+}
+
+/// Estimate IP pressure on every subnet used by an AKS pool.
+///
+/// Subnets are matched by resource ID. Pools on an AKS-managed VNet and
+/// subnets missing from `data` are logged and skipped. Results are sorted with
+/// subnets that cannot fit a surge first.
+pub fn aks_subnet_pressure(data: &Data, aks: &AksData) -> Vec<AksSubnetPressure> {
+    let mut by_subnet: BTreeMap<String, AksSubnetPressure> = BTreeMap::new();
+
+    for pool in &aks.data {
+        let name = format!("{}/{}", pool.cluster_name, pool.pool_name);
+        if pool.vnet_subnet_id.is_empty() {
+            log::info!("AKS pool '{name}' uses an AKS-managed VNet — not assessed");
+            continue;
+        }
+        // A pod subnet equal to the node subnet is already counted there.
+        let pod_subnet = (!pool.pod_subnet_id.is_empty()
+            && !pool
+                .pod_subnet_id
+                .eq_ignore_ascii_case(&pool.vnet_subnet_id))
+        .then_some(&pool.pod_subnet_id);
+        for subnet_id in std::iter::once(&pool.vnet_subnet_id).chain(pod_subnet) {
+            let Some(subnet) = data
+                .data
+                .iter()
+                .find(|s| s.subnet_id.eq_ignore_ascii_case(subnet_id))
+            else {
+                log::warn!(
+                    "AKS pool '{name}' uses subnet '{subnet_id}' which is not in the subnet data"
+                );
+                continue;
+            };
+            let entry = by_subnet
+                .entry(subnet_id.to_lowercase())
+                .or_insert_with(|| AksSubnetPressure {
+                    subnet_id: subnet.subnet_id.clone(),
+                    vnet_name: subnet.vnet_name.clone(),
+                    subnet_name: subnet.subnet_name.clone(),
+                    subnet_cidr: subnet.subnet_cidr,
+                    capacity: subnet
+                        .subnet_cidr
+                        .and_then(|c| num_az_hosts(c.mask).ok())
+                        .unwrap_or(0),
+                    in_use: u64::from(subnet.ip_configurations_count.unwrap_or(0)),
+                    aks_current: 0,
+                    aks_peak: 0,
+                    pools: Vec::new(),
+                });
+            let per_node = ips_per_node(pool, subnet_id);
+            let peak = peak_nodes(pool);
+            entry.aks_current += per_node * u64::from(pool.node_count.unwrap_or(0));
+            entry.aks_peak += per_node * (peak + surge_nodes(pool, peak));
+            if !entry.pools.contains(&name) {
+                entry.pools.push(name.clone());
+            }
+        }
+    }
+
+    let mut out: Vec<AksSubnetPressure> = by_subnet.into_values().collect();
+    out.sort_by(|a, b| {
+        (a.fits(), &a.vnet_name, &a.subnet_name).cmp(&(b.fits(), &b.vnet_name, &b.subnet_name))
+    });
+    out
+}
+
+/// Render AKS subnet pressure as terminal lines.
+pub fn format_aks_pressure(pressure: &[AksSubnetPressure]) -> String {
+    pressure
+        .iter()
+        .map(|p| {
+            let cidr = p
+                .subnet_cidr
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "None".to_string());
+            format!(
+                "AKS SUBNET: '{}/{}' {cidr} - peak {} of {} IPs ({}) [{}]",
+                p.vnet_name,
+                p.subnet_name,
+                p.required(),
+                p.capacity,
+                p.pools.join(", "),
+                if p.fits() { "OK" } else { "SURGE DOES NOT FIT" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    const NODE_SUBNET: &str = "/subscriptions/s/resourceGroups/rg/providers/Microsoft.Network/virtualNetworks/v/subnets/nodes";
+    const POD_SUBNET: &str = "/subscriptions/s/resourceGroups/rg/providers/Microsoft.Network/virtualNetworks/v/subnets/pods";
+
+    fn make_subnet(id: &str, cidr: &str, in_use: u32) -> Subnet {
+        Subnet {
+            vnet_name: "v".to_string(),
+            subnet_id: id.to_string(),
+            subnet_name: id.rsplit('/').next().unwrap().to_string(),
+            subnet_cidr: Some(Ipv4::new(cidr).unwrap()),
+            ip_configurations_count: Some(in_use),
+            ..Default::default()
+        }
+    }
+
+    fn make_pool(nodes: u32, max_pods: u32) -> AksPoolRow {
+        AksPoolRow {
+            cluster_name: "aks".to_string(),
+            pool_name: "np".to_string(),
+            network_plugin: "azure".to_string(),
+            vnet_subnet_id: NODE_SUBNET.to_string(),
+            max_pods: Some(max_pods),
+            node_count: Some(nodes),
+            ..Default::default()
+        }
+    }
+
+    fn make_aks(pools: Vec<AksPoolRow>) -> AksData {
+        AksData {
+            data: pools,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn azure_cni_counts_node_and_pod_ips_with_default_surge() {
+        // /24 = 251 Azure hosts; 3 nodes × (1 + 30) = 93 now, (3 + 1) × 31 = 124 at surge.
+        let data = Data {
+            data: vec![make_subnet(NODE_SUBNET, "10.0.0.0/24", 100)],
+            ..Default::default()
+        };
+        let p = aks_subnet_pressure(&data, &make_aks(vec![make_pool(3, 30)]));
+        assert_eq!(p.len(), 1);
+        assert_eq!(p[0].aks_current, 93);
+        assert_eq!(p[0].aks_peak, 124);
+        // 7 non-AKS IPs stay in use.
+        assert_eq!(p[0].required(), 131);
+        assert!(p[0].fits());
+    }
+
+    #[test]
+    fn autoscaler_maximum_and_percentage_surge_can_overflow_subnet() {
+        let mut pool = make_pool(2, 30);
+        pool.enable_auto_scaling = Some(true);
+        pool.max_count = Some(10);
+        pool.max_surge = "33%".to_string();
+        let data = Data {
+            data: vec![make_subnet(NODE_SUBNET, "10.0.0.0/24", 62)],
+            ..Default::default()
+        };
+        let p = aks_subnet_pressure(&data, &make_aks(vec![pool]));
+        // (10 + ceil(3.3)) × 31 = 434 > 251
+        assert_eq!(p[0].aks_peak, 434);
+        assert!(!p[0].fits());
+        assert!(format_aks_pressure(&p).contains("[SURGE DOES NOT FIT]"));
+    }
+
+    #[test]
+    fn pod_subnet_takes_pod_ips_and_overlay_takes_none() {
+        let mut pool = make_pool(2, 50);
+        pool.pod_subnet_id = POD_SUBNET.to_string();
+        let data = Data {
+            data: vec![
+                make_subnet(NODE_SUBNET, "10.0.0.0/27", 2),
+                make_subnet(POD_SUBNET, "10.0.4.0/24", 100),
+            ],
+            ..Default::default()
+        };
+        let p = aks_subnet_pressure(&data, &make_aks(vec![pool.clone()]));
+        let nodes = p.iter().find(|p| p.subnet_name == "nodes").unwrap();
+        let pods = p.iter().find(|p| p.subnet_name == "pods").unwrap();
+        assert_eq!(nodes.aks_peak, 3);
+        assert_eq!(pods.aks_peak, 150);
+
+        pool.pod_subnet_id.clear();
+        pool.network_plugin_mode = "overlay".to_string();
+        let p = aks_subnet_pressure(&data, &make_aks(vec![pool]));
+        assert_eq!(p.len(), 1);
+        assert_eq!(p[0].aks_peak, 3);
+    }
+
+    #[test]
+    fn pod_subnet_equal_to_node_subnet_is_counted_once() {
+        let mut pool = make_pool(3, 30);
+        pool.pod_subnet_id = NODE_SUBNET.to_uppercase();
+        let data = Data {
+            data: vec![make_subnet(NODE_SUBNET, "10.0.0.0/24", 100)],
+            ..Default::default()
+        };
+        let p = aks_subnet_pressure(&data, &make_aks(vec![pool]));
+        assert_eq!(p.len(), 1);
+        assert_eq!(p[0].pools.len(), 1);
+        assert_eq!(p[0].aks_current, 93);
+        assert_eq!(p[0].aks_peak, 124);
+    }
+
+    #[test]
+    fn managed_vnet_and_unknown_subnets_are_skipped() {
+        let mut managed = make_pool(3, 30);
+        managed.vnet_subnet_id.clear();
+        let data = Data::default();
+        assert!(aks_subnet_pressure(&data, &make_aks(vec![managed, make_pool(1, 30)])).is_empty());
+    }
+}
@@ -0,0 +1,562 @@
+//! Address allocation planning.
+//!
+//! Suggests the first free CIDR of a given size in a VNet, and proposes how to
+//! carve an existing subnet or free gap into smaller subnets, either N equal
+//! parts or a mix of sizes (e.g. `2x26,4x28`). Split requests are placed
+//! largest first from the low end, which keeps every block aligned and leaves
+//! the unused space contiguous at the top. Buffers (intentional gaps) are
+//! never allocated from.
+
+use crate::azure::Data;
+use crate::models::{cut_addr, Ipv4, Subnet, MAX_LENGTH, MAX_SPLIT_BITS};
+use std::error::Error;
+use std::net::Ipv4Addr;
+
+/// A proposed split of `space` into `allocations`, plus the unused `leftover`
+/// expressed as the fewest aligned CIDR blocks. `buffers` are the buffers
+/// overlapping `space`, kept free and not part of the leftover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitPlan {
+    pub space: Ipv4,
+    pub allocations: Vec<Ipv4>,
+    pub leftover: Vec<Ipv4>,
+    pub buffers: Vec<Ipv4>,
+}
+
+/// `count` subnets of prefix length `mask`, e.g. `2x26` → `SizeRequest { count: 2, mask: 26 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeRequest {
+    pub count: u32,
+    pub mask: u8,
+}
+
+fn block_size(mask: u8) -> u64 {
+    1u64 << (MAX_LENGTH - mask)
+}
+
+/// What is left of `[lo, hi)` after taking out the `cut` ranges, ascending.
+pub(crate) fn subtract_ranges(lo: u64, hi: u64, cut: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut cut: Vec<(u64, u64)> = cut
+        .iter()
+        .copied()
+        .filter(|&(c_lo, c_hi)| c_lo < hi && lo < c_hi)
+        .collect();
+    cut.sort_unstable();
+    let mut left = Vec::new();
+    let mut cursor = lo;
+    for (c_lo, c_hi) in cut {
+        if c_lo > cursor {
+            left.push((cursor, c_lo));
+        }
+        cursor = cursor.max(c_hi);
+    }
+    if cursor < hi {
+        left.push((cursor, hi));
+    }
+    left
+}
+
+/// Parse a size mix such as `2x26,4x28`, `2×/26 + 4×/28` or `/27` (count 1).
+pub fn parse_size_mix(s: &str) -> Result<Vec<SizeRequest>, Box<dyn Error>> {
+    let requests = s
+        .split([',', '+'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let (count, mask) = match item.split_once(['x', 'X', '×']) {
+                Some((count, mask)) => (count.trim(), mask.trim()),
+                None => ("1", item),
+            };
+            let count: u32 = count
+                .parse()
+                .map_err(|_| format!("Invalid count '{count}' in '{item}'"))?;
+            let mask: u8 = mask
+                .trim_start_matches('/')
+                .parse()
+                .map_err(|_| format!("Invalid prefix length '{mask}' in '{item}'"))?;
+            if mask > MAX_LENGTH {
+                return Err(format!("Prefix length /{mask} is too long in '{item}'").into());
+            }
+            Ok(SizeRequest { count, mask })
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    if requests.is_empty() {
+        return Err(format!("No subnet sizes in '{s}'").into());
+    }
+    Ok(requests)
+}
+
+/// Split `space` into `count` equal subnets, as large as possible, outside
+/// the `buffers`.
+pub fn split_equal(space: Ipv4, count: u32, buffers: &[Ipv4]) -> Result<SplitPlan, Box<dyn Error>> {
+    if count == 0 {
+        return Err("Subnet count must be at least 1".into());
+    }
+    // Smallest extra prefix bits giving at least `count` subnets.
+    let extra_bits = (u32::BITS - (count - 1).leading_zeros()) as u8;
+    let mask = space.mask + extra_bits;
+    if mask > MAX_LENGTH {
+        return Err(format!("{space} cannot be split into {count} subnets").into());
+    }
+    split_sizes(space, &[SizeRequest { count, mask }], buffers)
+}
+
+/// Split `space` into the requested mix of sizes, outside the `buffers`.
+///
+/// Fails when a requested size is larger than `space`, the total does not
+/// fit around the buffers, or it is more than `2^MAX_SPLIT_BITS` subnets.
+pub fn split_sizes(
+    space: Ipv4,
+    sizes: &[SizeRequest],
+    buffers: &[Ipv4],
+) -> Result<SplitPlan, Box<dyn Error>> {
+    if let Some(req) = sizes.iter().find(|r| r.mask < space.mask) {
+        return Err(format!("/{} does not fit inside {space}", req.mask).into());
+    }
+    let total: u64 = sizes.iter().map(|r| u64::from(r.count)).sum();
+    let max = 1u64 << MAX_SPLIT_BITS;
+    if total > max {
+        return Err(format!("Requested {total} subnets (at most {max})").into());
+    }
+    let needed: u64 = sizes
+        .iter()
+        .map(|r| u64::from(r.count) * block_size(r.mask))
+        .sum();
+    let start = u64::from(u32::from(cut_addr(space.addr, space.mask)?));
+    let end = start + block_size(space.mask);
+    let buffers: Vec<Ipv4> = buffers
+        .iter()
+        .copied()
+        .filter(|b| {
+            let (lo, hi) = b.range();
+            lo < end && start < hi
+        })
+        .collect();
+    let buffered: Vec<(u64, u64)> = buffers.iter().map(|b| b.range()).collect();
+    let available: u64 = subtract_ranges(start, end, &buffered)
+        .iter()
+        .map(|(lo, hi)| hi - lo)
+        .sum();
+    if needed > available {
+        let outside = if buffers.is_empty() {
+            ""
+        } else {
+            " outside its buffers"
+        };
+        return Err(format!(
+            "Requested {needed} addresses but {space} only has {available}{outside}"
+        )
+        .into());
+    }
+
+    // Largest blocks first: every block then starts on its own alignment.
+    let mut masks: Vec<u8> = sizes
+        .iter()
+        .flat_map(|r| std::iter::repeat_n(r.mask, r.count as usize))
+        .collect();
+    masks.sort_unstable();
+    let mut cursor = start;
+    let mut allocations = Vec::with_capacity(masks.len());
+    for mask in masks {
+        let size = block_size(mask);
+        // Lowest aligned block from the cursor that clears every buffer.
+        let mut candidate = cursor.div_ceil(size) * size;
+        while let Some(&(_, b_hi)) = buffered
+            .iter()
+            .find(|(b_lo, b_hi)| *b_lo < candidate + size && candidate < *b_hi)
+        {
+            candidate = b_hi.div_ceil(size) * size;
+        }
+        if candidate + size > end {
+            return Err(format!("/{mask} does not fit in {space} around its buffers").into());
+        }
+        allocations.push(Ipv4 {
+            addr: Ipv4Addr::from(candidate as u32),
+            mask,
+        });
+        cursor = candidate + size;
+    }
+
+    let taken: Vec<(u64, u64)> = allocations
+        .iter()
+        .map(|a| a.range())
+        .chain(buffered)
+        .collect();
+    let leftover = subtract_ranges(start, end, &taken)
+        .into_iter()
+        .flat_map(|(lo, hi)| cover_range(lo, hi))
+        .collect();
+    Ok(SplitPlan {
+        space,
+        allocations,
+        leftover,
+        buffers,
+    })
+}
+
+/// Cover the address range `[lo, hi)` with the fewest aligned CIDR blocks.
+pub fn cover_range(mut lo: u64, hi: u64) -> Vec<Ipv4> {
+    let mut blocks = Vec::new();
+    while lo < hi {
+        let align = if lo == 0 {
+            32
+        } else {
+            lo.trailing_zeros().min(32)
+        };
+        let fit = 63 - (hi - lo).leading_zeros();
+        let bits = align.min(fit);
+        blocks.push(Ipv4 {
+            addr: Ipv4Addr::from(lo as u32),
+            mask: MAX_LENGTH - bits as u8,
+        });
+        lo += 1u64 << bits;
+    }
+    blocks
+}
+
+/// First `/mask` block inside `spaces` that overlaps none of `used`.
+///
+/// Address spaces are searched in ascending order; within each, the lowest
+/// aligned free block wins.
+pub fn find_free_block(spaces: &[Ipv4], used: &[Ipv4], mask: u8) -> Option<Ipv4> {
+    if mask > MAX_LENGTH {
+        return None;
+    }
+    let size = block_size(mask);
+    let mut used: Vec<(u64, u64)> = used.iter().map(|u| u.range()).collect();
+    used.sort_unstable();
+    let mut spaces = spaces.to_vec();
+    spaces.sort();
+
+    for space in spaces.iter().filter(|s| s.mask <= mask) {
+        let start = u64::from(u32::from(space.lo()));
+        let end = start + block_size(space.mask);
+        let mut candidate = start;
+        for &(lo, hi) in used.iter().filter(|(lo, hi)| *hi > start && *lo < end) {
+            if candidate + size <= lo {
+                break;
+            }
+            // Next aligned address after this used range.
+            candidate = candidate.max(hi.div_ceil(size) * size);
+        }
+        if candidate + size <= end {
+            return Some(Ipv4 {
+                addr: Ipv4Addr::from(candidate as u32),
+                mask,
+            });
+        }
+    }
+    None
+}
+
+/// A free CIDR suggested for a new subnet in a VNet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubnetSuggestion {
+    pub vnet_name: String,
+    /// Full ARM resource ID of the VNet (empty for old caches).
+    pub vnet_id: String,
+    /// Resource group of the VNet (empty for old caches).
+    pub resource_group: String,
+    pub subscription_id: String,
+    pub subscription_name: String,
+    /// VNet address space the suggestion was taken from.
+    pub vnet_cidr: Ipv4,
+    pub cidr: Ipv4,
+}
+
+/// Subnets of VNet `vnet_name`, matched case-insensitively.
+///
+/// When the name exists in more than one subscription, `subscription` (ID or
+/// name) must pick one.
+pub(crate) fn vnet_rows<'a>(
+    data: &'a Data,
+    vnet_name: &str,
+    subscription: Option<&str>,
+) -> Result<Vec<&'a Subnet>, Box<dyn Error>> {
+    let rows: Vec<&Subnet> = data
+        .data
+        .iter()
+        .filter(|s| s.vnet_name.eq_ignore_ascii_case(vnet_name))
+        .filter(|s| {
+            subscription.is_none_or(|sub| {
+                s.subscription_id.eq_ignore_ascii_case(sub)
+                    || s.subscription_name.eq_ignore_ascii_case(sub)
+            })
+        })
+        .collect();
+    let first = rows
+        .first()
+        .ok_or_else(|| format!("VNet '{vnet_name}' not found"))?;
+    if let Some(other) = rows
+        .iter()
+        .find(|s| s.subscription_id != first.subscription_id)
+    {
+        return Err(format!(
+            "VNet '{vnet_name}' exists in subscriptions '{}' and '{}' — pick one with --subscription",
+            first.subscription_name, other.subscription_name
+        )
+        .into());
+    }
+    Ok(rows)
+}
+
+/// Suggest the first free `/mask` CIDR in VNet `vnet_name`, outside the
+/// `buffers` (intentional gaps).
+///
+/// The VNet name is matched case-insensitively. When the name exists in more
+/// than one subscription, `subscription` (ID or name) must pick one.
+pub fn suggest_subnet(
+    data: &Data,
+    vnet_name: &str,
+    subscription: Option<&str>,
+    mask: u8,
+    buffers: &[Ipv4],
+) -> Result<SubnetSuggestion, Box<dyn Error>> {
+    let rows = vnet_rows(data, vnet_name, subscription)?;
+    let first = rows[0];
+
+    let mut spaces: Vec<Ipv4> = rows.iter().map(|s| s.vnet_cidr).collect();
+    spaces.sort();
+    spaces.dedup();
+    let used: Vec<Ipv4> = rows
+        .iter()
+        .filter_map(|s| s.subnet_cidr)
+        .chain(buffers.iter().copied())
+        .collect();
+    let cidr = find_free_block(&spaces, &used, mask)
+        .ok_or_else(|| format!("No free /{mask} left in VNet '{vnet_name}'"))?;
+    let vnet_cidr = *spaces
+        .iter()
+        .find(|s| s.contains(cidr.addr))
+        .expect("free block comes from one of the address spaces");
+
+    Ok(SubnetSuggestion {
+        vnet_name: first.vnet_name.clone(),
+        vnet_id: first.vnet_id.clone(),
+        resource_group: first.resource_group.clone(),
+        subscription_id: first.subscription_id.clone(),
+        subscription_name: first.subscription_name.clone(),
+        vnet_cidr,
+        cidr,
+    })
+}
+
+/// Render a split plan as terminal lines.
+pub fn format_split_plan(plan: &SplitPlan) -> String {
+    let mut lines = vec![format!(
+        "Split plan for {} ({} subnet(s)):",
+        plan.space,
+        plan.allocations.len()
+    )];
+    lines.extend(plan.allocations.iter().map(|a| format!("  {a}")));
+    if !plan.buffers.is_empty() {
+        lines.push("Buffers (kept free):".to_string());
+        lines.extend(plan.buffers.iter().map(|b| format!("  {b}")));
+    }
+    if plan.leftover.is_empty() {
+        lines.push("Leftover: none".to_string());
+    } else {
+        let total: u64 = plan.leftover.iter().map(|b| block_size(b.mask)).sum();
+        lines.push(format!("Leftover: {total} address(es)"));
+        lines.extend(plan.leftover.iter().map(|b| format!("  {b}")));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidrs(v: &[Ipv4]) -> Vec<String> {
+        v.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_size_mix_accepts_common_spellings() {
+        let expected = vec![
+            SizeRequest { count: 2, mask: 26 },
+            SizeRequest { count: 4, mask: 28 },
+        ];
+        assert_eq!(parse_size_mix("2x26,4x28").unwrap(), expected);
+        assert_eq!(parse_size_mix("2×/26 + 4×/28").unwrap(), expected);
+        assert_eq!(
+            parse_size_mix("/27").unwrap(),
+            vec![SizeRequest { count: 1, mask: 27 }]
+        );
+        assert!(parse_size_mix("2x33").is_err());
+        assert!(parse_size_mix("").is_err());
+    }
+
+    #[test]
+    fn split_equal_rounds_up_to_power_of_two() {
+        let plan = split_equal(Ipv4::new("10.0.0.0/24").unwrap(), 3, &[]).unwrap();
+        assert_eq!(
+            cidrs(&plan.allocations),
+            vec!["10.0.0.0/26", "10.0.0.64/26", "10.0.0.128/26"]
+        );
+        assert_eq!(cidrs(&plan.leftover), vec!["10.0.0.192/26"]);
+    }
+
+    #[test]
+    fn split_sizes_places_largest_first_and_reports_leftover() {
+        let sizes = parse_size_mix("4x28,2x26").unwrap();
+        let plan = split_sizes(Ipv4::new("10.0.0.0/24").unwrap(), &sizes, &[]).unwrap();
+        assert_eq!(
+            cidrs(&plan.allocations),
+            vec![
+                "10.0.0.0/26",
+                "10.0.0.64/26",
+                "10.0.0.128/28",
+                "10.0.0.144/28",
+                "10.0.0.160/28",
+                "10.0.0.176/28",
+            ]
+        );
+        assert_eq!(cidrs(&plan.leftover), vec!["10.0.0.192/26"]);
+    }
+
+    #[test]
+    fn splits_leave_buffers_free() {
+        let space = Ipv4::new("10.0.0.0/24").unwrap();
+        let buffers = [Ipv4::new("10.0.0.64/26").unwrap()];
+        let plan = split_equal(space, 3, &buffers).unwrap();
+        assert_eq!(
+            cidrs(&plan.allocations),
+            vec!["10.0.0.0/26", "10.0.0.128/26", "10.0.0.192/26"]
+        );
+        assert!(plan.leftover.is_empty());
+        assert_eq!(cidrs(&plan.buffers), vec!["10.0.0.64/26"]);
+        assert!(format_split_plan(&plan).contains("Buffers (kept free):\n  10.0.0.64/26"));
+
+        let plan = split_sizes(space, &parse_size_mix("1x25").unwrap(), &buffers).unwrap();
+        assert_eq!(cidrs(&plan.allocations), vec!["10.0.0.128/25"]);
+        assert_eq!(cidrs(&plan.leftover), vec!["10.0.0.0/26"]);
+        assert!(split_equal(space, 4, &buffers).is_err());
+
+        // 192 addresses are free, but no aligned /25 clears the buffers.
+        let buffers = [
+            Ipv4::new("10.0.0.32/27").unwrap(),
+            Ipv4::new("10.0.0.160/27").unwrap(),
+        ];
+        assert!(split_sizes(space, &parse_size_mix("1x25").unwrap(), &buffers).is_err());
+    }
+
+    #[test]
+    fn split_sizes_rejects_requests_that_do_not_fit() {
+        let space = Ipv4::new("10.0.0.0/26").unwrap();
+        assert!(split_sizes(space, &parse_size_mix("2x26").unwrap(), &[]).is_err());
+        assert!(split_sizes(space, &parse_size_mix("1x25").unwrap(), &[]).is_err());
+        assert!(split_equal(space, 0, &[]).is_err());
+
+        let everything = Ipv4::new("0.0.0.0/0").unwrap();
+        assert!(split_equal(everything, u32::MAX, &[]).is_err());
+        assert!(split_sizes(everything, &parse_size_mix("4096x32,1x32").unwrap(), &[]).is_err());
+        assert_eq!(
+            split_equal(everything, 4096, &[])
+                .unwrap()
+                .allocations
+                .len(),
+            4096
+        );
+    }
+
+    #[test]
+    fn cover_range_uses_fewest_aligned_blocks() {
+        let lo = u64::from(u32::from(Ipv4Addr::new(10, 0, 0, 16)));
+        let hi = u64::from(u32::from(Ipv4Addr::new(10, 0, 1, 0)));
+        assert_eq!(
+            cidrs(&cover_range(lo, hi)),
+            vec![
+                "10.0.0.16/28",
+                "10.0.0.32/27",
+                "10.0.0.64/26",
+                "10.0.0.128/25"
+            ]
+        );
+    }
+
+    #[test]
+    fn find_free_block_skips_used_ranges_and_keeps_alignment() {
+        let spaces = [Ipv4::new("10.0.0.0/24").unwrap()];
+        let used = [
+            Ipv4::new("10.0.0.0/27").unwrap(),
+            Ipv4::new("10.0.0.64/28").unwrap(),
+        ];
+        assert_eq!(
+            find_free_block(&spaces, &used, 27),
+            Some(Ipv4::new("10.0.0.32/27").unwrap())
+        );
+        assert_eq!(
+            find_free_block(&spaces, &used, 26),
+            Some(Ipv4::new("10.0.0.128/26").unwrap())
+        );
+        assert_eq!(find_free_block(&spaces, &used, 24), None);
+    }
+
+    #[test]
+    fn find_free_block_moves_to_next_address_space_when_full() {
+        let spaces = [
+            Ipv4::new("10.1.0.0/24").unwrap(),
+            Ipv4::new("10.0.0.0/25").unwrap(),
+        ];
+        let used = [Ipv4::new("10.0.0.0/25").unwrap()];
+        assert_eq!(
+            find_free_block(&spaces, &used, 26),
+            Some(Ipv4::new("10.1.0.0/26").unwrap())
+        );
+    }
+
+    fn make_subnet(sub: &str, vnet: &str, vnet_cidr: &str, cidr: &str) -> crate::models::Subnet {
+        crate::models::Subnet {
+            subscription_id: sub.to_string(),
+            subscription_name: format!("{sub}-name"),
+            vnet_id: format!("/subscriptions/{sub}/virtualNetworks/{vnet}"),
+            resource_group: format!("rg-{vnet}"),
+            vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+            ..crate::models::Subnet::test(vnet, "", cidr)
+        }
+    }
+
+    #[test]
+    fn suggest_subnet_finds_free_cidr_in_named_vnet() {
+        let data = Data {
+            data: vec![
+                make_subnet("s1", "hub", "10.0.0.0/24", "10.0.0.0/26"),
+                make_subnet("s1", "spoke", "10.0.1.0/24", "10.0.1.0/26"),
+            ],
+            ..Default::default()
+        };
+        let s = suggest_subnet(&data, "HUB", None, 27, &[]).unwrap();
+        assert_eq!(s.vnet_name, "hub");
+        assert_eq!(s.vnet_id, "/subscriptions/s1/virtualNetworks/hub");
+        assert_eq!(s.resource_group, "rg-hub");
+        assert_eq!(s.cidr, Ipv4::new("10.0.0.64/27").unwrap());
+        assert_eq!(s.vnet_cidr, Ipv4::new("10.0.0.0/24").unwrap());
+        let buffers = [Ipv4::new("10.0.0.64/26").unwrap()];
+        let s = suggest_subnet(&data, "hub", None, 27, &buffers).unwrap();
+        assert_eq!(s.cidr, Ipv4::new("10.0.0.128/27").unwrap());
+        assert!(suggest_subnet(&data, "missing", None, 27, &[]).is_err());
+    }
+
+    #[test]
+    fn suggest_subnet_requires_subscription_for_ambiguous_vnet_name() {
+        let data = Data {
+            data: vec![
+                make_subnet("s1", "hub", "10.0.0.0/24", "10.0.0.0/26"),
+                make_subnet("s2", "hub", "10.9.0.0/24", "10.9.0.0/26"),
+            ],
+            ..Default::default()
+        };
+        assert!(suggest_subnet(&data, "hub", None, 26, &[]).is_err());
+        let s = suggest_subnet(&data, "hub", Some("s2-name"), 26, &[]).unwrap();
+        assert_eq!(s.cidr, Ipv4::new("10.9.0.64/26").unwrap());
+    }
+
+    #[test]
+    fn format_split_plan_lists_allocations_and_leftover() {
+        let plan = split_equal(Ipv4::new("10.0.0.0/24").unwrap(), 3, &[]).unwrap();
+        let out = format_split_plan(&plan);
+        assert!(out.starts_with("Split plan for 10.0.0.0/24 (3 subnet(s)):"));
+        assert!(out.contains("Leftover: 64 address(es)\n  10.0.0.192/26"));
+    }
+}
@@ -0,0 +1,228 @@
+//! Anonymized copies of subnet data for sharing and test fixtures.
+//!
+//! Names, resource groups and subscriptions are replaced with random
+//! pseudonyms; every distinct original value maps to the same pseudonym, so
+//! VNet/subnet relationships survive. CIDRs, DNS servers, locations,
+//! delegations and usage counts are kept as-is, preserving the address
+//! topology the analysis depends on. Reserved platform subnet names
+//! (`GatewaySubnet`, `AzureFirewallSubnet`, ...) are kept too.
+
+use super::role::{subnet_role, SubnetRole};
+use crate::azure::Data;
+use crate::models::Subnet;
+use std::collections::HashMap;
+
+/// Small deterministic PRNG (SplitMix64) — fixtures are reproducible per seed
+/// without pulling in a random number crate.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Maps original values to pseudonyms, one table per kind of value.
+struct Pseudonyms {
+    rng: SplitMix64,
+    seen: HashMap<(&'static str, String), String>,
+}
+
+impl Pseudonyms {
+    /// Pseudonym `<prefix>-<6 hex>` for `original`, stable within one run.
+    /// Matching is case-insensitive, like Azure resource names.
+    fn name(&mut self, prefix: &'static str, original: &str) -> String {
+        let rng = &mut self.rng;
+        self.seen
+            .entry((prefix, original.to_lowercase()))
+            .or_insert_with(|| format!("{prefix}-{:06x}", rng.next() >> 40))
+            .clone()
+    }
+
+    /// Random GUID-formatted subscription ID for `original`.
+    fn subscription_id(&mut self, original: &str) -> String {
+        let rng = &mut self.rng;
+        self.seen
+            .entry(("subscription-id", original.to_lowercase()))
+            .or_insert_with(|| {
+                let (a, b) = (rng.next(), rng.next());
+                format!(
+                    "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+                    a >> 32,
+                    (a >> 16) & 0xffff,
+                    a & 0xffff,
+                    b >> 48,
+                    b & 0xffff_ffff_ffff
+                )
+            })
+            .clone()
+    }
+}
+
+/// Last segment of an ARM resource ID (the resource name).
+fn id_name(id: &str) -> &str {
+    id.rsplit('/').next().unwrap_or_default()
+}
+
+/// ARM ID of a `Microsoft.Network/<kind>/<name>` resource.
+fn network_id(sub: &str, rg: &str, kind: &str, name: &str) -> String {
+    format!("/subscriptions/{sub}/resourceGroups/{rg}/providers/Microsoft.Network/{kind}/{name}")
+}
+
+fn anonymize_subnet(s: &Subnet, p: &mut Pseudonyms) -> Subnet {
+    let subscription_id = p.subscription_id(&s.subscription_id);
+    let subscription_name = p.name("sub", &s.subscription_name);
+    let resource_group = if s.resource_group.is_empty() {
+        String::new()
+    } else {
+        p.name("rg", &s.resource_group)
+    };
+    let vnet_name = p.name("vnet", &s.vnet_name);
+    let keep_name = subnet_role(s).is_some_and(|r| r != SubnetRole::ApplicationGateway);
+    let subnet_name = if keep_name {
+        s.subnet_name.clone()
+    } else {
+        p.name("snet", &s.subnet_name)
+    };
+    let vnet_id = if s.vnet_id.is_empty() {
+        String::new()
+    } else {
+        network_id(
+            &subscription_id,
+            &resource_group,
+            "virtualNetworks",
+            &vnet_name,
+        )
+    };
+    let subnet_id = if s.subnet_id.is_empty() || vnet_id.is_empty() {
+        String::new()
+    } else {
+        format!("{vnet_id}/subnets/{subnet_name}")
+    };
+    // An empty NSG string means "none" in some caches; keep it that way.
+    let nsg = s.nsg.as_deref().map(|id| {
+        if id.is_empty() {
+            return String::new();
+        }
+        let name = p.name("nsg", id_name(id));
+        network_id(
+            &subscription_id,
+            &resource_group,
+            "networkSecurityGroups",
+            &name,
+        )
+    });
+    let nat_gateway = s.nat_gateway.as_deref().map(|id| {
+        let name = p.name("ngw", id_name(id));
+        network_id(&subscription_id, &resource_group, "natGateways", &name)
+    });
+    let mut load_balancers: Vec<String> =
+        s.load_balancers.iter().map(|lb| p.name("lb", lb)).collect();
+    load_balancers.sort();
+
+    Subnet {
+        resource_group,
+        vnet_id,
+        vnet_name,
+        subnet_id,
+        subnet_name,
+        nsg,
+        nat_gateway,
+        load_balancers,
+        subscription_id,
+        subscription_name,
+        ..s.clone()
+    }
+}
+
+/// Return an anonymized copy of `data`; the same `seed` gives the same output.
+pub fn anonymize(data: &Data, seed: u64) -> Data {
+    let mut p = Pseudonyms {
+        rng: SplitMix64(seed),
+        seen: HashMap::new(),
+    };
+    Data {
+        data: data
+            .data
+            .iter()
+            .map(|s| anonymize_subnet(s, &mut p))
+            .collect(),
+        skip_token: None,
+        total_records: data.total_records,
+        count: data.count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::read_subnet_cache;
+
+    #[test]
+    fn names_and_ids_are_replaced_but_topology_is_kept() {
+        let data = read_subnet_cache(Some("src/tests/test_data/subnet_test_cache_03.json"))
+            .expect("fixture");
+        let anon = anonymize(&data, 42);
+        assert_eq!(anon.data.len(), data.data.len());
+
+        let json = serde_json::to_string(&anon).unwrap();
+        for s in &data.data {
+            assert!(!json.contains(&s.vnet_name), "leaked '{}'", s.vnet_name);
+            assert!(!json.contains(&s.subscription_id));
+        }
+        for (orig, new) in data.data.iter().zip(&anon.data) {
+            assert_eq!(orig.subnet_cidr, new.subnet_cidr);
+            assert_eq!(orig.vnet_cidr, new.vnet_cidr);
+            assert_eq!(orig.location, new.location);
+        }
+        // Subnets of one VNet still share the (new) VNet name.
+        let vnets = |d: &Data| {
+            let mut v: Vec<String> = d.data.iter().map(|s| s.vnet_name.clone()).collect();
+            v.dedup();
+            v.len()
+        };
+        assert_eq!(vnets(&data), vnets(&anon));
+
+        // Reproducible per seed.
+        let again = serde_json::to_string(&anonymize(&data, 42)).unwrap();
+        assert_eq!(json, again);
+        assert_ne!(json, serde_json::to_string(&anonymize(&data, 7)).unwrap());
+    }
+
+    #[test]
+    fn reserved_subnet_names_and_resource_ids_stay_consistent() {
+        let mut gw = Subnet {
+            subscription_id: "11111111-2222-3333-4444-555555555555".to_string(),
+            resource_group: "corp-net-rg".to_string(),
+            vnet_id: "/subscriptions/x/resourceGroups/corp-net-rg/providers/Microsoft.Network/virtualNetworks/corp-hub".to_string(),
+            ..Subnet::test("corp-hub", "GatewaySubnet", "")
+        };
+        gw.subnet_id = format!("{}/subnets/GatewaySubnet", gw.vnet_id);
+        let mut app = gw.clone();
+        app.subnet_name = "corp-app".to_string();
+        app.nsg = Some("/subscriptions/x/resourceGroups/corp-net-rg/providers/Microsoft.Network/networkSecurityGroups/corp-app-nsg".to_string());
+
+        let anon = anonymize(
+            &Data {
+                data: vec![gw, app],
+                ..Default::default()
+            },
+            1,
+        );
+        let (gw, app) = (&anon.data[0], &anon.data[1]);
+        assert_eq!(gw.subnet_name, "GatewaySubnet");
+        assert!(app.subnet_name.starts_with("snet-"));
+        assert_eq!(gw.vnet_name, app.vnet_name);
+        assert_eq!(
+            app.subnet_id,
+            format!("{}/subnets/{}", app.vnet_id, app.subnet_name)
+        );
+        assert!(app.vnet_id.contains(&app.subscription_id));
+        assert!(app.vnet_id.contains(&app.resource_group));
+        assert!(!app.nsg.as_deref().unwrap().contains("corp"));
+    }
+}
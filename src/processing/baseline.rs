@@ -0,0 +1,374 @@
+//! Drift against an approved ("golden") address plan file.
+//!
+//! `diff` compares two snapshots, so whatever changed yesterday becomes
+//! today's normal. A baseline is curated instead: exported once from the
+//! estate (`drift --export`), reviewed, trimmed and committed to git, and
+//! every later run is compared with it until a reviewed change updates the
+//! file. Only VNets, their address space and subnet prefixes are recorded —
+//! the part of the estate an address plan approves.
+//!
+//! VNets are matched by subscription ID and name, subnets by name and then
+//! the leftovers by prefix, so a subnet recreated under a new name is
+//! reported as renamed.
+
+use crate::azure::Data;
+use crate::models::{Ipv4, SubnetSource};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+/// Header written above an exported baseline.
+const BASELINE_HEADER: &str = "\
+# Approved address plan, compared by `azure-subnet-summary drift`.
+# Exported from the estate with `drift --export`; review it, commit it, and
+# change it only through reviewed pull requests.
+";
+
+/// The approved address plan.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    #[serde(default, rename = "vnet")]
+    pub vnets: Vec<BaselineVnet>,
+}
+
+/// An approved VNet.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BaselineVnet {
+    pub subscription_id: String,
+    /// Subscription display name, for readers; matching uses the ID.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subscription: String,
+    pub name: String,
+    pub address_space: Vec<Ipv4>,
+    #[serde(default, rename = "subnet")]
+    pub subnets: Vec<BaselineSubnet>,
+}
+
+/// An approved subnet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BaselineSubnet {
+    pub name: String,
+    pub cidr: Ipv4,
+}
+
+impl BaselineVnet {
+    fn key(&self) -> (String, String) {
+        (
+            self.subscription_id.to_lowercase(),
+            self.name.to_lowercase(),
+        )
+    }
+}
+
+impl Baseline {
+    /// The Azure VNets and subnets of `data` (reserved, on-prem and planned
+    /// records are left out), sorted by subscription and VNet name.
+    pub fn from_data(data: &Data) -> Baseline {
+        let mut vnets: BTreeMap<(String, String, String), BaselineVnet> = BTreeMap::new();
+        for s in data.data.iter().filter(|s| s.source == SubnetSource::Azure) {
+            let vnet = vnets
+                .entry((
+                    s.subscription_name.to_lowercase(),
+                    s.subscription_id.to_lowercase(),
+                    s.vnet_name.to_lowercase(),
+                ))
+                .or_insert_with(|| BaselineVnet {
+                    subscription_id: s.subscription_id.clone(),
+                    subscription: s.subscription_name.clone(),
+                    name: s.vnet_name.clone(),
+                    address_space: if s.vnet_address_space.is_empty() {
+                        vec![s.vnet_cidr]
+                    } else {
+                        s.vnet_address_space.clone()
+                    },
+                    subnets: Vec::new(),
+                });
+            if let Some(cidr) = s.subnet_cidr {
+                vnet.subnets.push(BaselineSubnet {
+                    name: s.subnet_name.clone(),
+                    cidr,
+                });
+            }
+        }
+        let mut vnets: Vec<BaselineVnet> = vnets.into_values().collect();
+        for vnet in &mut vnets {
+            vnet.subnets.sort_by_key(|s| s.cidr);
+            vnet.subnets.dedup();
+        }
+        Baseline { vnets }
+    }
+
+    /// Parse a baseline file's TOML.
+    pub fn parse(toml_text: &str) -> Result<Baseline, Box<dyn Error>> {
+        Ok(toml::from_str(toml_text).map_err(|e| format!("Invalid baseline: {e}"))?)
+    }
+
+    /// Read the baseline file at `path`.
+    pub fn load(path: &Path) -> Result<Baseline, Box<dyn Error>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error reading baseline {}: {e}", path.display()))?;
+        Baseline::parse(&text).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    /// The baseline as commented TOML, ready to commit.
+    pub fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        let body = toml::to_string(self).map_err(|e| format!("Error writing baseline: {e}"))?;
+        Ok(format!("{BASELINE_HEADER}\n{body}"))
+    }
+}
+
+/// How the estate departs from the approved plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BaselineDriftKind {
+    /// An approved VNet is not in Azure.
+    MissingVnet,
+    /// A VNet in Azure is not in the plan.
+    UnapprovedVnet,
+    /// The VNet address space differs; carries the approved prefixes.
+    AddressSpace { approved: Vec<Ipv4> },
+    /// An approved subnet is not in Azure.
+    MissingSubnet,
+    /// A subnet in Azure is not in the plan.
+    UnapprovedSubnet,
+    /// The subnet has a different prefix; carries the approved one.
+    Prefix { approved: Ipv4 },
+    /// The approved prefix is used under another name; carries the approved
+    /// name.
+    Renamed { approved: String },
+}
+
+impl fmt::Display for BaselineDriftKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaselineDriftKind::MissingVnet => f.write_str("approved VNet not found"),
+            BaselineDriftKind::UnapprovedVnet => f.write_str("VNet not in the approved plan"),
+            BaselineDriftKind::AddressSpace { approved } => {
+                let approved: Vec<String> = approved.iter().map(|p| p.to_string()).collect();
+                write!(f, "address space differs, approved {}", approved.join(", "))
+            }
+            BaselineDriftKind::MissingSubnet => f.write_str("approved subnet not found"),
+            BaselineDriftKind::UnapprovedSubnet => f.write_str("subnet not in the approved plan"),
+            BaselineDriftKind::Prefix { approved } => {
+                write!(f, "prefix differs, approved {approved}")
+            }
+            BaselineDriftKind::Renamed { approved } => {
+                write!(f, "approved as '{approved}'")
+            }
+        }
+    }
+}
+
+/// One difference between the estate and the approved plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaselineDrift {
+    pub kind: BaselineDriftKind,
+    pub subscription: String,
+    pub vnet_name: String,
+    /// Set for subnet drift.
+    pub subnet_name: Option<String>,
+    /// Actual prefixes (approved ones when missing from Azure).
+    pub prefixes: Vec<Ipv4>,
+}
+
+fn sorted(prefixes: &[Ipv4]) -> Vec<Ipv4> {
+    let mut prefixes = prefixes.to_vec();
+    prefixes.sort();
+    prefixes
+}
+
+/// Subnet drift within one VNet present in both the plan and Azure.
+fn subnet_drift(approved: &BaselineVnet, actual: &BaselineVnet, drift: &mut Vec<BaselineDrift>) {
+    let item = |kind, subnet: &BaselineSubnet| BaselineDrift {
+        kind,
+        subscription: actual.subscription.clone(),
+        vnet_name: actual.name.clone(),
+        subnet_name: Some(subnet.name.clone()),
+        prefixes: vec![subnet.cidr],
+    };
+    let mut unmatched: Vec<&BaselineSubnet> = Vec::new();
+    let mut remaining: Vec<&BaselineSubnet> = actual.subnets.iter().collect();
+    for a in &approved.subnets {
+        match remaining
+            .iter()
+            .position(|s| s.name.eq_ignore_ascii_case(&a.name))
+        {
+            Some(i) => {
+                let s = remaining.remove(i);
+                if s.cidr != a.cidr {
+                    drift.push(item(BaselineDriftKind::Prefix { approved: a.cidr }, s));
+                }
+            }
+            None => unmatched.push(a),
+        }
+    }
+    for a in unmatched {
+        match remaining.iter().position(|s| s.cidr == a.cidr) {
+            Some(i) => {
+                let s = remaining.remove(i);
+                let kind = BaselineDriftKind::Renamed {
+                    approved: a.name.clone(),
+                };
+                drift.push(item(kind, s));
+            }
+            None => drift.push(item(BaselineDriftKind::MissingSubnet, a)),
+        }
+    }
+    for s in remaining {
+        drift.push(item(BaselineDriftKind::UnapprovedSubnet, s));
+    }
+}
+
+/// Differences between the approved `baseline` and the estate in `data`,
+/// sorted by subscription, VNet and subnet.
+pub fn baseline_drift(baseline: &Baseline, data: &Data) -> Vec<BaselineDrift> {
+    let mut actual: BTreeMap<(String, String), BaselineVnet> = Baseline::from_data(data)
+        .vnets
+        .into_iter()
+        .map(|v| (v.key(), v))
+        .collect();
+    let mut drift = Vec::new();
+    for approved in &baseline.vnets {
+        let Some(vnet) = actual.remove(&approved.key()) else {
+            drift.push(BaselineDrift {
+                kind: BaselineDriftKind::MissingVnet,
+                subscription: approved.subscription.clone(),
+                vnet_name: approved.name.clone(),
+                subnet_name: None,
+                prefixes: approved.address_space.clone(),
+            });
+            continue;
+        };
+        if sorted(&approved.address_space) != sorted(&vnet.address_space) {
+            drift.push(BaselineDrift {
+                kind: BaselineDriftKind::AddressSpace {
+                    approved: approved.address_space.clone(),
+                },
+                subscription: vnet.subscription.clone(),
+                vnet_name: vnet.name.clone(),
+                subnet_name: None,
+                prefixes: vnet.address_space.clone(),
+            });
+        }
+        subnet_drift(approved, &vnet, &mut drift);
+    }
+    drift.extend(actual.into_values().map(|vnet| BaselineDrift {
+        kind: BaselineDriftKind::UnapprovedVnet,
+        subscription: vnet.subscription,
+        vnet_name: vnet.name,
+        subnet_name: None,
+        prefixes: vnet.address_space,
+    }));
+    drift.sort_by(|a, b| {
+        (&a.subscription, &a.vnet_name, &a.subnet_name).cmp(&(
+            &b.subscription,
+            &b.vnet_name,
+            &b.subnet_name,
+        ))
+    });
+    drift
+}
+
+/// Render drift as terminal lines.
+pub fn format_baseline_drift(drift: &[BaselineDrift]) -> String {
+    if drift.is_empty() {
+        return "No drift from the approved address plan.".to_string();
+    }
+    drift
+        .iter()
+        .map(|d| {
+            let name = match &d.subnet_name {
+                Some(subnet) => format!("{}/{subnet}", d.vnet_name),
+                None => d.vnet_name.clone(),
+            };
+            let prefixes: Vec<String> = d.prefixes.iter().map(|p| p.to_string()).collect();
+            format!(
+                "DRIFT: '{name}' {} [{}] - {}",
+                prefixes.join(", "),
+                d.subscription,
+                d.kind
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    fn subnet(vnet: &str, name: &str, cidr: &str) -> Subnet {
+        let mut s = Subnet {
+            subscription_id: "sub-1".to_string(),
+            subscription_name: "Prod".to_string(),
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            ..Subnet::test(vnet, name, cidr)
+        };
+        s.vnet_address_space = vec![s.vnet_cidr];
+        s
+    }
+
+    fn data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exported_baseline_round_trips_and_reports_drift() {
+        let mut reserved = subnet("hub", "future", "10.0.9.0/24");
+        reserved.source = SubnetSource::Reserved;
+        let approved = data(vec![
+            subnet("hub", "app", "10.0.1.0/24"),
+            subnet("hub", "db", "10.0.2.0/24"),
+            subnet("hub", "old-web", "10.0.3.0/24"),
+            subnet("hub", "legacy", "10.0.4.0/24"),
+            subnet("retired", "app", "10.1.0.0/24"),
+            reserved,
+        ]);
+        let baseline = Baseline::from_data(&approved);
+        assert_eq!(baseline.vnets.len(), 2);
+        assert_eq!(baseline.vnets[0].subnets.len(), 4);
+        let text = baseline.to_toml().unwrap();
+        assert!(text.starts_with("# Approved address plan"), "{text}");
+        assert!(text.contains("[[vnet.subnet]]"), "{text}");
+        assert_eq!(Baseline::parse(&text).unwrap(), baseline);
+        assert!(baseline_drift(&baseline, &approved).is_empty());
+        assert!(Baseline::parse("[[vnet]]\nname = 1").is_err());
+
+        let mut grown = subnet("hub", "app", "10.0.1.0/24");
+        grown
+            .vnet_address_space
+            .push(Ipv4::new("10.2.0.0/16").unwrap());
+        let now = data(vec![
+            grown,
+            subnet("hub", "db", "10.0.2.0/23"),
+            subnet("hub", "web", "10.0.3.0/24"),
+            subnet("hub", "experiment", "10.0.5.0/24"),
+            subnet("spoke", "app", "10.3.0.0/24"),
+        ]);
+        let out = format_baseline_drift(&baseline_drift(&baseline, &now));
+        assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            [
+                "DRIFT: 'hub' 10.0.0.0/16, 10.2.0.0/16 [Prod] - address space differs, approved 10.0.0.0/16",
+                "DRIFT: 'hub/db' 10.0.2.0/23 [Prod] - prefix differs, approved 10.0.2.0/24",
+                "DRIFT: 'hub/experiment' 10.0.5.0/24 [Prod] - subnet not in the approved plan",
+                "DRIFT: 'hub/legacy' 10.0.4.0/24 [Prod] - approved subnet not found",
+                "DRIFT: 'hub/web' 10.0.3.0/24 [Prod] - approved as 'old-web'",
+                "DRIFT: 'retired' 10.0.0.0/16 [Prod] - approved VNet not found",
+                "DRIFT: 'spoke' 10.0.0.0/16 [Prod] - VNet not in the approved plan",
+            ]
+        );
+        assert_eq!(
+            format_baseline_drift(&[]),
+            "No drift from the approved address plan."
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Intentional gaps (growth buffers).
+//!
+//! Some unallocated space is kept free on purpose, e.g. room for a VNet or
+//! subnet to grow into. Buffer CIDRs (`--buffer`, `buffer = [...]` in the
+//! config file) turn the gap rows they cover into `-buffer-` rows (between
+//! VNets) and `-vbuffer-` rows (inside a VNet), which are shown but not
+//! counted as free space; `suggest`, `split` and `simulate` do not allocate
+//! from them either.
+
+use super::allocator::cover_range;
+use super::gap_finder::SubnetPrintRow;
+use crate::models::{num_az_hosts, Ipv4};
+
+/// Row kind of a buffer between VNets (in place of `-gap-`).
+pub const BUFFER: &str = "-buffer-";
+/// Row kind of a buffer inside a VNet (in place of `-vgap-`).
+pub const VNET_BUFFER: &str = "-vbuffer-";
+
+/// `true` for rows marking an intentional gap.
+pub fn is_buffer(row: &SubnetPrintRow) -> bool {
+    row.gap == BUFFER || row.gap == VNET_BUFFER
+}
+
+/// Parse a `--buffer` CIDR.
+pub fn parse_buffer(s: &str) -> Result<Ipv4, String> {
+    Ipv4::new(s).map_err(|e| e.to_string())
+}
+
+/// Copy of the gap `row` for the sub-block `cidr`, labelled `gap`.
+fn gap_piece(row: &SubnetPrintRow, cidr: Ipv4, gap: &str) -> SubnetPrintRow {
+    SubnetPrintRow {
+        gap: gap.to_string(),
+        subnet_cidr: cidr.to_string(),
+        broadcast: cidr
+            .broadcast()
+            .map(|b| b.addr.to_string())
+            .unwrap_or_default(),
+        az_hosts: num_az_hosts(cidr.mask).unwrap_or(0) as usize,
+        ..row.clone()
+    }
+}
+
+/// Relabel the gap rows covered by `buffers`, splitting a gap that is only
+/// partly covered into aligned blocks inside and outside the buffers.
+pub fn mark_buffers(rows: Vec<SubnetPrintRow>, buffers: &[Ipv4]) -> Vec<SubnetPrintRow> {
+    if buffers.is_empty() {
+        return rows;
+    }
+    let mut out = Vec::with_capacity(rows.len());
+    for row in rows {
+        let label = match row.gap.as_str() {
+            "-gap-" => BUFFER,
+            "-vgap-" => VNET_BUFFER,
+            _ => {
+                out.push(row);
+                continue;
+            }
+        };
+        let Ok(cidr) = Ipv4::new(&row.subnet_cidr) else {
+            out.push(row);
+            continue;
+        };
+        let (lo, hi) = cidr.range();
+        // Parts of the gap inside a buffer, clipped, sorted and merged.
+        let mut covered: Vec<(u64, u64)> = buffers
+            .iter()
+            .map(|b| b.range())
+            .filter(|(b_lo, b_hi)| *b_lo < hi && lo < *b_hi)
+            .map(|(b_lo, b_hi)| (b_lo.max(lo), b_hi.min(hi)))
+            .collect();
+        if covered.is_empty() {
+            out.push(row);
+            continue;
+        }
+        covered.sort_unstable();
+        let mut cursor = lo;
+        for (c_lo, c_hi) in covered {
+            if c_hi <= cursor {
+                continue;
+            }
+            let c_lo = c_lo.max(cursor);
+            out.extend(
+                cover_range(cursor, c_lo)
+                    .into_iter()
+                    .map(|b| gap_piece(&row, b, &row.gap)),
+            );
+            out.extend(
+                cover_range(c_lo, c_hi)
+                    .into_iter()
+                    .map(|b| gap_piece(&row, b, label)),
+            );
+            cursor = c_hi;
+        }
+        out.extend(
+            cover_range(cursor, hi)
+                .into_iter()
+                .map(|b| gap_piece(&row, b, &row.gap)),
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+    use crate::output::build_rows;
+
+    #[test]
+    fn gaps_inside_buffers_are_relabelled_and_split() {
+        let mut s = Subnet {
+            vnet_cidr: Ipv4::new("10.0.0.0/24").unwrap(),
+            ..Subnet::test("hub", "app", "10.0.0.0/26")
+        };
+        s.vnet_address_space = vec![s.vnet_cidr];
+        let rows = build_rows(&[s], &[], 4, &[]);
+        let gaps: Vec<(&str, &str)> = rows
+            .iter()
+            .skip(1)
+            .map(|r| (r.gap.as_str(), r.subnet_cidr.as_str()))
+            .collect();
+        assert_eq!(
+            gaps,
+            [("-vgap-", "10.0.0.64/26"), ("-vgap-", "10.0.0.128/25")]
+        );
+
+        let buffers = [parse_buffer("10.0.0.128/26").unwrap()];
+        let marked = mark_buffers(rows, &buffers);
+        let gaps: Vec<(&str, &str)> = marked
+            .iter()
+            .skip(1)
+            .map(|r| (r.gap.as_str(), r.subnet_cidr.as_str()))
+            .collect();
+        assert_eq!(
+            gaps,
+            [
+                ("-vgap-", "10.0.0.64/26"),
+                ("-vbuffer-", "10.0.0.128/26"),
+                ("-vgap-", "10.0.0.192/26"),
+            ]
+        );
+        assert_eq!(marked[2].az_hosts, 59);
+        assert!(is_buffer(&marked[2]) && !is_buffer(&marked[3]));
+    }
+}
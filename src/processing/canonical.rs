@@ -0,0 +1,130 @@
+//! Non-canonical CIDR detection.
+//!
+//! Azure accepts prefixes with host bits set, such as `10.1.2.5/24`, and
+//! keeps them as written. Gap and overlap math assumes network addresses,
+//! so subnet and VNet prefixes are normalized (`10.1.2.0/24`) before
+//! processing, and every original is reported so it can be fixed at source.
+
+use crate::azure::Data;
+use crate::models::Ipv4;
+
+/// A prefix in the data whose address has host bits set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonCanonicalCidr {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    /// Subnet name, or `None` for a VNet address space prefix.
+    pub subnet_name: Option<String>,
+    /// The prefix as found in the data.
+    pub original: Ipv4,
+    /// The prefix with host bits cleared.
+    pub canonical: Ipv4,
+}
+
+/// `cidr` with its host bits cleared.
+pub fn canonical(cidr: Ipv4) -> Ipv4 {
+    Ipv4 {
+        addr: cidr.lo(),
+        mask: cidr.mask,
+    }
+}
+
+/// Normalize every subnet and VNet prefix in `data`, returning the prefixes
+/// that had host bits set. VNet prefixes are reported once per VNet.
+pub fn normalize_cidrs(mut data: Data) -> (Data, Vec<NonCanonicalCidr>) {
+    let mut found: Vec<NonCanonicalCidr> = Vec::new();
+    for s in &mut data.data {
+        for cidr in &mut s.vnet_address_space {
+            let fixed = canonical(*cidr);
+            if fixed == *cidr {
+                continue;
+            }
+            let entry = NonCanonicalCidr {
+                subscription_name: s.subscription_name.clone(),
+                vnet_name: s.vnet_name.clone(),
+                subnet_name: None,
+                original: *cidr,
+                canonical: fixed,
+            };
+            if !found.contains(&entry) {
+                found.push(entry);
+            }
+            *cidr = fixed;
+        }
+        s.vnet_cidr = canonical(s.vnet_cidr);
+        if let Some(cidr) = s.subnet_cidr {
+            let fixed = canonical(cidr);
+            if fixed != cidr {
+                found.push(NonCanonicalCidr {
+                    subscription_name: s.subscription_name.clone(),
+                    vnet_name: s.vnet_name.clone(),
+                    subnet_name: Some(s.subnet_name.clone()),
+                    original: cidr,
+                    canonical: fixed,
+                });
+                s.subnet_cidr = Some(fixed);
+            }
+        }
+    }
+    (data, found)
+}
+
+/// Log prefixes found by [`normalize_cidrs`] as warnings.
+pub fn log_non_canonical(found: &[NonCanonicalCidr]) {
+    for n in found {
+        let what = match n.subnet_name {
+            Some(ref subnet) => format!("Subnet '{subnet}' in VNet '{}'", n.vnet_name),
+            None => format!("VNet '{}' address space", n.vnet_name),
+        };
+        log::warn!(
+            "{what} ('{}') has host bits set in {}; treated as {}",
+            n.subscription_name,
+            n.original,
+            n.canonical
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    #[test]
+    fn host_bits_are_cleared_and_reported() {
+        let subnet = |name: &str, cidr: &str| {
+            let mut s = Subnet {
+                subscription_name: "Prod".to_string(),
+                vnet_cidr: Ipv4::new("10.1.0.7/16").unwrap(),
+                ..Subnet::test("hub", name, cidr)
+            };
+            s.vnet_address_space = vec![s.vnet_cidr];
+            s
+        };
+        let data = Data {
+            data: vec![subnet("app", "10.1.2.5/24"), subnet("db", "10.1.3.0/24")],
+            ..Default::default()
+        };
+        let (data, found) = normalize_cidrs(data);
+        assert_eq!(
+            data.data[0].subnet_cidr,
+            Some(Ipv4::new("10.1.2.0/24").unwrap())
+        );
+        assert_eq!(data.data[1].vnet_cidr, Ipv4::new("10.1.0.0/16").unwrap());
+        assert_eq!(
+            data.data[1].vnet_address_space,
+            [Ipv4::new("10.1.0.0/16").unwrap()]
+        );
+        let found: Vec<(Option<&str>, String)> = found
+            .iter()
+            .map(|n| (n.subnet_name.as_deref(), n.original.to_string()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (None, "10.1.0.7/16".to_string()),
+                (Some("app"), "10.1.2.5/24".to_string()),
+            ]
+        );
+    }
+}
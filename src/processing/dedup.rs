@@ -2,42 +2,38 @@
 //!
 //! Handles removing duplicate and unwanted subnet entries.
 
+use super::filter::IgnoreList;
 use crate::azure::Data;
 use std::error::Error;
 
-/// Default list of subnet names to ignore during processing.
-fn default_subnet_names_to_ignore() -> Vec<&'static str> {
-    vec![
-        "default",
-        "jenkinsarm-snet",
-        "pkrsn1ooslfxj77",
-        "pkrsn8jufz9plf6",
-        "pkrsnsnajtq3h3i",
-        "pkrsnxocivqofa6",
-        "orggmcmg",
-        "restore-vm-subnet",
-    ]
-}
-
 /// De-duplicate subnets by CIDR and subscription ID.
 ///
 /// # Arguments
 /// * `data` - The subnet data to de-duplicate
 /// * `subnet_names_to_ignore` - Optional list of subnet names to filter out
+///   everywhere (default: none)
 ///
 /// # Returns
 /// * `Ok(Data)` - De-duplicated data
 pub fn de_duplicate_subnets(
-    mut data: Data,
+    data: Data,
     subnet_names_to_ignore: Option<&Vec<&str>>,
 ) -> Result<Data, Box<dyn Error>> {
-    let default_ignore_list = default_subnet_names_to_ignore();
-    let subnet_names_to_ignore = subnet_names_to_ignore.unwrap_or(&default_ignore_list);
+    let ignore = subnet_names_to_ignore
+        .map(|names| IgnoreList::from_names(names))
+        .unwrap_or_default();
+    de_duplicate_subnets_ignoring(data, &ignore)
+}
 
-    // Filter out subnets with names that match the ignore list
-    data.data.retain(|s| {
-        !subnet_names_to_ignore.contains(&s.subnet_name.as_str()) && s.subnet_cidr.is_some()
-    });
+/// De-duplicate subnets by CIDR and subscription ID, dropping subnets matched
+/// by `ignore` and subnets without a CIDR.
+pub fn de_duplicate_subnets_ignoring(
+    mut data: Data,
+    ignore: &IgnoreList,
+) -> Result<Data, Box<dyn Error>> {
+    // Filter out subnets matched by the ignore list
+    data.data
+        .retain(|s| !ignore.ignores(s) && s.subnet_cidr.is_some());
 
     // Dedup data.data - must be sorted first
     data.data
@@ -79,9 +75,12 @@ mod tests {
             "Expected 3 subnets before de-duplication"
         );
 
-        let result = de_duplicate_subnets(data, None).expect("Failed to de-duplicate subnets");
-        let result_sorted =
-            de_duplicate_subnets(data_sorted, None).expect("Failed to de-duplicate subnets");
+        // Nothing is ignored by default; the `default` subnet is configured.
+        let filter = vec!["default"];
+        let result =
+            de_duplicate_subnets(data, Some(&filter)).expect("Failed to de-duplicate subnets");
+        let result_sorted = de_duplicate_subnets(data_sorted, Some(&filter))
+            .expect("Failed to de-duplicate subnets");
         assert_eq!(
             result.data.len(),
             1,
@@ -106,10 +105,10 @@ mod tests {
 
         let filter = vec![
             "default",
-            "pkrsn1ooslfxj77",
-            "pkrsnsnajtq3h3i",
-            "pkrsnxocivqofa6",
-            "ORGgmcmg",
+            "tmpsn1ooslfxj77",
+            "tmpsnsnajtq3h3i",
+            "tmpsnxocivqofa6",
+            "LABspace",
         ];
         let result =
             de_duplicate_subnets(data, Some(&filter)).expect("Failed to de-duplicate subnets");
@@ -132,10 +131,10 @@ mod tests {
 
         let filter = vec![
             "default",
-            "pkrsn1ooslfxj77",
-            "pkrsnsnajtq3h3i",
-            "pkrsnxocivqofa6",
-            "orggmcmg",
+            "tmpsn1ooslfxj77",
+            "tmpsnsnajtq3h3i",
+            "tmpsnxocivqofa6",
+            "labspace",
         ];
         let result =
             de_duplicate_subnets(data, Some(&filter)).expect("Failed to de-duplicate subnets");
@@ -0,0 +1,195 @@
+//! App Service and Container Apps integration subnet usage.
+//!
+//! Subnets delegated to App Service VNet integration or Container Apps
+//! environments consume IPs in bursts: platform upgrades and scale-outs bring
+//! up new instances before the old ones are released, so the current
+//! `ip_configurations_count` understates what the subnet must hold.
+
+use crate::azure::Data;
+use crate::models::{num_az_hosts, Ipv4, Subnet};
+use std::fmt;
+
+/// Service that a subnet is delegated to for integration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrationKind {
+    /// App Service / Functions VNet integration (`Microsoft.Web/serverFarms`).
+    AppService,
+    /// Container Apps environment (`Microsoft.App/environments`).
+    ContainerApps,
+}
+
+impl IntegrationKind {
+    /// Delegation service name that marks this integration.
+    fn service_name(self) -> &'static str {
+        match self {
+            IntegrationKind::AppService => "Microsoft.Web/serverFarms",
+            IntegrationKind::ContainerApps => "Microsoft.App/environments",
+        }
+    }
+
+    /// Largest prefix length Microsoft recommends for the subnet.
+    pub fn recommended_mask(self) -> u8 {
+        match self {
+            IntegrationKind::AppService => 26,
+            IntegrationKind::ContainerApps => 27,
+        }
+    }
+
+    fn from_subnet(subnet: &Subnet) -> Option<IntegrationKind> {
+        [IntegrationKind::AppService, IntegrationKind::ContainerApps]
+            .into_iter()
+            .find(|kind| {
+                subnet
+                    .delegations
+                    .iter()
+                    .any(|d| d.eq_ignore_ascii_case(kind.service_name()))
+            })
+    }
+}
+
+impl fmt::Display for IntegrationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IntegrationKind::AppService => "App Service",
+            IntegrationKind::ContainerApps => "Container Apps",
+        })
+    }
+}
+
+/// Usage of one integration subnet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegatedSubnetUsage {
+    pub kind: IntegrationKind,
+    pub vnet_name: String,
+    pub subnet_name: String,
+    pub subnet_cidr: Ipv4,
+    /// App Service plans / Container Apps environments sharing the subnet.
+    pub integrations: u32,
+    /// Usable Azure hosts in the subnet.
+    pub capacity: u64,
+    /// IP configurations currently in the subnet.
+    pub in_use: u64,
+}
+
+impl DelegatedSubnetUsage {
+    /// Addresses still free.
+    pub fn remaining(&self) -> u64 {
+        self.capacity.saturating_sub(self.in_use)
+    }
+
+    /// `true` when the subnet can absorb a burst that doubles current usage.
+    pub fn burst_fits(&self) -> bool {
+        self.remaining() >= self.in_use
+    }
+
+    /// `true` when the subnet is smaller than the recommended size.
+    pub fn undersized(&self) -> bool {
+        self.subnet_cidr.mask > self.kind.recommended_mask()
+    }
+}
+
+/// Collect every App Service / Container Apps integration subnet in `data`.
+pub fn delegated_subnet_usage(data: &Data) -> Vec<DelegatedSubnetUsage> {
+    data.data
+        .iter()
+        .filter_map(|s| {
+            let kind = IntegrationKind::from_subnet(s)?;
+            let cidr = s.subnet_cidr?;
+            Some(DelegatedSubnetUsage {
+                kind,
+                vnet_name: s.vnet_name.clone(),
+                subnet_name: s.subnet_name.clone(),
+                subnet_cidr: cidr,
+                integrations: s.service_association_links.unwrap_or(0),
+                capacity: num_az_hosts(cidr.mask).unwrap_or(0),
+                in_use: u64::from(s.ip_configurations_count.unwrap_or(0)),
+            })
+        })
+        .collect()
+}
+
+/// Render integration subnet usage as terminal lines.
+pub fn format_delegated_usage(usage: &[DelegatedSubnetUsage]) -> String {
+    usage
+        .iter()
+        .map(|u| {
+            let mut notes = Vec::new();
+            if !u.burst_fits() {
+                notes.push("BURST DOES NOT FIT".to_string());
+            }
+            if u.undersized() {
+                notes.push(format!("smaller than /{}", u.kind.recommended_mask()));
+            }
+            format!(
+                "{}: '{}/{}' {} - {} integration(s), {} of {} IPs free{}",
+                u.kind.to_string().to_uppercase(),
+                u.vnet_name,
+                u.subnet_name,
+                u.subnet_cidr,
+                u.integrations,
+                u.remaining(),
+                u.capacity,
+                if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", notes.join(", "))
+                }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_subnet(name: &str, cidr: &str, delegation: &str, in_use: u32, links: u32) -> Subnet {
+        Subnet {
+            delegations: vec![delegation.to_string()],
+            ip_configurations_count: Some(in_use),
+            service_association_links: Some(links),
+            ..Subnet::test("spoke", name, cidr)
+        }
+    }
+
+    #[test]
+    fn only_app_service_and_container_apps_delegations_are_reported() {
+        let data = Data {
+            data: vec![
+                make_subnet("web", "10.0.0.0/26", "Microsoft.Web/serverFarms", 10, 2),
+                make_subnet("aca", "10.0.1.0/27", "microsoft.app/environments", 4, 1),
+                make_subnet("sql", "10.0.2.0/27", "Microsoft.Sql/managedInstances", 8, 1),
+            ],
+            ..Default::default()
+        };
+        let usage = delegated_subnet_usage(&data);
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].kind, IntegrationKind::AppService);
+        assert_eq!(usage[0].integrations, 2);
+        assert_eq!(usage[0].remaining(), 49);
+        assert_eq!(usage[1].kind, IntegrationKind::ContainerApps);
+    }
+
+    #[test]
+    fn busy_or_small_subnets_are_flagged() {
+        let data = Data {
+            data: vec![make_subnet(
+                "web",
+                "10.0.0.0/28",
+                "Microsoft.Web/serverFarms",
+                7,
+                3,
+            )],
+            ..Default::default()
+        };
+        let usage = delegated_subnet_usage(&data);
+        // /28 = 11 Azure hosts; 7 used leaves 4, not enough to double.
+        assert!(!usage[0].burst_fits());
+        assert!(usage[0].undersized());
+        assert_eq!(
+            format_delegated_usage(&usage),
+            "APP SERVICE: 'spoke/web' 10.0.0.0/28 - 3 integration(s), 4 of 11 IPs free [BURST DOES NOT FIT, smaller than /26]"
+        );
+    }
+}
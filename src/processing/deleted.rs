@@ -0,0 +1,182 @@
+//! Recently deleted subnet tracking using snapshot history.
+//!
+//! A subnet in an earlier snapshot but not in today's data was deleted
+//! between the last snapshot that had it and the next one. Reporting that
+//! window with the old prefix, and whether the prefix has been taken since,
+//! narrows down "who deleted this subnet and when did it free up" in the
+//! Activity Log during incident reviews.
+
+use super::empty::subnet_key;
+use crate::azure::Data;
+use crate::models::Ipv4;
+use chrono::{Days, NaiveDate};
+use std::collections::HashSet;
+
+/// A subnet that is in the snapshot history but no longer in Azure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedSubnet {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub subnet_name: String,
+    /// Resource ID, for Activity Log queries (empty in old caches).
+    pub subnet_id: String,
+    /// Prefix the subnet held.
+    pub subnet_cidr: Ipv4,
+    /// Date of the newest snapshot that still had the subnet.
+    pub last_seen: NaiveDate,
+    /// Date of the next snapshot (or today), by which it was gone.
+    pub gone_by: NaiveDate,
+    /// Subnets in today's data overlapping the old prefix, as `vnet/subnet`.
+    pub reused_by: Vec<String>,
+}
+
+/// Find subnets deleted in the last `days` days, most recent first.
+///
+/// `history` yields earlier snapshots newest first; it is only read back to
+/// the first snapshot older than the window, so snapshots can be read lazily.
+pub fn find_deleted_subnets(
+    current: &Data,
+    today: NaiveDate,
+    history: impl IntoIterator<Item = (NaiveDate, Data)>,
+    days: u64,
+) -> Vec<DeletedSubnet> {
+    let cutoff = today - Days::new(days);
+    let mut seen: HashSet<String> = current.data.iter().map(subnet_key).collect();
+    let mut deleted: Vec<DeletedSubnet> = Vec::new();
+    let mut newer = today;
+    for (date, snapshot) in history {
+        if date >= today {
+            continue;
+        }
+        // Gone before the window: neither this nor older snapshots can add one.
+        if newer < cutoff {
+            break;
+        }
+        for s in &snapshot.data {
+            let Some(cidr) = s.subnet_cidr else {
+                continue;
+            };
+            if !seen.insert(subnet_key(s)) {
+                continue;
+            }
+            deleted.push(DeletedSubnet {
+                subscription_name: s.subscription_name.clone(),
+                vnet_name: s.vnet_name.clone(),
+                subnet_name: s.subnet_name.clone(),
+                subnet_id: s.subnet_id.clone(),
+                subnet_cidr: cidr,
+                last_seen: date,
+                gone_by: newer,
+                reused_by: current
+                    .data
+                    .iter()
+                    .filter(|c| {
+                        c.subnet_cidr
+                            .is_some_and(|c| c.lo() <= cidr.hi() && cidr.lo() <= c.hi())
+                    })
+                    .map(|c| format!("{}/{}", c.vnet_name, c.subnet_name))
+                    .collect(),
+            });
+        }
+        newer = date;
+    }
+    deleted.sort_by(|a, b| {
+        (b.gone_by, &a.vnet_name, &a.subnet_name).cmp(&(a.gone_by, &b.vnet_name, &b.subnet_name))
+    });
+    deleted
+}
+
+/// Render deleted subnets with their deletion window and prefix reuse.
+pub fn format_deleted_subnets(deleted: &[DeletedSubnet]) -> String {
+    if deleted.is_empty() {
+        return "No deleted subnets found.".to_string();
+    }
+    deleted
+        .iter()
+        .map(|d| {
+            let prefix = if d.reused_by.is_empty() {
+                "prefix still free".to_string()
+            } else {
+                format!("prefix now used by '{}'", d.reused_by.join("', '"))
+            };
+            format!(
+                "DELETED: '{}/{}' {} in '{}' - last seen {}, gone by {}; {prefix}",
+                d.vnet_name,
+                d.subnet_name,
+                d.subnet_cidr,
+                d.subscription_name,
+                d.last_seen,
+                d.gone_by
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    fn make_subnet(name: &str, cidr: &str) -> Subnet {
+        Subnet {
+            subscription_name: "Sub".to_string(),
+            ..Subnet::test("spoke", name, cidr)
+        }
+    }
+
+    fn make_data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap()
+    }
+
+    #[test]
+    fn subnets_missing_today_are_reported_with_their_deletion_window() {
+        let current = make_data(vec![
+            make_subnet("app", "10.0.0.0/24"),
+            make_subnet("app-v2", "10.0.2.0/25"),
+        ]);
+        let history = vec![
+            (
+                day(12),
+                make_data(vec![
+                    make_subnet("app", "10.0.0.0/24"),
+                    make_subnet("old-db", "10.0.1.0/24"),
+                ]),
+            ),
+            (
+                day(8),
+                make_data(vec![
+                    make_subnet("app", "10.0.0.0/24"),
+                    make_subnet("old-db", "10.0.1.0/24"),
+                    make_subnet("legacy", "10.0.2.0/24"),
+                ]),
+            ),
+            (
+                day(1),
+                make_data(vec![make_subnet("ancient", "10.0.3.0/24")]),
+            ),
+        ];
+        let deleted = find_deleted_subnets(&current, day(15), history, 6);
+        let found: Vec<(&str, NaiveDate, NaiveDate)> = deleted
+            .iter()
+            .map(|d| (d.subnet_name.as_str(), d.last_seen, d.gone_by))
+            .collect();
+        // "ancient" was gone by day 8, before the 6 day window.
+        assert_eq!(
+            found,
+            [("old-db", day(12), day(15)), ("legacy", day(8), day(12))]
+        );
+        let out = format_deleted_subnets(&deleted);
+        assert!(out.contains(
+            "DELETED: 'spoke/old-db' 10.0.1.0/24 in 'Sub' - last seen 2026-10-12, gone by 2026-10-15; prefix still free"
+        ));
+        assert!(out.ends_with("gone by 2026-10-12; prefix now used by 'spoke/app-v2'"));
+    }
+}
@@ -0,0 +1,358 @@
+//! Subnet changes between two snapshots.
+//!
+//! Azure cannot rename a subnet: a "rename" is a delete and a create at the
+//! same prefix, with a new resource ID. Subnets are therefore matched in two
+//! passes — by ARM resource ID first, then the leftovers by `(VNet, CIDR)` —
+//! so a subnet recreated under a new name is reported as renamed rather than
+//! as one removal plus one addition. The second pass also matches subnets in
+//! caches written before resource IDs were recorded.
+//!
+//! [`annotate_changes`] attaches the Activity Log write behind each change
+//! (who, when, which operation).
+
+use crate::azure::Data;
+use crate::clock::Clock;
+use crate::models::{ActivityEvent, Ipv4, Subnet};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Key a subnet is matched on across snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SubnetIdentity {
+    /// Lower-cased ARM resource ID of the subnet.
+    ResourceId(String),
+    /// Lower-cased VNet resource ID (or `<subscription>/<vnet name>` when
+    /// unknown) and the subnet prefix.
+    VnetCidr(String, Option<Ipv4>),
+}
+
+impl SubnetIdentity {
+    /// Primary identity: the resource ID, or the fallback when it is unknown.
+    pub fn of(s: &Subnet) -> SubnetIdentity {
+        if s.subnet_id.is_empty() {
+            SubnetIdentity::fallback(s)
+        } else {
+            SubnetIdentity::ResourceId(s.subnet_id.to_lowercase())
+        }
+    }
+
+    /// Fallback identity: the VNet and prefix, which survive a recreate.
+    pub fn fallback(s: &Subnet) -> SubnetIdentity {
+        let vnet = if s.vnet_id.is_empty() {
+            format!("{}/{}", s.subscription_id, s.vnet_name)
+        } else {
+            s.vnet_id.clone()
+        };
+        SubnetIdentity::VnetCidr(vnet.to_lowercase(), s.subnet_cidr)
+    }
+}
+
+/// What happened to a subnet between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// Same VNet and prefix under a new name; carries the old name.
+    Renamed {
+        from: String,
+    },
+    /// Same subnet with these properties changed.
+    Modified {
+        fields: Vec<&'static str>,
+    },
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Added => f.write_str("ADDED"),
+            ChangeKind::Removed => f.write_str("REMOVED"),
+            ChangeKind::Renamed { .. } => f.write_str("RENAMED"),
+            ChangeKind::Modified { .. } => f.write_str("CHANGED"),
+        }
+    }
+}
+
+/// Activity Log write behind a change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeAnnotation {
+    /// User principal name, or application ID of a service principal.
+    pub caller: String,
+    /// When, as `YYYY-MM-DD HH:MM` in the run's timezone.
+    pub at: String,
+    /// Operation, e.g. `Microsoft.Network/virtualNetworks/subnets/write`.
+    pub operation: String,
+}
+
+/// One changed subnet; `subnet` is the newer record (the old one for removals).
+#[derive(Debug, Clone)]
+pub struct SubnetChange {
+    pub kind: ChangeKind,
+    pub subnet: Subnet,
+    /// Set by [`annotate_changes`].
+    pub annotation: Option<ChangeAnnotation>,
+}
+
+/// Properties that differ between two records of the same subnet.
+fn changed_fields(old: &Subnet, new: &Subnet) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if old.subnet_cidr != new.subnet_cidr {
+        fields.push("prefix");
+    }
+    if !same_id(&old.nsg, &new.nsg) {
+        fields.push("nsg");
+    }
+    if !same_id(&old.nat_gateway, &new.nat_gateway) {
+        fields.push("nat gateway");
+    }
+    if old.delegations != new.delegations {
+        fields.push("delegations");
+    }
+    fields
+}
+
+/// Resource IDs compare case-insensitively.
+fn same_id(a: &Option<String>, b: &Option<String>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        (a, b) => a == b,
+    }
+}
+
+fn paired(old: &Subnet, new: &Subnet) -> Option<SubnetChange> {
+    let kind = if !old.subnet_name.eq_ignore_ascii_case(&new.subnet_name) {
+        ChangeKind::Renamed {
+            from: old.subnet_name.clone(),
+        }
+    } else {
+        let fields = changed_fields(old, new);
+        if fields.is_empty() {
+            return None;
+        }
+        ChangeKind::Modified { fields }
+    };
+    Some(SubnetChange {
+        kind,
+        subnet: new.clone(),
+        annotation: None,
+    })
+}
+
+/// Changes from `old` to `new`, sorted by VNet, subnet name and kind.
+pub fn diff_subnets(old: &Data, new: &Data) -> Vec<SubnetChange> {
+    let mut changes = Vec::new();
+
+    // Pass 1: resource ID.
+    let mut old_by_id: HashMap<SubnetIdentity, &Subnet> = old
+        .data
+        .iter()
+        .map(|s| (SubnetIdentity::of(s), s))
+        .collect();
+    let mut unmatched_new = Vec::new();
+    for s in &new.data {
+        match old_by_id.remove(&SubnetIdentity::of(s)) {
+            Some(o) => changes.extend(paired(o, s)),
+            None => unmatched_new.push(s),
+        }
+    }
+
+    // Pass 2: (VNet, prefix) among the leftovers.
+    let mut old_by_prefix: HashMap<SubnetIdentity, &Subnet> = old_by_id
+        .into_values()
+        .map(|s| (SubnetIdentity::fallback(s), s))
+        .collect();
+    for s in unmatched_new {
+        let matched = s
+            .subnet_cidr
+            .and_then(|_| old_by_prefix.remove(&SubnetIdentity::fallback(s)));
+        match matched {
+            Some(o) => changes.extend(paired(o, s)),
+            None => changes.push(SubnetChange {
+                kind: ChangeKind::Added,
+                subnet: s.clone(),
+                annotation: None,
+            }),
+        }
+    }
+    changes.extend(old_by_prefix.into_values().map(|s| SubnetChange {
+        kind: ChangeKind::Removed,
+        subnet: s.clone(),
+        annotation: None,
+    }));
+
+    changes.sort_by(|a, b| {
+        (
+            &a.subnet.vnet_name,
+            &a.subnet.subnet_name,
+            a.kind.to_string(),
+        )
+            .cmp(&(
+                &b.subnet.vnet_name,
+                &b.subnet.subnet_name,
+                b.kind.to_string(),
+            ))
+    });
+    changes
+}
+
+/// Attach the latest Activity Log write to each change: one on the subnet
+/// itself, or else one on its VNet (subnets defined inline in a VNet are
+/// changed by writing the VNet). `events` returns the writes on a resource
+/// ID, newest first; each ID is asked once.
+pub fn annotate_changes<F>(changes: &mut [SubnetChange], clock: &Clock, mut events: F)
+where
+    F: FnMut(&str) -> Vec<ActivityEvent>,
+{
+    let mut seen: HashMap<String, Option<ActivityEvent>> = HashMap::new();
+    let mut latest = |id: &str| -> Option<ActivityEvent> {
+        if id.is_empty() {
+            return None;
+        }
+        seen.entry(id.to_lowercase())
+            .or_insert_with(|| events(id).into_iter().next())
+            .clone()
+    };
+    let timezone = clock.now().timezone();
+    for change in changes {
+        let event = latest(&change.subnet.subnet_id).or_else(|| latest(&change.subnet.vnet_id));
+        change.annotation = event.map(|e| ChangeAnnotation {
+            caller: e.caller,
+            at: e
+                .timestamp
+                .with_timezone(&timezone)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            operation: e.operation,
+        });
+    }
+}
+
+/// Render changes as terminal lines.
+pub fn format_subnet_changes(changes: &[SubnetChange]) -> String {
+    if changes.is_empty() {
+        return "No subnet changes.".to_string();
+    }
+    changes
+        .iter()
+        .map(|c| {
+            let s = &c.subnet;
+            let cidr = s.subnet_cidr.map(|c| c.to_string()).unwrap_or_default();
+            let detail = match &c.kind {
+                ChangeKind::Renamed { from } => format!(" - was '{from}'"),
+                ChangeKind::Modified { fields } => format!(" - {}", fields.join(", ")),
+                ChangeKind::Added | ChangeKind::Removed => String::new(),
+            };
+            let by = match &c.annotation {
+                Some(a) => format!(" (by {} at {}, {})", a.caller, a.at, a.operation),
+                None => String::new(),
+            };
+            format!(
+                "{}: '{}/{}' {cidr}{detail}{by}",
+                c.kind, s.vnet_name, s.subnet_name
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subnet(name: &str, cidr: &str) -> Subnet {
+        let mut s = Subnet::test("hub", name, cidr);
+        s.vnet_id = "/subscriptions/s/providers/Microsoft.Network/virtualNetworks/hub".to_string();
+        s.subnet_id = format!("{}/subnets/{name}", s.vnet_id);
+        s
+    }
+
+    fn data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recreate_at_same_prefix_is_a_rename_not_delete_and_create() {
+        let mut nsg_added = subnet("app", "10.0.0.0/24");
+        nsg_added.nsg = Some("nsg-app".to_string());
+        let old = data(vec![
+            subnet("app", "10.0.0.0/24"),
+            subnet("old-db", "10.0.1.0/24"),
+            subnet("legacy", "10.0.2.0/24"),
+        ]);
+        let new = data(vec![
+            nsg_added,
+            subnet("db", "10.0.1.0/24"),
+            subnet("web", "10.0.3.0/24"),
+        ]);
+        let out = format_subnet_changes(&diff_subnets(&old, &new));
+        assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            [
+                "CHANGED: 'hub/app' 10.0.0.0/24 - nsg",
+                "RENAMED: 'hub/db' 10.0.1.0/24 - was 'old-db'",
+                "REMOVED: 'hub/legacy' 10.0.2.0/24",
+                "ADDED: 'hub/web' 10.0.3.0/24",
+            ]
+        );
+    }
+
+    #[test]
+    fn caches_without_resource_ids_match_on_vnet_and_prefix() {
+        let mut old = subnet("app", "10.0.0.0/24");
+        old.subnet_id.clear();
+        let mut resized = subnet("app", "10.0.0.0/25");
+        resized.subnet_id.clear();
+        let changes = diff_subnets(&data(vec![old.clone()]), &data(vec![old.clone()]));
+        assert!(changes.is_empty());
+        // Without an ID a resized subnet cannot be told from a recreate.
+        let kinds: Vec<String> = diff_subnets(&data(vec![old]), &data(vec![resized]))
+            .iter()
+            .map(|c| c.kind.to_string())
+            .collect();
+        assert_eq!(kinds, ["ADDED", "REMOVED"]);
+    }
+
+    #[test]
+    fn annotations_come_from_the_subnet_or_else_its_vnet() {
+        let old = data(vec![
+            subnet("app", "10.0.0.0/24"),
+            subnet("db", "10.0.1.0/24"),
+        ]);
+        let mut resized = subnet("app", "10.0.0.0/23");
+        resized.nsg = None;
+        let new = data(vec![resized, subnet("web", "10.0.2.0/24")]);
+        let mut changes = diff_subnets(&old, &new);
+        let clock = Clock::new(Some("Pacific/Auckland"), None).unwrap();
+        let mut asked = Vec::new();
+        annotate_changes(&mut changes, &clock, |id| {
+            asked.push(id.to_string());
+            let event = |caller: &str, op: &str| ActivityEvent {
+                caller: caller.to_string(),
+                timestamp: "2026-10-14T01:02:00Z".parse().unwrap(),
+                operation: format!("Microsoft.Network/virtualNetworks/{op}"),
+                resource_id: id.to_string(),
+            };
+            if id.ends_with("/subnets/app") {
+                vec![event("pipeline-app-id", "subnets/write")]
+            } else if id.ends_with("/hub") {
+                vec![event("ops@contoso.com", "write")]
+            } else {
+                Vec::new()
+            }
+        });
+        let out = format_subnet_changes(&changes);
+        assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            [
+                "CHANGED: 'hub/app' 10.0.0.0/23 - prefix (by pipeline-app-id at 2026-10-14 14:02, Microsoft.Network/virtualNetworks/subnets/write)",
+                "REMOVED: 'hub/db' 10.0.1.0/24 (by ops@contoso.com at 2026-10-14 14:02, Microsoft.Network/virtualNetworks/write)",
+                "ADDED: 'hub/web' 10.0.2.0/24 (by ops@contoso.com at 2026-10-14 14:02, Microsoft.Network/virtualNetworks/write)",
+            ]
+        );
+        // The VNet's log is read once for both subnets falling back to it.
+        assert_eq!(asked.iter().filter(|id| id.ends_with("/hub")).count(), 1);
+    }
+}
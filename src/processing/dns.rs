@@ -0,0 +1,182 @@
+//! VNets grouped by custom DNS server, with dead resolver detection.
+//!
+//! Each VNet lists the DNS servers its VMs are handed (none means
+//! Azure-provided DNS). Grouping VNets by server shows which resolvers the
+//! estate depends on; a server address outside every known subnet and
+//! on-premises range usually belongs to a decommissioned resolver, leaving
+//! the VNet's name resolution broken on its next DHCP renewal.
+
+use super::lookup::SubnetIndex;
+use super::merge::ExtraRange;
+use crate::azure::Data;
+use crate::models::SubnetSource;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::Ipv4Addr;
+
+/// Azure's virtual public IP answering DNS inside every VNet.
+pub const AZURE_DNS_IP: Ipv4Addr = Ipv4Addr::new(168, 63, 129, 16);
+
+/// Where a DNS server address lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolverLocation {
+    /// Azure-provided DNS ([`AZURE_DNS_IP`]).
+    AzureDns,
+    /// Inside a subnet of the estate, as `vnet/subnet`.
+    Subnet(String),
+    /// Inside a configured on-premises range, by name.
+    OnPrem(String),
+    /// Not in any known subnet or range: likely a dead resolver.
+    Unknown,
+    /// Not an IPv4 address.
+    Invalid,
+}
+
+/// One DNS server and the VNets configured to use it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsServerUsage {
+    pub server: String,
+    /// VNets as `subscription/vnet`, sorted.
+    pub vnets: Vec<String>,
+    /// Where the address lives; `None` unless resolvers were checked.
+    pub location: Option<ResolverLocation>,
+}
+
+impl DnsServerUsage {
+    /// `true` when the server was checked and is not a reachable resolver.
+    pub fn is_dead(&self) -> bool {
+        matches!(
+            self.location,
+            Some(ResolverLocation::Unknown | ResolverLocation::Invalid)
+        )
+    }
+}
+
+/// Group the VNets of `data` by configured DNS server, most used first.
+///
+/// With `check` set, each server address is located in the subnets of
+/// `data` or the on-premises entries of `ranges`.
+pub fn dns_server_usage(data: &Data, ranges: &[ExtraRange], check: bool) -> Vec<DnsServerUsage> {
+    let mut servers: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+    for s in data.data.iter().filter(|s| s.source.is_azure_resource()) {
+        for server in s.dns_servers.iter().flatten() {
+            servers
+                .entry(server.trim())
+                .or_default()
+                .insert(format!("{}/{}", s.subscription_name, s.vnet_name));
+        }
+    }
+    let index = SubnetIndex::new(data);
+    let locate = |server: &str| {
+        let Ok(ip) = server.parse::<Ipv4Addr>() else {
+            return ResolverLocation::Invalid;
+        };
+        if ip == AZURE_DNS_IP {
+            return ResolverLocation::AzureDns;
+        }
+        if let Some(s) = index.find_ip(ip) {
+            return ResolverLocation::Subnet(format!("{}/{}", s.vnet_name, s.subnet_name));
+        }
+        ranges
+            .iter()
+            .find(|r| r.source == SubnetSource::OnPrem && r.cidr.contains(ip))
+            .map_or(ResolverLocation::Unknown, |r| {
+                ResolverLocation::OnPrem(r.name.clone())
+            })
+    };
+    let mut usage: Vec<DnsServerUsage> = servers
+        .into_iter()
+        .map(|(server, vnets)| DnsServerUsage {
+            server: server.to_string(),
+            vnets: vnets.into_iter().collect(),
+            location: check.then(|| locate(server)),
+        })
+        .collect();
+    usage.sort_by_key(|u| std::cmp::Reverse(u.vnets.len()));
+    usage
+}
+
+/// Render one line per DNS server; dead resolvers are marked `DEAD RESOLVER`.
+pub fn format_dns_server_usage(usage: &[DnsServerUsage]) -> String {
+    if usage.is_empty() {
+        return "No VNets have custom DNS servers (all use Azure-provided DNS).".to_string();
+    }
+    usage
+        .iter()
+        .map(|u| {
+            let label = if u.is_dead() { "DEAD RESOLVER" } else { "DNS" };
+            let location = match &u.location {
+                None => String::new(),
+                Some(ResolverLocation::AzureDns) => " [Azure-provided DNS]".to_string(),
+                Some(ResolverLocation::Subnet(s)) => format!(" [in subnet '{s}']"),
+                Some(ResolverLocation::OnPrem(r)) => format!(" [in on-prem range '{r}']"),
+                Some(ResolverLocation::Unknown) => {
+                    " [not in any known subnet or on-prem range]".to_string()
+                }
+                Some(ResolverLocation::Invalid) => " [not an IPv4 address]".to_string(),
+            };
+            format!(
+                "{label}: {} - {} VNet(s): {}{location}",
+                u.server,
+                u.vnets.len(),
+                u.vnets.join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Ipv4, Subnet};
+
+    fn subnet(vnet: &str, name: &str, cidr: &str, dns: &[&str]) -> Subnet {
+        Subnet {
+            subscription_name: "Prod".to_string(),
+            dns_servers: (!dns.is_empty()).then(|| dns.iter().map(|d| d.to_string()).collect()),
+            ..Subnet::test(vnet, name, cidr)
+        }
+    }
+
+    #[test]
+    fn vnets_are_grouped_by_dns_server_and_dead_resolvers_flagged() {
+        let data = Data {
+            data: vec![
+                subnet("hub", "dns-inbound", "10.0.0.0/28", &[]),
+                subnet("spoke-a", "app", "10.1.0.0/24", &["10.0.0.4", "10.9.9.9"]),
+                subnet("spoke-a", "db", "10.1.1.0/24", &["10.0.0.4", "10.9.9.9"]),
+                subnet(
+                    "spoke-b",
+                    "app",
+                    "10.2.0.0/24",
+                    &["10.0.0.4", "192.168.1.53"],
+                ),
+                subnet("spoke-c", "app", "10.3.0.0/24", &["168.63.129.16"]),
+            ],
+            ..Default::default()
+        };
+        let ranges = [ExtraRange {
+            source: SubnetSource::OnPrem,
+            name: "dc-1".to_string(),
+            cidr: Ipv4::new("192.168.0.0/16").unwrap(),
+            location: None,
+        }];
+
+        let unchecked = dns_server_usage(&data, &ranges, false);
+        assert!(unchecked.iter().all(|u| u.location.is_none()));
+        assert_eq!(unchecked[0].vnets, ["Prod/spoke-a", "Prod/spoke-b"]);
+
+        let checked = dns_server_usage(&data, &ranges, true);
+        assert_eq!(
+            format_dns_server_usage(&checked)
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "DNS: 10.0.0.4 - 2 VNet(s): Prod/spoke-a, Prod/spoke-b [in subnet 'hub/dns-inbound']",
+                "DEAD RESOLVER: 10.9.9.9 - 1 VNet(s): Prod/spoke-a [not in any known subnet or on-prem range]",
+                "DNS: 168.63.129.16 - 1 VNet(s): Prod/spoke-c [Azure-provided DNS]",
+                "DNS: 192.168.1.53 - 1 VNet(s): Prod/spoke-b [in on-prem range 'dc-1']",
+            ]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Empty subnet detection using snapshot history.
+//!
+//! A subnet with no IP configurations today may just have been created. One
+//! that stayed empty across days of snapshots is a reclamation candidate.
+
+use crate::azure::Data;
+use crate::models::{Ipv4, Subnet};
+use crate::output::format::thousands;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+/// A subnet that has had no IP configurations for a while.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmptySubnet {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub subnet_name: String,
+    pub subnet_cidr: Ipv4,
+    /// Date of the oldest consecutive snapshot in which the subnet was empty.
+    pub empty_since: NaiveDate,
+    /// Days between `empty_since` and today.
+    pub days: i64,
+    /// `true` when the subnet was empty in the oldest snapshot available, so it
+    /// may have been empty for longer.
+    pub at_least: bool,
+}
+
+impl EmptySubnet {
+    /// Addresses freed by deleting the subnet.
+    pub fn addresses(&self) -> u64 {
+        1u64 << (32 - u32::from(self.subnet_cidr.mask))
+    }
+}
+
+/// Identity of a subnet across snapshots: its resource ID, or names for old caches.
+pub(super) fn subnet_key(s: &Subnet) -> String {
+    if s.subnet_id.is_empty() {
+        format!("{}/{}/{}", s.subscription_id, s.vnet_name, s.subnet_name).to_lowercase()
+    } else {
+        s.subnet_id.to_lowercase()
+    }
+}
+
+/// `ip_configurations_count` is null in Resource Graph when a subnet has none.
+fn is_empty(s: &Subnet) -> bool {
+    s.ip_configurations_count.unwrap_or(0) == 0
+}
+
+/// Find subnets empty today and in every snapshot of at least the last
+/// `min_days` days, largest first.
+///
+/// `history` yields earlier snapshots newest first; it is only advanced while
+/// some subnet is still a candidate, so snapshots can be read lazily.
+pub fn find_empty_subnets(
+    current: &Data,
+    today: NaiveDate,
+    history: impl IntoIterator<Item = (NaiveDate, Data)>,
+    min_days: i64,
+) -> Vec<EmptySubnet> {
+    // Candidates still empty in every snapshot seen so far, with their oldest date.
+    let mut open: HashMap<String, (&Subnet, NaiveDate)> = current
+        .data
+        .iter()
+        .filter(|s| is_empty(s) && s.subnet_cidr.is_some())
+        .map(|s| (subnet_key(s), (s, today)))
+        .collect();
+    let mut closed: Vec<(&Subnet, NaiveDate, bool)> = Vec::new();
+
+    let mut history = history.into_iter();
+    while !open.is_empty() {
+        let Some((date, snapshot)) = history.next() else {
+            break;
+        };
+        if date >= today {
+            continue;
+        }
+        let empty_then: HashMap<String, bool> = snapshot
+            .data
+            .iter()
+            .map(|s| (subnet_key(s), is_empty(s)))
+            .collect();
+        open.retain(|key, (subnet, since)| match empty_then.get(key) {
+            Some(true) => {
+                *since = date;
+                true
+            }
+            // Not yet created, or in use at that date: empty since the later snapshot.
+            _ => {
+                closed.push((subnet, *since, false));
+                false
+            }
+        });
+    }
+    closed.extend(open.into_values().map(|(s, since)| (s, since, true)));
+
+    let mut empty: Vec<EmptySubnet> = closed
+        .into_iter()
+        .filter(|(_, since, _)| (today - *since).num_days() >= min_days)
+        .filter_map(|(s, since, at_least)| {
+            Some(EmptySubnet {
+                subscription_name: s.subscription_name.clone(),
+                vnet_name: s.vnet_name.clone(),
+                subnet_name: s.subnet_name.clone(),
+                subnet_cidr: s.subnet_cidr?,
+                empty_since: since,
+                days: (today - since).num_days(),
+                at_least,
+            })
+        })
+        .collect();
+    empty.sort_by(|a, b| {
+        (a.subnet_cidr.mask, &a.vnet_name, &a.subnet_name).cmp(&(
+            b.subnet_cidr.mask,
+            &b.vnet_name,
+            &b.subnet_name,
+        ))
+    });
+    empty
+}
+
+/// Total reclaimable addresses per `(subscription name, VNet name)`.
+pub fn reclaimable_by_vnet(empty: &[EmptySubnet]) -> BTreeMap<(String, String), u64> {
+    let mut totals = BTreeMap::new();
+    for e in empty {
+        *totals
+            .entry((e.subscription_name.clone(), e.vnet_name.clone()))
+            .or_insert(0) += e.addresses();
+    }
+    totals
+}
+
+/// Render empty subnets followed by the reclaimable total per VNet.
+pub fn format_empty_subnets(empty: &[EmptySubnet]) -> String {
+    if empty.is_empty() {
+        return "No long-empty subnets found.".to_string();
+    }
+    let mut lines: Vec<String> = empty
+        .iter()
+        .map(|e| {
+            format!(
+                "EMPTY: '{}/{}' {} ({} addresses) - empty for {}{} days (since {})",
+                e.vnet_name,
+                e.subnet_name,
+                e.subnet_cidr,
+                thousands(e.addresses()),
+                if e.at_least { ">= " } else { "" },
+                e.days,
+                e.empty_since
+            )
+        })
+        .collect();
+    for ((sub, vnet), total) in reclaimable_by_vnet(empty) {
+        lines.push(format!(
+            "RECLAIMABLE: '{vnet}' in '{sub}' - {} addresses",
+            thousands(total)
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_subnet(name: &str, cidr: &str, in_use: u32) -> Subnet {
+        Subnet {
+            subscription_name: "Sub".to_string(),
+            ip_configurations_count: Some(in_use),
+            ..Subnet::test("spoke", name, cidr)
+        }
+    }
+
+    fn make_data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap()
+    }
+
+    #[test]
+    fn subnets_empty_across_history_are_reported_largest_first() {
+        let current = make_data(vec![
+            make_subnet("old-empty", "10.0.1.0/26", 0),
+            make_subnet("new-empty", "10.0.2.0/24", 0),
+            make_subnet("was-used", "10.0.3.0/24", 0),
+            make_subnet("busy", "10.0.0.0/24", 5),
+            make_subnet("big-empty", "10.1.0.0/23", 0),
+        ]);
+        let history = vec![
+            (
+                day(10),
+                make_data(vec![
+                    make_subnet("old-empty", "10.0.1.0/26", 0),
+                    make_subnet("new-empty", "10.0.2.0/24", 0),
+                    make_subnet("was-used", "10.0.3.0/24", 0),
+                    make_subnet("big-empty", "10.1.0.0/23", 0),
+                ]),
+            ),
+            (
+                day(1),
+                make_data(vec![
+                    make_subnet("old-empty", "10.0.1.0/26", 0),
+                    make_subnet("was-used", "10.0.3.0/24", 2),
+                    make_subnet("big-empty", "10.1.0.0/23", 0),
+                ]),
+            ),
+        ];
+        let empty = find_empty_subnets(&current, day(15), history, 7);
+        let names: Vec<&str> = empty.iter().map(|e| e.subnet_name.as_str()).collect();
+        // new-empty and was-used are empty since day 10 only (5 days).
+        assert_eq!(names, ["big-empty", "old-empty"]);
+        assert_eq!(empty[1].days, 14);
+        assert!(empty[1].at_least);
+
+        let out = format_empty_subnets(&empty);
+        assert!(out.contains("EMPTY: 'spoke/big-empty' 10.1.0.0/23 (512 addresses) - empty for >= 14 days (since 2026-10-01)"));
+        assert!(out.ends_with("RECLAIMABLE: 'spoke' in 'Sub' - 576 addresses"));
+    }
+
+    #[test]
+    fn history_is_not_read_once_no_candidates_remain() {
+        let current = make_data(vec![make_subnet("busy", "10.0.0.0/24", 1)]);
+        let history = std::iter::from_fn(|| -> Option<(NaiveDate, Data)> {
+            unreachable!("history should not be read")
+        });
+        assert!(find_empty_subnets(&current, day(15), history, 0).is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+//! Ignore list for subnets left out of the report.
+//!
+//! Each [`IgnoreRule`] names any combination of subnet name, subscription,
+//! VNet and subnet CIDR; a subnet is ignored when every field the rule sets
+//! matches. A rule with only `subnet` hides that name everywhere; adding
+//! `subscription` or `vnet` confines it, so a `default` subnet that matters in
+//! one VNet can stay while the others are hidden.
+//!
+//! Nothing is ignored unless rules are configured; the bundled
+//! `azure-subnet-summary.example.toml` has a starting list.
+
+use crate::models::{Ipv4, Subnet};
+use serde::Deserialize;
+use std::fmt;
+
+/// One ignore entry; unset fields match anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IgnoreRule {
+    /// Subnet name (exact match).
+    pub subnet: Option<String>,
+    /// Subscription display name or ID (case-insensitive).
+    pub subscription: Option<String>,
+    /// VNet name (case-insensitive).
+    pub vnet: Option<String>,
+    /// Subnet CIDR (exact match).
+    pub cidr: Option<Ipv4>,
+}
+
+impl IgnoreRule {
+    /// Rule that ignores `name` in every subscription and VNet.
+    pub fn subnet_name(name: &str) -> Self {
+        IgnoreRule {
+            subnet: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// `true` when every field set on the rule matches `s`.
+    pub fn matches(&self, s: &Subnet) -> bool {
+        self.subnet.as_ref().is_none_or(|n| *n == s.subnet_name)
+            && self.subscription.as_ref().is_none_or(|sub| {
+                sub.eq_ignore_ascii_case(&s.subscription_name)
+                    || sub.eq_ignore_ascii_case(&s.subscription_id)
+            })
+            && self
+                .vnet
+                .as_ref()
+                .is_none_or(|v| v.eq_ignore_ascii_case(&s.vnet_name))
+            && self.cidr.is_none_or(|c| s.subnet_cidr == Some(c))
+    }
+}
+
+impl fmt::Display for IgnoreRule {
+    /// The rule in `--ignore` syntax, e.g. `subnet=default,vnet=lab-vnet`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            ("subnet", self.subnet.clone()),
+            ("subscription", self.subscription.clone()),
+            ("vnet", self.vnet.clone()),
+            ("cidr", self.cidr.map(|c| c.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|v| format!("{key}={v}")))
+        .collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+/// Parse `--ignore` values like `subnet=default,vnet=hub-vnet`.
+///
+/// Keys are `subnet`, `subscription`, `vnet` and `cidr`; at least one is required.
+pub fn parse_ignore_rule(s: &str) -> Result<IgnoreRule, String> {
+    let mut rule = IgnoreRule::default();
+    for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{pair}'"))?;
+        let value = value.trim().to_string();
+        match key.trim() {
+            "subnet" => rule.subnet = Some(value),
+            "subscription" => rule.subscription = Some(value),
+            "vnet" => rule.vnet = Some(value),
+            "cidr" => rule.cidr = Some(Ipv4::new(&value).map_err(|e| e.to_string())?),
+            other => {
+                return Err(format!(
+                    "unknown key '{other}' (expected subnet, subscription, vnet or cidr)"
+                ))
+            }
+        }
+    }
+    if rule == IgnoreRule::default() {
+        return Err(
+            "an ignore rule needs at least one of subnet, subscription, vnet or cidr".into(),
+        );
+    }
+    Ok(rule)
+}
+
+/// Subnets to leave out of the report; the default ignores nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreList {
+    pub rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreList {
+    /// Unscoped rules for each subnet name.
+    pub fn from_names(names: &[&str]) -> Self {
+        IgnoreList {
+            rules: names.iter().map(|n| IgnoreRule::subnet_name(n)).collect(),
+        }
+    }
+
+    /// The configured `rules`.
+    pub fn from_rules(rules: &[IgnoreRule]) -> Self {
+        IgnoreList {
+            rules: rules.to_vec(),
+        }
+    }
+
+    /// Warn at startup which rules are active, so filtered subnets are never
+    /// a surprise.
+    pub fn log_active(&self) {
+        if self.rules.is_empty() {
+            return;
+        }
+        let rules: Vec<String> = self.rules.iter().map(|r| r.to_string()).collect();
+        log::warn!(
+            "Ignoring subnets matching {} rule(s): {}",
+            rules.len(),
+            rules.join("; ")
+        );
+    }
+
+    /// `true` when any rule matches `s`.
+    pub fn ignores(&self, s: &Subnet) -> bool {
+        self.rules.iter().any(|r| r.matches(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_subnet(sub: &str, vnet: &str, name: &str, cidr: &str) -> Subnet {
+        Subnet {
+            subscription_name: sub.to_string(),
+            subscription_id: format!("{sub}-id"),
+            ..Subnet::test(vnet, name, cidr)
+        }
+    }
+
+    #[test]
+    fn scoped_rules_only_ignore_matching_subnets() {
+        let list = IgnoreList::from_rules(&[
+            parse_ignore_rule("subnet=default, vnet=LAB-VNET").unwrap(),
+            parse_ignore_rule("subscription=sandbox-id").unwrap(),
+            parse_ignore_rule("cidr=10.9.0.0/24").unwrap(),
+        ]);
+        assert!(list.ignores(&make_subnet("Prod", "lab-vnet", "default", "10.0.0.0/24")));
+        assert!(!list.ignores(&make_subnet("Prod", "hub", "default", "10.0.1.0/24")));
+        assert!(list.ignores(&make_subnet("Sandbox", "hub", "app", "10.0.2.0/24")));
+        assert!(list.ignores(&make_subnet("Prod", "hub", "app", "10.9.0.0/24")));
+        assert!(!IgnoreList::from_rules(&[]).ignores(&make_subnet(
+            "Prod",
+            "hub",
+            "default",
+            "10.0.1.0/24"
+        )));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(parse_ignore_rule("").is_err());
+        assert!(parse_ignore_rule("region=westeurope").is_err());
+        assert!(parse_ignore_rule("cidr=10.0.0/24").is_err());
+        assert!(parse_ignore_rule("default").is_err());
+    }
+
+    #[test]
+    fn rules_display_in_ignore_syntax() {
+        let rule = parse_ignore_rule("vnet=lab-vnet,subnet=default,cidr=10.9.0.0/24").unwrap();
+        assert_eq!(
+            rule.to_string(),
+            "subnet=default,vnet=lab-vnet,cidr=10.9.0.0/24"
+        );
+        assert_eq!(parse_ignore_rule(&rule.to_string()).unwrap(), rule);
+    }
+}
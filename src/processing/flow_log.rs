@@ -0,0 +1,229 @@
+//! Flow log / Traffic Analytics coverage of subnets.
+//!
+//! A subnet's traffic is recorded when an enabled Network Watcher flow log
+//! targets its NSG (NSG flow logs), its VNet or the subnet itself (VNet flow
+//! logs). Security reviews also want Traffic Analytics on top, so coverage is
+//! graded: none, flow logs only, or flow logs with Traffic Analytics.
+
+use super::gap_finder::SubnetPrintRow;
+use crate::azure::{Data, FlowLogData};
+use crate::models::Subnet;
+use std::collections::HashMap;
+use std::fmt;
+
+/// How well a subnet's traffic is logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FlowLogCoverage {
+    /// No enabled flow log on the NSG, VNet or subnet.
+    None,
+    /// Enabled flow log, but no Traffic Analytics.
+    FlowLogs,
+    /// Enabled flow log with Traffic Analytics.
+    TrafficAnalytics,
+}
+
+impl fmt::Display for FlowLogCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FlowLogCoverage::None => "none",
+            FlowLogCoverage::FlowLogs => "flow-logs",
+            FlowLogCoverage::TrafficAnalytics => "traffic-analytics",
+        })
+    }
+}
+
+/// Best coverage per lower-cased flow log target ID.
+pub struct FlowLogIndex {
+    targets: HashMap<String, FlowLogCoverage>,
+}
+
+impl FlowLogIndex {
+    /// Index the enabled flow logs in `flow_logs`.
+    pub fn new(flow_logs: &FlowLogData) -> FlowLogIndex {
+        let mut targets: HashMap<String, FlowLogCoverage> = HashMap::new();
+        for log in flow_logs.data.iter().filter(|l| l.is_enabled()) {
+            let coverage = if log.has_traffic_analytics() {
+                FlowLogCoverage::TrafficAnalytics
+            } else {
+                FlowLogCoverage::FlowLogs
+            };
+            let best = targets
+                .entry(log.target_resource_id.to_lowercase())
+                .or_insert(coverage);
+            *best = (*best).max(coverage);
+        }
+        FlowLogIndex { targets }
+    }
+
+    /// Best coverage of `s` through its subnet, VNet or NSG.
+    pub fn coverage(&self, s: &Subnet) -> FlowLogCoverage {
+        [Some(&s.subnet_id), Some(&s.vnet_id), s.nsg.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter(|id| !id.is_empty())
+            .filter_map(|id| self.targets.get(&id.to_lowercase()).copied())
+            .max()
+            .unwrap_or(FlowLogCoverage::None)
+    }
+}
+
+/// Coverage per subnet, for filling the `flow_logs` column batch by batch.
+pub struct FlowLogAnnotator<'a> {
+    coverage: HashMap<(&'a str, &'a str, &'a str), FlowLogCoverage>,
+}
+
+impl<'a> FlowLogAnnotator<'a> {
+    pub fn new(subnets: &'a Data, flow_logs: &FlowLogData) -> FlowLogAnnotator<'a> {
+        let index = FlowLogIndex::new(flow_logs);
+        let coverage = subnets
+            .data
+            .iter()
+            .map(|s| {
+                let key = (
+                    s.subscription_id.as_str(),
+                    s.vnet_name.as_str(),
+                    s.subnet_name.as_str(),
+                );
+                (key, index.coverage(s))
+            })
+            .collect();
+        FlowLogAnnotator { coverage }
+    }
+
+    /// Fill the `flow_logs` column of the subnet rows in `rows`; gap rows stay empty.
+    pub fn annotate(&self, rows: &mut [SubnetPrintRow]) {
+        for row in rows {
+            let key = (
+                row.subscription_id.as_str(),
+                row.vnet_name.as_str(),
+                row.subnet_name.as_str(),
+            );
+            if let Some(c) = self.coverage.get(&key) {
+                row.flow_logs = c.to_string();
+            }
+        }
+    }
+}
+
+/// Fill the `flow_logs` column of the subnet rows in `rows`; gap rows stay empty.
+pub fn annotate_flow_logs(rows: &mut [SubnetPrintRow], subnets: &Data, flow_logs: &FlowLogData) {
+    FlowLogAnnotator::new(subnets, flow_logs).annotate(rows);
+}
+
+/// A subnet without full flow log coverage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowLogGap {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub subnet_name: String,
+    /// NSG name; empty when the subnet has none.
+    pub nsg: String,
+    pub coverage: FlowLogCoverage,
+}
+
+/// Subnets whose coverage is below Traffic Analytics, uncovered first.
+pub fn flow_log_gaps(subnets: &Data, flow_logs: &FlowLogData) -> Vec<FlowLogGap> {
+    let index = FlowLogIndex::new(flow_logs);
+    let mut gaps: Vec<FlowLogGap> = subnets
+        .data
+        .iter()
+        .filter_map(|s| {
+            let coverage = index.coverage(s);
+            (coverage < FlowLogCoverage::TrafficAnalytics).then(|| FlowLogGap {
+                subscription_name: s.subscription_name.clone(),
+                vnet_name: s.vnet_name.clone(),
+                subnet_name: s.subnet_name.clone(),
+                nsg: s
+                    .nsg
+                    .as_deref()
+                    .and_then(|id| id.rsplit('/').next())
+                    .unwrap_or_default()
+                    .to_string(),
+                coverage,
+            })
+        })
+        .collect();
+    gaps.sort_by(|a, b| {
+        (a.coverage, &a.vnet_name, &a.subnet_name).cmp(&(b.coverage, &b.vnet_name, &b.subnet_name))
+    });
+    gaps
+}
+
+/// Render the flow log audit as terminal lines.
+pub fn format_flow_log_gaps(gaps: &[FlowLogGap]) -> String {
+    if gaps.is_empty() {
+        return "All subnets have flow logs with Traffic Analytics.".to_string();
+    }
+    gaps.iter()
+        .map(|g| {
+            let nsg = if g.nsg.is_empty() {
+                "no NSG".to_string()
+            } else {
+                format!("NSG '{}'", g.nsg)
+            };
+            let problem = match g.coverage {
+                FlowLogCoverage::None => "no flow logs",
+                _ => "flow logs without Traffic Analytics",
+            };
+            format!(
+                "FLOW LOGS: '{}/{}' in '{}' ({nsg}) - {problem}",
+                g.vnet_name, g.subnet_name, g.subscription_name
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::FlowLogRow;
+
+    fn subnet(name: &str, nsg: Option<&str>) -> Subnet {
+        Subnet {
+            subscription_name: "Prod".to_string(),
+            vnet_id: "/sub/vnets/hub".to_string(),
+            subnet_id: format!("/sub/vnets/hub/subnets/{name}"),
+            nsg: nsg.map(|n| format!("/sub/nsgs/{n}")),
+            ..Subnet::test("hub", name, "")
+        }
+    }
+
+    fn flow_log(target: &str, enabled: bool, ta: bool) -> FlowLogRow {
+        FlowLogRow {
+            name: format!("fl-{target}"),
+            target_resource_id: target.to_string(),
+            enabled: Some(enabled),
+            traffic_analytics: Some(ta),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn coverage_comes_from_nsg_vnet_or_subnet_flow_logs() {
+        let subnets = Data {
+            data: vec![
+                subnet("app", Some("nsg-app")),
+                subnet("db", Some("nsg-db")),
+                subnet("mgmt", None),
+                subnet("GatewaySubnet", None),
+            ],
+            ..Default::default()
+        };
+        let flow_logs = FlowLogData {
+            data: vec![
+                flow_log("/SUB/NSGS/NSG-APP", true, true),
+                flow_log("/sub/nsgs/nsg-db", true, false),
+                flow_log("/sub/vnets/hub/subnets/mgmt", false, true),
+                flow_log("/sub/vnets/hub/subnets/GatewaySubnet", true, true),
+            ],
+            ..Default::default()
+        };
+        let gaps = flow_log_gaps(&subnets, &flow_logs);
+        assert_eq!(
+            format_flow_log_gaps(&gaps),
+            "FLOW LOGS: 'hub/mgmt' in 'Prod' (no NSG) - no flow logs\n\
+             FLOW LOGS: 'hub/db' in 'Prod' (NSG 'nsg-db') - flow logs without Traffic Analytics"
+        );
+    }
+}
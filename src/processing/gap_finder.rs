@@ -1,8 +1,15 @@
 //! Gap finding between subnets.
 //!
-//! Identifies unused IP address ranges between allocated subnets.
+//! Identifies unused IP address ranges between allocated subnets. Blocks
+//! inside each VNet CIDR are computed in parallel (rayon, `parallel` feature)
+//! and merged in order.
 
+use super::role::role_label;
 use crate::models::{next_subnet_ipv4, num_az_hosts, Ipv4, Subnet};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::net::Ipv4Addr;
 
 // ─── VnetCidr + Gap Iterator ──────────────────────────────────────────────────
@@ -18,6 +25,7 @@ pub struct VnetCidr {
     pub subscription_id: String,
     pub subscription_name: String,
     pub location: String,
+    pub resource_group: String,
     /// Subnets belonging to this VNet CIDR, sorted by start IP.
     pub subnets: Vec<Subnet>,
 }
@@ -42,6 +50,62 @@ pub enum GapKind<'a> {
     Subnet(&'a Subnet),
 }
 
+/// Group consecutive subnets sharing a `vnet_cidr` into [`VnetCidr`]s.
+///
+/// `subnets` should be sorted so each VNet CIDR's subnets are adjacent and in
+/// ascending order, as [`gaps`] expects.
+///
+/// Every prefix of a VNet's address space gets its own [`VnetCidr`]; prefixes
+/// without subnets are added empty, so their space shows as free inside the
+/// VNet rather than as a gap between VNets. An empty prefix overlapping
+/// another VNet CIDR (e.g. one excluded as a duplicate) is left out. The
+/// result is sorted by CIDR.
+pub fn group_vnet_cidrs(subnets: &[Subnet]) -> Vec<VnetCidr> {
+    let mut vnet_cidrs: Vec<VnetCidr> = Vec::new();
+    for subnet in subnets {
+        if let Some(last) = vnet_cidrs.last_mut() {
+            if last.cidr == subnet.vnet_cidr {
+                last.subnets.push(subnet.clone());
+                continue;
+            }
+        }
+        vnet_cidrs.push(VnetCidr {
+            cidr: subnet.vnet_cidr,
+            vnet_name: subnet.vnet_name.clone(),
+            subscription_id: subnet.subscription_id.clone(),
+            subscription_name: subnet.subscription_name.clone(),
+            location: subnet.location.clone(),
+            resource_group: subnet.resource_group.clone(),
+            subnets: vec![subnet.clone()],
+        });
+    }
+
+    let mut empty: Vec<VnetCidr> = Vec::new();
+    for subnet in subnets {
+        for prefix in &subnet.vnet_address_space {
+            let overlaps =
+                |vc: &VnetCidr| prefix.lo() <= vc.cidr.hi() && vc.cidr.lo() <= prefix.hi();
+            if vnet_cidrs.iter().chain(&empty).any(overlaps) {
+                continue;
+            }
+            empty.push(VnetCidr {
+                cidr: *prefix,
+                vnet_name: subnet.vnet_name.clone(),
+                subscription_id: subnet.subscription_id.clone(),
+                subscription_name: subnet.subscription_name.clone(),
+                location: subnet.location.clone(),
+                resource_group: subnet.resource_group.clone(),
+                subnets: Vec::new(),
+            });
+        }
+    }
+    if !empty.is_empty() {
+        vnet_cidrs.extend(empty);
+        vnet_cidrs.sort_by_key(|vc| vc.cidr);
+    }
+    vnet_cidrs
+}
+
 /// Iterate over all blocks inside `vnet_cidrs` as a flat sequence of [`GapEvent`]s.
 ///
 /// Each subnet becomes a `Subnet` event; unused space inside a VNet CIDR becomes
@@ -51,10 +115,23 @@ pub enum GapKind<'a> {
 /// `vnet_cidrs` must be sorted by `cidr` (ascending) and each `VnetCidr`'s
 /// `subnets` must be sorted by `subnet_cidr` (ascending).
 pub fn gaps<'a>(vnet_cidrs: &'a [VnetCidr], gap_mask: u8) -> Vec<GapEvent<'a>> {
-    let mut events = Vec::new();
+    // The blocks inside each VNet CIDR do not depend on any other VNet, so
+    // they are computed in parallel; only the global gaps need the ordering.
+    #[cfg(feature = "parallel")]
+    let per_vnet: Vec<Vec<GapEvent<'a>>> = vnet_cidrs
+        .par_iter()
+        .map(|vc| vnet_events(vc, gap_mask))
+        .collect();
+    #[cfg(not(feature = "parallel"))]
+    let per_vnet: Vec<Vec<GapEvent<'a>>> = vnet_cidrs
+        .iter()
+        .map(|vc| vnet_events(vc, gap_mask))
+        .collect();
+
+    let mut events = Vec::with_capacity(per_vnet.iter().map(Vec::len).sum());
     let mut current_ip: Option<Ipv4Addr> = None;
 
-    for vc in vnet_cidrs {
+    for (vc, inner) in vnet_cidrs.iter().zip(per_vnet) {
         // Global gap before this VNet CIDR.
         if let Some(ip) = current_ip {
             let mut gip = ip;
@@ -69,48 +146,53 @@ pub fn gaps<'a>(vnet_cidrs: &'a [VnetCidr], gap_mask: u8) -> Vec<GapEvent<'a>> {
             }
         }
 
-        // Subnets (and vgaps) inside this VNet CIDR.
-        let mut inner_ip = vc.cidr.lo();
-        for subnet in &vc.subnets {
-            if let Some(sub_cidr) = subnet.subnet_cidr {
-                // Vgap before this subnet.
-                while inner_ip < sub_cidr.lo() {
-                    let mask = find_biggest_subnet(inner_ip, gap_mask, sub_cidr);
-                    let block = Ipv4 {
-                        addr: inner_ip,
-                        mask,
-                    };
-                    events.push(GapEvent {
-                        cidr: block,
-                        kind: GapKind::Vnet(vc),
-                    });
-                    inner_ip = next_subnet_ipv4(block, None).unwrap().lo();
-                }
+        events.extend(inner);
+        current_ip = Some(next_subnet_ipv4(vc.cidr, None).unwrap().lo());
+    }
+
+    events
+}
+
+/// Subnet and vgap events inside one VNet CIDR, in address order.
+fn vnet_events(vc: &VnetCidr, gap_mask: u8) -> Vec<GapEvent<'_>> {
+    let mut events = Vec::new();
+    let mut inner_ip = vc.cidr.lo();
+    for subnet in &vc.subnets {
+        if let Some(sub_cidr) = subnet.subnet_cidr {
+            // Vgap before this subnet.
+            while inner_ip < sub_cidr.lo() {
+                let mask = find_biggest_subnet(inner_ip, gap_mask, sub_cidr);
+                let block = Ipv4 {
+                    addr: inner_ip,
+                    mask,
+                };
                 events.push(GapEvent {
-                    cidr: sub_cidr,
-                    kind: GapKind::Subnet(subnet),
+                    cidr: block,
+                    kind: GapKind::Vnet(vc),
                 });
-                inner_ip = next_subnet_ipv4(sub_cidr, None).unwrap().lo();
+                inner_ip = next_subnet_ipv4(block, None).unwrap().lo();
             }
-        }
-
-        // Trailing vgap to end of VNet CIDR.
-        while inner_ip <= vc.cidr.hi() {
-            let mask = find_biggest_subnet_within(inner_ip, gap_mask, vc.cidr);
-            let block = Ipv4 {
-                addr: inner_ip,
-                mask,
-            };
             events.push(GapEvent {
-                cidr: block,
-                kind: GapKind::Vnet(vc),
+                cidr: sub_cidr,
+                kind: GapKind::Subnet(subnet),
             });
-            inner_ip = next_subnet_ipv4(block, None).unwrap().lo();
+            inner_ip = next_subnet_ipv4(sub_cidr, None).unwrap().lo();
         }
-
-        current_ip = Some(next_subnet_ipv4(vc.cidr, None).unwrap().lo());
     }
 
+    // Trailing vgap to end of VNet CIDR.
+    while inner_ip <= vc.cidr.hi() {
+        let mask = find_biggest_subnet_within(inner_ip, gap_mask, vc.cidr);
+        let block = Ipv4 {
+            addr: inner_ip,
+            mask,
+        };
+        events.push(GapEvent {
+            cidr: block,
+            kind: GapKind::Vnet(vc),
+        });
+        inner_ip = next_subnet_ipv4(block, None).unwrap().lo();
+    }
     events
 }
 
@@ -121,10 +203,11 @@ pub struct PrevVnetContext {
     pub vnet_name: String,
     pub subscription_name: String,
     pub subscription_id: String,
+    pub resource_group: String,
 }
 
 /// Represents a row of subnet data for output.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SubnetPrintRow {
     /// Row index (0 for gap subnets).
     pub j: usize,
@@ -152,8 +235,42 @@ pub struct SubnetPrintRow {
     pub dns: String,
     /// Subscription ID.
     pub subscription_id: String,
+    /// Resource group of the VNet ("None" for gaps outside a VNet).
+    pub resource_group: String,
+    /// Well-known subnet role label (empty for ordinary subnets and gaps).
+    pub role: String,
+    /// NAT gateway name (empty for gaps).
+    pub nat_gateway: String,
+    /// Load balancers with a frontend in the subnet (empty for gaps).
+    pub load_balancers: String,
+    /// Flow log coverage (`none`, `flow-logs`, `traffic-analytics`); empty
+    /// unless flow logs were loaded.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub flow_logs: String,
+    /// Provenance: where the subnet record comes from (`azure`, `reserved`,
+    /// `on-prem`, `planned`); empty for gaps.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub source: String,
+    /// Provenance: when the subnet was fetched from Azure (RFC 3339).
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub fetched_at: String,
+    /// Provenance: Resource Graph result page the subnet came from.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub query_block: String,
+    /// Date the subnet's current unbroken run of snapshots starts; empty
+    /// unless the column was requested.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub first_seen: String,
+    /// Date Azure last returned the subnet: its record's fetch date, or a
+    /// later snapshot it is in.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub last_seen: String,
     /// Number of IP configurations using this subnet.
     pub ip_configurations_count: u32,
+    /// Extra columns computed by registered row processors
+    /// ([`crate::report::RowProcessor`]), by header.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
 }
 
 // ─── GapFinder ───────────────────────────────────────────────────────────────
@@ -279,7 +396,7 @@ pub fn process_subnet_row(
             .vnet_cidr
             .is_some_and(|vnet| vnet.contains(next_ip));
 
-        let (gap_label, gap_vnet_cidr, gap_vnet_name, gap_sub_name, gap_sub_id) =
+        let (gap_label, gap_vnet_cidr, gap_vnet_name, gap_sub_name, gap_sub_id, gap_rg) =
             if gap_in_current_vnet {
                 (
                     "-vgap-",
@@ -287,6 +404,7 @@ pub fn process_subnet_row(
                     s.vnet_name.clone(),
                     s.subscription_name.clone(),
                     s.subscription_id.clone(),
+                    s.resource_group.clone(),
                 )
             } else if gap_in_prev_vnet {
                 (
@@ -297,6 +415,7 @@ pub fn process_subnet_row(
                     prev_vnet_ctx.vnet_name.clone(),
                     prev_vnet_ctx.subscription_name.clone(),
                     prev_vnet_ctx.subscription_id.clone(),
+                    prev_vnet_ctx.resource_group.clone(),
                 )
             } else {
                 (
@@ -305,6 +424,7 @@ pub fn process_subnet_row(
                     "None".to_string(),
                     "None".to_string(),
                     "None".to_string(),
+                    "None".to_string(),
                 )
             };
 
@@ -322,7 +442,18 @@ pub fn process_subnet_row(
             nsg: "Unused_nsg".to_string(),
             dns: "Unused_dns".to_string(),
             subscription_id: gap_sub_id,
+            resource_group: gap_rg,
+            role: String::new(),
+            nat_gateway: String::new(),
+            load_balancers: String::new(),
+            flow_logs: String::new(),
+            source: String::new(),
+            fetched_at: String::new(),
+            query_block: String::new(),
             ip_configurations_count: 0,
+            first_seen: String::new(),
+            last_seen: String::new(),
+            extra: BTreeMap::new(),
         });
 
         next_ip = next_subnet_ipv4(next_subnet, None).unwrap().lo();
@@ -333,6 +464,7 @@ pub fn process_subnet_row(
         vnet_name: s.vnet_name.clone(),
         subscription_name: s.subscription_name.clone(),
         subscription_id: s.subscription_id.clone(),
+        resource_group: s.resource_group.clone(),
     };
 
     // Add the actual subnet row
@@ -351,10 +483,21 @@ pub fn process_subnet_row(
         vnet_cidr: s.vnet_cidr.to_string(),
         vnet_name: s.vnet_name.clone(),
         location: s.location.clone(),
-        nsg: extract_nsg_name(s.nsg.as_deref()),
+        nsg: extract_resource_name(s.nsg.as_deref()),
         dns: format_dns_servers(s.dns_servers.as_deref()),
         subscription_id: s.subscription_id.clone(),
+        resource_group: s.resource_group.clone(),
+        role: role_label(s),
+        nat_gateway: extract_resource_name(s.nat_gateway.as_deref()),
+        load_balancers: format_load_balancers(&s.load_balancers),
+        flow_logs: String::new(),
+        source: s.source.to_string(),
+        fetched_at: s.fetched_at.clone().unwrap_or_default(),
+        query_block: s.query_block.map(|b| b.to_string()).unwrap_or_default(),
         ip_configurations_count: s.ip_configurations_count.unwrap_or(0),
+        first_seen: String::new(),
+        last_seen: String::new(),
+        extra: BTreeMap::new(),
     });
 
     next_ip = next_subnet_ipv4(subnet_cidr, None).unwrap().lo();
@@ -381,22 +524,42 @@ fn create_row_from_subnet(
         vnet_cidr: s.vnet_cidr.to_string(),
         vnet_name: s.vnet_name.clone(),
         location: s.location.clone(),
-        nsg: extract_nsg_name(s.nsg.as_deref()),
+        nsg: extract_resource_name(s.nsg.as_deref()),
         dns: format_dns_servers(s.dns_servers.as_deref()),
         subscription_id: s.subscription_id.clone(),
+        resource_group: s.resource_group.clone(),
+        role: role_label(s),
+        nat_gateway: extract_resource_name(s.nat_gateway.as_deref()),
+        load_balancers: format_load_balancers(&s.load_balancers),
+        flow_logs: String::new(),
+        source: s.source.to_string(),
+        fetched_at: s.fetched_at.clone().unwrap_or_default(),
+        query_block: s.query_block.map(|b| b.to_string()).unwrap_or_default(),
         ip_configurations_count: s.ip_configurations_count.unwrap_or(0),
+        first_seen: String::new(),
+        last_seen: String::new(),
+        extra: BTreeMap::new(),
     }
 }
 
-/// Extract NSG name from full resource ID.
-pub(crate) fn extract_nsg_name(nsg: Option<&str>) -> String {
-    nsg.unwrap_or("None")
+/// Extract the resource name (e.g. NSG, NAT gateway) from a full resource ID.
+pub(crate) fn extract_resource_name(id: Option<&str>) -> String {
+    id.unwrap_or("None")
         .split('/')
         .next_back()
         .unwrap_or("None")
         .to_string()
 }
 
+/// Format load balancer names as a comma-separated string.
+pub(crate) fn format_load_balancers(load_balancers: &[String]) -> String {
+    if load_balancers.is_empty() {
+        "None".to_string()
+    } else {
+        load_balancers.join(",")
+    }
+}
+
 /// Format DNS servers as a comma-separated string.
 pub(crate) fn format_dns_servers(dns: Option<&[String]>) -> String {
     dns.map(|servers| servers.join(","))
@@ -439,7 +602,18 @@ pub fn fill_trailing_vgap(
             nsg: "Unused_nsg".to_string(),
             dns: "Unused_dns".to_string(),
             subscription_id: prev_vnet_ctx.subscription_id.clone(),
+            resource_group: prev_vnet_ctx.resource_group.clone(),
+            role: String::new(),
+            nat_gateway: String::new(),
+            load_balancers: String::new(),
+            flow_logs: String::new(),
+            source: String::new(),
+            fetched_at: String::new(),
+            query_block: String::new(),
             ip_configurations_count: 0,
+            first_seen: String::new(),
+            last_seen: String::new(),
+            extra: BTreeMap::new(),
         });
 
         next_ip = next_subnet_ipv4(next_subnet, None).unwrap().lo();
@@ -571,12 +745,10 @@ mod tests {
     }
     // Helper to build a minimal Subnet for gap tests.
     fn make_subnet(cidr: &str, vnet_cidr: &str, vnet_name: &str, subnet_name: &str) -> Subnet {
-        let mut s: Subnet = Default::default();
-        s.vnet_name = vnet_name.to_string();
-        s.vnet_cidr = Ipv4::new(vnet_cidr).unwrap();
-        s.subnet_name = subnet_name.to_string();
-        s.subnet_cidr = Some(Ipv4::new(cidr).unwrap());
-        s
+        Subnet {
+            vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+            ..Subnet::test(vnet_name, subnet_name, cidr)
+        }
     }
 
     const SKIP: Ipv4Addr = Ipv4Addr::new(10, 17, 255, 255);
@@ -653,11 +825,10 @@ mod tests {
 
     #[test]
     fn test_process_subnet_row_01() {
-        let mut result: Subnet = Default::default();
-        result.vnet_name = "jenkinsarm-vnet".to_string();
-        result.vnet_cidr = Ipv4::new("10.0.0.0/16").unwrap();
-        result.subnet_name = "jenkinsarm-snet".to_string();
-        result.subnet_cidr = Some(Ipv4::new("10.0.0.0/24").unwrap());
+        let result = Subnet {
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            ..Subnet::test("buildagent-vnet", "buildagent-snet", "10.0.0.0/24")
+        };
 
         let (next_ip, _prev_vnet_ctx, print_rows) = process_subnet_row(
             &result,
@@ -668,7 +839,7 @@ mod tests {
             Ipv4Addr::new(10, 17, 255, 255),
         );
 
-        assert_eq!(result.subnet_name, "jenkinsarm-snet");
+        assert_eq!(result.subnet_name, "buildagent-snet");
         assert_eq!(next_ip.to_string(), "10.0.1.0");
         assert_eq!(print_rows.len(), 1, "Expected 1 row for subnet");
     }
@@ -682,6 +853,7 @@ mod tests {
             subscription_id: "sub-001".to_string(),
             subscription_name: "Test Sub".to_string(),
             location: "eastus".to_string(),
+            resource_group: "rg-net".to_string(),
             subnets,
         }
     }
@@ -834,4 +1006,31 @@ mod tests {
         assert!(!trailing.is_empty(), "trailing vgaps expected");
         assert!(trailing.iter().all(|r| r.gap == "-vgap-"));
     }
+
+    /// A prefix of a multi-prefix VNet without subnets is free space of that
+    /// VNet, not a gap between VNets.
+    #[test]
+    fn empty_address_prefix_is_attributed_to_its_vnet() {
+        let mut s = make_subnet("10.0.0.0/24", "10.0.0.0/24", "hub", "app");
+        s.vnet_address_space = vec![s.vnet_cidr, Ipv4::new("10.0.2.0/24").unwrap()];
+        let vnet_cidrs = group_vnet_cidrs(&[s]);
+        assert_eq!(vnet_cidrs.len(), 2);
+        assert_eq!(vnet_cidrs[1].cidr, Ipv4::new("10.0.2.0/24").unwrap());
+        assert!(vnet_cidrs[1].subnets.is_empty());
+
+        let events = gaps(&vnet_cidrs, 4);
+        let kinds: Vec<(String, &str)> = events
+            .iter()
+            .map(|e| {
+                let kind = match e.kind {
+                    GapKind::Gap => "gap",
+                    GapKind::Vnet(vc) => vc.vnet_name.as_str(),
+                    GapKind::Subnet(s) => s.subnet_name.as_str(),
+                };
+                (e.cidr.to_string(), kind)
+            })
+            .collect();
+        assert!(kinds.contains(&("10.0.1.0/24".to_string(), "gap")));
+        assert!(kinds.contains(&("10.0.2.0/24".to_string(), "hub")));
+    }
 }
@@ -0,0 +1,221 @@
+//! VPN / ExpressRoute gateway address space.
+//!
+//! Lists each gateway with its SKU, the `GatewaySubnet` it sits in and its
+//! point-to-site client address pool. P2S pools are address space like any
+//! other: clients get addresses from them and reach the VNet through the
+//! gateway, so a pool that overlaps VNet space breaks routing for those
+//! clients. The pools are reported against every VNet they overlap and can
+//! be merged into the subnet data as stand-in VNets for the overlap checks.
+
+use super::MetadataIndex;
+use crate::azure::{Data, GatewayData, GatewayRow};
+use crate::models::{Ipv4, Subnet, SubnetSource};
+
+/// A gateway with the address space it uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GatewaySummary {
+    pub name: String,
+    pub subscription_name: String,
+    pub vnet_name: String,
+    /// `Vpn` or `ExpressRoute`.
+    pub gateway_type: String,
+    pub sku: String,
+    /// Prefix of the `GatewaySubnet`, when it is in the subnet data.
+    pub gateway_subnet: Option<Ipv4>,
+    pub p2s_pools: Vec<Ipv4>,
+}
+
+/// A P2S client pool overlapping the address space of a VNet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct P2sOverlap {
+    pub gateway: String,
+    pub pool: Ipv4,
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub vnet_cidr: Ipv4,
+}
+
+/// The valid P2S pool prefixes of `gw`; invalid ones are skipped with a warning.
+fn p2s_pools(gw: &GatewayRow) -> Vec<Ipv4> {
+    gw.p2s_address_pool
+        .iter()
+        .filter_map(|p| match Ipv4::new(p) {
+            Ok(cidr) => Some(cidr),
+            Err(e) => {
+                log::warn!("Gateway '{}': skipping P2S pool '{p}': {e}", gw.name);
+                None
+            }
+        })
+        .collect()
+}
+
+/// One summary per gateway, with its `GatewaySubnet` looked up in `subnets`.
+pub fn gateway_summary(gateways: &GatewayData, subnets: &Data) -> Vec<GatewaySummary> {
+    let index = MetadataIndex::new(subnets);
+    gateways
+        .data
+        .iter()
+        .map(|gw| {
+            let gateway_subnet = index.vnet(&gw.vnet_id).and_then(|v| v.gateway_subnet);
+            let subscription_name = index
+                .subscription_name(&gw.subscription_id)
+                .unwrap_or(&gw.subscription_id)
+                .to_string();
+            GatewaySummary {
+                name: gw.name.clone(),
+                subscription_name,
+                vnet_name: gw.vnet_name().to_string(),
+                gateway_type: gw.gateway_type.clone(),
+                sku: gw.sku.clone(),
+                gateway_subnet: gateway_subnet.and_then(|s| s.subnet_cidr),
+                p2s_pools: p2s_pools(gw),
+            }
+        })
+        .collect()
+}
+
+/// Stand-in VNets (one subnet covering the pool) for every P2S pool, with
+/// source [`SubnetSource::VpnClient`].
+pub fn p2s_subnets(gateways: &GatewayData) -> Vec<Subnet> {
+    gateways
+        .data
+        .iter()
+        .flat_map(|gw| {
+            p2s_pools(gw).into_iter().map(move |pool| Subnet {
+                resource_group: gw.resource_group.clone(),
+                vnet_name: format!("{}-p2s", gw.name),
+                vnet_cidr: pool,
+                vnet_address_space: vec![pool],
+                subnet_name: format!("{}-p2s", gw.name),
+                subnet_cidr: Some(pool),
+                location: gw.location.clone(),
+                subscription_id: SubnetSource::VpnClient.to_string(),
+                subscription_name: SubnetSource::VpnClient.to_string(),
+                source: SubnetSource::VpnClient,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// P2S pools overlapping the address space of a VNet in `subnets`, one entry
+/// per pool and VNet prefix.
+pub fn p2s_overlaps(gateways: &GatewayData, subnets: &Data) -> Vec<P2sOverlap> {
+    let mut out: Vec<P2sOverlap> = Vec::new();
+    for gw in &gateways.data {
+        for pool in p2s_pools(gw) {
+            for s in subnets.data.iter().filter(|s| s.source.is_azure_resource()) {
+                for cidr in &s.vnet_address_space {
+                    let overlaps = pool.lo() <= cidr.hi() && cidr.lo() <= pool.hi();
+                    let entry = P2sOverlap {
+                        gateway: gw.name.clone(),
+                        pool,
+                        subscription_name: s.subscription_name.clone(),
+                        vnet_name: s.vnet_name.clone(),
+                        vnet_cidr: *cidr,
+                    };
+                    if overlaps && !out.contains(&entry) {
+                        out.push(entry);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Render the gateway report and P2S overlaps as terminal lines.
+pub fn format_gateways(gateways: &[GatewaySummary], overlaps: &[P2sOverlap]) -> String {
+    if gateways.is_empty() {
+        return "No VPN or ExpressRoute gateways found.".to_string();
+    }
+    let mut lines: Vec<String> = gateways
+        .iter()
+        .map(|g| {
+            let subnet = g
+                .gateway_subnet
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            let p2s = if g.p2s_pools.is_empty() {
+                String::new()
+            } else {
+                let pools: Vec<String> = g.p2s_pools.iter().map(|p| p.to_string()).collect();
+                format!(", P2S pool {}", pools.join(", "))
+            };
+            format!(
+                "GATEWAY: '{}' ({} {}) in '{}/{}' - GatewaySubnet {subnet}{p2s}",
+                g.name, g.gateway_type, g.sku, g.subscription_name, g.vnet_name
+            )
+        })
+        .collect();
+    lines.extend(overlaps.iter().map(|o| {
+        format!(
+            "P2S OVERLAP: '{}' client pool {} overlaps VNet '{}' {} in '{}'",
+            o.gateway, o.pool, o.vnet_name, o.vnet_cidr, o.subscription_name
+        )
+    }));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subnet(vnet: &str, vnet_cidr: &str, name: &str, cidr: &str) -> Subnet {
+        let mut s = Subnet {
+            subscription_id: "sub-1".to_string(),
+            subscription_name: "Connectivity".to_string(),
+            vnet_id: format!("/sub-1/vnets/{vnet}"),
+            vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+            ..Subnet::test(vnet, name, cidr)
+        };
+        s.vnet_address_space = vec![s.vnet_cidr];
+        s
+    }
+
+    #[test]
+    fn gateways_are_summarised_and_overlapping_p2s_pools_reported() {
+        let subnets = Data {
+            data: vec![
+                subnet("hub", "10.0.0.0/22", "GatewaySubnet", "10.0.0.0/27"),
+                subnet("dev", "10.30.0.0/21", "app", "10.30.0.0/24"),
+            ],
+            ..Default::default()
+        };
+        let gateways = GatewayData {
+            data: vec![GatewayRow {
+                subscription_id: "sub-1".to_string(),
+                name: "vpngw-hub".to_string(),
+                gateway_type: "Vpn".to_string(),
+                sku: "VpnGw1AZ".to_string(),
+                vnet_id: "/SUB-1/vnets/hub".to_string(),
+                p2s_address_pool: vec!["10.30.4.0/24".to_string(), "172.16.0.0/24".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let summary = gateway_summary(&gateways, &subnets);
+        assert_eq!(summary[0].vnet_name, "hub");
+        assert_eq!(
+            summary[0].gateway_subnet,
+            Some(Ipv4::new("10.0.0.0/27").unwrap())
+        );
+        let overlaps = p2s_overlaps(&gateways, &subnets);
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!(overlaps[0].vnet_name, "dev");
+        let text = format_gateways(&summary, &overlaps);
+        assert!(text.contains(
+            "GATEWAY: 'vpngw-hub' (Vpn VpnGw1AZ) in 'Connectivity/hub' - GatewaySubnet \
+             10.0.0.0/27, P2S pool 10.30.4.0/24, 172.16.0.0/24"
+        ));
+        assert!(text.contains(
+            "P2S OVERLAP: 'vpngw-hub' client pool 10.30.4.0/24 overlaps VNet 'dev' 10.30.0.0/21"
+        ));
+        let stand_ins = p2s_subnets(&gateways);
+        assert_eq!(stand_ins.len(), 2);
+        assert!(stand_ins
+            .iter()
+            .all(|s| s.source == SubnetSource::VpnClient));
+    }
+}
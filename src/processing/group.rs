@@ -0,0 +1,278 @@
+//! Grouping and filtering of subnets by subscription metadata.
+//!
+//! Uses the subscription metadata query (state, tags, management group chain)
+//! to scope the report to a management group or summarise it per group.
+
+use super::MetadataIndex;
+use crate::azure::{Data, SubscriptionData};
+use crate::models::Subnet;
+use crate::output::format::thousands;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+
+/// Key used for grouping subnets in summaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// Group by subscription display name.
+    Subscription,
+    /// Group by management group path (root → direct parent).
+    ManagementGroup,
+    /// Group by resource group, qualified with the subscription name since
+    /// resource group names are only unique within a subscription.
+    ResourceGroup,
+}
+
+/// Label used for subnets whose subscription has no metadata record.
+const UNKNOWN_GROUP: &str = "(unknown)";
+
+/// Keep only subnets whose subscription sits under `management_group`.
+///
+/// `management_group` is matched case-insensitively against the ID and display
+/// name of every ancestor. Subnets of subscriptions without metadata are dropped.
+pub fn filter_by_management_group(
+    mut data: Data,
+    subscriptions: &SubscriptionData,
+    management_group: &str,
+) -> Data {
+    let in_scope: HashSet<&str> = subscriptions
+        .data
+        .iter()
+        .filter(|s| s.in_management_group(management_group))
+        .map(|s| s.subscription_id.as_str())
+        .collect();
+
+    let before = data.data.len();
+    data.data
+        .retain(|s| in_scope.contains(s.subscription_id.as_str()));
+    data.count = data.data.len() as i32;
+    log::info!(
+        "Management group filter '{management_group}': kept {} of {before} subnets ({} subscriptions in scope)",
+        data.data.len(),
+        in_scope.len()
+    );
+    data
+}
+
+/// Return the group label for `subnet` under the chosen grouping; borrowed
+/// from `subnet` or `index` where possible.
+pub fn group_key<'a>(
+    subnet: &'a Subnet,
+    index: &'a MetadataIndex<'_>,
+    group_by: GroupBy,
+) -> Cow<'a, str> {
+    match group_by {
+        GroupBy::Subscription => Cow::Borrowed(&subnet.subscription_name),
+        GroupBy::ManagementGroup => Cow::Borrowed(
+            index
+                .subscription(&subnet.subscription_id)
+                .and_then(|s| s.management_group_path.as_deref())
+                .unwrap_or(UNKNOWN_GROUP),
+        ),
+        GroupBy::ResourceGroup if subnet.resource_group.is_empty() => {
+            Cow::Owned(format!("{} / {UNKNOWN_GROUP}", subnet.subscription_name))
+        }
+        GroupBy::ResourceGroup => Cow::Owned(format!(
+            "{} / {}",
+            subnet.subscription_name, subnet.resource_group
+        )),
+    }
+}
+
+// group key → (vnets seen, subnet count, address count)
+type GroupTotals<'a> = BTreeMap<Cow<'a, str>, (HashSet<(&'a str, &'a str)>, usize, u64)>;
+
+/// Per-group totals for the summary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupSummary {
+    pub key: String,
+    pub vnet_count: usize,
+    pub subnet_count: usize,
+    /// Total addresses allocated to subnets in this group.
+    pub address_count: u64,
+}
+
+/// Summarise subnets per group, sorted by group key.
+pub fn summarize_groups(
+    data: &Data,
+    subscriptions: &SubscriptionData,
+    group_by: GroupBy,
+) -> Vec<GroupSummary> {
+    let index = MetadataIndex::new(data).with_subscriptions(subscriptions);
+    let mut groups: GroupTotals<'_> = BTreeMap::new();
+
+    for subnet in &data.data {
+        let entry = groups
+            .entry(group_key(subnet, &index, group_by))
+            .or_default();
+        entry
+            .0
+            .insert((subnet.vnet_name.as_str(), subnet.subscription_id.as_str()));
+        entry.1 += 1;
+        entry.2 += subnet
+            .subnet_cidr
+            .map(|c| 1u64 << (32 - u32::from(c.mask)))
+            .unwrap_or(0);
+    }
+
+    groups
+        .into_iter()
+        .map(|(key, (vnets, subnet_count, address_count))| GroupSummary {
+            key: key.into_owned(),
+            vnet_count: vnets.len(),
+            subnet_count,
+            address_count,
+        })
+        .collect()
+}
+
+/// Render group summaries as terminal lines.
+pub fn format_group_summary(groups: &[GroupSummary], group_by: GroupBy) -> String {
+    let label = match group_by {
+        GroupBy::Subscription => "SUBSCRIPTION",
+        GroupBy::ManagementGroup => "MANAGEMENT GROUP",
+        GroupBy::ResourceGroup => "RESOURCE GROUP",
+    };
+    groups
+        .iter()
+        .map(|g| {
+            format!(
+                "{label}: '{}' - {} VNet(s), {} subnet(s), {} address(es)",
+                g.key,
+                g.vnet_count,
+                g.subnet_count,
+                thousands(g.address_count)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Ipv4, ManagementGroupRef, Subscription};
+
+    fn make_subnet(vnet_name: &str, subscription_id: &str, subnet_cidr: &str) -> Subnet {
+        Subnet {
+            vnet_name: vnet_name.to_string(),
+            subscription_id: subscription_id.to_string(),
+            subscription_name: format!("{subscription_id}-name"),
+            subnet_cidr: Some(Ipv4::new(subnet_cidr).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    fn make_subs() -> SubscriptionData {
+        let mg = |name: &str| ManagementGroupRef {
+            name: name.to_string(),
+            display_name: String::new(),
+        };
+        SubscriptionData {
+            data: vec![
+                Subscription {
+                    subscription_id: "prod".to_string(),
+                    subscription_name: "Prod".to_string(),
+                    management_group_chain: vec![mg("corp"), mg("root")],
+                    ..Default::default()
+                },
+                Subscription {
+                    subscription_id: "lab".to_string(),
+                    subscription_name: "Lab".to_string(),
+                    management_group_chain: vec![mg("sandbox"), mg("root")],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn make_data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            count: subnets.len() as i32,
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filter_keeps_only_subscriptions_under_management_group() {
+        let data = make_data(vec![
+            make_subnet("a", "prod", "10.0.0.0/24"),
+            make_subnet("b", "lab", "10.1.0.0/24"),
+            make_subnet("c", "orphan", "10.2.0.0/24"),
+        ]);
+        let out = filter_by_management_group(data, &make_subs(), "CORP");
+        assert_eq!(out.data.len(), 1);
+        assert_eq!(out.data[0].vnet_name, "a");
+        assert_eq!(out.count, 1);
+    }
+
+    #[test]
+    fn root_management_group_keeps_all_known_subscriptions() {
+        let data = make_data(vec![
+            make_subnet("a", "prod", "10.0.0.0/24"),
+            make_subnet("b", "lab", "10.1.0.0/24"),
+            make_subnet("c", "orphan", "10.2.0.0/24"),
+        ]);
+        let out = filter_by_management_group(data, &make_subs(), "root");
+        assert_eq!(out.data.len(), 2);
+    }
+
+    #[test]
+    fn summarize_by_management_group_counts_vnets_and_addresses() {
+        let data = make_data(vec![
+            make_subnet("a", "prod", "10.0.0.0/24"),
+            make_subnet("a", "prod", "10.0.1.0/25"),
+            make_subnet("b", "lab", "10.1.0.0/24"),
+            make_subnet("c", "orphan", "10.2.0.0/28"),
+        ]);
+        let groups = summarize_groups(&data, &make_subs(), GroupBy::ManagementGroup);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].key, "(unknown)");
+        assert_eq!(groups[1].key, "root / corp");
+        assert_eq!(groups[1].vnet_count, 1);
+        assert_eq!(groups[1].subnet_count, 2);
+        assert_eq!(groups[1].address_count, 384);
+        assert_eq!(groups[2].key, "root / sandbox");
+    }
+
+    #[test]
+    fn summarize_by_resource_group_qualifies_with_subscription() {
+        let mut net = make_subnet("a", "prod", "10.0.0.0/24");
+        net.resource_group = "rg-net".to_string();
+        let mut other_sub = make_subnet("b", "lab", "10.1.0.0/24");
+        other_sub.resource_group = "rg-net".to_string();
+        let data = make_data(vec![
+            net.clone(),
+            net,
+            other_sub,
+            make_subnet("c", "prod", "10.2.0.0/24"),
+        ]);
+        let groups = summarize_groups(&data, &make_subs(), GroupBy::ResourceGroup);
+        let keys: Vec<_> = groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "lab-name / rg-net",
+                "prod-name / (unknown)",
+                "prod-name / rg-net"
+            ]
+        );
+        assert_eq!(groups[2].subnet_count, 2);
+    }
+
+    #[test]
+    fn format_group_summary_labels_grouping() {
+        let groups = vec![GroupSummary {
+            key: "Prod".to_string(),
+            vnet_count: 1,
+            subnet_count: 2,
+            address_count: 512,
+        }];
+        let out = format_group_summary(&groups, GroupBy::Subscription);
+        assert_eq!(
+            out,
+            "SUBSCRIPTION: 'Prod' - 1 VNet(s), 2 subnet(s), 512 address(es)"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Estate-wide address space ownership (`--heatmap`).
+//!
+//! Cuts a supernet (default `10.0.0.0/8`) into equal slices (default `/16`)
+//! and reports, per slice, which subscriptions and VNets hold address space
+//! in it and how much of it no VNet holds. Ownership is by VNet address
+//! space, so a VNet counts for its whole prefix whether or not it has
+//! subnets there.
+
+use crate::azure::Data;
+use crate::models::Ipv4;
+use std::net::Ipv4Addr;
+
+/// Supernet sliced when none is configured.
+pub const DEFAULT_HEATMAP_SUPERNET: &str = "10.0.0.0/8";
+/// Slice size when none is configured.
+pub const DEFAULT_HEATMAP_SLICE: u8 = 16;
+/// Upper bound on the number of slices, so a mistyped `/32` stays readable.
+const MAX_SLICES: u64 = 65_536;
+
+/// Address space one VNet holds in a slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceOwner {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub addresses: u64,
+}
+
+/// One slice of the supernet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeatmapSlice {
+    pub cidr: Ipv4,
+    /// VNets holding space in the slice, most addresses first.
+    pub owners: Vec<SliceOwner>,
+    /// Addresses of the slice held by no VNet.
+    pub free: u64,
+}
+
+impl HeatmapSlice {
+    /// Addresses in the slice.
+    pub fn size(&self) -> u64 {
+        1u64 << (32 - u32::from(self.cidr.mask))
+    }
+
+    /// Addresses held by a VNet.
+    pub fn used(&self) -> u64 {
+        self.size() - self.free
+    }
+}
+
+/// Slice `supernet` into `/slice_mask` blocks and attribute the VNet address
+/// space in `data` to them. Reserved, on-prem and VPN client ranges are not
+/// VNets and are left out.
+pub fn heatmap(data: &Data, supernet: Ipv4, slice_mask: u8) -> Result<Vec<HeatmapSlice>, String> {
+    if slice_mask < supernet.mask || slice_mask > 32 {
+        return Err(format!(
+            "Heatmap slice /{slice_mask} must be between /{} and /32 for {supernet}",
+            supernet.mask
+        ));
+    }
+    let count = 1u64 << (slice_mask - supernet.mask);
+    if count > MAX_SLICES {
+        return Err(format!(
+            "Heatmap slice /{slice_mask} cuts {supernet} into {count} slices (at most {MAX_SLICES})"
+        ));
+    }
+
+    // Each VNet prefix once, with its owner.
+    let mut prefixes: Vec<(&str, &str, Ipv4)> = Vec::new();
+    for s in data.data.iter().filter(|s| s.source.is_azure_resource()) {
+        for cidr in &s.vnet_address_space {
+            let entry = (s.subscription_name.as_str(), s.vnet_name.as_str(), *cidr);
+            if !prefixes.contains(&entry) {
+                prefixes.push(entry);
+            }
+        }
+    }
+
+    let (super_lo, _) = supernet.range();
+    let size = 1u64 << (32 - u32::from(slice_mask));
+    let slices = (0..count)
+        .map(|i| {
+            let lo = super_lo + i * size;
+            let hi = lo + size;
+            let cidr = Ipv4 {
+                addr: Ipv4Addr::from(lo as u32),
+                mask: slice_mask,
+            };
+            let mut owners: Vec<SliceOwner> = Vec::new();
+            let mut held: Vec<(u64, u64)> = Vec::new();
+            for (sub, vnet, prefix) in &prefixes {
+                let (p_lo, p_hi) = prefix.range();
+                if p_hi <= lo || hi <= p_lo {
+                    continue;
+                }
+                let clipped = (p_lo.max(lo), p_hi.min(hi));
+                held.push(clipped);
+                match owners
+                    .iter_mut()
+                    .find(|o| o.subscription_name == *sub && o.vnet_name == *vnet)
+                {
+                    Some(o) => o.addresses += clipped.1 - clipped.0,
+                    None => owners.push(SliceOwner {
+                        subscription_name: sub.to_string(),
+                        vnet_name: vnet.to_string(),
+                        addresses: clipped.1 - clipped.0,
+                    }),
+                }
+            }
+            // Overlapping prefixes (duplicates) count once towards used space.
+            held.sort_unstable();
+            let mut used = 0;
+            let mut cursor = lo;
+            for (h_lo, h_hi) in held {
+                let h_lo = h_lo.max(cursor);
+                if h_hi > h_lo {
+                    used += h_hi - h_lo;
+                    cursor = h_hi;
+                }
+            }
+            owners.sort_by(|a, b| {
+                b.addresses
+                    .cmp(&a.addresses)
+                    .then_with(|| a.subscription_name.cmp(&b.subscription_name))
+                    .then_with(|| a.vnet_name.cmp(&b.vnet_name))
+            });
+            HeatmapSlice {
+                cidr,
+                owners,
+                free: size - used,
+            }
+        })
+        .collect();
+    Ok(slices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Subnet, SubnetSource};
+
+    fn subnet(sub: &str, vnet: &str, space: &[&str]) -> Subnet {
+        let mut s = Subnet {
+            subscription_name: sub.to_string(),
+            vnet_name: vnet.to_string(),
+            vnet_address_space: space.iter().map(|c| Ipv4::new(c).unwrap()).collect(),
+            ..Default::default()
+        };
+        s.vnet_cidr = s.vnet_address_space[0];
+        s
+    }
+
+    #[test]
+    fn slices_report_owners_and_free_space() {
+        let data = Data {
+            data: vec![
+                subnet("Prod", "hub", &["10.0.0.0/17", "10.1.0.0/24"]),
+                subnet("Prod", "hub", &["10.0.0.0/17", "10.1.0.0/24"]),
+                subnet("Dev", "lab", &["10.0.128.0/18"]),
+                Subnet {
+                    source: SubnetSource::OnPrem,
+                    ..subnet("on-prem", "dc", &["10.0.192.0/18"])
+                },
+            ],
+            ..Default::default()
+        };
+        let slices = heatmap(&data, Ipv4::new("10.0.0.0/14").unwrap(), 16).unwrap();
+        assert_eq!(slices.len(), 4);
+        assert_eq!(slices[0].cidr.to_string(), "10.0.0.0/16");
+        assert_eq!(
+            slices[0]
+                .owners
+                .iter()
+                .map(|o| (o.vnet_name.as_str(), o.addresses))
+                .collect::<Vec<_>>(),
+            [("hub", 32_768), ("lab", 16_384)]
+        );
+        assert_eq!(slices[0].free, 16_384);
+        assert_eq!(slices[1].used(), 256);
+        assert!(slices[2].owners.is_empty() && slices[2].free == 65_536);
+
+        assert!(heatmap(&data, Ipv4::new("10.0.0.0/16").unwrap(), 8).is_err());
+        assert!(heatmap(&data, Ipv4::new("10.0.0.0/8").unwrap(), 32).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! Usable host counts of output rows under a [`ReservationModel`]
+//! (`--host-reservation`).
+//!
+//! Rows are built with Azure's 5 reserved addresses per subnet; ranges
+//! mirrored from other platforms (e.g. AWS VPCs merged with `--range`) are
+//! better counted with classic networking (2) or every address (0).
+
+use super::gap_finder::SubnetPrintRow;
+use crate::models::{num_hosts, Ipv4, ReservationModel};
+
+/// Recount `az_hosts` of every row with a CIDR under `model`.
+pub fn count_hosts(mut rows: Vec<SubnetPrintRow>, model: ReservationModel) -> Vec<SubnetPrintRow> {
+    if model == ReservationModel::Azure {
+        return rows;
+    }
+    for row in &mut rows {
+        if let Ok(cidr) = Ipv4::new(&row.subnet_cidr) {
+            row.az_hosts = num_hosts(cidr.mask, model).unwrap_or(0) as usize;
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+    use crate::output::build_rows;
+
+    #[test]
+    fn rows_are_recounted_under_the_selected_model() {
+        let mut s = Subnet {
+            vnet_cidr: Ipv4::new("10.0.0.0/24").unwrap(),
+            ..Subnet::test("vpc-mirror", "app", "10.0.0.0/25")
+        };
+        s.vnet_address_space = vec![s.vnet_cidr];
+        let rows = build_rows(&[s], &[], 4, &[]);
+        let hosts = |rows: &[SubnetPrintRow]| rows.iter().map(|r| r.az_hosts).collect::<Vec<_>>();
+        assert_eq!(hosts(&rows), [123, 123]);
+        assert_eq!(
+            hosts(&count_hosts(rows.clone(), ReservationModel::Classic)),
+            [126, 126]
+        );
+        assert_eq!(
+            hosts(&count_hosts(rows, ReservationModel::None)),
+            [128, 128]
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Reconciliation of Azure Virtual Network Manager IPAM pools with reality.
+//!
+//! An IPAM pool records which prefixes it handed out to which VNet. Those
+//! records drift when VNets are deleted, re-addressed outside AVNM, or created
+//! in pool space without an allocation. Pool records are compared with the
+//! VNet address space from the subnet data; only IPv4 prefixes are checked.
+
+use crate::azure::{Data, IpamPoolRow};
+use crate::models::Ipv4;
+use crate::output::format::thousands;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// How a pool record disagrees with the VNets in Azure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IpamDriftKind {
+    /// The allocated prefix lies outside the pool's address prefixes.
+    OutsidePool,
+    /// The allocation names a VNet that is not in the subnet data.
+    MissingVnet,
+    /// The allocated prefix is not part of the VNet's address space.
+    NotOnVnet,
+    /// A VNet prefix inside a (leaf) pool has no allocation record.
+    Unrecorded,
+}
+
+impl fmt::Display for IpamDriftKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IpamDriftKind::OutsidePool => "allocated outside the pool prefixes",
+            IpamDriftKind::MissingVnet => "allocated to a VNet that no longer exists",
+            IpamDriftKind::NotOnVnet => "allocated but not in the VNet address space",
+            IpamDriftKind::Unrecorded => "VNet uses pool space without an allocation",
+        })
+    }
+}
+
+/// One disagreement between a pool and the VNets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpamDrift {
+    pub kind: IpamDriftKind,
+    /// `<network manager>/<pool>`.
+    pub pool: String,
+    pub vnet_name: String,
+    pub prefix: Option<Ipv4>,
+}
+
+/// Size of a pool against its allocation records and actual VNet usage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpamPoolUsage {
+    /// `<network manager>/<pool>`.
+    pub pool: String,
+    pub prefixes: Vec<Ipv4>,
+    /// Addresses in the pool prefixes.
+    pub size: u64,
+    /// Addresses in the pool's allocation records.
+    pub allocated: u64,
+    /// Addresses of VNet address space inside the pool prefixes.
+    pub in_use: u64,
+}
+
+/// Pool usage and drift, as returned by [`reconcile_ipam_pools`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IpamReconciliation {
+    pub usage: Vec<IpamPoolUsage>,
+    pub drift: Vec<IpamDrift>,
+}
+
+fn addresses(c: &Ipv4) -> u64 {
+    1u64 << (32 - u32::from(c.mask))
+}
+
+fn within(outer: &Ipv4, inner: &Ipv4) -> bool {
+    outer.mask <= inner.mask && outer.contains(inner.lo())
+}
+
+fn ipv4_prefixes(prefixes: &[String]) -> Vec<Ipv4> {
+    prefixes.iter().filter_map(|p| Ipv4::new(p).ok()).collect()
+}
+
+/// Compare the pool allocation records with the VNets in `subnets`.
+///
+/// VNets are matched by resource ID, falling back to the VNet name for
+/// subnet caches written without IDs. Unrecorded VNet space is only reported
+/// for leaf pools: a parent pool hands its space to child pools, not VNets.
+pub fn reconcile_ipam_pools(pools: &[IpamPoolRow], subnets: &Data) -> IpamReconciliation {
+    let mut vnets: HashMap<String, (&str, &[Ipv4])> = HashMap::new();
+    let mut by_name: HashMap<String, String> = HashMap::new();
+    for s in &subnets.data {
+        let key = if s.vnet_id.is_empty() {
+            s.vnet_name.to_ascii_lowercase()
+        } else {
+            s.vnet_id.to_ascii_lowercase()
+        };
+        by_name.insert(s.vnet_name.to_ascii_lowercase(), key.clone());
+        vnets
+            .entry(key)
+            .or_insert((s.vnet_name.as_str(), s.vnet_address_space.as_slice()));
+    }
+    let vnet_key = |id: &str, name: &str| -> Option<String> {
+        let id = id.to_ascii_lowercase();
+        if vnets.contains_key(&id) {
+            return Some(id);
+        }
+        by_name.get(&name.to_ascii_lowercase()).cloned()
+    };
+    let parents: HashSet<(&str, &str)> = pools
+        .iter()
+        .filter(|p| !p.parent_pool_name.is_empty())
+        .map(|p| (p.network_manager.as_str(), p.parent_pool_name.as_str()))
+        .collect();
+
+    let mut result = IpamReconciliation::default();
+    for pool in pools {
+        let name = pool.display_name();
+        let prefixes = ipv4_prefixes(&pool.address_prefixes);
+        let in_pool = |c: &Ipv4| prefixes.iter().any(|p| within(p, c));
+        let mut recorded: HashSet<(String, Ipv4)> = HashSet::new();
+        let mut allocated = 0;
+        for alloc in &pool.allocations {
+            let key = vnet_key(&alloc.vnet_id, &alloc.vnet_name);
+            let vnet_space = key.as_ref().map(|k| vnets[k].1);
+            if key.is_none() {
+                result.drift.push(IpamDrift {
+                    kind: IpamDriftKind::MissingVnet,
+                    pool: name.clone(),
+                    vnet_name: alloc.vnet_name.clone(),
+                    prefix: None,
+                });
+            }
+            for prefix in ipv4_prefixes(&alloc.allocated_prefixes) {
+                allocated += addresses(&prefix);
+                let kind = if !in_pool(&prefix) {
+                    Some(IpamDriftKind::OutsidePool)
+                } else if vnet_space.is_some_and(|space| !space.contains(&prefix)) {
+                    Some(IpamDriftKind::NotOnVnet)
+                } else {
+                    None
+                };
+                if let Some(kind) = kind {
+                    result.drift.push(IpamDrift {
+                        kind,
+                        pool: name.clone(),
+                        vnet_name: alloc.vnet_name.clone(),
+                        prefix: Some(prefix),
+                    });
+                }
+                if let Some(key) = &key {
+                    recorded.insert((key.clone(), prefix));
+                }
+            }
+        }
+
+        let is_leaf = !parents.contains(&(pool.network_manager.as_str(), pool.name.as_str()));
+        let mut in_use = 0;
+        let mut keys: Vec<&String> = vnets.keys().collect();
+        keys.sort();
+        for key in keys {
+            let (vnet_name, space) = vnets[key];
+            for prefix in space.iter().filter(|c| in_pool(c)) {
+                in_use += addresses(prefix);
+                if is_leaf && !recorded.contains(&(key.clone(), *prefix)) {
+                    result.drift.push(IpamDrift {
+                        kind: IpamDriftKind::Unrecorded,
+                        pool: name.clone(),
+                        vnet_name: vnet_name.to_string(),
+                        prefix: Some(*prefix),
+                    });
+                }
+            }
+        }
+        result.usage.push(IpamPoolUsage {
+            pool: name,
+            size: prefixes.iter().map(addresses).sum(),
+            prefixes,
+            allocated,
+            in_use,
+        });
+    }
+    result.drift.sort_by(|a, b| {
+        (&a.pool, a.kind, &a.vnet_name, a.prefix).cmp(&(&b.pool, b.kind, &b.vnet_name, b.prefix))
+    });
+    result
+}
+
+/// Render pool usage followed by the drift as terminal lines.
+pub fn format_ipam_reconciliation(reconciliation: &IpamReconciliation) -> String {
+    let mut lines: Vec<String> = reconciliation
+        .usage
+        .iter()
+        .map(|u| {
+            let prefixes: Vec<String> = u.prefixes.iter().map(|p| p.to_string()).collect();
+            format!(
+                "IPAM POOL: '{}' {} - {} of {} addresses allocated, {} in use by VNets",
+                u.pool,
+                prefixes.join(", "),
+                thousands(u.allocated),
+                thousands(u.size),
+                thousands(u.in_use)
+            )
+        })
+        .collect();
+    for d in &reconciliation.drift {
+        let prefix = d.prefix.map(|p| format!(" {p}")).unwrap_or_default();
+        lines.push(format!(
+            "IPAM DRIFT: '{}' VNet '{}'{prefix} - {}",
+            d.pool, d.vnet_name, d.kind
+        ));
+    }
+    if lines.is_empty() {
+        lines.push("No IPAM pools found.".to_string());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::IpamAllocation;
+    use crate::models::Subnet;
+
+    fn subnet(vnet: &str, space: &[&str]) -> Subnet {
+        let mut s = Subnet {
+            vnet_name: vnet.to_string(),
+            vnet_id: format!("/subscriptions/s/resourceGroups/rg/providers/Microsoft.Network/virtualNetworks/{vnet}"),
+            vnet_address_space: space.iter().map(|c| Ipv4::new(c).unwrap()).collect(),
+            ..Default::default()
+        };
+        s.vnet_cidr = s.vnet_address_space[0];
+        s
+    }
+
+    fn alloc(vnet: &str, prefixes: &[&str]) -> IpamAllocation {
+        IpamAllocation {
+            vnet_id: format!("/subscriptions/s/resourceGroups/rg/providers/Microsoft.Network/virtualNetworks/{vnet}"),
+            vnet_name: vnet.to_string(),
+            allocated_prefixes: prefixes.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn pool(
+        name: &str,
+        parent: &str,
+        prefixes: &[&str],
+        allocations: Vec<IpamAllocation>,
+    ) -> IpamPoolRow {
+        IpamPoolRow {
+            network_manager: "avnm".to_string(),
+            name: name.to_string(),
+            parent_pool_name: parent.to_string(),
+            address_prefixes: prefixes.iter().map(|p| p.to_string()).collect(),
+            allocations,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pool_records_are_reconciled_with_vnets() {
+        let subnets = Data {
+            data: vec![
+                subnet("spoke-a", &["10.1.0.0/24"]),
+                subnet("spoke-b", &["10.1.1.0/24"]),
+                subnet("rogue", &["10.1.2.0/24"]),
+                subnet("elsewhere", &["192.168.0.0/24"]),
+            ],
+            ..Default::default()
+        };
+        let pools = [
+            pool("root", "", &["10.0.0.0/8"], vec![]),
+            pool(
+                "spokes",
+                "root",
+                &["10.1.0.0/16", "fd00::/48"],
+                vec![
+                    alloc("spoke-a", &["10.1.0.0/24"]),
+                    alloc("spoke-b", &["10.1.9.0/24"]),
+                    alloc("deleted", &["10.1.3.0/24"]),
+                    alloc("elsewhere", &["192.168.0.0/24"]),
+                ],
+            ),
+        ];
+        let result = reconcile_ipam_pools(&pools, &subnets);
+
+        let drift: Vec<(IpamDriftKind, &str, String)> = result
+            .drift
+            .iter()
+            .map(|d| {
+                (
+                    d.kind,
+                    d.vnet_name.as_str(),
+                    d.prefix.map(|p| p.to_string()).unwrap_or_default(),
+                )
+            })
+            .collect();
+        // The parent pool only hands space to child pools: no unrecorded VNets there.
+        assert_eq!(
+            drift,
+            [
+                (
+                    IpamDriftKind::OutsidePool,
+                    "elsewhere",
+                    "192.168.0.0/24".to_string()
+                ),
+                (IpamDriftKind::MissingVnet, "deleted", String::new()),
+                (
+                    IpamDriftKind::NotOnVnet,
+                    "spoke-b",
+                    "10.1.9.0/24".to_string()
+                ),
+                (
+                    IpamDriftKind::Unrecorded,
+                    "rogue",
+                    "10.1.2.0/24".to_string()
+                ),
+                (
+                    IpamDriftKind::Unrecorded,
+                    "spoke-b",
+                    "10.1.1.0/24".to_string()
+                ),
+            ]
+        );
+        let spokes = &result.usage[1];
+        assert_eq!(
+            (spokes.size, spokes.allocated, spokes.in_use),
+            (65536, 1024, 768)
+        );
+        assert_eq!(
+            format_ipam_reconciliation(&result).lines().nth(1),
+            Some("IPAM POOL: 'avnm/spokes' 10.1.0.0/16 - 1,024 of 65,536 addresses allocated, 768 in use by VNets")
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! IP address and resource ID lookup of subnets.
+//!
+//! [`SubnetIndex`] keeps the subnets sorted by start address so an IP is
+//! resolved with a binary search instead of a scan, which matters when a
+//! whole inventory file is looked up at once. Where subnets overlap (e.g.
+//! the same prefix in two unpeered VNets) the most specific subnet that
+//! starts closest to the address wins.
+
+use crate::azure::Data;
+use crate::models::Subnet;
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+
+/// Subnets indexed by address range and resource ID.
+pub struct SubnetIndex<'a> {
+    /// `(first, last, subnet)`, sorted by `first`, then shortest prefix first.
+    ranges: Vec<(u32, u32, &'a Subnet)>,
+    by_id: HashMap<String, &'a Subnet>,
+}
+
+impl<'a> SubnetIndex<'a> {
+    /// Index every subnet in `data` that has a prefix.
+    pub fn new(data: &'a Data) -> SubnetIndex<'a> {
+        let mut ranges: Vec<(u32, u32, &Subnet)> = data
+            .data
+            .iter()
+            .filter_map(|s| {
+                let cidr = s.subnet_cidr?;
+                Some((u32::from(cidr.lo()), u32::from(cidr.hi()), s))
+            })
+            .collect();
+        ranges.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)));
+        let by_id = data
+            .data
+            .iter()
+            .filter(|s| !s.subnet_id.is_empty())
+            .map(|s| (s.subnet_id.to_lowercase(), s))
+            .collect();
+        SubnetIndex { ranges, by_id }
+    }
+
+    /// Subnet containing `ip`.
+    pub fn find_ip(&self, ip: Ipv4Addr) -> Option<&'a Subnet> {
+        let ip = u32::from(ip);
+        let end = self.ranges.partition_point(|(first, _, _)| *first <= ip);
+        // Aligned prefixes nest, so the last range starting at or before `ip`
+        // that still covers it is the most specific match.
+        self.ranges[..end]
+            .iter()
+            .rev()
+            .find(|(_, last, _)| *last >= ip)
+            .map(|(_, _, s)| *s)
+    }
+
+    /// Subnet with ARM resource ID `subnet_id` (case-insensitive).
+    pub fn find_id(&self, subnet_id: &str) -> Option<&'a Subnet> {
+        self.by_id.get(&subnet_id.to_lowercase()).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ipv4;
+
+    fn subnet(name: &str, cidr: &str) -> Subnet {
+        Subnet {
+            subnet_name: name.to_string(),
+            subnet_id: format!("/subscriptions/s/virtualNetworks/v/subnets/{name}"),
+            subnet_cidr: Some(Ipv4::new(cidr).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ips_resolve_to_the_most_specific_subnet() {
+        let data = Data {
+            data: vec![
+                subnet("app", "10.0.1.0/24"),
+                subnet("first", "10.0.0.0/24"),
+                subnet("wide", "10.0.0.0/16"),
+                subnet("db", "10.0.2.0/24"),
+            ],
+            ..Default::default()
+        };
+        let index = SubnetIndex::new(&data);
+        let name = |ip: &str| {
+            index
+                .find_ip(ip.parse().unwrap())
+                .map(|s| s.subnet_name.as_str())
+        };
+        assert_eq!(name("10.0.0.9"), Some("first"));
+        assert_eq!(name("10.0.1.200"), Some("app"));
+        assert_eq!(name("10.0.2.0"), Some("db"));
+        assert_eq!(name("10.0.3.4"), Some("wide"));
+        assert_eq!(name("10.1.0.1"), None);
+        assert_eq!(
+            index
+                .find_id("/SUBSCRIPTIONS/s/virtualNetworks/v/subnets/db")
+                .map(|s| s.subnet_name.as_str()),
+            Some("db")
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Merging address ranges from other sources into the Azure subnet data.
+//!
+//! Reserved and on-premises ranges become stand-in VNets (one subnet covering
+//! the range, subscription named after the source), so overlap detection, gap
+//! finding and the CSV treat them like any other address space. Planned
+//! subnets from a Terraform plan are added to their live or planned VNet.
+//! Every record carries its [`SubnetSource`].
+
+use super::simulate::add_subnets;
+use crate::azure::Data;
+use crate::models::{Ipv4, Subnet, SubnetSource};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// A non-Azure address range (`[[range]]` in the config file, `--range`).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExtraRange {
+    /// `reserved`, `on-prem` or `planned`.
+    pub source: SubnetSource,
+    /// Label shown as VNet and subnet name.
+    pub name: String,
+    pub cidr: Ipv4,
+    /// Region or site shown in the location column.
+    #[serde(default)]
+    pub location: Option<String>,
+}
+
+impl ExtraRange {
+    /// Stand-in VNet with one subnet covering the range.
+    fn to_subnet(&self) -> Subnet {
+        Subnet {
+            vnet_name: self.name.clone(),
+            vnet_cidr: self.cidr,
+            vnet_address_space: vec![self.cidr],
+            subnet_name: self.name.clone(),
+            subnet_cidr: Some(self.cidr),
+            location: self.location.clone().unwrap_or_default(),
+            subscription_id: self.source.to_string(),
+            subscription_name: self.source.to_string(),
+            source: self.source,
+            ..Default::default()
+        }
+    }
+}
+
+/// Parse `--range` values like `source=reserved,cidr=10.50.0.0/16,name=future-dc`.
+///
+/// Keys are `source`, `cidr`, `name` and `location`; all but `location` are
+/// required.
+pub fn parse_extra_range(s: &str) -> Result<ExtraRange, String> {
+    let (mut source, mut cidr, mut name, mut location) = (None, None, None, None);
+    for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{pair}'"))?;
+        let value = value.trim();
+        match key.trim() {
+            "source" => {
+                source = Some(match value {
+                    "reserved" => SubnetSource::Reserved,
+                    "on-prem" => SubnetSource::OnPrem,
+                    "planned" => SubnetSource::Planned,
+                    other => {
+                        return Err(format!(
+                            "unknown source '{other}' (expected reserved, on-prem or planned)"
+                        ))
+                    }
+                })
+            }
+            "cidr" => cidr = Some(Ipv4::new(value).map_err(|e| e.to_string())?),
+            "name" => name = Some(value.to_string()),
+            "location" => location = Some(value.to_string()),
+            other => {
+                return Err(format!(
+                    "unknown key '{other}' (expected source, cidr, name or location)"
+                ))
+            }
+        }
+    }
+    Ok(ExtraRange {
+        source: source.ok_or("source= is required")?,
+        name: name.ok_or("name= is required")?,
+        cidr: cidr.ok_or("cidr= is required")?,
+        location,
+    })
+}
+
+/// A VNet created by a Terraform plan.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlannedVnet {
+    pub name: String,
+    pub address_space: Vec<Ipv4>,
+    pub resource_group: String,
+    pub location: String,
+}
+
+/// A subnet created by a Terraform plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedSubnet {
+    pub vnet_name: String,
+    pub name: String,
+    pub cidr: Ipv4,
+}
+
+/// VNets and subnets a Terraform plan creates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerraformPlan {
+    pub vnets: Vec<PlannedVnet>,
+    pub subnets: Vec<PlannedSubnet>,
+}
+
+#[derive(Deserialize)]
+struct PlanJson {
+    #[serde(default)]
+    resource_changes: Vec<ResourceChange>,
+}
+
+#[derive(Deserialize)]
+struct ResourceChange {
+    address: String,
+    #[serde(rename = "type")]
+    kind: String,
+    change: Change,
+}
+
+#[derive(Deserialize)]
+struct Change {
+    actions: Vec<String>,
+    after: Option<serde_json::Value>,
+}
+
+/// Read the `azurerm_virtual_network` and `azurerm_subnet` resources created
+/// by a plan in `terraform show -json <planfile>` format.
+///
+/// Resources whose name or prefixes are only known after apply are skipped
+/// with a warning.
+pub fn parse_terraform_plan(json: &str) -> Result<TerraformPlan, Box<dyn Error>> {
+    let plan: PlanJson =
+        serde_json::from_str(json).map_err(|e| format!("Error parsing Terraform plan: {e}"))?;
+    let mut out = TerraformPlan::default();
+    for rc in plan.resource_changes {
+        if !rc.change.actions.iter().any(|a| a == "create") {
+            continue;
+        }
+        let Some(after) = rc.change.after else {
+            continue;
+        };
+        let text = |key: &str| after.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let prefixes = |key: &str| -> Option<Vec<Ipv4>> {
+            after
+                .get(key)?
+                .as_array()?
+                .iter()
+                .map(|p| Ipv4::new(p.as_str()?).ok())
+                .collect()
+        };
+        match rc.kind.as_str() {
+            "azurerm_virtual_network" => match (text("name"), prefixes("address_space")) {
+                (Some(name), Some(address_space)) => out.vnets.push(PlannedVnet {
+                    name,
+                    address_space,
+                    resource_group: text("resource_group_name").unwrap_or_default(),
+                    location: text("location").unwrap_or_default(),
+                }),
+                _ => log::warn!("Skipping '{}': name or address space unknown", rc.address),
+            },
+            "azurerm_subnet" => match (
+                text("name"),
+                text("virtual_network_name"),
+                prefixes("address_prefixes"),
+            ) {
+                (Some(name), Some(vnet_name), Some(cidrs)) => {
+                    out.subnets
+                        .extend(cidrs.into_iter().map(|cidr| PlannedSubnet {
+                            vnet_name: vnet_name.clone(),
+                            name: name.clone(),
+                            cidr,
+                        }))
+                }
+                _ => log::warn!("Skipping '{}': name, VNet or prefixes unknown", rc.address),
+            },
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+/// `data` with `ranges` and the subnets created by `plan` added.
+///
+/// Planned subnets that already exist in `data` (same VNet and prefix) are
+/// skipped; ones that are misaligned, outside their VNet or overlap a
+/// subnet are left out with a warning.
+pub fn merge_sources(
+    data: Data,
+    ranges: &[ExtraRange],
+    plan: &TerraformPlan,
+) -> Result<Data, Box<dyn Error>> {
+    let mut extra: Vec<Subnet> = Vec::new();
+    for r in ranges {
+        if r.source == SubnetSource::Azure {
+            return Err(format!(
+                "Range '{}': source must be reserved, on-prem or planned",
+                r.name
+            )
+            .into());
+        }
+        extra.push(r.to_subnet());
+    }
+
+    // Planned subnets grouped by VNet; subnets of planned VNets are built here,
+    // the others are checked against their live VNet by `add_subnets`.
+    let mut by_vnet: BTreeMap<String, Vec<&PlannedSubnet>> = BTreeMap::new();
+    for p in &plan.subnets {
+        let deployed = data.data.iter().any(|s| {
+            s.vnet_name.eq_ignore_ascii_case(&p.vnet_name) && s.subnet_cidr == Some(p.cidr)
+        });
+        if deployed {
+            log::info!(
+                "Planned subnet '{}/{}' is already deployed",
+                p.vnet_name,
+                p.name
+            );
+        } else {
+            by_vnet
+                .entry(p.vnet_name.to_lowercase())
+                .or_default()
+                .push(p);
+        }
+    }
+    let mut data = data;
+    for planned in by_vnet.values() {
+        let vnet_name = &planned[0].vnet_name;
+        if let Some(vnet) = plan
+            .vnets
+            .iter()
+            .find(|v| v.name.eq_ignore_ascii_case(vnet_name))
+        {
+            for p in planned {
+                match vnet.address_space.iter().find(|a| a.contains(p.cidr.lo())) {
+                    Some(vnet_cidr) => extra.push(Subnet {
+                        resource_group: vnet.resource_group.clone(),
+                        vnet_name: vnet.name.clone(),
+                        vnet_cidr: *vnet_cidr,
+                        vnet_address_space: vnet.address_space.clone(),
+                        subnet_name: p.name.clone(),
+                        subnet_cidr: Some(p.cidr),
+                        location: vnet.location.clone(),
+                        subscription_id: SubnetSource::Planned.to_string(),
+                        subscription_name: SubnetSource::Planned.to_string(),
+                        ip_configurations_count: Some(0),
+                        source: SubnetSource::Planned,
+                        ..Default::default()
+                    }),
+                    None => log::warn!(
+                        "Planned subnet '{vnet_name}/{}' {} is outside the VNet address space",
+                        p.name,
+                        p.cidr
+                    ),
+                }
+            }
+            continue;
+        }
+        let proposals: Vec<(String, Ipv4)> =
+            planned.iter().map(|p| (p.name.clone(), p.cidr)).collect();
+        match add_subnets(&data, vnet_name, None, &proposals, &[]) {
+            Ok((merged, conflicts)) => {
+                for c in conflicts {
+                    log::warn!(
+                        "Planned subnet '{vnet_name}/{}' {} left out: {}",
+                        c.name,
+                        c.cidr,
+                        c.reason
+                    );
+                }
+                data = merged;
+            }
+            Err(e) => log::warn!("Planned subnets of VNet '{vnet_name}' left out: {e}"),
+        }
+    }
+
+    log::info!("Merged {} record(s) from other sources", extra.len());
+    data.data.extend(extra);
+    data.count = data.data.len() as i32;
+    data.total_records = Some(data.data.len() as u32);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_and_terraform_subnets_are_merged_with_their_source() {
+        let mut live = Subnet {
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            ..Subnet::test("hub", "app", "10.0.0.0/24")
+        };
+        live.vnet_address_space = vec![live.vnet_cidr];
+        let data = Data {
+            data: vec![live],
+            ..Default::default()
+        };
+        let plan = parse_terraform_plan(
+            r#"{"resource_changes": [
+                {"address": "azurerm_subnet.app", "type": "azurerm_subnet",
+                 "change": {"actions": ["no-op"], "after": {"name": "app",
+                   "virtual_network_name": "hub", "address_prefixes": ["10.0.0.0/24"]}}},
+                {"address": "azurerm_subnet.db", "type": "azurerm_subnet",
+                 "change": {"actions": ["create"], "after": {"name": "db",
+                   "virtual_network_name": "hub", "address_prefixes": ["10.0.1.0/24"]}}},
+                {"address": "azurerm_virtual_network.edge", "type": "azurerm_virtual_network",
+                 "change": {"actions": ["create"], "after": {"name": "edge",
+                   "address_space": ["10.9.0.0/24"], "location": "westeurope"}}},
+                {"address": "azurerm_subnet.dmz", "type": "azurerm_subnet",
+                 "change": {"actions": ["create"], "after": {"name": "dmz",
+                   "virtual_network_name": "edge", "address_prefixes": ["10.9.0.0/26"]}}}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(plan.subnets.len(), 2);
+        let ranges = [parse_extra_range("source=on-prem,cidr=192.168.0.0/16,name=dc-1").unwrap()];
+        let merged = merge_sources(data, &ranges, &plan).unwrap();
+        let records: Vec<(&str, &str, SubnetSource)> = merged
+            .data
+            .iter()
+            .map(|s| (s.vnet_name.as_str(), s.subnet_name.as_str(), s.source))
+            .collect();
+        assert_eq!(
+            records,
+            [
+                ("hub", "app", SubnetSource::Azure),
+                ("hub", "db", SubnetSource::Planned),
+                ("dc-1", "dc-1", SubnetSource::OnPrem),
+                ("edge", "dmz", SubnetSource::Planned),
+            ]
+        );
+        assert_eq!(merged.count, 4);
+        assert!(parse_extra_range("source=azure,cidr=10.0.0.0/8,name=x").is_err());
+    }
+}
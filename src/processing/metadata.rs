@@ -0,0 +1,189 @@
+//! Keyed subscription and VNet metadata, built once per pass.
+//!
+//! Summaries need "the name of subscription X" or "the VNet with ID Y" for
+//! every row. Scanning the subnet list for each one and cloning the strings
+//! made those passes quadratic; [`MetadataIndex`] is built in one sweep,
+//! borrows every name from the loaded data instead of copying it, and
+//! answers by key.
+
+use crate::azure::{Data, SubscriptionData};
+use crate::models::{Ipv4, Subnet};
+use std::collections::HashMap;
+
+/// What is known about one subscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionMeta<'a> {
+    pub name: &'a str,
+    /// Management group path, root first (`None` without metadata or when
+    /// the subscription sits under no group).
+    pub management_group_path: Option<String>,
+}
+
+/// What is known about one VNet, from its first subnet record.
+#[derive(Debug, Clone)]
+pub struct VnetMeta<'a> {
+    pub name: &'a str,
+    pub subscription_id: &'a str,
+    pub subscription_name: &'a str,
+    /// Address prefix holding the first subnet.
+    pub vnet_cidr: Ipv4,
+    /// The VNet's `GatewaySubnet`, if it has one.
+    pub gateway_subnet: Option<&'a Subnet>,
+}
+
+/// Subscription and VNet metadata keyed for lookups.
+#[derive(Debug, Default)]
+pub struct MetadataIndex<'a> {
+    subscriptions: HashMap<&'a str, SubscriptionMeta<'a>>,
+    vnets: Vec<VnetMeta<'a>>,
+    /// Lower-case VNet resource ID → index into `vnets`.
+    by_id: HashMap<String, usize>,
+    /// VNet name → index of the first VNet with that name.
+    by_name: HashMap<&'a str, usize>,
+}
+
+impl<'a> MetadataIndex<'a> {
+    /// Index the subscriptions and VNets of `data`.
+    pub fn new(data: &'a Data) -> MetadataIndex<'a> {
+        let mut index = MetadataIndex::default();
+        for s in &data.data {
+            index
+                .subscriptions
+                .entry(&s.subscription_id)
+                .or_insert_with(|| SubscriptionMeta {
+                    name: &s.subscription_name,
+                    management_group_path: None,
+                });
+            // Old caches have no VNet IDs; their VNets are keyed by
+            // subscription and name, which real IDs never look like.
+            let key = if s.vnet_id.is_empty() {
+                format!("{}|{}", s.subscription_id, s.vnet_name).to_lowercase()
+            } else {
+                s.vnet_id.to_lowercase()
+            };
+            let next = index.vnets.len();
+            let i = *index.by_id.entry(key).or_insert(next);
+            if i == next {
+                index.by_name.entry(&s.vnet_name).or_insert(i);
+                index.vnets.push(VnetMeta {
+                    name: &s.vnet_name,
+                    subscription_id: &s.subscription_id,
+                    subscription_name: &s.subscription_name,
+                    vnet_cidr: s.vnet_cidr,
+                    gateway_subnet: None,
+                });
+            }
+            if s.subnet_name.eq_ignore_ascii_case("GatewaySubnet") {
+                index.vnets[i].gateway_subnet.get_or_insert(s);
+            }
+        }
+        index
+    }
+
+    /// Add the management group paths from the subscription metadata query.
+    pub fn with_subscriptions(mut self, subscriptions: &'a SubscriptionData) -> Self {
+        for sub in &subscriptions.data {
+            let path = Some(sub.management_group_path()).filter(|p| !p.is_empty());
+            self.subscriptions
+                .entry(&sub.subscription_id)
+                .or_insert_with(|| SubscriptionMeta {
+                    name: &sub.subscription_name,
+                    management_group_path: None,
+                })
+                .management_group_path = path;
+        }
+        self
+    }
+
+    pub fn subscription(&self, subscription_id: &str) -> Option<&SubscriptionMeta<'a>> {
+        self.subscriptions.get(subscription_id)
+    }
+
+    /// Display name of a subscription seen in the data or the metadata.
+    pub fn subscription_name(&self, subscription_id: &str) -> Option<&'a str> {
+        self.subscription(subscription_id).map(|s| s.name)
+    }
+
+    /// VNet by ARM resource ID (case-insensitive).
+    pub fn vnet(&self, vnet_id: &str) -> Option<&VnetMeta<'a>> {
+        self.by_id
+            .get(&vnet_id.to_lowercase())
+            .map(|&i| &self.vnets[i])
+    }
+
+    /// First VNet named `name` (names are only unique per resource group).
+    pub fn vnet_named(&self, name: &str) -> Option<&VnetMeta<'a>> {
+        self.by_name.get(name).map(|&i| &self.vnets[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ManagementGroupRef, Subscription};
+
+    fn subnet(sub: &str, vnet: &str, name: &str, cidr: &str) -> Subnet {
+        Subnet {
+            subscription_id: sub.to_string(),
+            subscription_name: format!("{sub}-name"),
+            vnet_id: format!("/subscriptions/{sub}/virtualNetworks/{vnet}"),
+            vnet_cidr: Ipv4::new("10.0.0.0/16").unwrap(),
+            ..Subnet::test(vnet, name, cidr)
+        }
+    }
+
+    #[test]
+    fn lookups_by_subscription_vnet_id_and_name() {
+        let mut old_cache = subnet("lab", "lab-vnet", "app", "10.9.0.0/24");
+        old_cache.vnet_id.clear();
+        let data = Data {
+            data: vec![
+                subnet("prod", "hub", "app", "10.0.0.0/24"),
+                subnet("prod", "hub", "GatewaySubnet", "10.0.255.0/27"),
+                subnet("dev", "hub", "app", "10.1.0.0/24"),
+                old_cache,
+            ],
+            ..Default::default()
+        };
+        let subscriptions = SubscriptionData {
+            data: vec![Subscription {
+                subscription_id: "prod".to_string(),
+                subscription_name: "Prod".to_string(),
+                management_group_chain: vec![ManagementGroupRef {
+                    name: "corp".to_string(),
+                    display_name: String::new(),
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let index = MetadataIndex::new(&data).with_subscriptions(&subscriptions);
+
+        assert_eq!(index.subscription_name("prod"), Some("prod-name"));
+        let prod = index.subscription("prod").unwrap();
+        assert_eq!(prod.management_group_path.as_deref(), Some("corp"));
+        assert_eq!(
+            index.subscription("dev").unwrap().management_group_path,
+            None
+        );
+        assert_eq!(index.subscription_name("none"), None);
+
+        let hub = index
+            .vnet("/SUBSCRIPTIONS/prod/virtualNetworks/HUB")
+            .unwrap();
+        assert_eq!(hub.subscription_id, "prod");
+        let gateway = hub.gateway_subnet.unwrap();
+        assert_eq!(gateway.subnet_cidr, Ipv4::new("10.0.255.0/27").ok());
+        let dev_hub = index
+            .vnet("/subscriptions/dev/virtualNetworks/hub")
+            .unwrap();
+        assert!(dev_hub.gateway_subnet.is_none());
+
+        assert_eq!(index.vnet_named("hub").unwrap().subscription_id, "prod");
+        assert_eq!(
+            index.vnet_named("lab-vnet").unwrap().subscription_name,
+            "lab-name"
+        );
+        assert!(index.vnet_named("nope").is_none());
+    }
+}
@@ -1,24 +1,164 @@
 //! Subnet data processing logic.
 //!
 //! This module contains business logic for processing subnet data:
+//! - [`address_plan`] - Address plan (`[[plan]]`) conformance of VNets per region
+//! - [`age`] - First / last seen dates of subnets from snapshot history
+//! - [`aks`] - AKS subnet IP pressure estimation
+//! - [`anonymize`] - Anonymized copies of subnet data for fixtures
+//! - [`allocator`] - Free CIDR suggestions and split planning
+//! - [`baseline`] - Drift against an approved, curated address plan file (`drift`)
+//! - [`buffer`] - Intentional gaps (growth buffers) shown apart from free space
+//! - [`canonical`] - Non-canonical CIDRs (host bits set) normalized and reported
 //! - [`dedup`] - De-duplication of subnet records
+//! - [`diff`] - Subnet changes between snapshots (resource ID, then VNet + prefix identity)
+//! - [`deleted`] - Recently deleted subnets from snapshot history (incident reviews)
+//! - [`delegated`] - App Service / Container Apps integration subnet usage
+//! - [`dns`] - VNets grouped by custom DNS server, dead resolver detection
+//! - [`empty`] - Long-empty subnet detection using snapshot history
+//! - [`flow_log`] - Flow log / Traffic Analytics coverage of subnets
+//! - [`filter`] - Scoped ignore list (subnet name, subscription, VNet, CIDR)
 //! - [`gap_finder`] - Finding gaps between subnets
+//! - [`gateway`] - VPN / ExpressRoute gateway address space and P2S pool overlaps
+//! - [`heatmap`] - Address space ownership per slice of a supernet (`--heatmap`)
+//! - [`hosts`] - Usable host counts of output rows under a reservation model
+//! - [`ipam`] - Reconciliation of AVNM IPAM pool allocations with actual VNets
+//! - [`waste`] - Wasted address space in oversized subnets (peak usage from history)
+//! - [`summarize`] - Route summarization of VNet prefixes for on-prem advertisement
 //! - [`vnet`] - VNet aggregation and operations
+//! - [`policy`] - VNet policies from the config file (DDoS protection, encryption)
 //! - [`overlap`] - Detection and filtering of overlapping VNet CIDRs
+//! - [`group`] - Grouping and filtering by subscription / management group
+//! - [`lookup`] - IP address / resource ID to subnet index
+//! - [`merge`] - Reserved, on-prem and Terraform-planned ranges merged into the subnet data
+//! - [`nsg`] - Subnets grouped by NSG (inverse index, shared NSG audit)
+//! - [`names`] - Duplicate subnet / VNet name detection
+//! - [`role`] - Well-known subnet role classification
+//! - [`quota`] - Azure networking limit checks (VNets, subnets, peerings)
+//! - [`row_filter`] - Size thresholds over generated output rows
+//! - [`simulate`] - What-if simulation of address space usage
 
+mod address_plan;
+mod age;
+mod aks;
+mod allocator;
+mod anonymize;
+mod baseline;
+mod buffer;
+mod canonical;
 mod dedup;
+mod delegated;
+mod deleted;
+mod diff;
+mod dns;
+mod empty;
+mod filter;
+mod flow_log;
 pub(crate) mod gap_finder;
+mod gateway;
+mod group;
+mod heatmap;
+mod hosts;
+mod ipam;
+mod lookup;
+mod merge;
+mod metadata;
+mod names;
+mod nsg;
 mod overlap;
+mod policy;
+mod quota;
+pub(crate) mod role;
+mod row_filter;
+mod simulate;
+mod summarize;
 mod vnet;
+mod waste;
 
 // Re-export public functions
-pub use dedup::de_duplicate_subnets;
+pub use address_plan::{
+    format_conformance, plan_conformance, validate_plan, PlanBlock, PlanIssue, VnetConformance,
+};
+pub use age::{subnet_ages, SubnetAge, SubnetAgeAnnotator};
+pub use aks::{aks_subnet_pressure, format_aks_pressure, AksSubnetPressure};
+pub use allocator::{
+    cover_range, find_free_block, format_split_plan, parse_size_mix, split_equal, split_sizes,
+    suggest_subnet, SizeRequest, SplitPlan, SubnetSuggestion,
+};
+pub use anonymize::anonymize;
+pub use baseline::{
+    baseline_drift, format_baseline_drift, Baseline, BaselineDrift, BaselineDriftKind,
+    BaselineSubnet, BaselineVnet,
+};
+pub use buffer::{is_buffer, mark_buffers, parse_buffer, BUFFER, VNET_BUFFER};
+pub use canonical::{canonical, log_non_canonical, normalize_cidrs, NonCanonicalCidr};
+pub use dedup::{de_duplicate_subnets, de_duplicate_subnets_ignoring};
+pub use delegated::{
+    delegated_subnet_usage, format_delegated_usage, DelegatedSubnetUsage, IntegrationKind,
+};
+pub use deleted::{find_deleted_subnets, format_deleted_subnets, DeletedSubnet};
+pub use diff::{
+    annotate_changes, diff_subnets, format_subnet_changes, ChangeAnnotation, ChangeKind,
+    SubnetChange, SubnetIdentity,
+};
+pub use dns::{
+    dns_server_usage, format_dns_server_usage, DnsServerUsage, ResolverLocation, AZURE_DNS_IP,
+};
+pub use empty::{find_empty_subnets, format_empty_subnets, reclaimable_by_vnet, EmptySubnet};
+pub use filter::{parse_ignore_rule, IgnoreList, IgnoreRule};
+pub use flow_log::{
+    annotate_flow_logs, flow_log_gaps, format_flow_log_gaps, FlowLogAnnotator, FlowLogCoverage,
+    FlowLogGap, FlowLogIndex,
+};
 pub use gap_finder::{
-    fill_trailing_vgap, gaps, process_subnet_row, GapEvent, GapFinder, GapKind, PrevVnetContext,
-    SubnetPrintRow, VnetCidr,
+    fill_trailing_vgap, gaps, group_vnet_cidrs, process_subnet_row, GapEvent, GapFinder, GapKind,
+    PrevVnetContext, SubnetPrintRow, VnetCidr,
+};
+pub use gateway::{
+    format_gateways, gateway_summary, p2s_overlaps, p2s_subnets, GatewaySummary, P2sOverlap,
 };
+pub use group::{
+    filter_by_management_group, format_group_summary, group_key, summarize_groups, GroupBy,
+    GroupSummary,
+};
+pub use heatmap::{
+    heatmap, HeatmapSlice, SliceOwner, DEFAULT_HEATMAP_SLICE, DEFAULT_HEATMAP_SUPERNET,
+};
+pub use hosts::count_hosts;
+pub use ipam::{
+    format_ipam_reconciliation, reconcile_ipam_pools, IpamDrift, IpamDriftKind, IpamPoolUsage,
+    IpamReconciliation,
+};
+pub use lookup::SubnetIndex;
+pub use merge::{
+    merge_sources, parse_extra_range, parse_terraform_plan, ExtraRange, PlannedSubnet, PlannedVnet,
+    TerraformPlan,
+};
+pub use metadata::{MetadataIndex, SubscriptionMeta, VnetMeta};
+pub use names::{
+    find_duplicate_names, find_duplicate_subnet_names, find_duplicate_vnet_names,
+    log_duplicate_names, DuplicateName, DuplicateNameKind, NameOccurrence,
+};
+pub use nsg::{format_nsg_usage, NsgIndex, NsgUsage};
 pub use overlap::{
-    find_overlapping_vnets, log_overlapping_vnets, resolve_overlapping_vnets,
-    ConflictResolutionOutput, ExcludedSubnet, OverlapConflict, VnetInfo,
+    find_address_space_overlaps, find_overlapping_subnets, find_overlapping_vnets,
+    log_address_space_overlaps, log_overlapping_subnets, log_overlapping_vnets,
+    resolve_overlapping_vnets, ConflictResolutionOutput, ExcludedSubnet, OverlapConflict,
+    SubnetOverlap, VnetInfo,
+};
+pub use policy::{vnet_policy_violations, PolicyRequirement, PolicyViolation, VnetPolicy};
+pub use quota::{format_quota_usage, quota_usage, quota_warnings, QuotaKind, QuotaUsage};
+pub use role::{subnet_role, SubnetRole};
+pub use row_filter::{parse_prefix_len, RowFilter};
+pub use simulate::{
+    add_subnets, compare_usage, format_usage_changes, remove_subnets, space_usage, AddConflict,
+    FreeRange, SpaceUsage, UsageChange,
+};
+pub use summarize::{summarize_prefixes, RouteSummary};
+pub use vnet::{
+    format_vnets, format_vnets_page, get_vnets, prefix_usage, print_vnets, vnet_summary,
+    PrefixUsage, VnetPage,
+};
+pub use waste::{
+    find_oversized_subnets, format_oversized_subnets, waste_by_subscription, waste_by_vnet,
+    OversizedSubnet,
 };
-pub use vnet::{get_vnets, print_vnets};
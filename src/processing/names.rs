@@ -0,0 +1,235 @@
+//! Duplicate name detection.
+//!
+//! Beyond CIDR duplicates, flags names that confuse automation: subnets whose
+//! names collide (case-insensitively) within one VNet, and VNet names reused
+//! across subscriptions.
+
+use crate::azure::Data;
+use crate::models::{Ipv4, Subnet};
+use std::collections::BTreeMap;
+
+/// What kind of name collision was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateNameKind {
+    /// Two or more subnets in the same VNet share a name.
+    SubnetInVnet,
+    /// The same VNet name is used in more than one subscription.
+    VnetAcrossSubscriptions,
+}
+
+/// One place a duplicated name occurs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameOccurrence {
+    pub vnet_name: String,
+    pub subnet_name: String,
+    pub subnet_cidr: Option<Ipv4>,
+    pub subscription_id: String,
+    pub subscription_name: String,
+}
+
+impl From<&Subnet> for NameOccurrence {
+    fn from(s: &Subnet) -> Self {
+        NameOccurrence {
+            vnet_name: s.vnet_name.clone(),
+            subnet_name: s.subnet_name.clone(),
+            subnet_cidr: s.subnet_cidr,
+            subscription_id: s.subscription_id.clone(),
+            subscription_name: s.subscription_name.clone(),
+        }
+    }
+}
+
+/// A duplicated name with every occurrence of it.
+#[derive(Debug, Clone)]
+pub struct DuplicateName {
+    pub kind: DuplicateNameKind,
+    /// The duplicated name as first seen.
+    pub name: String,
+    pub occurrences: Vec<NameOccurrence>,
+}
+
+/// Find subnets sharing a name (case-insensitive) within the same VNet.
+///
+/// Rows with the same name and CIDR are the same subnet returned twice by
+/// Resource Graph and are not reported.
+pub fn find_duplicate_subnet_names(data: &Data) -> Vec<DuplicateName> {
+    let mut groups: BTreeMap<(String, String, String), Vec<NameOccurrence>> = BTreeMap::new();
+    for s in &data.data {
+        let key = (
+            s.subscription_id.clone(),
+            s.vnet_name.to_lowercase(),
+            s.subnet_name.to_lowercase(),
+        );
+        let occurrences = groups.entry(key).or_default();
+        let occurrence = NameOccurrence::from(s);
+        if !occurrences.iter().any(|o| {
+            o.subnet_name == occurrence.subnet_name && o.subnet_cidr == occurrence.subnet_cidr
+        }) {
+            occurrences.push(occurrence);
+        }
+    }
+
+    groups
+        .into_values()
+        .filter(|occ| occ.len() > 1)
+        .map(|occurrences| DuplicateName {
+            kind: DuplicateNameKind::SubnetInVnet,
+            name: occurrences[0].subnet_name.clone(),
+            occurrences,
+        })
+        .collect()
+}
+
+/// Find VNet names (case-insensitive) used in more than one subscription.
+///
+/// Each occurrence lists one VNet; `subnet_name`/`subnet_cidr` are taken from
+/// its first subnet row.
+pub fn find_duplicate_vnet_names(data: &Data) -> Vec<DuplicateName> {
+    let mut groups: BTreeMap<String, Vec<NameOccurrence>> = BTreeMap::new();
+    for s in &data.data {
+        let occurrences = groups.entry(s.vnet_name.to_lowercase()).or_default();
+        if !occurrences
+            .iter()
+            .any(|o| o.subscription_id == s.subscription_id && o.vnet_name == s.vnet_name)
+        {
+            occurrences.push(NameOccurrence::from(s));
+        }
+    }
+
+    groups
+        .into_values()
+        .filter(|occ| {
+            occ.iter()
+                .any(|o| o.subscription_id != occ[0].subscription_id)
+        })
+        .map(|occurrences| DuplicateName {
+            kind: DuplicateNameKind::VnetAcrossSubscriptions,
+            name: occurrences[0].vnet_name.clone(),
+            occurrences,
+        })
+        .collect()
+}
+
+/// Run both duplicate name checks.
+pub fn find_duplicate_names(data: &Data) -> Vec<DuplicateName> {
+    let mut dups = find_duplicate_subnet_names(data);
+    dups.extend(find_duplicate_vnet_names(data));
+    dups
+}
+
+/// Log duplicate names as warnings, listing every occurrence.
+pub fn log_duplicate_names(dups: &[DuplicateName]) {
+    if dups.is_empty() {
+        log::info!("No duplicate subnet or VNet names found.");
+        return;
+    }
+
+    for dup in dups {
+        match dup.kind {
+            DuplicateNameKind::SubnetInVnet => log::warn!(
+                "Duplicate subnet name '{}' in VNet '{}' ({} occurrences):",
+                dup.name,
+                dup.occurrences[0].vnet_name,
+                dup.occurrences.len()
+            ),
+            DuplicateNameKind::VnetAcrossSubscriptions => log::warn!(
+                "VNet name '{}' used in {} subscriptions:",
+                dup.name,
+                dup.occurrences.len()
+            ),
+        }
+        for o in &dup.occurrences {
+            let cidr = o.subnet_cidr.map(|c| c.to_string()).unwrap_or_default();
+            match dup.kind {
+                DuplicateNameKind::SubnetInVnet => log::warn!(
+                    "    - Subnet: '{}' {cidr}, Subscription: '{}' ({})",
+                    o.subnet_name,
+                    o.subscription_name,
+                    o.subscription_id
+                ),
+                DuplicateNameKind::VnetAcrossSubscriptions => log::warn!(
+                    "    - VNet: '{}', Subscription: '{}' ({})",
+                    o.vnet_name,
+                    o.subscription_name,
+                    o.subscription_id
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_subnet(sub_id: &str, vnet_name: &str, subnet_name: &str, cidr: &str) -> Subnet {
+        Subnet {
+            subscription_id: sub_id.to_string(),
+            subscription_name: format!("{sub_id}-name"),
+            ..Subnet::test(vnet_name, subnet_name, cidr)
+        }
+    }
+
+    fn make_data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            count: subnets.len() as i32,
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn subnet_names_colliding_case_insensitively_in_one_vnet_are_reported() {
+        let data = make_data(vec![
+            make_subnet("s1", "hub", "AppSnet", "10.0.0.0/24"),
+            make_subnet("s1", "hub", "appsnet", "10.0.1.0/24"),
+            make_subnet("s1", "hub", "web", "10.0.2.0/24"),
+            // Same name in a different VNet is fine.
+            make_subnet("s1", "spoke", "AppSnet", "10.1.0.0/24"),
+        ]);
+        let dups = find_duplicate_subnet_names(&data);
+        assert_eq!(dups.len(), 1);
+        assert_eq!(dups[0].kind, DuplicateNameKind::SubnetInVnet);
+        assert_eq!(dups[0].name, "AppSnet");
+        assert_eq!(dups[0].occurrences.len(), 2);
+    }
+
+    #[test]
+    fn repeated_graph_rows_for_one_subnet_are_not_duplicates() {
+        let data = make_data(vec![
+            make_subnet("s1", "hub", "app", "10.0.0.0/24"),
+            make_subnet("s1", "hub", "app", "10.0.0.0/24"),
+        ]);
+        assert!(find_duplicate_subnet_names(&data).is_empty());
+    }
+
+    #[test]
+    fn vnet_name_in_multiple_subscriptions_lists_every_subscription() {
+        let data = make_data(vec![
+            make_subnet("s1", "hub-vnet", "a", "10.0.0.0/24"),
+            make_subnet("s1", "hub-vnet", "b", "10.0.1.0/24"),
+            make_subnet("s2", "HUB-VNET", "a", "10.1.0.0/24"),
+            make_subnet("s3", "hub-vnet", "a", "10.2.0.0/24"),
+            make_subnet("s1", "only-here", "a", "10.3.0.0/24"),
+        ]);
+        let dups = find_duplicate_vnet_names(&data);
+        assert_eq!(dups.len(), 1);
+        assert_eq!(dups[0].kind, DuplicateNameKind::VnetAcrossSubscriptions);
+        let subs: Vec<&str> = dups[0]
+            .occurrences
+            .iter()
+            .map(|o| o.subscription_id.as_str())
+            .collect();
+        assert_eq!(subs, vec!["s1", "s2", "s3"]);
+    }
+
+    #[test]
+    fn find_duplicate_names_combines_both_checks() {
+        let data = make_data(vec![
+            make_subnet("s1", "hub", "app", "10.0.0.0/24"),
+            make_subnet("s1", "hub", "APP", "10.0.1.0/24"),
+            make_subnet("s2", "hub", "x", "10.1.0.0/24"),
+        ]);
+        assert_eq!(find_duplicate_names(&data).len(), 2);
+    }
+}
@@ -0,0 +1,152 @@
+//! Subnets grouped by NSG.
+//!
+//! An inverse index from NSG resource ID to the subnets it is attached to.
+//! One NSG protecting subnets in several VNets usually means unrelated
+//! workloads share rules, so a change for one opens ports for the others;
+//! the `subnets-by-nsg` report and the `shared_nsg` finding both read it.
+
+use super::gap_finder::extract_resource_name;
+use crate::azure::Data;
+use crate::models::Subnet;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Subnets per NSG, keyed by lower-cased NSG resource ID.
+pub struct NsgIndex<'a> {
+    nsgs: BTreeMap<String, Vec<&'a Subnet>>,
+}
+
+impl<'a> NsgIndex<'a> {
+    /// Index every subnet in `data` that has an NSG attached.
+    pub fn new(data: &'a Data) -> NsgIndex<'a> {
+        let mut nsgs: BTreeMap<String, Vec<&Subnet>> = BTreeMap::new();
+        for s in &data.data {
+            if let Some(nsg) = s.nsg.as_deref().filter(|id| !id.is_empty()) {
+                nsgs.entry(nsg.to_lowercase()).or_default().push(s);
+            }
+        }
+        NsgIndex { nsgs }
+    }
+
+    /// Subnets the NSG with resource ID `nsg` is attached to.
+    pub fn subnets(&self, nsg: &str) -> &[&'a Subnet] {
+        self.nsgs
+            .get(&nsg.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Number of distinct VNets the NSG with resource ID `nsg` protects.
+    pub fn vnet_count(&self, nsg: &str) -> usize {
+        vnets(self.subnets(nsg)).len()
+    }
+
+    /// Every NSG with its subnets, most widely shared first.
+    pub fn usage(&self) -> Vec<NsgUsage> {
+        let mut usage: Vec<NsgUsage> = self
+            .nsgs
+            .values()
+            .map(|subnets| NsgUsage {
+                nsg_name: extract_resource_name(subnets[0].nsg.as_deref()),
+                nsg_id: subnets[0].nsg.clone().unwrap_or_default(),
+                vnets: vnets(subnets).len(),
+                subnets: subnets
+                    .iter()
+                    .map(|s| format!("{}/{}", s.vnet_name, s.subnet_name))
+                    .collect(),
+            })
+            .collect();
+        usage.sort_by(|a, b| {
+            (b.vnets, b.subnets.len(), &a.nsg_name).cmp(&(a.vnets, a.subnets.len(), &b.nsg_name))
+        });
+        usage
+    }
+}
+
+/// Distinct (subscription, VNet) pairs of `subnets`.
+fn vnets<'s>(subnets: &[&'s Subnet]) -> BTreeSet<(&'s str, &'s str)> {
+    subnets
+        .iter()
+        .map(|s| (s.subscription_id.as_str(), s.vnet_name.as_str()))
+        .collect()
+}
+
+/// One NSG and the subnets it protects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NsgUsage {
+    pub nsg_name: String,
+    pub nsg_id: String,
+    /// Number of distinct VNets among `subnets`.
+    pub vnets: usize,
+    /// Subnets as `vnet/subnet`, in input order.
+    pub subnets: Vec<String>,
+}
+
+/// Render one line per NSG; NSGs spanning several VNets are marked `SHARED`.
+pub fn format_nsg_usage(usage: &[NsgUsage]) -> String {
+    if usage.is_empty() {
+        return "No subnets have an NSG attached.".to_string();
+    }
+    usage
+        .iter()
+        .map(|u| {
+            let label = if u.vnets > 1 { "SHARED" } else { "NSG" };
+            format!(
+                "{label}: '{}' - {} subnet(s) in {} VNet(s): {}",
+                u.nsg_name,
+                u.subnets.len(),
+                u.vnets,
+                u.subnets.join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subnet(vnet: &str, name: &str, nsg: Option<&str>) -> Subnet {
+        Subnet {
+            subscription_id: "sub-1".to_string(),
+            nsg: nsg.map(|n| format!("/subscriptions/sub-1/networkSecurityGroups/{n}")),
+            ..Subnet::test(vnet, name, "")
+        }
+    }
+
+    #[test]
+    fn subnets_are_grouped_by_nsg_and_shared_nsgs_listed_first() {
+        let mut upper = subnet("hub", "mgmt", None);
+        upper.nsg = Some("/SUBSCRIPTIONS/sub-1/networkSecurityGroups/NSG-ALL".to_string());
+        let data = Data {
+            data: vec![
+                subnet("spoke-a", "app", Some("nsg-app")),
+                subnet("spoke-a", "db", Some("nsg-all")),
+                subnet("spoke-b", "web", Some("nsg-all")),
+                subnet("spoke-a", "api", Some("nsg-app")),
+                subnet("spoke-b", "bare", None),
+                upper,
+            ],
+            ..Default::default()
+        };
+        let index = NsgIndex::new(&data);
+        assert_eq!(
+            index
+                .subnets("/subscriptions/sub-1/networkSecurityGroups/nsg-app")
+                .len(),
+            2
+        );
+        assert_eq!(
+            index.vnet_count("/subscriptions/sub-1/networksecuritygroups/nsg-all"),
+            3
+        );
+        assert!(index.subnets("nsg-missing").is_empty());
+        assert_eq!(
+            format_nsg_usage(&index.usage()).lines().collect::<Vec<_>>(),
+            [
+                "SHARED: 'nsg-all' - 3 subnet(s) in 3 VNet(s): spoke-a/db, spoke-b/web, hub/mgmt",
+                "NSG: 'nsg-app' - 2 subnet(s) in 1 VNet(s): spoke-a/app, spoke-a/api",
+            ]
+        );
+    }
+}
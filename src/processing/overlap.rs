@@ -1,11 +1,13 @@
 //! Overlapping VNet CIDR detection and filtering.
 //!
 //! Detects VNets with overlapping address spaces across different subscriptions
-//! and provides filtering options to handle them.
+//! and provides filtering options to handle them. Also checks each VNet's own
+//! address space for duplicate or overlapping prefixes, and (strict mode)
+//! any two subnets whose ranges intersect.
 
-use crate::azure::Data;
-use crate::models::{Ipv4, Subnet};
-use std::collections::HashMap;
+use crate::azure::{Data, PeeringEdge};
+use crate::models::{Ipv4, Subnet, SubnetSource};
+use std::collections::{HashMap, HashSet};
 
 /// An excluded subnet paired with the VNet name that won conflict resolution.
 pub struct ExcludedSubnet {
@@ -25,9 +27,12 @@ pub struct ConflictResolutionOutput {
 /// Resolve overlapping VNets, returning a typed split of active and excluded subnets.
 ///
 /// Winner selection priority within a conflict group:
-/// 1. Production subscription (name contains "prod", case-insensitive)
-/// 2. Most subnets
-/// 3. Alphabetical by subscription name
+/// 1. Live Azure VNets (over reserved, on-prem and planned ranges)
+/// 2. Production subscription (name contains "prod", case-insensitive)
+/// 3. Most subnets
+/// 4. Alphabetical by subscription name
+/// 5. Alphabetical by VNet name, then subscription ID (so ties resolve the
+///    same way on every run)
 ///
 /// Excludes entire VNets by CIDR so only the conflicting address space is removed;
 /// other address spaces of the same VNet remain active.
@@ -45,10 +50,15 @@ pub fn resolve_overlapping_vnets(data: Data) -> ConflictResolutionOutput {
     for conflict in &conflicts {
         let mut sorted_vnets = conflict.vnets.clone();
         sorted_vnets.sort_by(|a, b| {
-            is_production(&b.subscription_name)
-                .cmp(&is_production(&a.subscription_name))
+            (b.source == SubnetSource::Azure)
+                .cmp(&(a.source == SubnetSource::Azure))
+                .then_with(|| {
+                    is_production(&b.subscription_name).cmp(&is_production(&a.subscription_name))
+                })
                 .then_with(|| b.subnet_count.cmp(&a.subnet_count))
                 .then_with(|| a.subscription_name.cmp(&b.subscription_name))
+                .then_with(|| a.vnet_name.cmp(&b.vnet_name))
+                .then_with(|| a.subscription_id.cmp(&b.subscription_id))
         });
 
         let keeper = &sorted_vnets[0];
@@ -108,6 +118,7 @@ pub struct VnetInfo {
     pub subscription_name: String,
     pub location: String,
     pub subnet_count: usize,
+    pub source: SubnetSource,
 }
 
 /// Represents a group of VNets whose CIDRs overlap (directly or transitively).
@@ -164,6 +175,7 @@ pub fn find_overlapping_vnets(data: &Data) -> Vec<OverlapConflict> {
                 subscription_name: subnet.subscription_name.clone(),
                 location: subnet.location.clone(),
                 subnet_count: 1,
+                source: subnet.source,
             });
     }
 
@@ -225,6 +237,170 @@ pub fn find_overlapping_vnets(data: &Data) -> Vec<OverlapConflict> {
     conflicts
 }
 
+/// Two prefixes in one VNet's own address space that overlap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressSpaceOverlap {
+    pub vnet_name: String,
+    pub subscription_id: String,
+    pub subscription_name: String,
+    pub first: Ipv4,
+    pub second: Ipv4,
+}
+
+impl AddressSpaceOverlap {
+    /// `true` when the prefix is listed twice rather than partially overlapping.
+    pub fn is_duplicate(&self) -> bool {
+        self.first == self.second
+    }
+}
+
+/// Find duplicate or overlapping prefixes within each VNet's own address space.
+///
+/// Each VNet is checked once, however many subnets it has. Results are in
+/// data order.
+pub fn find_address_space_overlaps(data: &Data) -> Vec<AddressSpaceOverlap> {
+    let mut seen: HashSet<(&str, &str)> = HashSet::new();
+    let mut overlaps = Vec::new();
+    for subnet in &data.data {
+        if !seen.insert((&subnet.subscription_id, &subnet.vnet_name)) {
+            continue;
+        }
+        let space = &subnet.vnet_address_space;
+        for (i, a) in space.iter().enumerate() {
+            for b in &space[i + 1..] {
+                if cidrs_overlap(&[*a], &[*b]) {
+                    overlaps.push(AddressSpaceOverlap {
+                        vnet_name: subnet.vnet_name.clone(),
+                        subscription_id: subnet.subscription_id.clone(),
+                        subscription_name: subnet.subscription_name.clone(),
+                        first: *a,
+                        second: *b,
+                    });
+                }
+            }
+        }
+    }
+    overlaps
+}
+
+/// Log address space anomalies found by [`find_address_space_overlaps`] as warnings.
+pub fn log_address_space_overlaps(overlaps: &[AddressSpaceOverlap]) {
+    for o in overlaps {
+        log::warn!(
+            "VNet '{}' in '{}' ({}) address space {} {} and {}",
+            o.vnet_name,
+            o.subscription_name,
+            o.subscription_id,
+            if o.is_duplicate() {
+                "lists the same prefix twice:"
+            } else {
+                "has overlapping prefixes"
+            },
+            o.first,
+            o.second
+        );
+    }
+}
+
+/// Two subnets, in different places, whose address ranges intersect.
+#[derive(Debug, Clone)]
+pub struct SubnetOverlap {
+    pub first: Subnet,
+    pub second: Subnet,
+}
+
+/// Identity of a subnet: its resource ID, or names for old caches.
+fn subnet_identity(s: &Subnet) -> String {
+    if s.subnet_id.is_empty() {
+        format!("{}/{}/{}", s.subscription_id, s.vnet_name, s.subnet_name).to_lowercase()
+    } else {
+        s.subnet_id.to_lowercase()
+    }
+}
+
+/// `(subscription ID, VNet name)`, lowercased, as peering edges identify VNets.
+fn vnet_key(subscription_id: &str, vnet_name: &str) -> (String, String) {
+    (subscription_id.to_lowercase(), vnet_name.to_lowercase())
+}
+
+/// Find every pair of subnets whose ranges intersect, regardless of
+/// subscription — identical CIDRs in different subscriptions are routing
+/// hazards once the networks are connected.
+///
+/// Repeated records of the same subnet are not reported. A pair is allowed
+/// when both VNets are listed in `isolated_vnets` (case-insensitive) and are
+/// not peered with each other.
+pub fn find_overlapping_subnets(
+    data: &Data,
+    peering: &[PeeringEdge],
+    isolated_vnets: &[String],
+) -> Vec<SubnetOverlap> {
+    let peered: HashSet<((String, String), (String, String))> = peering
+        .iter()
+        .flat_map(|e| {
+            let local = vnet_key(&e.subscription_id, &e.vnet_name);
+            let remote = vnet_key(e.remote_subscription_id(), e.remote_vnet_name());
+            [(local.clone(), remote.clone()), (remote, local)]
+        })
+        .collect();
+    let isolated = |s: &Subnet| {
+        isolated_vnets
+            .iter()
+            .any(|v| v.eq_ignore_ascii_case(&s.vnet_name))
+    };
+    let allowed = |a: &Subnet, b: &Subnet| {
+        isolated(a)
+            && isolated(b)
+            && !peered.contains(&(
+                vnet_key(&a.subscription_id, &a.vnet_name),
+                vnet_key(&b.subscription_id, &b.vnet_name),
+            ))
+    };
+
+    // Sweep by start address, keeping the subnets that are still open.
+    let mut ranges: Vec<(Ipv4, &Subnet)> = data
+        .data
+        .iter()
+        .filter_map(|s| Some((s.subnet_cidr?, s)))
+        .collect();
+    ranges.sort_by_key(|(c, _)| (c.lo(), c.hi()));
+    let mut open: Vec<(Ipv4, &Subnet)> = Vec::new();
+    let mut overlaps = Vec::new();
+    for (cidr, subnet) in ranges {
+        open.retain(|(o, _)| o.hi() >= cidr.lo());
+        for (_, other) in &open {
+            if subnet_identity(other) != subnet_identity(subnet) && !allowed(other, subnet) {
+                overlaps.push(SubnetOverlap {
+                    first: (*other).clone(),
+                    second: subnet.clone(),
+                });
+            }
+        }
+        open.push((cidr, subnet));
+    }
+    overlaps
+}
+
+/// Log subnet overlaps found by [`find_overlapping_subnets`] as warnings.
+pub fn log_overlapping_subnets(overlaps: &[SubnetOverlap]) {
+    for o in overlaps {
+        let describe = |s: &Subnet| {
+            format!(
+                "'{}/{}' {} in '{}'",
+                s.vnet_name,
+                s.subnet_name,
+                s.subnet_cidr.map(|c| c.to_string()).unwrap_or_default(),
+                s.subscription_name
+            )
+        };
+        log::warn!(
+            "Subnet {} overlaps {}",
+            describe(&o.first),
+            describe(&o.second)
+        );
+    }
+}
+
 /// Log overlapping VNet conflicts as warnings.
 pub fn log_overlapping_vnets(conflicts: &[OverlapConflict]) {
     if conflicts.is_empty() {
@@ -442,6 +618,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn full_ties_keep_the_same_vnet_whatever_the_input_order() {
+        let alpha = make_subnet("alpha-vnet", "Dev Sub", "10.1.0.0/16", "10.1.1.0/24");
+        let beta = make_subnet("beta-vnet", "Dev Sub", "10.1.0.0/16", "10.1.2.0/24");
+        let twin_b = Subnet {
+            subscription_id: "sub-b".to_string(),
+            ..make_subnet("twin", "Twin Sub", "10.2.0.0/16", "10.2.1.0/24")
+        };
+        let twin_a = Subnet {
+            subscription_id: "sub-a".to_string(),
+            ..twin_b.clone()
+        };
+
+        for subnets in [
+            vec![alpha.clone(), beta.clone(), twin_a.clone(), twin_b.clone()],
+            vec![twin_b.clone(), beta.clone(), twin_a.clone(), alpha.clone()],
+        ] {
+            let out = resolve_overlapping_vnets(make_data(subnets));
+            let mut kept: Vec<(&str, &str)> = out
+                .active
+                .data
+                .iter()
+                .map(|s| (s.vnet_name.as_str(), s.subscription_id.as_str()))
+                .collect();
+            kept.sort_unstable();
+            // Same subscription name and subnet count: VNet name decides,
+            // then subscription ID.
+            assert_eq!(kept, [("alpha-vnet", "sub-dev-sub"), ("twin", "sub-a")]);
+        }
+    }
+
     #[test]
     fn non_conflicting_cidr_of_same_vnet_is_not_excluded() {
         // pd-ibe-westus-arm has two address spaces:
@@ -530,4 +737,67 @@ mod tests {
         assert_eq!(out.excluded[0].winner_vnet_name, "winner-vnet");
         assert_eq!(out.excluded[0].subnet.vnet_name, "loser-vnet");
     }
+
+    #[test]
+    fn address_space_overlaps_are_found_once_per_vnet() {
+        let space =
+            |cidrs: &[&str]| -> Vec<Ipv4> { cidrs.iter().map(|c| Ipv4::new(c).unwrap()).collect() };
+        let mut a1 = make_subnet("vnet-a", "Sub A", "10.0.0.0/16", "10.0.1.0/24");
+        a1.vnet_address_space = space(&["10.0.0.0/16", "10.0.128.0/17", "10.9.0.0/16"]);
+        let mut a2 = a1.clone();
+        a2.subnet_cidr = Some(Ipv4::new("10.0.2.0/24").unwrap());
+        let mut b = make_subnet("vnet-b", "Sub B", "10.2.0.0/16", "10.2.1.0/24");
+        b.vnet_address_space = space(&["10.2.0.0/16", "10.2.0.0/16"]);
+        let mut c = make_subnet("vnet-c", "Sub C", "10.3.0.0/16", "10.3.1.0/24");
+        c.vnet_address_space = space(&["10.3.0.0/16", "10.4.0.0/16"]);
+
+        let overlaps = find_address_space_overlaps(&make_data(vec![a1, a2, b, c]));
+        assert_eq!(overlaps.len(), 2);
+        assert_eq!(overlaps[0].vnet_name, "vnet-a");
+        assert_eq!(overlaps[0].second, Ipv4::new("10.0.128.0/17").unwrap());
+        assert!(!overlaps[0].is_duplicate());
+        assert_eq!(overlaps[1].vnet_name, "vnet-b");
+        assert!(overlaps[1].is_duplicate());
+    }
+
+    #[test]
+    fn strict_check_flags_intersecting_subnets_unless_isolated_and_unpeered() {
+        let mut dup = make_subnet("dup-vnet", "Prod", "10.0.0.0/16", "10.0.1.0/24");
+        dup.subnet_id = "/subscriptions/sub-prod/dup".to_string();
+        let data = make_data(vec![
+            make_subnet("lab-a", "Lab A", "10.0.0.0/16", "10.0.1.0/24"),
+            make_subnet("lab-b", "Lab B", "10.0.0.0/16", "10.0.1.0/25"),
+            dup.clone(),
+            dup,
+            make_subnet("other", "Prod", "10.9.0.0/16", "10.9.0.0/24"),
+        ]);
+        let names = |overlaps: &[SubnetOverlap]| -> Vec<(String, String)> {
+            overlaps
+                .iter()
+                .map(|o| (o.first.vnet_name.clone(), o.second.vnet_name.clone()))
+                .collect()
+        };
+
+        // Repeated records of dup-vnet's subnet are not an overlap with itself.
+        let all = find_overlapping_subnets(&data, &[], &[]);
+        assert_eq!(all.len(), 5, "{:?}", names(&all));
+
+        let isolated = ["LAB-A".to_string(), "lab-b".to_string()];
+        let relaxed = names(&find_overlapping_subnets(&data, &[], &isolated));
+        assert_eq!(relaxed.len(), 4);
+        assert!(!relaxed
+            .iter()
+            .any(|(a, b)| a.starts_with("lab-") && b.starts_with("lab-")));
+
+        let peering = [PeeringEdge {
+            subscription_id: "sub-lab-a".to_string(),
+            vnet_name: "lab-a".to_string(),
+            remote_vnet_id: "/subscriptions/sub-lab-b/resourceGroups/rg/providers/Microsoft.Network/virtualNetworks/lab-b".to_string(),
+            ..Default::default()
+        }];
+        assert_eq!(
+            find_overlapping_subnets(&data, &peering, &isolated).len(),
+            5
+        );
+    }
 }
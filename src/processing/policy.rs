@@ -0,0 +1,204 @@
+//! VNet policies from the config file.
+//!
+//! A `[[policy]]` entry scopes a set of VNets by subscription and/or VNet
+//! name pattern, or management group, and states what every VNet in scope
+//! must have, e.g. "all prod VNets must have DDoS Network Protection" or
+//! "VNets under the EU management group must stay in EU regions" (data
+//! residency). Patterns are regular expressions matched case-insensitively,
+//! so `subscription = "prod"` covers both `Prod-Payments` and `corp-prod`.
+
+use crate::azure::{Data, SubscriptionData};
+use crate::models::Subnet;
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::error::Error;
+
+/// One `[[policy]]` entry; unset patterns match every VNet.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VnetPolicy {
+    /// Name shown in findings.
+    pub name: String,
+    /// Subscription display name or ID pattern.
+    pub subscription: Option<String>,
+    /// VNet name pattern.
+    pub vnet: Option<String>,
+    /// Management group (ID or display name) anywhere above the VNet's
+    /// subscription.
+    pub management_group: Option<String>,
+    /// VNets in scope must have a DDoS Network Protection plan.
+    pub require_ddos: bool,
+    /// VNets in scope must have VNet encryption enabled.
+    pub require_encryption: bool,
+    /// VNets in scope must be in one of these Azure locations, e.g.
+    /// `["westeurope", "northeurope"]`; empty allows any.
+    pub allowed_locations: Vec<String>,
+}
+
+/// What a VNet in scope of a policy is missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyRequirement {
+    DdosProtection,
+    Encryption,
+    /// The VNet's location is not in `allowed_locations`.
+    Location,
+}
+
+/// A VNet that breaks a policy; `vnet` is one of its subnets.
+#[derive(Debug, Clone)]
+pub struct PolicyViolation<'a> {
+    pub policy: String,
+    pub requirement: PolicyRequirement,
+    pub vnet: &'a Subnet,
+}
+
+fn pattern(p: &Option<String>, policy: &str) -> Result<Option<Regex>, Box<dyn Error>> {
+    p.as_deref()
+        .map(|p| {
+            RegexBuilder::new(p)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| format!("Invalid pattern '{p}' in policy '{policy}': {e}").into())
+        })
+        .transpose()
+}
+
+/// Every VNet in `data` that breaks one of `policies`, once per policy and
+/// requirement, in input order. `subscriptions` resolves management group
+/// scopes; VNets of subscriptions it does not list are out of their scope.
+pub fn vnet_policy_violations<'a>(
+    data: &'a Data,
+    subscriptions: &SubscriptionData,
+    policies: &[VnetPolicy],
+) -> Result<Vec<PolicyViolation<'a>>, Box<dyn Error>> {
+    let mut violations = Vec::new();
+    for policy in policies {
+        let subscription = pattern(&policy.subscription, &policy.name)?;
+        let vnet = pattern(&policy.vnet, &policy.name)?;
+        let in_group: Option<HashSet<&str>> = policy.management_group.as_deref().map(|mg| {
+            subscriptions
+                .data
+                .iter()
+                .filter(|s| s.in_management_group(mg))
+                .map(|s| s.subscription_id.as_str())
+                .collect()
+        });
+        let mut seen = HashSet::new();
+        for s in &data.data {
+            let in_scope = subscription.as_ref().is_none_or(|re| {
+                re.is_match(&s.subscription_name) || re.is_match(&s.subscription_id)
+            }) && vnet.as_ref().is_none_or(|re| re.is_match(&s.vnet_name))
+                && in_group
+                    .as_ref()
+                    .is_none_or(|ids| ids.contains(s.subscription_id.as_str()));
+            if !in_scope || !seen.insert((s.subscription_id.as_str(), s.vnet_name.as_str())) {
+                continue;
+            }
+            let missing = [
+                (policy.require_ddos && s.ddos_protection_plan.is_none())
+                    .then_some(PolicyRequirement::DdosProtection),
+                (policy.require_encryption && s.encryption.is_none())
+                    .then_some(PolicyRequirement::Encryption),
+                (!policy.allowed_locations.is_empty()
+                    && !policy
+                        .allowed_locations
+                        .iter()
+                        .any(|l| l.eq_ignore_ascii_case(&s.location)))
+                .then_some(PolicyRequirement::Location),
+            ];
+            violations.extend(
+                missing
+                    .into_iter()
+                    .flatten()
+                    .map(|requirement| PolicyViolation {
+                        policy: policy.name.clone(),
+                        requirement,
+                        vnet: s,
+                    }),
+            );
+        }
+    }
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ManagementGroupRef, Subscription};
+
+    fn subnet(subscription: &str, vnet: &str, ddos: bool) -> Subnet {
+        Subnet {
+            subscription_id: format!("id-{subscription}"),
+            subscription_name: subscription.to_string(),
+            vnet_name: vnet.to_string(),
+            ddos_protection_plan: ddos.then(|| "/plans/ddos".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn policy_reports_each_vnet_in_scope_once() {
+        let data = Data {
+            data: vec![
+                subnet("Prod-Payments", "pay-vnet", false),
+                subnet("Prod-Payments", "pay-vnet", false),
+                subnet("corp-prod", "hub", true),
+                subnet("Dev", "dev-vnet", false),
+            ],
+            ..Default::default()
+        };
+        let policies = [VnetPolicy {
+            name: "prod-ddos".to_string(),
+            subscription: Some("prod".to_string()),
+            require_ddos: true,
+            ..Default::default()
+        }];
+        let subscriptions = SubscriptionData::default();
+        let violations = vnet_policy_violations(&data, &subscriptions, &policies).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].vnet.vnet_name, "pay-vnet");
+        assert_eq!(violations[0].requirement, PolicyRequirement::DdosProtection);
+
+        let bad = [VnetPolicy {
+            vnet: Some("(".to_string()),
+            ..Default::default()
+        }];
+        assert!(vnet_policy_violations(&data, &subscriptions, &bad).is_err());
+    }
+
+    #[test]
+    fn vnets_under_a_management_group_must_be_in_allowed_locations() {
+        let mut eu = subnet("Prod-Payments", "pay-vnet", false);
+        eu.location = "westeurope".to_string();
+        let mut stray = subnet("Prod-Payments", "pay-dr", false);
+        stray.location = "EastUS".to_string();
+        let mut other = subnet("Dev", "dev-vnet", false);
+        other.location = "eastus".to_string();
+        let data = Data {
+            data: vec![eu, stray, other],
+            ..Default::default()
+        };
+        let subscriptions = SubscriptionData {
+            data: vec![Subscription {
+                subscription_id: "id-Prod-Payments".to_string(),
+                management_group_chain: vec![ManagementGroupRef {
+                    name: "mg-eu".to_string(),
+                    display_name: "EU".to_string(),
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let policies = [VnetPolicy {
+            name: "eu-residency".to_string(),
+            management_group: Some("eu".to_string()),
+            allowed_locations: vec!["westeurope".to_string(), "northeurope".to_string()],
+            ..Default::default()
+        }];
+        let violations = vnet_policy_violations(&data, &subscriptions, &policies).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].vnet.vnet_name, "pay-dr");
+        assert_eq!(violations[0].requirement, PolicyRequirement::Location);
+    }
+}
@@ -0,0 +1,221 @@
+//! Azure networking limit checks.
+//!
+//! Azure caps how many VNets a subscription may hold per region, how many
+//! subnets a VNet may hold and how many peerings a VNet may have. Hitting one
+//! blocks the next deployment, so usage is compared with the default limits
+//! (see "Azure subscription and service limits" in the Azure docs) and
+//! reported once it passes a percentage of the limit.
+
+use crate::azure::{Data, PeeringEdge};
+use crate::output::format::{percent, thousands};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A networking limit that is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QuotaKind {
+    /// Virtual networks per subscription per region.
+    VnetsPerSubscription,
+    /// Subnets per virtual network.
+    SubnetsPerVnet,
+    /// Peerings per virtual network.
+    PeeringsPerVnet,
+}
+
+impl QuotaKind {
+    /// Default Azure limit.
+    pub fn limit(self) -> u64 {
+        match self {
+            QuotaKind::VnetsPerSubscription => 1000,
+            QuotaKind::SubnetsPerVnet => 3000,
+            QuotaKind::PeeringsPerVnet => 500,
+        }
+    }
+}
+
+impl fmt::Display for QuotaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            QuotaKind::VnetsPerSubscription => "VNets per subscription and region",
+            QuotaKind::SubnetsPerVnet => "subnets per VNet",
+            QuotaKind::PeeringsPerVnet => "peerings per VNet",
+        })
+    }
+}
+
+/// Usage of one limit in one scope (a subscription region or a VNet).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaUsage {
+    pub kind: QuotaKind,
+    /// `subscription (region)` or `subscription/vnet`.
+    pub scope: String,
+    pub used: u64,
+    pub limit: u64,
+}
+
+impl QuotaUsage {
+    fn new(kind: QuotaKind, scope: String, used: usize) -> Self {
+        QuotaUsage {
+            kind,
+            scope,
+            used: used as u64,
+            limit: kind.limit(),
+        }
+    }
+
+    /// Percentage of the limit in use (rounded down).
+    pub fn pct(&self) -> u64 {
+        self.used * 100 / self.limit
+    }
+}
+
+/// Usage of every checked limit in every scope, highest percentage first.
+///
+/// VNets and subnets are counted by name, so duplicate records of the same
+/// subnet count once.
+pub fn quota_usage(data: &Data, peerings: &[PeeringEdge]) -> Vec<QuotaUsage> {
+    let mut vnets: HashMap<(&str, &str), HashSet<&str>> = HashMap::new();
+    let mut subnets: HashMap<(&str, &str), HashSet<&str>> = HashMap::new();
+    // Reserved and on-prem ranges are not Azure resources; planned subnets
+    // will count once deployed.
+    for s in data.data.iter().filter(|s| s.source.is_azure_resource()) {
+        vnets
+            .entry((s.subscription_name.as_str(), s.location.as_str()))
+            .or_default()
+            .insert(&s.vnet_name);
+        let in_vnet = subnets
+            .entry((s.subscription_name.as_str(), s.vnet_name.as_str()))
+            .or_default();
+        if s.subnet_cidr.is_some() {
+            in_vnet.insert(&s.subnet_name);
+        }
+    }
+    let mut peered: HashMap<(&str, &str), HashSet<&str>> = HashMap::new();
+    for p in peerings {
+        peered
+            .entry((p.subscription_name.as_str(), p.vnet_name.as_str()))
+            .or_default()
+            .insert(&p.peering_name);
+    }
+
+    let mut usage: Vec<QuotaUsage> = vnets
+        .into_iter()
+        .map(|((sub, location), names)| {
+            QuotaUsage::new(
+                QuotaKind::VnetsPerSubscription,
+                format!("{sub} ({location})"),
+                names.len(),
+            )
+        })
+        .chain(subnets.into_iter().map(|((sub, vnet), names)| {
+            QuotaUsage::new(
+                QuotaKind::SubnetsPerVnet,
+                format!("{sub}/{vnet}"),
+                names.len(),
+            )
+        }))
+        .chain(peered.into_iter().map(|((sub, vnet), names)| {
+            QuotaUsage::new(
+                QuotaKind::PeeringsPerVnet,
+                format!("{sub}/{vnet}"),
+                names.len(),
+            )
+        }))
+        .collect();
+    usage.sort_by(|a, b| {
+        (b.used * a.limit)
+            .cmp(&(a.used * b.limit))
+            .then_with(|| (a.kind, &a.scope).cmp(&(b.kind, &b.scope)))
+    });
+    usage
+}
+
+/// Usage at or above `warn_pct` percent of its limit.
+pub fn quota_warnings(usage: Vec<QuotaUsage>, warn_pct: u8) -> Vec<QuotaUsage> {
+    usage
+        .into_iter()
+        .filter(|u| u.used * 100 >= u.limit * u64::from(warn_pct))
+        .collect()
+}
+
+/// Render quota usage as terminal lines.
+pub fn format_quota_usage(usage: &[QuotaUsage]) -> String {
+    if usage.is_empty() {
+        return "QUOTA: all networking limits below the warning threshold".to_string();
+    }
+    usage
+        .iter()
+        .map(|u| {
+            format!(
+                "QUOTA: '{}' - {} of {} {} ({})",
+                u.scope,
+                thousands(u.used),
+                thousands(u.limit),
+                u.kind,
+                percent(u.used, u.limit)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    fn make_subnet(vnet: &str, name: &str) -> Subnet {
+        Subnet {
+            subscription_name: "Prod".to_string(),
+            location: "westeurope".to_string(),
+            ..Subnet::test(vnet, name, "10.0.0.0/24")
+        }
+    }
+
+    fn make_peering(vnet: &str, name: &str) -> PeeringEdge {
+        PeeringEdge {
+            subscription_id: "sub-id".to_string(),
+            subscription_name: "Prod".to_string(),
+            vnet_name: vnet.to_string(),
+            vnet_cidr: vec![],
+            peering_name: name.to_string(),
+            peering_state: "Connected".to_string(),
+            remote_vnet_id: format!("/subscriptions/x/virtualNetworks/{name}"),
+        }
+    }
+
+    #[test]
+    fn usage_is_counted_per_scope_and_filtered_by_threshold() {
+        let data = Data {
+            data: vec![
+                make_subnet("hub", "a"),
+                make_subnet("hub", "a"),
+                make_subnet("hub", "b"),
+                make_subnet("spoke", "c"),
+            ],
+            ..Default::default()
+        };
+        let peerings: Vec<PeeringEdge> = (0..450)
+            .map(|i| make_peering("hub", &format!("p{i}")))
+            .collect();
+
+        let usage = quota_usage(&data, &peerings);
+        assert_eq!(usage.len(), 4);
+        assert_eq!(usage[0].kind, QuotaKind::PeeringsPerVnet);
+        assert_eq!((usage[0].used, usage[0].pct()), (450, 90));
+        let vnets = usage
+            .iter()
+            .find(|u| u.kind == QuotaKind::VnetsPerSubscription)
+            .unwrap();
+        assert_eq!((vnets.scope.as_str(), vnets.used), ("Prod (westeurope)", 2));
+        assert!(usage
+            .iter()
+            .any(|u| u.kind == QuotaKind::SubnetsPerVnet && u.scope == "Prod/hub" && u.used == 2));
+
+        let warnings = quota_warnings(usage, 80);
+        assert_eq!(
+            format_quota_usage(&warnings),
+            "QUOTA: 'Prod/hub' - 450 of 500 peerings per VNet (90%)"
+        );
+    }
+}
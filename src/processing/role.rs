@@ -0,0 +1,141 @@
+//! Well-known subnet role classification.
+//!
+//! Azure reserves some subnet names for platform services (gateways, firewall,
+//! Bastion, Route Server) and marks others by delegation. Labelling them makes
+//! the report easier to scan than raw names alone.
+
+use crate::models::Subnet;
+use std::fmt;
+
+/// Delegation service name for Application Gateway v2 dedicated subnets.
+const APP_GATEWAY_DELEGATION: &str = "Microsoft.Network/applicationGateways";
+
+/// Platform role of a well-known Azure subnet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubnetRole {
+    /// `GatewaySubnet` — VPN / ExpressRoute virtual network gateways.
+    Gateway,
+    /// `AzureFirewallSubnet`.
+    Firewall,
+    /// `AzureFirewallManagementSubnet` (forced tunnelling).
+    FirewallManagement,
+    /// `AzureBastionSubnet`.
+    Bastion,
+    /// `RouteServerSubnet`.
+    RouteServer,
+    /// Delegated to Application Gateway.
+    ApplicationGateway,
+}
+
+impl SubnetRole {
+    /// Short label used in report columns.
+    pub fn label(self) -> &'static str {
+        match self {
+            SubnetRole::Gateway => "gateway",
+            SubnetRole::Firewall => "firewall",
+            SubnetRole::FirewallManagement => "firewall-mgmt",
+            SubnetRole::Bastion => "bastion",
+            SubnetRole::RouteServer => "route-server",
+            SubnetRole::ApplicationGateway => "app-gateway",
+        }
+    }
+
+    /// Azure manages these subnets itself; they do not support (or need) a
+    /// customer NSG.
+    pub fn nsg_exempt(self) -> bool {
+        matches!(
+            self,
+            SubnetRole::Gateway
+                | SubnetRole::Firewall
+                | SubnetRole::FirewallManagement
+                | SubnetRole::RouteServer
+        )
+    }
+}
+
+impl fmt::Display for SubnetRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Classify `subnet` by its reserved name or delegation.
+///
+/// Reserved names are matched case-insensitively; `None` means an ordinary
+/// workload subnet.
+pub fn subnet_role(subnet: &Subnet) -> Option<SubnetRole> {
+    let by_name = [
+        ("GatewaySubnet", SubnetRole::Gateway),
+        ("AzureFirewallSubnet", SubnetRole::Firewall),
+        (
+            "AzureFirewallManagementSubnet",
+            SubnetRole::FirewallManagement,
+        ),
+        ("AzureBastionSubnet", SubnetRole::Bastion),
+        ("RouteServerSubnet", SubnetRole::RouteServer),
+    ];
+    by_name
+        .iter()
+        .find(|(name, _)| subnet.subnet_name.eq_ignore_ascii_case(name))
+        .map(|&(_, role)| role)
+        .or_else(|| {
+            subnet
+                .delegations
+                .iter()
+                .any(|d| d.eq_ignore_ascii_case(APP_GATEWAY_DELEGATION))
+                .then_some(SubnetRole::ApplicationGateway)
+        })
+}
+
+/// Role label for report columns; empty for ordinary subnets.
+pub(crate) fn role_label(subnet: &Subnet) -> String {
+    subnet_role(subnet)
+        .map(|r| r.label().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_subnet(name: &str, delegations: &[&str]) -> Subnet {
+        Subnet {
+            subnet_name: name.to_string(),
+            delegations: delegations.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reserved_names_are_classified_case_insensitively() {
+        assert_eq!(
+            subnet_role(&make_subnet("GatewaySubnet", &[])),
+            Some(SubnetRole::Gateway)
+        );
+        assert_eq!(
+            subnet_role(&make_subnet("azurebastionsubnet", &[])),
+            Some(SubnetRole::Bastion)
+        );
+        assert_eq!(
+            subnet_role(&make_subnet("AzureFirewallManagementSubnet", &[])),
+            Some(SubnetRole::FirewallManagement)
+        );
+    }
+
+    #[test]
+    fn app_gateway_is_detected_by_delegation() {
+        let s = make_subnet("appgw-snet", &["Microsoft.Network/applicationGateways"]);
+        assert_eq!(subnet_role(&s), Some(SubnetRole::ApplicationGateway));
+        assert_eq!(
+            subnet_role(&make_subnet("appgw-snet", &["Microsoft.Web/serverFarms"])),
+            None
+        );
+    }
+
+    #[test]
+    fn only_platform_managed_roles_are_nsg_exempt() {
+        assert!(SubnetRole::Firewall.nsg_exempt());
+        assert!(!SubnetRole::Bastion.nsg_exempt());
+        assert!(!SubnetRole::ApplicationGateway.nsg_exempt());
+    }
+}
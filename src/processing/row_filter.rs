@@ -0,0 +1,134 @@
+//! Post-processing filter over generated subnet rows.
+//!
+//! Applied to the rows from [`build_rows`](crate::output::build_rows) so the
+//! CSV and table show only gaps/subnets with enough room (`--min-free`,
+//! `--only-gaps-larger-than`).
+
+use super::buffer::is_buffer;
+use super::gap_finder::SubnetPrintRow;
+use crate::models::Ipv4;
+
+/// Size thresholds a row must meet to be shown. The default keeps every row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RowFilter {
+    /// Keep rows with at least this many free Azure hosts.
+    pub min_free: Option<u64>,
+    /// Keep only gap rows whose block is larger than this size (prefix
+    /// length, e.g. `25` keeps /24 and larger gaps).
+    pub gaps_larger_than: Option<u8>,
+}
+
+/// `true` for unused space between or inside VNets.
+fn is_gap(row: &SubnetPrintRow) -> bool {
+    row.gap == "-gap-" || row.gap == "-vgap-"
+}
+
+/// Free Azure hosts in a row: all of a gap, the unused part of a subnet,
+/// nothing for a vWAN hub or a buffer (not allocatable).
+fn free_hosts(row: &SubnetPrintRow) -> u64 {
+    if is_gap(row) {
+        row.az_hosts as u64
+    } else if row.gap == "VWAN_HUB" || is_buffer(row) {
+        0
+    } else {
+        (row.az_hosts as u64).saturating_sub(u64::from(row.ip_configurations_count))
+    }
+}
+
+impl RowFilter {
+    /// `true` when any threshold is set.
+    pub fn is_active(&self) -> bool {
+        self.min_free.is_some() || self.gaps_larger_than.is_some()
+    }
+
+    /// Whether `row` meets every threshold.
+    pub fn keeps(&self, row: &SubnetPrintRow) -> bool {
+        if let Some(prefix) = self.gaps_larger_than {
+            let big_enough = Ipv4::new(&row.subnet_cidr).is_ok_and(|c| c.mask < prefix);
+            if !(is_gap(row) && big_enough) {
+                return false;
+            }
+        }
+        self.min_free.is_none_or(|n| free_hosts(row) >= n)
+    }
+
+    /// Drop the rows that do not meet the thresholds.
+    pub fn apply(&self, mut rows: Vec<SubnetPrintRow>) -> Vec<SubnetPrintRow> {
+        if self.is_active() {
+            rows.retain(|row| self.keeps(row));
+        }
+        rows
+    }
+}
+
+/// Parse a prefix length given as `/25` or `25`.
+pub fn parse_prefix_len(s: &str) -> Result<u8, String> {
+    let len: u8 = s
+        .trim_start_matches('/')
+        .parse()
+        .map_err(|_| format!("'{s}' is not a prefix length like /25"))?;
+    if len > 32 {
+        return Err(format!("prefix length /{len} is larger than /32"));
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+    use crate::output::build_rows;
+
+    fn make_subnet(subnet_cidr: &str, used: u32) -> Subnet {
+        Subnet {
+            subscription_id: "sub-id".to_string(),
+            vnet_cidr: Ipv4::new("10.0.0.0/23").unwrap(),
+            ip_configurations_count: Some(used),
+            ..Subnet::test("vnet", subnet_cidr, subnet_cidr)
+        }
+    }
+
+    fn cidrs(rows: &[SubnetPrintRow]) -> Vec<&str> {
+        rows.iter().map(|r| r.subnet_cidr.as_str()).collect()
+    }
+
+    #[test]
+    fn rows_are_filtered_by_free_hosts_and_gap_size() {
+        // Gaps: 10.0.0.64/26 inside the VNet and 10.0.1.0/24 after the last subnet.
+        let subnets = [
+            make_subnet("10.0.0.0/26", 50),
+            make_subnet("10.0.0.128/25", 3),
+        ];
+        let rows = build_rows(&subnets, &[], 24, &[]);
+
+        let min_free = RowFilter {
+            min_free: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(
+            cidrs(&min_free.apply(build_rows(&subnets, &[], 24, &[]))),
+            ["10.0.0.128/25", "10.0.1.0/24"]
+        );
+
+        let big_gaps = RowFilter {
+            gaps_larger_than: Some(25),
+            ..Default::default()
+        };
+        assert_eq!(cidrs(&big_gaps.apply(rows.clone())), ["10.0.1.0/24"]);
+
+        // Strictly larger: a /24 gap is not larger than /24.
+        let no_gaps = RowFilter {
+            gaps_larger_than: Some(24),
+            ..Default::default()
+        };
+        assert!(no_gaps.apply(rows).is_empty());
+    }
+
+    #[test]
+    fn prefix_len_accepts_slash_or_number() {
+        assert_eq!(parse_prefix_len("/25"), Ok(25));
+        assert_eq!(parse_prefix_len("24"), Ok(24));
+        assert!(parse_prefix_len("/33").is_err());
+        assert!(parse_prefix_len("big").is_err());
+    }
+}
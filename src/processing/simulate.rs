@@ -0,0 +1,543 @@
+//! What-if simulation on the in-memory subnet data.
+//!
+//! Nothing here touches Azure: subnets are removed from or planned subnets
+//! added to a copy of the data, gap finding is re-run, and address space usage
+//! per VNet CIDR is compared before and after. Buffers (intentional gaps) are
+//! not free space, and planned subnets may not take them.
+
+use super::allocator::{cover_range, subtract_ranges, vnet_rows};
+use super::gap_finder::{gaps, group_vnet_cidrs, GapKind, VnetCidr};
+use crate::azure::Data;
+use crate::models::{Ipv4, Subnet, SubnetSource};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::net::Ipv4Addr;
+
+/// A contiguous run of unused addresses inside a VNet CIDR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeRange {
+    pub lo: Ipv4Addr,
+    pub hi: Ipv4Addr,
+}
+
+impl FreeRange {
+    /// The range of addresses `[lo, hi)`.
+    fn from_range((lo, hi): (u64, u64)) -> FreeRange {
+        FreeRange {
+            lo: Ipv4Addr::from(lo as u32),
+            hi: Ipv4Addr::from((hi - 1) as u32),
+        }
+    }
+
+    /// The range as addresses `[lo, hi)`.
+    fn bounds(&self) -> (u64, u64) {
+        (
+            u64::from(u32::from(self.lo)),
+            u64::from(u32::from(self.hi)) + 1,
+        )
+    }
+
+    /// Number of addresses in the range.
+    pub fn size(&self) -> u64 {
+        let (lo, hi) = self.bounds();
+        hi - lo
+    }
+
+    /// The fewest aligned CIDR blocks covering the range.
+    pub fn blocks(&self) -> Vec<Ipv4> {
+        let (lo, hi) = self.bounds();
+        cover_range(lo, hi)
+    }
+}
+
+impl fmt::Display for FreeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let blocks: Vec<String> = self.blocks().iter().map(|b| b.to_string()).collect();
+        write!(
+            f,
+            "{} - {} ({} addresses: {})",
+            self.lo,
+            self.hi,
+            self.size(),
+            blocks.join(", ")
+        )
+    }
+}
+
+/// Address space usage of one VNet CIDR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceUsage {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub vnet_cidr: Ipv4,
+    /// Addresses taken by subnets.
+    pub used: u64,
+    /// Unused ranges outside the buffers, ascending.
+    pub free: Vec<FreeRange>,
+    /// Parts of the VNet CIDR inside a buffer, ascending; never free.
+    pub buffered: Vec<FreeRange>,
+}
+
+impl SpaceUsage {
+    /// Addresses in the VNet CIDR.
+    pub fn total(&self) -> u64 {
+        1u64 << (32 - u32::from(self.vnet_cidr.mask))
+    }
+
+    /// Percentage of the VNet CIDR taken by subnets.
+    pub fn utilisation_pct(&self) -> f64 {
+        self.used as f64 * 100.0 / self.total() as f64
+    }
+
+    /// Size of the largest contiguous free range.
+    pub fn largest_free(&self) -> u64 {
+        self.free.iter().map(FreeRange::size).max().unwrap_or(0)
+    }
+
+    fn key(&self) -> (&str, &str, Ipv4) {
+        (&self.subscription_name, &self.vnet_name, self.vnet_cidr)
+    }
+}
+
+/// Run gap finding on every VNet CIDR in `data` and collect its usage, with
+/// the `buffers` left out of the free space.
+pub fn space_usage(data: &Data, buffers: &[Ipv4]) -> Vec<SpaceUsage> {
+    // Group per VNet first so equal CIDRs of different VNets stay apart; each
+    // VNet's prefixes (including empty ones) are split by `group_vnet_cidrs`.
+    let mut by_vnet: BTreeMap<(&str, &str), Vec<Subnet>> = BTreeMap::new();
+    for s in &data.data {
+        by_vnet
+            .entry((&s.subscription_id, &s.vnet_name))
+            .or_default()
+            .push(s.clone());
+    }
+
+    // VNets are independent: measure them in parallel.
+    let by_vnet: Vec<Vec<Subnet>> = by_vnet.into_values().collect();
+    let measure = |mut subnets: Vec<Subnet>| {
+        subnets.sort_by_key(|s| s.subnet_cidr);
+        group_vnet_cidrs(&subnets)
+            .into_iter()
+            .map(|vc| vnet_usage(&vc, buffers))
+            .collect::<Vec<_>>()
+    };
+    #[cfg(feature = "parallel")]
+    let mut usage: Vec<SpaceUsage> = by_vnet.into_par_iter().flat_map_iter(measure).collect();
+    #[cfg(not(feature = "parallel"))]
+    let mut usage: Vec<SpaceUsage> = by_vnet.into_iter().flat_map(measure).collect();
+    usage.sort_by(|a, b| {
+        (a.vnet_cidr, &a.subscription_name, &a.vnet_name).cmp(&(
+            b.vnet_cidr,
+            &b.subscription_name,
+            &b.vnet_name,
+        ))
+    });
+    usage
+}
+
+/// Used addresses and merged free ranges of one VNet CIDR.
+fn vnet_usage(vc: &VnetCidr, buffers: &[Ipv4]) -> SpaceUsage {
+    let mut used = 0;
+    let mut free: Vec<FreeRange> = Vec::new();
+    for event in gaps(std::slice::from_ref(vc), 0) {
+        match event.kind {
+            GapKind::Subnet(_) => used += 1u64 << (32 - u32::from(event.cidr.mask)),
+            GapKind::Vnet(_) => match free.last_mut() {
+                Some(last)
+                    if u64::from(u32::from(last.hi)) + 1
+                        == u64::from(u32::from(event.cidr.lo())) =>
+                {
+                    last.hi = event.cidr.hi();
+                }
+                _ => free.push(FreeRange {
+                    lo: event.cidr.lo(),
+                    hi: event.cidr.hi(),
+                }),
+            },
+            GapKind::Gap => {}
+        }
+    }
+    let (lo, hi) = vc.cidr.range();
+    let cut: Vec<(u64, u64)> = buffers.iter().map(|b| b.range()).collect();
+    // The complement of the unbuffered space: buffers clipped and merged.
+    let buffered = subtract_ranges(lo, hi, &subtract_ranges(lo, hi, &cut));
+    let free = free
+        .iter()
+        .flat_map(|r| {
+            let (r_lo, r_hi) = r.bounds();
+            subtract_ranges(r_lo, r_hi, &buffered)
+        })
+        .map(FreeRange::from_range)
+        .collect();
+    SpaceUsage {
+        subscription_name: vc.subscription_name.clone(),
+        vnet_name: vc.vnet_name.clone(),
+        vnet_cidr: vc.cidr,
+        used,
+        free,
+        buffered: buffered.into_iter().map(FreeRange::from_range).collect(),
+    }
+}
+
+/// Usage of one VNet CIDR before and after a simulated change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageChange {
+    pub before: SpaceUsage,
+    pub after: SpaceUsage,
+}
+
+impl UsageChange {
+    /// Free ranges that exist only after the change.
+    pub fn new_free_ranges(&self) -> Vec<FreeRange> {
+        self.after
+            .free
+            .iter()
+            .filter(|r| !self.before.free.contains(r))
+            .copied()
+            .collect()
+    }
+}
+
+/// Pair up VNet CIDRs whose usage differs between `before` and `after`.
+///
+/// A VNet CIDR missing from `after` (every subnet removed) counts as free
+/// outside its buffers.
+pub fn compare_usage(before: &[SpaceUsage], after: &[SpaceUsage]) -> Vec<UsageChange> {
+    before
+        .iter()
+        .filter_map(|b| {
+            let a = after
+                .iter()
+                .find(|a| a.key() == b.key())
+                .cloned()
+                .unwrap_or_else(|| {
+                    let (lo, hi) = b.vnet_cidr.range();
+                    let buffered: Vec<(u64, u64)> =
+                        b.buffered.iter().map(FreeRange::bounds).collect();
+                    SpaceUsage {
+                        used: 0,
+                        free: subtract_ranges(lo, hi, &buffered)
+                            .into_iter()
+                            .map(FreeRange::from_range)
+                            .collect(),
+                        ..b.clone()
+                    }
+                });
+            (*b != a).then(|| UsageChange {
+                before: b.clone(),
+                after: a,
+            })
+        })
+        .collect()
+}
+
+/// Does `selector` pick `subnet`? Selectors are a CIDR, `vnet/subnet` or a
+/// subnet name, matched case-insensitively.
+fn selects(selector: &str, subnet: &Subnet) -> bool {
+    if let Ok(cidr) = Ipv4::new(selector) {
+        return subnet.subnet_cidr == Some(cidr);
+    }
+    match selector.split_once('/') {
+        Some((vnet, name)) => {
+            subnet.vnet_name.eq_ignore_ascii_case(vnet)
+                && subnet.subnet_name.eq_ignore_ascii_case(name)
+        }
+        None => subnet.subnet_name.eq_ignore_ascii_case(selector),
+    }
+}
+
+/// Copy of `data` without the subnets picked by `selectors`, plus the removed
+/// subnets. Every selector must match at least one subnet.
+pub fn remove_subnets(
+    data: &Data,
+    selectors: &[String],
+) -> Result<(Data, Vec<Subnet>), Box<dyn Error>> {
+    if let Some(unmatched) = selectors
+        .iter()
+        .find(|sel| !data.data.iter().any(|s| selects(sel, s)))
+    {
+        return Err(format!("No subnet matches '{unmatched}'").into());
+    }
+    let (removed, kept): (Vec<Subnet>, Vec<Subnet>) = data
+        .data
+        .iter()
+        .cloned()
+        .partition(|s| selectors.iter().any(|sel| selects(sel, s)));
+    Ok((
+        Data {
+            count: kept.len() as i32,
+            data: kept,
+            ..data.clone()
+        },
+        removed,
+    ))
+}
+
+/// A planned subnet that cannot be added as proposed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddConflict {
+    pub name: String,
+    pub cidr: Ipv4,
+    pub reason: String,
+}
+
+/// Why `cidr` cannot be added next to `existing` (live or already planned) in
+/// a VNet with address space `spaces` and `buffers`, if it cannot.
+fn add_conflict(
+    cidr: Ipv4,
+    spaces: &[Ipv4],
+    existing: &[&Subnet],
+    buffers: &[Ipv4],
+) -> Option<String> {
+    if cidr.addr != cidr.lo() {
+        return Some(format!("not aligned, network address is {}", cidr.lo()));
+    }
+    if !spaces
+        .iter()
+        .any(|sp| sp.contains(cidr.lo()) && sp.contains(cidr.hi()))
+    {
+        let spaces: Vec<String> = spaces.iter().map(|s| s.to_string()).collect();
+        return Some(format!(
+            "outside the VNet address space ({})",
+            spaces.join(", ")
+        ));
+    }
+    if let Some(b) = buffers
+        .iter()
+        .find(|b| b.lo() <= cidr.hi() && cidr.lo() <= b.hi())
+    {
+        return Some(format!("overlaps buffer {b}"));
+    }
+    existing.iter().find_map(|s| {
+        let sc = s.subnet_cidr?;
+        (sc.lo() <= cidr.hi() && cidr.lo() <= sc.hi())
+            .then(|| format!("overlaps subnet '{}' {sc}", s.subnet_name))
+    })
+}
+
+/// Copy of `data` with planned subnets `proposals` (name, CIDR) added to VNet
+/// `vnet_name`, plus the proposals that conflict and were left out.
+///
+/// Each proposal must be aligned, inside the VNet address space and clear of
+/// the `buffers`, live subnets and earlier proposals. `subscription` picks
+/// the VNet when its name is not unique.
+pub fn add_subnets(
+    data: &Data,
+    vnet_name: &str,
+    subscription: Option<&str>,
+    proposals: &[(String, Ipv4)],
+    buffers: &[Ipv4],
+) -> Result<(Data, Vec<AddConflict>), Box<dyn Error>> {
+    let rows = vnet_rows(data, vnet_name, subscription)?;
+    let template = rows[0];
+    let mut spaces: Vec<Ipv4> = rows
+        .iter()
+        .flat_map(|s| s.vnet_address_space.iter().copied().chain([s.vnet_cidr]))
+        .collect();
+    spaces.sort();
+    spaces.dedup();
+
+    let mut added: Vec<Subnet> = Vec::new();
+    let mut conflicts = Vec::new();
+    for (name, cidr) in proposals {
+        let existing: Vec<&Subnet> = rows.iter().copied().chain(&added).collect();
+        if let Some(reason) = add_conflict(*cidr, &spaces, &existing, buffers) {
+            conflicts.push(AddConflict {
+                name: name.clone(),
+                cidr: *cidr,
+                reason,
+            });
+            continue;
+        }
+        let vnet_cidr = *spaces
+            .iter()
+            .find(|sp| sp.contains(cidr.lo()))
+            .expect("checked above");
+        added.push(Subnet {
+            vnet_cidr,
+            subnet_id: String::new(),
+            subnet_name: name.clone(),
+            subnet_cidr: Some(*cidr),
+            nsg: None,
+            delegations: Vec::new(),
+            nat_gateway: None,
+            load_balancers: Vec::new(),
+            ip_configurations_count: Some(0),
+            service_association_links: None,
+            source: SubnetSource::Planned,
+            ..template.clone()
+        });
+    }
+
+    let mut all = data.data.clone();
+    all.extend(added);
+    Ok((
+        Data {
+            count: all.len() as i32,
+            data: all,
+            ..data.clone()
+        },
+        conflicts,
+    ))
+}
+
+/// Render usage changes per VNet CIDR with the free ranges that are new
+/// (freed space after a reclaim, what is left of split ranges after an add).
+pub fn format_usage_changes(changes: &[UsageChange]) -> String {
+    if changes.is_empty() {
+        return "No VNet address space changes.".to_string();
+    }
+    let mut lines = Vec::new();
+    for c in changes {
+        lines.push(format!(
+            "VNET: '{}' {} [{}] - used {} -> {} addresses ({:.1}% -> {:.1}%), largest free range {} -> {}",
+            c.after.vnet_name,
+            c.after.vnet_cidr,
+            c.after.subscription_name,
+            c.before.used,
+            c.after.used,
+            c.before.utilisation_pct(),
+            c.after.utilisation_pct(),
+            c.before.largest_free(),
+            c.after.largest_free()
+        ));
+        for range in c.new_free_ranges() {
+            lines.push(format!("  FREE: {range}"));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_subnet(vnet: &str, name: &str, cidr: &str) -> Subnet {
+        Subnet {
+            subscription_name: "Sub".to_string(),
+            vnet_cidr: Ipv4::new("10.0.0.0/22").unwrap(),
+            ..Subnet::test(vnet, name, cidr)
+        }
+    }
+
+    fn hub() -> Data {
+        Data {
+            data: vec![
+                make_subnet("hub", "a", "10.0.0.0/24"),
+                make_subnet("hub", "b", "10.0.1.0/24"),
+                make_subnet("hub", "c", "10.0.2.0/25"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn usage_merges_adjacent_gap_blocks_into_ranges() {
+        let usage = space_usage(&hub(), &[]);
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].used, 640);
+        // 10.0.2.128/25 + 10.0.3.0/24 is one contiguous range.
+        assert_eq!(usage[0].free.len(), 1);
+        assert_eq!(usage[0].largest_free(), 384);
+        assert_eq!(
+            usage[0].free[0].to_string(),
+            "10.0.2.128 - 10.0.3.255 (384 addresses: 10.0.2.128/25, 10.0.3.0/24)"
+        );
+    }
+
+    #[test]
+    fn reclaiming_subnets_shows_the_contiguous_space_freed() {
+        let data = hub();
+        let (after, removed) =
+            remove_subnets(&data, &["hub/B".to_string(), "10.0.2.0/25".to_string()]).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(after.data.len(), 1);
+
+        let changes = compare_usage(&space_usage(&data, &[]), &space_usage(&after, &[]));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].after.used, 256);
+        let freed = changes[0].new_free_ranges();
+        assert_eq!(freed.len(), 1);
+        assert_eq!(
+            freed[0].blocks(),
+            [
+                Ipv4::new("10.0.1.0/24").unwrap(),
+                Ipv4::new("10.0.2.0/23").unwrap()
+            ]
+        );
+        assert!(format_usage_changes(&changes)
+            .contains("(62.5% -> 25.0%), largest free range 384 -> 768"));
+
+        let (empty, _) = remove_subnets(
+            &data,
+            &["a".to_string(), "hub/b".to_string(), "c".to_string()],
+        )
+        .unwrap();
+        let changes = compare_usage(&space_usage(&data, &[]), &space_usage(&empty, &[]));
+        assert_eq!(changes[0].after.largest_free(), 1024);
+
+        assert!(remove_subnets(&data, &["nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn planned_subnets_are_checked_and_added() {
+        let data = hub();
+        let cidr = |c: &str| Ipv4::new(c).unwrap();
+        let proposals = vec![
+            ("web".to_string(), cidr("10.0.3.0/25")),
+            ("clash".to_string(), cidr("10.0.1.128/25")),
+            ("outside".to_string(), cidr("10.9.0.0/24")),
+            ("unaligned".to_string(), cidr("10.0.2.200/26")),
+            ("again".to_string(), cidr("10.0.3.64/26")),
+            ("db".to_string(), cidr("10.0.3.128/26")),
+        ];
+        let (after, conflicts) = add_subnets(&data, "HUB", None, &proposals, &[]).unwrap();
+        let reasons: Vec<(&str, &str)> = conflicts
+            .iter()
+            .map(|c| (c.name.as_str(), c.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                ("clash", "overlaps subnet 'b' 10.0.1.0/24"),
+                ("outside", "outside the VNet address space (10.0.0.0/22)"),
+                ("unaligned", "not aligned, network address is 10.0.2.192"),
+                ("again", "overlaps subnet 'web' 10.0.3.0/25"),
+            ]
+        );
+        assert_eq!(after.data.len(), 5);
+
+        let changes = compare_usage(&space_usage(&data, &[]), &space_usage(&after, &[]));
+        assert_eq!(changes[0].after.used, 640 + 128 + 64);
+        assert!(add_subnets(&data, "nope", None, &proposals, &[]).is_err());
+    }
+
+    #[test]
+    fn buffers_are_not_free_and_cannot_be_planned() {
+        let data = hub();
+        let buffers = [Ipv4::new("10.0.3.0/25").unwrap()];
+        let usage = space_usage(&data, &buffers);
+        assert_eq!(usage[0].largest_free(), 128);
+        assert_eq!(
+            usage[0].buffered[0].to_string(),
+            "10.0.3.0 - 10.0.3.127 (128 addresses: 10.0.3.0/25)"
+        );
+
+        let proposals = vec![("web".to_string(), Ipv4::new("10.0.3.0/26").unwrap())];
+        let (_, conflicts) = add_subnets(&data, "hub", None, &proposals, &buffers).unwrap();
+        assert_eq!(conflicts[0].reason, "overlaps buffer 10.0.3.0/25");
+
+        // Reclaiming everything frees the VNet CIDR except the buffer.
+        let (empty, _) = remove_subnets(
+            &data,
+            &["a".to_string(), "hub/b".to_string(), "c".to_string()],
+        )
+        .unwrap();
+        let changes = compare_usage(&usage, &space_usage(&empty, &buffers));
+        let free: u64 = changes[0].after.free.iter().map(FreeRange::size).sum();
+        assert_eq!(free, 1024 - 128);
+    }
+}
@@ -0,0 +1,126 @@
+//! Route summarization of VNet address space for on-premises advertisement.
+//!
+//! ExpressRoute and VPN gateways accept a limited number of prefixes, so the
+//! Azure address space is advertised as few blocks as possible. Exact
+//! summarization merges overlapping, nested and adjacent prefixes into the
+//! fewest aligned blocks covering exactly the same addresses. With a prefix
+//! budget, neighbouring blocks are further merged into their common supernet,
+//! cheapest first, which also advertises addresses Azure does not use.
+
+use super::allocator::cover_range;
+use crate::models::Ipv4;
+
+/// Summarized prefix list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSummary {
+    pub prefixes: Vec<Ipv4>,
+    /// Addresses in the input prefixes.
+    pub covered: u64,
+    /// Addresses advertised beyond the input prefixes (0 for exact summaries).
+    pub extra: u64,
+}
+
+/// Smallest aligned block covering `[lo, hi)`.
+fn supernet(lo: u64, hi: u64) -> Ipv4 {
+    let bits = 64 - (lo ^ (hi - 1)).leading_zeros();
+    let base = (lo >> bits) << bits;
+    Ipv4 {
+        addr: std::net::Ipv4Addr::from(base as u32),
+        mask: 32 - bits as u8,
+    }
+}
+
+/// Summarize `prefixes` into the fewest aligned blocks.
+///
+/// With `max_prefixes`, neighbouring blocks are merged into their supernet,
+/// each time picking the merge that adds the fewest extra addresses, until
+/// at most `max_prefixes` remain.
+pub fn summarize_prefixes(prefixes: &[Ipv4], max_prefixes: Option<usize>) -> RouteSummary {
+    let mut ranges: Vec<(u64, u64)> = prefixes.iter().map(Ipv4::range).collect();
+    ranges.sort();
+    let mut merged: Vec<(u64, u64)> = Vec::new();
+    for (lo, hi) in ranges {
+        match merged.last_mut() {
+            Some(last) if lo <= last.1 => last.1 = last.1.max(hi),
+            _ => merged.push((lo, hi)),
+        }
+    }
+    let covered: u64 = merged.iter().map(|(lo, hi)| hi - lo).sum();
+    let mut blocks: Vec<(u64, u64)> = merged
+        .iter()
+        .flat_map(|&(lo, hi)| cover_range(lo, hi))
+        .map(|c| c.range())
+        .collect();
+
+    let budget = max_prefixes.unwrap_or(usize::MAX).max(1);
+    while blocks.len() > budget {
+        // Supernet of neighbours i and i+1 absorbs every block inside it;
+        // its cost is the unused space it adds.
+        let (start, end, net) = (0..blocks.len() - 1)
+            .map(|i| {
+                let net = supernet(blocks[i].0, blocks[i + 1].1).range();
+                let first = blocks[..=i]
+                    .iter()
+                    .rposition(|b| b.0 < net.0)
+                    .map_or(0, |p| p + 1);
+                let last = i + 1 + blocks[i + 1..].iter().take_while(|b| b.1 <= net.1).count() - 1;
+                let inside: u64 = blocks[first..=last].iter().map(|(lo, hi)| hi - lo).sum();
+                (first, last, net, (net.1 - net.0) - inside)
+            })
+            .min_by_key(|&(_, _, net, cost)| (cost, net.0))
+            .map(|(first, last, net, _)| (first, last, net))
+            .expect("at least two blocks");
+        blocks.splice(start..=end, [net]);
+    }
+
+    let advertised: u64 = blocks.iter().map(|(lo, hi)| hi - lo).sum();
+    RouteSummary {
+        prefixes: blocks.iter().map(|&(lo, hi)| supernet(lo, hi)).collect(),
+        covered,
+        extra: advertised - covered,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidrs(list: &[&str]) -> Vec<Ipv4> {
+        list.iter().map(|c| Ipv4::new(c).unwrap()).collect()
+    }
+
+    fn strings(summary: &RouteSummary) -> Vec<String> {
+        summary.prefixes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn exact_summary_merges_adjacent_and_nested_prefixes() {
+        let summary = summarize_prefixes(
+            &cidrs(&[
+                "10.0.1.0/24",
+                "10.0.0.0/24",
+                "10.0.0.128/25",
+                "10.0.2.0/24",
+                "10.8.0.0/16",
+            ]),
+            None,
+        );
+        assert_eq!(
+            strings(&summary),
+            ["10.0.0.0/23", "10.0.2.0/24", "10.8.0.0/16"]
+        );
+        assert_eq!((summary.covered, summary.extra), (768 + 65536, 0));
+    }
+
+    #[test]
+    fn prefix_budget_merges_cheapest_neighbours_first() {
+        let input = cidrs(&["10.0.0.0/23", "10.0.2.0/24", "10.8.0.0/16"]);
+        let summary = summarize_prefixes(&input, Some(2));
+        assert_eq!(strings(&summary), ["10.0.0.0/22", "10.8.0.0/16"]);
+        assert_eq!(summary.extra, 256);
+
+        let summary = summarize_prefixes(&input, Some(1));
+        assert_eq!(strings(&summary), ["10.0.0.0/12"]);
+        assert_eq!(summary.extra, (1 << 20) - 65536 - 768);
+    }
+}
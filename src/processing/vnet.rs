@@ -3,8 +3,11 @@
 //! Groups subnets into their parent VNets for reporting.
 
 use crate::azure::Data;
-use crate::models::VnetList;
-use crate::processing::ExcludedSubnet;
+use crate::i18n::Lang;
+use crate::models::{Ipv4, Vnet, VnetList};
+use crate::output::format::thousands;
+use crate::output::{paint, Style};
+use crate::processing::{ExcludedSubnet, SubnetPrintRow, BUFFER};
 use std::collections::HashMap;
 use std::error::Error;
 
@@ -34,12 +37,135 @@ pub fn get_vnets(data: &Data) -> Result<VnetList<'_>, Box<dyn Error>> {
     Ok(vnets)
 }
 
+/// Address use of one prefix of a VNet's address space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixUsage {
+    pub cidr: Ipv4,
+    /// Subnets in this prefix.
+    pub subnets: usize,
+    /// Addresses taken by those subnets.
+    pub used: u64,
+}
+
+impl PrefixUsage {
+    /// Addresses in the prefix.
+    pub fn size(&self) -> u64 {
+        1u64 << (32 - u32::from(self.cidr.mask))
+    }
+
+    /// Addresses not taken by a subnet.
+    pub fn free(&self) -> u64 {
+        self.size().saturating_sub(self.used)
+    }
+}
+
+/// Usage of every prefix in `vnet`'s address space, including prefixes
+/// without subnets, in address order. Prefixes whose subnets were excluded
+/// as duplicates of another VNet are left out.
+pub fn prefix_usage(vnet: &Vnet<'_>, excluded: &[ExcludedSubnet]) -> Vec<PrefixUsage> {
+    let mut prefixes: Vec<Ipv4> = vnet.vnet_cidr.clone();
+    for s in &vnet.subnets {
+        for cidr in &s.vnet_address_space {
+            let dup = excluded.iter().any(|e| {
+                e.subnet.vnet_name == vnet.vnet_name
+                    && e.subnet.subscription_name == vnet.subscription_name
+                    && e.subnet.vnet_cidr == *cidr
+            });
+            if !dup && !prefixes.contains(cidr) {
+                prefixes.push(*cidr);
+            }
+        }
+    }
+    prefixes.sort();
+    prefixes
+        .into_iter()
+        .map(|cidr| {
+            let in_prefix = vnet.subnets.iter().filter(|s| s.vnet_cidr == cidr);
+            PrefixUsage {
+                cidr,
+                subnets: in_prefix.clone().count(),
+                used: in_prefix
+                    .filter_map(|s| s.subnet_cidr)
+                    .map(|c| 1u64 << (32 - u32::from(c.mask)))
+                    .sum(),
+            }
+        })
+        .collect()
+}
+
+/// Which VNets of the summary to show (`--only-vnet`, `--offset`, `--limit`).
+///
+/// The default shows every VNet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VnetPage {
+    /// Only show VNets with this name (case-insensitive).
+    pub only_vnet: Option<String>,
+    /// Number of VNets to skip, in summary order.
+    pub offset: usize,
+    /// Maximum number of VNets to show.
+    pub limit: Option<usize>,
+}
+
+impl VnetPage {
+    fn matches(&self, vnet_name: &str) -> bool {
+        self.only_vnet
+            .as_deref()
+            .is_none_or(|name| name.eq_ignore_ascii_case(vnet_name))
+    }
+
+    fn is_everything(&self) -> bool {
+        self.only_vnet.is_none() && self.offset == 0 && self.limit.is_none()
+    }
+
+    /// The subnet and gap `rows` of the selected VNets, for the table output.
+    ///
+    /// VNets are paged in summary order; space between VNets is only kept
+    /// when every VNet is shown.
+    pub fn rows(&self, rows: &[SubnetPrintRow]) -> Vec<SubnetPrintRow> {
+        use std::collections::HashSet;
+
+        if self.is_everything() {
+            return rows.to_vec();
+        }
+        let key = |r: &SubnetPrintRow| (r.subscription_name.clone(), r.vnet_name.clone());
+        let in_vnet = |r: &&SubnetPrintRow| r.gap != "-gap-" && r.gap != BUFFER;
+        let mut vnets: Vec<(String, String)> = rows
+            .iter()
+            .filter(in_vnet)
+            .filter(|r| self.matches(&r.vnet_name))
+            .map(key)
+            .collect();
+        vnets.sort();
+        vnets.dedup();
+        let shown: HashSet<(String, String)> = vnets
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        rows.iter()
+            .filter(in_vnet)
+            .filter(|r| shown.contains(&key(r)))
+            .cloned()
+            .collect()
+    }
+}
+
 /// Render VNet summary as a string, grouping conflict pairs.
 ///
 /// Winners are shown in green. Excluded VNets are shown beneath their winner
 /// with a `[DUP of <winner>]` reference.
 pub fn format_vnets(vnets: &VnetList<'_>, excluded: &[ExcludedSubnet]) -> String {
-    use colored::Colorize;
+    format_vnets_page(vnets, excluded, &VnetPage::default(), Lang::En)
+}
+
+/// Like [`format_vnets`], but only the VNets selected by `page`, followed by
+/// a line saying which part of the summary is shown, in language `lang`.
+pub fn format_vnets_page(
+    vnets: &VnetList<'_>,
+    excluded: &[ExcludedSubnet],
+    page: &VnetPage,
+    lang: Lang,
+) -> String {
     use std::collections::HashSet;
 
     // Build: winner_vnet_name → excl_vnet_name → (subscription_name, CIDRs, count)
@@ -63,57 +189,126 @@ pub fn format_vnets(vnets: &VnetList<'_>, excluded: &[ExcludedSubnet]) -> String
     // All vnets in the list are active (no excluded subnets in data anymore)
     let mut active_vnets: Vec<&crate::models::Vnet<'_>> = vnets.vnets.values().collect();
     active_vnets.sort_by_key(|v| (v.subscription_name, v.vnet_name));
+    active_vnets.retain(|v| page.matches(v.vnet_name));
+    let matched = active_vnets.len();
+    let shown: Vec<_> = active_vnets
+        .into_iter()
+        .skip(page.offset)
+        .take(page.limit.unwrap_or(usize::MAX))
+        .collect();
 
     let mut lines = Vec::new();
 
-    for vnet in &active_vnets {
-        let cidrs = vnet
-            .vnet_cidr
+    for vnet in &shown {
+        let prefixes = prefix_usage(vnet, excluded);
+        let cidrs = prefixes
             .iter()
-            .map(|c| c.to_string())
+            .map(|p| p.cidr.to_string())
             .collect::<Vec<_>>()
             .join(", ");
         let subnet_count = vnet.subnets.len();
 
-        let line = format!(
+        let mut line = lang.fill(
             "VNET: '{}' '{}' - {} [{} subnet(s)]",
-            vnet.vnet_name, vnet.subscription_name, cidrs, subnet_count
+            &[
+                &vnet.vnet_name,
+                &vnet.subscription_name,
+                &cidrs,
+                &subnet_count,
+            ],
         );
+        if let Some(plan) = vnet.ddos_protection_plan {
+            let plan = plan.rsplit('/').next().unwrap_or(plan);
+            line.push_str(&lang.fill(" [DDoS plan '{}']", &[&plan]));
+        }
+        if let Some(enforcement) = vnet.encryption {
+            line.push_str(&lang.fill(" [encryption: {}]", &[&enforcement]));
+        }
+
+        // Per-prefix free space, for VNets with more than one prefix.
+        let prefix_lines: Vec<String> = if prefixes.len() > 1 {
+            prefixes
+                .iter()
+                .map(|p| {
+                    lang.fill(
+                        "  PREFIX: {} - {} of {} addresses free [{} subnet(s)]",
+                        &[
+                            &p.cidr,
+                            &thousands(p.free()),
+                            &thousands(p.size()),
+                            &p.subnets,
+                        ],
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
 
         if winner_names.contains(vnet.vnet_name) {
-            lines.push(line.green().to_string());
+            lines.push(paint(line, Style::Winner));
+            lines.extend(prefix_lines);
             if let Some(excl_map) = excluded_by_winner.get(vnet.vnet_name) {
                 let mut excl_names: Vec<&String> = excl_map.keys().collect();
                 excl_names.sort();
                 for excl_name in excl_names {
                     let (sub_name, excl_cidrs, count) = &excl_map[excl_name];
-                    lines.push(
-                        format!(
+                    lines.push(paint(
+                        lang.fill(
                             "  EXCL: '{}' '{}' - {} [{} subnet(s)] [DUP of '{}']",
-                            excl_name,
-                            sub_name,
-                            excl_cidrs.join(", "),
-                            count,
-                            vnet.vnet_name
-                        )
-                        .red()
-                        .to_string(),
-                    );
+                            &[
+                                excl_name,
+                                sub_name,
+                                &excl_cidrs.join(", "),
+                                count,
+                                &vnet.vnet_name,
+                            ],
+                        ),
+                        Style::Duplicate,
+                    ));
                 }
             }
         } else {
             lines.push(line);
+            lines.extend(prefix_lines);
+        }
+    }
+
+    if !page.is_everything() {
+        let first = page.offset.min(matched);
+        let last = first + shown.len();
+        let mut footer = if shown.is_empty() {
+            lang.fill("-- no VNets shown ({} match) --", &[&matched])
+        } else {
+            lang.fill("-- VNets {}-{} of {} --", &[&(first + 1), &last, &matched])
+        };
+        if last < matched {
+            footer.push_str(&lang.fill(" next page: --offset {}", &[&last]));
         }
+        lines.push(footer);
     }
 
     lines.join("\n")
 }
 
-/// Print VNet summary to stdout.
+/// Print the VNets selected by `page` of the VNet summary to stdout.
 pub fn print_vnets(
     vnets: &VnetList<'_>,
     excluded: &[ExcludedSubnet],
+    page: &VnetPage,
+    lang: Lang,
 ) -> Result<(), Box<dyn Error>> {
+    println!("{}", vnet_summary(vnets, excluded, page, lang));
+    Ok(())
+}
+
+/// The VNet summary [`print_vnets`] prints, after logging the VNet counts.
+pub fn vnet_summary(
+    vnets: &VnetList<'_>,
+    excluded: &[ExcludedSubnet],
+    page: &VnetPage,
+    lang: Lang,
+) -> String {
     let total = vnets.vnets.len();
     let excluded_vnet_count = {
         use std::collections::HashSet;
@@ -129,10 +324,7 @@ pub fn print_vnets(
         excluded_vnet_count,
     );
 
-    let output = format_vnets(vnets, excluded);
-    println!("{output}");
-
-    Ok(())
+    format_vnets_page(vnets, excluded, page, lang)
 }
 
 #[cfg(test)]
@@ -142,14 +334,12 @@ mod tests {
     use crate::models::{Ipv4, Subnet};
 
     fn make_subnet(vnet_name: &str, sub_name: &str, vnet_cidr: &str, subnet_cidr: &str) -> Subnet {
-        let mut s: Subnet = Default::default();
-        s.vnet_name = vnet_name.to_string();
-        s.subscription_name = sub_name.to_string();
-        s.subscription_id = "sub-id".to_string();
-        s.vnet_cidr = Ipv4::new(vnet_cidr).unwrap();
-        s.subnet_cidr = Some(Ipv4::new(subnet_cidr).unwrap());
-        s.subnet_name = format!("{vnet_name}-subnet");
-        s
+        Subnet {
+            subscription_name: sub_name.to_string(),
+            subscription_id: "sub-id".to_string(),
+            vnet_cidr: Ipv4::new(vnet_cidr).unwrap(),
+            ..Subnet::test(vnet_name, &format!("{vnet_name}-subnet"), subnet_cidr)
+        }
     }
 
     fn make_data(subnets: Vec<Subnet>) -> Data {
@@ -219,6 +409,124 @@ mod tests {
         );
     }
 
+    #[test]
+    fn page_selects_vnets_and_says_where_to_continue() {
+        let active = make_data(
+            ["vnet-a", "vnet-b", "vnet-c"]
+                .iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    make_subnet(
+                        name,
+                        "Sub",
+                        &format!("10.{i}.0.0/16"),
+                        &format!("10.{i}.1.0/24"),
+                    )
+                })
+                .collect(),
+        );
+        let vnets = get_vnets(&active).unwrap();
+
+        let page = VnetPage {
+            offset: 1,
+            limit: Some(1),
+            ..Default::default()
+        };
+        let plain = strip_ansi(&format_vnets_page(&vnets, &[], &page, Lang::En));
+        assert!(!plain.contains("vnet-a") && plain.contains("vnet-b") && !plain.contains("vnet-c"));
+        assert!(
+            plain.ends_with("-- VNets 2-2 of 3 -- next page: --offset 2"),
+            "{plain}"
+        );
+
+        let only = VnetPage {
+            only_vnet: Some("VNET-C".to_string()),
+            ..Default::default()
+        };
+        let plain = strip_ansi(&format_vnets_page(&vnets, &[], &only, Lang::En));
+        assert!(plain.contains("vnet-c") && !plain.contains("vnet-a"));
+        assert!(plain.ends_with("-- VNets 1-1 of 1 --"), "{plain}");
+
+        assert!(!format_vnets(&vnets, &[]).contains("--"));
+    }
+
+    #[test]
+    fn only_vnet_selects_the_table_rows() {
+        let subnets = [
+            make_subnet("vnet-a", "Sub", "10.1.0.0/24", "10.1.0.0/24"),
+            make_subnet("vnet-b", "Sub", "10.2.0.0/24", "10.2.0.0/25"),
+        ];
+        let rows = crate::output::build_rows(&subnets, &[], 25, &[]);
+
+        let only = VnetPage {
+            only_vnet: Some("VNET-B".to_string()),
+            ..Default::default()
+        };
+        let shown = only.rows(&rows);
+        assert!(!shown.is_empty());
+        assert!(shown.iter().all(|r| r.vnet_name == "vnet-b"));
+        assert_eq!(VnetPage::default().rows(&rows).len(), rows.len());
+    }
+
+    #[test]
+    fn offset_and_limit_page_the_table_rows_in_summary_order() {
+        let subnets = [
+            make_subnet("vnet-c", "Sub", "10.3.0.0/24", "10.3.0.0/24"),
+            make_subnet("vnet-a", "Sub", "10.1.0.0/24", "10.1.0.0/24"),
+            make_subnet("vnet-b", "Sub", "10.2.0.0/24", "10.2.0.0/24"),
+        ];
+        let rows = crate::output::build_rows(&subnets, &[], 24, &[]);
+
+        let page = VnetPage {
+            offset: 1,
+            limit: Some(1),
+            ..Default::default()
+        };
+        let shown = page.rows(&rows);
+        assert!(!shown.is_empty());
+        assert!(shown.iter().all(|r| r.vnet_name == "vnet-b"));
+    }
+
+    #[test]
+    fn ddos_plan_and_encryption_are_shown_when_set() {
+        let mut protected = make_subnet("hub", "Prod", "10.1.0.0/16", "10.1.1.0/24");
+        protected.ddos_protection_plan = Some(
+            "/subscriptions/s/resourceGroups/rg/providers/Microsoft.Network/ddosProtectionPlans/ddos-prod"
+                .to_string(),
+        );
+        protected.encryption = Some("DropUnencrypted".to_string());
+        let data = make_data(vec![
+            protected,
+            make_subnet("spoke", "Prod", "10.2.0.0/16", "10.2.1.0/24"),
+        ]);
+        let vnets = get_vnets(&data).unwrap();
+        let plain = strip_ansi(&format_vnets(&vnets, &[]));
+        assert_eq!(
+            plain.lines().collect::<Vec<_>>(),
+            [
+                "VNET: 'hub' 'Prod' - 10.1.0.0/16 [1 subnet(s)] [DDoS plan 'ddos-prod'] [encryption: DropUnencrypted]",
+                "VNET: 'spoke' 'Prod' - 10.2.0.0/16 [1 subnet(s)]",
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_prefix_vnets_report_free_space_per_prefix() {
+        let mut a = make_subnet("hub", "Prod", "10.1.0.0/24", "10.1.0.0/26");
+        a.vnet_address_space = vec![a.vnet_cidr, Ipv4::new("10.9.0.0/24").unwrap()];
+        let data = make_data(vec![a]);
+        let vnets = get_vnets(&data).unwrap();
+        let plain = strip_ansi(&format_vnets(&vnets, &[]));
+        assert_eq!(
+            plain.lines().collect::<Vec<_>>(),
+            [
+                "VNET: 'hub' 'Prod' - 10.1.0.0/24, 10.9.0.0/24 [1 subnet(s)]",
+                "  PREFIX: 10.1.0.0/24 - 192 of 256 addresses free [1 subnet(s)]",
+                "  PREFIX: 10.9.0.0/24 - 256 of 256 addresses free [0 subnet(s)]",
+            ]
+        );
+    }
+
     /// Strip ANSI escape codes from a string for plain-text assertions.
     fn strip_ansi(s: &str) -> String {
         let mut result = String::new();
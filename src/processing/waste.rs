@@ -0,0 +1,230 @@
+//! Wasted address space in oversized subnets.
+//!
+//! A subnet sized for far more IPs than it has ever used ties up space other
+//! workloads could take. Each subnet's peak `ip_configurations_count` over the
+//! snapshot history is compared with the smallest prefix that would still
+//! hold it (plus headroom); the difference in addresses is the estimated
+//! waste, totalled per VNet and per subscription.
+
+use super::empty::subnet_key;
+use super::role::subnet_role;
+use crate::azure::Data;
+use crate::models::{num_az_hosts, Ipv4};
+use crate::output::format::thousands;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+/// Smallest subnet Azure allows.
+const MIN_AZURE_MASK: u8 = 29;
+
+/// A subnet larger than its peak usage needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OversizedSubnet {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub subnet_name: String,
+    pub subnet_cidr: Ipv4,
+    /// Highest IP configuration count today or in any snapshot.
+    pub peak: u64,
+    /// Oldest snapshot date the peak was taken over (today without history).
+    pub since: NaiveDate,
+    /// Prefix length that holds the peak plus headroom.
+    pub right_size_mask: u8,
+}
+
+impl OversizedSubnet {
+    /// Addresses that right-sizing would free.
+    pub fn wasted(&self) -> u64 {
+        (1u64 << (32 - u32::from(self.subnet_cidr.mask)))
+            - (1u64 << (32 - u32::from(self.right_size_mask)))
+    }
+}
+
+/// Longest prefix (smallest subnet) with at least `required` Azure hosts.
+fn right_size_mask(required: u64) -> u8 {
+    (0..=MIN_AZURE_MASK)
+        .rev()
+        .find(|&mask| num_az_hosts(mask).is_ok_and(|hosts| hosts >= required))
+        .unwrap_or(0)
+}
+
+/// Find subnets that could shrink by at least one prefix length while still
+/// holding their peak usage plus `headroom_pct` percent, most waste first.
+///
+/// `history` yields earlier snapshots (any order). Subnets with a well-known
+/// role (gateway, firewall, ...) are skipped; their size is set by the service.
+pub fn find_oversized_subnets(
+    current: &Data,
+    today: NaiveDate,
+    history: impl IntoIterator<Item = (NaiveDate, Data)>,
+    headroom_pct: u64,
+) -> Vec<OversizedSubnet> {
+    let mut peaks: HashMap<String, (u64, NaiveDate)> = current
+        .data
+        .iter()
+        .map(|s| {
+            let used = u64::from(s.ip_configurations_count.unwrap_or(0));
+            (subnet_key(s), (used, today))
+        })
+        .collect();
+    for (date, snapshot) in history {
+        if date >= today {
+            continue;
+        }
+        for s in &snapshot.data {
+            if let Some((peak, since)) = peaks.get_mut(&subnet_key(s)) {
+                *peak = (*peak).max(u64::from(s.ip_configurations_count.unwrap_or(0)));
+                *since = (*since).min(date);
+            }
+        }
+    }
+
+    let mut oversized: Vec<OversizedSubnet> = current
+        .data
+        .iter()
+        .filter(|s| subnet_role(s).is_none())
+        .filter_map(|s| {
+            let cidr = s.subnet_cidr?;
+            let (peak, since) = peaks[&subnet_key(s)];
+            // Saturates on absurd headroom: no size fits, so nothing shrinks.
+            let required = peak
+                .saturating_mul(headroom_pct.saturating_add(100))
+                .div_ceil(100);
+            let mask = right_size_mask(required);
+            (mask > cidr.mask).then(|| OversizedSubnet {
+                subscription_name: s.subscription_name.clone(),
+                vnet_name: s.vnet_name.clone(),
+                subnet_name: s.subnet_name.clone(),
+                subnet_cidr: cidr,
+                peak,
+                since,
+                right_size_mask: mask,
+            })
+        })
+        .collect();
+    oversized.sort_by(|a, b| {
+        b.wasted()
+            .cmp(&a.wasted())
+            .then_with(|| (&a.vnet_name, &a.subnet_name).cmp(&(&b.vnet_name, &b.subnet_name)))
+    });
+    oversized
+}
+
+/// Total wasted addresses per `(subscription name, VNet name)`.
+pub fn waste_by_vnet(oversized: &[OversizedSubnet]) -> BTreeMap<(String, String), u64> {
+    let mut totals = BTreeMap::new();
+    for o in oversized {
+        *totals
+            .entry((o.subscription_name.clone(), o.vnet_name.clone()))
+            .or_insert(0) += o.wasted();
+    }
+    totals
+}
+
+/// Total wasted addresses per subscription name.
+pub fn waste_by_subscription(oversized: &[OversizedSubnet]) -> BTreeMap<String, u64> {
+    let mut totals = BTreeMap::new();
+    for o in oversized {
+        *totals.entry(o.subscription_name.clone()).or_insert(0) += o.wasted();
+    }
+    totals
+}
+
+/// Render oversized subnets followed by the waste per VNet and subscription.
+pub fn format_oversized_subnets(oversized: &[OversizedSubnet]) -> String {
+    if oversized.is_empty() {
+        return "No oversized subnets found.".to_string();
+    }
+    let mut lines: Vec<String> = oversized
+        .iter()
+        .map(|o| {
+            format!(
+                "OVERSIZED: '{}/{}' {} - peak {} IPs since {}, fits a /{} ({} addresses wasted)",
+                o.vnet_name,
+                o.subnet_name,
+                o.subnet_cidr,
+                o.peak,
+                o.since,
+                o.right_size_mask,
+                o.wasted()
+            )
+        })
+        .collect();
+    for ((sub, vnet), total) in waste_by_vnet(oversized) {
+        lines.push(format!(
+            "WASTED: '{vnet}' in '{sub}' - {} addresses",
+            thousands(total)
+        ));
+    }
+    for (sub, total) in waste_by_subscription(oversized) {
+        lines.push(format!(
+            "WASTED: subscription '{sub}' - {} addresses",
+            thousands(total)
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Subnet;
+
+    fn make_subnet(vnet: &str, name: &str, cidr: &str, in_use: u32) -> Subnet {
+        Subnet {
+            subscription_name: "Sub".to_string(),
+            ip_configurations_count: Some(in_use),
+            ..Subnet::test(vnet, name, cidr)
+        }
+    }
+
+    fn make_data(subnets: Vec<Subnet>) -> Data {
+        Data {
+            data: subnets,
+            ..Default::default()
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap()
+    }
+
+    #[test]
+    fn waste_is_measured_against_peak_usage_with_headroom() {
+        let current = make_data(vec![
+            make_subnet("spoke", "app", "10.0.0.0/22", 10),
+            make_subnet("spoke", "busy", "10.0.4.0/24", 200),
+            make_subnet("hub", "GatewaySubnet", "10.1.0.0/24", 2),
+            make_subnet("hub", "mgmt", "10.1.1.0/24", 0),
+        ]);
+        let history = vec![(
+            day(1),
+            make_data(vec![make_subnet("spoke", "app", "10.0.0.0/22", 40)]),
+        )];
+        let oversized = find_oversized_subnets(&current, day(15), history, 20);
+
+        // app: peak 40 + 20 % = 48 hosts -> /26 (59 hosts); 1024 - 64 wasted.
+        let names: Vec<(&str, u8, u64)> = oversized
+            .iter()
+            .map(|o| (o.subnet_name.as_str(), o.right_size_mask, o.wasted()))
+            .collect();
+        assert_eq!(names, [("app", 26, 960), ("mgmt", 29, 248)]);
+        assert_eq!(oversized[0].since, day(1));
+
+        let out = format_oversized_subnets(&oversized);
+        assert!(
+            out.contains("WASTED: 'spoke' in 'Sub' - 960 addresses"),
+            "{out}"
+        );
+        assert!(
+            out.ends_with("WASTED: subscription 'Sub' - 1,208 addresses"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn huge_headroom_does_not_overflow() {
+        let current = make_data(vec![make_subnet("spoke", "app", "10.0.0.0/22", 10)]);
+        assert!(find_oversized_subnets(&current, day(15), vec![], u64::MAX).is_empty());
+    }
+}
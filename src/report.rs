@@ -0,0 +1,453 @@
+//! Report findings.
+//!
+//! A [`Report`] collects actionable findings about the subnet estate (missing
+//! NSGs, NSGs shared across VNets, subnets that should be resized), each with
+//! a [`Severity`]. Output writers such as the remediation script render from
+//! it instead of re-inspecting subnets. VNet-level findings from config
+//! policies (DDoS protection, encryption, allowed locations) are added with
+//! [`Report::add_policy_findings`]. Embedding applications register
+//! [`RowProcessor`]s on a report with [`Report::add_processor`] to transform
+//! the subnet rows or add computed columns.
+
+use crate::azure::{Data, SubscriptionData};
+use crate::models::{cut_addr, num_az_hosts, Ipv4, Subnet};
+use crate::processing::{
+    subnet_role, vnet_policy_violations, NsgIndex, PolicyRequirement, SubnetPrintRow, VnetPolicy,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+/// Recommend a resize once this percentage of Azure usable hosts is in use.
+pub const RESIZE_UTILISATION_PCT: u64 = 80;
+
+/// How serious a finding is; `check` fails on findings at or above a level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warn,
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// What a finding is about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FindingKind {
+    /// Subnet has no Network Security Group attached.
+    MissingNsg,
+    /// Subnet is nearly full; `suggested` is the next larger aligned prefix,
+    /// which is free in the VNet.
+    ResizeRecommended { suggested: Ipv4 },
+    /// VNet in scope of `policy` has no DDoS Network Protection plan.
+    DdosProtectionRequired { policy: String },
+    /// VNet in scope of `policy` does not have encryption enabled.
+    EncryptionRequired { policy: String },
+    /// VNet in scope of `policy` is in `location`, outside its allowed
+    /// locations.
+    LocationMismatch { policy: String, location: String },
+    /// Subnet's NSG also protects subnets in other VNets; `vnets` counts all
+    /// of them.
+    SharedNsg { nsg: String, vnets: usize },
+}
+
+/// Every [`FindingKind::id`].
+pub const FINDING_IDS: &[&str] = &[
+    "missing_nsg",
+    "resize_recommended",
+    "ddos_protection_required",
+    "encryption_required",
+    "location_mismatch",
+    "shared_nsg",
+];
+
+impl FindingKind {
+    /// Stable identifier used in output and suppression files.
+    pub fn id(&self) -> &'static str {
+        match self {
+            FindingKind::MissingNsg => "missing_nsg",
+            FindingKind::ResizeRecommended { .. } => "resize_recommended",
+            FindingKind::DdosProtectionRequired { .. } => "ddos_protection_required",
+            FindingKind::EncryptionRequired { .. } => "encryption_required",
+            FindingKind::LocationMismatch { .. } => "location_mismatch",
+            FindingKind::SharedNsg { .. } => "shared_nsg",
+        }
+    }
+
+    /// Default severity of this kind of finding.
+    pub fn severity(&self) -> Severity {
+        match self {
+            FindingKind::MissingNsg => Severity::Error,
+            FindingKind::ResizeRecommended { .. } => Severity::Warn,
+            FindingKind::DdosProtectionRequired { .. }
+            | FindingKind::EncryptionRequired { .. }
+            | FindingKind::LocationMismatch { .. } => Severity::Error,
+            FindingKind::SharedNsg { .. } => Severity::Info,
+        }
+    }
+}
+
+/// One finding about one subnet, or about a VNet for policy findings.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    #[serde(flatten)]
+    pub kind: FindingKind,
+    pub severity: Severity,
+    pub subscription_id: String,
+    pub subscription_name: String,
+    pub vnet_name: String,
+    /// Subnet name (empty for VNet-level findings).
+    pub subnet_name: String,
+    /// Full ARM resource ID of the subnet, or of the VNet for VNet-level
+    /// findings (empty for old caches).
+    pub subnet_id: String,
+    pub subnet_cidr: Option<Ipv4>,
+    /// Human-readable explanation.
+    pub message: String,
+}
+
+impl Finding {
+    fn new(kind: FindingKind, subnet: &Subnet, message: String) -> Self {
+        Finding {
+            severity: kind.severity(),
+            kind,
+            subscription_id: subnet.subscription_id.clone(),
+            subscription_name: subnet.subscription_name.clone(),
+            vnet_name: subnet.vnet_name.clone(),
+            subnet_name: subnet.subnet_name.clone(),
+            subnet_id: subnet.subnet_id.clone(),
+            subnet_cidr: subnet.subnet_cidr,
+            message,
+        }
+    }
+
+    /// Finding about the VNet that `subnet` belongs to.
+    fn for_vnet(kind: FindingKind, subnet: &Subnet, message: String) -> Self {
+        Finding {
+            subnet_name: String::new(),
+            subnet_id: subnet.vnet_id.clone(),
+            subnet_cidr: None,
+            ..Finding::new(kind, subnet, message)
+        }
+    }
+}
+
+/// Custom transformation of the subnet and gap rows, registered by an
+/// embedding application with [`Report::add_processor`].
+///
+/// Processors run in registration order after the rows are built (and host
+/// counts applied), before `--min-free` / `--only-gaps-larger-than`, so every
+/// writer (CSV, table, JSON, templates) sees their output. They are called
+/// once with all rows, also with `--stream`, which prints the rows only
+/// after every processor has run. Computed columns go in
+/// [`SubnetPrintRow::extra`] and are appended to the CSV by header.
+pub trait RowProcessor: Send + Sync {
+    /// Name used in logs and errors.
+    fn name(&self) -> &str;
+    fn process(&self, rows: Vec<SubnetPrintRow>) -> Result<Vec<SubnetPrintRow>, Box<dyn Error>>;
+}
+
+/// Findings for one run of the report.
+#[derive(Clone, Default, Serialize)]
+pub struct Report {
+    pub generated_at: DateTime<Utc>,
+    pub findings: Vec<Finding>,
+    /// Registered with [`Report::add_processor`]; not part of the output.
+    #[serde(skip)]
+    pub processors: Vec<Arc<dyn RowProcessor>>,
+}
+
+impl fmt::Debug for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let processors: Vec<&str> = self.processors.iter().map(|p| p.name()).collect();
+        f.debug_struct("Report")
+            .field("generated_at", &self.generated_at)
+            .field("findings", &self.findings)
+            .field("processors", &processors)
+            .finish()
+    }
+}
+
+impl Report {
+    /// Register `processor` to run after the ones already registered.
+    pub fn add_processor(&mut self, processor: Box<dyn RowProcessor>) -> &mut Self {
+        self.processors.push(Arc::from(processor));
+        self
+    }
+
+    /// Run every registered processor over `rows`.
+    pub fn apply_processors(
+        &self,
+        rows: Vec<SubnetPrintRow>,
+    ) -> Result<Vec<SubnetPrintRow>, Box<dyn Error>> {
+        self.processors.iter().try_fold(rows, |rows, p| {
+            log::info!("Applying row processor '{}'", p.name());
+            p.process(rows)
+                .map_err(|e| format!("Row processor '{}' failed: {e}", p.name()).into())
+        })
+    }
+
+    /// Inspect every subnet in `data` and collect findings. Reserved, on-prem
+    /// and VPN client ranges only stand in for address space and get none.
+    pub fn from_subnets(data: &Data) -> Report {
+        let mut findings = Vec::new();
+        let nsgs = NsgIndex::new(data);
+        for subnet in data.data.iter().filter(|s| s.source.is_azure_resource()) {
+            let nsg_exempt = subnet_role(subnet).is_some_and(|r| r.nsg_exempt());
+            if subnet.nsg.is_none() && !nsg_exempt {
+                findings.push(Finding::new(
+                    FindingKind::MissingNsg,
+                    subnet,
+                    format!("Subnet '{}' has no NSG attached", subnet.subnet_name),
+                ));
+            }
+            if let Some(nsg) = subnet.nsg.as_deref() {
+                let vnets = nsgs.vnet_count(nsg);
+                if vnets > 1 {
+                    let nsg = nsg.rsplit('/').next().unwrap_or(nsg).to_string();
+                    findings.push(Finding::new(
+                        FindingKind::SharedNsg {
+                            nsg: nsg.clone(),
+                            vnets,
+                        },
+                        subnet,
+                        format!(
+                            "Subnet '{}' shares NSG '{nsg}' with subnets in {} other VNet(s)",
+                            subnet.subnet_name,
+                            vnets - 1
+                        ),
+                    ));
+                }
+            }
+            if let Some(finding) = resize_finding(subnet, data) {
+                findings.push(finding);
+            }
+        }
+        Report {
+            generated_at: Utc::now(),
+            findings,
+            processors: Vec::new(),
+        }
+    }
+
+    /// Add a finding for every VNet in `data` that breaks one of `policies`;
+    /// `subscriptions` resolves management group scopes.
+    pub fn add_policy_findings(
+        &mut self,
+        data: &Data,
+        subscriptions: &SubscriptionData,
+        policies: &[VnetPolicy],
+    ) -> Result<(), Box<dyn Error>> {
+        for v in vnet_policy_violations(data, subscriptions, policies)? {
+            let policy = v.policy;
+            let (kind, message) = match v.requirement {
+                PolicyRequirement::DdosProtection => (
+                    FindingKind::DdosProtectionRequired {
+                        policy: policy.clone(),
+                    },
+                    format!(
+                        "VNet '{}' has no DDoS protection plan (policy '{policy}')",
+                        v.vnet.vnet_name
+                    ),
+                ),
+                PolicyRequirement::Encryption => (
+                    FindingKind::EncryptionRequired {
+                        policy: policy.clone(),
+                    },
+                    format!(
+                        "VNet '{}' does not have encryption enabled (policy '{policy}')",
+                        v.vnet.vnet_name
+                    ),
+                ),
+                PolicyRequirement::Location => (
+                    FindingKind::LocationMismatch {
+                        policy: policy.clone(),
+                        location: v.vnet.location.clone(),
+                    },
+                    format!(
+                        "VNet '{}' is in '{}', outside the locations allowed by policy '{policy}'",
+                        v.vnet.vnet_name, v.vnet.location
+                    ),
+                ),
+            };
+            self.findings.push(Finding::for_vnet(kind, v.vnet, message));
+        }
+        Ok(())
+    }
+}
+
+/// Recommend doubling a nearly-full subnet when the adjacent block is free.
+fn resize_finding(subnet: &Subnet, data: &Data) -> Option<Finding> {
+    let cidr = subnet.subnet_cidr?;
+    let used = u64::from(subnet.ip_configurations_count?);
+    let hosts = num_az_hosts(cidr.mask).ok().filter(|&h| h > 0)?;
+    if used * 100 < hosts * RESIZE_UTILISATION_PCT || cidr.mask == 0 {
+        return None;
+    }
+
+    let mask = cidr.mask - 1;
+    let suggested = Ipv4 {
+        addr: cut_addr(cidr.addr, mask).ok()?,
+        mask,
+    };
+    let fits_vnet = subnet.vnet_cidr.mask <= mask && subnet.vnet_cidr.contains(suggested.lo());
+    let sibling_free = !data.data.iter().any(|other| {
+        other.vnet_name == subnet.vnet_name
+            && other.subscription_id == subnet.subscription_id
+            && other
+                .subnet_cidr
+                .is_some_and(|o| o != cidr && o.lo() <= suggested.hi() && suggested.lo() <= o.hi())
+    });
+    if !(fits_vnet && sibling_free) {
+        return None;
+    }
+
+    Some(Finding::new(
+        FindingKind::ResizeRecommended { suggested },
+        subnet,
+        format!(
+            "Subnet '{}' uses {used} of {hosts} Azure hosts; resize {cidr} → {suggested}",
+            subnet.subnet_name
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SubnetSource;
+
+    fn make_subnet(name: &str, cidr: &str, nsg: Option<&str>, ip_count: Option<u32>) -> Subnet {
+        Subnet {
+            vnet_cidr: Ipv4::new("10.0.0.0/24").unwrap(),
+            nsg: nsg.map(str::to_string),
+            ip_configurations_count: ip_count,
+            ..Subnet::test("hub", name, cidr)
+        }
+    }
+
+    fn report(subnets: Vec<Subnet>) -> Report {
+        Report::from_subnets(&Data {
+            data: subnets,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn missing_nsg_is_reported_except_for_platform_subnets() {
+        let r = report(vec![
+            make_subnet("app", "10.0.0.0/26", None, None),
+            make_subnet("GatewaySubnet", "10.0.0.64/27", None, None),
+            make_subnet("web", "10.0.0.96/27", Some("nsg-id"), None),
+        ]);
+        assert_eq!(r.findings.len(), 1);
+        assert_eq!(r.findings[0].kind, FindingKind::MissingNsg);
+        assert_eq!(r.findings[0].severity, Severity::Error);
+        assert_eq!(r.findings[0].subnet_name, "app");
+    }
+
+    #[test]
+    fn stand_in_ranges_get_no_findings() {
+        let mut on_prem = make_subnet("dc", "10.0.0.0/26", None, None);
+        on_prem.source = SubnetSource::OnPrem;
+        let mut reserved = make_subnet("future", "10.0.0.64/26", None, None);
+        reserved.source = SubnetSource::Reserved;
+        let mut p2s = make_subnet("gw-p2s", "10.0.0.192/27", None, None);
+        p2s.source = SubnetSource::VpnClient;
+        let mut planned = make_subnet("app", "10.0.0.128/26", None, None);
+        planned.source = SubnetSource::Planned;
+        let r = report(vec![on_prem, reserved, p2s, planned]);
+        assert_eq!(r.findings.len(), 1);
+        assert_eq!(r.findings[0].subnet_name, "app");
+    }
+
+    #[test]
+    fn nsg_shared_across_vnets_is_reported_for_each_subnet() {
+        let mut spoke = make_subnet("web", "10.0.0.64/27", Some("/nsgs/nsg-shared"), None);
+        spoke.vnet_name = "spoke".to_string();
+        let r = report(vec![
+            make_subnet("app", "10.0.0.0/27", Some("/nsgs/nsg-shared"), None),
+            make_subnet("db", "10.0.0.32/27", Some("/nsgs/nsg-db"), None),
+            spoke,
+        ]);
+        let shared: Vec<&str> = r
+            .findings
+            .iter()
+            .filter(|f| f.kind.id() == "shared_nsg")
+            .map(|f| f.subnet_name.as_str())
+            .collect();
+        assert_eq!(shared, ["app", "web"]);
+        assert_eq!(r.findings[0].severity, Severity::Info);
+        assert_eq!(
+            r.findings[0].message,
+            "Subnet 'app' shares NSG 'nsg-shared' with subnets in 1 other VNet(s)"
+        );
+    }
+
+    #[test]
+    fn nearly_full_subnet_gets_resize_when_sibling_block_is_free() {
+        // /27 = 27 Azure hosts; 22 used is > 80 %.
+        let r = report(vec![make_subnet(
+            "app",
+            "10.0.0.32/27",
+            Some("n"),
+            Some(22),
+        )]);
+        assert_eq!(
+            r.findings[0].kind,
+            FindingKind::ResizeRecommended {
+                suggested: Ipv4::new("10.0.0.0/26").unwrap()
+            }
+        );
+    }
+
+    #[test]
+    fn no_resize_when_sibling_block_is_used_or_utilisation_is_low() {
+        let r = report(vec![
+            make_subnet("app", "10.0.0.32/27", Some("n"), Some(22)),
+            make_subnet("db", "10.0.0.0/27", Some("n"), Some(1)),
+        ]);
+        assert!(r.findings.is_empty());
+    }
+
+    #[test]
+    fn policy_findings_are_about_the_vnet() {
+        let mut s = make_subnet("app", "10.0.0.0/26", Some("n"), None);
+        s.vnet_id = "/subscriptions/s/virtualNetworks/hub".to_string();
+        s.ddos_protection_plan = Some("/plans/ddos".to_string());
+        let data = Data {
+            data: vec![s],
+            ..Default::default()
+        };
+        let mut r = Report::from_subnets(&data);
+        let policies = [VnetPolicy {
+            name: "all-encrypted".to_string(),
+            require_ddos: true,
+            require_encryption: true,
+            ..Default::default()
+        }];
+        r.add_policy_findings(&data, &SubscriptionData::default(), &policies)
+            .unwrap();
+        assert_eq!(r.findings.len(), 1);
+        let f = &r.findings[0];
+        assert_eq!(f.kind.id(), "encryption_required");
+        assert_eq!(f.subnet_name, "");
+        assert_eq!(f.subnet_id, "/subscriptions/s/virtualNetworks/hub");
+        assert_eq!(
+            f.message,
+            "VNet 'hub' does not have encryption enabled (policy 'all-encrypted')"
+        );
+    }
+}
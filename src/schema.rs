@@ -0,0 +1,420 @@
+//! JSON Schemas of the serialized outputs, printed by `schema`.
+//!
+//! Consumers of the JSON outputs (`--output json`, report findings, cache
+//! files / snapshots) generate types and validate payloads from these. They
+//! are written by hand next to the serde definitions; the tests check every
+//! schema against real serialized values, so a field added to [`Subnet`],
+//! [`Finding`], [`Report`] or the cache format without updating the schema
+//! fails the build.
+//!
+//! [`Subnet`]: crate::models::Subnet
+//! [`Finding`]: crate::report::Finding
+//! [`Report`]: crate::report::Report
+
+use crate::report::FINDING_IDS;
+use serde_json::{json, Map, Value};
+
+/// JSON Schema dialect of every schema.
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Names accepted by [`schema`], with the `$defs` entry each one is rooted at.
+pub const SCHEMAS: &[(&str, &str)] = &[
+    ("subnet", "Subnet"),
+    ("finding", "Finding"),
+    ("report", "Report"),
+    ("cache", "SubnetCache"),
+];
+
+fn string(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+fn nullable_string(description: &str) -> Value {
+    json!({ "type": ["string", "null"], "description": description })
+}
+
+fn nullable_count(description: &str) -> Value {
+    json!({ "type": ["integer", "null"], "minimum": 0, "description": description })
+}
+
+fn reference(def: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{def}") })
+}
+
+fn object(description: &str, properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Every type definition, keyed by name.
+fn definitions() -> Map<String, Value> {
+    let cidr = json!({
+        "type": "string",
+        "description": "IPv4 prefix in CIDR notation, e.g. 10.0.0.0/24",
+        "pattern": r"^(\d{1,3}\.){3}\d{1,3}/\d{1,2}$",
+    });
+    let nullable_cidr = json!({ "oneOf": [reference("Cidr"), { "type": "null" }] });
+
+    let subnet = object(
+        "Azure subnet as written to cache files and `--output json`.",
+        json!({
+            "resource_group": string("Resource group of the virtual network (empty when unknown)"),
+            "vnet_id": string("ARM resource ID of the virtual network (empty for old caches)"),
+            "vnet_name": string("Name of the virtual network"),
+            "vnet_cidr": {
+                "description": "The VNet address prefix containing the subnet (one element)",
+                "type": "array", "items": reference("Cidr"), "minItems": 1,
+            },
+            "vnet_address_space": {
+                "description": "Every address prefix of the VNet, in Azure's order",
+                "type": "array", "items": reference("Cidr"),
+            },
+            "subnet_id": string("ARM resource ID of the subnet (empty for old caches)"),
+            "subnet_name": string("Name of the subnet"),
+            "subnet_cidr": nullable_cidr,
+            "nsg": nullable_string("Network Security Group ID"),
+            "delegations": {
+                "description": "Service names the subnet is delegated to",
+                "type": "array", "items": { "type": "string" },
+            },
+            "nat_gateway": nullable_string("NAT gateway ID"),
+            "load_balancers": {
+                "description": "Internal load balancers with a frontend in the subnet",
+                "type": "array", "items": { "type": "string" },
+            },
+            "location": string("Azure region"),
+            "dns_servers": {
+                "description": "Custom DNS servers of the VNet",
+                "type": ["array", "null"], "items": { "type": "string" },
+            },
+            "ddos_protection_plan": nullable_string("DDoS Network Protection plan ID of the VNet"),
+            "encryption": nullable_string("VNet encryption enforcement (AllowUnencrypted / DropUnencrypted)"),
+            "subscription_id": string("Azure subscription ID"),
+            "subscription_name": string("Azure subscription display name"),
+            "ip_configurations_count": nullable_count("IP configurations (NICs) in the subnet"),
+            "service_association_links": nullable_count("Service association links of the subnet"),
+            "source": {
+                "description": "Where the record comes from",
+                "enum": ["azure", "reserved", "on-prem", "planned", "vpn-client"],
+            },
+            "fetched_at": nullable_string("RFC 3339 time the record was fetched from Azure"),
+            "query_block": nullable_count("Resource Graph result page, starting at 0"),
+        }),
+        &[
+            "vnet_name",
+            "vnet_cidr",
+            "subnet_name",
+            "subnet_cidr",
+            "nsg",
+            "location",
+            "dns_servers",
+            "subscription_id",
+            "subscription_name",
+            "ip_configurations_count",
+        ],
+    );
+
+    // Kind-specific fields sit next to the common ones (`kind` is the tag).
+    let kind_fields: [(&str, &[&str]); 6] = [
+        ("missing_nsg", &[]),
+        ("resize_recommended", &["suggested"]),
+        ("ddos_protection_required", &["policy"]),
+        ("encryption_required", &["policy"]),
+        ("location_mismatch", &["policy", "location"]),
+        ("shared_nsg", &["nsg", "vnets"]),
+    ];
+    debug_assert_eq!(kind_fields.map(|(id, _)| id), FINDING_IDS);
+    let finding = json!({
+        "type": "object",
+        "description": "One report finding about a subnet, or a VNet for policy findings.",
+        "properties": {
+            "kind": { "description": "What the finding is about", "enum": FINDING_IDS },
+            "severity": { "enum": ["info", "warn", "error"] },
+            "subscription_id": string("Azure subscription ID"),
+            "subscription_name": string("Azure subscription display name"),
+            "vnet_name": string("Name of the virtual network"),
+            "subnet_name": string("Subnet name (empty for VNet-level findings)"),
+            "subnet_id": string("ARM resource ID of the subnet, or of the VNet for VNet-level findings"),
+            "subnet_cidr": nullable_cidr,
+            "message": string("Human-readable explanation"),
+            "suggested": {
+                "allOf": [reference("Cidr")],
+                "description": "resize_recommended: next larger aligned prefix, free in the VNet",
+            },
+            "policy": string("Policy findings: name of the VNet policy"),
+            "location": string("location_mismatch: region of the VNet"),
+            "nsg": string("shared_nsg: NSG name"),
+            "vnets": {
+                "type": "integer", "minimum": 0,
+                "description": "shared_nsg: VNets whose subnets use the NSG",
+            },
+        },
+        "required": [
+            "kind", "severity", "subscription_id", "subscription_name", "vnet_name",
+            "subnet_name", "subnet_id", "subnet_cidr", "message",
+        ],
+        "oneOf": kind_fields.map(|(id, fields)| json!({
+            "properties": { "kind": { "const": id } },
+            "required": fields,
+        })),
+        "additionalProperties": false,
+    });
+
+    let report = object(
+        "Findings of one run.",
+        json!({
+            "generated_at": {
+                "type": "string", "format": "date-time",
+                "description": "When the report was generated (UTC)",
+            },
+            "findings": { "type": "array", "items": reference("Finding") },
+        }),
+        &["generated_at", "findings"],
+    );
+
+    let account = object(
+        "Azure CLI account the data was fetched under.",
+        json!({
+            "tenant_id": string("Tenant ID"),
+            "subscription_id": string("Default subscription"),
+            "user": string("Signed-in user or service principal"),
+        }),
+        &["tenant_id", "subscription_id", "user"],
+    );
+    let cache_meta = object(
+        "Provenance of a cache file.",
+        json!({
+            "key": string("Hash of the query and scope, as used in the file name"),
+            "query": string("KQL query the data was fetched with"),
+            "tenant": nullable_string("Tenant scope of the query"),
+            "management_group": nullable_string("Management group scope of the query"),
+            "subscriptions": {
+                "description": "Subscription scope of the query (sorted)",
+                "type": "array", "items": { "type": "string" },
+            },
+            "generated_at": nullable_string("RFC 3339 time the data was fetched, in the run's timezone"),
+            "account": { "oneOf": [reference("AccountContext"), { "type": "null" }] },
+        }),
+        &["key", "query", "tenant", "management_group"],
+    );
+    let cache = object(
+        "Subnet cache file / snapshot (net_<date>_cache_subnet_<key>.json).",
+        json!({
+            "data": { "type": "array", "items": reference("Subnet") },
+            "skip_token": nullable_string("Resource Graph pagination token"),
+            "total_records": nullable_count("Records matching the query"),
+            "count": { "type": "integer", "description": "Records in the response" },
+            "cache_meta": reference("CacheMeta"),
+        }),
+        &["data", "skip_token", "total_records", "count"],
+    );
+
+    [
+        ("Cidr", cidr),
+        ("Subnet", subnet),
+        ("Finding", finding),
+        ("Report", report),
+        ("AccountContext", account),
+        ("CacheMeta", cache_meta),
+        ("SubnetCache", cache),
+    ]
+    .into_iter()
+    .map(|(name, def)| (name.to_string(), def))
+    .collect()
+}
+
+/// Schema document for `name` (see [`SCHEMAS`]), or all definitions under
+/// `$defs` when `name` is None. Unknown names give None.
+pub fn schema(name: Option<&str>) -> Option<Value> {
+    let defs = definitions();
+    let mut root = match name {
+        None => Map::new(),
+        Some(name) => {
+            let (_, def) = SCHEMAS.iter().find(|(n, _)| *n == name)?;
+            let mut root = defs[*def].as_object()?.clone();
+            root.insert("title".to_string(), json!(def));
+            root
+        }
+    };
+    root.insert("$schema".to_string(), json!(DIALECT));
+    root.insert("$defs".to_string(), Value::Object(defs));
+    Some(Value::Object(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::{AccountContext, CacheMeta, Data};
+    use crate::models::{Ipv4, Subnet};
+    use crate::report::{Finding, FindingKind, Report, Severity};
+
+    /// Check `value` against the schema keywords used above.
+    fn check(
+        value: &Value,
+        schema: &Value,
+        defs: &Map<String, Value>,
+        at: &str,
+    ) -> Result<(), String> {
+        if let Some(r) = schema["$ref"].as_str() {
+            return check(value, &defs[r.trim_start_matches("#/$defs/")], defs, at);
+        }
+        let types: Vec<&str> = match &schema["type"] {
+            Value::String(t) => vec![t],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        let type_of = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        };
+        if !types.is_empty() && !types.contains(&type_of) {
+            return Err(format!("{at}: {type_of} is not {types:?}"));
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                return Err(format!("{at}: {value} not in enum"));
+            }
+        }
+        if !schema["const"].is_null() && schema["const"] != *value {
+            return Err(format!("{at}: {value} is not {}", schema["const"]));
+        }
+        for sub in schema["allOf"].as_array().into_iter().flatten() {
+            check(value, sub, defs, at)?;
+        }
+        if let Some(options) = schema["oneOf"].as_array() {
+            let matching = options
+                .iter()
+                .filter(|s| check(value, s, defs, at).is_ok())
+                .count();
+            if matching != 1 {
+                return Err(format!("{at}: {matching} oneOf branches match"));
+            }
+        }
+        if let (Value::Array(items), Some(item)) = (value, schema.get("items")) {
+            for (i, v) in items.iter().enumerate() {
+                check(v, item, defs, &format!("{at}[{i}]"))?;
+            }
+        }
+        if let Value::Object(fields) = value {
+            for required in schema["required"].as_array().into_iter().flatten() {
+                if !fields.contains_key(required.as_str().unwrap()) {
+                    return Err(format!("{at}: missing {required}"));
+                }
+            }
+            for (key, v) in fields {
+                match schema["properties"].get(key) {
+                    Some(sub) => check(v, sub, defs, &format!("{at}.{key}"))?,
+                    None if schema["additionalProperties"] == false => {
+                        return Err(format!("{at}: '{key}' is not in the schema"))
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn conforms<T: serde::Serialize>(name: &str, value: &T) {
+        let root = schema(Some(name)).unwrap();
+        let defs = root["$defs"].as_object().unwrap();
+        let value = serde_json::to_value(value).unwrap();
+        check(&value, &root, defs, name).unwrap();
+    }
+
+    #[test]
+    fn serialized_outputs_match_their_schemas() {
+        let subnet = Subnet {
+            nsg: Some("/subscriptions/s/nsg".to_string()),
+            dns_servers: Some(vec!["10.0.0.4".to_string()]),
+            ip_configurations_count: Some(3),
+            ..Subnet::test("hub", "app", "10.0.0.0/24")
+        };
+        conforms("subnet", &subnet);
+        conforms("subnet", &Subnet::default());
+
+        let kinds = [
+            FindingKind::MissingNsg,
+            FindingKind::ResizeRecommended {
+                suggested: Ipv4::new("10.0.0.0/23").unwrap(),
+            },
+            FindingKind::DdosProtectionRequired {
+                policy: "prod".to_string(),
+            },
+            FindingKind::EncryptionRequired {
+                policy: "prod".to_string(),
+            },
+            FindingKind::LocationMismatch {
+                policy: "eu".to_string(),
+                location: "eastus".to_string(),
+            },
+            FindingKind::SharedNsg {
+                nsg: "nsg-a".to_string(),
+                vnets: 2,
+            },
+        ];
+        let findings: Vec<Finding> = kinds
+            .into_iter()
+            .map(|kind| Finding {
+                severity: Severity::Warn,
+                kind,
+                subscription_id: "s".to_string(),
+                subscription_name: "sub".to_string(),
+                vnet_name: "hub".to_string(),
+                subnet_name: "app".to_string(),
+                subnet_id: String::new(),
+                subnet_cidr: subnet.subnet_cidr,
+                message: "m".to_string(),
+            })
+            .collect();
+        for finding in &findings {
+            conforms("finding", finding);
+        }
+        let report = Report {
+            generated_at: chrono::Utc::now(),
+            findings,
+            ..Default::default()
+        };
+        conforms("report", &report);
+
+        let data = Data {
+            data: vec![subnet],
+            ..Default::default()
+        };
+        let mut cache = serde_json::to_value(&data).unwrap();
+        let meta = CacheMeta {
+            key: "abc".to_string(),
+            query: "Resources".to_string(),
+            tenant: None,
+            management_group: Some("mg".to_string()),
+            subscriptions: vec!["s".to_string()],
+            generated_at: Some("2026-10-15T06:00:00Z".to_string()),
+            account: Some(AccountContext {
+                tenant_id: "t".to_string(),
+                subscription_id: "s".to_string(),
+                user: "u".to_string(),
+            }),
+        };
+        cache["cache_meta"] = serde_json::to_value(&meta).unwrap();
+        conforms("cache", &cache);
+        cache["data"][0]["unknown_field"] = json!(1);
+        let cache_schema = schema(Some("cache")).unwrap();
+        let defs = cache_schema["$defs"].as_object().unwrap();
+        assert!(check(&cache, &cache_schema, defs, "cache").is_err());
+
+        assert!(schema(Some("vnet")).is_none());
+        let all = schema(None).unwrap();
+        for (_, def) in SCHEMAS {
+            assert!(all["$defs"].get(def).is_some(), "{def}");
+        }
+    }
+}
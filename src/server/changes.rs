@@ -0,0 +1,256 @@
+//! `GET /changes?since=<ts>` — subnet changes since a point in time.
+//!
+//! The state at `since` is the newest snapshot this server served at or
+//! before that time. Older states come from the history, which keeps one
+//! snapshot per day: the newest dated on or before the day of `since` (in
+//! the run's timezone), other than the file being served, which each
+//! refresh overwrites. The state is compared with the snapshot being served
+//! by the snapshot diff engine, and each change is returned as a flat record
+//! for consumers syncing incrementally; they pass the
+//! `current.generated_at` of one response as `since` of the next.
+
+use crate::azure::{list_snapshots, read_cache_file, Data, QueryOptions};
+use crate::clock::Clock;
+use crate::processing::{diff_subnets, normalize_cidrs, ChangeKind, SubnetChange};
+use chrono::{DateTime, NaiveDate};
+use serde::Serialize;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Where dated snapshots are found.
+#[derive(Debug, Clone)]
+pub struct History {
+    /// Directory holding the `report-<date>/` snapshot history.
+    pub root: PathBuf,
+    /// Query scope whose snapshots are compared.
+    pub options: QueryOptions,
+}
+
+/// One changed subnet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangeRecord {
+    /// `added`, `removed`, `renamed` or `modified`.
+    pub change: &'static str,
+    pub subscription_id: String,
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub subnet_name: String,
+    pub subnet_cidr: Option<String>,
+    pub subnet_id: String,
+    /// Previous name of a renamed subnet.
+    pub renamed_from: Option<String>,
+    /// Properties of a modified subnet that changed.
+    pub fields: Vec<&'static str>,
+}
+
+impl From<&SubnetChange> for ChangeRecord {
+    fn from(c: &SubnetChange) -> Self {
+        let s = &c.subnet;
+        let (change, renamed_from, fields) = match &c.kind {
+            ChangeKind::Added => ("added", None, Vec::new()),
+            ChangeKind::Removed => ("removed", None, Vec::new()),
+            ChangeKind::Renamed { from } => ("renamed", Some(from.clone()), Vec::new()),
+            ChangeKind::Modified { fields } => ("modified", None, fields.clone()),
+        };
+        ChangeRecord {
+            change,
+            subscription_id: s.subscription_id.clone(),
+            subscription_name: s.subscription_name.clone(),
+            vnet_name: s.vnet_name.clone(),
+            subnet_name: s.subnet_name.clone(),
+            subnet_cidr: s.subnet_cidr.map(|c| c.to_string()),
+            subnet_id: s.subnet_id.clone(),
+            renamed_from,
+            fields,
+        }
+    }
+}
+
+/// A snapshot taking part in the comparison.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotRef {
+    pub file: String,
+    /// Snapshot date (baseline) or fetch time (current).
+    pub generated_at: Option<String>,
+}
+
+/// A snapshot served by an earlier refresh of this process, kept because
+/// the next refresh overwrites its file.
+#[derive(Debug, Clone)]
+pub struct ServedSnapshot {
+    pub snapshot: SnapshotRef,
+    pub data: Data,
+}
+
+/// Answer of `GET /changes`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Changes {
+    pub since: String,
+    pub baseline: SnapshotRef,
+    pub current: SnapshotRef,
+    pub changes: Vec<ChangeRecord>,
+}
+
+/// Day of `since`, an RFC 3339 timestamp or a date as `YYYY-MM-DD`.
+pub fn since_date(since: &str, clock: &Clock) -> Result<NaiveDate, String> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(since) {
+        return Ok(t.with_timezone(&clock.now().timezone()).date_naive());
+    }
+    NaiveDate::parse_from_str(since, "%Y-%m-%d").map_err(|_| {
+        format!("'since' must be an RFC 3339 timestamp or YYYY-MM-DD date, got '{since}'")
+    })
+}
+
+/// Changes between the state at `since` and `current`; `served` holds the
+/// snapshots of earlier refreshes, oldest first.
+pub fn changes_since(
+    history: &History,
+    clock: &Clock,
+    since: &str,
+    current: &SnapshotRef,
+    served: &[Arc<ServedSnapshot>],
+) -> Result<Option<Changes>, Box<dyn Error>> {
+    let day = since_date(since, clock)?;
+    let at = DateTime::parse_from_rfc3339(since).ok();
+    let earlier = served.iter().rev().find(|s| {
+        let generated = s.snapshot.generated_at.as_deref();
+        let generated = generated.and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+        matches!((generated, at), (Some(g), Some(at)) if g <= at)
+    });
+    let (baseline, old) = match earlier {
+        Some(s) => (s.snapshot.clone(), s.data.clone()),
+        None => {
+            let Some(dated) = list_snapshots(&history.root, &history.options, clock)
+                .into_iter()
+                .rev()
+                .find(|s| s.date <= day && s.path != Path::new(&current.file))
+            else {
+                return Ok(None);
+            };
+            let (old, _) = normalize_cidrs(read_cache_file::<Data>(&dated.path)?);
+            let baseline = SnapshotRef {
+                file: dated.path.to_string_lossy().into_owned(),
+                generated_at: Some(clock.format_date(dated.date)),
+            };
+            (baseline, old)
+        }
+    };
+    let (new, _) = normalize_cidrs(read_cache_file::<Data>(Path::new(&current.file))?);
+    Ok(Some(Changes {
+        since: since.to_string(),
+        baseline,
+        current: current.clone(),
+        changes: diff_subnets(&old, &new).iter().map(Into::into).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::cache_key;
+    use crate::models::Subnet;
+
+    fn subnet(name: &str, cidr: &str) -> Subnet {
+        let mut s = Subnet::test("hub", name, cidr);
+        s.vnet_id = "/subscriptions/s/providers/Microsoft.Network/virtualNetworks/hub".to_string();
+        s.subnet_id = format!("{}/subnets/{name}", s.vnet_id);
+        s
+    }
+
+    fn write(path: &Path, subnets: Vec<Subnet>) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let data = Data {
+            data: subnets,
+            ..Default::default()
+        };
+        std::fs::write(path, serde_json::to_string(&data).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn changes_are_relative_to_the_snapshot_of_the_since_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = QueryOptions::default();
+        let key = cache_key::<Data>(&options);
+        let dated = |date: &str| {
+            dir.path().join(format!(
+                "report-{date}/cache/net_{date}_cache_subnet_{key}.json"
+            ))
+        };
+        write(&dated("2026-10-01"), vec![subnet("app", "10.0.0.0/24")]);
+        write(
+            &dated("2026-10-08"),
+            vec![subnet("app", "10.0.0.0/24"), subnet("db", "10.0.1.0/24")],
+        );
+        let current = dated("2026-10-15");
+        write(
+            &current,
+            vec![subnet("db", "10.0.1.0/24"), subnet("web", "10.0.2.0/24")],
+        );
+        let history = History {
+            root: dir.path().to_path_buf(),
+            options,
+        };
+        let clock = Clock::default();
+        let current = SnapshotRef {
+            file: current.to_string_lossy().into_owned(),
+            generated_at: Some("2026-10-15T06:00:00Z".to_string()),
+        };
+
+        let changes = changes_since(&history, &clock, "2026-10-09T12:00:00Z", &current, &[])
+            .unwrap()
+            .unwrap();
+        assert!(changes.baseline.file.contains("2026-10-08"));
+        let summary: Vec<_> = changes
+            .changes
+            .iter()
+            .map(|c| (c.change, c.subnet_name.as_str()))
+            .collect();
+        assert_eq!(summary, [("removed", "app"), ("added", "web")]);
+
+        let all = changes_since(&history, &clock, "2026-10-01", &current, &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(all.changes.len(), 3);
+        assert!(all.baseline.file.contains("2026-10-01"));
+
+        assert!(changes_since(&history, &clock, "2026-09-30", &current, &[])
+            .unwrap()
+            .is_none());
+        assert!(changes_since(&history, &clock, "last tuesday", &current, &[]).is_err());
+
+        // Today's file is the one being served, never its own baseline.
+        let today = changes_since(&history, &clock, "2026-10-15T05:00:00Z", &current, &[])
+            .unwrap()
+            .unwrap();
+        assert!(today.baseline.file.contains("2026-10-08"));
+
+        // Within the day, the snapshot of an earlier refresh is the baseline.
+        let earlier = ServedSnapshot {
+            snapshot: SnapshotRef {
+                file: current.file.clone(),
+                generated_at: Some("2026-10-15T02:00:00Z".to_string()),
+            },
+            data: Data {
+                data: vec![subnet("db", "10.0.1.0/24")],
+                ..Default::default()
+            },
+        };
+        let served = [
+            Arc::new(earlier),
+            Arc::new(ServedSnapshot {
+                snapshot: current.clone(),
+                data: Data::default(),
+            }),
+        ];
+        let since_last = changes_since(&history, &clock, "2026-10-15T02:00:00Z", &current, &served)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            since_last.baseline.generated_at.as_deref(),
+            Some("2026-10-15T02:00:00Z")
+        );
+        assert_eq!(since_last.changes.len(), 1);
+        assert_eq!(since_last.changes[0].subnet_name, "web");
+    }
+}
@@ -0,0 +1,202 @@
+//! Cron expressions for the `serve` refresh schedule (`refresh_cron`).
+//!
+//! The classic five fields — minute, hour, day of month, month, day of week
+//! — each `*`, a number, a range `a-b`, a step `*/n` or `a-b/n`, or a comma
+//! list of those. Day of week is 0-7 with both 0 and 7 meaning Sunday. As in
+//! cron, when both day fields are restricted a day matching either runs.
+//! Times are taken in the run's timezone (`--timezone`, default UTC).
+
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// How far ahead [`CronSchedule::next_after`] looks (covers `29 2 29 2 *`).
+const SEARCH_DAYS: i64 = 366 * 8;
+
+/// A parsed five-field cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    text: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day of month field was `*` (only the weekday restricts the day).
+    any_day: bool,
+    /// Day of week field was `*` (only the day of month restricts the day).
+    any_weekday: bool,
+}
+
+/// Bit set of the values `field` allows within `min..=max`.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step in '{part}'"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let number = |n: &str| {
+            n.parse::<u32>()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or_else(|| format!("'{n}' is not in {min}-{max}"))
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (number(a)?, number(b)?),
+                // `5/15` means from 5 to the end in steps of 15.
+                None if step > 1 => (number(range)?, max),
+                None => (number(range)?, number(range)?),
+            },
+        };
+        if from > to {
+            return Err(format!("range '{range}' runs backwards"));
+        }
+        for v in (from..=to).step_by(step as usize) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "'{s}' is not a cron expression (minute hour day-of-month month day-of-week)"
+            ));
+        };
+        let err = |e: String| format!("Invalid cron expression '{s}': {e}");
+        let mut weekdays = parse_field(weekday, 0, 7).map_err(err)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(CronSchedule {
+            text: fields.join(" "),
+            minutes: parse_field(minute, 0, 59).map_err(err)?,
+            hours: parse_field(hour, 0, 23).map_err(err)?,
+            days: parse_field(day, 1, 31).map_err(err)?,
+            months: parse_field(month, 1, 12).map_err(err)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for CronSchedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl CronSchedule {
+    fn day_matches(&self, t: &NaiveDateTime) -> bool {
+        let day = self.days & (1 << t.day()) != 0;
+        let weekday = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// First scheduled time strictly after `after`, in its timezone. Local
+    /// times skipped by a DST change are skipped.
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let end = start + Duration::days(SEARCH_DAYS);
+        let mut t = start;
+        while t < end {
+            if self.months & (1 << t.month()) == 0 || !self.day_matches(&t) {
+                t = (t.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else if let Some(next) = after.timezone().from_local_datetime(&t).earliest() {
+                return Some(next);
+            } else {
+                t += Duration::minutes(1);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().to_utc()
+    }
+
+    #[test]
+    fn next_run_follows_the_expression() {
+        let daily: CronSchedule = "0 6 * * *".parse().unwrap();
+        assert_eq!(
+            daily.next_after(&at("2026-10-15T05:59:30Z")),
+            Some(at("2026-10-15T06:00:00Z"))
+        );
+        assert_eq!(
+            daily.next_after(&at("2026-10-15T06:00:00Z")),
+            Some(at("2026-10-16T06:00:00Z"))
+        );
+
+        // Weekdays at 07:30; 2026-10-17 is a Saturday.
+        let weekdays: CronSchedule = "30 7 * * 1-5".parse().unwrap();
+        assert_eq!(
+            weekdays.next_after(&at("2026-10-16T08:00:00Z")),
+            Some(at("2026-10-19T07:30:00Z"))
+        );
+
+        let quarter_hour: CronSchedule = "*/15 * * * *".parse().unwrap();
+        assert_eq!(
+            quarter_hour.next_after(&at("2026-10-15T10:16:00Z")),
+            Some(at("2026-10-15T10:30:00Z"))
+        );
+
+        // Both day fields restricted: the 1st of the month or any Sunday (7).
+        let either: CronSchedule = "0 0 1 * 7".parse().unwrap();
+        assert_eq!(
+            either.next_after(&at("2026-10-15T00:00:00Z")),
+            Some(at("2026-10-18T00:00:00Z"))
+        );
+
+        for bad in [
+            "0 6 * *",
+            "60 * * * *",
+            "0 6 * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(bad.parse::<CronSchedule>().is_err(), "{bad}");
+        }
+        assert_eq!(
+            "0  6 * *  *".parse::<CronSchedule>().unwrap().to_string(),
+            "0 6 * * *"
+        );
+    }
+}
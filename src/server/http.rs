@@ -0,0 +1,123 @@
+//! Minimal HTTP/1.1 for the `serve` endpoints.
+//!
+//! Enough of the protocol for probes and JSON API clients: one request per
+//! connection, the request line and headers parsed, any body ignored, and a
+//! JSON response with `Connection: close`.
+
+use reqwest::Url;
+use serde::Serialize;
+use std::error::Error;
+use std::io::{BufRead, Read, Write};
+
+/// Longest request line or header accepted.
+const MAX_LINE: usize = 8 * 1024;
+
+/// Most request headers accepted.
+const MAX_HEADERS: usize = 100;
+
+/// A parsed request: method, path and decoded query parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+}
+
+impl Request {
+    /// Parse a request target such as `/changes?since=2026-10-01`.
+    pub fn new(method: &str, target: &str) -> Result<Request, Box<dyn Error>> {
+        let url = Url::parse("http://localhost")?
+            .join(target)
+            .map_err(|e| format!("Invalid request target '{target}': {e}"))?;
+        Ok(Request {
+            method: method.to_string(),
+            path: url.path().to_string(),
+            query: url.query_pairs().into_owned().collect(),
+        })
+    }
+
+    /// Value of query parameter `name`, if given.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Read one CRLF-terminated line of at most [`MAX_LINE`] bytes.
+fn read_line(reader: &mut impl BufRead, what: &str) -> Result<String, Box<dyn Error>> {
+    let mut line = String::new();
+    reader.take(MAX_LINE as u64 + 1).read_line(&mut line)?;
+    if line.len() > MAX_LINE {
+        return Err(format!("{what} too long").into());
+    }
+    Ok(line)
+}
+
+/// Read one request from `reader`. Lines are read at most [`MAX_LINE`]
+/// bytes at a time and at most [`MAX_HEADERS`] headers are skipped, so a
+/// client cannot make the server buffer without bound.
+pub fn read_request(reader: &mut impl BufRead) -> Result<Request, Box<dyn Error>> {
+    let line = read_line(reader, "Request line")?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(format!("Malformed request line '{}'", line.trim()).into());
+    };
+    let request = Request::new(method, target)?;
+    // Skip the headers; no endpoint reads them.
+    for _ in 0..=MAX_HEADERS {
+        let header = read_line(reader, "Request header")?;
+        if header.trim().is_empty() {
+            return Ok(request);
+        }
+    }
+    Err("Too many request headers".into())
+}
+
+/// A JSON response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    /// `status` with `value` serialized as the body.
+    pub fn json<T: Serialize>(status: u16, value: &T) -> Response {
+        let body = serde_json::to_string_pretty(value).unwrap_or_else(|e| {
+            serde_json::json!({ "error": format!("Error serializing response: {e}") }).to_string()
+        });
+        Response { status, body }
+    }
+
+    /// `status` with `{"error": message}` as the body.
+    pub fn error(status: u16, message: &str) -> Response {
+        Response::json(status, &serde_json::json!({ "error": message }))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+
+    /// Write the response, headers and body, to `writer`.
+    pub fn write_to(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.reason(),
+            self.body.len(),
+            self.body
+        )?;
+        writer.flush()
+    }
+}
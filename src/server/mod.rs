@@ -0,0 +1,492 @@
+//! Long-running server mode (`serve`).
+//!
+//! - [`changes`] - Subnet changes since a timestamp (`GET /changes?since=`)
+//! - [`cron`] - Cron expressions for the refresh schedule (`refresh_cron`)
+//! - [`http`] - Minimal HTTP/1.1 request parsing and JSON responses
+//!
+//! A [`Server`] refreshes the snapshot once at startup and then on its cron
+//! schedule, and answers HTTP requests about it. A refresh is supplied by the
+//! caller (the `serve` command fetches from Azure and regenerates the report);
+//! the server records when it ran and how it went, so `GET /status` shows the
+//! last run and the next one.
+//!
+//! `GET /healthz` (liveness) answers 200 while the process serves requests;
+//! `GET /readyz` (readiness) answers 503 until a refresh has succeeded and
+//! while the served snapshot is older than the maximum age. Both report the
+//! snapshot age, the last fetch's outcome and the record counts, for AKS /
+//! Container Apps probes.
+
+mod changes;
+mod cron;
+mod http;
+
+pub use changes::{
+    changes_since, since_date, ChangeRecord, Changes, History, ServedSnapshot, SnapshotRef,
+};
+pub use cron::CronSchedule;
+pub use http::{read_request, Request, Response};
+
+use crate::azure::read_cache_file;
+use crate::clock::Clock;
+use crate::processing::normalize_cidrs;
+use chrono::SecondsFormat;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long a client may take to send its request or read the response.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Refreshes whose snapshots `/changes` can compare against.
+const MAX_SERVED_SNAPSHOTS: usize = 48;
+
+/// Threads answering requests.
+const WORKERS: usize = 8;
+
+/// Accepted connections that may wait for a free worker.
+const QUEUED_CONNECTIONS: usize = 64;
+
+/// What a successful refresh produced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RefreshOutcome {
+    /// Subnet records in the refreshed snapshot.
+    pub subnets: usize,
+    /// Distinct VNets among the subnets.
+    pub vnets: usize,
+    /// When the snapshot was fetched from Azure, if recorded.
+    pub generated_at: Option<String>,
+    /// Snapshot file written (or read) by the refresh.
+    pub cache_file: String,
+}
+
+/// One completed refresh.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RefreshRun {
+    pub started_at: String,
+    pub finished_at: String,
+    pub ok: bool,
+    /// Why the refresh failed; `None` when it succeeded.
+    pub error: Option<String>,
+    /// `None` when the refresh failed.
+    pub outcome: Option<RefreshOutcome>,
+}
+
+/// Scheduler state reported by `GET /status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ServerStatus {
+    /// The cron expression, if refreshes are scheduled.
+    pub schedule: Option<String>,
+    /// A refresh is in progress.
+    pub running: bool,
+    pub next_run: Option<String>,
+    pub last_run: Option<RefreshRun>,
+    /// Most recent successful refresh (the one being served).
+    pub last_success: Option<RefreshRun>,
+}
+
+/// Probe answer of `GET /healthz` and `GET /readyz`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Health {
+    /// A snapshot is being served and it is not too old.
+    pub ready: bool,
+    /// Why the server is not ready; `None` when it is.
+    pub reason: Option<String>,
+    /// When the served snapshot was fetched.
+    pub snapshot_generated_at: Option<String>,
+    pub snapshot_age_seconds: Option<i64>,
+    /// `ok` / `failed` for the most recent refresh, `pending` before the first.
+    pub last_fetch: &'static str,
+    pub last_fetch_at: Option<String>,
+    pub last_fetch_error: Option<String>,
+    pub subnets: usize,
+    pub vnets: usize,
+}
+
+/// A refresh: fetch the snapshot and regenerate the reports.
+pub type Refresh = dyn Fn() -> Result<RefreshOutcome, Box<dyn Error>> + Send;
+
+/// Shared state of a running server; cheap to clone into threads.
+#[derive(Clone)]
+pub struct Server {
+    clock: Clock,
+    status: Arc<Mutex<ServerStatus>>,
+    /// Oldest snapshot `/readyz` accepts; any age without one.
+    max_snapshot_age: Option<chrono::Duration>,
+    /// Snapshot history for `/changes`; the endpoint is off without one.
+    history: Option<History>,
+    /// Snapshots of the latest refreshes, oldest first, for `/changes`
+    /// within a day.
+    served: Arc<Mutex<VecDeque<Arc<ServedSnapshot>>>>,
+}
+
+impl Server {
+    pub fn new(clock: Clock, schedule: Option<&CronSchedule>) -> Server {
+        Server {
+            clock,
+            status: Arc::new(Mutex::new(ServerStatus {
+                schedule: schedule.map(|s| s.to_string()),
+                ..ServerStatus::default()
+            })),
+            max_snapshot_age: None,
+            history: None,
+            served: Arc::default(),
+        }
+    }
+
+    /// Answer `/changes` from the snapshots in `history`.
+    pub fn with_history(mut self, history: History) -> Server {
+        self.history = Some(history);
+        self
+    }
+
+    /// Report not ready while the served snapshot is older than `age`.
+    pub fn with_max_snapshot_age(mut self, age: chrono::Duration) -> Server {
+        self.max_snapshot_age = Some(age);
+        self
+    }
+
+    /// Probe answer for the current state.
+    pub fn health(&self) -> Health {
+        let status = self.status();
+        let served = status.last_success.as_ref();
+        let outcome = served.and_then(|r| r.outcome.as_ref());
+        // Snapshots without a recorded fetch time count from the refresh.
+        let generated_at = served.map(|r| {
+            outcome
+                .and_then(|o| o.generated_at.clone())
+                .unwrap_or_else(|| r.finished_at.clone())
+        });
+        let age = generated_at
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| self.clock.now().fixed_offset() - t);
+        let reason = match (served, age, self.max_snapshot_age) {
+            (None, _, _) if status.last_run.is_none() => {
+                Some("waiting for the first refresh".to_string())
+            }
+            (None, _, _) => Some("no refresh has succeeded yet".to_string()),
+            (Some(_), Some(age), Some(max)) if age > max => Some(format!(
+                "snapshot is {}h old (maximum {}h)",
+                age.num_hours(),
+                max.num_hours()
+            )),
+            _ => None,
+        };
+        let last = status.last_run.as_ref();
+        Health {
+            ready: reason.is_none(),
+            reason,
+            snapshot_generated_at: generated_at,
+            snapshot_age_seconds: age.map(|a| a.num_seconds()),
+            last_fetch: match last {
+                None => "pending",
+                Some(r) if r.ok => "ok",
+                Some(_) => "failed",
+            },
+            last_fetch_at: last.map(|r| r.finished_at.clone()),
+            last_fetch_error: last.and_then(|r| r.error.clone()),
+            subnets: outcome.map_or(0, |o| o.subnets),
+            vnets: outcome.map_or(0, |o| o.vnets),
+        }
+    }
+
+    /// Snapshot of the scheduler state.
+    pub fn status(&self) -> ServerStatus {
+        self.status.lock().expect("status lock").clone()
+    }
+
+    /// Run `refresh` now and record the result.
+    pub fn refresh(&self, refresh: &Refresh) {
+        let started_at = self.clock.timestamp();
+        self.status.lock().expect("status lock").running = true;
+        log::info!("Refresh started");
+        let result = refresh();
+        let run = RefreshRun {
+            started_at,
+            finished_at: self.clock.timestamp(),
+            ok: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            outcome: result.ok(),
+        };
+        match &run.error {
+            None => log::info!("Refresh finished"),
+            Some(e) => log::error!("Refresh failed: {e}"),
+        }
+        if let Some(snapshot) = self.served_snapshot(&run) {
+            let mut served = self.served.lock().expect("served lock");
+            if served.len() == MAX_SERVED_SNAPSHOTS {
+                served.pop_front();
+            }
+            served.push_back(Arc::new(snapshot));
+        }
+        let mut status = self.status.lock().expect("status lock");
+        status.running = false;
+        if run.ok {
+            status.last_success = Some(run.clone());
+        }
+        status.last_run = Some(run);
+    }
+
+    /// The snapshot a successful refresh serves, kept for `/changes` since
+    /// the next refresh overwrites the file.
+    fn served_snapshot(&self, run: &RefreshRun) -> Option<ServedSnapshot> {
+        self.history.as_ref()?;
+        let outcome = run.outcome.as_ref()?;
+        let data = match read_cache_file(Path::new(&outcome.cache_file)) {
+            Ok(data) => normalize_cidrs(data).0,
+            Err(e) => {
+                log::warn!("Could not keep the served snapshot for /changes: {e}");
+                return None;
+            }
+        };
+        Some(ServedSnapshot {
+            snapshot: SnapshotRef {
+                file: outcome.cache_file.clone(),
+                generated_at: outcome
+                    .generated_at
+                    .clone()
+                    .or(Some(run.finished_at.clone())),
+            },
+            data,
+        })
+    }
+
+    /// Refresh now, then at every time of `schedule` (once only without one),
+    /// on a background thread.
+    pub fn start_scheduler(
+        &self,
+        schedule: Option<CronSchedule>,
+        refresh: Box<Refresh>,
+    ) -> thread::JoinHandle<()> {
+        let server = self.clone();
+        thread::spawn(move || {
+            server.refresh(refresh.as_ref());
+            let Some(schedule) = schedule else {
+                return;
+            };
+            loop {
+                let now = server.clock.now();
+                let Some(next) = schedule.next_after(&now) else {
+                    log::error!("Cron expression '{schedule}' never runs again");
+                    return;
+                };
+                let at = next.to_rfc3339_opts(SecondsFormat::Secs, true);
+                log::info!("Next refresh at {at}");
+                server.status.lock().expect("status lock").next_run = Some(at);
+                thread::sleep((next - now).to_std().unwrap_or_default());
+                server.refresh(refresh.as_ref());
+            }
+        })
+    }
+
+    /// Answer one request.
+    pub fn handle(&self, request: &Request) -> Response {
+        if request.method != "GET" {
+            return Response::error(405, "Only GET is supported");
+        }
+        match request.path.as_str() {
+            "/status" => Response::json(200, &self.status()),
+            "/healthz" => Response::json(200, &self.health()),
+            "/readyz" => {
+                let health = self.health();
+                Response::json(if health.ready { 200 } else { 503 }, &health)
+            }
+            "/changes" => self.changes(request),
+            path => Response::error(404, &format!("No endpoint '{path}'")),
+        }
+    }
+
+    fn changes(&self, request: &Request) -> Response {
+        let Some(ref history) = self.history else {
+            return Response::error(404, "No snapshot history configured");
+        };
+        let Some(since) = request.param("since") else {
+            return Response::error(400, "Missing query parameter 'since'");
+        };
+        if let Err(e) = since_date(since, &self.clock) {
+            return Response::error(400, &e);
+        }
+        let Some(served) = self.status().last_success else {
+            return Response::error(503, "No snapshot is being served yet");
+        };
+        let Some(outcome) = served.outcome else {
+            return Response::error(503, "No snapshot is being served yet");
+        };
+        let current = SnapshotRef {
+            file: outcome.cache_file,
+            generated_at: outcome.generated_at.or(Some(served.finished_at)),
+        };
+        // Reading the history can take a while; refreshes must not wait for it.
+        let served: Vec<Arc<ServedSnapshot>> = self
+            .served
+            .lock()
+            .expect("served lock")
+            .iter()
+            .cloned()
+            .collect();
+        match changes_since(history, &self.clock, since, &current, &served) {
+            Ok(Some(changes)) => Response::json(200, &changes),
+            Ok(None) => Response::error(404, &format!("No snapshot on or before '{since}'")),
+            Err(e) => Response::error(500, &e.to_string()),
+        }
+    }
+
+    fn handle_connection(&self, stream: TcpStream) -> Result<(), Box<dyn Error>> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        let response = match read_request(&mut BufReader::new(stream)) {
+            Ok(request) => self.handle(&request),
+            Err(e) => Response::error(400, &e.to_string()),
+        };
+        response.write_to(&mut writer)?;
+        Ok(())
+    }
+
+    /// Answer requests on `listener` until the process ends, on
+    /// [`WORKERS`] threads. Connections beyond [`QUEUED_CONNECTIONS`]
+    /// waiting for a worker are closed unanswered.
+    pub fn serve(&self, listener: TcpListener) -> Result<(), Box<dyn Error>> {
+        log::info!("Listening on http://{}", listener.local_addr()?);
+        let (queue, connections) = mpsc::sync_channel::<TcpStream>(QUEUED_CONNECTIONS);
+        let connections = Arc::new(Mutex::new(connections));
+        for _ in 0..WORKERS {
+            let server = self.clone();
+            let connections = Arc::clone(&connections);
+            thread::spawn(move || loop {
+                let next = match connections.lock() {
+                    Ok(rx) => rx.recv(),
+                    Err(_) => return,
+                };
+                let Ok(stream) = next else { return };
+                if let Err(e) = server.handle_connection(stream) {
+                    log::warn!("Error answering request: {e}");
+                }
+            });
+        }
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(mpsc::TrySendError::Full(_)) = queue.try_send(stream) {
+                        log::warn!("Too many connections waiting; dropping one");
+                    }
+                }
+                Err(e) => log::warn!("Error accepting connection: {e}"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_reports_the_last_refresh() {
+        let server = Server::new(Clock::default(), Some(&"0 6 * * *".parse().unwrap()));
+        let get = |target: &str| server.handle(&Request::new("GET", target).unwrap());
+
+        let before: serde_json::Value = serde_json::from_str(&get("/status").body).unwrap();
+        assert_eq!(before["schedule"], "0 6 * * *");
+        assert!(before["last_run"].is_null());
+
+        server.refresh(&|| {
+            Ok(RefreshOutcome {
+                subnets: 42,
+                vnets: 3,
+                generated_at: None,
+                cache_file: "net_2026-10-15_cache_subnet.json".to_string(),
+            })
+        });
+        server.refresh(&|| Err("az: not logged in".into()));
+        let status = server.status();
+        let last = status.last_run.unwrap();
+        assert!(!last.ok);
+        assert_eq!(last.error.as_deref(), Some("az: not logged in"));
+        assert_eq!(status.last_success.unwrap().outcome.unwrap().subnets, 42);
+        assert!(!status.running);
+
+        assert_eq!(get("/nope").status, 404);
+        assert_eq!(
+            server
+                .handle(&Request::new("POST", "/status").unwrap())
+                .status,
+            405
+        );
+
+        let mut raw = "GET /status?verbose=1 HTTP/1.1\r\nHost: localhost\r\n\r\n".as_bytes();
+        let request = read_request(&mut raw).unwrap();
+        assert_eq!(request.path, "/status");
+        assert_eq!(request.param("verbose"), Some("1"));
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(100_000));
+        let err = read_request(&mut long.as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "Request line too long");
+        let headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X-A: b\r\n".repeat(1000));
+        assert!(read_request(&mut headers.as_bytes()).is_err());
+        let mut out = Vec::new();
+        server.handle(&request).write_to(&mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[test]
+    fn readiness_follows_the_served_snapshot() {
+        let server =
+            Server::new(Clock::default(), None).with_max_snapshot_age(chrono::Duration::hours(26));
+        let probe = |path: &str| server.handle(&Request::new("GET", path).unwrap());
+        assert_eq!(probe("/healthz").status, 200);
+        assert_eq!(probe("/readyz").status, 503);
+        assert_eq!(server.health().last_fetch, "pending");
+
+        server.refresh(&|| Err("az: not logged in".into()));
+        let health = server.health();
+        assert_eq!(health.last_fetch, "failed");
+        assert_eq!(
+            health.reason.as_deref(),
+            Some("no refresh has succeeded yet")
+        );
+
+        let fetched = (Clock::default().now() - chrono::Duration::hours(2)).to_rfc3339();
+        let again = fetched.clone();
+        server.refresh(&move || {
+            Ok(RefreshOutcome {
+                subnets: 120,
+                vnets: 14,
+                generated_at: Some(fetched.clone()),
+                cache_file: "snapshot.json".to_string(),
+            })
+        });
+        assert_eq!(probe("/readyz").status, 200);
+        let health = server.health();
+        assert_eq!((health.subnets, health.vnets), (120, 14));
+        assert!((7199..=7201).contains(&health.snapshot_age_seconds.unwrap()));
+
+        // A failed refresh keeps serving the previous snapshot until it is too old.
+        server.refresh(&|| Err("throttled".into()));
+        assert_eq!(probe("/readyz").status, 200);
+        let stale =
+            Server::new(Clock::default(), None).with_max_snapshot_age(chrono::Duration::hours(1));
+        stale.refresh(&move || {
+            Ok(RefreshOutcome {
+                subnets: 1,
+                vnets: 1,
+                generated_at: Some(again.clone()),
+                cache_file: "snapshot.json".to_string(),
+            })
+        });
+        let health = stale.health();
+        assert!(!health.ready);
+        assert_eq!(
+            health.reason.as_deref(),
+            Some("snapshot is 2h old (maximum 1h)")
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Signatures of report files (`--sign`) and their verification (`verify`).
+//!
+//! Keys and signatures use minisign's file formats, so a key pair made with
+//! `minisign -G -W` signs reports here and consumers check them with
+//! `verify` and the public key. Each file gets a `<file>.minisig` next to
+//! it; its trusted comment, covered by the signature, names the file and
+//! when it was signed.
+//!
+//! Only what Ed25519 alone can do is supported: unencrypted secret keys
+//! (`-W`; password-protected ones need scrypt) and the original `Ed`
+//! signatures of the whole file (not the BLAKE2b-prehashed `ED` ones recent
+//! minisign versions write by default).
+
+use crate::clock::Clock;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Extension of signature files.
+pub const SIGNATURE_EXTENSION: &str = "minisig";
+
+/// Signature algorithm tag of whole-file Ed25519 signatures.
+const ALG_ED25519: &[u8; 2] = b"Ed";
+/// Tag of BLAKE2b-prehashed signatures, which are not supported.
+const ALG_PREHASHED: &[u8; 2] = b"ED";
+const KEY_ID_LEN: usize = 8;
+/// Algorithm, KDF and checksum tags, salt, KDF limits, key ID, key pair
+/// and checksum.
+const SECRET_KEY_LEN: usize = 2 + 2 + 2 + 32 + 8 + 8 + KEY_ID_LEN + 64 + 32;
+
+/// The base64 line of a minisign file: the first line that is not a comment.
+fn payload(text: &str, what: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with("untrusted comment:"))
+        .ok_or_else(|| format!("No {what} found"))?;
+    Ok(STANDARD
+        .decode(line)
+        .map_err(|e| format!("Invalid {what}: {e}"))?)
+}
+
+/// Key ID as minisign prints it.
+fn key_id_hex(key_id: &[u8; KEY_ID_LEN]) -> String {
+    format!("{:016X}", u64::from_le_bytes(*key_id))
+}
+
+fn read(path: &Path) -> Result<String, Box<dyn Error>> {
+    Ok(std::fs::read_to_string(path)
+        .map_err(|e| format!("Error reading {}: {e}", path.display()))?)
+}
+
+/// A minisign secret key.
+pub struct SigningKey {
+    key_id: [u8; KEY_ID_LEN],
+    pair: Ed25519KeyPair,
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SigningKey({})", key_id_hex(&self.key_id))
+    }
+}
+
+impl SigningKey {
+    /// Parse the contents of an unencrypted minisign secret key file.
+    pub fn parse(text: &str) -> Result<SigningKey, Box<dyn Error>> {
+        let bytes = payload(text, "secret key")?;
+        if bytes.len() != SECRET_KEY_LEN || &bytes[..2] != ALG_ED25519 {
+            return Err("Not a minisign Ed25519 secret key".into());
+        }
+        if bytes[2..4] != [0, 0] {
+            return Err(
+                "The secret key is password-protected; create an unencrypted one with `minisign -G -W`"
+                    .into(),
+            );
+        }
+        let key_id: [u8; KEY_ID_LEN] = bytes[54..62].try_into()?;
+        let (seed, public) = bytes[62..126].split_at(32);
+        let pair = Ed25519KeyPair::from_seed_and_public_key(seed, public)
+            .map_err(|e| format!("Invalid secret key: {e}"))?;
+        Ok(SigningKey { key_id, pair })
+    }
+
+    /// Read the secret key file at `path`.
+    pub fn load(path: &Path) -> Result<SigningKey, Box<dyn Error>> {
+        SigningKey::parse(&read(path)?).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        let mut key = [0u8; 32];
+        key.copy_from_slice(self.pair.public_key().as_ref());
+        PublicKey {
+            key_id: self.key_id,
+            key,
+        }
+    }
+
+    /// Signature file contents for `data`, with `trusted_comment` covered
+    /// by the signature.
+    pub fn sign(&self, data: &[u8], trusted_comment: &str) -> String {
+        let signature = self.pair.sign(data);
+        let mut line = ALG_ED25519.to_vec();
+        line.extend_from_slice(&self.key_id);
+        line.extend_from_slice(signature.as_ref());
+        let mut global = signature.as_ref().to_vec();
+        global.extend_from_slice(trusted_comment.as_bytes());
+        format!(
+            "untrusted comment: signature from azure-subnet-summary key {}\n{}\ntrusted comment: {trusted_comment}\n{}\n",
+            key_id_hex(&self.key_id),
+            STANDARD.encode(line),
+            STANDARD.encode(self.pair.sign(&global))
+        )
+    }
+
+    /// Write `<path>.minisig` for the file at `path`, timestamped by `clock`.
+    pub fn sign_file(&self, path: &Path, clock: &Clock) -> Result<PathBuf, Box<dyn Error>> {
+        let data =
+            std::fs::read(path).map_err(|e| format!("Error reading {}: {e}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let comment = format!("timestamp:{}\tfile:{name}", clock.now().timestamp());
+        let sig_path = signature_path(path);
+        std::fs::write(&sig_path, self.sign(&data, &comment))
+            .map_err(|e| format!("Error writing {}: {e}", sig_path.display()))?;
+        Ok(sig_path)
+    }
+}
+
+/// A minisign public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey {
+    key_id: [u8; KEY_ID_LEN],
+    key: [u8; 32],
+}
+
+impl fmt::Display for PublicKey {
+    /// The public key file contents.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = ALG_ED25519.to_vec();
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(&self.key);
+        writeln!(
+            f,
+            "untrusted comment: minisign public key {}",
+            key_id_hex(&self.key_id)
+        )?;
+        writeln!(f, "{}", STANDARD.encode(bytes))
+    }
+}
+
+impl PublicKey {
+    /// Key ID as minisign prints it.
+    pub fn key_id(&self) -> String {
+        key_id_hex(&self.key_id)
+    }
+
+    /// Parse a public key file's contents, or the bare base64 key.
+    pub fn parse(text: &str) -> Result<PublicKey, Box<dyn Error>> {
+        let bytes = payload(text, "public key")?;
+        if bytes.len() != 2 + KEY_ID_LEN + 32 || &bytes[..2] != ALG_ED25519 {
+            return Err("Not a minisign Ed25519 public key".into());
+        }
+        Ok(PublicKey {
+            key_id: bytes[2..10].try_into()?,
+            key: bytes[10..].try_into()?,
+        })
+    }
+
+    /// A public key file path, or the key itself (as `minisign -P` takes it).
+    pub fn from_arg(value: &str) -> Result<PublicKey, Box<dyn Error>> {
+        let path = Path::new(value);
+        if path.is_file() {
+            PublicKey::parse(&read(path)?).map_err(|e| format!("{value}: {e}").into())
+        } else {
+            PublicKey::parse(value)
+        }
+    }
+
+    /// Check `signature` (signature file contents) over `data`, returning
+    /// its trusted comment.
+    pub fn verify(&self, data: &[u8], signature: &str) -> Result<String, Box<dyn Error>> {
+        let lines: Vec<&str> = signature.lines().map(str::trim_end).collect();
+        let (Some(line), Some(comment), Some(global)) = (lines.get(1), lines.get(2), lines.get(3))
+        else {
+            return Err("Truncated signature file".into());
+        };
+        let bytes = STANDARD
+            .decode(line)
+            .map_err(|e| format!("Invalid signature: {e}"))?;
+        if bytes.len() != 2 + KEY_ID_LEN + 64 {
+            return Err("Invalid signature length".into());
+        }
+        if &bytes[..2] == ALG_PREHASHED {
+            return Err(
+                "Prehashed (BLAKE2b) signatures are not supported; sign with `minisign -S -l`"
+                    .into(),
+            );
+        }
+        if &bytes[..2] != ALG_ED25519 {
+            return Err("Unknown signature algorithm".into());
+        }
+        if bytes[2..10] != self.key_id {
+            return Err(format!(
+                "Signed with key {}, not {}",
+                key_id_hex(bytes[2..10].try_into()?),
+                key_id_hex(&self.key_id)
+            )
+            .into());
+        }
+        let sig = &bytes[10..];
+        let key = UnparsedPublicKey::new(&ED25519, self.key);
+        key.verify(data, sig)
+            .map_err(|_| "Signature does not match the file contents")?;
+        let comment = comment
+            .strip_prefix("trusted comment: ")
+            .ok_or("Missing trusted comment")?;
+        let global = STANDARD
+            .decode(global)
+            .map_err(|e| format!("Invalid comment signature: {e}"))?;
+        let mut signed = sig.to_vec();
+        signed.extend_from_slice(comment.as_bytes());
+        key.verify(&signed, &global)
+            .map_err(|_| "Trusted comment was altered")?;
+        Ok(comment.to_string())
+    }
+
+    /// Check `<path>.minisig` against the file at `path`.
+    pub fn verify_file(&self, path: &Path) -> Result<String, Box<dyn Error>> {
+        let data =
+            std::fs::read(path).map_err(|e| format!("Error reading {}: {e}", path.display()))?;
+        self.verify(&data, &read(&signature_path(path))?)
+    }
+}
+
+/// `<path>.minisig`.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{SIGNATURE_EXTENSION}"));
+    PathBuf::from(name)
+}
+
+/// Sign `files`, the report files a run wrote, returning the number signed.
+/// Other files in the report directory (earlier runs of the day, anything
+/// dropped there) are left alone: a signature attests this run's output.
+pub fn sign_files(
+    key: &SigningKey,
+    files: &[PathBuf],
+    clock: &Clock,
+) -> Result<usize, Box<dyn Error>> {
+    for file in files {
+        key.sign_file(file, clock)?;
+    }
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unencrypted minisign secret key file for `seed`.
+    fn secret_key_file(seed: [u8; 32], key_id: [u8; 8]) -> String {
+        let pair = Ed25519KeyPair::from_seed_unchecked(&seed).unwrap();
+        let mut bytes = b"Ed\0\0B2".to_vec();
+        bytes.extend_from_slice(&[0u8; 48]);
+        bytes.extend_from_slice(&key_id);
+        bytes.extend_from_slice(&seed);
+        bytes.extend_from_slice(pair.public_key().as_ref());
+        bytes.extend_from_slice(&[0u8; 32]);
+        format!(
+            "untrusted comment: minisign secret key\n{}\n",
+            STANDARD.encode(bytes)
+        )
+    }
+
+    #[test]
+    fn signed_reports_verify_and_tampering_is_caught() {
+        let key = SigningKey::parse(&secret_key_file([7; 32], *b"azsubkey")).unwrap();
+        let public = PublicKey::parse(&key.public_key().to_string()).unwrap();
+        assert_eq!(public, key.public_key());
+
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("net_2026-10-15_subnets.csv");
+        std::fs::write(&csv, "\"cnt\",\"gap\"\n").unwrap();
+        let md = dir.path().join("net_2026-10-15_duplicates.md");
+        std::fs::write(&md, "# none\n").unwrap();
+        let stray = dir.path().join("notes.txt");
+        std::fs::write(&stray, "not from this run\n").unwrap();
+        assert_eq!(
+            sign_files(&key, &[csv.clone(), md], &Clock::default()).unwrap(),
+            2
+        );
+        assert!(signature_path(&csv).exists());
+        assert!(
+            !signature_path(&stray).exists(),
+            "only the run's files are signed"
+        );
+
+        let comment = public.verify_file(&csv).unwrap();
+        assert!(
+            comment.ends_with("\tfile:net_2026-10-15_subnets.csv"),
+            "{comment}"
+        );
+
+        std::fs::write(&csv, "\"cnt\",\"gap\"\n10.0.0.0/8\n").unwrap();
+        let err = public.verify_file(&csv).unwrap_err().to_string();
+        assert!(err.contains("does not match"), "{err}");
+
+        let signature = key.sign(b"data", "timestamp:1\tfile:a.csv");
+        let forged = signature.replace("file:a.csv", "file:b.csv");
+        let err = public.verify(b"data", &forged).unwrap_err().to_string();
+        assert!(err.contains("altered"), "{err}");
+
+        let other = SigningKey::parse(&secret_key_file([9; 32], *b"otherkey")).unwrap();
+        let err = other
+            .public_key()
+            .verify(b"data", &signature)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Signed with key"), "{err}");
+
+        let mut encrypted = STANDARD
+            .decode(secret_key_file([7; 32], [0; 8]).lines().nth(1).unwrap())
+            .unwrap();
+        encrypted[2..4].copy_from_slice(b"Sc");
+        let err = SigningKey::parse(&STANDARD.encode(encrypted)).unwrap_err();
+        assert!(err.to_string().contains("minisign -G -W"), "{err}");
+    }
+}
@@ -0,0 +1,92 @@
+//! Stable, serializable view of the pipeline output for golden tests.
+//!
+//! [`PipelineSnapshot`] captures what a run derives from a subnet cache — the
+//! CSV rows (subnets and gaps), the VNets excluded by overlap resolution, the
+//! overlap groups and the report findings — without timestamps, file paths or
+//! terminal colours. The snapshot tests in `tests/golden_test.rs` render it as
+//! JSON for each fixture cache, so a refactor can show its output is unchanged.
+
+use crate::azure::{Data, VWanRow};
+use crate::models::Ipv4;
+use crate::output::build_rows;
+use crate::pipeline::resolve_subnets;
+use crate::processing::{find_overlapping_vnets, IgnoreList, SubnetPrintRow};
+use crate::report::{Finding, Report};
+use serde::Serialize;
+use std::error::Error;
+
+/// A subnet dropped because its VNet lost overlap resolution.
+#[derive(Debug, Serialize)]
+pub struct SnapshotExclusion {
+    pub subscription_name: String,
+    pub vnet_name: String,
+    pub subnet_name: String,
+    pub subnet_cidr: Option<Ipv4>,
+    pub winner_vnet_name: String,
+}
+
+/// Everything a run derives from its input data, in a deterministic order.
+#[derive(Debug, Serialize)]
+pub struct PipelineSnapshot {
+    /// Subnet and gap rows as written to the CSV.
+    pub rows: Vec<SubnetPrintRow>,
+    pub excluded: Vec<SnapshotExclusion>,
+    /// Groups of overlapping VNet CIDRs as sorted `subscription/vnet cidr`
+    /// labels, before resolution.
+    pub vnet_overlaps: Vec<Vec<String>>,
+    pub findings: Vec<Finding>,
+}
+
+impl PipelineSnapshot {
+    /// Run the processing steps of [`pipeline::run`](crate::pipeline::run)
+    /// over `subnets`, leaving out subnets matched by `ignore`, and collect
+    /// the results.
+    pub fn build(
+        mut subnets: Data,
+        vwan: &[VWanRow],
+        gap_mask: u8,
+        ignore: &IgnoreList,
+    ) -> Result<PipelineSnapshot, Box<dyn Error>> {
+        subnets.data.sort_by_key(|s| s.subnet_cidr);
+
+        let mut vnet_overlaps: Vec<Vec<String>> = find_overlapping_vnets(&subnets)
+            .into_iter()
+            .map(|conflict| {
+                let mut labels: Vec<String> = conflict
+                    .vnets
+                    .iter()
+                    .flat_map(|v| {
+                        v.vnet_cidr
+                            .iter()
+                            .map(move |c| format!("{}/{} {c}", v.subscription_name, v.vnet_name))
+                    })
+                    .collect();
+                labels.sort();
+                labels
+            })
+            .collect();
+        vnet_overlaps.sort();
+
+        let resolved = resolve_subnets(subnets, ignore)?;
+        let rows = build_rows(&resolved.active.data, &resolved.excluded, gap_mask, vwan);
+        let excluded = resolved
+            .excluded
+            .iter()
+            .map(|e| SnapshotExclusion {
+                subscription_name: e.subnet.subscription_name.clone(),
+                vnet_name: e.subnet.vnet_name.clone(),
+                subnet_name: e.subnet.subnet_name.clone(),
+                subnet_cidr: e.subnet.subnet_cidr,
+                winner_vnet_name: e.winner_vnet_name.clone(),
+            })
+            .collect();
+        let findings = Report::from_subnets(&resolved.active).findings;
+
+        Ok(PipelineSnapshot {
+            rows,
+            excluded,
+            vnet_overlaps,
+            findings,
+        })
+    }
+}
@@ -0,0 +1,208 @@
+//! Suppressions for known, accepted findings.
+//!
+//! A suppressions file (TOML) lists findings that should stop failing
+//! `check`, like a lint allowlist. Each entry names the finding type and the
+//! subnet resource ID, says why it is accepted and may expire:
+//!
+//! ```toml
+//! [[suppress]]
+//! finding = "missing_nsg"
+//! resource_id = "/subscriptions/.../virtualNetworks/hub/subnets/app"
+//! expires = "2026-12-31"
+//! reason = "NSG rollout for hub tracked in CHG-1234"
+//! ```
+//!
+//! An expired entry no longer applies, so the finding fails `check` again.
+
+use crate::report::{Finding, FINDING_IDS};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::error::Error;
+use std::path::Path;
+
+/// Suppressions file read when `--suppressions` is not given; it may be absent.
+pub const DEFAULT_SUPPRESSIONS_FILE: &str = "azure-subnet-summary.suppressions.toml";
+
+/// One accepted finding.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppression {
+    /// Finding type, e.g. `missing_nsg` or `resize_recommended`.
+    pub finding: String,
+    /// Subnet resource ID (case-insensitive).
+    pub resource_id: String,
+    /// Last day the suppression applies; `None` never expires.
+    #[serde(default)]
+    pub expires: Option<NaiveDate>,
+    /// Why the finding is accepted.
+    pub reason: String,
+}
+
+impl Suppression {
+    /// `true` when this entry covers `finding`, ignoring expiry.
+    pub fn covers(&self, finding: &Finding) -> bool {
+        !finding.subnet_id.is_empty()
+            && self.finding == finding.kind.id()
+            && self.resource_id.eq_ignore_ascii_case(&finding.subnet_id)
+    }
+
+    /// `true` once `today` is past the expiry date.
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires.is_some_and(|e| today > e)
+    }
+}
+
+/// All entries of a suppressions file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Suppressions {
+    pub suppress: Vec<Suppression>,
+}
+
+impl Suppressions {
+    /// Parse suppressions from TOML text.
+    ///
+    /// Unknown finding types are rejected so a typo cannot silently match nothing.
+    pub fn parse(toml_text: &str) -> Result<Suppressions, Box<dyn Error>> {
+        let suppressions: Suppressions =
+            toml::from_str(toml_text).map_err(|e| format!("Invalid suppressions: {e}"))?;
+        if let Some(s) = suppressions
+            .suppress
+            .iter()
+            .find(|s| !FINDING_IDS.contains(&s.finding.as_str()))
+        {
+            return Err(format!(
+                "Invalid suppressions: unknown finding '{}' (expected one of {})",
+                s.finding,
+                FINDING_IDS.join(", ")
+            )
+            .into());
+        }
+        Ok(suppressions)
+    }
+
+    /// Load the suppressions file.
+    ///
+    /// With `path == None` the [`DEFAULT_SUPPRESSIONS_FILE`] is read if it
+    /// exists, otherwise there are no suppressions. An explicit `path` must exist.
+    pub fn load(path: Option<&str>) -> Result<Suppressions, Box<dyn Error>> {
+        let file = path.unwrap_or(DEFAULT_SUPPRESSIONS_FILE);
+        if path.is_none() && !Path::new(file).exists() {
+            return Ok(Suppressions::default());
+        }
+        let text = std::fs::read_to_string(file)
+            .map_err(|e| format!("Error reading suppressions file {file}: {e}"))?;
+        log::info!("Using suppressions file '{file}'");
+        Suppressions::parse(&text).map_err(|e| format!("{file}: {e}").into())
+    }
+
+    /// Split `findings` into `(active, suppressed)` as of `today`.
+    ///
+    /// Logs a warning for each expired entry that would otherwise have applied.
+    pub fn partition(
+        &self,
+        findings: Vec<Finding>,
+        today: NaiveDate,
+    ) -> (Vec<Finding>, Vec<(Finding, &Suppression)>) {
+        let mut active = Vec::new();
+        let mut suppressed = Vec::new();
+        for finding in findings {
+            let matching: Vec<&Suppression> = self
+                .suppress
+                .iter()
+                .filter(|s| s.covers(&finding))
+                .collect();
+            match matching.iter().find(|s| !s.is_expired(today)) {
+                Some(s) => suppressed.push((finding, *s)),
+                None => {
+                    for s in matching {
+                        log::warn!(
+                            "Suppression of {} for '{}' expired on {} — finding applies again",
+                            s.finding,
+                            s.resource_id,
+                            s.expires.map(|e| e.to_string()).unwrap_or_default()
+                        );
+                    }
+                    active.push(finding);
+                }
+            }
+        }
+        (active, suppressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{FindingKind, Severity};
+
+    fn finding(kind: FindingKind, subnet_id: &str) -> Finding {
+        Finding {
+            severity: kind.severity(),
+            kind,
+            subscription_id: "sub-1".to_string(),
+            subscription_name: "Prod".to_string(),
+            vnet_name: "hub".to_string(),
+            subnet_name: "app".to_string(),
+            subnet_id: subnet_id.to_string(),
+            subnet_cidr: None,
+            message: "msg".to_string(),
+        }
+    }
+
+    #[test]
+    fn matching_unexpired_entries_suppress_findings() {
+        let suppressions = Suppressions::parse(
+            r#"
+            [[suppress]]
+            finding = "missing_nsg"
+            resource_id = "/SUBSCRIPTIONS/sub-1/subnets/app"
+            reason = "accepted"
+
+            [[suppress]]
+            finding = "missing_nsg"
+            resource_id = "/subscriptions/sub-1/subnets/old"
+            expires = "2026-10-01"
+            reason = "was accepted"
+            "#,
+        )
+        .unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let (active, suppressed) = suppressions.partition(
+            vec![
+                finding(FindingKind::MissingNsg, "/subscriptions/sub-1/subnets/app"),
+                finding(FindingKind::MissingNsg, "/subscriptions/sub-1/subnets/old"),
+                finding(
+                    FindingKind::ResizeRecommended {
+                        suggested: crate::models::Ipv4::new("10.0.0.0/26").unwrap(),
+                    },
+                    "/subscriptions/sub-1/subnets/app",
+                ),
+            ],
+            today,
+        );
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].1.reason, "accepted");
+        let active: Vec<(&str, Severity)> =
+            active.iter().map(|f| (f.kind.id(), f.severity)).collect();
+        assert_eq!(
+            active,
+            [
+                ("missing_nsg", Severity::Error),
+                ("resize_recommended", Severity::Warn)
+            ]
+        );
+    }
+
+    #[test]
+    fn entries_need_a_reason_and_a_known_finding() {
+        assert!(Suppressions::parse(
+            "[[suppress]]\nfinding = \"missing_nsg\"\nresource_id = \"/x\""
+        )
+        .is_err());
+        assert!(Suppressions::parse(
+            "[[suppress]]\nfinding = \"missing-nsg\"\nresource_id = \"/x\"\nreason = \"r\""
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! Golden tests for the pipeline output.
+//!
+//! Each test builds a [`PipelineSnapshot`] from a fixture cache and compares
+//! its JSON with the snapshot committed under `tests/snapshots/`. After an
+//! intended output change, review and accept the new snapshots with
+//! `cargo insta review` (or `INSTA_UPDATE=always cargo test`).
+
+use azure_subnet_summary::azure::{read_subnet_cache, read_vwan_cache};
+use azure_subnet_summary::config::Config;
+use azure_subnet_summary::processing::IgnoreList;
+use azure_subnet_summary::snapshot::PipelineSnapshot;
+
+/// Default `--gap-mask`.
+const GAP_MASK: u8 = 4;
+
+/// Ignore rules of the bundled example config, which the fixtures were
+/// recorded with.
+fn ignore_list() -> IgnoreList {
+    let config = Config::parse(include_str!("../azure-subnet-summary.example.toml"))
+        .expect("Failed to parse example config");
+    IgnoreList::from_rules(&config.ignore)
+}
+
+fn snapshot(cache: &str, vwan_cache: Option<&str>) -> PipelineSnapshot {
+    let data = read_subnet_cache(Some(&format!("src/tests/test_data/{cache}")))
+        .expect("Failed to read subnet cache");
+    let vwan = match vwan_cache {
+        Some(f) => {
+            read_vwan_cache(Some(&format!("src/tests/test_data/{f}")))
+                .expect("Failed to read vWAN cache")
+                .data
+        }
+        None => Vec::new(),
+    };
+    PipelineSnapshot::build(data, &vwan, GAP_MASK, &ignore_list())
+        .expect("Failed to build snapshot")
+}
+
+#[test]
+fn golden_subnet_cache_01() {
+    insta::assert_json_snapshot!(snapshot("subnet_test_cache_01.json", None));
+}
+
+#[test]
+fn golden_subnet_cache_02() {
+    insta::assert_json_snapshot!(snapshot("subnet_test_cache_02.json", None));
+}
+
+#[test]
+fn golden_subnet_cache_03() {
+    insta::assert_json_snapshot!(snapshot("subnet_test_cache_03.json", None));
+}
+
+#[test]
+fn golden_subnet_cache_04_with_vwan() {
+    insta::assert_json_snapshot!(snapshot(
+        "subnet_test_cache_04.json",
+        Some("vwan_test_cache_01.json")
+    ));
+}
@@ -18,10 +18,10 @@ fn test_full_workflow_with_cache() {
     // De-duplicate
     let filter = vec![
         "default",
-        "pkrsn1ooslfxj77",
-        "pkrsnsnajtq3h3i",
-        "pkrsnxocivqofa6",
-        "orggmcmg",
+        "tmpsn1ooslfxj77",
+        "tmpsnsnajtq3h3i",
+        "tmpsnxocivqofa6",
+        "labspace",
     ];
     let data = de_duplicate_subnets(data, Some(&filter)).expect("Failed to de-duplicate");
 